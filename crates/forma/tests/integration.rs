@@ -1803,7 +1803,7 @@ fn full_lifecycle_end_to_end() {
         .iter()
         .filter(|w| {
             w["check"] == "required_sections_nonempty"
-                && w["message"].as_str().map_or(false, |m| m.contains("'api'"))
+                && w["message"].as_str().is_some_and(|m| m.contains("'api'"))
         })
         .collect();
     assert!(
@@ -0,0 +1,70 @@
+use crate::error::PensaError;
+
+pub const API_VERSION_HEADER: &str = "x-pensa-api-version";
+pub const MIN_API_VERSION_HEADER: &str = "x-pensa-min-api-version";
+pub const DAEMON_VERSION_HEADER: &str = "x-pensa-version";
+
+pub const CURRENT_API_VERSION: u32 = 1;
+pub const MIN_API_VERSION: u32 = 1;
+
+/// The response shape negotiated for a request. Handlers that change their
+/// output between versions read this from the request extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ApiVersion(pub u32);
+
+impl Default for ApiVersion {
+    fn default() -> Self {
+        ApiVersion(CURRENT_API_VERSION)
+    }
+}
+
+impl ApiVersion {
+    pub fn served_by(self, min: u32, max: u32) -> bool {
+        (min..=max).contains(&self.0)
+    }
+
+    pub fn negotiate(requested: Option<&str>) -> Result<Self, PensaError> {
+        let Some(raw) = requested else {
+            return Ok(ApiVersion::default());
+        };
+        match raw.trim().parse::<u32>() {
+            Ok(v) if (MIN_API_VERSION..=CURRENT_API_VERSION).contains(&v) => Ok(ApiVersion(v)),
+            _ => Err(PensaError::UnsupportedApiVersion(raw.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_header_uses_current() {
+        assert_eq!(
+            ApiVersion::negotiate(None).unwrap(),
+            ApiVersion(CURRENT_API_VERSION)
+        );
+    }
+
+    #[test]
+    fn supported_version_is_accepted() {
+        assert_eq!(ApiVersion::negotiate(Some(" 1 ")).unwrap(), ApiVersion(1));
+    }
+
+    #[test]
+    fn served_by_checks_the_range() {
+        assert!(ApiVersion(2).served_by(1, 2));
+        assert!(!ApiVersion(1).served_by(2, 3));
+        assert!(!ApiVersion(3).served_by(1, 2));
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        for raw in ["0", "99", "v1", ""] {
+            assert!(matches!(
+                ApiVersion::negotiate(Some(raw)),
+                Err(PensaError::UnsupportedApiVersion(_))
+            ));
+        }
+    }
+}
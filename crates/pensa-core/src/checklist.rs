@@ -0,0 +1,81 @@
+use crate::error::PensaError;
+use crate::types::Checklist;
+
+/// Byte offset of the item's box character (the ` ` or `x` between the
+/// brackets) within `line`, when the line is an item.
+fn box_offset(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let rest = trimmed.strip_prefix(['-', '*', '+'])?.strip_prefix(' ')?;
+    let marker = trimmed.len() - rest.len();
+    let bytes = rest.as_bytes();
+    let is_item = bytes.len() >= 3
+        && bytes[0] == b'['
+        && matches!(bytes[1], b' ' | b'x' | b'X')
+        && bytes[2] == b']'
+        && bytes.get(3).is_none_or(|b| b.is_ascii_whitespace());
+    is_item.then_some(indent + marker + 1)
+}
+
+pub fn items(description: &str) -> Vec<bool> {
+    description
+        .lines()
+        .filter_map(|line| box_offset(line).map(|at| line.as_bytes()[at] != b' '))
+        .collect()
+}
+
+pub fn summary(description: Option<&str>) -> Option<Checklist> {
+    let items = items(description?);
+    (!items.is_empty()).then(|| Checklist {
+        done: items.iter().filter(|done| **done).count(),
+        total: items.len(),
+    })
+}
+
+pub fn toggle(description: &str, index: usize) -> Result<String, PensaError> {
+    let mut seen = 0;
+    let mut out = String::with_capacity(description.len());
+    for line in description.split_inclusive('\n') {
+        if let Some(at) = box_offset(line) {
+            seen += 1;
+            if seen == index {
+                let ticked = line.as_bytes()[at] != b' ';
+                out.push_str(&line[..at]);
+                out.push(if ticked { ' ' } else { 'x' });
+                out.push_str(&line[at + 1..]);
+                continue;
+            }
+        }
+        out.push_str(line);
+    }
+    if index == 0 || index > seen {
+        return Err(PensaError::InvalidArgument(format!(
+            "no checklist item {index}; the description has {seen}"
+        )));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: &str = "Steps:\n- [ ] add the column\n  * [x] backfill\n- [] not an item\n+ [X] ship\n-[ ] nor this";
+
+    #[test]
+    fn items_are_counted_and_toggled_in_order() {
+        assert_eq!(items(DESCRIPTION), [false, true, true]);
+        let summary = summary(Some(DESCRIPTION)).unwrap();
+        assert_eq!((summary.done, summary.total), (2, 3));
+        assert!(super::summary(Some("no items")).is_none());
+        assert!(super::summary(None).is_none());
+
+        let ticked = toggle(DESCRIPTION, 1).unwrap();
+        assert!(ticked.contains("- [x] add the column\n"));
+        let unticked = toggle(&ticked, 2).unwrap();
+        assert!(unticked.contains("  * [ ] backfill\n"));
+        assert_eq!(items(&unticked), [true, false, true]);
+        assert!(toggle(DESCRIPTION, 4).is_err());
+        assert!(toggle(DESCRIPTION, 0).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::DEFAULT_ID_PREFIX;
+use crate::error::PensaError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitCommit {
+    pub sha: String,
+    pub summary: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitRefs {
+    pub mentioned: Vec<String>,
+    pub fixes: Vec<String>,
+}
+
+pub fn parse_commit_refs(message: &str, prefix: &str) -> CommitRefs {
+    let mut refs = CommitRefs::default();
+    for line in message.lines() {
+        let trimmed = line.trim();
+        let is_fixes_trailer = trimmed
+            .split_once(':')
+            .is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("fixes"));
+        for id in find_issue_ids(trimmed, prefix) {
+            if is_fixes_trailer && !refs.fixes.contains(&id) {
+                refs.fixes.push(id.clone());
+            }
+            if !refs.mentioned.contains(&id) {
+                refs.mentioned.push(id);
+            }
+        }
+    }
+    refs
+}
+
+pub fn find_issue_ids(text: &str, prefix: &str) -> Vec<String> {
+    let mut found = find_prefixed_ids(text, DEFAULT_ID_PREFIX);
+    if prefix != DEFAULT_ID_PREFIX {
+        found.extend(find_prefixed_ids(text, prefix));
+        found.sort_by_key(|(start, _)| *start);
+    }
+    let mut ids: Vec<String> = Vec::new();
+    for (_, id) in found {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+fn find_prefixed_ids(text: &str, prefix: &str) -> Vec<(usize, String)> {
+    let bytes = text.as_bytes();
+    let needle = format!("{prefix}-");
+    let mut ids = Vec::new();
+    let mut i = 0;
+    while let Some(pos) = text[i..].find(&needle) {
+        let start = i + pos;
+        let hex_start = start + needle.len();
+        let hex_end = hex_start + 8;
+        let boundary_before = start == 0 || !is_id_char(bytes[start - 1]);
+        let hex_ok = hex_end <= bytes.len()
+            && bytes[hex_start..hex_end]
+                .iter()
+                .all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase());
+        let boundary_after = hex_end >= bytes.len() || !is_id_char(bytes[hex_end]);
+        if boundary_before && hex_ok && boundary_after {
+            ids.push((start, text[start..hex_end].to_string()));
+            i = hex_end;
+        } else {
+            i = hex_start;
+        }
+    }
+    ids
+}
+
+fn is_id_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'-' || b == b'_'
+}
+
+pub fn git_log(repo: &Path, since: Option<&str>) -> Result<Vec<GitCommit>, PensaError> {
+    let range = match since {
+        Some(rev) => format!("{rev}..HEAD"),
+        None => "HEAD".to_string(),
+    };
+    let output = Command::new("git")
+        .args(["log", "--reverse", "--format=%H%x1f%s%x1f%B%x1e", &range])
+        .current_dir(repo)
+        .output()
+        .map_err(|e| PensaError::Internal(format!("failed to run git log: {e}")))?;
+    if !output.status.success() {
+        return Err(PensaError::InvalidArgument(format!(
+            "git log {range} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(3, '\x1f');
+            let sha = fields.next()?.trim();
+            if sha.is_empty() {
+                return None;
+            }
+            Some(GitCommit {
+                sha: sha.to_string(),
+                summary: fields.next().unwrap_or("").to_string(),
+                message: fields.next().unwrap_or("").trim_end().to_string(),
+            })
+        })
+        .collect())
+}
+
+pub fn resolve_commit(repo: &Path, rev: &str) -> Option<(String, String)> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%H%x1f%s", rev, "--"])
+        .current_dir(repo)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (sha, summary) = stdout.trim_end().split_once('\x1f')?;
+    Some((sha.to_string(), summary.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_ids_with_boundaries() {
+        assert_eq!(
+            find_issue_ids("fix pn-0a1b2c3d and (pn-deadbeef).", "pn"),
+            vec!["pn-0a1b2c3d", "pn-deadbeef"]
+        );
+        assert!(find_issue_ids("xpn-0a1b2c3d", "pn").is_empty());
+        assert!(find_issue_ids("pn-0a1b2c3d4", "pn").is_empty());
+        assert!(find_issue_ids("pn-0A1B2C3D", "pn").is_empty());
+        assert!(find_issue_ids("pn-0a1b", "pn").is_empty());
+    }
+
+    #[test]
+    fn finds_ids_with_a_custom_prefix_alongside_pn() {
+        assert_eq!(
+            find_issue_ids("acme-0a1b2c3d after pn-deadbeef, xacme-11111111", "acme"),
+            vec!["acme-0a1b2c3d", "pn-deadbeef"]
+        );
+        assert!(find_issue_ids("acme-0a1b2c3d", "pn").is_empty());
+    }
+
+    #[test]
+    fn dedups_repeated_ids() {
+        assert_eq!(
+            find_issue_ids("pn-0a1b2c3d pn-0a1b2c3d", "pn"),
+            vec!["pn-0a1b2c3d"]
+        );
+    }
+
+    #[test]
+    fn fixes_trailer_is_separated_from_mentions() {
+        let msg = "Refactor login\n\nRelated to pn-11111111.\n\nFixes: pn-22222222, pn-33333333\n";
+        let refs = parse_commit_refs(msg, "pn");
+        assert_eq!(
+            refs.mentioned,
+            vec!["pn-11111111", "pn-22222222", "pn-33333333"]
+        );
+        assert_eq!(refs.fixes, vec!["pn-22222222", "pn-33333333"]);
+    }
+
+    #[test]
+    fn fixes_trailer_is_case_insensitive() {
+        let refs = parse_commit_refs("msg\n\nfixes: pn-22222222", "pn");
+        assert_eq!(refs.fixes, vec!["pn-22222222"]);
+    }
+
+    #[test]
+    fn git_log_reads_commits_in_order() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let out = Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .output()
+                .unwrap();
+            assert!(out.status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "first"]);
+        git(&[
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "second pn-0a1b2c3d\n\nFixes: pn-deadbeef",
+        ]);
+
+        let all = git_log(tmp.path(), None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].summary, "first");
+        assert_eq!(all[1].summary, "second pn-0a1b2c3d");
+        assert_eq!(
+            parse_commit_refs(&all[1].message, "pn").fixes,
+            vec!["pn-deadbeef"]
+        );
+
+        let since = git_log(tmp.path(), Some(&all[0].sha)).unwrap();
+        assert_eq!(since.len(), 1);
+        assert_eq!(since[0].sha, all[1].sha);
+
+        let (sha, summary) = resolve_commit(tmp.path(), &all[0].sha[..7]).unwrap();
+        assert_eq!(sha, all[0].sha);
+        assert_eq!(summary, "first");
+        assert!(resolve_commit(tmp.path(), "nonexistent").is_none());
+    }
+}
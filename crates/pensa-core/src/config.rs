@@ -0,0 +1,627 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::TimeDelta;
+use serde::Deserialize;
+
+use crate::error::PensaError;
+use crate::types::{IssueType, Priority};
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub doctor: DoctorConfig,
+    #[serde(default)]
+    pub specs: SpecsConfig,
+    #[serde(default)]
+    pub client: ClientConfig,
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+    #[serde(default)]
+    pub workload: WorkloadConfig,
+    #[serde(default)]
+    pub issues: IssuesConfig,
+    #[serde(default)]
+    pub workflow: WorkflowConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub routing: BTreeMap<String, RoutingRule>,
+}
+
+/// Defaults for issues created with `--spec <stem>`. Anything the create
+/// sets itself wins; labels are added on top of none.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingRule {
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub priority: Option<Priority>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IssuesConfig {
+    pub default_priority: Option<Priority>,
+    pub types: Option<Vec<IssueType>>,
+    pub id_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowConfig {
+    #[serde(default)]
+    pub require_claim: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportConfig {
+    #[serde(default)]
+    pub auto: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_second: Option<u32>,
+    pub burst: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests_per_second: u32,
+    pub burst: u32,
+}
+
+pub const DEFAULT_ID_PREFIX: &str = "pn";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DoctorConfig {
+    pub stale_after: Option<String>,
+    pub stale_issue_after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkloadConfig {
+    pub max_active: Option<i64>,
+}
+
+pub const DEFAULT_MAX_ACTIVE: i64 = 3;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    pub connect_timeout: Option<String>,
+    pub request_timeout: Option<String>,
+    pub retries: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EscalationConfig {
+    pub untouched_after: Option<String>,
+    pub blocking_dependents: Option<usize>,
+    pub blocking_priority: Option<Priority>,
+    pub interval: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscalationPolicy {
+    pub untouched_after: Option<TimeDelta>,
+    pub blocking_dependents: Option<usize>,
+    pub blocking_priority: Priority,
+    pub interval: TimeDelta,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecsConfig {
+    pub dir: Option<PathBuf>,
+    #[serde(default)]
+    pub validate: SpecValidation,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpecValidation {
+    #[default]
+    Forma,
+    Reject,
+    Warn,
+    Off,
+}
+
+impl SpecValidation {
+    pub fn checks_files(self) -> bool {
+        matches!(self, SpecValidation::Reject | SpecValidation::Warn)
+    }
+}
+
+impl Config {
+    pub fn load(pensa_dir: &Path) -> Result<Config, PensaError> {
+        let path = pensa_dir.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => {
+                return Err(PensaError::Internal(format!(
+                    "failed to read {}: {e}",
+                    path.display()
+                )));
+            }
+        };
+        let config: Config = toml::from_str(&content)
+            .map_err(|e| PensaError::InvalidArgument(format!("invalid {}: {e}", path.display())))?;
+        config
+            .validate()
+            .map_err(|e| PensaError::InvalidArgument(format!("invalid {}: {e}", path.display())))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let durations = [
+            self.doctor.stale_after.as_deref(),
+            self.doctor.stale_issue_after.as_deref(),
+            self.escalation.untouched_after.as_deref(),
+            self.escalation.interval.as_deref(),
+        ];
+        for d in durations.into_iter().flatten() {
+            parse_duration(d).map_err(|e| e.to_string())?;
+        }
+        if let Some(prefix) = &self.issues.id_prefix {
+            let valid = prefix.len() <= 8
+                && prefix.starts_with(|c: char| c.is_ascii_lowercase())
+                && prefix
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+            if !valid {
+                return Err(format!(
+                    "id_prefix {prefix:?} must be up to 8 lowercase letters and digits, starting with a letter"
+                ));
+            }
+        }
+        if self.issues.types.as_ref().is_some_and(|t| t.is_empty()) {
+            return Err("[issues] types must allow at least one type".to_string());
+        }
+        if self.rate_limit.requests_per_second == Some(0) || self.rate_limit.burst == Some(0) {
+            return Err("[rate_limit] values must be at least 1".to_string());
+        }
+        for (spec, rule) in &self.routing {
+            let blank = rule
+                .assignee
+                .as_deref()
+                .is_some_and(|a| a.trim().is_empty())
+                || rule.labels.iter().any(|l| l.trim().is_empty());
+            if blank {
+                return Err(format!(
+                    "[routing.{spec}] assignee and labels must not be empty"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn default_priority(&self) -> Priority {
+        self.issues.default_priority.unwrap_or(Priority::P2)
+    }
+
+    pub fn route(&self, spec: Option<&str>) -> Option<&RoutingRule> {
+        self.routing.get(spec?)
+    }
+
+    pub fn default_priority_for(&self, spec: Option<&str>) -> Priority {
+        self.route(spec)
+            .and_then(|rule| rule.priority)
+            .unwrap_or_else(|| self.default_priority())
+    }
+
+    pub fn id_prefix(&self) -> &str {
+        self.issues
+            .id_prefix
+            .as_deref()
+            .unwrap_or(DEFAULT_ID_PREFIX)
+    }
+
+    pub fn allows_type(&self, issue_type: IssueType) -> bool {
+        self.issues
+            .types
+            .as_ref()
+            .is_none_or(|types| types.contains(&issue_type))
+    }
+
+    pub fn specs_dir(&self, project_dir: &Path) -> PathBuf {
+        project_dir.join(self.specs.dir.as_deref().unwrap_or(Path::new("specs")))
+    }
+
+    pub fn escalation_policy(&self) -> Result<Option<EscalationPolicy>, PensaError> {
+        let cfg = &self.escalation;
+        if cfg.untouched_after.is_none() && cfg.blocking_dependents.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(EscalationPolicy {
+            untouched_after: cfg
+                .untouched_after
+                .as_deref()
+                .map(parse_duration)
+                .transpose()?,
+            blocking_dependents: cfg.blocking_dependents,
+            blocking_priority: cfg.blocking_priority.unwrap_or(Priority::P1),
+            interval: match cfg.interval.as_deref() {
+                Some(s) => parse_duration(s)?,
+                None => TimeDelta::hours(1),
+            },
+        }))
+    }
+
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        let requests_per_second = self.rate_limit.requests_per_second?;
+        Some(RateLimit {
+            requests_per_second,
+            burst: self.rate_limit.burst.unwrap_or(requests_per_second),
+        })
+    }
+
+    pub fn max_active(&self) -> i64 {
+        self.workload.max_active.unwrap_or(DEFAULT_MAX_ACTIVE)
+    }
+
+    pub fn stale_after(&self) -> Result<Option<TimeDelta>, PensaError> {
+        self.doctor
+            .stale_after
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+    }
+
+    pub fn stale_issue_after(&self) -> Result<Option<TimeDelta>, PensaError> {
+        self.doctor
+            .stale_issue_after
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+    }
+}
+
+fn split_key(key: &str) -> Result<(&str, &str), PensaError> {
+    key.split_once('.')
+        .filter(|(section, field)| !section.is_empty() && !field.is_empty() && !field.contains('.'))
+        .ok_or_else(|| {
+            PensaError::InvalidArgument(format!("config key must be <section>.<key>: {key:?}"))
+        })
+}
+
+fn read_document(pensa_dir: &Path) -> Result<toml_edit::DocumentMut, PensaError> {
+    let path = pensa_dir.join(CONFIG_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(PensaError::Internal(format!(
+                "failed to read {}: {e}",
+                path.display()
+            )));
+        }
+    };
+    content
+        .parse()
+        .map_err(|e| PensaError::InvalidArgument(format!("invalid {}: {e}", path.display())))
+}
+
+pub fn get_value(pensa_dir: &Path, key: &str) -> Result<Option<String>, PensaError> {
+    let (section, field) = split_key(key)?;
+    let doc = read_document(pensa_dir)?;
+    Ok(doc
+        .get(section)
+        .and_then(|s| s.get(field))
+        .and_then(|item| item.as_value())
+        .map(|value| match value.as_str() {
+            Some(s) => s.to_string(),
+            None => value.to_string().trim().to_string(),
+        }))
+}
+
+/// Sets `key` (`section.key`) in `config.toml`, keeping the rest of the
+/// file and its comments as they are. `value` is read as TOML (`3`, `true`,
+/// `["bug", "task"]`), falling back to a plain string. The file is only
+/// written when the result is a valid config.
+pub fn set_value(pensa_dir: &Path, key: &str, value: &str) -> Result<(), PensaError> {
+    let (section, field) = split_key(key)?;
+    let mut doc = read_document(pensa_dir)?;
+    let parsed = value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| toml_edit::Value::from(value));
+    let table = doc
+        .entry(section)
+        .or_insert_with(toml_edit::table)
+        .as_table_mut()
+        .ok_or_else(|| PensaError::InvalidArgument(format!("[{section}] is not a table")))?;
+    table[field] = toml_edit::value(parsed);
+
+    let content = doc.to_string();
+    let config: Config = toml::from_str(&content)
+        .map_err(|e| PensaError::InvalidArgument(format!("invalid {key}: {}", e.message())))?;
+    config
+        .validate()
+        .map_err(|e| PensaError::InvalidArgument(format!("invalid {key}: {e}")))?;
+    let path = pensa_dir.join(CONFIG_FILE);
+    std::fs::write(&path, content)
+        .map_err(|e| PensaError::Internal(format!("failed to write {}: {e}", path.display())))
+}
+
+pub fn parse_duration(input: &str) -> Result<TimeDelta, PensaError> {
+    let invalid = || PensaError::InvalidArgument(format!("invalid duration: {input:?}"));
+    let s = input.trim();
+    if s.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total: i64 = 0;
+    let mut digits = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let n: i64 = digits.parse().map_err(|_| invalid())?;
+        digits.clear();
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 604800,
+            _ => return Err(invalid()),
+        };
+        total = n
+            .checked_mul(unit)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(invalid)?;
+    }
+    if !digits.is_empty() {
+        return Err(invalid());
+    }
+
+    TimeDelta::try_seconds(total).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("90s").unwrap(), TimeDelta::seconds(90));
+        assert_eq!(parse_duration("30m").unwrap(), TimeDelta::minutes(30));
+        assert_eq!(parse_duration("4h").unwrap(), TimeDelta::hours(4));
+        assert_eq!(parse_duration("2d").unwrap(), TimeDelta::days(2));
+        assert_eq!(parse_duration("1w").unwrap(), TimeDelta::weeks(1));
+        assert_eq!(parse_duration("1h30m").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(parse_duration("0s").unwrap(), TimeDelta::zero());
+    }
+
+    #[test]
+    fn parse_duration_rejects_garbage() {
+        for bad in ["", "4", "h", "4x", "-4h", "4h5"] {
+            assert!(
+                matches!(parse_duration(bad), Err(PensaError::InvalidArgument(_))),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn load_missing_file_is_default() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert!(config.doctor.stale_after.is_none());
+        assert!(config.stale_after().unwrap().is_none());
+    }
+
+    #[test]
+    fn load_doctor_stale_after() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[doctor]\nstale_after = \"4h\"\nstale_issue_after = \"30d\"\n",
+        )
+        .unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.stale_after().unwrap(), Some(TimeDelta::hours(4)));
+        assert_eq!(
+            config.stale_issue_after().unwrap(),
+            Some(TimeDelta::days(30))
+        );
+    }
+
+    #[test]
+    fn load_specs_section() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.specs.validate, SpecValidation::Forma);
+        assert_eq!(config.specs_dir(Path::new("/p")), Path::new("/p/specs"));
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[specs]\ndir = \"docs/specs\"\nvalidate = \"warn\"\n",
+        )
+        .unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.specs.validate, SpecValidation::Warn);
+        assert_eq!(
+            config.specs_dir(Path::new("/p")),
+            Path::new("/p/docs/specs")
+        );
+    }
+
+    #[test]
+    fn load_escalation_section() {
+        let dir = TempDir::new().unwrap();
+        assert!(
+            Config::load(dir.path())
+                .unwrap()
+                .escalation_policy()
+                .unwrap()
+                .is_none()
+        );
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[escalation]\nuntouched_after = \"14d\"\nblocking_dependents = 3\n",
+        )
+        .unwrap();
+        let policy = Config::load(dir.path())
+            .unwrap()
+            .escalation_policy()
+            .unwrap()
+            .unwrap();
+        assert_eq!(policy.untouched_after, Some(TimeDelta::days(14)));
+        assert_eq!(policy.blocking_dependents, Some(3));
+        assert_eq!(policy.blocking_priority, Priority::P1);
+        assert_eq!(policy.interval, TimeDelta::hours(1));
+    }
+
+    #[test]
+    fn load_routing_rules() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[issues]\ndefault_priority = \"p3\"\n\n\
+             [routing.auth]\nassignee = \"wlorey\"\nlabels = [\"security\"]\npriority = \"p1\"\n\n\
+             [routing.billing]\nlabels = [\"payments\"]\n",
+        )
+        .unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        let auth = config.route(Some("auth")).unwrap();
+        assert_eq!(auth.assignee.as_deref(), Some("wlorey"));
+        assert_eq!(auth.labels, ["security"]);
+        assert_eq!(config.default_priority_for(Some("auth")), Priority::P1);
+        assert_eq!(config.default_priority_for(Some("billing")), Priority::P3);
+        assert_eq!(config.default_priority_for(None), Priority::P3);
+        assert!(config.route(Some("other")).is_none());
+        assert!(config.route(None).is_none());
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[routing.auth]\nassignee = \" \"\n",
+        )
+        .unwrap();
+        assert!(Config::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn load_rate_limit_section() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(Config::load(dir.path()).unwrap().rate_limit(), None);
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[rate_limit]\nrequests_per_second = 20\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Config::load(dir.path()).unwrap().rate_limit(),
+            Some(RateLimit {
+                requests_per_second: 20,
+                burst: 20,
+            })
+        );
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[rate_limit]\nrequests_per_second = 0\n",
+        )
+        .unwrap();
+        assert!(Config::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn issue_settings_default_and_load() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.default_priority(), Priority::P2);
+        assert_eq!(config.id_prefix(), "pn");
+        assert!(config.allows_type(IssueType::Chore));
+        assert!(!config.workflow.require_claim && !config.export.auto);
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[issues]\ndefault_priority = \"p1\"\ntypes = [\"bug\", \"task\"]\nid_prefix = \"acme\"\n",
+        )
+        .unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.default_priority(), Priority::P1);
+        assert_eq!(config.id_prefix(), "acme");
+        assert!(config.allows_type(IssueType::Bug));
+        assert!(!config.allows_type(IssueType::Chore));
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[issues]\nid_prefix = \"Not Ok\"\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            Config::load(dir.path()),
+            Err(PensaError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn set_value_keeps_comments_and_validates() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        std::fs::write(&path, "# team settings\n[doctor]\nstale_after = \"4h\"\n").unwrap();
+
+        set_value(dir.path(), "issues.default_priority", "p0").unwrap();
+        set_value(dir.path(), "workflow.require_claim", "true").unwrap();
+        set_value(dir.path(), "issues.types", r#"["bug", "task"]"#).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# team settings\n"), "{content}");
+        assert_eq!(
+            get_value(dir.path(), "issues.default_priority").unwrap(),
+            Some("p0".to_string())
+        );
+        assert_eq!(
+            get_value(dir.path(), "issues.types").unwrap(),
+            Some(r#"["bug", "task"]"#.to_string())
+        );
+        assert_eq!(get_value(dir.path(), "export.auto").unwrap(), None);
+        let config = Config::load(dir.path()).unwrap();
+        assert!(config.workflow.require_claim);
+
+        for (key, value) in [
+            ("doctor.stale_after", "soon"),
+            ("issues.default_priority", "p9"),
+            ("issues.bogus", "1"),
+            ("nosection", "1"),
+        ] {
+            assert!(
+                matches!(
+                    set_value(dir.path(), key, value),
+                    Err(PensaError::InvalidArgument(_))
+                ),
+                "{key} = {value} should be rejected"
+            );
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+    }
+
+    #[test]
+    fn load_rejects_unknown_keys() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE), "[doctor]\nbogus = 1\n").unwrap();
+        assert!(matches!(
+            Config::load(dir.path()),
+            Err(PensaError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,6836 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use rusqlite::Connection;
+use rusqlite::types::Value;
+
+use crate::checklist;
+use crate::commits::find_issue_ids;
+use crate::config::{Config, EscalationPolicy};
+use crate::error::PensaError;
+use crate::id::{generate_id, generate_id_with_prefix, generate_secret};
+use crate::mentions::{find_mentions, mention_detail};
+use crate::types::{
+    Actor, Comment, CommitLink, CountGroup, CountResult, CreateIssueParams, DbStats, Dep,
+    DepTreeNode, DocRef, DoctorFinding, DoctorReport, EVENT_TYPES, Escalation, Event,
+    ExclusionReason, ExportImportResult, ExternalDep, ExternalKind, FieldChange,
+    GroupedCountResult, Inbox, InboxEntry, Issue, IssueChange, IssueDetail, IssueText, IssueType,
+    Label, ListFilters, PlannedIssue, PromoteRefsResult, ReadyExplanation, Resolution, RowAction,
+    RowChange, SkippedRef, Snapshot, SnapshotDiff, SpecDetail, SpecStatusEntry, SrcRef, StaleIssue,
+    Status, StatusEntry, UpdateFields, Webhook, WorkloadEntry, WorkloadReport,
+};
+
+/// Bumped with each change `run_migrations` makes to existing databases:
+/// the base tables, then `issues.resolution`, then `issues.rev`, then the
+/// `actors` registry.
+pub const SCHEMA_VERSION: i64 = 4;
+
+pub struct Db {
+    pub conn: Connection,
+    pub pensa_dir: PathBuf,
+    pub data_dir: PathBuf,
+}
+
+pub fn data_dir_for(project_dir: &Path) -> PathBuf {
+    data_dir(project_dir)
+}
+
+fn project_hash(project_dir: &Path) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+    let canonical = project_dir
+        .canonicalize()
+        .unwrap_or_else(|_| project_dir.to_path_buf());
+    Sha256::digest(canonical.to_string_lossy().as_bytes()).into()
+}
+
+fn data_dir(project_dir: &Path) -> PathBuf {
+    let hash = project_hash(project_dir);
+    let hex: String = hash[..8].iter().map(|b| format!("{b:02x}")).collect();
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".local/share/pensa").join(hex)
+}
+
+fn old_data_dir(project_dir: &Path) -> PathBuf {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let canonical = project_dir
+        .canonicalize()
+        .unwrap_or_else(|_| project_dir.to_path_buf());
+    let mut hasher = DefaultHasher::new();
+    canonical.to_string_lossy().hash(&mut hasher);
+    let hash = format!("{:016x}", hasher.finish());
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".local/share/pensa").join(hash)
+}
+
+pub fn find_project_root() -> Option<PathBuf> {
+    let mut dir = std::env::current_dir().ok()?;
+    loop {
+        let candidate = dir.join(".pensa");
+        if candidate.is_dir() && is_genuine_pensa_dir(&candidate) {
+            return Some(dir);
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+fn is_genuine_pensa_dir(pensa_dir: &Path) -> bool {
+    pensa_dir.join("issues.jsonl").exists()
+        || pensa_dir.join("deps.jsonl").exists()
+        || pensa_dir.join("db.sqlite").exists()
+}
+
+pub fn project_port(project_dir: &Path) -> u16 {
+    let hash = project_hash(project_dir);
+    let raw = u16::from_be_bytes([hash[8], hash[9]]);
+    10000 + (raw % 50000)
+}
+
+/// Columns `list` can sort by. Keys come from clients, so only these names
+/// ever reach the `ORDER BY` clause.
+const SORT_FIELDS: &[&str] = &[
+    "priority",
+    "created_at",
+    "updated_at",
+    "closed_at",
+    "status",
+    "title",
+    "issue_type",
+    "assignee",
+    "spec",
+    "resolution",
+    "id",
+];
+
+/// Compiles a sort spec like `-priority,updated_at` into an `ORDER BY`
+/// clause. Each key is a field from [`SORT_FIELDS`], descending when
+/// prefixed with `-`. Ties fall back to `created_at` then `id`, ascending.
+fn order_clause(spec: &str) -> Result<String, PensaError> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut used: Vec<&str> = Vec::new();
+    for key in spec.split(',').map(str::trim) {
+        let (field, direction) = match key.strip_prefix('-') {
+            Some(field) => (field, "DESC"),
+            None => (key.strip_prefix('+').unwrap_or(key), "ASC"),
+        };
+        if !SORT_FIELDS.contains(&field) {
+            return Err(PensaError::InvalidArgument(format!(
+                "invalid sort field {key:?}; expected one of {}",
+                SORT_FIELDS.join(", ")
+            )));
+        }
+        if used.contains(&field) {
+            return Err(PensaError::InvalidArgument(format!(
+                "sort field {field:?} given more than once"
+            )));
+        }
+        used.push(field);
+        terms.push(format!("{field} {direction}"));
+    }
+    for tiebreak in ["created_at", "id"] {
+        if !used.contains(&tiebreak) {
+            terms.push(format!("{tiebreak} ASC"));
+        }
+    }
+    Ok(format!("ORDER BY {}", terms.join(", ")))
+}
+
+fn parse_dt(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+}
+
+pub(crate) fn issue_from_row(row: &rusqlite::Row) -> Result<Issue, rusqlite::Error> {
+    let issue_type_str: String = row.get("issue_type")?;
+    let status_str: String = row.get("status")?;
+    let priority_str: String = row.get("priority")?;
+    let created_at_str: String = row.get("created_at")?;
+    let updated_at_str: String = row.get("updated_at")?;
+    let closed_at_str: Option<String> = row.get("closed_at")?;
+    let resolution_str: Option<String> = row.get("resolution")?;
+
+    let description: Option<String> = row.get("description")?;
+
+    Ok(Issue {
+        id: row.get("id")?,
+        title: row.get("title")?,
+        checklist: checklist::summary(description.as_deref()),
+        description,
+        issue_type: issue_type_str.parse().unwrap(),
+        status: status_str.parse().unwrap(),
+        priority: priority_str.parse().unwrap(),
+        spec: row.get("spec")?,
+        fixes: row.get("fixes")?,
+        assignee: row.get("assignee")?,
+        created_at: parse_dt(&created_at_str),
+        updated_at: parse_dt(&updated_at_str),
+        closed_at: closed_at_str.map(|s| parse_dt(&s)),
+        close_reason: row.get("close_reason")?,
+        resolution: resolution_str.map(|s| s.parse().unwrap()),
+        rev: row.get("rev")?,
+    })
+}
+
+fn webhook_from_row(row: &rusqlite::Row) -> Result<Webhook, rusqlite::Error> {
+    let events: String = row.get("events")?;
+    let created_at_str: String = row.get("created_at")?;
+    Ok(Webhook {
+        id: row.get("id")?,
+        url: row.get("url")?,
+        events: events
+            .split(',')
+            .filter(|e| !e.is_empty())
+            .map(str::to_string)
+            .collect(),
+        spec: row.get("spec")?,
+        secret: row.get("secret")?,
+        last_event_id: row.get("last_event_id")?,
+        created_at: parse_dt(&created_at_str),
+    })
+}
+
+fn actor_from_row(row: &rusqlite::Row) -> Result<Actor, rusqlite::Error> {
+    let first_seen_str: String = row.get("first_seen")?;
+    let last_seen_str: String = row.get("last_seen")?;
+    Ok(Actor {
+        name: row.get("name")?,
+        display_name: row.get("display_name")?,
+        alias_of: row.get("alias_of")?,
+        first_seen: parse_dt(&first_seen_str),
+        last_seen: parse_dt(&last_seen_str),
+    })
+}
+
+fn snapshot_from_row(row: &rusqlite::Row) -> Result<Snapshot, rusqlite::Error> {
+    let created_at_str: String = row.get("created_at")?;
+    Ok(Snapshot {
+        id: row.get("id")?,
+        label: row.get("label")?,
+        issue_count: row.get("issue_count")?,
+        created_at: parse_dt(&created_at_str),
+    })
+}
+
+pub(crate) fn comment_from_row(row: &rusqlite::Row) -> Result<Comment, rusqlite::Error> {
+    let created_at_str: String = row.get("created_at")?;
+    Ok(Comment {
+        id: row.get("id")?,
+        issue_id: row.get("issue_id")?,
+        actor: row.get("actor")?,
+        text: row.get("text")?,
+        created_at: parse_dt(&created_at_str),
+    })
+}
+
+pub(crate) fn src_ref_from_row(row: &rusqlite::Row) -> Result<SrcRef, rusqlite::Error> {
+    let created_at_str: String = row.get("created_at")?;
+    Ok(SrcRef {
+        id: row.get("id")?,
+        issue_id: row.get("issue_id")?,
+        path: row.get("path")?,
+        reason: row.get("reason")?,
+        created_at: parse_dt(&created_at_str),
+    })
+}
+
+pub(crate) fn doc_ref_from_row(row: &rusqlite::Row) -> Result<DocRef, rusqlite::Error> {
+    let created_at_str: String = row.get("created_at")?;
+    Ok(DocRef {
+        id: row.get("id")?,
+        issue_id: row.get("issue_id")?,
+        path: row.get("path")?,
+        reason: row.get("reason")?,
+        created_at: parse_dt(&created_at_str),
+    })
+}
+
+pub(crate) fn label_from_row(row: &rusqlite::Row) -> Result<Label, rusqlite::Error> {
+    let created_at_str: String = row.get("created_at")?;
+    Ok(Label {
+        issue_id: row.get("issue_id")?,
+        label: row.get("label")?,
+        created_at: parse_dt(&created_at_str),
+    })
+}
+
+fn external_dep_from_row(row: &rusqlite::Row) -> Result<ExternalDep, rusqlite::Error> {
+    let kind_str: String = row.get("kind")?;
+    let resolved_at_str: Option<String> = row.get("resolved_at")?;
+    let created_at_str: String = row.get("created_at")?;
+    Ok(ExternalDep {
+        issue_id: row.get("issue_id")?,
+        target: row.get("target")?,
+        kind: kind_str.parse().unwrap(),
+        resolved_at: resolved_at_str.map(|s| parse_dt(&s)),
+        created_at: parse_dt(&created_at_str),
+    })
+}
+
+pub(crate) fn commit_link_from_row(row: &rusqlite::Row) -> Result<CommitLink, rusqlite::Error> {
+    let created_at_str: String = row.get("created_at")?;
+    Ok(CommitLink {
+        issue_id: row.get("issue_id")?,
+        sha: row.get("sha")?,
+        summary: row.get("summary")?,
+        created_at: parse_dt(&created_at_str),
+    })
+}
+
+impl Db {
+    pub fn open(project_dir: &Path) -> Result<Db, PensaError> {
+        let pensa_dir = project_dir.join(".pensa");
+        let dd = data_dir(project_dir);
+
+        let old_dd = old_data_dir(project_dir);
+        if old_dd != dd
+            && old_dd.exists()
+            && !dd.exists()
+            && let Err(e) = fs::rename(&old_dd, &dd)
+        {
+            tracing::warn!(
+                old = %old_dd.display(),
+                new = %dd.display(),
+                "rename failed, will create fresh: {e}"
+            );
+        }
+
+        Self::open_with_data_dir(pensa_dir, dd)
+    }
+
+    pub fn open_with_data_dir(pensa_dir: PathBuf, data_dir: PathBuf) -> Result<Db, PensaError> {
+        fs::create_dir_all(&pensa_dir)
+            .map_err(|e| PensaError::Internal(format!("failed to create .pensa dir: {e}")))?;
+        fs::create_dir_all(&data_dir)
+            .map_err(|e| PensaError::Internal(format!("failed to create data dir: {e}")))?;
+
+        let old_db = pensa_dir.join("db.sqlite");
+        let new_db = data_dir.join("db.sqlite");
+        if old_db.exists() && !new_db.exists() && fs::rename(&old_db, &new_db).is_err() {
+            fs::copy(&old_db, &new_db)
+                .map_err(|e| PensaError::Internal(format!("failed to migrate db.sqlite: {e}")))?;
+            let _ = fs::remove_file(&old_db);
+        }
+
+        let conn = Connection::open(&new_db)
+            .map_err(|e| PensaError::Internal(format!("failed to open database: {e}")))?;
+
+        conn.pragma_update(None, "busy_timeout", 5000)
+            .map_err(|e| PensaError::Internal(format!("failed to set busy_timeout: {e}")))?;
+        conn.pragma_update(None, "foreign_keys", "ON")
+            .map_err(|e| PensaError::Internal(format!("failed to enable foreign_keys: {e}")))?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| PensaError::Internal(format!("failed to enable WAL: {e}")))?;
+
+        Self::run_migrations(&conn)?;
+
+        let db = Db {
+            conn,
+            pensa_dir: pensa_dir.clone(),
+            data_dir,
+        };
+
+        let issue_count: i64 = db
+            .conn
+            .query_row("SELECT COUNT(*) FROM issues", [], |row| row.get(0))
+            .map_err(|e| PensaError::Internal(format!("failed to count issues: {e}")))?;
+
+        if issue_count == 0 && pensa_dir.join("issues.jsonl").exists() {
+            db.import_jsonl()?;
+        } else {
+            let ref_count: i64 = db
+                .conn
+                .query_row("SELECT COUNT(*) FROM issue_refs", [], |row| row.get(0))
+                .map_err(|e| PensaError::Internal(format!("failed to count refs: {e}")))?;
+            if ref_count == 0 && issue_count > 0 {
+                db.rebuild_refs()?;
+            }
+        }
+
+        Ok(db)
+    }
+
+    fn run_migrations(conn: &Connection) -> Result<(), PensaError> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS issues (
+                id          TEXT PRIMARY KEY,
+                title       TEXT NOT NULL,
+                description TEXT,
+                issue_type  TEXT NOT NULL CHECK (issue_type IN ('bug', 'task', 'test', 'chore')),
+                status      TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'in_progress', 'closed')),
+                priority    TEXT NOT NULL DEFAULT 'p2' CHECK (priority IN ('p0', 'p1', 'p2', 'p3')),
+                spec        TEXT,
+                fixes       TEXT REFERENCES issues(id),
+                assignee    TEXT,
+                created_at  TEXT NOT NULL,
+                updated_at  TEXT NOT NULL,
+                closed_at   TEXT,
+                close_reason TEXT,
+                resolution  TEXT CHECK (resolution IN ('fixed', 'wont_fix', 'duplicate', 'obsolete'))
+            );
+
+            CREATE TABLE IF NOT EXISTS deps (
+                issue_id      TEXT NOT NULL REFERENCES issues(id),
+                depends_on_id TEXT NOT NULL REFERENCES issues(id),
+                PRIMARY KEY (issue_id, depends_on_id),
+                CHECK (issue_id != depends_on_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS comments (
+                id         TEXT PRIMARY KEY,
+                issue_id   TEXT NOT NULL REFERENCES issues(id),
+                actor      TEXT NOT NULL,
+                text       TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS events (
+                id         INTEGER PRIMARY KEY AUTOINCREMENT,
+                issue_id   TEXT NOT NULL REFERENCES issues(id),
+                event_type TEXT NOT NULL,
+                actor      TEXT,
+                detail     TEXT,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS src_refs (
+                id         TEXT PRIMARY KEY,
+                issue_id   TEXT NOT NULL REFERENCES issues(id),
+                path       TEXT NOT NULL,
+                reason     TEXT,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS doc_refs (
+                id         TEXT PRIMARY KEY,
+                issue_id   TEXT NOT NULL REFERENCES issues(id),
+                path       TEXT NOT NULL,
+                reason     TEXT,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS commits (
+                issue_id   TEXT NOT NULL REFERENCES issues(id),
+                sha        TEXT NOT NULL,
+                summary    TEXT,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (issue_id, sha)
+            );
+
+            CREATE TABLE IF NOT EXISTS labels (
+                issue_id   TEXT NOT NULL REFERENCES issues(id),
+                label      TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (issue_id, label)
+            );
+
+            CREATE TABLE IF NOT EXISTS external_deps (
+                issue_id    TEXT NOT NULL REFERENCES issues(id),
+                target      TEXT NOT NULL,
+                kind        TEXT NOT NULL CHECK (kind IN ('github', 'jira', 'url')),
+                resolved_at TEXT,
+                created_at  TEXT NOT NULL,
+                PRIMARY KEY (issue_id, target)
+            );
+
+            CREATE TABLE IF NOT EXISTS issue_refs (
+                issue_id TEXT NOT NULL REFERENCES issues(id),
+                ref_id   TEXT NOT NULL,
+                source   TEXT NOT NULL CHECK (source IN ('description', 'comment')),
+                PRIMARY KEY (issue_id, ref_id, source)
+            );
+
+            -- Watches and inbox cursors are per-actor local state: not
+            -- exported, and kept across `import` (hence no foreign key).
+            CREATE TABLE IF NOT EXISTS watchers (
+                issue_id       TEXT NOT NULL,
+                actor          TEXT NOT NULL,
+                since_event_id INTEGER NOT NULL,
+                created_at     TEXT NOT NULL,
+                PRIMARY KEY (issue_id, actor)
+            );
+
+            CREATE TABLE IF NOT EXISTS inbox_cursors (
+                actor         TEXT PRIMARY KEY,
+                last_event_id INTEGER NOT NULL
+            );
+
+            -- Webhooks are local too: a clone should not start posting to
+            -- another checkout's endpoints.
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id            TEXT PRIMARY KEY,
+                url           TEXT NOT NULL,
+                events        TEXT NOT NULL,
+                spec          TEXT,
+                secret        TEXT NOT NULL,
+                last_event_id INTEGER NOT NULL,
+                created_at    TEXT NOT NULL
+            );
+
+            -- Snapshots stay local as well; each holds every issue as JSON.
+            CREATE TABLE IF NOT EXISTS snapshots (
+                id          TEXT PRIMARY KEY,
+                label       TEXT,
+                issue_count INTEGER NOT NULL,
+                issues      TEXT NOT NULL,
+                created_at  TEXT NOT NULL
+            );
+
+            -- Every event actor and assignee, kept up to date by triggers.
+            -- Exported, so aliases and display names travel with the issues.
+            CREATE TABLE IF NOT EXISTS actors (
+                name         TEXT PRIMARY KEY,
+                display_name TEXT,
+                alias_of     TEXT,
+                first_seen   TEXT NOT NULL,
+                last_seen    TEXT NOT NULL
+            );",
+        )
+        .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+
+        // Databases created before close resolutions existed lack the column.
+        let has_resolution: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('issues') WHERE name = 'resolution'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+        if !has_resolution {
+            conn.execute_batch(
+                "ALTER TABLE issues ADD COLUMN resolution TEXT
+                 CHECK (resolution IN ('fixed', 'wont_fix', 'duplicate', 'obsolete'))",
+            )
+            .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+        }
+
+        // Likewise for revisions; existing issues start at 1.
+        let has_rev: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('issues') WHERE name = 'rev'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+        if !has_rev {
+            conn.execute_batch("ALTER TABLE issues ADD COLUMN rev INTEGER NOT NULL DEFAULT 1")
+                .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+        }
+        // Any write to an issue bumps its revision, unless it set one itself.
+        conn.execute_batch(
+            "CREATE TRIGGER IF NOT EXISTS issues_bump_rev AFTER UPDATE ON issues
+             WHEN NEW.rev = OLD.rev
+             BEGIN
+                 UPDATE issues SET rev = OLD.rev + 1 WHERE id = NEW.id;
+             END;",
+        )
+        .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+
+        // Names register themselves as they act or are assigned. Databases
+        // from before the registry fill it from their history once.
+        conn.execute_batch(
+            "CREATE TRIGGER IF NOT EXISTS events_seen_actor AFTER INSERT ON events
+             WHEN NEW.actor IS NOT NULL AND NEW.actor != ''
+             BEGIN
+                 INSERT INTO actors (name, first_seen, last_seen)
+                 VALUES (NEW.actor, NEW.created_at, NEW.created_at)
+                 ON CONFLICT (name) DO UPDATE SET last_seen = MAX(last_seen, excluded.last_seen);
+             END;
+             CREATE TRIGGER IF NOT EXISTS issues_seen_assignee AFTER INSERT ON issues
+             WHEN NEW.assignee IS NOT NULL AND NEW.assignee != ''
+             BEGIN
+                 INSERT INTO actors (name, first_seen, last_seen)
+                 VALUES (NEW.assignee, NEW.updated_at, NEW.updated_at)
+                 ON CONFLICT (name) DO UPDATE SET last_seen = MAX(last_seen, excluded.last_seen);
+             END;
+             CREATE TRIGGER IF NOT EXISTS issues_seen_reassignee AFTER UPDATE OF assignee ON issues
+             WHEN NEW.assignee IS NOT NULL AND NEW.assignee != ''
+             BEGIN
+                 INSERT INTO actors (name, first_seen, last_seen)
+                 VALUES (NEW.assignee, NEW.updated_at, NEW.updated_at)
+                 ON CONFLICT (name) DO UPDATE SET last_seen = MAX(last_seen, excluded.last_seen);
+             END;",
+        )
+        .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+        if version < 4 {
+            conn.execute_batch(
+                "INSERT OR IGNORE INTO actors (name, first_seen, last_seen)
+                 SELECT actor, MIN(created_at), MAX(created_at) FROM events
+                 WHERE actor IS NOT NULL AND actor != '' GROUP BY actor;
+                 INSERT OR IGNORE INTO actors (name, first_seen, last_seen)
+                 SELECT assignee, MIN(updated_at), MAX(updated_at) FROM issues
+                 WHERE assignee IS NOT NULL AND assignee != '' GROUP BY assignee;",
+            )
+            .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+        }
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(|e| PensaError::Internal(format!("migration failed: {e}")))?;
+
+        Ok(())
+    }
+
+    fn config(&self) -> Result<Config, PensaError> {
+        Config::load(&self.pensa_dir)
+    }
+
+    fn check_close_allowed(&self, issue: &Issue) -> Result<(), PensaError> {
+        if issue.status == Status::Open && self.config()?.workflow.require_claim {
+            return Err(PensaError::InvalidStatusTransition {
+                from: "open".to_string(),
+                to: "closed".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Creates an issue with its dependencies and labels in one transaction:
+    /// a missing dep target, a cycle, or a bad label rolls the whole creation
+    /// back.
+    pub fn create_issue(&self, params: &CreateIssueParams) -> Result<Issue, PensaError> {
+        let config = self.config()?;
+        let mut deps: Vec<&String> = Vec::new();
+        let mut external_deps: Vec<(&String, ExternalKind)> = Vec::new();
+        for dep_id in &params.deps {
+            if let Some(kind) = ExternalKind::classify(dep_id) {
+                if !external_deps.iter().any(|(target, _)| *target == dep_id) {
+                    external_deps.push((dep_id, kind));
+                }
+                continue;
+            }
+            self.get_issue_only(dep_id)?;
+            if !deps.contains(&dep_id) {
+                deps.push(dep_id);
+            }
+        }
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| PensaError::Internal(format!("failed to begin transaction: {e}")))?;
+        let id = self.insert_issue(&config, params)?;
+        for dep_id in deps {
+            self.insert_dep(&id, dep_id)?;
+        }
+        for (target, kind) in external_deps {
+            self.insert_external_dep(&id, target, kind)?;
+        }
+        self.refresh_refs(&id)?;
+        tx.commit()
+            .map_err(|e| PensaError::Internal(format!("failed to commit issue: {e}")))?;
+        self.get_issue_only(&id)
+    }
+
+    /// Creates every issue in `plan` in one transaction, so a bad entry
+    /// leaves nothing behind. Each entry's `deps` name other entries by key,
+    /// in any order, or existing issues by ID. Entries get the spec's
+    /// `[routing]` defaults as `pn create` does. Returns the issues in plan
+    /// order.
+    pub fn create_issues(
+        &self,
+        plan: &[PlannedIssue],
+        actor: &str,
+    ) -> Result<Vec<Issue>, PensaError> {
+        let config = self.config()?;
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| PensaError::Internal(format!("failed to begin transaction: {e}")))?;
+
+        let mut ids: HashMap<&str, String> = HashMap::new();
+        for entry in plan {
+            if entry.key.trim().is_empty() {
+                return Err(PensaError::InvalidArgument(format!(
+                    "plan entry \"{}\" has no key",
+                    entry.title
+                )));
+            }
+            if ids.contains_key(entry.key.as_str()) {
+                return Err(PensaError::InvalidArgument(format!(
+                    "duplicate plan key: {}",
+                    entry.key
+                )));
+            }
+            let route = config
+                .route(entry.spec.as_deref())
+                .cloned()
+                .unwrap_or_default();
+            let params = CreateIssueParams {
+                title: entry.title.clone(),
+                issue_type: entry.issue_type,
+                priority: entry
+                    .priority
+                    .unwrap_or_else(|| config.default_priority_for(entry.spec.as_deref())),
+                description: entry.description.clone(),
+                spec: entry.spec.clone(),
+                fixes: None,
+                assignee: entry.assignee.clone().or(route.assignee),
+                deps: Vec::new(),
+                labels: entry.labels.iter().chain(&route.labels).cloned().collect(),
+                actor: actor.to_string(),
+            };
+            let id = self.insert_issue(&config, &params)?;
+            ids.insert(&entry.key, id);
+        }
+
+        for entry in plan {
+            let id = &ids[entry.key.as_str()];
+            let mut added: Vec<&str> = Vec::new();
+            for dep in &entry.deps {
+                let dep_id = match ids.get(dep.as_str()) {
+                    Some(dep_id) => dep_id.as_str(),
+                    None => match self.get_issue_only(dep) {
+                        Ok(_) => dep.as_str(),
+                        Err(PensaError::NotFound(_)) => {
+                            return Err(PensaError::InvalidArgument(format!(
+                                "{} depends on {dep}, which is neither a plan key nor an issue",
+                                entry.key
+                            )));
+                        }
+                        Err(e) => return Err(e),
+                    },
+                };
+                if !added.contains(&dep_id) {
+                    self.insert_dep(id, dep_id)?;
+                    added.push(dep_id);
+                }
+            }
+        }
+        for entry in plan {
+            self.refresh_refs(&ids[entry.key.as_str()])?;
+        }
+        tx.commit()
+            .map_err(|e| PensaError::Internal(format!("failed to commit plan: {e}")))?;
+        plan.iter()
+            .map(|entry| self.get_issue_only(&ids[entry.key.as_str()]))
+            .collect()
+    }
+
+    fn insert_issue(
+        &self,
+        config: &Config,
+        params: &CreateIssueParams,
+    ) -> Result<String, PensaError> {
+        if !config.allows_type(params.issue_type) {
+            return Err(PensaError::InvalidArgument(format!(
+                "issue type {} is not allowed in this project",
+                params.issue_type.as_str()
+            )));
+        }
+        let id = generate_id_with_prefix(config.id_prefix());
+        let ts = now();
+        self.conn.execute(
+            "INSERT INTO issues (id, title, description, issue_type, status, priority, spec, fixes, assignee, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            rusqlite::params![
+                id,
+                params.title,
+                params.description,
+                params.issue_type.as_str(),
+                "open",
+                params.priority.as_str(),
+                params.spec,
+                params.fixes,
+                params.assignee,
+                ts,
+                ts,
+            ],
+        )
+        .map_err(|e| PensaError::Internal(format!("failed to create issue: {e}")))?;
+
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, created_at) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![id, "created", params.actor, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log create event: {e}")))?;
+        for label in &params.labels {
+            self.add_label(&id, label, &params.actor)?;
+        }
+        Ok(id)
+    }
+
+    fn insert_dep(&self, id: &str, dep_id: &str) -> Result<(), PensaError> {
+        if self.has_cycle(id, dep_id)? {
+            return Err(PensaError::CycleDetected);
+        }
+        self.conn
+            .execute(
+                "INSERT INTO deps (issue_id, depends_on_id) VALUES (?1, ?2)",
+                rusqlite::params![id, dep_id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add dep: {e}")))?;
+        Ok(())
+    }
+
+    fn insert_external_dep(
+        &self,
+        id: &str,
+        target: &str,
+        kind: ExternalKind,
+    ) -> Result<(), PensaError> {
+        self.conn
+            .execute(
+                "INSERT INTO external_deps (issue_id, target, kind, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![id, target, kind.as_str(), now()],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add dep: {e}")))?;
+        Ok(())
+    }
+
+    pub(crate) fn get_issue_only(&self, id: &str) -> Result<Issue, PensaError> {
+        self.conn
+            .query_row(
+                "SELECT * FROM issues WHERE id = ?1",
+                rusqlite::params![id],
+                issue_from_row,
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => PensaError::NotFound(id.to_string()),
+                other => PensaError::Internal(format!("failed to get issue: {other}")),
+            })
+    }
+
+    pub fn get_issue(&self, id: &str) -> Result<IssueDetail, PensaError> {
+        let issue = self.get_issue_only(id)?;
+
+        let mut dep_stmt = self
+            .conn
+            .prepare(
+                "SELECT i.* FROM issues i
+                 JOIN deps d ON d.depends_on_id = i.id
+                 WHERE d.issue_id = ?1",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare deps query: {e}")))?;
+        let deps = dep_stmt
+            .query_map(rusqlite::params![id], issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query deps: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read deps: {e}")))?;
+
+        let mut comment_stmt = self
+            .conn
+            .prepare("SELECT * FROM comments WHERE issue_id = ?1 ORDER BY created_at")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare comments query: {e}")))?;
+        let comments = comment_stmt
+            .query_map(rusqlite::params![id], comment_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query comments: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read comments: {e}")))?;
+
+        let mut src_ref_stmt = self
+            .conn
+            .prepare("SELECT * FROM src_refs WHERE issue_id = ?1 ORDER BY created_at")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare src_refs query: {e}")))?;
+        let src_refs = src_ref_stmt
+            .query_map(rusqlite::params![id], src_ref_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query src_refs: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read src_refs: {e}")))?;
+
+        let mut doc_ref_stmt = self
+            .conn
+            .prepare("SELECT * FROM doc_refs WHERE issue_id = ?1 ORDER BY created_at")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare doc_refs query: {e}")))?;
+        let doc_refs = doc_ref_stmt
+            .query_map(rusqlite::params![id], doc_ref_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query doc_refs: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read doc_refs: {e}")))?;
+
+        let external_deps = self.list_external_deps(id)?;
+        let commits = self.list_commits(id)?;
+        let labels = self.list_labels(id)?;
+        let references = self.list_refs(id)?;
+        let referenced_by = self.referenced_by(id)?;
+        let fixed_by = self.fixed_by(id)?;
+
+        Ok(IssueDetail {
+            issue,
+            deps,
+            external_deps,
+            comments,
+            src_refs,
+            doc_refs,
+            commits,
+            labels,
+            references,
+            referenced_by,
+            fixed_by,
+        })
+    }
+
+    pub fn claim_issue(&self, id: &str, actor: &str) -> Result<Issue, PensaError> {
+        let rows = self
+            .conn
+            .execute(
+                "UPDATE issues SET status = 'in_progress', assignee = ?1, updated_at = ?2 WHERE id = ?3 AND status = 'open'",
+                rusqlite::params![actor, now(), id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to claim issue: {e}")))?;
+
+        if rows == 0 {
+            let issue = self.get_issue_only(id)?;
+            return Err(PensaError::AlreadyClaimed {
+                id: id.to_string(),
+                holder: issue.assignee.unwrap_or_default(),
+            });
+        }
+
+        let ts = now();
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, created_at) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![id, "claimed", actor, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log claim event: {e}")))?;
+
+        self.get_issue_only(id)
+    }
+
+    pub fn release_issue(&self, id: &str, actor: &str) -> Result<Issue, PensaError> {
+        self.get_issue_only(id)?;
+
+        let ts = now();
+        self.conn
+            .execute(
+                "UPDATE issues SET status = 'open', assignee = NULL, updated_at = ?1 WHERE id = ?2",
+                rusqlite::params![ts, id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to release issue: {e}")))?;
+
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, created_at) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![id, "released", actor, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log release event: {e}")))?;
+
+        self.get_issue_only(id)
+    }
+
+    pub fn close_issue(
+        &self,
+        id: &str,
+        reason: Option<&str>,
+        resolution: Option<Resolution>,
+        force: bool,
+        actor: &str,
+    ) -> Result<Issue, PensaError> {
+        self.close_issue_chain(id, reason, resolution, force, actor)
+            .map(|(issue, _)| issue)
+    }
+
+    /// Closes `id` and follows its `fixes` chain in one transaction. The
+    /// issue it fixes is closed as fixed once no other open issue fixes it,
+    /// then the issue that one fixes, and so on. The chain ends at an issue
+    /// that is already closed, still has open fixes, or was seen before.
+    /// Returns the closed issue and the IDs auto-closed, in order.
+    pub fn close_issue_chain(
+        &self,
+        id: &str,
+        reason: Option<&str>,
+        resolution: Option<Resolution>,
+        force: bool,
+        actor: &str,
+    ) -> Result<(Issue, Vec<String>), PensaError> {
+        let issue = self.get_issue_only(id)?;
+
+        if !force && issue.status == Status::Closed {
+            return Err(PensaError::InvalidStatusTransition {
+                from: "closed".to_string(),
+                to: "closed".to_string(),
+            });
+        }
+        if !force {
+            self.check_close_allowed(&issue)?;
+        }
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| PensaError::Internal(format!("failed to begin transaction: {e}")))?;
+        let ts = now();
+        self.conn
+            .execute(
+                "UPDATE issues SET status = 'closed', closed_at = ?1, close_reason = ?2, resolution = ?3, updated_at = ?1 WHERE id = ?4",
+                rusqlite::params![ts, reason, resolution.map(|r| r.as_str()), id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to close issue: {e}")))?;
+
+        let detail = match (resolution, reason) {
+            (Some(r), Some(text)) => Some(format!("{}: {text}", r.as_str())),
+            (Some(r), None) => Some(r.as_str().to_string()),
+            (None, text) => text.map(str::to_string),
+        };
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![id, "closed", actor, detail, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log close event: {e}")))?;
+
+        let mut auto_closed = Vec::new();
+        let mut seen = HashSet::from([id.to_string()]);
+        let mut closer = id.to_string();
+        let mut next = issue.fixes;
+        while let Some(fixes_id) = next.take() {
+            if !seen.insert(fixes_id.clone()) {
+                break;
+            }
+            let target = match self.get_issue_only(&fixes_id) {
+                Ok(target) => target,
+                Err(PensaError::NotFound(_)) => break,
+                Err(e) => return Err(e),
+            };
+            if target.status == Status::Closed {
+                break;
+            }
+            let remaining: i64 = self
+                .conn
+                .query_row(
+                    "SELECT COUNT(*) FROM issues WHERE fixes = ?1 AND status != 'closed'",
+                    rusqlite::params![fixes_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to check remaining fix tasks: {e}"))
+                })?;
+            if remaining > 0 {
+                break;
+            }
+
+            self.conn
+                .execute(
+                    "UPDATE issues SET status = 'closed', closed_at = ?1, close_reason = 'fixed', resolution = 'fixed', updated_at = ?1 WHERE id = ?2",
+                    rusqlite::params![ts, fixes_id],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to auto-close linked bug: {e}")))?;
+            self.conn
+                .execute(
+                    "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![fixes_id, "closed", actor, format!("fixed: last fix task {closer} closed"), ts],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to log auto-close event: {e}")))?;
+
+            auto_closed.push(fixes_id.clone());
+            closer = fixes_id;
+            next = target.fixes;
+        }
+        tx.commit()
+            .map_err(|e| PensaError::Internal(format!("failed to commit close: {e}")))?;
+
+        Ok((self.get_issue_only(id)?, auto_closed))
+    }
+
+    pub fn reopen_issue(
+        &self,
+        id: &str,
+        reason: Option<&str>,
+        cascade: bool,
+        actor: &str,
+    ) -> Result<Issue, PensaError> {
+        self.get_issue_only(id)?;
+
+        let closed_fixes: Vec<String> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id FROM issues WHERE fixes = ?1 AND status = 'closed' ORDER BY id")
+                .map_err(|e| PensaError::Internal(format!("failed to find fix tasks: {e}")))?;
+            stmt.query_map(rusqlite::params![id], |row| row.get(0))
+                .and_then(|rows| rows.collect())
+                .map_err(|e| PensaError::Internal(format!("failed to find fix tasks: {e}")))?
+        };
+
+        let ts = now();
+        let reopen = |issue_id: &str, detail: Option<String>| -> Result<(), PensaError> {
+            self.conn
+                .execute(
+                    "UPDATE issues SET status = 'open', closed_at = NULL, close_reason = NULL, resolution = NULL, updated_at = ?1 WHERE id = ?2",
+                    rusqlite::params![ts, issue_id],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to reopen issue: {e}")))?;
+            self.conn
+                .execute(
+                    "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![issue_id, "reopened", actor, detail, ts],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to log reopen event: {e}")))?;
+            Ok(())
+        };
+
+        let link = match (closed_fixes.is_empty(), cascade) {
+            (true, _) => None,
+            (false, true) => Some(format!("also reopened {}", closed_fixes.join(", "))),
+            (false, false) => Some(format!("fixed by {}", closed_fixes.join(", "))),
+        };
+        let detail = match (reason, link) {
+            (Some(reason), Some(link)) => Some(format!("{reason} ({link})")),
+            (reason, link) => reason.map(str::to_string).or(link),
+        };
+        reopen(id, detail)?;
+
+        if cascade {
+            for task in &closed_fixes {
+                let detail = match reason {
+                    Some(reason) => format!("{id} reopened: {reason}"),
+                    None => format!("{id} reopened"),
+                };
+                reopen(task, Some(detail))?;
+            }
+        }
+
+        self.get_issue_only(id)
+    }
+
+    pub fn delete_issue(&self, id: &str, force: bool) -> Result<(), PensaError> {
+        self.get_issue_only(id)?;
+
+        if !force {
+            let dependents: i64 = self
+                .conn
+                .query_row(
+                    "SELECT COUNT(*) FROM deps WHERE depends_on_id = ?1",
+                    rusqlite::params![id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to check dependents: {e}")))?;
+
+            let comments: i64 = self
+                .conn
+                .query_row(
+                    "SELECT COUNT(*) FROM comments WHERE issue_id = ?1",
+                    rusqlite::params![id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to check comments: {e}")))?;
+
+            if dependents > 0 || comments > 0 {
+                return Err(PensaError::DeleteRequiresForce(format!(
+                    "issue has {dependents} dependents and {comments} comments"
+                )));
+            }
+        }
+
+        self.conn
+            .execute(
+                "DELETE FROM deps WHERE issue_id = ?1 OR depends_on_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete deps: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM external_deps WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete external deps: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM comments WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete comments: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM src_refs WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete src_refs: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM doc_refs WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete doc_refs: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM commits WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete commits: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM labels WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete labels: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM issue_refs WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete refs: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM watchers WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete watchers: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM events WHERE issue_id = ?1",
+                rusqlite::params![id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to delete events: {e}")))?;
+        self.conn
+            .execute("DELETE FROM issues WHERE id = ?1", rusqlite::params![id])
+            .map_err(|e| PensaError::Internal(format!("failed to delete issue: {e}")))?;
+
+        Ok(())
+    }
+
+    pub fn check_rev(&self, id: &str, expected: Option<i64>) -> Result<Issue, PensaError> {
+        let issue = self.get_issue_only(id)?;
+        match expected {
+            Some(expected) if expected != issue.rev => Err(PensaError::RevisionMismatch {
+                expected,
+                current: Box::new(issue),
+            }),
+            _ => Ok(issue),
+        }
+    }
+
+    pub fn update_issue(
+        &self,
+        id: &str,
+        fields: &UpdateFields,
+        actor: &str,
+    ) -> Result<Issue, PensaError> {
+        let before = self.check_rev(id, fields.expect_rev)?;
+        if fields.status == Some(Status::Closed) {
+            self.check_close_allowed(&before)?;
+        }
+
+        let mut set_clauses = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        let mut changed = serde_json::Map::new();
+        // The text an edit replaces, so `issue_text_at` can read back past it.
+        let mut previous = serde_json::Map::new();
+        if fields.title.is_some() {
+            previous.insert("title".into(), before.title.clone().into());
+        }
+        if fields.description.is_some() {
+            previous.insert("description".into(), before.description.clone().into());
+        }
+
+        if let Some(title) = &fields.title {
+            set_clauses.push("title = ?");
+            values.push(Value::Text(title.clone()));
+            changed.insert("title".into(), serde_json::Value::String(title.clone()));
+        }
+        if let Some(description) = &fields.description {
+            set_clauses.push("description = ?");
+            values.push(Value::Text(description.clone()));
+            changed.insert(
+                "description".into(),
+                serde_json::Value::String(description.clone()),
+            );
+        }
+        if let Some(priority) = &fields.priority {
+            set_clauses.push("priority = ?");
+            values.push(Value::Text(priority.as_str().to_string()));
+            changed.insert(
+                "priority".into(),
+                serde_json::Value::String(priority.as_str().to_string()),
+            );
+        }
+        if let Some(status) = &fields.status {
+            set_clauses.push("status = ?");
+            values.push(Value::Text(status.as_str().to_string()));
+            changed.insert(
+                "status".into(),
+                serde_json::Value::String(status.as_str().to_string()),
+            );
+        }
+        if let Some(assignee) = &fields.assignee {
+            set_clauses.push("assignee = ?");
+            if assignee.is_empty() {
+                values.push(Value::Null);
+            } else {
+                values.push(Value::Text(assignee.clone()));
+            }
+            changed.insert(
+                "assignee".into(),
+                serde_json::Value::String(assignee.clone()),
+            );
+        }
+        if let Some(spec) = &fields.spec {
+            set_clauses.push("spec = ?");
+            values.push(Value::Text(spec.clone()));
+            changed.insert("spec".into(), serde_json::Value::String(spec.clone()));
+        }
+        if let Some(fixes) = &fields.fixes {
+            set_clauses.push("fixes = ?");
+            values.push(Value::Text(fixes.clone()));
+            changed.insert("fixes".into(), serde_json::Value::String(fixes.clone()));
+        }
+
+        let ts = now();
+        set_clauses.push("updated_at = ?");
+        values.push(Value::Text(ts.clone()));
+
+        values.push(Value::Text(id.to_string()));
+
+        // The revision is re-checked in the write itself: `check_rev` above
+        // only reports early, and another connection may update in between.
+        let mut sql = format!("UPDATE issues SET {} WHERE id = ?", set_clauses.join(", "));
+        if let Some(expected) = fields.expect_rev {
+            sql.push_str(" AND rev = ?");
+            values.push(Value::Integer(expected));
+        }
+
+        let updated = self
+            .conn
+            .execute(&sql, rusqlite::params_from_iter(values))
+            .map_err(|e| PensaError::Internal(format!("failed to update issue: {e}")))?;
+        if updated == 0 {
+            return Err(PensaError::RevisionMismatch {
+                expected: fields.expect_rev.unwrap_or(before.rev),
+                current: Box::new(self.get_issue_only(id)?),
+            });
+        }
+
+        if !previous.is_empty() {
+            changed.insert("previous".into(), serde_json::Value::Object(previous));
+        }
+        let detail = serde_json::Value::Object(changed).to_string();
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![id, "updated", actor, detail, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log update event: {e}")))?;
+
+        if fields.description.is_some() {
+            self.refresh_refs(id)?;
+        }
+        self.get_issue_only(id)
+    }
+
+    pub fn check_item(&self, id: &str, index: usize, actor: &str) -> Result<Issue, PensaError> {
+        let issue = self.get_issue_only(id)?;
+        let description = checklist::toggle(issue.description.as_deref().unwrap_or(""), index)?;
+        self.update_issue(
+            id,
+            &UpdateFields {
+                description: Some(description),
+                ..Default::default()
+            },
+            actor,
+        )
+    }
+
+    pub fn list_issues(&self, filters: &ListFilters) -> Result<Vec<Issue>, PensaError> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(status) = &filters.status {
+            conditions.push("status = ?");
+            values.push(Value::Text(status.as_str().to_string()));
+        }
+        if let Some(priority) = &filters.priority {
+            conditions.push("priority = ?");
+            values.push(Value::Text(priority.as_str().to_string()));
+        }
+        if let Some(assignee) = &filters.assignee {
+            conditions.push(ASSIGNEE_CONDITION);
+            let identity = self.resolve_actor(assignee)?;
+            values.extend([Value::Text(identity.clone()), Value::Text(identity)]);
+        }
+        if let Some(issue_type) = &filters.issue_type {
+            conditions.push("issue_type = ?");
+            values.push(Value::Text(issue_type.as_str().to_string()));
+        }
+        if let Some(spec) = &filters.spec {
+            conditions.push("spec = ?");
+            values.push(Value::Text(spec.clone()));
+        }
+        if let Some(resolution) = &filters.resolution {
+            conditions.push("resolution = ?");
+            values.push(Value::Text(resolution.as_str().to_string()));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let order_clause = order_clause(filters.sort.as_deref().unwrap_or("priority"))?;
+
+        let limit_clause = filters
+            .limit
+            .map(|n| format!("LIMIT {n}"))
+            .unwrap_or_default();
+
+        let sql = format!("SELECT * FROM issues {where_clause} {order_clause} {limit_clause}");
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare list query: {e}")))?;
+        let issues = stmt
+            .query_map(rusqlite::params_from_iter(&values), issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to list issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read issues: {e}")))?;
+
+        Ok(issues)
+    }
+
+    pub fn ready_issues(&self, filters: &ListFilters) -> Result<Vec<Issue>, PensaError> {
+        let mut conditions = vec![
+            "status = 'open'".to_string(),
+            "(issue_type != 'bug' OR id NOT IN (SELECT fixes FROM issues WHERE fixes IS NOT NULL AND status != 'closed') OR id IN (SELECT issue_id FROM events WHERE event_type = 'reopened'))".to_string(),
+            "id NOT IN (SELECT d.issue_id FROM deps d JOIN issues i ON d.depends_on_id = i.id WHERE i.status != 'closed')".to_string(),
+            "id NOT IN (SELECT issue_id FROM external_deps WHERE resolved_at IS NULL)".to_string(),
+        ];
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(priority) = &filters.priority {
+            conditions.push("priority = ?".to_string());
+            values.push(Value::Text(priority.as_str().to_string()));
+        }
+        if let Some(assignee) = &filters.assignee {
+            conditions.push(ASSIGNEE_CONDITION.to_string());
+            let identity = self.resolve_actor(assignee)?;
+            values.extend([Value::Text(identity.clone()), Value::Text(identity)]);
+        }
+        if let Some(issue_type) = &filters.issue_type {
+            conditions.push("issue_type = ?".to_string());
+            values.push(Value::Text(issue_type.as_str().to_string()));
+        }
+        if let Some(spec) = &filters.spec {
+            conditions.push("spec = ?".to_string());
+            values.push(Value::Text(spec.clone()));
+        }
+
+        let where_clause = format!("WHERE {}", conditions.join(" AND "));
+        let limit_clause = filters
+            .limit
+            .map(|n| format!("LIMIT {n}"))
+            .unwrap_or_default();
+
+        let sql = format!(
+            "SELECT * FROM issues {where_clause} ORDER BY priority ASC, created_at ASC {limit_clause}"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare ready query: {e}")))?;
+        let issues = stmt
+            .query_map(rusqlite::params_from_iter(&values), issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query ready issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read ready issues: {e}")))?;
+
+        Ok(issues)
+    }
+
+    pub fn explain_ready(
+        &self,
+        id: &str,
+        filters: &ListFilters,
+    ) -> Result<ReadyExplanation, PensaError> {
+        let issue = self.get_issue_only(id)?;
+        let mut reasons = self.exclusion_reasons(&issue, filters)?;
+        if reasons.is_empty()
+            && let Some(limit) = filters.limit
+            && !self.ready_issues(filters)?.iter().any(|i| i.id == issue.id)
+        {
+            reasons.push(ExclusionReason::PastLimit { limit });
+        }
+        Ok(ready_explanation(issue, reasons))
+    }
+
+    pub fn explain_not_ready(
+        &self,
+        filters: &ListFilters,
+    ) -> Result<Vec<ReadyExplanation>, PensaError> {
+        let ready: HashSet<String> = self
+            .ready_issues(filters)?
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        let mut explanations = Vec::new();
+        for issue in self.list_issues(&ListFilters::default())? {
+            if issue.status == Status::Closed || ready.contains(&issue.id) {
+                continue;
+            }
+            let mut reasons = self.exclusion_reasons(&issue, filters)?;
+            if reasons.is_empty() {
+                reasons.push(ExclusionReason::PastLimit {
+                    limit: filters.limit.unwrap_or_default(),
+                });
+            }
+            explanations.push(ready_explanation(issue, reasons));
+        }
+        Ok(explanations)
+    }
+
+    fn exclusion_reasons(
+        &self,
+        issue: &Issue,
+        filters: &ListFilters,
+    ) -> Result<Vec<ExclusionReason>, PensaError> {
+        let mut reasons = Vec::new();
+        match issue.status {
+            Status::Closed => reasons.push(ExclusionReason::Closed),
+            Status::InProgress => reasons.push(ExclusionReason::Claimed {
+                assignee: issue.assignee.clone(),
+            }),
+            Status::Open => {}
+        }
+        let mut blockers = self.issue_ids(
+            "SELECT d.depends_on_id FROM deps d JOIN issues i ON d.depends_on_id = i.id
+             WHERE d.issue_id = ?1 AND i.status != 'closed' ORDER BY d.depends_on_id",
+            &issue.id,
+        )?;
+        blockers.extend(self.issue_ids(
+            "SELECT target FROM external_deps
+             WHERE issue_id = ?1 AND resolved_at IS NULL ORDER BY target",
+            &issue.id,
+        )?);
+        if !blockers.is_empty() {
+            reasons.push(ExclusionReason::BlockedBy { ids: blockers });
+        }
+        if issue.issue_type == IssueType::Bug
+            && self
+                .issue_ids(
+                    "SELECT issue_id FROM events WHERE issue_id = ?1 AND event_type = 'reopened'",
+                    &issue.id,
+                )?
+                .is_empty()
+        {
+            let fixes = self.issue_ids(
+                "SELECT id FROM issues WHERE fixes = ?1 AND status != 'closed' ORDER BY id",
+                &issue.id,
+            )?;
+            if !fixes.is_empty() {
+                reasons.push(ExclusionReason::FixInProgress { ids: fixes });
+            }
+        }
+        let checks = [
+            (
+                "priority",
+                filters.priority.map(|p| p.as_str().to_string()),
+                Some(issue.priority.as_str().to_string()),
+            ),
+            (
+                "assignee",
+                filters
+                    .assignee
+                    .as_deref()
+                    .map(|a| self.resolve_actor(a))
+                    .transpose()?,
+                issue
+                    .assignee
+                    .as_deref()
+                    .map(|a| self.resolve_actor(a))
+                    .transpose()?,
+            ),
+            (
+                "issue_type",
+                filters.issue_type.map(|t| t.as_str().to_string()),
+                Some(issue.issue_type.as_str().to_string()),
+            ),
+            ("spec", filters.spec.clone(), issue.spec.clone()),
+        ];
+        for (filter, wanted, actual) in checks {
+            if let Some(wanted) = wanted
+                && actual.as_ref() != Some(&wanted)
+            {
+                reasons.push(ExclusionReason::Filtered {
+                    filter: filter.to_string(),
+                    wanted,
+                    actual,
+                });
+            }
+        }
+        Ok(reasons)
+    }
+
+    fn issue_ids(&self, sql: &str, id: &str) -> Result<Vec<String>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare query: {e}")))?;
+        let ids = stmt
+            .query_map(rusqlite::params![id], |row| row.get(0))
+            .map_err(|e| PensaError::Internal(format!("failed to query issues: {e}")))?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read issues: {e}")))?;
+        Ok(ids)
+    }
+
+    pub fn blocked_issues(&self) -> Result<Vec<Issue>, PensaError> {
+        let sql = "SELECT * FROM issues
+                    WHERE id IN (SELECT d.issue_id FROM deps d
+                                 JOIN issues blocker ON d.depends_on_id = blocker.id
+                                 WHERE blocker.status != 'closed')
+                       OR id IN (SELECT issue_id FROM external_deps WHERE resolved_at IS NULL)
+                    ORDER BY priority ASC, created_at ASC";
+
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare blocked query: {e}")))?;
+        let issues = stmt
+            .query_map([], issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query blocked issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read blocked issues: {e}")))?;
+
+        Ok(issues)
+    }
+
+    pub fn search_issues(&self, query: &str) -> Result<Vec<Issue>, PensaError> {
+        let pattern = format!("%{query}%");
+        let sql = "SELECT * FROM issues WHERE title LIKE ?1 OR description LIKE ?1 ORDER BY priority ASC, created_at ASC";
+
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare search query: {e}")))?;
+        let issues = stmt
+            .query_map(rusqlite::params![pattern], issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to search issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read search results: {e}")))?;
+
+        Ok(issues)
+    }
+
+    pub fn count_issues(
+        &self,
+        group_by: &[&str],
+        resolution: Option<Resolution>,
+    ) -> Result<serde_json::Value, PensaError> {
+        let (filter_clause, filter_values): (&str, Vec<Value>) = match resolution {
+            Some(r) => (
+                "WHERE issues.resolution = ?",
+                vec![Value::Text(r.as_str().to_string())],
+            ),
+            None => ("", vec![]),
+        };
+
+        if group_by.is_empty() {
+            let sql = if resolution.is_some() {
+                format!("SELECT COUNT(*) FROM issues {filter_clause}")
+            } else {
+                "SELECT COUNT(*) FROM issues WHERE status != 'closed'".to_string()
+            };
+            let count: i64 = self
+                .conn
+                .query_row(&sql, rusqlite::params_from_iter(&filter_values), |row| {
+                    row.get(0)
+                })
+                .map_err(|e| PensaError::Internal(format!("failed to count issues: {e}")))?;
+
+            return Ok(serde_json::to_value(CountResult { count }).unwrap());
+        }
+
+        let valid_fields: &[&str] = &[
+            "status",
+            "priority",
+            "issue_type",
+            "assignee",
+            "spec",
+            "label",
+            "resolution",
+        ];
+        for field in group_by {
+            if !valid_fields.contains(field) {
+                return Err(PensaError::Internal(format!(
+                    "invalid group_by field: {field}"
+                )));
+            }
+        }
+
+        let columns: Vec<&str> = group_by
+            .iter()
+            .map(|f| if *f == "label" { "l.label" } else { f })
+            .collect();
+        let join_clause = if group_by.contains(&"label") {
+            "LEFT JOIN labels l ON l.issue_id = issues.id"
+        } else {
+            ""
+        };
+        let group_clause = columns.join(", ");
+        let sql = format!(
+            "SELECT {group_clause}, COUNT(*) as cnt FROM issues {join_clause} {filter_clause} GROUP BY {group_clause} ORDER BY {group_clause}"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare count query: {e}")))?;
+
+        let groups = stmt
+            .query_map(rusqlite::params_from_iter(&filter_values), |row| {
+                let mut key_parts = Vec::new();
+                for i in 0..group_by.len() {
+                    let val: Option<String> = row.get(i)?;
+                    key_parts.push(val.unwrap_or_else(|| "-".to_string()));
+                }
+                let count: i64 = row.get(group_by.len())?;
+                Ok(CountGroup {
+                    key: key_parts.join("/"),
+                    count,
+                })
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to count issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read count results: {e}")))?;
+
+        // An issue with several labels lands in several label groups, so the
+        // total counts issues rather than summing the groups.
+        let total: i64 = self
+            .conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM issues {filter_clause}"),
+                rusqlite::params_from_iter(&filter_values),
+                |row| row.get(0),
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to count issues: {e}")))?;
+
+        Ok(serde_json::to_value(GroupedCountResult { total, groups }).unwrap())
+    }
+
+    pub fn project_status(&self) -> Result<Vec<StatusEntry>, PensaError> {
+        let sql = "SELECT issue_type,
+                          SUM(CASE WHEN status = 'open' THEN 1 ELSE 0 END) as open_count,
+                          SUM(CASE WHEN status = 'in_progress' THEN 1 ELSE 0 END) as in_progress_count,
+                          SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END) as closed_count
+                   FROM issues
+                   GROUP BY issue_type
+                   ORDER BY issue_type";
+
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare status query: {e}")))?;
+
+        let entries = stmt
+            .query_map([], |row| {
+                let issue_type_str: String = row.get(0)?;
+                Ok(StatusEntry {
+                    issue_type: issue_type_str.parse().unwrap(),
+                    open: row.get(1)?,
+                    in_progress: row.get(2)?,
+                    closed: row.get(3)?,
+                })
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to query project status: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read project status: {e}")))?;
+
+        Ok(entries)
+    }
+
+    pub fn workload(&self, max_active: i64) -> Result<WorkloadReport, PensaError> {
+        let sql = "SELECT COALESCE(a.alias_of, i.assignee) AS identity,
+                          SUM(CASE WHEN i.status = 'in_progress' THEN 1 ELSE 0 END),
+                          SUM(CASE WHEN i.status = 'open' THEN 1 ELSE 0 END),
+                          SUM(CASE WHEN i.priority IN ('p0', 'p1') THEN 1 ELSE 0 END),
+                          (SELECT display_name FROM actors WHERE name = COALESCE(a.alias_of, i.assignee))
+                   FROM issues i
+                   LEFT JOIN actors a ON a.name = i.assignee
+                   WHERE i.status != 'closed' AND i.assignee IS NOT NULL AND i.assignee != ''
+                   GROUP BY identity";
+
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare workload query: {e}")))?;
+
+        let mut assignees = stmt
+            .query_map([], |row| {
+                let in_progress: i64 = row.get(1)?;
+                let open: i64 = row.get(2)?;
+                Ok(WorkloadEntry {
+                    assignee: row.get(0)?,
+                    display_name: row.get(4)?,
+                    in_progress,
+                    open,
+                    active: in_progress + open,
+                    urgent: row.get(3)?,
+                    overloaded: in_progress + open > max_active,
+                })
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to query workload: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read workload: {e}")))?;
+        assignees.sort_by(|a, b| {
+            b.active
+                .cmp(&a.active)
+                .then(b.in_progress.cmp(&a.in_progress))
+                .then(a.assignee.cmp(&b.assignee))
+        });
+
+        let unassigned: i64 = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM issues
+                 WHERE status = 'open' AND (assignee IS NULL OR assignee = '')",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to count unassigned: {e}")))?;
+
+        Ok(WorkloadReport {
+            max_active,
+            unassigned,
+            assignees,
+        })
+    }
+
+    pub fn spec_status(&self) -> Result<Vec<SpecStatusEntry>, PensaError> {
+        self.query_spec_status(None)
+    }
+
+    pub fn spec_detail(&self, spec: &str) -> Result<SpecDetail, PensaError> {
+        let summary = self
+            .query_spec_status(Some(spec))?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| SpecStatusEntry {
+                spec: spec.to_string(),
+                open: 0,
+                in_progress: 0,
+                closed: 0,
+                open_p0: 0,
+                open_p1: 0,
+                blocked: 0,
+            });
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT * FROM issues WHERE spec = ?1 AND status != 'closed'
+                 ORDER BY priority ASC, created_at ASC",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare spec query: {e}")))?;
+        let outstanding = stmt
+            .query_map(rusqlite::params![spec], issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query spec issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read spec issues: {e}")))?;
+
+        Ok(SpecDetail {
+            summary,
+            outstanding,
+        })
+    }
+
+    fn query_spec_status(&self, spec: Option<&str>) -> Result<Vec<SpecStatusEntry>, PensaError> {
+        let sql = "SELECT spec,
+                          SUM(CASE WHEN status = 'open' THEN 1 ELSE 0 END),
+                          SUM(CASE WHEN status = 'in_progress' THEN 1 ELSE 0 END),
+                          SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END),
+                          SUM(CASE WHEN status != 'closed' AND priority = 'p0' THEN 1 ELSE 0 END),
+                          SUM(CASE WHEN status != 'closed' AND priority = 'p1' THEN 1 ELSE 0 END),
+                          SUM(CASE WHEN status != 'closed' AND id IN (
+                              SELECT d.issue_id FROM deps d
+                              JOIN issues blocker ON d.depends_on_id = blocker.id
+                              WHERE blocker.status != 'closed'
+                          ) THEN 1 ELSE 0 END)
+                   FROM issues
+                   WHERE spec IS NOT NULL AND (?1 IS NULL OR spec = ?1)
+                   GROUP BY spec
+                   ORDER BY spec";
+
+        let mut stmt = self.conn.prepare(sql).map_err(|e| {
+            PensaError::Internal(format!("failed to prepare spec status query: {e}"))
+        })?;
+
+        let entries = stmt
+            .query_map(rusqlite::params![spec], |row| {
+                Ok(SpecStatusEntry {
+                    spec: row.get(0)?,
+                    open: row.get(1)?,
+                    in_progress: row.get(2)?,
+                    closed: row.get(3)?,
+                    open_p0: row.get(4)?,
+                    open_p1: row.get(5)?,
+                    blocked: row.get(6)?,
+                })
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to query spec status: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read spec status: {e}")))?;
+
+        Ok(entries)
+    }
+
+    pub fn add_dep(&self, child_id: &str, parent_id: &str, actor: &str) -> Result<(), PensaError> {
+        self.get_issue_only(child_id)?;
+        if let Some(kind) = ExternalKind::classify(parent_id) {
+            self.insert_external_dep(child_id, parent_id, kind)?;
+            return self.log_dep_event(
+                child_id,
+                "dep_added",
+                actor,
+                &format!("depends on {parent_id}"),
+            );
+        }
+        self.get_issue_only(parent_id)?;
+
+        if self.has_cycle(child_id, parent_id)? {
+            return Err(PensaError::CycleDetected);
+        }
+
+        self.conn
+            .execute(
+                "INSERT INTO deps (issue_id, depends_on_id) VALUES (?1, ?2)",
+                rusqlite::params![child_id, parent_id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add dep: {e}")))?;
+
+        self.log_dep_event(
+            child_id,
+            "dep_added",
+            actor,
+            &format!("depends on {parent_id}"),
+        )
+    }
+
+    fn log_dep_event(
+        &self,
+        issue_id: &str,
+        event_type: &str,
+        actor: &str,
+        detail: &str,
+    ) -> Result<(), PensaError> {
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![issue_id, event_type, actor, detail, now()],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log {event_type} event: {e}")))?;
+        Ok(())
+    }
+
+    pub fn remove_dep(
+        &self,
+        child_id: &str,
+        parent_id: &str,
+        actor: &str,
+    ) -> Result<(), PensaError> {
+        let sql = if ExternalKind::classify(parent_id).is_some() {
+            "DELETE FROM external_deps WHERE issue_id = ?1 AND target = ?2"
+        } else {
+            "DELETE FROM deps WHERE issue_id = ?1 AND depends_on_id = ?2"
+        };
+        let rows = self
+            .conn
+            .execute(sql, rusqlite::params![child_id, parent_id])
+            .map_err(|e| PensaError::Internal(format!("failed to remove dep: {e}")))?;
+
+        if rows == 0 {
+            return Err(PensaError::NotFound(format!(
+                "dep {child_id} -> {parent_id}"
+            )));
+        }
+
+        self.log_dep_event(
+            child_id,
+            "dep_removed",
+            actor,
+            &format!("no longer depends on {parent_id}"),
+        )
+    }
+
+    pub fn resolve_external_dep(
+        &self,
+        issue_id: &str,
+        target: &str,
+        resolved: bool,
+        actor: &str,
+    ) -> Result<ExternalDep, PensaError> {
+        let rows = self
+            .conn
+            .execute(
+                "UPDATE external_deps SET resolved_at = ?3 WHERE issue_id = ?1 AND target = ?2",
+                rusqlite::params![issue_id, target, resolved.then(now)],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to resolve dep: {e}")))?;
+        if rows == 0 {
+            return Err(PensaError::NotFound(format!("dep {issue_id} -> {target}")));
+        }
+        let detail = if resolved {
+            format!("{target} resolved")
+        } else {
+            format!("{target} unresolved")
+        };
+        self.log_dep_event(issue_id, "dep_resolved", actor, &detail)?;
+        self.conn
+            .query_row(
+                "SELECT * FROM external_deps WHERE issue_id = ?1 AND target = ?2",
+                rusqlite::params![issue_id, target],
+                external_dep_from_row,
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to get dep: {e}")))
+    }
+
+    pub fn list_external_deps(&self, issue_id: &str) -> Result<Vec<ExternalDep>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM external_deps WHERE issue_id = ?1 ORDER BY created_at, target")
+            .map_err(|e| {
+                PensaError::Internal(format!("failed to prepare external deps query: {e}"))
+            })?;
+        let deps = stmt
+            .query_map(rusqlite::params![issue_id], external_dep_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query external deps: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read external deps: {e}")))?;
+        Ok(deps)
+    }
+
+    pub fn list_deps(&self, id: &str) -> Result<Vec<Issue>, PensaError> {
+        self.get_issue_only(id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT i.* FROM issues i
+                 JOIN deps d ON d.depends_on_id = i.id
+                 WHERE d.issue_id = ?1",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare deps query: {e}")))?;
+
+        let deps = stmt
+            .query_map(rusqlite::params![id], issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query deps: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read deps: {e}")))?;
+
+        Ok(deps)
+    }
+
+    pub fn dep_tree(&self, id: &str, direction: &str) -> Result<Vec<DepTreeNode>, PensaError> {
+        self.get_issue_only(id)?;
+
+        let sql = if direction == "up" {
+            // What blocks this issue? Follow deps WHERE issue_id=id upward
+            "WITH RECURSIVE tree(id, depth) AS (
+                SELECT depends_on_id, 1 FROM deps WHERE issue_id = ?1
+                UNION ALL
+                SELECT d.depends_on_id, t.depth + 1
+                FROM deps d JOIN tree t ON d.issue_id = t.id
+            )
+            SELECT i.id, i.title, i.status, i.priority, i.issue_type, t.depth
+            FROM tree t JOIN issues i ON t.id = i.id
+            ORDER BY t.depth ASC"
+        } else {
+            // What does this issue block? Follow deps WHERE depends_on_id=id downward
+            "WITH RECURSIVE tree(id, depth) AS (
+                SELECT issue_id, 1 FROM deps WHERE depends_on_id = ?1
+                UNION ALL
+                SELECT d.issue_id, t.depth + 1
+                FROM deps d JOIN tree t ON d.depends_on_id = t.id
+            )
+            SELECT i.id, i.title, i.status, i.priority, i.issue_type, t.depth
+            FROM tree t JOIN issues i ON t.id = i.id
+            ORDER BY t.depth ASC"
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| PensaError::Internal(format!("failed to prepare dep_tree query: {e}")))?;
+
+        let nodes = stmt
+            .query_map(rusqlite::params![id], |row| {
+                let status_str: String = row.get("status")?;
+                let priority_str: String = row.get("priority")?;
+                let issue_type_str: String = row.get("issue_type")?;
+                Ok(DepTreeNode {
+                    id: row.get("id")?,
+                    title: row.get("title")?,
+                    status: status_str.parse().unwrap(),
+                    priority: priority_str.parse().unwrap(),
+                    issue_type: issue_type_str.parse().unwrap(),
+                    depth: row.get("depth")?,
+                })
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to query dep_tree: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read dep_tree: {e}")))?;
+
+        Ok(nodes)
+    }
+
+    pub fn detect_cycles(&self) -> Result<Vec<Vec<String>>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT issue_id FROM deps")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare cycles query: {e}")))?;
+
+        let all_ids: Vec<String> = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| PensaError::Internal(format!("failed to query for cycles: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read cycle ids: {e}")))?;
+
+        let mut cycles = Vec::new();
+        let mut visited_global = std::collections::HashSet::new();
+
+        for start_id in &all_ids {
+            if visited_global.contains(start_id) {
+                continue;
+            }
+
+            let mut stack = vec![(start_id.clone(), vec![start_id.clone()])];
+            let mut visited_local = std::collections::HashSet::new();
+
+            while let Some((current, path)) = stack.pop() {
+                if !visited_local.insert(current.clone()) {
+                    continue;
+                }
+
+                let mut dep_stmt = self
+                    .conn
+                    .prepare("SELECT depends_on_id FROM deps WHERE issue_id = ?1")
+                    .map_err(|e| {
+                        PensaError::Internal(format!("failed to prepare dep lookup: {e}"))
+                    })?;
+
+                let parents: Vec<String> = dep_stmt
+                    .query_map(rusqlite::params![current], |row| row.get(0))
+                    .map_err(|e| PensaError::Internal(format!("failed to query deps: {e}")))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| PensaError::Internal(format!("failed to read deps: {e}")))?;
+
+                for parent in parents {
+                    if parent == *start_id && path.len() > 1 {
+                        let mut cycle = path.clone();
+                        cycle.push(parent);
+                        cycles.push(cycle);
+                    } else if !visited_local.contains(&parent) {
+                        let mut new_path = path.clone();
+                        new_path.push(parent.clone());
+                        stack.push((parent, new_path));
+                    }
+                }
+            }
+
+            visited_global.extend(visited_local);
+        }
+
+        Ok(cycles)
+    }
+
+    fn has_cycle(&self, child_id: &str, parent_id: &str) -> Result<bool, PensaError> {
+        // BFS from parent_id: if we can reach child_id, adding child->parent creates a cycle
+        let mut queue = std::collections::VecDeque::new();
+        let mut visited = std::collections::HashSet::new();
+        queue.push_back(parent_id.to_string());
+        visited.insert(parent_id.to_string());
+
+        while let Some(current) = queue.pop_front() {
+            if current == child_id {
+                return Ok(true);
+            }
+
+            let mut stmt = self
+                .conn
+                .prepare("SELECT depends_on_id FROM deps WHERE issue_id = ?1")
+                .map_err(|e| PensaError::Internal(format!("failed to check cycle: {e}")))?;
+
+            let parents: Vec<String> = stmt
+                .query_map(rusqlite::params![current], |row| row.get(0))
+                .map_err(|e| PensaError::Internal(format!("failed to query cycle check: {e}")))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PensaError::Internal(format!("failed to read cycle check: {e}")))?;
+
+            for p in parents {
+                if visited.insert(p.clone()) {
+                    queue.push_back(p);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub fn add_comment(
+        &self,
+        issue_id: &str,
+        actor: &str,
+        text: &str,
+    ) -> Result<Comment, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let id = generate_id();
+        let ts = now();
+
+        self.conn
+            .execute(
+                "INSERT INTO comments (id, issue_id, actor, text, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![id, issue_id, actor, text, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add comment: {e}")))?;
+
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![issue_id, "commented", actor, text, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log comment event: {e}")))?;
+
+        for name in find_mentions(text).iter().filter(|n| *n != actor) {
+            self.conn
+                .execute(
+                    "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![issue_id, "mentioned", actor, mention_detail(name, text), ts],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to log mention event: {e}")))?;
+        }
+
+        self.refresh_refs(issue_id)?;
+        Ok(Comment {
+            id,
+            issue_id: issue_id.to_string(),
+            actor: actor.to_string(),
+            text: text.to_string(),
+            created_at: parse_dt(&ts),
+        })
+    }
+
+    pub fn list_comments(&self, issue_id: &str) -> Result<Vec<Comment>, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM comments WHERE issue_id = ?1 ORDER BY created_at")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare comments query: {e}")))?;
+
+        let comments = stmt
+            .query_map(rusqlite::params![issue_id], comment_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query comments: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read comments: {e}")))?;
+
+        Ok(comments)
+    }
+
+    pub fn add_src_ref(
+        &self,
+        issue_id: &str,
+        path: &str,
+        reason: Option<&str>,
+        actor: &str,
+    ) -> Result<SrcRef, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let id = generate_id();
+        let ts = now();
+
+        self.conn
+            .execute(
+                "INSERT INTO src_refs (id, issue_id, path, reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![id, issue_id, path, reason, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add src_ref: {e}")))?;
+
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![issue_id, "src_ref_added", actor, path, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log src_ref event: {e}")))?;
+
+        Ok(SrcRef {
+            id,
+            issue_id: issue_id.to_string(),
+            path: path.to_string(),
+            reason: reason.map(|s| s.to_string()),
+            created_at: parse_dt(&ts),
+        })
+    }
+
+    pub fn list_src_refs(&self, issue_id: &str) -> Result<Vec<SrcRef>, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM src_refs WHERE issue_id = ?1 ORDER BY created_at")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare src_refs query: {e}")))?;
+
+        let refs = stmt
+            .query_map(rusqlite::params![issue_id], src_ref_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query src_refs: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read src_refs: {e}")))?;
+
+        Ok(refs)
+    }
+
+    pub fn remove_src_ref(&self, ref_id: &str, actor: &str) -> Result<(), PensaError> {
+        let (issue_id, path): (String, String) = self
+            .conn
+            .query_row(
+                "SELECT issue_id, path FROM src_refs WHERE id = ?1",
+                rusqlite::params![ref_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => {
+                    PensaError::NotFound(format!("src_ref {ref_id}"))
+                }
+                other => PensaError::Internal(format!("failed to find src_ref: {other}")),
+            })?;
+
+        self.conn
+            .execute(
+                "DELETE FROM src_refs WHERE id = ?1",
+                rusqlite::params![ref_id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to remove src_ref: {e}")))?;
+
+        let ts = now();
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![issue_id, "src_ref_removed", actor, path, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log src_ref removal event: {e}")))?;
+
+        Ok(())
+    }
+
+    pub fn add_doc_ref(
+        &self,
+        issue_id: &str,
+        path: &str,
+        reason: Option<&str>,
+        actor: &str,
+    ) -> Result<DocRef, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let id = generate_id();
+        let ts = now();
+
+        self.conn
+            .execute(
+                "INSERT INTO doc_refs (id, issue_id, path, reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![id, issue_id, path, reason, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add doc_ref: {e}")))?;
+
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![issue_id, "doc_ref_added", actor, path, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log doc_ref event: {e}")))?;
+
+        Ok(DocRef {
+            id,
+            issue_id: issue_id.to_string(),
+            path: path.to_string(),
+            reason: reason.map(|s| s.to_string()),
+            created_at: parse_dt(&ts),
+        })
+    }
+
+    pub fn list_doc_refs(&self, issue_id: &str) -> Result<Vec<DocRef>, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM doc_refs WHERE issue_id = ?1 ORDER BY created_at")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare doc_refs query: {e}")))?;
+
+        let refs = stmt
+            .query_map(rusqlite::params![issue_id], doc_ref_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query doc_refs: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read doc_refs: {e}")))?;
+
+        Ok(refs)
+    }
+
+    pub fn remove_doc_ref(&self, ref_id: &str, actor: &str) -> Result<(), PensaError> {
+        let (issue_id, path): (String, String) = self
+            .conn
+            .query_row(
+                "SELECT issue_id, path FROM doc_refs WHERE id = ?1",
+                rusqlite::params![ref_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => {
+                    PensaError::NotFound(format!("doc_ref {ref_id}"))
+                }
+                other => PensaError::Internal(format!("failed to find doc_ref: {other}")),
+            })?;
+
+        self.conn
+            .execute(
+                "DELETE FROM doc_refs WHERE id = ?1",
+                rusqlite::params![ref_id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to remove doc_ref: {e}")))?;
+
+        let ts = now();
+        self.conn
+            .execute(
+                "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![issue_id, "doc_ref_removed", actor, path, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to log doc_ref removal event: {e}")))?;
+
+        Ok(())
+    }
+
+    pub fn link_commit(
+        &self,
+        issue_id: &str,
+        sha: &str,
+        summary: Option<&str>,
+        actor: &str,
+    ) -> Result<CommitLink, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let ts = now();
+        let rows = self
+            .conn
+            .execute(
+                "INSERT OR IGNORE INTO commits (issue_id, sha, summary, created_at) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![issue_id, sha, summary, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to link commit: {e}")))?;
+
+        if rows > 0 {
+            self.conn
+                .execute(
+                    "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![issue_id, "commit_linked", actor, sha, ts],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to log commit link event: {e}")))?;
+        }
+
+        self.conn
+            .query_row(
+                "SELECT * FROM commits WHERE issue_id = ?1 AND sha = ?2",
+                rusqlite::params![issue_id, sha],
+                commit_link_from_row,
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to read commit link: {e}")))
+    }
+
+    pub fn list_commits(&self, issue_id: &str) -> Result<Vec<CommitLink>, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM commits WHERE issue_id = ?1 ORDER BY created_at, sha")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare commits query: {e}")))?;
+
+        let commits = stmt
+            .query_map(rusqlite::params![issue_id], commit_link_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query commits: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read commits: {e}")))?;
+
+        Ok(commits)
+    }
+
+    pub fn add_label(
+        &self,
+        issue_id: &str,
+        label: &str,
+        actor: &str,
+    ) -> Result<Vec<String>, PensaError> {
+        self.get_issue_only(issue_id)?;
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(PensaError::InvalidArgument(
+                "label must not be empty".to_string(),
+            ));
+        }
+
+        let ts = now();
+        let rows = self
+            .conn
+            .execute(
+                "INSERT OR IGNORE INTO labels (issue_id, label, created_at) VALUES (?1, ?2, ?3)",
+                rusqlite::params![issue_id, label, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add label: {e}")))?;
+
+        if rows > 0 {
+            self.conn
+                .execute(
+                    "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![issue_id, "labeled", actor, label, ts],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to log label event: {e}")))?;
+        }
+
+        self.list_labels(issue_id)
+    }
+
+    pub fn remove_label(
+        &self,
+        issue_id: &str,
+        label: &str,
+        actor: &str,
+    ) -> Result<Vec<String>, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let rows = self
+            .conn
+            .execute(
+                "DELETE FROM labels WHERE issue_id = ?1 AND label = ?2",
+                rusqlite::params![issue_id, label],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to remove label: {e}")))?;
+
+        if rows > 0 {
+            self.conn
+                .execute(
+                    "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![issue_id, "unlabeled", actor, label, now()],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to log label event: {e}")))?;
+        }
+
+        self.list_labels(issue_id)
+    }
+
+    pub fn list_labels(&self, issue_id: &str) -> Result<Vec<String>, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT label FROM labels WHERE issue_id = ?1 ORDER BY label")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare labels query: {e}")))?;
+
+        let labels = stmt
+            .query_map(rusqlite::params![issue_id], |row| row.get(0))
+            .map_err(|e| PensaError::Internal(format!("failed to query labels: {e}")))?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read labels: {e}")))?;
+
+        Ok(labels)
+    }
+
+    pub fn watch_issue(&self, issue_id: &str, actor: &str) -> Result<Vec<String>, PensaError> {
+        self.get_issue_only(issue_id)?;
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO watchers (issue_id, actor, since_event_id, created_at)
+                 VALUES (?1, ?2, (SELECT COALESCE(MAX(id), 0) FROM events), ?3)",
+                rusqlite::params![issue_id, actor, now()],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add watcher: {e}")))?;
+        self.list_watchers(issue_id)
+    }
+
+    pub fn unwatch_issue(&self, issue_id: &str, actor: &str) -> Result<Vec<String>, PensaError> {
+        self.get_issue_only(issue_id)?;
+        self.conn
+            .execute(
+                "DELETE FROM watchers WHERE issue_id = ?1 AND actor = ?2",
+                rusqlite::params![issue_id, actor],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to remove watcher: {e}")))?;
+        self.list_watchers(issue_id)
+    }
+
+    pub fn list_watchers(&self, issue_id: &str) -> Result<Vec<String>, PensaError> {
+        self.get_issue_only(issue_id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT actor FROM watchers WHERE issue_id = ?1 ORDER BY actor")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare watchers query: {e}")))?;
+
+        let watchers = stmt
+            .query_map(rusqlite::params![issue_id], |row| row.get(0))
+            .map_err(|e| PensaError::Internal(format!("failed to query watchers: {e}")))?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read watchers: {e}")))?;
+
+        Ok(watchers)
+    }
+
+    pub fn watched_issues(&self, actor: &str) -> Result<Vec<Issue>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT i.* FROM issues i
+                 JOIN watchers w ON w.issue_id = i.id
+                 WHERE w.actor = ?1
+                 ORDER BY w.created_at, i.id",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare watched query: {e}")))?;
+
+        let issues = stmt
+            .query_map(rusqlite::params![actor], issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query watched issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read watched issues: {e}")))?;
+
+        Ok(issues)
+    }
+
+    /// Events that arrived after `actor`'s last
+    /// [`mark_inbox_read`](Self::mark_inbox_read), oldest first: those on
+    /// issues they watch from when they started watching, and mentions of
+    /// them or of any alias of their identity anywhere. The actor's own
+    /// events are left out.
+    pub fn inbox(&self, actor: &str) -> Result<Inbox, PensaError> {
+        let last_read = self.inbox_cursor(actor)?;
+        let identity = self.resolve_actor(actor)?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT e.id, e.issue_id, e.event_type, e.actor, e.detail, e.created_at,
+                        i.title
+                 FROM events e
+                 LEFT JOIN watchers w ON w.issue_id = e.issue_id AND w.actor = ?1
+                 JOIN issues i ON i.id = e.issue_id
+                 WHERE e.id > ?2
+                   AND (e.actor IS NULL OR e.actor != ?1)
+                   AND (e.id > w.since_event_id
+                        OR (e.event_type = 'mentioned'
+                            AND EXISTS (SELECT 1 FROM actors a
+                                        WHERE (a.name = ?3 OR a.alias_of = ?3)
+                                          AND substr(e.detail, 1, length(a.name) + 2) = '@' || a.name || ':'
+                                        UNION ALL
+                                        SELECT 1 WHERE substr(e.detail, 1, length(?1) + 2) = '@' || ?1 || ':')))
+                 ORDER BY e.id",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare inbox query: {e}")))?;
+
+        let entries = stmt
+            .query_map(rusqlite::params![actor, last_read, identity], |row| {
+                let created_at_str: String = row.get("created_at")?;
+                Ok(InboxEntry {
+                    event: Event {
+                        id: row.get("id")?,
+                        issue_id: row.get("issue_id")?,
+                        event_type: row.get("event_type")?,
+                        actor: row.get("actor")?,
+                        detail: row.get("detail")?,
+                        created_at: parse_dt(&created_at_str),
+                    },
+                    title: row.get("title")?,
+                })
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to query inbox: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read inbox: {e}")))?;
+
+        Ok(Inbox {
+            actor: actor.to_string(),
+            last_read,
+            entries,
+        })
+    }
+
+    /// Moves `actor`'s inbox cursor forward to `up_to`. The cursor never
+    /// moves back, so a stale acknowledgement cannot resurface old events.
+    pub fn mark_inbox_read(&self, actor: &str, up_to: i64) -> Result<i64, PensaError> {
+        self.conn
+            .execute(
+                "INSERT INTO inbox_cursors (actor, last_event_id) VALUES (?1, ?2)
+                 ON CONFLICT(actor) DO UPDATE SET last_event_id = MAX(last_event_id, excluded.last_event_id)",
+                rusqlite::params![actor, up_to],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to update inbox cursor: {e}")))?;
+        self.inbox_cursor(actor)
+    }
+
+    fn inbox_cursor(&self, actor: &str) -> Result<i64, PensaError> {
+        self.conn
+            .query_row(
+                "SELECT COALESCE(MAX(last_event_id), 0) FROM inbox_cursors WHERE actor = ?1",
+                rusqlite::params![actor],
+                |row| row.get(0),
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to read inbox cursor: {e}")))
+    }
+
+    pub fn add_webhook(
+        &self,
+        url: &str,
+        events: &[String],
+        spec: Option<&str>,
+    ) -> Result<Webhook, PensaError> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(PensaError::InvalidArgument(format!(
+                "webhook url must be http(s): {url}"
+            )));
+        }
+        if let Some(unknown) = events.iter().find(|e| !EVENT_TYPES.contains(&e.as_str())) {
+            return Err(PensaError::InvalidArgument(format!(
+                "unknown event type '{unknown}' (expected one of: {})",
+                EVENT_TYPES.join(", ")
+            )));
+        }
+        let id = format!("wh-{}", &generate_id()[3..]);
+        self.conn
+            .execute(
+                "INSERT INTO webhooks (id, url, events, spec, secret, last_event_id, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, (SELECT COALESCE(MAX(id), 0) FROM events), ?6)",
+                rusqlite::params![id, url, events.join(","), spec, generate_secret(), now()],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add webhook: {e}")))?;
+        self.get_webhook(&id)
+    }
+
+    pub fn get_webhook(&self, id: &str) -> Result<Webhook, PensaError> {
+        self.conn
+            .query_row(
+                "SELECT * FROM webhooks WHERE id = ?1",
+                rusqlite::params![id],
+                webhook_from_row,
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => PensaError::NotFound(id.to_string()),
+                other => PensaError::Internal(format!("failed to get webhook: {other}")),
+            })
+    }
+
+    pub fn list_webhooks(&self) -> Result<Vec<Webhook>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM webhooks ORDER BY created_at, id")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare webhooks query: {e}")))?;
+
+        let webhooks = stmt
+            .query_map([], webhook_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query webhooks: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read webhooks: {e}")))?;
+
+        Ok(webhooks)
+    }
+
+    pub fn remove_webhook(&self, id: &str) -> Result<(), PensaError> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM webhooks WHERE id = ?1", rusqlite::params![id])
+            .map_err(|e| PensaError::Internal(format!("failed to remove webhook: {e}")))?;
+        if removed == 0 {
+            return Err(PensaError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn list_actors(&self) -> Result<Vec<Actor>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT * FROM actors
+                 ORDER BY COALESCE(alias_of, name), alias_of IS NOT NULL, name",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare actors query: {e}")))?;
+
+        let actors = stmt
+            .query_map([], actor_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query actors: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read actors: {e}")))?;
+
+        Ok(actors)
+    }
+
+    fn find_actor(&self, name: &str) -> Result<Option<Actor>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM actors WHERE name = ?1")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare actor query: {e}")))?;
+        let mut rows = stmt
+            .query_map(rusqlite::params![name], actor_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query actor: {e}")))?;
+        rows.next()
+            .transpose()
+            .map_err(|e| PensaError::Internal(format!("failed to read actor: {e}")))
+    }
+
+    fn known_actor(&self, name: &str) -> Result<Actor, PensaError> {
+        self.find_actor(name)?
+            .ok_or_else(|| PensaError::InvalidArgument(format!("unknown actor: {name}")))
+    }
+
+    pub fn resolve_actor(&self, name: &str) -> Result<String, PensaError> {
+        Ok(self
+            .find_actor(name)?
+            .and_then(|a| a.alias_of)
+            .unwrap_or_else(|| name.to_string()))
+    }
+
+    pub fn rename_actor(
+        &self,
+        name: &str,
+        display_name: Option<&str>,
+    ) -> Result<Actor, PensaError> {
+        let identity = self.resolve_actor(&self.known_actor(name)?.name)?;
+        let display_name = display_name.map(str::trim).filter(|d| !d.is_empty());
+        self.conn
+            .execute(
+                "UPDATE actors SET display_name = ?1 WHERE name = ?2",
+                rusqlite::params![display_name, identity],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to rename actor: {e}")))?;
+        self.known_actor(&identity)
+    }
+
+    /// Makes `alias` another name for the identity `target` stands for,
+    /// registering `alias` if it has not acted yet. Aliases never chain:
+    /// any that pointed at `alias` move to the same identity.
+    pub fn alias_actor(&self, alias: &str, target: &str) -> Result<Actor, PensaError> {
+        let identity = self.resolve_actor(&self.known_actor(target)?.name)?;
+        if identity == alias {
+            return Err(PensaError::InvalidArgument(format!(
+                "{alias} cannot be an alias of itself"
+            )));
+        }
+        let ts = now();
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO actors (name, first_seen, last_seen) VALUES (?1, ?2, ?2)",
+                rusqlite::params![alias, ts],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to add actor: {e}")))?;
+        self.conn
+            .execute(
+                "UPDATE actors SET alias_of = ?1 WHERE name = ?2 OR alias_of = ?2",
+                rusqlite::params![identity, alias],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to alias actor: {e}")))?;
+        self.known_actor(alias)
+    }
+
+    pub fn unalias_actor(&self, alias: &str) -> Result<Actor, PensaError> {
+        if self.known_actor(alias)?.alias_of.is_none() {
+            return Err(PensaError::InvalidArgument(format!(
+                "{alias} is not an alias"
+            )));
+        }
+        self.conn
+            .execute(
+                "UPDATE actors SET alias_of = NULL WHERE name = ?1",
+                rusqlite::params![alias],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to unalias actor: {e}")))?;
+        self.known_actor(alias)
+    }
+
+    pub fn create_snapshot(&self, label: Option<&str>) -> Result<Snapshot, PensaError> {
+        let issues = self.list_issues(&ListFilters::default())?;
+        let json = serde_json::to_string(&issues)
+            .map_err(|e| PensaError::Internal(format!("failed to encode snapshot: {e}")))?;
+        let id = format!("sn-{}", &generate_id()[3..]);
+        self.conn
+            .execute(
+                "INSERT INTO snapshots (id, label, issue_count, issues, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![id, label, issues.len(), json, now()],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to create snapshot: {e}")))?;
+        Ok(self.load_snapshot(&id)?.0)
+    }
+
+    pub fn list_snapshots(&self) -> Result<Vec<Snapshot>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM snapshots ORDER BY created_at, rowid")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare snapshots query: {e}")))?;
+        let snapshots = stmt
+            .query_map([], snapshot_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query snapshots: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read snapshots: {e}")))?;
+        Ok(snapshots)
+    }
+
+    /// A snapshot and its issues, by id or by label. A label used more than
+    /// once means the newest snapshot with it.
+    fn load_snapshot(&self, key: &str) -> Result<(Snapshot, Vec<Issue>), PensaError> {
+        let (snapshot, json) = self
+            .conn
+            .query_row(
+                "SELECT * FROM snapshots WHERE id = ?1 OR label = ?1
+                 ORDER BY id = ?1 DESC, created_at DESC, rowid DESC LIMIT 1",
+                rusqlite::params![key],
+                |row| Ok((snapshot_from_row(row)?, row.get::<_, String>("issues")?)),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => PensaError::NotFound(key.to_string()),
+                other => PensaError::Internal(format!("failed to get snapshot: {other}")),
+            })?;
+        let issues = serde_json::from_str(&json)
+            .map_err(|e| PensaError::Internal(format!("failed to decode snapshot: {e}")))?;
+        Ok((snapshot, issues))
+    }
+
+    pub fn diff_snapshots(&self, from: &str, to: Option<&str>) -> Result<SnapshotDiff, PensaError> {
+        let (from, before) = self.load_snapshot(from)?;
+        let (to, after) = match to {
+            Some(to) => {
+                let (snapshot, issues) = self.load_snapshot(to)?;
+                (snapshot.id, issues)
+            }
+            None => (
+                "now".to_string(),
+                self.list_issues(&ListFilters::default())?,
+            ),
+        };
+        Ok(diff_issues(from.id, to, &before, &after))
+    }
+
+    pub fn diff_since(&self, since: DateTime<Utc>) -> Result<SnapshotDiff, PensaError> {
+        let cutoff = since.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let id: String = self
+            .conn
+            .query_row(
+                "SELECT id FROM snapshots WHERE created_at <= ?1
+                 ORDER BY created_at DESC, rowid DESC LIMIT 1",
+                rusqlite::params![cutoff],
+                |row| row.get(0),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => PensaError::InvalidArgument(format!(
+                    "no snapshot taken at or before {cutoff}; take one with `pn snapshot create`"
+                )),
+                other => PensaError::Internal(format!("failed to find snapshot: {other}")),
+            })?;
+        self.diff_snapshots(&id, None)
+    }
+
+    pub fn pending_webhook_events(
+        &self,
+        webhook: &Webhook,
+        limit: usize,
+    ) -> Result<Vec<(Event, Issue)>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT e.id AS event_id, e.event_type, e.actor, e.detail,
+                        e.created_at AS event_created_at, i.*
+                 FROM events e
+                 JOIN issues i ON i.id = e.issue_id
+                 WHERE e.id > ?1
+                   AND (?2 = '' OR instr(',' || ?2 || ',', ',' || e.event_type || ',') > 0)
+                   AND (?3 IS NULL OR i.spec = ?3)
+                 ORDER BY e.id
+                 LIMIT ?4",
+            )
+            .map_err(|e| {
+                PensaError::Internal(format!("failed to prepare webhook events query: {e}"))
+            })?;
+
+        let pending = stmt
+            .query_map(
+                rusqlite::params![
+                    webhook.last_event_id,
+                    webhook.events.join(","),
+                    webhook.spec,
+                    limit as i64
+                ],
+                |row| {
+                    let created_at_str: String = row.get("event_created_at")?;
+                    let issue = issue_from_row(row)?;
+                    let event = Event {
+                        id: row.get("event_id")?,
+                        issue_id: issue.id.clone(),
+                        event_type: row.get("event_type")?,
+                        actor: row.get("actor")?,
+                        detail: row.get("detail")?,
+                        created_at: parse_dt(&created_at_str),
+                    };
+                    Ok((event, issue))
+                },
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to query webhook events: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read webhook events: {e}")))?;
+
+        Ok(pending)
+    }
+
+    pub fn latest_event_id(&self) -> Result<i64, PensaError> {
+        self.conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM events", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to read latest event: {e}")))
+    }
+
+    pub fn mark_webhook_delivered(&self, id: &str, event_id: i64) -> Result<(), PensaError> {
+        self.conn
+            .execute(
+                "UPDATE webhooks SET last_event_id = MAX(last_event_id, ?2) WHERE id = ?1",
+                rusqlite::params![id, event_id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to update webhook cursor: {e}")))?;
+        Ok(())
+    }
+
+    fn refresh_refs(&self, issue_id: &str) -> Result<(), PensaError> {
+        let prefix = self.config()?.id_prefix().to_string();
+        let description: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT description FROM issues WHERE id = ?1",
+                rusqlite::params![issue_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to read description: {e}")))?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT text FROM comments WHERE issue_id = ?1")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare comments query: {e}")))?;
+        let comments = stmt
+            .query_map(rusqlite::params![issue_id], |row| row.get::<_, String>(0))
+            .map_err(|e| PensaError::Internal(format!("failed to query comments: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read comments: {e}")))?;
+
+        self.conn
+            .execute(
+                "DELETE FROM issue_refs WHERE issue_id = ?1",
+                rusqlite::params![issue_id],
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to clear refs: {e}")))?;
+
+        let sources = description
+            .iter()
+            .map(|d| ("description", d.as_str()))
+            .chain(comments.iter().map(|c| ("comment", c.as_str())));
+        for (source, text) in sources {
+            for ref_id in find_issue_ids(text, &prefix) {
+                if ref_id == issue_id {
+                    continue;
+                }
+                self.conn
+                    .execute(
+                        "INSERT OR IGNORE INTO issue_refs (issue_id, ref_id, source) VALUES (?1, ?2, ?3)",
+                        rusqlite::params![issue_id, ref_id, source],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to store ref: {e}")))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn rebuild_refs(&self) -> Result<(), PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM issues")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare issues query: {e}")))?;
+        let ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| PensaError::Internal(format!("failed to query issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read issues: {e}")))?;
+        for id in ids {
+            self.refresh_refs(&id)?;
+        }
+        Ok(())
+    }
+
+    pub fn list_refs(&self, issue_id: &str) -> Result<Vec<String>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT ref_id FROM issue_refs WHERE issue_id = ?1 ORDER BY ref_id")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare refs query: {e}")))?;
+        stmt.query_map(rusqlite::params![issue_id], |row| row.get(0))
+            .map_err(|e| PensaError::Internal(format!("failed to query refs: {e}")))?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read refs: {e}")))
+    }
+
+    pub fn referenced_by(&self, issue_id: &str) -> Result<Vec<Issue>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT i.* FROM issues i
+                 WHERE i.id IN (SELECT issue_id FROM issue_refs WHERE ref_id = ?1)
+                 ORDER BY i.id",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare refs query: {e}")))?;
+        stmt.query_map(rusqlite::params![issue_id], issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query refs: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read refs: {e}")))
+    }
+
+    pub fn fixed_by(&self, issue_id: &str) -> Result<Vec<Issue>, PensaError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM issues WHERE fixes = ?1 ORDER BY created_at, id")
+            .map_err(|e| PensaError::Internal(format!("failed to prepare fixes query: {e}")))?;
+        stmt.query_map(rusqlite::params![issue_id], issue_from_row)
+            .map_err(|e| PensaError::Internal(format!("failed to query fixes: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read fixes: {e}")))
+    }
+
+    pub fn promote_refs(
+        &self,
+        issue_id: &str,
+        actor: &str,
+    ) -> Result<PromoteRefsResult, PensaError> {
+        self.get_issue_only(issue_id)?;
+        let existing: Vec<String> = self
+            .list_deps(issue_id)?
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+
+        let mut result = PromoteRefsResult::default();
+        for ref_id in self.list_refs(issue_id)? {
+            let reason = if existing.contains(&ref_id) {
+                Some("already a dependency")
+            } else {
+                match self.add_dep(issue_id, &ref_id, actor) {
+                    Ok(()) => None,
+                    Err(PensaError::NotFound(_)) => Some("issue not found"),
+                    Err(PensaError::CycleDetected) => Some("would create a cycle"),
+                    Err(e) => return Err(e),
+                }
+            };
+            match reason {
+                None => result.added.push(ref_id),
+                Some(reason) => result.skipped.push(SkippedRef {
+                    id: ref_id,
+                    reason: reason.to_string(),
+                }),
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn issue_history(&self, id: &str) -> Result<Vec<Event>, PensaError> {
+        self.get_issue_only(id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, issue_id, event_type, actor, detail, created_at
+                 FROM events WHERE issue_id = ?1 ORDER BY created_at DESC, id DESC",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare history query: {e}")))?;
+
+        let events = stmt
+            .query_map(rusqlite::params![id], |row| {
+                let created_at_str: String = row.get("created_at")?;
+                Ok(Event {
+                    id: row.get("id")?,
+                    issue_id: row.get("issue_id")?,
+                    event_type: row.get("event_type")?,
+                    actor: row.get("actor")?,
+                    detail: row.get("detail")?,
+                    created_at: parse_dt(&created_at_str),
+                })
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to query history: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read history: {e}")))?;
+
+        Ok(events)
+    }
+
+    /// The issue's title and description as they read at `at`, rebuilt from
+    /// its `updated` events. Each edit records the text it replaced, so the
+    /// text from before the first edit is known too; edits made before
+    /// pensa kept it leave that unknown, which is an error.
+    pub fn issue_text_at(&self, id: &str, at: DateTime<Utc>) -> Result<IssueText, PensaError> {
+        let issue = self.get_issue_only(id)?;
+        if at < issue.created_at {
+            return Err(PensaError::InvalidArgument(format!(
+                "{id} was created after {}",
+                at.to_rfc3339()
+            )));
+        }
+        let edits: Vec<(Event, serde_json::Value)> = self
+            .issue_history(id)?
+            .into_iter()
+            .rev()
+            .filter(|e| e.event_type == "updated")
+            .filter_map(|e| {
+                let detail = serde_json::from_str(e.detail.as_deref()?).ok()?;
+                Some((e, detail))
+            })
+            .collect();
+
+        let mut text = IssueText {
+            id: issue.id,
+            at,
+            title: issue.title,
+            description: issue.description,
+            edited_at: None,
+            edited_by: None,
+        };
+        for field in ["title", "description"] {
+            let touching: Vec<&(Event, serde_json::Value)> = edits
+                .iter()
+                .filter(|(_, detail)| detail.get(field).is_some())
+                .collect();
+            let value = match touching.iter().rev().find(|(e, _)| e.created_at <= at) {
+                Some((event, detail)) => {
+                    if text.edited_at.is_none_or(|t| event.created_at > t) {
+                        text.edited_at = Some(event.created_at);
+                        text.edited_by = event.actor.clone();
+                    }
+                    detail[field].clone()
+                }
+                None => match touching.first() {
+                    None => continue,
+                    Some((event, detail)) => detail
+                        .get("previous")
+                        .and_then(|p| p.get(field))
+                        .cloned()
+                        .ok_or_else(|| {
+                            PensaError::InvalidArgument(format!(
+                                "{id}'s {field} before {} was not recorded",
+                                event.created_at.to_rfc3339()
+                            ))
+                        })?,
+                },
+            };
+            let value = value.as_str().map(String::from);
+            match field {
+                "title" => text.title = value.unwrap_or_default(),
+                _ => text.description = value,
+            }
+        }
+        Ok(text)
+    }
+
+    pub fn checkpoint(&self) -> Result<(), PensaError> {
+        self.conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| PensaError::Internal(format!("failed to checkpoint: {e}")))
+    }
+
+    pub fn export_jsonl(&self) -> Result<ExportImportResult, PensaError> {
+        let issues = self.list_issues(&ListFilters::default())?;
+        let sorted_issues = {
+            let mut v = issues;
+            v.sort_by_key(|a| a.created_at);
+            v
+        };
+
+        let mut deps: Vec<Dep> = {
+            let mut stmt = self
+                .conn
+                .prepare(
+                    "SELECT issue_id, depends_on_id FROM deps ORDER BY issue_id, depends_on_id",
+                )
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to query deps for export: {e}"))
+                })?;
+            stmt.query_map([], |row| {
+                Ok(Dep {
+                    issue_id: row.get(0)?,
+                    depends_on_id: row.get(1)?,
+                })
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to read deps for export: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to collect deps for export: {e}")))?
+        };
+        deps.sort_by(|a, b| {
+            a.issue_id
+                .cmp(&b.issue_id)
+                .then(a.depends_on_id.cmp(&b.depends_on_id))
+        });
+
+        let mut comments: Vec<Comment> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT * FROM comments ORDER BY created_at")
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to query comments for export: {e}"))
+                })?;
+            stmt.query_map([], comment_from_row)
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to read comments for export: {e}"))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to collect comments for export: {e}"))
+                })?
+        };
+        comments.sort_by_key(|a| a.created_at);
+
+        let mut src_refs: Vec<SrcRef> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT * FROM src_refs ORDER BY created_at")
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to query src_refs for export: {e}"))
+                })?;
+            stmt.query_map([], src_ref_from_row)
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to read src_refs for export: {e}"))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to collect src_refs for export: {e}"))
+                })?
+        };
+        src_refs.sort_by_key(|a| a.created_at);
+
+        let mut doc_refs: Vec<DocRef> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT * FROM doc_refs ORDER BY created_at")
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to query doc_refs for export: {e}"))
+                })?;
+            stmt.query_map([], doc_ref_from_row)
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to read doc_refs for export: {e}"))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to collect doc_refs for export: {e}"))
+                })?
+        };
+        doc_refs.sort_by_key(|a| a.created_at);
+
+        let commits: Vec<CommitLink> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT * FROM commits ORDER BY created_at, issue_id, sha")
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to query commits for export: {e}"))
+                })?;
+            stmt.query_map([], commit_link_from_row)
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to read commits for export: {e}"))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to collect commits for export: {e}"))
+                })?
+        };
+
+        let labels: Vec<Label> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT * FROM labels ORDER BY issue_id, label")
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to query labels for export: {e}"))
+                })?;
+            stmt.query_map([], label_from_row)
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to read labels for export: {e}"))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to collect labels for export: {e}"))
+                })?
+        };
+
+        let external_deps: Vec<ExternalDep> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT * FROM external_deps ORDER BY issue_id, target")
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to query external deps for export: {e}"))
+                })?;
+            stmt.query_map([], external_dep_from_row)
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to read external deps for export: {e}"))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to collect external deps for export: {e}"))
+                })?
+        };
+
+        let actors = self.list_actors()?;
+
+        let issues_path = self.pensa_dir.join("issues.jsonl");
+        let deps_path = self.pensa_dir.join("deps.jsonl");
+        let comments_path = self.pensa_dir.join("comments.jsonl");
+        let src_refs_path = self.pensa_dir.join("src_refs.jsonl");
+        let doc_refs_path = self.pensa_dir.join("doc_refs.jsonl");
+        let commits_path = self.pensa_dir.join("commits.jsonl");
+        let labels_path = self.pensa_dir.join("labels.jsonl");
+        let external_deps_path = self.pensa_dir.join("external_deps.jsonl");
+        let actors_path = self.pensa_dir.join("actors.jsonl");
+
+        let mut issues_content = String::new();
+        for issue in &sorted_issues {
+            issues_content.push_str(&serde_json::to_string(issue).unwrap());
+            issues_content.push('\n');
+        }
+        fs::write(&issues_path, &issues_content)
+            .map_err(|e| PensaError::Internal(format!("failed to write issues.jsonl: {e}")))?;
+
+        let mut deps_content = String::new();
+        for dep in &deps {
+            deps_content.push_str(&serde_json::to_string(dep).unwrap());
+            deps_content.push('\n');
+        }
+        fs::write(&deps_path, &deps_content)
+            .map_err(|e| PensaError::Internal(format!("failed to write deps.jsonl: {e}")))?;
+
+        let mut comments_content = String::new();
+        for comment in &comments {
+            comments_content.push_str(&serde_json::to_string(comment).unwrap());
+            comments_content.push('\n');
+        }
+        fs::write(&comments_path, &comments_content)
+            .map_err(|e| PensaError::Internal(format!("failed to write comments.jsonl: {e}")))?;
+
+        let mut src_refs_content = String::new();
+        for sr in &src_refs {
+            src_refs_content.push_str(&serde_json::to_string(sr).unwrap());
+            src_refs_content.push('\n');
+        }
+        fs::write(&src_refs_path, &src_refs_content)
+            .map_err(|e| PensaError::Internal(format!("failed to write src_refs.jsonl: {e}")))?;
+
+        let mut doc_refs_content = String::new();
+        for dr in &doc_refs {
+            doc_refs_content.push_str(&serde_json::to_string(dr).unwrap());
+            doc_refs_content.push('\n');
+        }
+        fs::write(&doc_refs_path, &doc_refs_content)
+            .map_err(|e| PensaError::Internal(format!("failed to write doc_refs.jsonl: {e}")))?;
+
+        let mut commits_content = String::new();
+        for c in &commits {
+            commits_content.push_str(&serde_json::to_string(c).unwrap());
+            commits_content.push('\n');
+        }
+        fs::write(&commits_path, &commits_content)
+            .map_err(|e| PensaError::Internal(format!("failed to write commits.jsonl: {e}")))?;
+
+        let mut labels_content = String::new();
+        for l in &labels {
+            labels_content.push_str(&serde_json::to_string(l).unwrap());
+            labels_content.push('\n');
+        }
+        fs::write(&labels_path, &labels_content)
+            .map_err(|e| PensaError::Internal(format!("failed to write labels.jsonl: {e}")))?;
+
+        let mut external_deps_content = String::new();
+        for d in &external_deps {
+            external_deps_content.push_str(&serde_json::to_string(d).unwrap());
+            external_deps_content.push('\n');
+        }
+        fs::write(&external_deps_path, &external_deps_content).map_err(|e| {
+            PensaError::Internal(format!("failed to write external_deps.jsonl: {e}"))
+        })?;
+
+        let mut actors_content = String::new();
+        for a in &actors {
+            actors_content.push_str(&serde_json::to_string(a).unwrap());
+            actors_content.push('\n');
+        }
+        fs::write(&actors_path, &actors_content)
+            .map_err(|e| PensaError::Internal(format!("failed to write actors.jsonl: {e}")))?;
+
+        Ok(ExportImportResult {
+            status: "ok".to_string(),
+            issues: sorted_issues.len(),
+            deps: deps.len(),
+            comments: comments.len(),
+            src_refs: src_refs.len(),
+            doc_refs: doc_refs.len(),
+            commits: commits.len(),
+            labels: labels.len(),
+            external_deps: external_deps.len(),
+            actors: actors.len(),
+        })
+    }
+
+    pub fn import_jsonl(&self) -> Result<ExportImportResult, PensaError> {
+        let issues_path = self.pensa_dir.join("issues.jsonl");
+        let deps_path = self.pensa_dir.join("deps.jsonl");
+        let comments_path = self.pensa_dir.join("comments.jsonl");
+        let src_refs_path = self.pensa_dir.join("src_refs.jsonl");
+        let doc_refs_path = self.pensa_dir.join("doc_refs.jsonl");
+        let commits_path = self.pensa_dir.join("commits.jsonl");
+        let labels_path = self.pensa_dir.join("labels.jsonl");
+        let external_deps_path = self.pensa_dir.join("external_deps.jsonl");
+        let actors_path = self.pensa_dir.join("actors.jsonl");
+
+        self.conn
+            .execute_batch(
+                "DELETE FROM actors;
+                 DELETE FROM events;
+                 DELETE FROM issue_refs;
+                 DELETE FROM external_deps;
+                 DELETE FROM labels;
+                 DELETE FROM commits;
+                 DELETE FROM src_refs;
+                 DELETE FROM doc_refs;
+                 DELETE FROM comments;
+                 DELETE FROM deps;
+                 DELETE FROM issues;",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to clear tables for import: {e}")))?;
+
+        let mut issue_count = 0;
+        if issues_path.exists() {
+            let content = fs::read_to_string(&issues_path)
+                .map_err(|e| PensaError::Internal(format!("failed to read issues.jsonl: {e}")))?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let issue: Issue = serde_json::from_str(line)
+                    .map_err(|e| PensaError::Internal(format!("failed to parse issue: {e}")))?;
+                self.conn
+                    .execute(
+                        "INSERT INTO issues (id, title, description, issue_type, status, priority, spec, fixes, assignee, created_at, updated_at, closed_at, close_reason, resolution, rev)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                        rusqlite::params![
+                            issue.id,
+                            issue.title,
+                            issue.description,
+                            issue.issue_type.as_str(),
+                            issue.status.as_str(),
+                            issue.priority.as_str(),
+                            issue.spec,
+                            issue.fixes,
+                            issue.assignee,
+                            issue.created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                            issue.updated_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                            issue.closed_at.map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+                            issue.close_reason,
+                            issue.resolution.map(|r| r.as_str()),
+                            issue.rev.max(1),
+                        ],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to import issue: {e}")))?;
+                issue_count += 1;
+            }
+        }
+
+        let mut dep_count = 0;
+        if deps_path.exists() {
+            let content = fs::read_to_string(&deps_path)
+                .map_err(|e| PensaError::Internal(format!("failed to read deps.jsonl: {e}")))?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let dep: Dep = serde_json::from_str(line)
+                    .map_err(|e| PensaError::Internal(format!("failed to parse dep: {e}")))?;
+                self.conn
+                    .execute(
+                        "INSERT INTO deps (issue_id, depends_on_id) VALUES (?1, ?2)",
+                        rusqlite::params![dep.issue_id, dep.depends_on_id],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to import dep: {e}")))?;
+                dep_count += 1;
+            }
+        }
+
+        let mut comment_count = 0;
+        if comments_path.exists() {
+            let content = fs::read_to_string(&comments_path)
+                .map_err(|e| PensaError::Internal(format!("failed to read comments.jsonl: {e}")))?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let comment: Comment = serde_json::from_str(line)
+                    .map_err(|e| PensaError::Internal(format!("failed to parse comment: {e}")))?;
+                self.conn
+                    .execute(
+                        "INSERT INTO comments (id, issue_id, actor, text, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                        rusqlite::params![
+                            comment.id,
+                            comment.issue_id,
+                            comment.actor,
+                            comment.text,
+                            comment.created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                        ],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to import comment: {e}")))?;
+                comment_count += 1;
+            }
+        }
+
+        let mut src_ref_count = 0;
+        if src_refs_path.exists() {
+            let content = fs::read_to_string(&src_refs_path)
+                .map_err(|e| PensaError::Internal(format!("failed to read src_refs.jsonl: {e}")))?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let sr: SrcRef = serde_json::from_str(line)
+                    .map_err(|e| PensaError::Internal(format!("failed to parse src_ref: {e}")))?;
+                self.conn
+                    .execute(
+                        "INSERT INTO src_refs (id, issue_id, path, reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                        rusqlite::params![
+                            sr.id,
+                            sr.issue_id,
+                            sr.path,
+                            sr.reason,
+                            sr.created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                        ],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to import src_ref: {e}")))?;
+                src_ref_count += 1;
+            }
+        }
+
+        let mut doc_ref_count = 0;
+        if doc_refs_path.exists() {
+            let content = fs::read_to_string(&doc_refs_path)
+                .map_err(|e| PensaError::Internal(format!("failed to read doc_refs.jsonl: {e}")))?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let dr: DocRef = serde_json::from_str(line)
+                    .map_err(|e| PensaError::Internal(format!("failed to parse doc_ref: {e}")))?;
+                self.conn
+                    .execute(
+                        "INSERT INTO doc_refs (id, issue_id, path, reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                        rusqlite::params![
+                            dr.id,
+                            dr.issue_id,
+                            dr.path,
+                            dr.reason,
+                            dr.created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                        ],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to import doc_ref: {e}")))?;
+                doc_ref_count += 1;
+            }
+        }
+
+        let mut commit_count = 0;
+        if commits_path.exists() {
+            let content = fs::read_to_string(&commits_path)
+                .map_err(|e| PensaError::Internal(format!("failed to read commits.jsonl: {e}")))?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let c: CommitLink = serde_json::from_str(line)
+                    .map_err(|e| PensaError::Internal(format!("failed to parse commit: {e}")))?;
+                self.conn
+                    .execute(
+                        "INSERT INTO commits (issue_id, sha, summary, created_at) VALUES (?1, ?2, ?3, ?4)",
+                        rusqlite::params![
+                            c.issue_id,
+                            c.sha,
+                            c.summary,
+                            c.created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                        ],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to import commit: {e}")))?;
+                commit_count += 1;
+            }
+        }
+
+        let mut label_count = 0;
+        if labels_path.exists() {
+            let content = fs::read_to_string(&labels_path)
+                .map_err(|e| PensaError::Internal(format!("failed to read labels.jsonl: {e}")))?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let l: Label = serde_json::from_str(line)
+                    .map_err(|e| PensaError::Internal(format!("failed to parse label: {e}")))?;
+                self.conn
+                    .execute(
+                        "INSERT INTO labels (issue_id, label, created_at) VALUES (?1, ?2, ?3)",
+                        rusqlite::params![
+                            l.issue_id,
+                            l.label,
+                            l.created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                        ],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to import label: {e}")))?;
+                label_count += 1;
+            }
+        }
+
+        let mut external_dep_count = 0;
+        if external_deps_path.exists() {
+            let content = fs::read_to_string(&external_deps_path).map_err(|e| {
+                PensaError::Internal(format!("failed to read external_deps.jsonl: {e}"))
+            })?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let d: ExternalDep = serde_json::from_str(line).map_err(|e| {
+                    PensaError::Internal(format!("failed to parse external dep: {e}"))
+                })?;
+                self.conn
+                    .execute(
+                        "INSERT INTO external_deps (issue_id, target, kind, resolved_at, created_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        rusqlite::params![
+                            d.issue_id,
+                            d.target,
+                            d.kind.as_str(),
+                            d.resolved_at.map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+                            d.created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                        ],
+                    )
+                    .map_err(|e| {
+                        PensaError::Internal(format!("failed to import external dep: {e}"))
+                    })?;
+                external_dep_count += 1;
+            }
+        }
+
+        // Assignees imported above have registered themselves already; the
+        // file's rows replace theirs.
+        let mut actor_count = 0;
+        if actors_path.exists() {
+            let content = fs::read_to_string(&actors_path)
+                .map_err(|e| PensaError::Internal(format!("failed to read actors.jsonl: {e}")))?;
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let a: Actor = serde_json::from_str(line)
+                    .map_err(|e| PensaError::Internal(format!("failed to parse actor: {e}")))?;
+                self.conn
+                    .execute(
+                        "INSERT OR REPLACE INTO actors (name, display_name, alias_of, first_seen, last_seen)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        rusqlite::params![
+                            a.name,
+                            a.display_name,
+                            a.alias_of,
+                            a.first_seen.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                            a.last_seen.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                        ],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to import actor: {e}")))?;
+                actor_count += 1;
+            }
+        }
+
+        self.rebuild_refs()?;
+
+        Ok(ExportImportResult {
+            status: "ok".to_string(),
+            issues: issue_count,
+            deps: dep_count,
+            comments: comment_count,
+            src_refs: src_ref_count,
+            doc_refs: doc_ref_count,
+            commits: commit_count,
+            labels: label_count,
+            external_deps: external_dep_count,
+            actors: actor_count,
+        })
+    }
+
+    /// Applies `policy` to unclosed issues, logging an `escalated` event per
+    /// change. Blocking bugs are raised first; an issue escalated for blocking
+    /// is not also aged in the same pass. With `dry_run`, nothing is written.
+    pub fn escalate_priorities(
+        &self,
+        policy: &EscalationPolicy,
+        dry_run: bool,
+    ) -> Result<Vec<Escalation>, PensaError> {
+        let mut escalations: Vec<Escalation> = Vec::new();
+
+        if let Some(threshold) = policy.blocking_dependents {
+            let mut stmt = self
+                .conn
+                .prepare(
+                    "SELECT i.*, COUNT(*) AS dependents
+                     FROM issues i
+                     JOIN deps d ON d.depends_on_id = i.id
+                     JOIN issues dep ON dep.id = d.issue_id
+                     WHERE i.issue_type = 'bug' AND i.status != 'closed' AND dep.status != 'closed'
+                     GROUP BY i.id
+                     HAVING COUNT(*) >= ?1
+                     ORDER BY i.id",
+                )
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to prepare escalation query: {e}"))
+                })?;
+            let blocking = stmt
+                .query_map(rusqlite::params![threshold as i64], |row| {
+                    Ok((issue_from_row(row)?, row.get::<_, i64>("dependents")?))
+                })
+                .map_err(|e| PensaError::Internal(format!("failed to query blocking bugs: {e}")))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PensaError::Internal(format!("failed to read blocking bugs: {e}")))?;
+
+            for (issue, dependents) in blocking {
+                if issue.priority > policy.blocking_priority {
+                    escalations.push(Escalation {
+                        issue_id: issue.id,
+                        title: issue.title,
+                        from: issue.priority,
+                        to: policy.blocking_priority,
+                        reason: format!("blocks {dependents} open issues"),
+                    });
+                }
+            }
+        }
+
+        if let Some(untouched_after) = policy.untouched_after {
+            let cutoff = (Utc::now() - untouched_after)
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string();
+            let mut stmt = self
+                .conn
+                .prepare(
+                    "SELECT * FROM issues WHERE status = 'open' AND priority != 'p0' AND updated_at < ?1 ORDER BY id",
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to prepare escalation query: {e}")))?;
+            let untouched = stmt
+                .query_map(rusqlite::params![cutoff], issue_from_row)
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to query untouched issues: {e}"))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    PensaError::Internal(format!("failed to read untouched issues: {e}"))
+                })?;
+
+            for issue in untouched {
+                if escalations.iter().any(|e| e.issue_id == issue.id) {
+                    continue;
+                }
+                let Some(to) = issue.priority.escalated() else {
+                    continue;
+                };
+                let days = (Utc::now() - issue.updated_at).num_days();
+                escalations.push(Escalation {
+                    issue_id: issue.id,
+                    title: issue.title,
+                    from: issue.priority,
+                    to,
+                    reason: format!("untouched for {days}d"),
+                });
+            }
+        }
+
+        if dry_run {
+            return Ok(escalations);
+        }
+
+        for esc in &escalations {
+            let ts = now();
+            self.conn
+                .execute(
+                    "UPDATE issues SET priority = ?1, updated_at = ?2 WHERE id = ?3",
+                    rusqlite::params![esc.to.as_str(), ts, esc.issue_id],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to escalate issue: {e}")))?;
+            let detail = serde_json::json!({
+                "from": esc.from,
+                "to": esc.to,
+                "reason": esc.reason,
+            })
+            .to_string();
+            self.conn
+                .execute(
+                    "INSERT INTO events (issue_id, event_type, actor, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![esc.issue_id, "escalated", "pensa", detail, ts],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to log escalation event: {e}")))?;
+        }
+
+        Ok(escalations)
+    }
+
+    pub fn stats(&self) -> Result<DbStats, PensaError> {
+        let schema_version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|e| PensaError::Internal(format!("failed to read schema version: {e}")))?;
+        let names: Vec<String> = {
+            let mut stmt = self
+                .conn
+                .prepare(
+                    "SELECT name FROM sqlite_master
+                     WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to list tables: {e}")))?;
+            stmt.query_map([], |row| row.get(0))
+                .map_err(|e| PensaError::Internal(format!("failed to list tables: {e}")))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PensaError::Internal(format!("failed to list tables: {e}")))?
+        };
+        let mut tables = BTreeMap::new();
+        for name in names {
+            let count: i64 = self
+                .conn
+                .query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+                    row.get(0)
+                })
+                .map_err(|e| PensaError::Internal(format!("failed to count {name}: {e}")))?;
+            tables.insert(name, count);
+        }
+        let size = |path: PathBuf| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let last_export = fs::metadata(self.pensa_dir.join("issues.jsonl"))
+            .and_then(|m| m.modified())
+            .ok()
+            .map(DateTime::<Utc>::from);
+        Ok(DbStats {
+            schema_version,
+            tables,
+            db_bytes: size(self.data_dir.join("db.sqlite")),
+            wal_bytes: size(self.data_dir.join("db.sqlite-wal")),
+            last_export,
+        })
+    }
+
+    /// Runs `op` in a transaction that is then rolled back, returning its
+    /// result with the rows it changed. Backs `--dry-run`.
+    pub fn preview<T>(
+        &self,
+        op: impl FnOnce(&Self) -> Result<T, PensaError>,
+    ) -> Result<(T, Vec<RowChange>), PensaError> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| PensaError::Internal(format!("failed to begin transaction: {e}")))?;
+        let before = self.preview_rows()?;
+        let result = op(self)?;
+        let after = self.preview_rows()?;
+        tx.rollback()
+            .map_err(|e| PensaError::Internal(format!("failed to roll back dry run: {e}")))?;
+
+        let mut changes = Vec::new();
+        for ((table, _), (old, new)) in PREVIEW_TABLES.iter().zip(before.iter().zip(&after)) {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let action = match (old.get(key), new.get(key)) {
+                    (Some(_), None) => RowAction::Delete,
+                    (None, Some(_)) => RowAction::Insert,
+                    (Some(a), Some(b)) if a != b => RowAction::Update,
+                    _ => continue,
+                };
+                changes.push(RowChange {
+                    table: table.to_string(),
+                    action,
+                    key: key.clone(),
+                });
+            }
+        }
+        Ok((result, changes))
+    }
+
+    fn preview_rows(&self) -> Result<Vec<BTreeMap<String, String>>, PensaError> {
+        PREVIEW_TABLES
+            .iter()
+            .map(|(table, key)| {
+                let mut stmt = self
+                    .conn
+                    .prepare(&format!(
+                        "SELECT CAST({} AS TEXT) AS row_key, * FROM {table}",
+                        key.join(" || ' ' || ")
+                    ))
+                    .map_err(|e| PensaError::Internal(format!("failed to read {table}: {e}")))?;
+                let columns = stmt.column_count();
+                stmt.query_map([], |row| {
+                    let values = (1..columns)
+                        .map(|i| row.get::<_, Value>(i).map(|v| format!("{v:?}")))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok((row.get(0)?, values.join("\u{1f}")))
+                })
+                .map_err(|e| PensaError::Internal(format!("failed to read {table}: {e}")))?
+                .collect::<Result<BTreeMap<_, _>, _>>()
+                .map_err(|e| PensaError::Internal(format!("failed to read {table}: {e}")))
+            })
+            .collect()
+    }
+
+    pub fn stale_issues(
+        &self,
+        older_than: TimeDelta,
+        status: Option<Status>,
+    ) -> Result<Vec<StaleIssue>, PensaError> {
+        let now_dt = Utc::now();
+        let cutoff = (now_dt - older_than)
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT * FROM issues WHERE updated_at < ?1
+                 AND (status = ?2 OR (?2 IS NULL AND status != 'closed'))
+                 ORDER BY updated_at, id",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to prepare stale query: {e}")))?;
+        let issues = stmt
+            .query_map(
+                rusqlite::params![cutoff, status.map(|s| s.as_str())],
+                issue_from_row,
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to query stale issues: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read stale issues: {e}")))?;
+        Ok(issues
+            .into_iter()
+            .map(|issue| StaleIssue {
+                age_days: (now_dt - issue.updated_at).num_days(),
+                issue,
+            })
+            .collect())
+    }
+
+    pub fn stale_issue_findings(
+        &self,
+        older_than: TimeDelta,
+    ) -> Result<Vec<DoctorFinding>, PensaError> {
+        Ok(self
+            .stale_issues(older_than, None)?
+            .into_iter()
+            .map(|stale| DoctorFinding {
+                check: "stale_issue".to_string(),
+                message: format!(
+                    "issue \"{}\" is {} and untouched for {}d",
+                    stale.issue.title,
+                    stale.issue.status.as_str(),
+                    stale.age_days
+                ),
+                issue_id: Some(stale.issue.id),
+                claim_age_secs: None,
+            })
+            .collect())
+    }
+
+    pub fn doctor(
+        &self,
+        fix: bool,
+        stale_after: Option<TimeDelta>,
+        specs_dir: Option<&Path>,
+    ) -> Result<DoctorReport, PensaError> {
+        let mut findings = Vec::new();
+        let mut fixes_applied = Vec::new();
+
+        // Check 1: Stale claims — in_progress issues claimed longer ago than stale_after
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT i.id, i.title, i.assignee,
+                        COALESCE(
+                            (SELECT MAX(e.created_at) FROM events e
+                             WHERE e.issue_id = i.id AND e.event_type = 'claimed'),
+                            i.updated_at
+                        ) AS claimed_at
+                 FROM issues i WHERE i.status = 'in_progress'
+                 ORDER BY claimed_at ASC",
+            )
+            .map_err(|e| PensaError::Internal(format!("failed to check stale claims: {e}")))?;
+
+        let in_progress: Vec<(String, String, Option<String>, String)> = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| PensaError::Internal(format!("failed to query stale claims: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PensaError::Internal(format!("failed to read stale claims: {e}")))?;
+
+        let threshold = stale_after.unwrap_or_else(TimeDelta::zero);
+        let now_dt = Utc::now();
+        let mut stale_ids = Vec::new();
+        for (id, title, assignee, claimed_at) in &in_progress {
+            let age = (now_dt - parse_dt(claimed_at)).max(TimeDelta::zero());
+            if age < threshold {
+                continue;
+            }
+            findings.push(DoctorFinding {
+                check: "stale_claim".to_string(),
+                message: format!(
+                    "issue \"{title}\" is in_progress (claimed by {}, {} ago)",
+                    assignee.as_deref().unwrap_or("unknown"),
+                    format_age(age)
+                ),
+                issue_id: Some(id.clone()),
+                claim_age_secs: Some(age.num_seconds()),
+            });
+            stale_ids.push(id.clone());
+        }
+
+        if fix && !stale_ids.is_empty() {
+            let ts = now();
+            for id in &stale_ids {
+                self.conn
+                    .execute(
+                        "UPDATE issues SET status = 'open', assignee = NULL, updated_at = ?1 WHERE id = ?2",
+                        rusqlite::params![ts, id],
+                    )
+                    .map_err(|e| PensaError::Internal(format!("failed to fix stale claims: {e}")))?;
+            }
+            fixes_applied.push(format!("released {} stale claims", stale_ids.len()));
+        }
+
+        // Check 2: Orphaned deps
+        let orphaned_deps: Vec<(String, String)> = {
+            let mut stmt = self
+                .conn
+                .prepare(
+                    "SELECT d.issue_id, d.depends_on_id FROM deps d
+                     WHERE d.issue_id NOT IN (SELECT id FROM issues)
+                        OR d.depends_on_id NOT IN (SELECT id FROM issues)",
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to check orphaned deps: {e}")))?;
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| PensaError::Internal(format!("failed to query orphaned deps: {e}")))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PensaError::Internal(format!("failed to read orphaned deps: {e}")))?
+        };
+
+        for (issue_id, depends_on_id) in &orphaned_deps {
+            findings.push(DoctorFinding {
+                check: "orphaned_dep".to_string(),
+                message: format!("dep {issue_id} -> {depends_on_id} references non-existent issue"),
+                issue_id: None,
+                claim_age_secs: None,
+            });
+        }
+
+        if fix && !orphaned_deps.is_empty() {
+            self.conn
+                .execute(
+                    "DELETE FROM deps WHERE issue_id NOT IN (SELECT id FROM issues)
+                        OR depends_on_id NOT IN (SELECT id FROM issues)",
+                    [],
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to fix orphaned deps: {e}")))?;
+            fixes_applied.push(format!("removed {} orphaned deps", orphaned_deps.len()));
+        }
+
+        // Check 3: JSONL/SQLite drift (all entity files)
+        let entity_checks: &[(&str, &str)] = &[
+            ("issues.jsonl", "issues"),
+            ("deps.jsonl", "deps"),
+            ("comments.jsonl", "comments"),
+            ("src_refs.jsonl", "src_refs"),
+            ("doc_refs.jsonl", "doc_refs"),
+            ("commits.jsonl", "commits"),
+            ("labels.jsonl", "labels"),
+        ];
+        for (jsonl_file, table_name) in entity_checks {
+            let path = self.pensa_dir.join(jsonl_file);
+            if path.exists() {
+                let content = fs::read_to_string(&path).unwrap_or_default();
+                let jsonl_count = content.lines().filter(|l| !l.trim().is_empty()).count();
+                let db_count: i64 = self
+                    .conn
+                    .query_row(&format!("SELECT COUNT(*) FROM {table_name}"), [], |row| {
+                        row.get(0)
+                    })
+                    .map_err(|e| {
+                        PensaError::Internal(format!("failed to count {table_name}: {e}"))
+                    })?;
+                if jsonl_count as i64 != db_count {
+                    findings.push(DoctorFinding {
+                        check: "jsonl_drift".to_string(),
+                        message: format!(
+                            "{jsonl_file} has {jsonl_count} entries but DB has {db_count}"
+                        ),
+                        issue_id: None,
+                        claim_age_secs: None,
+                    });
+                }
+            }
+        }
+
+        // Check 4: Open issues referencing specs missing from the specs directory
+        if let Some(specs_dir) = specs_dir {
+            let mut stmt = self
+                .conn
+                .prepare(
+                    "SELECT id, spec FROM issues
+                     WHERE spec IS NOT NULL AND status != 'closed'
+                     ORDER BY spec, id",
+                )
+                .map_err(|e| PensaError::Internal(format!("failed to check specs: {e}")))?;
+            let with_spec: Vec<(String, String)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| PensaError::Internal(format!("failed to query specs: {e}")))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PensaError::Internal(format!("failed to read specs: {e}")))?;
+
+            for (id, spec) in with_spec {
+                let path = specs_dir.join(format!("{spec}.md"));
+                if !path.is_file() {
+                    findings.push(DoctorFinding {
+                        check: "missing_spec".to_string(),
+                        message: format!(
+                            "issue references spec '{spec}' but {} does not exist",
+                            path.display()
+                        ),
+                        issue_id: Some(id),
+                        claim_age_secs: None,
+                    });
+                }
+            }
+        }
+
+        Ok(DoctorReport {
+            findings,
+            fixes_applied,
+            dry_run: false,
+            changes: Vec::new(),
+        })
+    }
+}
+
+/// Matches issues assigned to an identity, given twice, or to any of its
+/// aliases.
+const ASSIGNEE_CONDITION: &str =
+    "(assignee = ? OR assignee IN (SELECT name FROM actors WHERE alias_of = ?))";
+
+const PREVIEW_TABLES: &[(&str, &[&str])] = &[
+    ("issues", &["id"]),
+    ("deps", &["issue_id", "depends_on_id"]),
+    ("external_deps", &["issue_id", "target"]),
+    ("comments", &["id"]),
+    ("src_refs", &["id"]),
+    ("doc_refs", &["id"]),
+    ("commits", &["issue_id", "sha"]),
+    ("labels", &["issue_id", "label"]),
+    ("issue_refs", &["issue_id", "ref_id", "source"]),
+    ("watchers", &["issue_id", "actor"]),
+    ("events", &["id"]),
+    ("actors", &["name"]),
+];
+
+pub fn now() -> String {
+    Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+pub fn parse_date(s: &str) -> Result<DateTime<Utc>, PensaError> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|d| d.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|_| {
+            PensaError::InvalidArgument(format!(
+                "invalid date '{s}' (expected YYYY-MM-DD or RFC 3339)"
+            ))
+        })
+}
+
+fn ready_explanation(issue: Issue, reasons: Vec<ExclusionReason>) -> ReadyExplanation {
+    ReadyExplanation {
+        ready: reasons.is_empty(),
+        id: issue.id,
+        title: issue.title,
+        status: issue.status,
+        priority: issue.priority,
+        issue_type: issue.issue_type,
+        reasons,
+    }
+}
+
+fn diff_fields(issue: &Issue) -> [(&'static str, Option<String>); 8] {
+    [
+        ("title", Some(issue.title.clone())),
+        ("status", Some(issue.status.as_str().to_string())),
+        ("priority", Some(issue.priority.as_str().to_string())),
+        ("issue_type", Some(issue.issue_type.as_str().to_string())),
+        ("spec", issue.spec.clone()),
+        ("assignee", issue.assignee.clone()),
+        ("description", issue.description.clone()),
+        (
+            "resolution",
+            issue.resolution.map(|r| r.as_str().to_string()),
+        ),
+    ]
+}
+
+pub fn diff_issues(from: String, to: String, before: &[Issue], after: &[Issue]) -> SnapshotDiff {
+    let before_by_id: HashMap<&str, &Issue> = before.iter().map(|i| (i.id.as_str(), i)).collect();
+    let after_ids: HashSet<&str> = after.iter().map(|i| i.id.as_str()).collect();
+    let mut diff = SnapshotDiff {
+        from,
+        to,
+        created: Vec::new(),
+        closed: Vec::new(),
+        changed: Vec::new(),
+        deleted: before
+            .iter()
+            .filter(|i| !after_ids.contains(i.id.as_str()))
+            .cloned()
+            .collect(),
+    };
+    for issue in after {
+        let old = before_by_id.get(issue.id.as_str());
+        if old.is_none() {
+            diff.created.push(issue.clone());
+        }
+        if issue.status == Status::Closed && old.is_none_or(|o| o.status != Status::Closed) {
+            diff.closed.push(issue.clone());
+            continue;
+        }
+        let Some(old) = old else { continue };
+        let changes: Vec<FieldChange> = diff_fields(old)
+            .into_iter()
+            .zip(diff_fields(issue))
+            .filter(|((_, a), (_, b))| a != b)
+            .map(|((field, from), (_, to))| FieldChange {
+                field: field.to_string(),
+                from,
+                to,
+            })
+            .collect();
+        if !changes.is_empty() {
+            diff.changed.push(IssueChange {
+                id: issue.id.clone(),
+                title: issue.title.clone(),
+                changes,
+            });
+        }
+    }
+    diff
+}
+
+fn format_age(age: TimeDelta) -> String {
+    let secs = age.num_seconds();
+    let (days, hours, mins) = (secs / 86400, (secs % 86400) / 3600, (secs % 3600) / 60);
+    if days > 0 {
+        format!("{days}d{hours}h")
+    } else if hours > 0 {
+        format!("{hours}h{mins}m")
+    } else if mins > 0 {
+        format!("{mins}m")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Checklist, CreateIssueParams, IssueType, Priority, Status};
+    use tempfile::TempDir;
+
+    fn open_temp_db() -> (Db, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let pensa_dir = dir.path().join(".pensa");
+        let data_dir = dir.path().join("data");
+        let db = Db::open_with_data_dir(pensa_dir, data_dir).unwrap();
+        (db, dir)
+    }
+
+    #[test]
+    fn open_creates_tables() {
+        let (db, _dir) = open_temp_db();
+
+        let tables: Vec<String> = db
+            .conn
+            .prepare("SELECT name FROM sqlite_master WHERE type='table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert!(tables.contains(&"issues".to_string()));
+        assert!(tables.contains(&"deps".to_string()));
+        assert!(tables.contains(&"comments".to_string()));
+        assert!(tables.contains(&"events".to_string()));
+    }
+
+    #[test]
+    fn open_is_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let pensa_dir = dir.path().join(".pensa");
+        let data_dir = dir.path().join("data");
+        let _db1 = Db::open_with_data_dir(pensa_dir.clone(), data_dir.clone()).unwrap();
+        let _db2 = Db::open_with_data_dir(pensa_dir, data_dir).unwrap();
+    }
+
+    #[test]
+    fn foreign_keys_enforced() {
+        let (db, _dir) = open_temp_db();
+
+        let result = db.conn.execute(
+            "INSERT INTO deps (issue_id, depends_on_id) VALUES ('nonexistent-a', 'nonexistent-b')",
+            [],
+        );
+
+        assert!(
+            result.is_err(),
+            "should reject dep referencing nonexistent issues"
+        );
+    }
+
+    #[test]
+    fn create_and_get() {
+        let (db, _dir) = open_temp_db();
+
+        let issue = db
+            .create_issue(&CreateIssueParams {
+                title: "login crash".into(),
+                issue_type: IssueType::Bug,
+                priority: Priority::P0,
+                description: Some("crashes on empty password".into()),
+                spec: None,
+                fixes: None,
+                assignee: Some("alice".into()),
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+
+        assert!(issue.id.starts_with("pn-"));
+        assert_eq!(issue.title, "login crash");
+        assert_eq!(issue.issue_type, IssueType::Bug);
+        assert_eq!(issue.priority, Priority::P0);
+        assert_eq!(issue.status, Status::Open);
+        assert_eq!(
+            issue.description.as_deref(),
+            Some("crashes on empty password")
+        );
+        assert_eq!(issue.assignee.as_deref(), Some("alice"));
+        assert!(issue.spec.is_none());
+        assert!(issue.fixes.is_none());
+        assert!(issue.closed_at.is_none());
+        assert!(issue.close_reason.is_none());
+
+        let detail = db.get_issue(&issue.id).unwrap();
+        assert_eq!(detail.issue.id, issue.id);
+        assert_eq!(detail.issue.title, "login crash");
+        assert!(detail.deps.is_empty());
+        assert!(detail.comments.is_empty());
+    }
+
+    #[test]
+    fn get_nonexistent() {
+        let (db, _dir) = open_temp_db();
+        let result = db.get_issue("pn-00000000");
+        assert!(matches!(result, Err(PensaError::NotFound(_))));
+    }
+
+    #[test]
+    fn update_fields() {
+        let (db, _dir) = open_temp_db();
+
+        let issue = db
+            .create_issue(&CreateIssueParams {
+                title: "original title".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: Some("original desc".into()),
+                spec: None,
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+
+        let updated = db
+            .update_issue(
+                &issue.id,
+                &UpdateFields {
+                    title: Some("new title".to_string()),
+                    priority: Some(Priority::P1),
+                    ..Default::default()
+                },
+                "test-agent",
+            )
+            .unwrap();
+
+        assert_eq!(updated.title, "new title");
+        assert_eq!(updated.priority, Priority::P1);
+        assert_eq!(updated.description.as_deref(), Some("original desc"));
+        assert_eq!(updated.issue_type, IssueType::Task);
+        assert!(updated.updated_at >= issue.updated_at);
+    }
+
+    #[test]
+    fn check_item_toggles_the_description_checklist() {
+        let (db, _dir) = open_temp_db();
+
+        let issue = db
+            .create_issue(&CreateIssueParams {
+                title: "migrate".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: Some("- [ ] add column\n- [ ] backfill\n".into()),
+                spec: None,
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+        assert_eq!(issue.checklist, Some(Checklist { done: 0, total: 2 }));
+
+        let checked = db.check_item(&issue.id, 2, "test-agent").unwrap();
+        assert_eq!(
+            checked.description.as_deref(),
+            Some("- [ ] add column\n- [x] backfill\n")
+        );
+        assert_eq!(checked.checklist, Some(Checklist { done: 1, total: 2 }));
+        assert_eq!(checked.rev, issue.rev + 1);
+        assert!(db.check_item(&issue.id, 3, "test-agent").is_err());
+
+        let unchecked = db.check_item(&issue.id, 2, "test-agent").unwrap();
+        assert_eq!(unchecked.checklist, Some(Checklist { done: 0, total: 2 }));
+        assert!(create_task(&db, "plain").checklist.is_none());
+    }
+
+    #[test]
+    fn create_issues_links_plan_keys_in_one_transaction() {
+        let (db, _dir) = open_temp_db();
+        let existing = create_task(&db, "existing");
+        let entry = |key: &str, deps: &[&str]| PlannedIssue {
+            key: key.into(),
+            title: format!("do {key}"),
+            issue_type: IssueType::Task,
+            priority: None,
+            description: None,
+            spec: Some("auth".into()),
+            assignee: None,
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            labels: vec!["seeded".into()],
+        };
+
+        let created = db
+            .create_issues(
+                &[
+                    entry("api", &["schema"]),
+                    entry("schema", &[existing.id.as_str()]),
+                ],
+                "sgf",
+            )
+            .unwrap();
+        assert_eq!(created.len(), 2);
+        assert_eq!(created[0].title, "do api");
+        let api = db.get_issue(&created[0].id).unwrap();
+        assert_eq!(api.deps[0].id, created[1].id);
+        assert_eq!(api.labels, ["seeded"]);
+        let schema = db.get_issue(&created[1].id).unwrap();
+        assert_eq!(schema.deps[0].id, existing.id);
+
+        let before = db.list_issues(&ListFilters::default()).unwrap().len();
+        let bad = db.create_issues(&[entry("ok", &[]), entry("broken", &["nowhere"])], "sgf");
+        assert!(matches!(bad, Err(PensaError::InvalidArgument(_))));
+        let cycle = db.create_issues(&[entry("a", &["b"]), entry("b", &["a"])], "sgf");
+        assert!(matches!(cycle, Err(PensaError::CycleDetected)));
+        assert_eq!(
+            db.list_issues(&ListFilters::default()).unwrap().len(),
+            before
+        );
+    }
+
+    #[test]
+    fn update_logs_event() {
+        let (db, _dir) = open_temp_db();
+
+        let issue = db
+            .create_issue(&CreateIssueParams {
+                title: "test issue".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: None,
+                spec: None,
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+
+        db.update_issue(
+            &issue.id,
+            &UpdateFields {
+                title: Some("updated title".to_string()),
+                ..Default::default()
+            },
+            "test-agent",
+        )
+        .unwrap();
+
+        let mut stmt = db
+            .conn
+            .prepare(
+                "SELECT event_type, detail FROM events WHERE issue_id = ?1 ORDER BY created_at",
+            )
+            .unwrap();
+        let events: Vec<(String, Option<String>)> = stmt
+            .query_map(rusqlite::params![issue.id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, "created");
+        assert_eq!(events[1].0, "updated");
+        assert!(events[1].1.as_ref().unwrap().contains("updated title"));
+    }
+
+    fn params_for(title: &str) -> CreateIssueParams {
+        CreateIssueParams {
+            title: title.into(),
+            issue_type: IssueType::Task,
+            priority: Priority::P2,
+            description: None,
+            spec: None,
+            fixes: None,
+            assignee: None,
+            deps: vec![],
+            labels: Vec::new(),
+            actor: "test-agent".into(),
+        }
+    }
+
+    fn create_task(db: &Db, title: &str) -> Issue {
+        db.create_issue(&params_for(title)).unwrap()
+    }
+
+    #[test]
+    fn claim_sets_in_progress() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "implement auth");
+
+        let claimed = db.claim_issue(&issue.id, "agent-1").unwrap();
+
+        assert_eq!(claimed.status, Status::InProgress);
+        assert_eq!(claimed.assignee.as_deref(), Some("agent-1"));
+    }
+
+    #[test]
+    fn double_claim_fails() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "implement auth");
+
+        db.claim_issue(&issue.id, "agent-1").unwrap();
+        let result = db.claim_issue(&issue.id, "agent-2");
+
+        assert!(matches!(result, Err(PensaError::AlreadyClaimed { .. })));
+        if let Err(PensaError::AlreadyClaimed { holder, .. }) = result {
+            assert_eq!(holder, "agent-1");
+        }
+    }
+
+    #[test]
+    fn release_clears() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "implement auth");
+
+        db.claim_issue(&issue.id, "agent-1").unwrap();
+        let released = db.release_issue(&issue.id, "agent-1").unwrap();
+
+        assert_eq!(released.status, Status::Open);
+        assert!(released.assignee.is_none());
+    }
+
+    #[test]
+    fn close_reopen_cycle() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "implement auth");
+
+        let closed = db
+            .close_issue(&issue.id, Some("done"), None, false, "agent-1")
+            .unwrap();
+        assert_eq!(closed.status, Status::Closed);
+        assert_eq!(closed.close_reason.as_deref(), Some("done"));
+        assert!(closed.closed_at.is_some());
+
+        let reopened = db
+            .reopen_issue(&issue.id, Some("not done"), false, "agent-1")
+            .unwrap();
+        assert_eq!(reopened.status, Status::Open);
+        assert!(reopened.closed_at.is_none());
+        assert!(reopened.close_reason.is_none());
+
+        let closed_again = db
+            .close_issue(&issue.id, None, None, false, "agent-1")
+            .unwrap();
+        assert_eq!(closed_again.status, Status::Closed);
+    }
+
+    #[test]
+    fn fixes_auto_close() {
+        let (db, _dir) = open_temp_db();
+
+        let bug = db
+            .create_issue(&CreateIssueParams {
+                title: "login crash".into(),
+                issue_type: IssueType::Bug,
+                priority: Priority::P0,
+                description: None,
+                spec: None,
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+
+        let task = db
+            .create_issue(&CreateIssueParams {
+                title: "fix login".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P1,
+                description: None,
+                spec: None,
+                fixes: Some(bug.id.clone()),
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+        let fixed_by = db.get_issue(&bug.id).unwrap().fixed_by;
+        assert_eq!(fixed_by.len(), 1);
+        assert_eq!(fixed_by[0].id, task.id);
+
+        db.close_issue(&task.id, Some("implemented"), None, false, "agent-1")
+            .unwrap();
+
+        let bug_after = db.get_issue_only(&bug.id).unwrap();
+        assert_eq!(bug_after.status, Status::Closed);
+        assert_eq!(bug_after.close_reason.as_deref(), Some("fixed"));
+    }
+
+    #[test]
+    fn closing_follows_the_fixes_chain_and_stops_at_cycles() {
+        let (db, _dir) = open_temp_db();
+        let bug = |title: &str, fixes: Option<&str>| {
+            db.create_issue(&CreateIssueParams {
+                title: title.into(),
+                issue_type: IssueType::Bug,
+                priority: Priority::P1,
+                description: None,
+                spec: None,
+                fixes: fixes.map(String::from),
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap()
+        };
+        let reported = bug("crash on login (reported)", None);
+        let original = bug("crash on login", Some(&reported.id));
+        let task = bug("fix the crash", Some(&original.id));
+
+        let (closed, auto_closed) = db
+            .close_issue_chain(&task.id, None, None, false, "agent-1")
+            .unwrap();
+        assert_eq!(closed.status, Status::Closed);
+        assert_eq!(auto_closed, [original.id.clone(), reported.id.clone()]);
+        let last = db.issue_history(&reported.id).unwrap().remove(0);
+        assert_eq!(
+            last.detail,
+            Some(format!("fixed: last fix task {} closed", original.id))
+        );
+
+        // Two issues fixing each other: the chain closes the other one and
+        // stops instead of coming back round.
+        let a = bug("a", None);
+        let b = bug("b", Some(&a.id));
+        db.update_issue(
+            &a.id,
+            &UpdateFields {
+                fixes: Some(b.id.clone()),
+                ..Default::default()
+            },
+            "test-agent",
+        )
+        .unwrap();
+        let (_, auto_closed) = db
+            .close_issue_chain(&a.id, None, None, false, "agent-1")
+            .unwrap();
+        assert_eq!(auto_closed, [b.id]);
+
+        // A fix still open keeps its target, and everything past it, open.
+        let top = bug("top", None);
+        let middle = bug("middle", Some(&top.id));
+        let done = bug("done", Some(&middle.id));
+        bug("still going", Some(&middle.id));
+        let (_, auto_closed) = db
+            .close_issue_chain(&done.id, None, None, false, "agent-1")
+            .unwrap();
+        assert!(auto_closed.is_empty());
+        assert_eq!(db.get_issue_only(&top.id).unwrap().status, Status::Open);
+    }
+
+    #[test]
+    fn reopen_names_or_cascades_to_fix_tasks() {
+        let (db, _dir) = open_temp_db();
+        let bug = db
+            .create_issue(&CreateIssueParams {
+                title: "login crash".into(),
+                issue_type: IssueType::Bug,
+                priority: Priority::P0,
+                description: None,
+                spec: None,
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+        let task = db
+            .create_issue(&CreateIssueParams {
+                title: "fix login".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P1,
+                description: None,
+                spec: None,
+                fixes: Some(bug.id.clone()),
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+        let last_detail = |id: &str| {
+            db.issue_history(id)
+                .unwrap()
+                .into_iter()
+                .max_by_key(|e| e.id)
+                .and_then(|e| e.detail)
+        };
+
+        db.close_issue(&task.id, None, None, false, "agent-1")
+            .unwrap();
+        assert_eq!(
+            last_detail(&bug.id),
+            Some(format!("fixed: last fix task {} closed", task.id))
+        );
+
+        db.reopen_issue(&bug.id, Some("still crashes"), false, "qa")
+            .unwrap();
+        assert_eq!(
+            last_detail(&bug.id),
+            Some(format!("still crashes (fixed by {})", task.id))
+        );
+        assert_eq!(db.get_issue_only(&task.id).unwrap().status, Status::Closed);
+
+        db.close_issue(&bug.id, None, None, false, "qa").unwrap();
+        db.reopen_issue(&bug.id, Some("crashes again"), true, "qa")
+            .unwrap();
+        assert_eq!(
+            last_detail(&bug.id),
+            Some(format!("crashes again (also reopened {})", task.id))
+        );
+        assert_eq!(db.get_issue_only(&task.id).unwrap().status, Status::Open);
+        assert_eq!(
+            last_detail(&task.id),
+            Some(format!("{} reopened: crashes again", bug.id))
+        );
+    }
+
+    #[test]
+    fn delete_requires_force() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "implement auth");
+
+        db.conn
+            .execute(
+                "INSERT INTO comments (id, issue_id, actor, text, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params!["pn-comment01", issue.id, "agent", "note", now()],
+            )
+            .unwrap();
+
+        let result = db.delete_issue(&issue.id, false);
+        assert!(matches!(result, Err(PensaError::DeleteRequiresForce(_))));
+    }
+
+    #[test]
+    fn preview_reports_changed_rows_and_rolls_back() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+        db.add_dep(&b.id, &a.id, "alice").unwrap();
+
+        let ((), changes) = db.preview(|db| db.delete_issue(&a.id, true)).unwrap();
+        let deleted: Vec<(&str, &str)> = changes
+            .iter()
+            .filter(|c| c.table != "events")
+            .map(|c| {
+                assert_eq!(c.action, RowAction::Delete);
+                (c.table.as_str(), c.key.as_str())
+            })
+            .collect();
+        let dep_key = format!("{} {}", b.id, a.id);
+        assert_eq!(
+            deleted,
+            [("issues", a.id.as_str()), ("deps", dep_key.as_str())]
+        );
+        assert!(changes.iter().any(|c| c.table == "events"));
+        assert_eq!(db.list_deps(&b.id).unwrap().len(), 1);
+
+        let result = db.preview(|db| db.delete_issue(&a.id, false));
+        assert!(matches!(result, Err(PensaError::DeleteRequiresForce(_))));
+        db.get_issue_only(&a.id).unwrap();
+    }
+
+    #[test]
+    fn force_delete_cascades() {
+        let (db, _dir) = open_temp_db();
+        let issue_a = create_task(&db, "task A");
+        let issue_b = create_task(&db, "task B");
+
+        db.conn
+            .execute(
+                "INSERT INTO deps (issue_id, depends_on_id) VALUES (?1, ?2)",
+                rusqlite::params![issue_b.id, issue_a.id],
+            )
+            .unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO comments (id, issue_id, actor, text, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params!["pn-comment01", issue_a.id, "agent", "note", now()],
+            )
+            .unwrap();
+
+        db.delete_issue(&issue_a.id, true).unwrap();
+
+        assert!(matches!(
+            db.get_issue_only(&issue_a.id),
+            Err(PensaError::NotFound(_))
+        ));
+
+        let dep_count: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM deps WHERE issue_id = ?1 OR depends_on_id = ?1",
+                rusqlite::params![issue_a.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(dep_count, 0);
+
+        let comment_count: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM comments WHERE issue_id = ?1",
+                rusqlite::params![issue_a.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(comment_count, 0);
+
+        let event_count: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM events WHERE issue_id = ?1",
+                rusqlite::params![issue_a.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(event_count, 0);
+    }
+
+    // --- Phase 6: Query tests ---
+
+    fn create_issue_with(db: &Db, title: &str, issue_type: IssueType, priority: Priority) -> Issue {
+        db.create_issue(&CreateIssueParams {
+            title: title.into(),
+            issue_type,
+            priority,
+            description: None,
+            spec: None,
+            fixes: None,
+            assignee: None,
+            deps: vec![],
+            labels: Vec::new(),
+            actor: "test-agent".into(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn stale_revisions_are_refused() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "contended");
+        assert_eq!(issue.rev, 1);
+        let claimed = db.claim_issue(&issue.id, "agent-1").unwrap();
+        assert_eq!(claimed.rev, 2, "every write bumps the revision");
+
+        let stale = db.update_issue(
+            &issue.id,
+            &UpdateFields {
+                title: Some("clobbered".into()),
+                expect_rev: Some(1),
+                ..Default::default()
+            },
+            "agent-2",
+        );
+        match stale {
+            Err(PensaError::RevisionMismatch { expected, current }) => {
+                assert_eq!(expected, 1);
+                assert_eq!((current.rev, current.title.as_str()), (2, "contended"));
+            }
+            other => panic!("expected a revision mismatch, got {other:?}"),
+        }
+
+        let updated = db
+            .update_issue(
+                &issue.id,
+                &UpdateFields {
+                    title: Some("renamed".into()),
+                    expect_rev: Some(2),
+                    ..Default::default()
+                },
+                "agent-1",
+            )
+            .unwrap();
+        assert_eq!((updated.rev, updated.title.as_str()), (3, "renamed"));
+    }
+
+    #[test]
+    fn create_with_bad_deps_leaves_nothing_behind() {
+        let (db, _dir) = open_temp_db();
+        let parent = create_task(&db, "parent");
+        let params = |deps: Vec<String>| CreateIssueParams {
+            title: "child".into(),
+            issue_type: IssueType::Task,
+            priority: Priority::P2,
+            description: None,
+            spec: None,
+            fixes: None,
+            assignee: None,
+            deps,
+            labels: Vec::new(),
+            actor: "test-agent".into(),
+        };
+
+        let missing = db.create_issue(&params(vec![parent.id.clone(), "pn-00000000".into()]));
+        assert!(matches!(missing, Err(PensaError::NotFound(id)) if id == "pn-00000000"));
+        let count = |table: &str| -> i64 {
+            db.conn
+                .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        assert_eq!((count("issues"), count("deps"), count("events")), (1, 0, 1));
+
+        let child = db
+            .create_issue(&params(vec![parent.id.clone(), parent.id.clone()]))
+            .unwrap();
+        let deps = db.get_issue(&child.id).unwrap().deps;
+        assert_eq!(deps.len(), 1, "repeated deps are added once");
+        assert_eq!(deps[0].id, parent.id);
+    }
+
+    #[test]
+    fn create_applies_labels_in_the_same_transaction() {
+        let (db, _dir) = open_temp_db();
+        let params = |labels: &[&str]| CreateIssueParams {
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            ..params_for("labeled")
+        };
+
+        let issue = db.create_issue(&params(&["backend", "triage"])).unwrap();
+        assert_eq!(db.list_labels(&issue.id).unwrap(), ["backend", "triage"]);
+
+        let count = |table: &str| -> i64 {
+            db.conn
+                .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        let before = (count("issues"), count("labels"));
+        assert!(matches!(
+            db.create_issue(&params(&["backend", "  "])),
+            Err(PensaError::InvalidArgument(_))
+        ));
+        assert_eq!((count("issues"), count("labels")), before);
+    }
+
+    #[test]
+    fn create_accepts_external_deps() {
+        let (db, _dir) = open_temp_db();
+        let parent = create_task(&db, "parent");
+        let url = "https://github.com/acme/api/issues/12";
+        let child = db
+            .create_issue(&CreateIssueParams {
+                title: "child".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: None,
+                spec: None,
+                fixes: None,
+                assignee: None,
+                deps: vec![url.into(), parent.id.clone(), "OPS-17".into(), url.into()],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+
+        let detail = db.get_issue(&child.id).unwrap();
+        assert_eq!(detail.deps.len(), 1);
+        assert_eq!(detail.deps[0].id, parent.id);
+        let targets: Vec<&str> = detail
+            .external_deps
+            .iter()
+            .map(|d| d.target.as_str())
+            .collect();
+        assert_eq!(targets.len(), 2);
+        assert!(targets.contains(&url) && targets.contains(&"OPS-17"));
+        assert!(
+            db.blocked_issues()
+                .unwrap()
+                .iter()
+                .any(|i| i.id == child.id)
+        );
+    }
+
+    #[test]
+    fn project_config_shapes_creation_and_closing() {
+        let (db, _dir) = open_temp_db();
+        fs::write(
+            db.pensa_dir.join("config.toml"),
+            "[issues]\ntypes = [\"task\", \"bug\"]\nid_prefix = \"acme\"\n\n[workflow]\nrequire_claim = true\n",
+        )
+        .unwrap();
+
+        let chore = db.create_issue(&CreateIssueParams {
+            issue_type: IssueType::Chore,
+            ..params_for("cleanup")
+        });
+        assert!(matches!(chore, Err(PensaError::InvalidArgument(_))));
+
+        let other = create_task(&db, "other");
+        assert!(other.id.starts_with("acme-"), "{}", other.id);
+        let issue = db
+            .create_issue(&CreateIssueParams {
+                description: Some(format!("after {}", other.id)),
+                ..params_for("mentions")
+            })
+            .unwrap();
+        assert_eq!(
+            db.get_issue(&issue.id).unwrap().references,
+            std::slice::from_ref(&other.id)
+        );
+
+        let unclaimed = db.close_issue(&issue.id, None, None, false, "test-agent");
+        assert!(matches!(
+            unclaimed,
+            Err(PensaError::InvalidStatusTransition { ref from, .. }) if from == "open"
+        ));
+        let via_update = db.update_issue(
+            &issue.id,
+            &UpdateFields {
+                status: Some(Status::Closed),
+                ..Default::default()
+            },
+            "test-agent",
+        );
+        assert!(matches!(
+            via_update,
+            Err(PensaError::InvalidStatusTransition { .. })
+        ));
+        db.close_issue(&other.id, None, None, true, "test-agent")
+            .unwrap();
+
+        db.claim_issue(&issue.id, "test-agent").unwrap();
+        let closed = db
+            .close_issue(&issue.id, None, None, false, "test-agent")
+            .unwrap();
+        assert_eq!(closed.status, Status::Closed);
+    }
+
+    #[test]
+    fn list_with_filters() {
+        let (db, _dir) = open_temp_db();
+
+        let _t1 = create_issue_with(&db, "task p0", IssueType::Task, Priority::P0);
+        let _t2 = create_issue_with(&db, "task p2", IssueType::Task, Priority::P2);
+        let _b1 = create_issue_with(&db, "bug p1", IssueType::Bug, Priority::P1);
+        let closed = create_task(&db, "closed task");
+        db.close_issue(&closed.id, None, None, false, "test-agent")
+            .unwrap();
+
+        // No filters — returns all 4
+        let all = db.list_issues(&ListFilters::default()).unwrap();
+        assert_eq!(all.len(), 4);
+        // Default sort: priority ASC — p0 first
+        assert_eq!(all[0].priority, Priority::P0);
+
+        // Filter by status=open
+        let open = db
+            .list_issues(&ListFilters {
+                status: Some(Status::Open),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(open.len(), 3);
+        assert!(open.iter().all(|i| i.status == Status::Open));
+
+        // Filter by issue_type=bug
+        let bugs = db
+            .list_issues(&ListFilters {
+                issue_type: Some(IssueType::Bug),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(bugs.len(), 1);
+        assert_eq!(bugs[0].title, "bug p1");
+
+        // Filter by priority
+        let p0s = db
+            .list_issues(&ListFilters {
+                priority: Some(Priority::P0),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(p0s.len(), 1);
+        assert_eq!(p0s[0].title, "task p0");
+
+        // With limit
+        let limited = db
+            .list_issues(&ListFilters {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(limited.len(), 2);
+
+        // Sort by title
+        let by_title = db
+            .list_issues(&ListFilters {
+                sort: Some("title".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_title[0].title, "bug p1");
+
+        // Descending priority, then title
+        let desc = db
+            .list_issues(&ListFilters {
+                sort: Some("-priority,title".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(desc.windows(2).all(|w| w[0].priority >= w[1].priority));
+        assert_eq!(desc.last().unwrap().priority, Priority::P0);
+
+        let bad = db.list_issues(&ListFilters {
+            sort: Some("bogus".into()),
+            ..Default::default()
+        });
+        assert!(matches!(bad, Err(PensaError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn ready_includes_unplanned_bugs() {
+        let (db, _dir) = open_temp_db();
+
+        let bug = create_issue_with(&db, "a bug", IssueType::Bug, Priority::P0);
+        create_issue_with(&db, "a task", IssueType::Task, Priority::P1);
+
+        // Unplanned bug (no fix tasks) should appear in ready
+        let ready = db.ready_issues(&ListFilters::default()).unwrap();
+        assert_eq!(ready.len(), 2);
+        assert_eq!(ready[0].title, "a bug"); // p0 sorts first
+        assert_eq!(ready[1].title, "a task");
+
+        // Create a fix task for the bug → bug becomes planned
+        db.create_issue(&CreateIssueParams {
+            title: "fix the bug".into(),
+            issue_type: IssueType::Task,
+            priority: Priority::P1,
+            description: None,
+            spec: None,
+            fixes: Some(bug.id.clone()),
+            assignee: None,
+            deps: vec![],
+            labels: Vec::new(),
+            actor: "test-agent".into(),
+        })
+        .unwrap();
+
+        // Planned bug should no longer appear in ready
+        let ready2 = db.ready_issues(&ListFilters::default()).unwrap();
+        assert_eq!(ready2.len(), 2); // fix task + original task, but not the bug
+        let ready2_ids: Vec<&str> = ready2.iter().map(|i| i.id.as_str()).collect();
+        assert!(!ready2_ids.contains(&bug.id.as_str()));
+    }
+
+    #[test]
+    fn ready_includes_reopened_bugs_despite_fix_tasks() {
+        let (db, _dir) = open_temp_db();
+
+        let bug = create_issue_with(&db, "a bug", IssueType::Bug, Priority::P0);
+
+        // Create a fix task for the bug
+        let fix = db
+            .create_issue(&CreateIssueParams {
+                title: "fix the bug".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P1,
+                description: None,
+                spec: None,
+                fixes: Some(bug.id.clone()),
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+
+        // Bug is planned (has open fix task) → excluded from ready
+        let ready = db.ready_issues(&ListFilters::default()).unwrap();
+        let ready_ids: Vec<&str> = ready.iter().map(|i| i.id.as_str()).collect();
+        assert!(!ready_ids.contains(&bug.id.as_str()));
+
+        // Close the fix task → bug auto-closes
+        db.close_issue(&fix.id, Some("done"), None, false, "test-agent")
+            .unwrap();
+        let closed_bug = db.get_issue_only(&bug.id).unwrap();
+        assert_eq!(closed_bug.status, Status::Closed);
+
+        // Reopen the bug
+        db.reopen_issue(&bug.id, Some("still broken"), false, "test-agent")
+            .unwrap();
+
+        // Create a new fix task for the reopened bug
+        db.create_issue(&CreateIssueParams {
+            title: "fix the bug again".into(),
+            issue_type: IssueType::Task,
+            priority: Priority::P1,
+            description: None,
+            spec: None,
+            fixes: Some(bug.id.clone()),
+            assignee: None,
+            deps: vec![],
+            labels: Vec::new(),
+            actor: "test-agent".into(),
+        })
+        .unwrap();
+
+        // Reopened bug should appear in ready despite having open fix tasks
+        let ready2 = db.ready_issues(&ListFilters::default()).unwrap();
+        let ready2_ids: Vec<&str> = ready2.iter().map(|i| i.id.as_str()).collect();
+        assert!(
+            ready2_ids.contains(&bug.id.as_str()),
+            "reopened bug should appear in ready even with open fix tasks"
+        );
+    }
+
+    #[test]
+    fn ready_excludes_blocked() {
+        let (db, _dir) = open_temp_db();
+
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+
+        // B depends on A
+        db.conn
+            .execute(
+                "INSERT INTO deps (issue_id, depends_on_id) VALUES (?1, ?2)",
+                rusqlite::params![b.id, a.id],
+            )
+            .unwrap();
+
+        let ready = db.ready_issues(&ListFilters::default()).unwrap();
+        let ready_ids: Vec<&str> = ready.iter().map(|i| i.id.as_str()).collect();
+        assert!(ready_ids.contains(&a.id.as_str()));
+        assert!(!ready_ids.contains(&b.id.as_str()));
+    }
+
+    #[test]
+    fn external_deps_block_until_resolved() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+        let url = "https://github.com/acme/api/issues/42";
+
+        assert_eq!(ExternalKind::classify(url), Some(ExternalKind::Github));
+        assert_eq!(ExternalKind::classify("OPS-17"), Some(ExternalKind::Jira));
+        assert_eq!(
+            ExternalKind::classify("https://example.com/status"),
+            Some(ExternalKind::Url)
+        );
+        assert_eq!(ExternalKind::classify(&b.id), None);
+
+        db.add_dep(&a.id, url, "alice").unwrap();
+        db.add_dep(&a.id, "OPS-17", "alice").unwrap();
+        db.add_dep(&b.id, &a.id, "alice").unwrap();
+
+        let ready = db.ready_issues(&ListFilters::default()).unwrap();
+        assert!(ready.is_empty(), "{ready:?}");
+        let blocked = db.blocked_issues().unwrap();
+        assert!(blocked.iter().any(|i| i.id == a.id));
+        let detail = db.get_issue(&a.id).unwrap();
+        assert_eq!(detail.external_deps.len(), 2);
+        assert!(detail.deps.is_empty());
+
+        let dep = db.resolve_external_dep(&a.id, url, true, "alice").unwrap();
+        assert!(dep.resolved_at.is_some());
+        db.remove_dep(&a.id, "OPS-17", "alice").unwrap();
+        let ready = db.ready_issues(&ListFilters::default()).unwrap();
+        let ready_ids: Vec<&str> = ready.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ready_ids, [a.id.as_str()]);
+
+        assert!(
+            db.resolve_external_dep(&a.id, "OPS-17", true, "alice")
+                .is_err()
+        );
+
+        let exported = db.export_jsonl().unwrap();
+        assert_eq!(exported.external_deps, 1);
+        db.resolve_external_dep(&a.id, url, false, "alice").unwrap();
+        let imported = db.import_jsonl().unwrap();
+        assert_eq!(imported.external_deps, 1);
+        let deps = db.list_external_deps(&a.id).unwrap();
+        assert_eq!(deps[0].kind, ExternalKind::Github);
+        assert!(deps[0].resolved_at.is_some());
+    }
+
+    #[test]
+    fn explain_names_why_issues_are_not_ready() {
+        let (db, _dir) = open_temp_db();
+        let a = create_issue_with(&db, "task A", IssueType::Task, Priority::P0);
+        let b = create_task(&db, "task B");
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap();
+        let bug = create_issue_with(&db, "a bug", IssueType::Bug, Priority::P1);
+        let fix = db
+            .create_issue(&CreateIssueParams {
+                fixes: Some(bug.id.clone()),
+                ..params_for("fix the bug")
+            })
+            .unwrap();
+        let c = create_task(&db, "task C");
+        db.claim_issue(&c.id, "alice").unwrap();
+
+        let excluded = db.explain_not_ready(&ListFilters::default()).unwrap();
+        let reasons = |id: &str| {
+            excluded
+                .iter()
+                .find(|e| e.id == id)
+                .map(|e| e.reasons.clone())
+        };
+        assert_eq!(excluded.len(), 3);
+        assert_eq!(
+            reasons(&b.id),
+            Some(vec![ExclusionReason::BlockedBy {
+                ids: vec![a.id.clone()]
+            }])
+        );
+        assert_eq!(
+            reasons(&bug.id),
+            Some(vec![ExclusionReason::FixInProgress {
+                ids: vec![fix.id.clone()]
+            }])
+        );
+        assert_eq!(
+            reasons(&c.id),
+            Some(vec![ExclusionReason::Claimed {
+                assignee: Some("alice".into())
+            }])
+        );
+
+        let bugs_only = ListFilters {
+            issue_type: Some(IssueType::Bug),
+            ..Default::default()
+        };
+        assert_eq!(
+            db.explain_ready(&a.id, &bugs_only).unwrap().reasons,
+            [ExclusionReason::Filtered {
+                filter: "issue_type".into(),
+                wanted: "bug".into(),
+                actual: Some("task".into()),
+            }]
+        );
+        let first_only = ListFilters {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert!(db.explain_ready(&a.id, &first_only).unwrap().ready);
+        assert_eq!(
+            db.explain_ready(&fix.id, &first_only).unwrap().reasons,
+            [ExclusionReason::PastLimit { limit: 1 }]
+        );
+    }
+
+    #[test]
+    fn blocked_returns_blocked() {
+        let (db, _dir) = open_temp_db();
+
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+
+        // B depends on A (A is open, so B is blocked)
+        db.conn
+            .execute(
+                "INSERT INTO deps (issue_id, depends_on_id) VALUES (?1, ?2)",
+                rusqlite::params![b.id, a.id],
+            )
+            .unwrap();
+
+        let blocked = db.blocked_issues().unwrap();
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].id, b.id);
+    }
+
+    #[test]
+    fn search_case_insensitive() {
+        let (db, _dir) = open_temp_db();
+
+        db.create_issue(&CreateIssueParams {
+            title: "login crash on Safari".into(),
+            issue_type: IssueType::Bug,
+            priority: Priority::P0,
+            description: Some("user sees blank screen".into()),
+            spec: None,
+            fixes: None,
+            assignee: None,
+            deps: vec![],
+            labels: Vec::new(),
+            actor: "test-agent".into(),
+        })
+        .unwrap();
+        create_task(&db, "implement auth");
+
+        // Case-insensitive search on title
+        let results = db.search_issues("LOGIN").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "login crash on Safari");
+
+        // Search on description
+        let results = db.search_issues("blank screen").unwrap();
+        assert_eq!(results.len(), 1);
+
+        // No match
+        let results = db.search_issues("nonexistent").unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn count_basic() {
+        let (db, _dir) = open_temp_db();
+
+        create_task(&db, "task 1");
+        create_task(&db, "task 2");
+        let closed = create_task(&db, "task 3");
+        db.close_issue(&closed.id, None, None, false, "test-agent")
+            .unwrap();
+
+        // Count non-closed
+        let result = db.count_issues(&[], None).unwrap();
+        assert_eq!(result["count"], 2);
+
+        // Count grouped by status
+        let result = db.count_issues(&["status"], None).unwrap();
+        assert_eq!(result["total"], 3);
+        let groups = result["groups"].as_array().unwrap();
+        assert!(!groups.is_empty());
+    }
+
+    #[test]
+    fn resolutions_are_recorded_filtered_and_counted() {
+        let (db, _dir) = open_temp_db();
+        let fixed = create_task(&db, "fixed");
+        let dup = create_task(&db, "dup");
+        let plain = create_task(&db, "plain");
+        create_task(&db, "open");
+        db.close_issue(&fixed.id, None, Some(Resolution::Fixed), false, "a")
+            .unwrap();
+        let closed = db
+            .close_issue(
+                &dup.id,
+                Some("same as fixed"),
+                Some(Resolution::Duplicate),
+                false,
+                "a",
+            )
+            .unwrap();
+        assert_eq!(closed.resolution, Some(Resolution::Duplicate));
+        db.close_issue(&plain.id, Some("done"), None, false, "a")
+            .unwrap();
+
+        let dups = db
+            .list_issues(&ListFilters {
+                resolution: Some(Resolution::Duplicate),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(dups.len(), 1);
+        assert_eq!(dups[0].id, dup.id);
+
+        let result = db.count_issues(&["resolution"], None).unwrap();
+        assert_eq!(
+            result["groups"],
+            serde_json::json!([
+                {"key": "-", "count": 2},
+                {"key": "duplicate", "count": 1},
+                {"key": "fixed", "count": 1},
+            ])
+        );
+        let result = db.count_issues(&[], Some(Resolution::Fixed)).unwrap();
+        assert_eq!(result["count"], 1);
+
+        let history = db.issue_history(&dup.id).unwrap();
+        let close = history.iter().find(|e| e.event_type == "closed").unwrap();
+        assert_eq!(close.detail.as_deref(), Some("duplicate: same as fixed"));
+
+        let reopened = db.reopen_issue(&dup.id, None, false, "a").unwrap();
+        assert_eq!(reopened.resolution, None);
+
+        db.export_jsonl().unwrap();
+        db.import_jsonl().unwrap();
+        assert_eq!(
+            db.get_issue_only(&fixed.id).unwrap().resolution,
+            Some(Resolution::Fixed)
+        );
+    }
+
+    #[test]
+    fn open_adds_resolution_column_to_existing_database() {
+        let dir = TempDir::new().unwrap();
+        let data_dir = dir.path().join("data");
+        fs::create_dir_all(&data_dir).unwrap();
+        Connection::open(data_dir.join("db.sqlite"))
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE issues (
+                    id TEXT PRIMARY KEY, title TEXT NOT NULL, description TEXT,
+                    issue_type TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'open',
+                    priority TEXT NOT NULL DEFAULT 'p2', spec TEXT, fixes TEXT,
+                    assignee TEXT, created_at TEXT NOT NULL, updated_at TEXT NOT NULL,
+                    closed_at TEXT, close_reason TEXT
+                );",
+            )
+            .unwrap();
+
+        let db = Db::open_with_data_dir(dir.path().join(".pensa"), data_dir).unwrap();
+        let issue = create_task(&db, "after upgrade");
+        let closed = db
+            .close_issue(&issue.id, None, Some(Resolution::Obsolete), false, "a")
+            .unwrap();
+        assert_eq!(closed.resolution, Some(Resolution::Obsolete));
+    }
+
+    #[test]
+    fn order_clause_compiles_multiple_keys() {
+        assert_eq!(
+            order_clause("priority").unwrap(),
+            "ORDER BY priority ASC, created_at ASC, id ASC"
+        );
+        assert_eq!(
+            order_clause("-priority, updated_at").unwrap(),
+            "ORDER BY priority DESC, updated_at ASC, created_at ASC, id ASC"
+        );
+        assert_eq!(
+            order_clause("-created_at").unwrap(),
+            "ORDER BY created_at DESC, id ASC"
+        );
+        for bad in [
+            "",
+            "priority,",
+            "rowid",
+            "priority; DROP TABLE issues",
+            "title,-title",
+        ] {
+            assert!(
+                matches!(order_clause(bad), Err(PensaError::InvalidArgument(_))),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn inbox_shows_others_events_on_watched_issues_until_read() {
+        let (db, _dir) = open_temp_db();
+        let watched = create_task(&db, "watched");
+        let other = create_task(&db, "other");
+        assert_eq!(db.watch_issue(&watched.id, "alice").unwrap(), vec!["alice"]);
+        db.watch_issue(&watched.id, "alice").unwrap();
+        assert_eq!(db.watched_issues("alice").unwrap().len(), 1);
+
+        db.add_comment(&watched.id, "agent-1", "working on it")
+            .unwrap();
+        db.add_comment(&watched.id, "alice", "thanks").unwrap();
+        db.add_comment(&other.id, "agent-1", "unrelated").unwrap();
+
+        let inbox = db.inbox("alice").unwrap();
+        assert_eq!(inbox.last_read, 0);
+        assert_eq!(inbox.entries.len(), 1);
+        assert_eq!(inbox.entries[0].event.event_type, "commented");
+        assert_eq!(inbox.entries[0].title, "watched");
+
+        let up_to = inbox.entries[0].event.id;
+        assert_eq!(db.mark_inbox_read("alice", up_to).unwrap(), up_to);
+        assert_eq!(db.mark_inbox_read("alice", 0).unwrap(), up_to);
+        assert!(db.inbox("alice").unwrap().entries.is_empty());
+
+        db.claim_issue(&watched.id, "agent-2").unwrap();
+        let inbox = db.inbox("alice").unwrap();
+        assert_eq!(inbox.entries.len(), 1);
+        assert_eq!(inbox.entries[0].event.event_type, "claimed");
+
+        assert!(db.unwatch_issue(&watched.id, "alice").unwrap().is_empty());
+        assert!(db.inbox("alice").unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn mentions_reach_the_inbox_without_watching() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "login lockout");
+        db.add_comment(
+            &issue.id,
+            "sgf-build-1",
+            "@wlorey should lockout be per IP? cc @sgf-build-1",
+        )
+        .unwrap();
+        db.add_comment(&issue.id, "sgf-build-1", "@alice unrelated")
+            .unwrap();
+
+        let inbox = db.inbox("wlorey").unwrap();
+        assert_eq!(inbox.entries.len(), 1);
+        let event = &inbox.entries[0].event;
+        assert_eq!(event.event_type, "mentioned");
+        assert_eq!(event.actor.as_deref(), Some("sgf-build-1"));
+        assert_eq!(
+            event.detail.as_deref(),
+            Some("@wlorey: @wlorey should lockout be per IP? cc @sgf-build-1")
+        );
+        assert!(db.inbox("sgf-build-1").unwrap().entries.is_empty());
+
+        db.add_comment(&issue.id, "bob", "ping @wlore").unwrap();
+        assert_eq!(db.inbox("wlorey").unwrap().entries.len(), 1);
+
+        db.add_comment(&issue.id, "wlorey", "looking").unwrap();
+        db.alias_actor("William", "wlorey").unwrap();
+        db.add_comment(&issue.id, "bob", "@William any news?")
+            .unwrap();
+        let inbox = db.inbox("wlorey").unwrap();
+        assert_eq!(inbox.entries.len(), 2);
+        assert_eq!(db.inbox("William").unwrap().entries.len(), 2);
+    }
+
+    #[test]
+    fn snapshot_diffs_sort_issues_by_what_happened() {
+        let (db, _dir) = open_temp_db();
+        let fixed = create_task(&db, "fixed");
+        let tweaked = create_task(&db, "tweaked");
+        let dropped = create_task(&db, "dropped");
+        let start = db.create_snapshot(Some("start")).unwrap();
+        assert!(start.id.starts_with("sn-"));
+        assert_eq!(start.issue_count, 3);
+
+        db.close_issue(&fixed.id, None, Some(Resolution::Fixed), false, "agent")
+            .unwrap();
+        db.update_issue(
+            &tweaked.id,
+            &UpdateFields {
+                priority: Some(Priority::P0),
+                assignee: Some("bob".into()),
+                ..Default::default()
+            },
+            "agent",
+        )
+        .unwrap();
+        db.delete_issue(&dropped.id, true).unwrap();
+        let quick = create_task(&db, "quick");
+        db.close_issue(&quick.id, None, None, false, "agent")
+            .unwrap();
+
+        let ids = |issues: &[Issue]| {
+            let mut ids: Vec<String> = issues.iter().map(|i| i.id.clone()).collect();
+            ids.sort();
+            ids
+        };
+        let diff = db.diff_snapshots("start", None).unwrap();
+        assert_eq!(
+            (diff.from.as_str(), diff.to.as_str()),
+            (start.id.as_str(), "now")
+        );
+        assert_eq!(ids(&diff.created), [quick.id.as_str()]);
+        let mut closed = vec![fixed.id.clone(), quick.id.clone()];
+        closed.sort();
+        assert_eq!(ids(&diff.closed), closed);
+        assert_eq!(ids(&diff.deleted), [dropped.id.as_str()]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].id, tweaked.id);
+        assert_eq!(
+            diff.changed[0].changes,
+            [
+                FieldChange {
+                    field: "priority".into(),
+                    from: Some("p2".into()),
+                    to: Some("p0".into()),
+                },
+                FieldChange {
+                    field: "assignee".into(),
+                    from: None,
+                    to: Some("bob".into()),
+                },
+            ]
+        );
+
+        let end = db.create_snapshot(None).unwrap();
+        assert_eq!(
+            db.diff_snapshots(&start.id, Some(&end.id))
+                .unwrap()
+                .closed
+                .len(),
+            2
+        );
+        assert_eq!(db.list_snapshots().unwrap().len(), 2);
+        assert!(matches!(
+            db.diff_snapshots("nope", None),
+            Err(PensaError::NotFound(_))
+        ));
+
+        let later = db.diff_since(parse_date("2999-01-01").unwrap()).unwrap();
+        assert_eq!(later.from, end.id);
+        assert!(later.created.is_empty() && later.changed.is_empty());
+        assert!(matches!(
+            db.diff_since(parse_date("2000-01-01T00:00:00Z").unwrap()),
+            Err(PensaError::InvalidArgument(_))
+        ));
+        assert!(parse_date("yesterday").is_err());
+    }
+
+    #[test]
+    fn webhooks_see_only_new_events_matching_their_filters() {
+        let (db, _dir) = open_temp_db();
+        let before = create_task(&db, "before");
+        assert!(matches!(
+            db.add_webhook("http://localhost/hook", &["shipped".to_string()], None),
+            Err(PensaError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            db.add_webhook("localhost/hook", &[], None),
+            Err(PensaError::InvalidArgument(_))
+        ));
+        let all = db.add_webhook("http://localhost/all", &[], None).unwrap();
+        let closes = db
+            .add_webhook(
+                "https://example.com/closes",
+                &["closed".to_string(), "claimed".to_string()],
+                Some("auth"),
+            )
+            .unwrap();
+        assert!(closes.id.starts_with("wh-"));
+        assert_eq!(closes.secret.as_deref().map(str::len), Some(64));
+        assert_ne!(all.secret, closes.secret);
+
+        let auth = db
+            .create_issue(&CreateIssueParams {
+                title: "auth task".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: None,
+                spec: Some("auth".into()),
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+        db.claim_issue(&auth.id, "agent-1").unwrap();
+        db.close_issue(&before.id, None, None, true, "agent-1")
+            .unwrap();
+
+        let types = |hook: &Webhook| -> Vec<String> {
+            db.pending_webhook_events(hook, 10)
+                .unwrap()
+                .into_iter()
+                .map(|(e, i)| format!("{} {}", e.event_type, i.title))
+                .collect()
+        };
+        assert_eq!(
+            types(&all),
+            ["created auth task", "claimed auth task", "closed before"]
+        );
+        assert_eq!(types(&closes), ["claimed auth task"]);
+
+        db.mark_webhook_delivered(&all.id, db.latest_event_id().unwrap())
+            .unwrap();
+        let all = db.get_webhook(&all.id).unwrap();
+        assert!(types(&all).is_empty());
+        assert_eq!(db.list_webhooks().unwrap().len(), 2);
+
+        db.remove_webhook(&all.id).unwrap();
+        assert!(matches!(
+            db.remove_webhook(&all.id),
+            Err(PensaError::NotFound(_))
+        ));
+        assert_eq!(db.list_webhooks().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn count_by_spec_and_label() {
+        let (db, _dir) = open_temp_db();
+
+        let spec_issue = db
+            .create_issue(&CreateIssueParams {
+                title: "auth task".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: None,
+                spec: Some("auth".into()),
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+        let plain = create_task(&db, "plain task");
+        create_task(&db, "unlabeled task");
+        db.add_label(&spec_issue.id, "backend", "test-agent")
+            .unwrap();
+        db.add_label(&spec_issue.id, "security", "test-agent")
+            .unwrap();
+        db.add_label(&plain.id, "backend", "test-agent").unwrap();
+
+        let result = db.count_issues(&["spec"], None).unwrap();
+        assert_eq!(result["total"], 3);
+        assert_eq!(
+            result["groups"],
+            serde_json::json!([
+                {"key": "-", "count": 2},
+                {"key": "auth", "count": 1},
+            ])
+        );
+
+        let result = db.count_issues(&["label"], None).unwrap();
+        assert_eq!(result["total"], 3);
+        assert_eq!(
+            result["groups"],
+            serde_json::json!([
+                {"key": "-", "count": 1},
+                {"key": "backend", "count": 2},
+                {"key": "security", "count": 1},
+            ])
+        );
+    }
+
+    #[test]
+    fn labels_are_deduplicated_and_survive_export_import() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "task A");
+
+        db.add_label(&issue.id, "backend", "agent-1").unwrap();
+        let labels = db.add_label(&issue.id, " backend ", "agent-1").unwrap();
+        assert_eq!(labels, vec!["backend".to_string()]);
+        assert!(matches!(
+            db.add_label(&issue.id, "  ", "agent-1"),
+            Err(PensaError::InvalidArgument(_))
+        ));
+        assert_eq!(db.get_issue(&issue.id).unwrap().labels, labels);
+
+        let export = db.export_jsonl().unwrap();
+        assert_eq!(export.labels, 1);
+        let import = db.import_jsonl().unwrap();
+        assert_eq!(import.labels, 1);
+
+        let labels = db.remove_label(&issue.id, "backend", "agent-1").unwrap();
+        assert!(labels.is_empty());
+    }
+
+    #[test]
+    fn issue_text_at_reads_back_past_edits() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "Add login");
+        for (title, description) in [("Add login", "Use sessions"), ("Add SSO login", "Use OIDC")] {
+            db.update_issue(
+                &issue.id,
+                &UpdateFields {
+                    title: Some(title.into()),
+                    description: Some(description.into()),
+                    ..Default::default()
+                },
+                "alice",
+            )
+            .unwrap();
+        }
+        // Edits within one second share a timestamp; spread them out.
+        let at = |s: &str| parse_date(s).unwrap();
+        let ids: Vec<i64> = db
+            .issue_history(&issue.id)
+            .unwrap()
+            .iter()
+            .rev()
+            .map(|e| e.id)
+            .collect();
+        for (id, day) in ids.iter().zip(["01", "02", "03"]) {
+            db.conn
+                .execute(
+                    "UPDATE events SET created_at = ?1 WHERE id = ?2",
+                    rusqlite::params![format!("2026-01-{day}T00:00:00Z"), id],
+                )
+                .unwrap();
+        }
+        db.conn
+            .execute(
+                "UPDATE issues SET created_at = '2026-01-01T00:00:00Z' WHERE id = ?1",
+                rusqlite::params![issue.id],
+            )
+            .unwrap();
+
+        let original = db
+            .issue_text_at(&issue.id, at("2026-01-01T12:00:00Z"))
+            .unwrap();
+        assert_eq!(original.title, "Add login");
+        assert_eq!(original.description, None);
+        assert_eq!(original.edited_at, None);
+
+        let first = db.issue_text_at(&issue.id, at("2026-01-02")).unwrap();
+        assert_eq!(first.description.as_deref(), Some("Use sessions"));
+        assert_eq!(first.edited_by.as_deref(), Some("alice"));
+
+        let latest = db.issue_text_at(&issue.id, at("2026-02-01")).unwrap();
+        assert_eq!(latest.title, "Add SSO login");
+        assert_eq!(latest.description.as_deref(), Some("Use OIDC"));
+
+        assert!(matches!(
+            db.issue_text_at(&issue.id, at("2025-12-31")),
+            Err(PensaError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn history_newest_first() {
+        let (db, _dir) = open_temp_db();
+
+        let issue = create_task(&db, "lifecycle test");
+
+        db.update_issue(
+            &issue.id,
+            &UpdateFields {
+                title: Some("updated title".into()),
+                ..Default::default()
+            },
+            "test-agent",
+        )
+        .unwrap();
+
+        db.close_issue(&issue.id, Some("done"), None, false, "test-agent")
+            .unwrap();
+
+        let history = db.issue_history(&issue.id).unwrap();
+        assert_eq!(history.len(), 3);
+        // Newest first
+        assert_eq!(history[0].event_type, "closed");
+        assert_eq!(history[1].event_type, "updated");
+        assert_eq!(history[2].event_type, "created");
+    }
+
+    // --- Phase 7: Dependency tests ---
+
+    #[test]
+    fn add_and_list_deps() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap();
+
+        let deps = db.list_deps(&b.id).unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].id, a.id);
+    }
+
+    #[test]
+    fn cycle_detection_rejects() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+        let c = create_task(&db, "task C");
+
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap(); // B depends on A
+        db.add_dep(&c.id, &b.id, "test-agent").unwrap(); // C depends on B
+
+        // A depends on C would create A->C->B->A cycle
+        let result = db.add_dep(&a.id, &c.id, "test-agent");
+        assert!(matches!(result, Err(PensaError::CycleDetected)));
+    }
+
+    #[test]
+    fn dep_tree_down() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+        let c = create_task(&db, "task C");
+
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap(); // B depends on A
+        db.add_dep(&c.id, &b.id, "test-agent").unwrap(); // C depends on B
+
+        // A blocks B blocks C — tree(A, down) returns B at depth 1 and C at depth 2
+        let tree = db.dep_tree(&a.id, "down").unwrap();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].id, b.id);
+        assert_eq!(tree[0].depth, 1);
+        assert_eq!(tree[1].id, c.id);
+        assert_eq!(tree[1].depth, 2);
+    }
+
+    #[test]
+    fn dep_tree_up() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+        let c = create_task(&db, "task C");
+
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap(); // B depends on A
+        db.add_dep(&c.id, &b.id, "test-agent").unwrap(); // C depends on B
+
+        // tree(C, up) returns B at depth 1 and A at depth 2
+        let tree = db.dep_tree(&c.id, "up").unwrap();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].id, b.id);
+        assert_eq!(tree[0].depth, 1);
+        assert_eq!(tree[1].id, a.id);
+        assert_eq!(tree[1].depth, 2);
+    }
+
+    #[test]
+    fn remove_dep_works() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap();
+        assert_eq!(db.list_deps(&b.id).unwrap().len(), 1);
+
+        db.remove_dep(&b.id, &a.id, "test-agent").unwrap();
+        assert!(db.list_deps(&b.id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn detect_cycles_empty() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+        let c = create_task(&db, "task C");
+
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap();
+        db.add_dep(&c.id, &b.id, "test-agent").unwrap();
+
+        // The cycle A->C was rejected, so detect_cycles should return empty
+        let _ = db.add_dep(&a.id, &c.id, "test-agent");
+
+        let cycles = db.detect_cycles().unwrap();
+        assert!(cycles.is_empty());
+    }
+
+    // Phase 8 tests
+
+    #[test]
+    fn add_and_list_comments() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "test issue");
+
+        let comment = db.add_comment(&issue.id, "alice", "looks good").unwrap();
+        assert!(comment.id.starts_with("pn-"));
+        assert_eq!(comment.issue_id, issue.id);
+        assert_eq!(comment.actor, "alice");
+        assert_eq!(comment.text, "looks good");
+
+        let comments = db.list_comments(&issue.id).unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].text, "looks good");
+
+        // Verify "commented" event was logged
+        let events = db.issue_history(&issue.id).unwrap();
+        assert!(events.iter().any(|e| e.event_type == "commented"));
+    }
+
+    #[test]
+    fn export_import_roundtrip() {
+        let (db, _dir) = open_temp_db();
+
+        let a = create_task(&db, "task A");
+        let b = db
+            .create_issue(&CreateIssueParams {
+                title: "task B".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P1,
+                description: Some("desc B".into()),
+                spec: Some("auth".into()),
+                fixes: None,
+                assignee: Some("bob".into()),
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap();
+
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap();
+        db.add_comment(&a.id, "alice", "observation 1").unwrap();
+
+        let export_result = db.export_jsonl().unwrap();
+        assert_eq!(export_result.status, "ok");
+        assert_eq!(export_result.issues, 2);
+        assert_eq!(export_result.deps, 1);
+        assert_eq!(export_result.comments, 1);
+
+        // Import clears and reimports
+        let import_result = db.import_jsonl().unwrap();
+        assert_eq!(import_result.status, "ok");
+        assert_eq!(import_result.issues, 2);
+        assert_eq!(import_result.deps, 1);
+        assert_eq!(import_result.comments, 1);
+
+        // Verify data is intact
+        let issues = db.list_issues(&ListFilters::default()).unwrap();
+        assert_eq!(issues.len(), 2);
+
+        let issue_b = db.get_issue_only(&b.id).unwrap();
+        assert_eq!(issue_b.title, "task B");
+        assert_eq!(issue_b.priority, Priority::P1);
+        assert_eq!(issue_b.description.as_deref(), Some("desc B"));
+        assert_eq!(issue_b.spec.as_deref(), Some("auth"));
+        assert_eq!(issue_b.assignee.as_deref(), Some("bob"));
+
+        let deps = db.list_deps(&b.id).unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].id, a.id);
+
+        let comments = db.list_comments(&a.id).unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].text, "observation 1");
+    }
+
+    #[test]
+    fn jsonl_sorted() {
+        let (db, _dir) = open_temp_db();
+
+        // Create issues in reverse order with slight delay
+        let c = create_task(&db, "task C");
+        let b = create_task(&db, "task B");
+        let a = create_task(&db, "task A");
+
+        db.add_dep(&b.id, &a.id, "test-agent").unwrap();
+        db.add_dep(&c.id, &a.id, "test-agent").unwrap();
+        db.add_comment(&a.id, "alice", "first").unwrap();
+        db.add_comment(&b.id, "bob", "second").unwrap();
+
+        db.export_jsonl().unwrap();
+
+        // Read and verify sorting
+        let issues_content = fs::read_to_string(db.pensa_dir.join("issues.jsonl")).unwrap();
+        let issue_lines: Vec<Issue> = issues_content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        for w in issue_lines.windows(2) {
+            assert!(w[0].created_at <= w[1].created_at);
+        }
+
+        let deps_content = fs::read_to_string(db.pensa_dir.join("deps.jsonl")).unwrap();
+        let dep_lines: Vec<crate::types::Dep> = deps_content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        for w in dep_lines.windows(2) {
+            assert!(
+                (w[0].issue_id.as_str(), w[0].depends_on_id.as_str())
+                    <= (w[1].issue_id.as_str(), w[1].depends_on_id.as_str())
+            );
+        }
+
+        let comments_content = fs::read_to_string(db.pensa_dir.join("comments.jsonl")).unwrap();
+        let comment_lines: Vec<Comment> = comments_content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        for w in comment_lines.windows(2) {
+            assert!(w[0].created_at <= w[1].created_at);
+        }
+    }
+
+    #[test]
+    fn auto_import_on_open() {
+        let dir = TempDir::new().unwrap();
+        let pensa_dir = dir.path().join(".pensa");
+        let data_dir = dir.path().join("data");
+
+        // First, create data and export
+        {
+            let db = Db::open_with_data_dir(pensa_dir.clone(), data_dir.clone()).unwrap();
+            create_task(&db, "task A");
+            create_task(&db, "task B");
+            db.export_jsonl().unwrap();
+        }
+
+        // Delete the DB file
+        fs::remove_file(data_dir.join("db.sqlite")).unwrap();
+
+        // Re-open — should auto-import from JSONL
+        let db = Db::open_with_data_dir(pensa_dir, data_dir).unwrap();
+        let issues = db.list_issues(&ListFilters::default()).unwrap();
+        assert_eq!(issues.len(), 2);
+    }
+
+    #[test]
+    fn spec_status_rolls_up_per_spec() {
+        let (db, _dir) = open_temp_db();
+        let spec_task = |title: &str, spec: &str, priority: Priority| {
+            db.create_issue(&CreateIssueParams {
+                title: title.into(),
+                issue_type: IssueType::Task,
+                priority,
+                description: None,
+                spec: Some(spec.into()),
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap()
+        };
+        let a1 = spec_task("a1", "auth", Priority::P0);
+        let a2 = spec_task("a2", "auth", Priority::P1);
+        let a3 = spec_task("a3", "auth", Priority::P2);
+        spec_task("b1", "billing", Priority::P2);
+        create_task(&db, "no spec");
+
+        db.claim_issue(&a2.id, "agent-1").unwrap();
+        db.close_issue(&a3.id, None, None, false, "agent-1")
+            .unwrap();
+        db.add_dep(&a2.id, &a1.id, "agent-1").unwrap();
+
+        let entries = db.spec_status().unwrap();
+        assert_eq!(entries.len(), 2);
+        let auth = &entries[0];
+        assert_eq!(auth.spec, "auth");
+        assert_eq!((auth.open, auth.in_progress, auth.closed), (1, 1, 1));
+        assert_eq!((auth.open_p0, auth.open_p1, auth.blocked), (1, 1, 1));
+        assert_eq!(entries[1].spec, "billing");
+
+        let detail = db.spec_detail("auth").unwrap();
+        let ids: Vec<&str> = detail.outstanding.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec![a1.id.as_str(), a2.id.as_str()]);
+
+        let empty = db.spec_detail("missing").unwrap();
+        assert_eq!(empty.summary.open, 0);
+        assert!(empty.outstanding.is_empty());
+    }
+
+    #[test]
+    fn doctor_reports_open_issues_with_missing_spec_files() {
+        let (db, dir) = open_temp_db();
+        let specs_dir = dir.path().join("specs");
+        fs::create_dir_all(&specs_dir).unwrap();
+        fs::write(specs_dir.join("auth.md"), "# Auth").unwrap();
+
+        let spec_task = |title: &str, spec: &str| {
+            db.create_issue(&CreateIssueParams {
+                title: title.into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: None,
+                spec: Some(spec.into()),
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "test-agent".into(),
+            })
+            .unwrap()
+        };
+        spec_task("present", "auth");
+        let gone = spec_task("gone", "billing");
+        let closed = spec_task("closed", "billing");
+        db.close_issue(&closed.id, None, None, false, "test")
+            .unwrap();
+
+        let report = db.doctor(false, None, None).unwrap();
+        assert!(!report.findings.iter().any(|f| f.check == "missing_spec"));
+
+        let report = db.doctor(false, None, Some(&specs_dir)).unwrap();
+        let missing: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.check == "missing_spec")
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].issue_id.as_deref(), Some(gone.id.as_str()));
+        assert!(missing[0].message.contains("billing"));
+    }
+
+    #[test]
+    fn link_commit_is_idempotent_and_shown_in_detail() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "task A");
+
+        let link = db
+            .link_commit(&issue.id, "abc123", Some("fix login"), "agent-1")
+            .unwrap();
+        assert_eq!(link.sha, "abc123");
+        assert_eq!(link.summary.as_deref(), Some("fix login"));
+        db.link_commit(&issue.id, "abc123", Some("fix login"), "agent-1")
+            .unwrap();
+
+        let detail = db.get_issue(&issue.id).unwrap();
+        assert_eq!(detail.commits.len(), 1);
+
+        let linked_events = db
+            .issue_history(&issue.id)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.event_type == "commit_linked")
+            .count();
+        assert_eq!(linked_events, 1, "relinking should not log a second event");
+
+        assert!(matches!(
+            db.link_commit("pn-00000000", "abc123", None, "agent-1"),
+            Err(PensaError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn commits_survive_export_import_and_cascade_on_delete() {
+        let (db, _dir) = open_temp_db();
+        let issue = create_task(&db, "task A");
+        db.link_commit(&issue.id, "abc123", None, "agent-1")
+            .unwrap();
+
+        let export = db.export_jsonl().unwrap();
+        assert_eq!(export.commits, 1);
+        assert!(db.pensa_dir.join("commits.jsonl").exists());
+
+        let import = db.import_jsonl().unwrap();
+        assert_eq!(import.commits, 1);
+        assert_eq!(db.list_commits(&issue.id).unwrap().len(), 1);
+
+        db.delete_issue(&issue.id, true).unwrap();
+        let remaining: i64 = db
+            .conn
+            .query_row("SELECT COUNT(*) FROM commits", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn workload_counts_unclosed_issues_per_assignee() {
+        let (db, _dir) = open_temp_db();
+        for title in ["a", "b", "c"] {
+            let issue = create_task(&db, title);
+            db.claim_issue(&issue.id, "alice").unwrap();
+        }
+        let urgent = create_issue_with(&db, "urgent", IssueType::Bug, Priority::P0);
+        db.update_issue(
+            &urgent.id,
+            &UpdateFields {
+                assignee: Some("bob".into()),
+                ..Default::default()
+            },
+            "lead",
+        )
+        .unwrap();
+        let done = create_task(&db, "done");
+        db.claim_issue(&done.id, "bob").unwrap();
+        db.close_issue(&done.id, None, None, false, "bob").unwrap();
+        create_task(&db, "nobody");
+
+        let report = db.workload(2).unwrap();
+        assert_eq!(report.unassigned, 1);
+        assert_eq!(report.assignees.len(), 2);
+        let alice = &report.assignees[0];
+        assert_eq!(alice.assignee, "alice");
+        assert_eq!((alice.in_progress, alice.open, alice.active), (3, 0, 3));
+        assert!(alice.overloaded);
+        let bob = &report.assignees[1];
+        assert_eq!((bob.in_progress, bob.open, bob.urgent), (0, 1, 1));
+        assert!(!bob.overloaded);
+    }
+
+    #[test]
+    fn aliased_actors_share_one_identity() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "a");
+        db.claim_issue(&a.id, "wlorey").unwrap();
+        let b = create_task(&db, "b");
+        db.claim_issue(&b.id, "sgf-build-auth-1").unwrap();
+        create_task(&db, "c");
+
+        let names: Vec<String> = db
+            .list_actors()
+            .unwrap()
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert!(names.contains(&"wlorey".to_string()), "{names:?}");
+        assert!(names.contains(&"sgf-build-auth-1".to_string()), "{names:?}");
+
+        db.alias_actor("sgf-build-auth-1", "wlorey").unwrap();
+        db.alias_actor("William Lorey", "sgf-build-auth-1").unwrap();
+        let renamed = db
+            .rename_actor("William Lorey", Some("William Lorey"))
+            .unwrap();
+        assert_eq!(renamed.name, "wlorey");
+        assert_eq!(db.resolve_actor("William Lorey").unwrap(), "wlorey");
+        assert!(db.alias_actor("wlorey", "William Lorey").is_err());
+        assert!(db.rename_actor("nobody", Some("x")).is_err());
+
+        let filters = ListFilters {
+            assignee: Some("sgf-build-auth-1".into()),
+            ..Default::default()
+        };
+        let mut ids: Vec<String> = db
+            .list_issues(&filters)
+            .unwrap()
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        ids.sort();
+        let mut expected = vec![a.id.clone(), b.id.clone()];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let report = db.workload(5).unwrap();
+        assert_eq!(report.assignees.len(), 1);
+        assert_eq!(report.assignees[0].assignee, "wlorey");
+        assert_eq!(
+            report.assignees[0].display_name.as_deref(),
+            Some("William Lorey")
+        );
+        assert_eq!(report.assignees[0].in_progress, 2);
+
+        db.unalias_actor("sgf-build-auth-1").unwrap();
+        assert_eq!(db.list_issues(&filters).unwrap().len(), 1);
+        assert!(db.unalias_actor("wlorey").is_err());
+
+        db.export_jsonl().unwrap();
+        db.import_jsonl().unwrap();
+        assert_eq!(db.resolve_actor("William Lorey").unwrap(), "wlorey");
+    }
+
+    #[test]
+    fn stale_issues_are_unclosed_and_oldest_first() {
+        let (db, _dir) = open_temp_db();
+        let older = create_task(&db, "older");
+        let old = create_task(&db, "old");
+        let closed = create_task(&db, "closed long ago");
+        create_task(&db, "fresh");
+        db.close_issue(&closed.id, None, None, false, "agent-1")
+            .unwrap();
+        db.claim_issue(&old.id, "agent-1").unwrap();
+        for (id, at) in [
+            (&older.id, "2020-01-01T00:00:00Z"),
+            (&old.id, "2020-02-01T00:00:00Z"),
+            (&closed.id, "2020-01-01T00:00:00Z"),
+        ] {
+            db.conn
+                .execute(
+                    "UPDATE issues SET updated_at = ?1 WHERE id = ?2",
+                    rusqlite::params![at, id],
+                )
+                .unwrap();
+        }
+
+        let stale = db.stale_issues(TimeDelta::days(14), None).unwrap();
+        let ids: Vec<&str> = stale.iter().map(|s| s.issue.id.as_str()).collect();
+        assert_eq!(ids, [older.id.as_str(), old.id.as_str()]);
+        assert!(stale[0].age_days > 365);
+
+        let open = db
+            .stale_issues(TimeDelta::days(14), Some(Status::Open))
+            .unwrap();
+        assert_eq!(open.len(), 1);
+
+        let findings = db.stale_issue_findings(TimeDelta::days(14)).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[1].check, "stale_issue");
+        assert!(findings[1].message.contains("in_progress"));
+    }
+
+    #[test]
+    fn escalation_ages_untouched_and_raises_blocking_bugs() {
+        let (db, _dir) = open_temp_db();
+        let old = create_issue_with(&db, "old task", IssueType::Task, Priority::P2);
+        let fresh = create_issue_with(&db, "fresh task", IssueType::Task, Priority::P2);
+        let bug = create_issue_with(&db, "blocking bug", IssueType::Bug, Priority::P3);
+        for title in ["dependent a", "dependent b"] {
+            let dep = create_task(&db, title);
+            db.add_dep(&dep.id, &bug.id, "agent-1").unwrap();
+        }
+        db.conn
+            .execute(
+                "UPDATE issues SET updated_at = '2020-01-01T00:00:00Z' WHERE id IN (?1, ?2)",
+                rusqlite::params![old.id, bug.id],
+            )
+            .unwrap();
+
+        let policy = EscalationPolicy {
+            untouched_after: Some(TimeDelta::days(14)),
+            blocking_dependents: Some(2),
+            blocking_priority: Priority::P1,
+            interval: TimeDelta::hours(1),
+        };
+
+        let preview = db.escalate_priorities(&policy, true).unwrap();
+        assert_eq!(preview.len(), 2);
+        assert_eq!(db.get_issue_only(&old.id).unwrap().priority, Priority::P2);
+
+        let applied = db.escalate_priorities(&policy, false).unwrap();
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied[0].issue_id, bug.id);
+        assert_eq!(applied[0].to, Priority::P1);
+        assert_eq!(db.get_issue_only(&bug.id).unwrap().priority, Priority::P1);
+        assert_eq!(db.get_issue_only(&old.id).unwrap().priority, Priority::P1);
+        assert_eq!(db.get_issue_only(&fresh.id).unwrap().priority, Priority::P2);
+
+        let history = db.issue_history(&old.id).unwrap();
+        assert_eq!(history[0].event_type, "escalated");
+
+        assert!(db.escalate_priorities(&policy, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn references_are_tracked_and_promoted_to_deps() {
+        let (db, _dir) = open_temp_db();
+        let target = create_task(&db, "target");
+        let other = create_task(&db, "other");
+        let source = db
+            .create_issue(&CreateIssueParams {
+                title: "source".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: Some(format!("needs {} first", target.id)),
+                spec: None,
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "agent-1".into(),
+            })
+            .unwrap();
+        db.add_comment(
+            &source.id,
+            "agent-1",
+            &format!("also {} and pn-00000000", other.id),
+        )
+        .unwrap();
+
+        let detail = db.get_issue(&source.id).unwrap();
+        let mut expected = vec![
+            target.id.clone(),
+            other.id.clone(),
+            "pn-00000000".to_string(),
+        ];
+        expected.sort();
+        assert_eq!(detail.references, expected);
+        let referenced_by = db.get_issue(&target.id).unwrap().referenced_by;
+        assert_eq!(referenced_by.len(), 1);
+        assert_eq!(referenced_by[0].id, source.id);
+
+        db.add_dep(&other.id, &source.id, "agent-1").unwrap();
+        let result = db.promote_refs(&source.id, "agent-1").unwrap();
+        assert_eq!(result.added, vec![target.id.clone()]);
+        let reasons: Vec<&str> = result.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert!(reasons.contains(&"would create a cycle"));
+        assert!(reasons.contains(&"issue not found"));
+
+        db.update_issue(
+            &source.id,
+            &UpdateFields {
+                description: Some("no refs".into()),
+                ..Default::default()
+            },
+            "agent-1",
+        )
+        .unwrap();
+        assert!(db.get_issue(&target.id).unwrap().referenced_by.is_empty());
+
+        db.export_jsonl().unwrap();
+        db.import_jsonl().unwrap();
+        assert_eq!(db.list_refs(&source.id).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn doctor_detects_stale() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+
+        db.claim_issue(&a.id, "agent-1").unwrap();
+        db.claim_issue(&b.id, "agent-2").unwrap();
+
+        let report = db.doctor(false, None, None).unwrap();
+        assert_eq!(report.findings.len(), 2);
+        assert!(report.findings.iter().all(|f| f.check == "stale_claim"));
+        assert!(report.fixes_applied.is_empty());
+    }
+
+    #[test]
+    fn doctor_fix_releases() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+
+        db.claim_issue(&a.id, "agent-1").unwrap();
+        db.claim_issue(&b.id, "agent-2").unwrap();
+
+        let report = db.doctor(true, None, None).unwrap();
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.fixes_applied.len(), 1);
+        assert!(report.fixes_applied[0].contains("2 stale claims"));
+
+        // Verify all issues are now open
+        let issue_a = db.get_issue_only(&a.id).unwrap();
+        assert_eq!(issue_a.status, Status::Open);
+        assert!(issue_a.assignee.is_none());
+
+        let issue_b = db.get_issue_only(&b.id).unwrap();
+        assert_eq!(issue_b.status, Status::Open);
+        assert!(issue_b.assignee.is_none());
+    }
+
+    #[test]
+    fn doctor_stale_after_uses_claim_age() {
+        let (db, _dir) = open_temp_db();
+        let old = create_task(&db, "old claim");
+        let fresh = create_task(&db, "fresh claim");
+
+        db.claim_issue(&old.id, "agent-1").unwrap();
+        db.claim_issue(&fresh.id, "agent-2").unwrap();
+        let five_hours_ago = (Utc::now() - TimeDelta::hours(5))
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        db.conn
+            .execute(
+                "UPDATE events SET created_at = ?1 WHERE issue_id = ?2 AND event_type = 'claimed'",
+                rusqlite::params![five_hours_ago, old.id],
+            )
+            .unwrap();
+
+        let report = db.doctor(true, Some(TimeDelta::hours(4)), None).unwrap();
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(
+            report.findings[0].issue_id.as_deref(),
+            Some(old.id.as_str())
+        );
+        let age = report.findings[0].claim_age_secs.unwrap();
+        assert!(age >= 5 * 3600, "claim age should be ~5h, got {age}s");
+        assert!(report.findings[0].message.contains("5h0m ago"));
+        assert!(report.fixes_applied[0].contains("1 stale claims"));
+
+        assert_eq!(db.get_issue_only(&old.id).unwrap().status, Status::Open);
+        assert_eq!(
+            db.get_issue_only(&fresh.id).unwrap().status,
+            Status::InProgress
+        );
+    }
+
+    #[test]
+    fn doctor_detects_drift_all_entity_files() {
+        let (db, _dir) = open_temp_db();
+        let a = create_task(&db, "task A");
+        let b = create_task(&db, "task B");
+        db.add_dep(&b.id, &a.id, "test").unwrap();
+        db.add_comment(&a.id, "test", "hello").unwrap();
+        db.add_src_ref(&a.id, "src/main.rs", Some("entry"), "test")
+            .unwrap();
+        db.add_doc_ref(&b.id, "README.md", Some("docs"), "test")
+            .unwrap();
+
+        db.export_jsonl().unwrap();
+
+        // No drift yet — should be clean
+        let report = db.doctor(false, None, None).unwrap();
+        let drift_findings: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.check == "jsonl_drift")
+            .collect();
+        assert!(drift_findings.is_empty(), "expected no drift initially");
+
+        // Create a third issue in DB only (no re-export) to cause issues.jsonl drift
+        create_task(&db, "task C");
+        // Add another comment directly to cause comments.jsonl drift
+        db.add_comment(&a.id, "test", "extra comment").unwrap();
+        // Add another dep to cause deps.jsonl drift
+        let c_issues = db.list_issues(&ListFilters::default()).unwrap();
+        let c_id = c_issues.iter().find(|i| i.title == "task C").unwrap();
+        db.add_dep(&c_id.id, &a.id, "test").unwrap();
+        // Add another src_ref to cause src_refs.jsonl drift
+        db.add_src_ref(&b.id, "lib.rs", None, "test").unwrap();
+        // Add another doc_ref to cause doc_refs.jsonl drift
+        db.add_doc_ref(&a.id, "CHANGELOG.md", None, "test").unwrap();
+
+        let report = db.doctor(false, None, None).unwrap();
+        let drift_findings: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.check == "jsonl_drift")
+            .collect();
+        assert_eq!(
+            drift_findings.len(),
+            5,
+            "expected drift for all 5 entity files, got: {drift_findings:?}"
+        );
+
+        let messages: Vec<&str> = drift_findings.iter().map(|f| f.message.as_str()).collect();
+        assert!(messages.iter().any(|m| m.contains("issues.jsonl")));
+        assert!(messages.iter().any(|m| m.contains("deps.jsonl")));
+        assert!(messages.iter().any(|m| m.contains("comments.jsonl")));
+        assert!(messages.iter().any(|m| m.contains("src_refs.jsonl")));
+        assert!(messages.iter().any(|m| m.contains("doc_refs.jsonl")));
+    }
+
+    #[test]
+    fn is_genuine_pensa_dir_with_issues_jsonl() {
+        let dir = TempDir::new().unwrap();
+        let pensa = dir.path().join(".pensa");
+        fs::create_dir_all(&pensa).unwrap();
+        assert!(!is_genuine_pensa_dir(&pensa));
+
+        fs::write(pensa.join("issues.jsonl"), "").unwrap();
+        assert!(is_genuine_pensa_dir(&pensa));
+    }
+
+    #[test]
+    fn is_genuine_pensa_dir_with_db_sqlite() {
+        let dir = TempDir::new().unwrap();
+        let pensa = dir.path().join(".pensa");
+        fs::create_dir_all(&pensa).unwrap();
+
+        fs::write(pensa.join("db.sqlite"), "").unwrap();
+        assert!(is_genuine_pensa_dir(&pensa));
+    }
+
+    #[test]
+    fn is_genuine_pensa_dir_rogue_daemon_only() {
+        let dir = TempDir::new().unwrap();
+        let pensa = dir.path().join(".pensa");
+        fs::create_dir_all(&pensa).unwrap();
+
+        fs::write(pensa.join("daemon.port"), "12345").unwrap();
+        fs::write(pensa.join("daemon.project"), "/some/path").unwrap();
+        assert!(!is_genuine_pensa_dir(&pensa));
+    }
+}
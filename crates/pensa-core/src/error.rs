@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::types::Issue;
+
+#[derive(Debug)]
+pub enum PensaError {
+    NotFound(String),
+    AlreadyClaimed { id: String, holder: String },
+    CycleDetected,
+    InvalidStatusTransition { from: String, to: String },
+    DeleteRequiresForce(String),
+    SpecNotFound(String),
+    FormaUnavailable,
+    InvalidArgument(String),
+    DaemonUnreachable(String),
+    UnsupportedApiVersion(String),
+    DaemonVersionMismatch(String),
+    ReadOnly,
+    InvalidToken,
+    RateLimited { retry_after: u64 },
+    RevisionMismatch { expected: i64, current: Box<Issue> },
+    Internal(String),
+}
+
+impl fmt::Display for PensaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PensaError::NotFound(id) => write!(f, "issue not found: {id}"),
+            PensaError::AlreadyClaimed { id, holder } => {
+                write!(f, "issue {id} already claimed by {holder}")
+            }
+            PensaError::CycleDetected => write!(f, "adding this dependency would create a cycle"),
+            PensaError::InvalidStatusTransition { from, to } => {
+                write!(f, "invalid status transition from {from} to {to}")
+            }
+            PensaError::DeleteRequiresForce(reason) => {
+                write!(f, "delete requires --force: {reason}")
+            }
+            PensaError::SpecNotFound(stem) => {
+                write!(f, "spec '{stem}' not found")
+            }
+            PensaError::FormaUnavailable => {
+                write!(f, "forma daemon not running, cannot validate --spec")
+            }
+            PensaError::InvalidArgument(msg) => write!(f, "invalid argument: {msg}"),
+            PensaError::DaemonUnreachable(msg) => write!(f, "daemon unreachable: {msg}"),
+            PensaError::UnsupportedApiVersion(requested) => write!(
+                f,
+                "unsupported api version '{requested}' (daemon supports {}..={})",
+                crate::api_version::MIN_API_VERSION,
+                crate::api_version::CURRENT_API_VERSION
+            ),
+            PensaError::DaemonVersionMismatch(msg) => write!(f, "daemon version mismatch: {msg}"),
+            PensaError::ReadOnly => write!(f, "daemon is in read-only mode"),
+            PensaError::InvalidToken => write!(f, "missing or wrong daemon token"),
+            PensaError::RateLimited { retry_after } => {
+                write!(f, "rate limit exceeded, retry in {retry_after}s")
+            }
+            PensaError::RevisionMismatch { expected, current } => write!(
+                f,
+                "issue {} changed since revision {expected} (now at {})",
+                current.id, current.rev
+            ),
+            PensaError::Internal(msg) => write!(f, "internal error: {msg}"),
+        }
+    }
+}
+
+impl PensaError {
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            PensaError::NotFound(_) => Some("not_found"),
+            PensaError::AlreadyClaimed { .. } => Some("already_claimed"),
+            PensaError::CycleDetected => Some("cycle_detected"),
+            PensaError::InvalidStatusTransition { .. } => Some("invalid_status_transition"),
+            PensaError::DeleteRequiresForce(_) => Some("delete_requires_force"),
+            PensaError::SpecNotFound(_) => Some("spec_not_found"),
+            PensaError::FormaUnavailable => Some("forma_unavailable"),
+            PensaError::InvalidArgument(_) => Some("invalid_argument"),
+            PensaError::DaemonUnreachable(_) => Some("daemon_unreachable"),
+            PensaError::UnsupportedApiVersion(_) => Some("unsupported_api_version"),
+            PensaError::DaemonVersionMismatch(_) => Some("daemon_version_mismatch"),
+            PensaError::ReadOnly => Some("read_only"),
+            PensaError::InvalidToken => Some("invalid_token"),
+            PensaError::RateLimited { .. } => Some("rate_limited"),
+            PensaError::RevisionMismatch { .. } => Some("revision_mismatch"),
+            PensaError::Internal(_) => None,
+        }
+    }
+
+    /// Process exit status for `pn`, distinct per error class so scripts can
+    /// branch without parsing output. 1 is reserved for internal errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PensaError::Internal(_) => 1,
+            PensaError::InvalidArgument(_) => 2,
+            PensaError::NotFound(_) => 3,
+            PensaError::AlreadyClaimed { .. } => 4,
+            PensaError::CycleDetected => 5,
+            PensaError::InvalidStatusTransition { .. } => 6,
+            PensaError::DeleteRequiresForce(_) => 7,
+            PensaError::SpecNotFound(_) => 8,
+            PensaError::FormaUnavailable => 9,
+            PensaError::DaemonUnreachable(_) => 10,
+            PensaError::UnsupportedApiVersion(_) => 11,
+            PensaError::ReadOnly => 12,
+            PensaError::DaemonVersionMismatch(_) => 13,
+            PensaError::RevisionMismatch { .. } => 14,
+            PensaError::RateLimited { .. } => 15,
+            PensaError::InvalidToken => 16,
+        }
+    }
+
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            PensaError::NotFound(id) => Some(serde_json::json!({ "id": id })),
+            PensaError::AlreadyClaimed { id, holder } => {
+                Some(serde_json::json!({ "id": id, "holder": holder }))
+            }
+            PensaError::InvalidStatusTransition { from, to } => Some(serde_json::json!({
+                "from": from,
+                "to": to,
+                "allowed": allowed_transitions(from),
+            })),
+            PensaError::DeleteRequiresForce(reason) => {
+                Some(serde_json::json!({ "reason": reason }))
+            }
+            PensaError::SpecNotFound(stem) => Some(serde_json::json!({ "spec": stem })),
+            PensaError::RevisionMismatch { expected, current } => Some(serde_json::json!({
+                "id": current.id,
+                "expected": expected,
+                "rev": current.rev,
+                "current": current,
+            })),
+            PensaError::RateLimited { retry_after } => {
+                Some(serde_json::json!({ "retry_after": retry_after }))
+            }
+            PensaError::UnsupportedApiVersion(requested) => Some(serde_json::json!({
+                "requested": requested,
+                "min": crate::api_version::MIN_API_VERSION,
+                "max": crate::api_version::CURRENT_API_VERSION,
+            })),
+            _ => None,
+        }
+    }
+}
+
+pub fn allowed_transitions(from: &str) -> &'static [&'static str] {
+    match from {
+        "open" => &["in_progress", "closed"],
+        "in_progress" => &["open", "closed"],
+        "closed" => &["open"],
+        _ => &[],
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl From<&PensaError> for ErrorResponse {
+    fn from(err: &PensaError) -> Self {
+        ErrorResponse {
+            error: err.to_string(),
+            code: err.code().map(String::from),
+            details: err.details(),
+        }
+    }
+}
+
+impl ErrorResponse {
+    pub fn into_error(self) -> PensaError {
+        let detail = |key: &str| {
+            self.details
+                .as_ref()
+                .and_then(|d| d[key].as_str())
+                .map(String::from)
+        };
+        match self.code.as_deref() {
+            Some("not_found") => PensaError::NotFound(detail("id").unwrap_or(self.error)),
+            Some("already_claimed") => PensaError::AlreadyClaimed {
+                id: detail("id").unwrap_or_default(),
+                holder: detail("holder").unwrap_or(self.error),
+            },
+            Some("cycle_detected") => PensaError::CycleDetected,
+            Some("invalid_status_transition") => PensaError::InvalidStatusTransition {
+                from: detail("from").unwrap_or_default(),
+                to: detail("to").unwrap_or(self.error),
+            },
+            Some("delete_requires_force") => {
+                PensaError::DeleteRequiresForce(detail("reason").unwrap_or(self.error))
+            }
+            Some("spec_not_found") => {
+                PensaError::SpecNotFound(detail("spec").unwrap_or(self.error))
+            }
+            Some("forma_unavailable") => PensaError::FormaUnavailable,
+            Some("invalid_argument") => PensaError::InvalidArgument(self.error),
+            Some("read_only") => PensaError::ReadOnly,
+            Some("invalid_token") => PensaError::InvalidToken,
+            Some("rate_limited") => PensaError::RateLimited {
+                retry_after: self
+                    .details
+                    .as_ref()
+                    .and_then(|d| d["retry_after"].as_u64())
+                    .unwrap_or(1),
+            },
+            Some("daemon_version_mismatch") => PensaError::DaemonVersionMismatch(self.error),
+            Some("revision_mismatch") => {
+                let details = self.details.as_ref();
+                let current =
+                    details.and_then(|d| serde_json::from_value(d["current"].clone()).ok());
+                match (details.and_then(|d| d["expected"].as_i64()), current) {
+                    (Some(expected), Some(current)) => PensaError::RevisionMismatch {
+                        expected,
+                        current: Box::new(current),
+                    },
+                    _ => PensaError::Internal(self.error),
+                }
+            }
+            Some("unsupported_api_version") => {
+                PensaError::UnsupportedApiVersion(detail("requested").unwrap_or(self.error))
+            }
+            _ => PensaError::Internal(self.error),
+        }
+    }
+}
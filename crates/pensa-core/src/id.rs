@@ -1,11 +1,25 @@
 use uuid::Uuid;
 
 pub fn generate_id() -> String {
+    generate_id_with_prefix("pn")
+}
+
+/// An id such as `acme-0a1b2c3d`, for projects that set their own prefix.
+pub fn generate_id_with_prefix(prefix: &str) -> String {
     let uuid = Uuid::now_v7();
     let hex = format!("{:032x}", uuid.as_u128());
     // Use the last 8 hex chars (random_b portion of UUIDv7) for
     // collision resistance even when IDs are generated in the same millisecond.
-    format!("pn-{}", &hex[24..])
+    format!("{prefix}-{}", &hex[24..])
+}
+
+/// A random 256-bit key, hex-encoded, for signing webhook payloads.
+pub fn generate_secret() -> String {
+    format!(
+        "{:032x}{:032x}",
+        Uuid::new_v4().as_u128(),
+        Uuid::new_v4().as_u128()
+    )
 }
 
 #[cfg(test)]
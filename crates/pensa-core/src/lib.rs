@@ -0,0 +1,159 @@
+//! Pensa's issue store as a library.
+//!
+//! This crate holds the SQLite-backed [`Db`](db::Db), the shared
+//! [`types`], [`error`]s, and [`config`] loading that the `pn` CLI and
+//! daemon are built on. Rust tools that want to manage issues in-process
+//! instead of over HTTP can open a project through [`Pensa`]:
+//!
+//! ```no_run
+//! use pensa_core::Pensa;
+//! use pensa_core::types::{CreateIssueParams, IssueType, ListFilters, Priority};
+//!
+//! let pensa = Pensa::open(std::path::Path::new("."))?;
+//! let issue = pensa.db().create_issue(&CreateIssueParams {
+//!     title: "login crash".into(),
+//!     issue_type: IssueType::Bug,
+//!     priority: Priority::P0,
+//!     description: None,
+//!     spec: None,
+//!     fixes: None,
+//!     assignee: None,
+//!     deps: vec![],
+//!     labels: Vec::new(),
+//!     actor: "my-tool".into(),
+//! })?;
+//! pensa.db().claim_issue(&issue.id, "my-tool")?;
+//! let ready = pensa.db().ready_issues(&ListFilters::default())?;
+//! # let _ = ready;
+//! # Ok::<(), pensa_core::error::PensaError>(())
+//! ```
+//!
+//! The database lives in the same per-project data directory the daemon
+//! uses, so changes made here are visible to `pn` and vice versa. SQLite
+//! serializes writers, but a running daemon does not see in-process writes
+//! until its next query; it does not cache issue state.
+
+pub mod api_version;
+pub mod checklist;
+pub mod commits;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod id;
+pub mod mentions;
+pub mod types;
+
+use std::path::{Path, PathBuf};
+
+use config::Config;
+use db::Db;
+use error::PensaError;
+
+pub struct Pensa {
+    project_dir: PathBuf,
+    db: Db,
+    config: Config,
+}
+
+impl Pensa {
+    /// Opens the project rooted at `project_dir`, creating `.pensa/` and the
+    /// database on first use. An empty database is populated from the
+    /// committed JSONL files, as the daemon does on startup.
+    pub fn open(project_dir: &Path) -> Result<Pensa, PensaError> {
+        let config = Config::load(&project_dir.join(".pensa"))?;
+        let db = Db::open(project_dir)?;
+        Ok(Pensa {
+            project_dir: project_dir.to_path_buf(),
+            db,
+            config,
+        })
+    }
+
+    pub fn open_with_data_dir(project_dir: &Path, data_dir: PathBuf) -> Result<Pensa, PensaError> {
+        let pensa_dir = project_dir.join(".pensa");
+        let config = Config::load(&pensa_dir)?;
+        let db = Db::open_with_data_dir(pensa_dir, data_dir)?;
+        Ok(Pensa {
+            project_dir: project_dir.to_path_buf(),
+            db,
+            config,
+        })
+    }
+
+    pub fn discover() -> Result<Pensa, PensaError> {
+        let root = db::find_project_root()
+            .ok_or_else(|| PensaError::NotFound("no .pensa directory found".to_string()))?;
+        Self::open(&root)
+    }
+
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn export(&self) -> Result<types::ExportImportResult, PensaError> {
+        self.db.export_jsonl()
+    }
+
+    pub fn import(&self) -> Result<types::ExportImportResult, PensaError> {
+        self.db.import_jsonl()
+    }
+
+    pub fn escalate(&self, dry_run: bool) -> Result<Vec<types::Escalation>, PensaError> {
+        let policy = self.config.escalation_policy()?.ok_or_else(|| {
+            PensaError::InvalidArgument(
+                "no escalation rules configured in .pensa/config.toml".to_string(),
+            )
+        })?;
+        self.db.escalate_priorities(&policy, dry_run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CreateIssueParams, IssueType, Priority};
+
+    #[test]
+    fn open_round_trips_through_export_and_import() {
+        let project = tempfile::TempDir::new().unwrap();
+        let data = tempfile::TempDir::new().unwrap();
+        let pensa = Pensa::open_with_data_dir(project.path(), data.path().to_path_buf()).unwrap();
+        let issue = pensa
+            .db()
+            .create_issue(&CreateIssueParams {
+                title: "in-process".into(),
+                issue_type: IssueType::Task,
+                priority: Priority::P2,
+                description: None,
+                spec: None,
+                fixes: None,
+                assignee: None,
+                deps: vec![],
+                labels: Vec::new(),
+                actor: "lib".into(),
+            })
+            .unwrap();
+        assert_eq!(pensa.export().unwrap().issues, 1);
+        drop(pensa);
+
+        let fresh = tempfile::TempDir::new().unwrap();
+        let reopened =
+            Pensa::open_with_data_dir(project.path(), fresh.path().to_path_buf()).unwrap();
+        assert_eq!(
+            reopened.db().get_issue(&issue.id).unwrap().issue.title,
+            "in-process"
+        );
+        assert!(matches!(
+            reopened.escalate(true),
+            Err(PensaError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,43 @@
+/// Actor names mentioned in `text`, in order of first appearance. A mention
+/// is `@` at the start of a word followed by letters, digits, `_`, `-`, or
+/// `.`; trailing punctuation is not part of the name, and e-mail addresses
+/// are not mentions.
+pub fn find_mentions(text: &str) -> Vec<String> {
+    let bytes = text.as_bytes();
+    let mut names: Vec<String> = Vec::new();
+    for (at, _) in text.match_indices('@') {
+        if at > 0 && is_name_char(bytes[at - 1]) {
+            continue;
+        }
+        let rest = &text[at + 1..];
+        let len = rest.bytes().take_while(|b| is_name_char(*b)).count();
+        let name = rest[..len].trim_end_matches(['.', '-']);
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+pub fn mention_detail(name: &str, text: &str) -> String {
+    format!("@{name}: {text}")
+}
+
+fn is_name_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_mentions_at_word_starts() {
+        assert_eq!(
+            find_mentions("@wlorey can you check this? cc @sgf-build-1, @wlorey."),
+            ["wlorey", "sgf-build-1"]
+        );
+        assert!(find_mentions("mail bob@example.com or use @ alone").is_empty());
+        assert_eq!(find_mentions("(@alice)"), ["alice"]);
+    }
+}
@@ -0,0 +1,750 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub struct ParseEnumError(pub String);
+
+impl fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid enum value: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseEnumError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueType {
+    Bug,
+    Task,
+    Test,
+    Chore,
+}
+
+impl IssueType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueType::Bug => "bug",
+            IssueType::Task => "task",
+            IssueType::Test => "test",
+            IssueType::Chore => "chore",
+        }
+    }
+}
+
+impl FromStr for IssueType {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bug" => Ok(IssueType::Bug),
+            "task" => Ok(IssueType::Task),
+            "test" => Ok(IssueType::Test),
+            "chore" => Ok(IssueType::Chore),
+            _ => Err(ParseEnumError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Open,
+    InProgress,
+    Closed,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Open => "open",
+            Status::InProgress => "in_progress",
+            Status::Closed => "closed",
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Status::Open),
+            "in_progress" => Ok(Status::InProgress),
+            "closed" => Ok(Status::Closed),
+            _ => Err(ParseEnumError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    P0,
+    P1,
+    P2,
+    P3,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::P0 => "p0",
+            Priority::P1 => "p1",
+            Priority::P2 => "p2",
+            Priority::P3 => "p3",
+        }
+    }
+}
+
+impl Priority {
+    pub fn escalated(self) -> Option<Priority> {
+        match self {
+            Priority::P0 => None,
+            Priority::P1 => Some(Priority::P0),
+            Priority::P2 => Some(Priority::P1),
+            Priority::P3 => Some(Priority::P2),
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "p0" => Ok(Priority::P0),
+            "p1" => Ok(Priority::P1),
+            "p2" => Ok(Priority::P2),
+            "p3" => Ok(Priority::P3),
+            _ => Err(ParseEnumError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    Fixed,
+    WontFix,
+    Duplicate,
+    Obsolete,
+}
+
+impl Resolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::Fixed => "fixed",
+            Resolution::WontFix => "wont_fix",
+            Resolution::Duplicate => "duplicate",
+            Resolution::Obsolete => "obsolete",
+        }
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(Resolution::Fixed),
+            "wont_fix" => Ok(Resolution::WontFix),
+            "duplicate" => Ok(Resolution::Duplicate),
+            "obsolete" => Ok(Resolution::Obsolete),
+            _ => Err(ParseEnumError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    pub id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub issue_type: IssueType,
+    pub status: Status,
+    pub priority: Priority,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+    #[serde(default)]
+    pub rev: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checklist: Option<Checklist>,
+}
+
+impl Issue {
+    pub const FIELDS: [&str; 16] = [
+        "id",
+        "title",
+        "description",
+        "issue_type",
+        "status",
+        "priority",
+        "spec",
+        "fixes",
+        "assignee",
+        "created_at",
+        "updated_at",
+        "closed_at",
+        "close_reason",
+        "resolution",
+        "rev",
+        "checklist",
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checklist {
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: String,
+    pub issue_id: String,
+    pub actor: String,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SrcRef {
+    pub id: String,
+    pub issue_id: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocRef {
+    pub id: String,
+    pub issue_id: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Label {
+    pub issue_id: String,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitLink {
+    pub issue_id: String,
+    pub sha: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalKind {
+    Github,
+    Jira,
+    Url,
+}
+
+impl ExternalKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExternalKind::Github => "github",
+            ExternalKind::Jira => "jira",
+            ExternalKind::Url => "url",
+        }
+    }
+
+    pub fn classify(target: &str) -> Option<Self> {
+        let github = ["https://github.com/", "http://github.com/"]
+            .iter()
+            .find_map(|prefix| target.strip_prefix(prefix))
+            .and_then(|path| {
+                let parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+                match parts.as_slice() {
+                    [_, _, "issues" | "pull", n] => n.parse::<u64>().ok(),
+                    _ => None,
+                }
+            });
+        if github.is_some() {
+            return Some(ExternalKind::Github);
+        }
+        if target.starts_with("https://") || target.starts_with("http://") {
+            return Some(ExternalKind::Url);
+        }
+        let (project, number) = target.split_once('-')?;
+        let is_key = project.starts_with(|c: char| c.is_ascii_uppercase())
+            && project
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit());
+        is_key.then_some(ExternalKind::Jira)
+    }
+}
+
+impl FromStr for ExternalKind {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "github" => Ok(ExternalKind::Github),
+            "jira" => Ok(ExternalKind::Jira),
+            "url" => Ok(ExternalKind::Url),
+            _ => Err(ParseEnumError(s.to_string())),
+        }
+    }
+}
+
+/// A dependency on something tracked outside pensa. It blocks its issue
+/// until marked resolved, since pensa cannot see whether it is done.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalDep {
+    pub issue_id: String,
+    pub target: String,
+    pub kind: ExternalKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueDetail {
+    #[serde(flatten)]
+    pub issue: Issue,
+    pub deps: Vec<Issue>,
+    #[serde(default)]
+    pub external_deps: Vec<ExternalDep>,
+    pub comments: Vec<Comment>,
+    pub src_refs: Vec<SrcRef>,
+    pub doc_refs: Vec<DocRef>,
+    pub commits: Vec<CommitLink>,
+    pub labels: Vec<String>,
+    pub references: Vec<String>,
+    pub referenced_by: Vec<Issue>,
+    pub fixed_by: Vec<Issue>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromoteRefsResult {
+    pub added: Vec<String>,
+    pub skipped: Vec<SkippedRef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedRef {
+    pub id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub id: i64,
+    pub issue_id: String,
+    pub event_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueText {
+    pub id: String,
+    pub at: DateTime<Utc>,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edited_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxEntry {
+    #[serde(flatten)]
+    pub event: Event,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inbox {
+    pub actor: String,
+    pub last_read: i64,
+    pub entries: Vec<InboxEntry>,
+}
+
+pub const EVENT_TYPES: &[&str] = &[
+    "created",
+    "updated",
+    "claimed",
+    "released",
+    "closed",
+    "reopened",
+    "commented",
+    "labeled",
+    "unlabeled",
+    "dep_added",
+    "dep_removed",
+    "dep_resolved",
+    "src_ref_added",
+    "src_ref_removed",
+    "doc_ref_added",
+    "doc_ref_removed",
+    "commit_linked",
+    "escalated",
+    "mentioned",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    /// HMAC-SHA256 key for the signature header. Only shown when the
+    /// webhook is added.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub last_event_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Actor {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias_of: Option<String>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ExclusionReason {
+    Closed,
+    Claimed {
+        assignee: Option<String>,
+    },
+    BlockedBy {
+        ids: Vec<String>,
+    },
+    FixInProgress {
+        ids: Vec<String>,
+    },
+    Filtered {
+        filter: String,
+        wanted: String,
+        actual: Option<String>,
+    },
+    PastLimit {
+        limit: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadyExplanation {
+    pub id: String,
+    pub title: String,
+    pub status: Status,
+    pub priority: Priority,
+    pub issue_type: IssueType,
+    pub ready: bool,
+    pub reasons: Vec<ExclusionReason>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub issue_count: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueChange {
+    pub id: String,
+    pub title: String,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub from: String,
+    pub to: String,
+    pub created: Vec<Issue>,
+    /// Closed since `from`, including issues created in between.
+    pub closed: Vec<Issue>,
+    /// Changed in any other way, closing aside.
+    pub changed: Vec<IssueChange>,
+    pub deleted: Vec<Issue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dep {
+    pub issue_id: String,
+    pub depends_on_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepTreeNode {
+    pub id: String,
+    pub title: String,
+    pub status: Status,
+    pub priority: Priority,
+    pub issue_type: IssueType,
+    pub depth: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateIssueParams {
+    pub title: String,
+    pub issue_type: IssueType,
+    pub priority: Priority,
+    pub description: Option<String>,
+    pub spec: Option<String>,
+    pub fixes: Option<String>,
+    pub assignee: Option<String>,
+    pub deps: Vec<String>,
+    pub labels: Vec<String>,
+    pub actor: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedIssue {
+    pub key: String,
+    pub title: String,
+    #[serde(rename = "type", default = "planned_issue_type")]
+    pub issue_type: IssueType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deps: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+}
+
+fn planned_issue_type() -> IssueType {
+    IssueType::Task
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UpdateFields {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub priority: Option<Priority>,
+    pub status: Option<Status>,
+    pub assignee: Option<String>,
+    pub spec: Option<String>,
+    pub fixes: Option<String>,
+    pub expect_rev: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListFilters {
+    pub status: Option<Status>,
+    pub priority: Option<Priority>,
+    pub assignee: Option<String>,
+    pub issue_type: Option<IssueType>,
+    pub spec: Option<String>,
+    pub resolution: Option<Resolution>,
+    pub sort: Option<String>,
+    pub limit: Option<usize>,
+    /// The issue fields a response keeps, all when empty. Applied when the
+    /// daemon serializes the results, not by the query itself.
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountResult {
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedCountResult {
+    pub total: i64,
+    pub groups: Vec<CountGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountGroup {
+    pub key: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEntry {
+    pub issue_type: IssueType,
+    pub open: i64,
+    pub in_progress: i64,
+    pub closed: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecStatusEntry {
+    pub spec: String,
+    pub open: i64,
+    pub in_progress: i64,
+    pub closed: i64,
+    pub open_p0: i64,
+    pub open_p1: i64,
+    pub blocked: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecDetail {
+    #[serde(flatten)]
+    pub summary: SpecStatusEntry,
+    pub outstanding: Vec<Issue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportImportResult {
+    pub status: String,
+    pub issues: usize,
+    pub deps: usize,
+    pub comments: usize,
+    pub src_refs: usize,
+    pub doc_refs: usize,
+    #[serde(default)]
+    pub commits: usize,
+    #[serde(default)]
+    pub labels: usize,
+    #[serde(default)]
+    pub external_deps: usize,
+    #[serde(default)]
+    pub actors: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleIssue {
+    #[serde(flatten)]
+    pub issue: Issue,
+    pub age_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Escalation {
+    pub issue_id: String,
+    pub title: String,
+    pub from: Priority,
+    pub to: Priority,
+    pub reason: String,
+}
+
+/// Unclosed issues held by one assignee. `active` is `in_progress + open`;
+/// `urgent` counts the p0/p1 issues among them. Issues held under an alias
+/// count toward the identity it points at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkloadEntry {
+    pub assignee: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub in_progress: i64,
+    pub open: i64,
+    pub active: i64,
+    pub urgent: i64,
+    pub overloaded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkloadReport {
+    pub max_active: i64,
+    pub unassigned: i64,
+    pub assignees: Vec<WorkloadEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorFinding {
+    pub check: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claim_age_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorReport {
+    pub findings: Vec<DoctorFinding>,
+    pub fixes_applied: Vec<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<RowChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbStats {
+    pub schema_version: i64,
+    pub tables: BTreeMap<String, i64>,
+    pub db_bytes: u64,
+    pub wal_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_export: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub uptime_secs: u64,
+    pub read_only: bool,
+    #[serde(flatten)]
+    pub db: DbStats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowAction {
+    Insert,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowChange {
+    pub table: String,
+    pub action: RowAction,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunReport {
+    pub changes: Vec<RowChange>,
+}
@@ -1,12 +1,94 @@
-use reqwest::blocking::Client as HttpClient;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::Duration;
+
+use reqwest::Method;
+use reqwest::blocking::{Client as HttpClient, RequestBuilder, Response};
 use serde_json::Value;
 
+use crate::api_version::{API_VERSION_HEADER, DAEMON_VERSION_HEADER, MIN_API_VERSION_HEADER};
+use crate::config::{ClientConfig, Config, parse_duration};
 use crate::error::{ErrorResponse, PensaError};
-use crate::types::{CreateIssueParams, ListFilters};
+use crate::types::{CreateIssueParams, ListFilters, PlannedIssue, Resolution, Status};
+
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientSettings {
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    pub retries: u32,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        ClientSettings {
+            connect_timeout: Duration::from_secs(3),
+            request_timeout: Duration::from_secs(60),
+            retries: 2,
+        }
+    }
+}
 
+impl ClientSettings {
+    pub fn resolve(
+        config: &ClientConfig,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, PensaError> {
+        let duration = |value: Option<String>, default: Duration| -> Result<Duration, PensaError> {
+            match value {
+                Some(v) => parse_duration(&v)?
+                    .to_std()
+                    .map_err(|_| PensaError::InvalidArgument(format!("invalid duration: {v:?}"))),
+                None => Ok(default),
+            }
+        };
+        let defaults = ClientSettings::default();
+        let retries = match env("PN_RETRIES") {
+            Some(v) => v
+                .trim()
+                .parse()
+                .map_err(|_| PensaError::InvalidArgument(format!("invalid PN_RETRIES: {v:?}")))?,
+            None => config.retries.unwrap_or(defaults.retries),
+        };
+        Ok(ClientSettings {
+            connect_timeout: duration(
+                env("PN_CONNECT_TIMEOUT").or_else(|| config.connect_timeout.clone()),
+                defaults.connect_timeout,
+            )?,
+            request_timeout: duration(
+                env("PN_REQUEST_TIMEOUT").or_else(|| config.request_timeout.clone()),
+                defaults.request_timeout,
+            )?,
+            retries,
+        })
+    }
+
+    /// Exponential backoff with full jitter: a random delay in
+    /// `[0, base * 2^attempt)`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = RETRY_BASE_DELAY.as_millis() as u64 * 2u64.pow(attempt.min(6));
+        let random = RandomState::new().build_hasher().finish();
+        Duration::from_millis(random % ceiling)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DaemonInfo {
+    pub url: String,
+    pub version: Option<String>,
+    pub api_version: Option<u32>,
+    pub min_api_version: Option<u32>,
+}
+
+/// Blocking daemon client. Holds one pooled HTTP client, so keep a single
+/// instance per process rather than building one per request.
 pub struct Client {
     http: HttpClient,
     base_url: String,
+    api_version: Option<u32>,
+    settings: ClientSettings,
 }
 
 impl Default for Client {
@@ -17,13 +99,71 @@ impl Default for Client {
 
 impl Client {
     pub fn new() -> Self {
+        let api_version = std::env::var("PN_API_VERSION")
+            .ok()
+            .and_then(|v| v.trim().parse().ok());
+        Self::with_api_version(api_version)
+    }
+
+    pub fn with_api_version(api_version: Option<u32>) -> Self {
+        let settings = Config::load(&Self::project_dir().join(".pensa"))
+            .and_then(|config| ClientSettings::resolve(&config.client, |k| std::env::var(k).ok()))
+            .unwrap_or_else(|e| {
+                tracing::warn!("using default client settings: {e}");
+                ClientSettings::default()
+            });
+        Self::with_settings(api_version, settings)
+    }
+
+    pub fn with_settings(api_version: Option<u32>, settings: ClientSettings) -> Self {
         let base_url = Self::resolve_url();
         let http = HttpClient::builder()
-            .connect_timeout(std::time::Duration::from_secs(3))
-            .timeout(std::time::Duration::from_secs(5))
+            .connect_timeout(settings.connect_timeout)
+            .timeout(settings.request_timeout)
             .build()
             .expect("build http client");
-        Client { http, base_url }
+        Client {
+            http,
+            base_url,
+            api_version,
+            settings,
+        }
+    }
+
+    /// Sends a request with the negotiated API version. GET and HEAD requests
+    /// are retried with jittered backoff when the daemon cannot be reached or
+    /// times out; other methods are sent once since they may have applied.
+    fn send(&self, builder: RequestBuilder) -> Result<Response, PensaError> {
+        let builder = match self.api_version {
+            Some(v) => builder.header(API_VERSION_HEADER, v),
+            None => builder,
+        };
+        let request = builder
+            .build()
+            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let retries = if matches!(*request.method(), Method::GET | Method::HEAD) {
+            self.settings.retries
+        } else {
+            0
+        };
+
+        let mut attempt = 0;
+        loop {
+            let this_try = request
+                .try_clone()
+                .ok_or_else(|| PensaError::Internal("request body is not cloneable".into()))?;
+            match self.http.execute(this_try) {
+                Ok(resp) => return Ok(resp),
+                Err(e) if e.is_connect() || e.is_timeout() => {
+                    if attempt >= retries {
+                        return Err(PensaError::DaemonUnreachable(e.to_string()));
+                    }
+                    thread::sleep(self.settings.backoff(attempt));
+                    attempt += 1;
+                }
+                Err(e) => return Err(PensaError::Internal(e.to_string())),
+            }
+        }
     }
 
     fn resolve_url() -> String {
@@ -70,10 +210,70 @@ impl Client {
     }
 
     pub fn check_reachable(&self) -> Result<(), String> {
-        match self.http.get(format!("{}/status", self.base_url)).send() {
-            Ok(resp) if resp.status().is_success() => Ok(()),
-            Ok(resp) => Err(format!("daemon returned status {}", resp.status())),
-            Err(e) => Err(format!("cannot reach daemon at {}: {}", self.base_url, e)),
+        self.daemon_info().map(|_| ())
+    }
+
+    pub fn daemon_info(&self) -> Result<DaemonInfo, String> {
+        let resp = match self.http.get(format!("{}/status", self.base_url)).send() {
+            Ok(resp) if resp.status().is_success() => resp,
+            Ok(resp) => return Err(format!("daemon returned status {}", resp.status())),
+            Err(e) => return Err(format!("cannot reach daemon at {}: {}", self.base_url, e)),
+        };
+        let header = |name: &str| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let api_version = header(API_VERSION_HEADER).and_then(|v| v.parse().ok());
+        Ok(DaemonInfo {
+            url: self.base_url.clone(),
+            version: header(DAEMON_VERSION_HEADER),
+            api_version,
+            min_api_version: header(MIN_API_VERSION_HEADER)
+                .and_then(|v| v.parse().ok())
+                .or(api_version),
+        })
+    }
+
+    pub fn health(&self) -> Result<Value, PensaError> {
+        let resp = self.send(self.http.get(format!("{}/health", self.base_url)))?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn read_only(&self) -> Result<Value, PensaError> {
+        let resp = self.send(self.http.get(format!("{}/read-only", self.base_url)))?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn set_read_only(&self, enabled: bool) -> Result<Value, PensaError> {
+        let token = std::fs::read_to_string(
+            Self::project_dir()
+                .join(".pensa")
+                .join(crate::daemon::TOKEN_FILE),
+        )
+        .unwrap_or_default();
+        let resp = self.send(
+            self.http
+                .post(format!("{}/read-only", self.base_url))
+                .header(crate::daemon::TOKEN_HEADER, token.trim())
+                .json(&serde_json::json!({ "enabled": enabled })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
         }
     }
 
@@ -82,22 +282,9 @@ impl Client {
     }
 
     fn parse_error(resp: reqwest::blocking::Response) -> PensaError {
-        if let Ok(err_resp) = resp.json::<ErrorResponse>() {
-            match err_resp.code.as_deref() {
-                Some("not_found") => PensaError::NotFound(err_resp.error),
-                Some("already_claimed") => PensaError::AlreadyClaimed {
-                    id: String::new(),
-                    holder: err_resp.error,
-                },
-                Some("cycle_detected") => PensaError::CycleDetected,
-                Some("invalid_status_transition") => PensaError::InvalidStatusTransition {
-                    from: String::new(),
-                    to: err_resp.error,
-                },
-                _ => PensaError::Internal(err_resp.error),
-            }
-        } else {
-            PensaError::Internal("unknown error from daemon".to_string())
+        match resp.json::<ErrorResponse>() {
+            Ok(err_resp) => err_resp.into_error(),
+            Err(_) => PensaError::Internal("unknown error from daemon".to_string()),
         }
     }
 
@@ -122,12 +309,11 @@ impl Client {
             body["assignee"] = Value::String(a.clone());
         }
 
-        let resp = self
-            .http
-            .post(format!("{}/issues", self.base_url))
-            .json(&body)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues", self.base_url))
+                .json(&body),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -137,11 +323,7 @@ impl Client {
     }
 
     pub fn get_issue(&self, id: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/{}", self.base_url, id))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(self.http.get(format!("{}/issues/{}", self.base_url, id)))?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -150,16 +332,24 @@ impl Client {
         }
     }
 
-    pub fn update_issue(&self, id: &str, fields: &Value, actor: &str) -> Result<Value, PensaError> {
+    pub fn update_issue(
+        &self,
+        id: &str,
+        fields: &Value,
+        expect_rev: Option<i64>,
+        actor: &str,
+    ) -> Result<Value, PensaError> {
         let mut body = fields.clone();
         body["actor"] = Value::String(actor.to_string());
 
-        let resp = self
+        let mut req = self
             .http
             .patch(format!("{}/issues/{}", self.base_url, id))
-            .json(&body)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+            .json(&body);
+        if let Some(rev) = expect_rev {
+            req = req.header("if-match", format!("\"{rev}\""));
+        }
+        let resp = self.send(req)?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -174,11 +364,7 @@ impl Client {
             url.push_str("?force=true");
         }
 
-        let resp = self
-            .http
-            .delete(&url)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(self.http.delete(&url))?;
 
         if resp.status().is_success() {
             Ok(())
@@ -187,25 +373,45 @@ impl Client {
         }
     }
 
+    pub fn preview_delete(&self, id: &str, force: bool) -> Result<Value, PensaError> {
+        let mut params = vec![("dry_run", "true")];
+        if force {
+            params.push(("force", "true"));
+        }
+
+        let resp = self.send(
+            self.http
+                .delete(format!("{}/issues/{}", self.base_url, id))
+                .query(&params),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
     pub fn close_issue(
         &self,
         id: &str,
         reason: Option<&str>,
+        resolution: Option<Resolution>,
         force: bool,
         actor: &str,
     ) -> Result<Value, PensaError> {
         let body = serde_json::json!({
             "reason": reason,
+            "resolution": resolution,
             "force": force,
             "actor": actor,
         });
 
-        let resp = self
-            .http
-            .post(format!("{}/issues/{}/close", self.base_url, id))
-            .json(&body)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/close", self.base_url, id))
+                .json(&body),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -218,19 +424,20 @@ impl Client {
         &self,
         id: &str,
         reason: Option<&str>,
+        cascade: bool,
         actor: &str,
     ) -> Result<Value, PensaError> {
         let body = serde_json::json!({
             "reason": reason,
+            "cascade": cascade,
             "actor": actor,
         });
 
-        let resp = self
-            .http
-            .post(format!("{}/issues/{}/reopen", self.base_url, id))
-            .json(&body)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/reopen", self.base_url, id))
+                .json(&body),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -240,12 +447,41 @@ impl Client {
     }
 
     pub fn release_issue(&self, id: &str, actor: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .post(format!("{}/issues/{}/release", self.base_url, id))
-            .header("x-pensa-actor", actor)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/release", self.base_url, id))
+                .header("x-pensa-actor", actor),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn seed_issues(&self, plan: &[PlannedIssue], actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/seed", self.base_url))
+                .header("x-pensa-actor", actor)
+                .json(&serde_json::json!({ "issues": plan })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn check_item(&self, id: &str, index: usize, actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/check", self.base_url, id))
+                .header("x-pensa-actor", actor)
+                .json(&serde_json::json!({ "index": index })),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -271,19 +507,24 @@ impl Client {
         if let Some(ref s) = filters.spec {
             params.push(("spec".to_string(), s.clone()));
         }
+        if let Some(ref r) = filters.resolution {
+            params.push(("resolution".to_string(), r.as_str().to_string()));
+        }
         if let Some(ref s) = filters.sort {
             params.push(("sort".to_string(), s.clone()));
         }
         if let Some(l) = filters.limit {
             params.push(("limit".to_string(), l.to_string()));
         }
+        if !filters.fields.is_empty() {
+            params.push(("fields".to_string(), filters.fields.join(",")));
+        }
 
-        let resp = self
-            .http
-            .get(format!("{}/issues", self.base_url))
-            .query(&params)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues", self.base_url))
+                .query(&params),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -293,6 +534,18 @@ impl Client {
     }
 
     pub fn ready_issues(&self, filters: &ListFilters) -> Result<Value, PensaError> {
+        self.get_ready("/issues/ready", filters)
+    }
+
+    pub fn explain_not_ready(&self, filters: &ListFilters) -> Result<Value, PensaError> {
+        self.get_ready("/issues/ready/explain", filters)
+    }
+
+    pub fn explain_ready(&self, id: &str, filters: &ListFilters) -> Result<Value, PensaError> {
+        self.get_ready(&format!("/issues/{id}/explain"), filters)
+    }
+
+    fn get_ready(&self, path: &str, filters: &ListFilters) -> Result<Value, PensaError> {
         let mut params = Vec::new();
         if let Some(ref p) = filters.priority {
             params.push(("priority".to_string(), p.as_str().to_string()));
@@ -309,13 +562,33 @@ impl Client {
         if let Some(l) = filters.limit {
             params.push(("limit".to_string(), l.to_string()));
         }
+        if !filters.fields.is_empty() {
+            params.push(("fields".to_string(), filters.fields.join(",")));
+        }
 
-        let resp = self
-            .http
-            .get(format!("{}/issues/ready", self.base_url))
-            .query(&params)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .get(format!("{}{path}", self.base_url))
+                .query(&params),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn stale_issues(&self, days: i64, status: Option<Status>) -> Result<Value, PensaError> {
+        let mut params = vec![("days", days.to_string())];
+        if let Some(status) = status {
+            params.push(("status", status.as_str().to_string()));
+        }
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/stale", self.base_url))
+                .query(&params),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -325,11 +598,7 @@ impl Client {
     }
 
     pub fn blocked_issues(&self) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/blocked", self.base_url))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(self.http.get(format!("{}/issues/blocked", self.base_url)))?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -338,13 +607,16 @@ impl Client {
         }
     }
 
-    pub fn search_issues(&self, query: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/search", self.base_url))
-            .query(&[("q", query)])
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+    pub fn search_issues(&self, query: &str, fields: &[String]) -> Result<Value, PensaError> {
+        let mut params = vec![("q", query.to_string())];
+        if !fields.is_empty() {
+            params.push(("fields", fields.join(",")));
+        }
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/search", self.base_url))
+                .query(&params),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -355,31 +627,45 @@ impl Client {
 
     pub fn count_issues(
         &self,
-        by_status: bool,
-        by_priority: bool,
-        by_issue_type: bool,
-        by_assignee: bool,
+        group_by: &[&str],
+        resolution: Option<Resolution>,
     ) -> Result<Value, PensaError> {
-        let mut params = Vec::new();
-        if by_status {
-            params.push(("by_status", "true"));
+        let mut params: Vec<(String, &str)> = group_by
+            .iter()
+            .map(|field| (format!("by_{field}"), "true"))
+            .collect();
+        if let Some(r) = resolution {
+            params.push(("resolution".to_string(), r.as_str()));
         }
-        if by_priority {
-            params.push(("by_priority", "true"));
-        }
-        if by_issue_type {
-            params.push(("by_issue_type", "true"));
+
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/count", self.base_url))
+                .query(&params),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
         }
-        if by_assignee {
-            params.push(("by_assignee", "true"));
+    }
+
+    pub fn spec_status(&self) -> Result<Value, PensaError> {
+        let resp = self.send(self.http.get(format!("{}/specs/status", self.base_url)))?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
         }
+    }
 
-        let resp = self
-            .http
-            .get(format!("{}/issues/count", self.base_url))
-            .query(&params)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+    pub fn spec_detail(&self, spec: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .get(format!("{}/specs/{spec}/status", self.base_url)),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -389,11 +675,35 @@ impl Client {
     }
 
     pub fn project_status(&self) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/status", self.base_url))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(self.http.get(format!("{}/status", self.base_url)))?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn workload(&self, max_active: Option<i64>) -> Result<Value, PensaError> {
+        let mut req = self.http.get(format!("{}/workload", self.base_url));
+        if let Some(n) = max_active {
+            req = req.query(&[("max_active", n)]);
+        }
+        let resp = self.send(req)?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn issue_text_at(&self, id: &str, at: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/text", self.base_url, id))
+                .query(&[("at", at)]),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -403,11 +713,27 @@ impl Client {
     }
 
     pub fn issue_history(&self, id: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/{}/history", self.base_url, id))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/history", self.base_url, id)),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn promote_refs(&self, issue_id: &str, actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!(
+                    "{}/issues/{}/deps/from-refs",
+                    self.base_url, issue_id
+                ))
+                .header("x-pensa-actor", actor),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -428,12 +754,11 @@ impl Client {
             "actor": actor,
         });
 
-        let resp = self
-            .http
-            .post(format!("{}/deps", self.base_url))
-            .json(&body)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/deps", self.base_url))
+                .json(&body),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -443,12 +768,38 @@ impl Client {
     }
 
     pub fn remove_dep(&self, issue_id: &str, depends_on_id: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .delete(format!("{}/deps", self.base_url))
-            .query(&[("issue_id", issue_id), ("depends_on_id", depends_on_id)])
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .delete(format!("{}/deps", self.base_url))
+                .query(&[("issue_id", issue_id), ("depends_on_id", depends_on_id)]),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn resolve_dep(
+        &self,
+        issue_id: &str,
+        target: &str,
+        resolved: bool,
+        actor: &str,
+    ) -> Result<Value, PensaError> {
+        let body = serde_json::json!({
+            "issue_id": issue_id,
+            "target": target,
+            "resolved": resolved,
+            "actor": actor,
+        });
+
+        let resp = self.send(
+            self.http
+                .post(format!("{}/deps/resolve", self.base_url))
+                .json(&body),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -458,11 +809,10 @@ impl Client {
     }
 
     pub fn list_deps(&self, id: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/{}/deps", self.base_url, id))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/deps", self.base_url, id)),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -472,12 +822,11 @@ impl Client {
     }
 
     pub fn dep_tree(&self, id: &str, direction: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/{}/deps/tree", self.base_url, id))
-            .query(&[("direction", direction)])
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/deps/tree", self.base_url, id))
+                .query(&[("direction", direction)]),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -487,11 +836,7 @@ impl Client {
     }
 
     pub fn dep_cycles(&self) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/deps/cycles", self.base_url))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(self.http.get(format!("{}/deps/cycles", self.base_url)))?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -506,12 +851,11 @@ impl Client {
             "actor": actor,
         });
 
-        let resp = self
-            .http
-            .post(format!("{}/issues/{}/comments", self.base_url, id))
-            .json(&body)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/comments", self.base_url, id))
+                .json(&body),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -521,11 +865,10 @@ impl Client {
     }
 
     pub fn list_comments(&self, id: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/{}/comments", self.base_url, id))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/comments", self.base_url, id)),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -549,12 +892,11 @@ impl Client {
             body["reason"] = Value::String(r.to_string());
         }
 
-        let resp = self
-            .http
-            .post(format!("{}/issues/{}/src-refs", self.base_url, issue_id))
-            .json(&body)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/src-refs", self.base_url, issue_id))
+                .json(&body),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -564,11 +906,10 @@ impl Client {
     }
 
     pub fn list_src_refs(&self, issue_id: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/{}/src-refs", self.base_url, issue_id))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/src-refs", self.base_url, issue_id)),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -578,12 +919,11 @@ impl Client {
     }
 
     pub fn remove_src_ref(&self, ref_id: &str, actor: &str) -> Result<(), PensaError> {
-        let resp = self
-            .http
-            .delete(format!("{}/src-refs/{}", self.base_url, ref_id))
-            .header("x-pensa-actor", actor)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .delete(format!("{}/src-refs/{}", self.base_url, ref_id))
+                .header("x-pensa-actor", actor),
+        )?;
 
         if resp.status().is_success() {
             Ok(())
@@ -607,12 +947,11 @@ impl Client {
             body["reason"] = Value::String(r.to_string());
         }
 
-        let resp = self
-            .http
-            .post(format!("{}/issues/{}/doc-refs", self.base_url, issue_id))
-            .json(&body)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/doc-refs", self.base_url, issue_id))
+                .json(&body),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -622,11 +961,10 @@ impl Client {
     }
 
     pub fn list_doc_refs(&self, issue_id: &str) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .get(format!("{}/issues/{}/doc-refs", self.base_url, issue_id))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/doc-refs", self.base_url, issue_id)),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -636,12 +974,229 @@ impl Client {
     }
 
     pub fn remove_doc_ref(&self, ref_id: &str, actor: &str) -> Result<(), PensaError> {
-        let resp = self
-            .http
-            .delete(format!("{}/doc-refs/{}", self.base_url, ref_id))
-            .header("x-pensa-actor", actor)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .delete(format!("{}/doc-refs/{}", self.base_url, ref_id))
+                .header("x-pensa-actor", actor),
+        )?;
+
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn add_label(&self, issue_id: &str, label: &str, actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/labels", self.base_url, issue_id))
+                .json(&serde_json::json!({
+                    "label": label,
+                    "actor": actor,
+                })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn remove_label(
+        &self,
+        issue_id: &str,
+        label: &str,
+        actor: &str,
+    ) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .delete(format!(
+                    "{}/issues/{}/labels/{}",
+                    self.base_url, issue_id, label
+                ))
+                .header("x-pensa-actor", actor),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn list_labels(&self, issue_id: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/labels", self.base_url, issue_id)),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn watch_issue(&self, issue_id: &str, actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/watchers", self.base_url, issue_id))
+                .json(&serde_json::json!({ "actor": actor })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn unwatch_issue(&self, issue_id: &str, actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(self.http.delete(format!(
+            "{}/issues/{}/watchers/{}",
+            self.base_url, issue_id, actor
+        )))?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn list_watchers(&self, issue_id: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/watchers", self.base_url, issue_id)),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn watched_issues(&self, actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .get(format!("{}/watching", self.base_url))
+                .header("x-pensa-actor", actor),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn inbox(&self, actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .get(format!("{}/inbox", self.base_url))
+                .header("x-pensa-actor", actor),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn mark_inbox_read(&self, up_to: i64, actor: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/inbox/read", self.base_url))
+                .json(&serde_json::json!({ "up_to": up_to, "actor": actor })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn add_webhook(
+        &self,
+        url: &str,
+        events: &[String],
+        spec: Option<&str>,
+    ) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/webhooks", self.base_url))
+                .json(&serde_json::json!({ "url": url, "events": events, "spec": spec })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn list_webhooks(&self) -> Result<Value, PensaError> {
+        let resp = self.send(self.http.get(format!("{}/webhooks", self.base_url)))?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn list_actors(&self) -> Result<Value, PensaError> {
+        let resp = self.send(self.http.get(format!("{}/actors", self.base_url)))?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn rename_actor(
+        &self,
+        name: &str,
+        display_name: Option<&str>,
+    ) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/actors/rename", self.base_url))
+                .json(&serde_json::json!({ "name": name, "display_name": display_name })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn alias_actor(&self, alias: &str, actor: Option<&str>) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/actors/alias", self.base_url))
+                .json(&serde_json::json!({ "alias": alias, "actor": actor })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn remove_webhook(&self, id: &str) -> Result<(), PensaError> {
+        let resp = self.send(
+            self.http
+                .delete(format!("{}/webhooks/{}", self.base_url, id)),
+        )?;
 
         if resp.status().is_success() {
             Ok(())
@@ -650,12 +1205,96 @@ impl Client {
         }
     }
 
+    pub fn create_snapshot(&self, label: Option<&str>) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .post(format!("{}/snapshots", self.base_url))
+                .json(&serde_json::json!({ "label": label })),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn list_snapshots(&self) -> Result<Value, PensaError> {
+        let resp = self.send(self.http.get(format!("{}/snapshots", self.base_url)))?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn diff(
+        &self,
+        from: Option<&str>,
+        to: Option<&str>,
+        since: Option<&str>,
+    ) -> Result<Value, PensaError> {
+        let params: Vec<(&str, &str)> = [("from", from), ("to", to), ("since", since)]
+            .into_iter()
+            .filter_map(|(k, v)| Some((k, v?)))
+            .collect();
+        let resp = self.send(
+            self.http
+                .get(format!("{}/diff", self.base_url))
+                .query(&params),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn link_commit(
+        &self,
+        issue_id: &str,
+        sha: &str,
+        summary: Option<&str>,
+        actor: &str,
+    ) -> Result<Value, PensaError> {
+        let mut body = serde_json::json!({
+            "sha": sha,
+            "actor": actor,
+        });
+        if let Some(s) = summary {
+            body["summary"] = Value::String(s.to_string());
+        }
+
+        let resp = self.send(
+            self.http
+                .post(format!("{}/issues/{}/commits", self.base_url, issue_id))
+                .json(&body),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn list_commits(&self, issue_id: &str) -> Result<Value, PensaError> {
+        let resp = self.send(
+            self.http
+                .get(format!("{}/issues/{}/commits", self.base_url, issue_id)),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
     pub fn export(&self) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .post(format!("{}/export", self.base_url))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(self.http.post(format!("{}/export", self.base_url)))?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -664,12 +1303,36 @@ impl Client {
         }
     }
 
-    pub fn import(&self) -> Result<Value, PensaError> {
-        let resp = self
-            .http
-            .post(format!("{}/import", self.base_url))
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+    pub fn import(&self, dry_run: bool) -> Result<Value, PensaError> {
+        let mut params = Vec::new();
+        if dry_run {
+            params.push(("dry_run", "true"));
+        }
+
+        let resp = self.send(
+            self.http
+                .post(format!("{}/import", self.base_url))
+                .query(&params),
+        )?;
+
+        if resp.status().is_success() {
+            resp.json().map_err(|e| PensaError::Internal(e.to_string()))
+        } else {
+            Err(Self::parse_error(resp))
+        }
+    }
+
+    pub fn escalate(&self, dry_run: bool) -> Result<Value, PensaError> {
+        let mut params = Vec::new();
+        if dry_run {
+            params.push(("dry_run", "true"));
+        }
+
+        let resp = self.send(
+            self.http
+                .post(format!("{}/escalate", self.base_url))
+                .query(&params),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -678,18 +1341,28 @@ impl Client {
         }
     }
 
-    pub fn doctor(&self, fix: bool) -> Result<Value, PensaError> {
+    pub fn doctor(
+        &self,
+        fix: bool,
+        dry_run: bool,
+        stale_after: Option<&str>,
+    ) -> Result<Value, PensaError> {
         let mut params = Vec::new();
         if fix {
             params.push(("fix", "true"));
         }
+        if dry_run {
+            params.push(("dry_run", "true"));
+        }
+        if let Some(s) = stale_after {
+            params.push(("stale_after", s));
+        }
 
-        let resp = self
-            .http
-            .post(format!("{}/doctor", self.base_url))
-            .query(&params)
-            .send()
-            .map_err(|e| PensaError::Internal(e.to_string()))?;
+        let resp = self.send(
+            self.http
+                .post(format!("{}/doctor", self.base_url))
+                .query(&params),
+        )?;
 
         if resp.status().is_success() {
             resp.json().map_err(|e| PensaError::Internal(e.to_string()))
@@ -698,3 +1371,60 @@ impl Client {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_default_without_config_or_env() {
+        let settings = ClientSettings::resolve(&ClientConfig::default(), |_| None).unwrap();
+        assert_eq!(settings, ClientSettings::default());
+    }
+
+    #[test]
+    fn settings_env_overrides_config() {
+        let config = ClientConfig {
+            connect_timeout: Some("10s".into()),
+            request_timeout: Some("1m".into()),
+            retries: Some(5),
+        };
+        let settings = ClientSettings::resolve(&config, |_| None).unwrap();
+        assert_eq!(settings.connect_timeout, Duration::from_secs(10));
+        assert_eq!(settings.request_timeout, Duration::from_secs(60));
+        assert_eq!(settings.retries, 5);
+
+        let env = |key: &str| match key {
+            "PN_REQUEST_TIMEOUT" => Some("30s".to_string()),
+            "PN_RETRIES" => Some("0".to_string()),
+            _ => None,
+        };
+        let settings = ClientSettings::resolve(&config, env).unwrap();
+        assert_eq!(settings.connect_timeout, Duration::from_secs(10));
+        assert_eq!(settings.request_timeout, Duration::from_secs(30));
+        assert_eq!(settings.retries, 0);
+    }
+
+    #[test]
+    fn settings_reject_bad_values() {
+        let env = |key: &str| (key == "PN_RETRIES").then(|| "many".to_string());
+        assert!(matches!(
+            ClientSettings::resolve(&ClientConfig::default(), env),
+            Err(PensaError::InvalidArgument(_))
+        ));
+        let config = ClientConfig {
+            connect_timeout: Some("soon".into()),
+            ..Default::default()
+        };
+        assert!(ClientSettings::resolve(&config, |_| None).is_err());
+    }
+
+    #[test]
+    fn backoff_stays_under_exponential_ceiling() {
+        let settings = ClientSettings::default();
+        for attempt in 0..4 {
+            let ceiling = RETRY_BASE_DELAY * 2u32.pow(attempt);
+            assert!(settings.backoff(attempt) < ceiling);
+        }
+    }
+}
@@ -1,26 +1,97 @@
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
 
-use axum::extract::{Path, Query, State};
-use axum::http::{HeaderMap, StatusCode};
+use axum::extract::{ConnectInfo, Path, Query, Request, State};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode, header};
+use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use serde::Deserialize;
 use tokio::sync::Notify;
 
+use crate::api_version::{
+    API_VERSION_HEADER, ApiVersion, DAEMON_VERSION_HEADER, MIN_API_VERSION, MIN_API_VERSION_HEADER,
+};
+use crate::config::{CONFIG_FILE, Config, SpecValidation, parse_duration};
 use crate::db::Db;
 use crate::error::{ErrorResponse, PensaError};
-use crate::types::{CreateIssueParams, IssueType, ListFilters, Priority, Status, UpdateFields};
+use crate::rate_limit::RateLimiter;
+use crate::types::{
+    CreateIssueParams, DryRunReport, HealthReport, Issue, IssueType, ListFilters, PlannedIssue,
+    Priority, Resolution, Status, UpdateFields,
+};
 
 struct DaemonState {
     db: Mutex<Db>,
     project_dir: PathBuf,
     shutdown: Notify,
+    read_only: AtomicBool,
+    /// Written to `.pensa/daemon.token`; `/read-only` only accepts requests
+    /// carrying it, so a client that can merely reach the port cannot lift
+    /// read-only mode.
+    token: String,
+    rate_limiter: RateLimiter,
+    started_at: Instant,
+    config: Mutex<Option<CachedConfig>>,
+}
+
+/// The config as last read, keyed by the file's mtime and length so the
+/// per-request middleware only re-parses it after it changes.
+struct CachedConfig {
+    stamp: Option<(SystemTime, u64)>,
+    config: Option<Arc<Config>>,
+}
+
+impl DaemonState {
+    /// The project config for middleware, or `None` if it fails to load. The
+    /// failure is logged once per change to the file.
+    fn config(&self) -> Option<Arc<Config>> {
+        let pensa_dir = self.project_dir.join(".pensa");
+        let stamp = std::fs::metadata(pensa_dir.join(CONFIG_FILE))
+            .and_then(|meta| Ok((meta.modified()?, meta.len())))
+            .ok();
+        let mut cached = self.config.lock().unwrap();
+        if let Some(cached) = cached.as_ref().filter(|c| c.stamp == stamp) {
+            return cached.config.clone();
+        }
+        let config = match Config::load(&pensa_dir) {
+            Ok(config) => Some(Arc::new(config)),
+            Err(e) => {
+                tracing::warn!("ignoring config for rate limiting and auto-export: {e}");
+                None
+            }
+        };
+        *cached = Some(CachedConfig {
+            stamp,
+            config: config.clone(),
+        });
+        config
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DaemonOptions {
+    pub read_only: bool,
 }
 
 type AppState = Arc<DaemonState>;
 
+pub const TOKEN_FILE: &str = "daemon.token";
+pub const TOKEN_HEADER: &str = "x-pensa-token";
+
+fn write_token(path: &std::path::Path, token: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(token.as_bytes())
+}
+
 struct AppError(PensaError);
 
 impl IntoResponse for AppError {
@@ -30,13 +101,26 @@ impl IntoResponse for AppError {
             PensaError::AlreadyClaimed { .. }
             | PensaError::CycleDetected
             | PensaError::InvalidStatusTransition { .. }
-            | PensaError::DeleteRequiresForce(_) => StatusCode::CONFLICT,
+            | PensaError::DeleteRequiresForce(_)
+            | PensaError::RevisionMismatch { .. } => StatusCode::CONFLICT,
+            PensaError::ReadOnly | PensaError::InvalidToken => StatusCode::FORBIDDEN,
+            PensaError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
             PensaError::SpecNotFound(_) => StatusCode::UNPROCESSABLE_ENTITY,
-            PensaError::FormaUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            PensaError::FormaUnavailable
+            | PensaError::DaemonUnreachable(_)
+            | PensaError::DaemonVersionMismatch(_) => StatusCode::SERVICE_UNAVAILABLE,
+            PensaError::InvalidArgument(_) | PensaError::UnsupportedApiVersion(_) => {
+                StatusCode::BAD_REQUEST
+            }
             PensaError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
         let body = ErrorResponse::from(&self.0);
-        (status, Json(body)).into_response()
+        let mut resp = (status, Json(body)).into_response();
+        if let PensaError::RateLimited { retry_after } = self.0 {
+            resp.headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        resp
     }
 }
 
@@ -97,11 +181,53 @@ async fn validate_spec_against_forma(
     }
 }
 
+async fn validate_spec(
+    project_dir: &std::path::Path,
+    stem: &str,
+) -> Result<Option<String>, PensaError> {
+    let config = Config::load(&project_dir.join(".pensa"))?;
+    match config.specs.validate {
+        SpecValidation::Off => Ok(None),
+        SpecValidation::Forma => validate_spec_against_forma(project_dir, stem)
+            .await
+            .map(|()| None),
+        mode => {
+            let path = config.specs_dir(project_dir).join(format!("{stem}.md"));
+            if path.is_file() {
+                Ok(None)
+            } else if mode == SpecValidation::Reject {
+                Err(PensaError::SpecNotFound(stem.to_string()))
+            } else {
+                Ok(Some(format!(
+                    "spec '{stem}' not found at {}",
+                    path.display()
+                )))
+            }
+        }
+    }
+}
+
+fn with_warnings(mut value: serde_json::Value, warnings: Vec<String>) -> serde_json::Value {
+    if !warnings.is_empty() {
+        value["warnings"] = serde_json::json!(warnings);
+    }
+    value
+}
+
 pub async fn start(port: u16, project_dir: PathBuf) {
     start_with_data_dir(port, project_dir, None).await;
 }
 
 pub async fn start_with_data_dir(port: u16, project_dir: PathBuf, data_dir: Option<PathBuf>) {
+    start_with_options(port, project_dir, data_dir, DaemonOptions::default()).await;
+}
+
+pub async fn start_with_options(
+    port: u16,
+    project_dir: PathBuf,
+    data_dir: Option<PathBuf>,
+    options: DaemonOptions,
+) {
     let db = match data_dir {
         Some(dd) => {
             let pensa_dir = project_dir.join(".pensa");
@@ -113,13 +239,20 @@ pub async fn start_with_data_dir(port: u16, project_dir: PathBuf, data_dir: Opti
         db: Mutex::new(db),
         project_dir: project_dir.clone(),
         shutdown: Notify::new(),
+        read_only: AtomicBool::new(options.read_only),
+        token: crate::id::generate_secret(),
+        rate_limiter: RateLimiter::default(),
+        started_at: Instant::now(),
+        config: Mutex::new(None),
     });
 
     let app = Router::new()
         .route("/issues", get(list_issues).post(create_issue))
         .route("/issues/ready", get(ready_issues))
+        .route("/issues/ready/explain", get(explain_not_ready))
         .route("/issues/blocked", get(blocked_issues))
         .route("/issues/search", get(search_issues))
+        .route("/issues/stale", get(stale_issues))
         .route("/issues/count", get(count_issues))
         .route(
             "/issues/{id}",
@@ -128,9 +261,14 @@ pub async fn start_with_data_dir(port: u16, project_dir: PathBuf, data_dir: Opti
         .route("/issues/{id}/close", post(close_issue))
         .route("/issues/{id}/reopen", post(reopen_issue))
         .route("/issues/{id}/release", post(release_issue))
+        .route("/issues/seed", post(seed_issues))
+        .route("/issues/{id}/check", post(check_item))
         .route("/issues/{id}/history", get(issue_history))
+        .route("/issues/{id}/text", get(issue_text))
+        .route("/issues/{id}/explain", get(explain_ready))
         .route("/issues/{id}/deps", get(list_deps))
         .route("/issues/{id}/deps/tree", get(dep_tree))
+        .route("/issues/{id}/deps/from-refs", post(promote_refs))
         .route(
             "/issues/{id}/comments",
             get(list_comments).post(add_comment),
@@ -145,13 +283,57 @@ pub async fn start_with_data_dir(port: u16, project_dir: PathBuf, data_dir: Opti
             get(list_doc_refs).post(add_doc_ref),
         )
         .route("/doc-refs/{id}", axum::routing::delete(remove_doc_ref))
+        .route("/issues/{id}/commits", get(list_commits).post(link_commit))
+        .route("/issues/{id}/labels", get(list_labels).post(add_label))
+        .route(
+            "/issues/{id}/labels/{label}",
+            axum::routing::delete(remove_label),
+        )
+        .route(
+            "/issues/{id}/watchers",
+            get(list_watchers).post(watch_issue),
+        )
+        .route(
+            "/issues/{id}/watchers/{actor}",
+            axum::routing::delete(unwatch_issue),
+        )
+        .route("/watching", get(watched_issues))
+        .route("/inbox", get(inbox))
+        .route("/inbox/read", post(mark_inbox_read))
+        .route("/webhooks", get(list_webhooks).post(add_webhook))
+        .route("/webhooks/{id}", axum::routing::delete(remove_webhook))
+        .route("/actors", get(list_actors))
+        .route("/actors/rename", post(rename_actor))
+        .route("/actors/alias", post(alias_actor))
+        .route("/snapshots", get(list_snapshots).post(create_snapshot))
+        .route("/diff", get(diff))
         .route("/deps", post(add_dep).delete(remove_dep))
         .route("/deps/cycles", get(detect_cycles))
+        .route("/deps/resolve", post(resolve_external_dep))
         .route("/export", post(export_jsonl))
         .route("/import", post(import_jsonl))
         .route("/doctor", post(doctor))
+        .route("/escalate", post(escalate))
         .route("/status", get(project_status))
+        .route("/workload", get(workload))
+        .route("/specs/status", get(spec_status))
+        .route("/specs/{spec}/status", get(spec_detail))
         .route("/shutdown", post(shutdown_endpoint))
+        .route("/read-only", get(get_read_only).post(set_read_only))
+        .route("/health", get(health))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            export_after_writes,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            reject_writes_when_read_only,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            limit_rate,
+        ))
+        .layer(axum::middleware::from_fn(negotiate_api_version))
         .with_state(state.clone());
 
     let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}"))
@@ -161,7 +343,11 @@ pub async fn start_with_data_dir(port: u16, project_dir: PathBuf, data_dir: Opti
     let pensa_dir = project_dir.join(".pensa");
     let port_file = pensa_dir.join("daemon.port");
     let project_file = pensa_dir.join("daemon.project");
+    let token_file = pensa_dir.join(TOKEN_FILE);
     let _ = std::fs::create_dir_all(&pensa_dir);
+    if let Err(e) = write_token(&token_file, &state.token) {
+        tracing::warn!("failed to write token file: {e}");
+    }
     if let Err(e) = std::fs::write(&port_file, port.to_string()) {
         tracing::warn!("failed to write port file: {e}");
     }
@@ -172,15 +358,169 @@ pub async fn start_with_data_dir(port: u16, project_dir: PathBuf, data_dir: Opti
         tracing::warn!("failed to write project file: {e}");
     }
 
+    tokio::spawn(escalation_task(state.clone()));
+    tokio::spawn(webhook_task(state.clone()));
+
     tracing::info!("pensa daemon listening on port {port}");
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(state))
-        .await
-        .expect("server error");
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(state.clone()))
+    .await
+    .expect("server error");
+
+    // In-flight requests have finished. Keep the lock so the background
+    // tasks cannot write after the final export.
+    let db = state.db.lock().unwrap();
+    if project_dir.exists() {
+        if let Err(e) = db.export_jsonl() {
+            tracing::warn!("final export failed: {e}");
+        }
+        if let Err(e) = db.checkpoint() {
+            tracing::warn!("final checkpoint failed: {e}");
+        }
+    }
 
     let _ = std::fs::remove_file(&port_file);
     let _ = std::fs::remove_file(&project_file);
+    let _ = std::fs::remove_file(&token_file);
+    drop(db);
+}
+
+async fn negotiate_api_version(mut req: Request, next: Next) -> Response {
+    let requested = req
+        .headers()
+        .get(API_VERSION_HEADER)
+        .map(|v| v.to_str().unwrap_or_default().to_string());
+    let (version, mut resp) = match ApiVersion::negotiate(requested.as_deref()) {
+        Ok(version) => {
+            req.extensions_mut().insert(version);
+            (version, next.run(req).await)
+        }
+        Err(e) => (ApiVersion::default(), AppError(e).into_response()),
+    };
+    let headers = resp.headers_mut();
+    headers.insert(API_VERSION_HEADER, HeaderValue::from(version.0));
+    headers.insert(MIN_API_VERSION_HEADER, HeaderValue::from(MIN_API_VERSION));
+    headers.insert(
+        DAEMON_VERSION_HEADER,
+        HeaderValue::from_static(env!("CARGO_PKG_VERSION")),
+    );
+    resp
+}
+
+/// Requests that only read state, or that operators need while the daemon is
+/// frozen (toggling the mode, exporting a backup, shutting down). Dry runs
+/// roll back whatever they change, so they count as reads, but only on the
+/// routes whose handlers honor `dry_run`; any other route would ignore the
+/// flag and write.
+fn allowed_when_read_only(req: &Request) -> bool {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return true;
+    }
+    let query = req.uri().query().unwrap_or_default();
+    if query.split('&').any(|p| p == "dry_run=true") && has_dry_run(req.method(), req.uri().path())
+    {
+        return true;
+    }
+    match req.uri().path() {
+        "/read-only" | "/export" | "/shutdown" => true,
+        "/doctor" => !query.split('&').any(|p| p == "fix=true"),
+        _ => false,
+    }
+}
+
+fn has_dry_run(method: &Method, path: &str) -> bool {
+    match *method {
+        Method::DELETE => path
+            .strip_prefix("/issues/")
+            .is_some_and(|id| !id.is_empty() && !id.contains('/')),
+        Method::POST => matches!(path, "/import" | "/doctor" | "/escalate"),
+        _ => false,
+    }
+}
+
+async fn reject_writes_when_read_only(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if state.read_only.load(Ordering::SeqCst) && !allowed_when_read_only(&req) {
+        return AppError(PensaError::ReadOnly).into_response();
+    }
+    next.run(req).await
+}
+
+/// Applies `[rate_limit]` per actor, or per client address for requests
+/// without `X-Pensa-Actor`. Shutdown and the read-only toggle are exempt so
+/// an operator can always stop a runaway client.
+async fn limit_rate(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let exempt = matches!(req.uri().path(), "/shutdown" | "/read-only");
+    let limit = state.config().and_then(|c| c.rate_limit());
+    if let Some(limit) = limit.filter(|_| !exempt) {
+        let key = match actor_from_headers(req.headers()) {
+            Some(actor) => format!("actor:{actor}"),
+            None => match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+                Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+                None => "ip:unknown".to_string(),
+            },
+        };
+        if let Err(wait) = state.rate_limiter.check(&key, limit, Instant::now()) {
+            tracing::debug!(%key, "rate limited");
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            return AppError(PensaError::RateLimited { retry_after }).into_response();
+        }
+    }
+    next.run(req).await
+}
+
+async fn export_after_writes(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let writes = !allowed_when_read_only(&req) && req.uri().path() != "/import";
+    let resp = next.run(req).await;
+    if !writes || !resp.status().is_success() {
+        return resp;
+    }
+    let auto = state.config().is_some_and(|c| c.export.auto);
+    if auto && let Err(e) = state.db.lock().unwrap().export_jsonl() {
+        tracing::warn!("auto-export failed: {e}");
+    }
+    resp
+}
+
+#[derive(Deserialize)]
+struct ReadOnlyBody {
+    enabled: bool,
+}
+
+async fn get_read_only(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "read_only": state.read_only.load(Ordering::SeqCst) }))
+}
+
+async fn set_read_only(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<ReadOnlyBody>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let token = headers.get(TOKEN_HEADER).and_then(|v| v.to_str().ok());
+    if token != Some(state.token.as_str()) {
+        return Err(AppError(PensaError::InvalidToken));
+    }
+    let previous = state.read_only.swap(body.enabled, Ordering::SeqCst);
+    if previous != body.enabled {
+        tracing::info!(read_only = body.enabled, "read-only mode toggled");
+    }
+    Ok(Json(serde_json::json!({ "read_only": body.enabled })))
+}
+
+async fn health(State(state): State<AppState>) -> Result<Json<HealthReport>, AppError> {
+    let db = state.db.lock().unwrap().stats()?;
+    Ok(Json(HealthReport {
+        uptime_secs: state.started_at.elapsed().as_secs(),
+        read_only: state.read_only.load(Ordering::SeqCst),
+        db,
+    }))
 }
 
 async fn shutdown_endpoint(State(state): State<AppState>) -> StatusCode {
@@ -188,6 +528,110 @@ async fn shutdown_endpoint(State(state): State<AppState>) -> StatusCode {
     StatusCode::OK
 }
 
+/// Periodically applies the `[escalation]` policy. The config is re-read on
+/// every tick so edits take effect without restarting the daemon.
+async fn escalation_task(state: AppState) {
+    loop {
+        let policy = Config::load(&state.project_dir.join(".pensa"))
+            .and_then(|config| config.escalation_policy());
+        let interval = match &policy {
+            Ok(Some(policy)) => policy.interval,
+            _ => chrono::TimeDelta::hours(1),
+        };
+        tokio::time::sleep(
+            interval
+                .to_std()
+                .unwrap_or(std::time::Duration::from_secs(3600)),
+        )
+        .await;
+
+        if state.read_only.load(Ordering::SeqCst) {
+            continue;
+        }
+        match policy {
+            Ok(Some(policy)) => {
+                let db = state.db.lock().unwrap();
+                match db.escalate_priorities(&policy, false) {
+                    Ok(escalated) if !escalated.is_empty() => {
+                        tracing::info!(count = escalated.len(), "escalated issue priorities");
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("priority escalation failed: {e}"),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("skipping priority escalation: {e}"),
+        }
+    }
+}
+
+const WEBHOOK_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+const WEBHOOK_BATCH: usize = 100;
+
+/// Delivers new events to every webhook, each in event order and all
+/// webhooks concurrently. An event that still fails after its retries is
+/// logged and skipped, so one dead endpoint cannot hold the others back.
+async fn webhook_task(state: AppState) {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .expect("failed to build webhook client");
+    loop {
+        tokio::time::sleep(WEBHOOK_POLL_INTERVAL).await;
+
+        let batches = {
+            let db = state.db.lock().unwrap();
+            let pending = db.latest_event_id().and_then(|latest| {
+                db.list_webhooks()?
+                    .into_iter()
+                    .map(|hook| {
+                        let events = db.pending_webhook_events(&hook, WEBHOOK_BATCH)?;
+                        Ok((hook, events, latest))
+                    })
+                    .collect::<Result<Vec<_>, PensaError>>()
+            });
+            match pending {
+                Ok(pending) => pending,
+                Err(e) => {
+                    tracing::warn!("failed to load webhook deliveries: {e}");
+                    continue;
+                }
+            }
+        };
+
+        let mut deliveries = tokio::task::JoinSet::new();
+        for (hook, events, latest) in batches {
+            let state = state.clone();
+            let client = client.clone();
+            deliveries.spawn(async move {
+                let full_batch = events.len() == WEBHOOK_BATCH;
+                for (event, issue) in events {
+                    if let Err(e) = crate::webhooks::deliver(&client, &hook, &event, &issue).await {
+                        tracing::warn!(
+                            webhook = %hook.id,
+                            event = event.id,
+                            "giving up on webhook delivery: {e}"
+                        );
+                    }
+                    let db = state.db.lock().unwrap();
+                    if let Err(e) = db.mark_webhook_delivered(&hook.id, event.id) {
+                        tracing::warn!("failed to update webhook cursor: {e}");
+                    }
+                }
+                // Events past the filters are skipped over once caught up.
+                if !full_batch {
+                    let db = state.db.lock().unwrap();
+                    if let Err(e) = db.mark_webhook_delivered(&hook.id, latest) {
+                        tracing::warn!("failed to update webhook cursor: {e}");
+                    }
+                }
+            });
+        }
+        deliveries.join_all().await;
+    }
+}
+
 async fn shutdown_signal(state: AppState) {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
@@ -250,8 +694,7 @@ async fn shutdown_signal(state: AppState) {
 struct CreateIssueBody {
     title: String,
     issue_type: IssueType,
-    #[serde(default = "default_priority")]
-    priority: Priority,
+    priority: Option<Priority>,
     description: Option<String>,
     spec: Option<String>,
     fixes: Option<String>,
@@ -261,10 +704,6 @@ struct CreateIssueBody {
     actor: Option<String>,
 }
 
-fn default_priority() -> Priority {
-    Priority::P2
-}
-
 async fn create_issue(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -275,34 +714,96 @@ async fn create_issue(
         .or_else(|| actor_from_headers(&headers))
         .unwrap_or_else(|| "unknown".to_string());
 
+    let mut warnings = Vec::new();
     if let Some(ref spec) = body.spec {
-        validate_spec_against_forma(&state.project_dir, spec).await?;
+        warnings.extend(validate_spec(&state.project_dir, spec).await?);
     }
 
+    let config = Config::load(&state.project_dir.join(".pensa"))?;
+    let route = config
+        .route(body.spec.as_deref())
+        .cloned()
+        .unwrap_or_default();
     let params = CreateIssueParams {
         title: body.title,
         issue_type: body.issue_type,
-        priority: body.priority,
+        priority: body
+            .priority
+            .unwrap_or_else(|| config.default_priority_for(body.spec.as_deref())),
         description: body.description,
         spec: body.spec,
         fixes: body.fixes,
-        assignee: body.assignee,
+        assignee: body.assignee.or(route.assignee),
         deps: body.deps,
+        labels: route.labels,
         actor,
     };
 
     let db = state.db.lock().unwrap();
     let issue = db.create_issue(&params)?;
-    Ok((StatusCode::CREATED, Json(issue)))
+    let value = with_warnings(serde_json::to_value(issue).unwrap(), warnings);
+    Ok((StatusCode::CREATED, Json(value)))
+}
+
+#[derive(Deserialize)]
+struct SeedIssuesBody {
+    issues: Vec<PlannedIssue>,
+}
+
+async fn seed_issues(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<SeedIssuesBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let actor = actor_from_headers(&headers).unwrap_or_else(|| "unknown".to_string());
+
+    let mut warnings = Vec::new();
+    let mut specs: Vec<&str> = body
+        .issues
+        .iter()
+        .filter_map(|i| i.spec.as_deref())
+        .collect();
+    specs.sort_unstable();
+    specs.dedup();
+    for spec in specs {
+        warnings.extend(validate_spec(&state.project_dir, spec).await?);
+    }
+
+    let db = state.db.lock().unwrap();
+    let issues = db.create_issues(&body.issues, &actor)?;
+    let value = with_warnings(serde_json::json!({ "issues": issues }), warnings);
+    Ok((StatusCode::CREATED, Json(value)))
+}
+
+fn etag(rev: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{rev}\"")).unwrap()
+}
+
+/// The revision an `If-Match` header requires, if any. Accepts the quoted
+/// `ETag` form as well as a bare number.
+fn if_match(headers: &HeaderMap) -> Result<Option<i64>, PensaError> {
+    let Some(value) = headers.get(axum::http::header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let raw = value.to_str().unwrap_or_default().trim();
+    raw.trim_start_matches("W/")
+        .trim_matches('"')
+        .parse()
+        .map(Some)
+        .map_err(|_| PensaError::InvalidArgument(format!("If-Match must be a revision: {raw}")))
 }
 
 async fn get_issue(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<serde_json::Value>, AppError> {
+) -> Result<Response, AppError> {
     let db = state.db.lock().unwrap();
     let detail = db.get_issue(&id)?;
-    Ok(Json(serde_json::to_value(detail).unwrap()))
+    let rev = detail.issue.rev;
+    let mut resp = Json(serde_json::to_value(detail).unwrap()).into_response();
+    resp.headers_mut()
+        .insert(axum::http::header::ETAG, etag(rev));
+    Ok(resp)
 }
 
 #[derive(Deserialize)]
@@ -332,11 +833,16 @@ async fn update_issue(
         .or_else(|| actor_from_headers(&headers))
         .unwrap_or_else(|| "unknown".to_string());
 
+    let mut warnings = Vec::new();
     if let Some(ref spec) = body.spec {
-        validate_spec_against_forma(&state.project_dir, spec).await?;
+        warnings.extend(validate_spec(&state.project_dir, spec).await?);
     }
 
+    let expect_rev = if_match(&headers)?;
     let db = state.db.lock().unwrap();
+    if body.claim || body.unclaim {
+        db.check_rev(&id, expect_rev)?;
+    }
 
     if body.claim {
         let issue = db.claim_issue(&id, &actor)?;
@@ -356,31 +862,42 @@ async fn update_issue(
         assignee: body.assignee,
         spec: body.spec,
         fixes: body.fixes,
+        expect_rev,
     };
 
     let issue = db.update_issue(&id, &fields, &actor)?;
-    Ok(Json(serde_json::to_value(issue).unwrap()))
+    Ok(Json(with_warnings(
+        serde_json::to_value(issue).unwrap(),
+        warnings,
+    )))
 }
 
 #[derive(Deserialize)]
 struct DeleteQuery {
     #[serde(default)]
     force: bool,
+    #[serde(default)]
+    dry_run: bool,
 }
 
 async fn delete_issue(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Query(query): Query<DeleteQuery>,
-) -> Result<StatusCode, AppError> {
+) -> Result<Response, AppError> {
     let db = state.db.lock().unwrap();
+    if query.dry_run {
+        let ((), changes) = db.preview(|db| db.delete_issue(&id, query.force))?;
+        return Ok(Json(DryRunReport { changes }).into_response());
+    }
     db.delete_issue(&id, query.force)?;
-    Ok(StatusCode::NO_CONTENT)
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 #[derive(Deserialize)]
 struct CloseBody {
     reason: Option<String>,
+    resolution: Option<Resolution>,
     #[serde(default)]
     force: bool,
     actor: Option<String>,
@@ -398,13 +915,25 @@ async fn close_issue(
         .unwrap_or_else(|| "unknown".to_string());
 
     let db = state.db.lock().unwrap();
-    let issue = db.close_issue(&id, body.reason.as_deref(), body.force, &actor)?;
-    Ok(Json(serde_json::to_value(issue).unwrap()))
+    let (issue, auto_closed) = db.close_issue_chain(
+        &id,
+        body.reason.as_deref(),
+        body.resolution,
+        body.force,
+        &actor,
+    )?;
+    let mut value = serde_json::to_value(issue).unwrap();
+    if !auto_closed.is_empty() {
+        value["auto_closed"] = serde_json::json!(auto_closed);
+    }
+    Ok(Json(value))
 }
 
 #[derive(Deserialize)]
 struct ReopenBody {
     reason: Option<String>,
+    #[serde(default)]
+    cascade: bool,
     actor: Option<String>,
 }
 
@@ -420,7 +949,7 @@ async fn reopen_issue(
         .unwrap_or_else(|| "unknown".to_string());
 
     let db = state.db.lock().unwrap();
-    let issue = db.reopen_issue(&id, body.reason.as_deref(), &actor)?;
+    let issue = db.reopen_issue(&id, body.reason.as_deref(), body.cascade, &actor)?;
     Ok(Json(serde_json::to_value(issue).unwrap()))
 }
 
@@ -436,6 +965,24 @@ async fn release_issue(
     Ok(Json(serde_json::to_value(issue).unwrap()))
 }
 
+#[derive(Deserialize)]
+struct CheckItemBody {
+    index: usize,
+}
+
+async fn check_item(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<CheckItemBody>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let actor = actor_from_headers(&headers).unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let issue = db.check_item(&id, body.index, &actor)?;
+    Ok(Json(serde_json::to_value(issue).unwrap()))
+}
+
 // --- Query endpoints ---
 
 #[derive(Deserialize)]
@@ -446,8 +993,36 @@ struct ListQuery {
     #[serde(rename = "type")]
     issue_type: Option<IssueType>,
     spec: Option<String>,
+    resolution: Option<Resolution>,
     sort: Option<String>,
     limit: Option<usize>,
+    fields: Option<String>,
+}
+
+fn select_fields(
+    issues: Vec<Issue>,
+    fields: Option<&str>,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let values = issues.into_iter().map(|i| serde_json::to_value(i).unwrap());
+    let Some(fields) = fields.filter(|f| !f.trim().is_empty()) else {
+        return Ok(values.collect());
+    };
+    let fields: Vec<&str> = fields.split(',').map(str::trim).collect();
+    if let Some(unknown) = fields.iter().find(|f| !Issue::FIELDS.contains(f)) {
+        return Err(PensaError::InvalidArgument(format!(
+            "unknown field: {unknown} (expected one of {})",
+            Issue::FIELDS.join(", ")
+        ))
+        .into());
+    }
+    Ok(values
+        .map(|mut value| {
+            if let Some(obj) = value.as_object_mut() {
+                obj.retain(|k, _| fields.contains(&k.as_str()));
+            }
+            value
+        })
+        .collect())
 }
 
 async fn list_issues(
@@ -460,17 +1035,15 @@ async fn list_issues(
         assignee: query.assignee,
         issue_type: query.issue_type,
         spec: query.spec,
+        resolution: query.resolution,
         sort: query.sort,
         limit: query.limit,
+        ..Default::default()
     };
 
     let db = state.db.lock().unwrap();
     let issues = db.list_issues(&filters)?;
-    let values: Vec<serde_json::Value> = issues
-        .into_iter()
-        .map(|i| serde_json::to_value(i).unwrap())
-        .collect();
-    Ok(Json(values))
+    Ok(Json(select_fields(issues, query.fields.as_deref())?))
 }
 
 #[derive(Deserialize)]
@@ -481,28 +1054,49 @@ struct ReadyQuery {
     issue_type: Option<IssueType>,
     spec: Option<String>,
     limit: Option<usize>,
+    fields: Option<String>,
+}
+
+impl ReadyQuery {
+    fn filters(self) -> ListFilters {
+        ListFilters {
+            priority: self.priority,
+            assignee: self.assignee,
+            issue_type: self.issue_type,
+            spec: self.spec,
+            limit: self.limit,
+            ..Default::default()
+        }
+    }
 }
 
 async fn ready_issues(
     State(state): State<AppState>,
     Query(query): Query<ReadyQuery>,
 ) -> Result<Json<Vec<serde_json::Value>>, AppError> {
-    let filters = ListFilters {
-        priority: query.priority,
-        assignee: query.assignee,
-        issue_type: query.issue_type,
-        spec: query.spec,
-        limit: query.limit,
-        ..Default::default()
-    };
+    let fields = query.fields.clone();
+    let db = state.db.lock().unwrap();
+    let issues = db.ready_issues(&query.filters())?;
+    Ok(Json(select_fields(issues, fields.as_deref())?))
+}
 
+async fn explain_not_ready(
+    State(state): State<AppState>,
+    Query(query): Query<ReadyQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
     let db = state.db.lock().unwrap();
-    let issues = db.ready_issues(&filters)?;
-    let values: Vec<serde_json::Value> = issues
-        .into_iter()
-        .map(|i| serde_json::to_value(i).unwrap())
-        .collect();
-    Ok(Json(values))
+    let explanations = db.explain_not_ready(&query.filters())?;
+    Ok(Json(serde_json::to_value(explanations).unwrap()))
+}
+
+async fn explain_ready(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ReadyQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.lock().unwrap();
+    let explanation = db.explain_ready(&id, &query.filters())?;
+    Ok(Json(serde_json::to_value(explanation).unwrap()))
 }
 
 async fn blocked_issues(
@@ -520,6 +1114,7 @@ async fn blocked_issues(
 #[derive(Deserialize)]
 struct SearchQuery {
     q: String,
+    fields: Option<String>,
 }
 
 async fn search_issues(
@@ -528,11 +1123,30 @@ async fn search_issues(
 ) -> Result<Json<Vec<serde_json::Value>>, AppError> {
     let db = state.db.lock().unwrap();
     let issues = db.search_issues(&query.q)?;
-    let values: Vec<serde_json::Value> = issues
-        .into_iter()
-        .map(|i| serde_json::to_value(i).unwrap())
-        .collect();
-    Ok(Json(values))
+    Ok(Json(select_fields(issues, query.fields.as_deref())?))
+}
+
+const DEFAULT_STALE_DAYS: i64 = 14;
+
+#[derive(Deserialize)]
+struct StaleQuery {
+    days: Option<i64>,
+    status: Option<Status>,
+}
+
+async fn stale_issues(
+    State(state): State<AppState>,
+    Query(query): Query<StaleQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_STALE_DAYS);
+    if days < 0 {
+        return Err(
+            PensaError::InvalidArgument(format!("days must not be negative, got {days}")).into(),
+        );
+    }
+    let db = state.db.lock().unwrap();
+    let stale = db.stale_issues(chrono::TimeDelta::days(days), query.status)?;
+    Ok(Json(serde_json::to_value(stale).unwrap()))
 }
 
 #[derive(Deserialize)]
@@ -545,6 +1159,13 @@ struct CountQuery {
     by_issue_type: bool,
     #[serde(default)]
     by_assignee: bool,
+    #[serde(default)]
+    by_spec: bool,
+    #[serde(default)]
+    by_label: bool,
+    #[serde(default)]
+    by_resolution: bool,
+    resolution: Option<Resolution>,
 }
 
 async fn count_issues(
@@ -564,12 +1185,39 @@ async fn count_issues(
     if query.by_assignee {
         group_by.push("assignee");
     }
+    if query.by_spec {
+        group_by.push("spec");
+    }
+    if query.by_label {
+        group_by.push("label");
+    }
+    if query.by_resolution {
+        group_by.push("resolution");
+    }
 
     let db = state.db.lock().unwrap();
-    let result = db.count_issues(&group_by)?;
+    let result = db.count_issues(&group_by, query.resolution)?;
     Ok(Json(result))
 }
 
+#[derive(Deserialize)]
+struct WorkloadQuery {
+    max_active: Option<i64>,
+}
+
+async fn workload(
+    State(state): State<AppState>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.lock().unwrap();
+    let max_active = match query.max_active {
+        Some(n) => n,
+        None => Config::load(&db.pensa_dir)?.max_active(),
+    };
+    let report = db.workload(max_active)?;
+    Ok(Json(serde_json::to_value(report).unwrap()))
+}
+
 async fn project_status(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<serde_json::Value>>, AppError> {
@@ -582,6 +1230,43 @@ async fn project_status(
     Ok(Json(values))
 }
 
+async fn spec_status(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<serde_json::Value>>, AppError> {
+    let db = state.db.lock().unwrap();
+    let entries = db.spec_status()?;
+    let values: Vec<serde_json::Value> = entries
+        .into_iter()
+        .map(|e| serde_json::to_value(e).unwrap())
+        .collect();
+    Ok(Json(values))
+}
+
+async fn spec_detail(
+    State(state): State<AppState>,
+    Path(spec): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.lock().unwrap();
+    let detail = db.spec_detail(&spec)?;
+    Ok(Json(serde_json::to_value(detail).unwrap()))
+}
+
+#[derive(Deserialize)]
+struct TextQuery {
+    at: String,
+}
+
+async fn issue_text(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TextQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let at = crate::db::parse_date(&query.at)?;
+    let db = state.db.lock().unwrap();
+    let text = db.issue_text_at(&id, at)?;
+    Ok(Json(serde_json::to_value(text).unwrap()))
+}
+
 async fn issue_history(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -623,6 +1308,18 @@ async fn add_dep(
     })))
 }
 
+async fn promote_refs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let actor = actor_from_headers(&headers).unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let result = db.promote_refs(&id, &actor)?;
+    Ok(Json(serde_json::to_value(result).unwrap()))
+}
+
 #[derive(Deserialize)]
 struct RemoveDepQuery {
     issue_id: String,
@@ -645,6 +1342,34 @@ async fn remove_dep(
     })))
 }
 
+#[derive(Deserialize)]
+struct ResolveDepBody {
+    issue_id: String,
+    target: String,
+    #[serde(default = "default_true")]
+    resolved: bool,
+    actor: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+async fn resolve_external_dep(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<ResolveDepBody>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let actor = body
+        .actor
+        .or_else(|| actor_from_headers(&headers))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let dep = db.resolve_external_dep(&body.issue_id, &body.target, body.resolved, &actor)?;
+    Ok(Json(serde_json::to_value(dep).unwrap()))
+}
+
 async fn list_deps(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -818,6 +1543,324 @@ async fn remove_doc_ref(
     Ok(StatusCode::NO_CONTENT)
 }
 
+// --- Commit endpoints ---
+
+#[derive(Deserialize)]
+struct LinkCommitBody {
+    sha: String,
+    summary: Option<String>,
+    actor: Option<String>,
+}
+
+async fn link_commit(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<LinkCommitBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let actor = body
+        .actor
+        .or_else(|| actor_from_headers(&headers))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let link = db.link_commit(&id, &body.sha, body.summary.as_deref(), &actor)?;
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+async fn list_commits(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<serde_json::Value>>, AppError> {
+    let db = state.db.lock().unwrap();
+    let commits = db.list_commits(&id)?;
+    let values: Vec<serde_json::Value> = commits
+        .into_iter()
+        .map(|c| serde_json::to_value(c).unwrap())
+        .collect();
+    Ok(Json(values))
+}
+
+// --- Label endpoints ---
+
+#[derive(Deserialize)]
+struct AddLabelBody {
+    label: String,
+    actor: Option<String>,
+}
+
+async fn add_label(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<AddLabelBody>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let actor = body
+        .actor
+        .or_else(|| actor_from_headers(&headers))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let labels = db.add_label(&id, &body.label, &actor)?;
+    Ok(Json(labels))
+}
+
+async fn remove_label(
+    State(state): State<AppState>,
+    Path((id, label)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<String>>, AppError> {
+    let actor = actor_from_headers(&headers).unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let labels = db.remove_label(&id, &label, &actor)?;
+    Ok(Json(labels))
+}
+
+async fn list_labels(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let db = state.db.lock().unwrap();
+    let labels = db.list_labels(&id)?;
+    Ok(Json(labels))
+}
+
+// --- Watch endpoints ---
+
+#[derive(Deserialize)]
+struct WatchBody {
+    actor: Option<String>,
+}
+
+async fn watch_issue(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<WatchBody>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let actor = body
+        .actor
+        .or_else(|| actor_from_headers(&headers))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let watchers = db.watch_issue(&id, &actor)?;
+    Ok(Json(watchers))
+}
+
+async fn unwatch_issue(
+    State(state): State<AppState>,
+    Path((id, actor)): Path<(String, String)>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let db = state.db.lock().unwrap();
+    let watchers = db.unwatch_issue(&id, &actor)?;
+    Ok(Json(watchers))
+}
+
+async fn list_watchers(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let db = state.db.lock().unwrap();
+    let watchers = db.list_watchers(&id)?;
+    Ok(Json(watchers))
+}
+
+async fn watched_issues(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<serde_json::Value>>, AppError> {
+    let actor = actor_from_headers(&headers).unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let issues = db.watched_issues(&actor)?;
+    let values: Vec<serde_json::Value> = issues
+        .into_iter()
+        .map(|i| serde_json::to_value(i).unwrap())
+        .collect();
+    Ok(Json(values))
+}
+
+async fn inbox(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let actor = actor_from_headers(&headers).unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let inbox = db.inbox(&actor)?;
+    Ok(Json(serde_json::to_value(inbox).unwrap()))
+}
+
+#[derive(Deserialize)]
+struct InboxReadBody {
+    up_to: i64,
+    actor: Option<String>,
+}
+
+async fn mark_inbox_read(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<InboxReadBody>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let actor = body
+        .actor
+        .or_else(|| actor_from_headers(&headers))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let db = state.db.lock().unwrap();
+    let last_read = db.mark_inbox_read(&actor, body.up_to)?;
+    Ok(Json(
+        serde_json::json!({ "actor": actor, "last_read": last_read }),
+    ))
+}
+
+// --- Webhook endpoints ---
+
+#[derive(Deserialize)]
+struct AddWebhookBody {
+    url: String,
+    #[serde(default)]
+    events: Vec<String>,
+    spec: Option<String>,
+}
+
+async fn add_webhook(
+    State(state): State<AppState>,
+    Json(body): Json<AddWebhookBody>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let db = state.db.lock().unwrap();
+    let webhook = db.add_webhook(&body.url, &body.events, body.spec.as_deref())?;
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::to_value(webhook).unwrap()),
+    ))
+}
+
+async fn list_webhooks(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<serde_json::Value>>, AppError> {
+    let db = state.db.lock().unwrap();
+    let values = db
+        .list_webhooks()?
+        .into_iter()
+        .map(|mut w| {
+            w.secret = None;
+            serde_json::to_value(w).unwrap()
+        })
+        .collect();
+    Ok(Json(values))
+}
+
+async fn remove_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let db = state.db.lock().unwrap();
+    db.remove_webhook(&id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// --- Actor endpoints ---
+
+async fn list_actors(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<serde_json::Value>>, AppError> {
+    let db = state.db.lock().unwrap();
+    let values = db
+        .list_actors()?
+        .into_iter()
+        .map(|a| serde_json::to_value(a).unwrap())
+        .collect();
+    Ok(Json(values))
+}
+
+#[derive(Deserialize)]
+struct RenameActorBody {
+    name: String,
+    display_name: Option<String>,
+}
+
+async fn rename_actor(
+    State(state): State<AppState>,
+    Json(body): Json<RenameActorBody>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.lock().unwrap();
+    let actor = db.rename_actor(&body.name, body.display_name.as_deref())?;
+    Ok(Json(serde_json::to_value(actor).unwrap()))
+}
+
+#[derive(Deserialize)]
+struct AliasActorBody {
+    alias: String,
+    actor: Option<String>,
+}
+
+async fn alias_actor(
+    State(state): State<AppState>,
+    Json(body): Json<AliasActorBody>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.lock().unwrap();
+    let actor = match &body.actor {
+        Some(actor) => db.alias_actor(&body.alias, actor)?,
+        None => db.unalias_actor(&body.alias)?,
+    };
+    Ok(Json(serde_json::to_value(actor).unwrap()))
+}
+
+// --- Snapshot endpoints ---
+
+#[derive(Deserialize)]
+struct CreateSnapshotBody {
+    label: Option<String>,
+}
+
+async fn create_snapshot(
+    State(state): State<AppState>,
+    Json(body): Json<CreateSnapshotBody>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let db = state.db.lock().unwrap();
+    let snapshot = db.create_snapshot(body.label.as_deref())?;
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::to_value(snapshot).unwrap()),
+    ))
+}
+
+async fn list_snapshots(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.lock().unwrap();
+    let snapshots = db.list_snapshots()?;
+    Ok(Json(serde_json::to_value(snapshots).unwrap()))
+}
+
+#[derive(Deserialize)]
+struct DiffQuery {
+    from: Option<String>,
+    to: Option<String>,
+    since: Option<String>,
+}
+
+async fn diff(
+    State(state): State<AppState>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.lock().unwrap();
+    let diff = match (query.from, query.since) {
+        (Some(from), None) => db.diff_snapshots(&from, query.to.as_deref())?,
+        (None, Some(since)) => db.diff_since(crate::db::parse_date(&since)?)?,
+        _ => {
+            return Err(PensaError::InvalidArgument(
+                "give either a snapshot to diff from or --since".to_string(),
+            )
+            .into());
+        }
+    };
+    Ok(Json(serde_json::to_value(diff).unwrap()))
+}
+
 // --- Data endpoints ---
 
 async fn export_jsonl(State(state): State<AppState>) -> Result<Json<serde_json::Value>, AppError> {
@@ -826,8 +1869,23 @@ async fn export_jsonl(State(state): State<AppState>) -> Result<Json<serde_json::
     Ok(Json(serde_json::to_value(result).unwrap()))
 }
 
-async fn import_jsonl(State(state): State<AppState>) -> Result<Json<serde_json::Value>, AppError> {
+#[derive(Deserialize)]
+struct DryRunQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+async fn import_jsonl(
+    State(state): State<AppState>,
+    Query(query): Query<DryRunQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
     let db = state.db.lock().unwrap();
+    if query.dry_run {
+        let (_, changes) = db.preview(Db::import_jsonl)?;
+        return Ok(Json(
+            serde_json::to_value(DryRunReport { changes }).unwrap(),
+        ));
+    }
     let result = db.import_jsonl()?;
     Ok(Json(serde_json::to_value(result).unwrap()))
 }
@@ -836,6 +1894,9 @@ async fn import_jsonl(State(state): State<AppState>) -> Result<Json<serde_json::
 struct DoctorQuery {
     #[serde(default)]
     fix: bool,
+    #[serde(default)]
+    dry_run: bool,
+    stale_after: Option<String>,
 }
 
 async fn doctor(
@@ -843,6 +1904,49 @@ async fn doctor(
     Query(query): Query<DoctorQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let db = state.db.lock().unwrap();
-    let report = db.doctor(query.fix)?;
+    let config = Config::load(&db.pensa_dir)?;
+    let stale_after = match query.stale_after.as_deref() {
+        Some(s) => Some(parse_duration(s)?),
+        None => config.stale_after()?,
+    };
+    let specs_dir = config
+        .specs
+        .validate
+        .checks_files()
+        .then(|| config.specs_dir(&state.project_dir));
+    let mut report = if query.fix && query.dry_run {
+        let (mut report, changes) =
+            db.preview(|db| db.doctor(true, stale_after, specs_dir.as_deref()))?;
+        report.dry_run = true;
+        report.changes = changes;
+        report
+    } else {
+        db.doctor(query.fix, stale_after, specs_dir.as_deref())?
+    };
+    if let Some(after) = config.stale_issue_after()? {
+        report.findings.extend(db.stale_issue_findings(after)?);
+    }
     Ok(Json(serde_json::to_value(report).unwrap()))
 }
+
+#[derive(Deserialize)]
+struct EscalateQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+async fn escalate(
+    State(state): State<AppState>,
+    Query(query): Query<EscalateQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.lock().unwrap();
+    let policy = Config::load(&db.pensa_dir)?
+        .escalation_policy()?
+        .ok_or_else(|| {
+            PensaError::InvalidArgument(
+                "no escalation rules configured in .pensa/config.toml".to_string(),
+            )
+        })?;
+    let escalated = db.escalate_priorities(&policy, query.dry_run)?;
+    Ok(Json(serde_json::to_value(escalated).unwrap()))
+}
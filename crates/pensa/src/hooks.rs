@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::PensaError;
+
+pub const HOOK_MARKER: &str = "# installed by pn hooks install";
+
+pub const HOOKS: &[&str] = &["prepare-commit-msg", "commit-msg"];
+
+pub fn hook_script(hook: &str) -> String {
+    format!("#!/bin/sh\n{HOOK_MARKER}\nexec pn hooks {hook} \"$@\"\n")
+}
+
+pub fn hooks_dir(repo: &Path) -> Result<PathBuf, PensaError> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .current_dir(repo)
+        .output()
+        .map_err(|e| PensaError::Internal(format!("failed to run git rev-parse: {e}")))?;
+    if !output.status.success() {
+        return Err(PensaError::InvalidArgument(format!(
+            "not a git repository: {}",
+            repo.display()
+        )));
+    }
+    let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(if dir.is_absolute() {
+        dir
+    } else {
+        repo.join(dir)
+    })
+}
+
+pub fn install_hooks(repo: &Path, force: bool) -> Result<Vec<PathBuf>, PensaError> {
+    let dir = hooks_dir(repo)?;
+    let paths: Vec<PathBuf> = HOOKS.iter().map(|hook| dir.join(hook)).collect();
+
+    if !force {
+        for path in &paths {
+            if let Ok(existing) = std::fs::read_to_string(path)
+                && !existing.contains(HOOK_MARKER)
+            {
+                return Err(PensaError::InvalidArgument(format!(
+                    "{} already exists (use --force to overwrite)",
+                    path.display()
+                )));
+            }
+        }
+    }
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| PensaError::Internal(format!("failed to create {}: {e}", dir.display())))?;
+    for (hook, path) in HOOKS.iter().zip(&paths) {
+        std::fs::write(path, hook_script(hook)).map_err(|e| {
+            PensaError::Internal(format!("failed to write {}: {e}", path.display()))
+        })?;
+        make_executable(path)?;
+    }
+    Ok(paths)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), PensaError> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| PensaError::Internal(format!("failed to chmod {}: {e}", path.display())))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), PensaError> {
+    Ok(())
+}
+
+pub fn strip_comments(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn append_refs(message: &str, ids: &[String], prefix: &str) -> String {
+    let existing = crate::commits::find_issue_ids(&strip_comments(message), prefix);
+    let missing: Vec<&String> = ids.iter().filter(|id| !existing.contains(id)).collect();
+    if missing.is_empty() {
+        return message.to_string();
+    }
+
+    let (body, comments) = match message.find("\n#") {
+        Some(pos) => message.split_at(pos + 1),
+        None => (message, ""),
+    };
+    let body = body.trim_end();
+    let trailers: String = missing.iter().map(|id| format!("Refs: {id}\n")).collect();
+    let separator = if body.is_empty() {
+        "\n\n"
+    } else if ends_with_trailer(body) {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    format!("{body}{separator}{trailers}{comments}")
+}
+
+fn ends_with_trailer(body: &str) -> bool {
+    let Some((_, last_para)) = body.rsplit_once("\n\n") else {
+        return false;
+    };
+    last_para.lines().all(|line| {
+        line.split_once(": ").is_some_and(|(key, _)| {
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn append_refs_adds_trailer_block() {
+        let msg = "Fix login\n\nLonger description.\n";
+        assert_eq!(
+            append_refs(msg, &ids(&["pn-0a1b2c3d"]), "pn"),
+            "Fix login\n\nLonger description.\n\nRefs: pn-0a1b2c3d\n"
+        );
+    }
+
+    #[test]
+    fn append_refs_joins_existing_trailers() {
+        let msg = "Fix login\n\nSigned-off-by: A <a@b.c>\n";
+        assert_eq!(
+            append_refs(msg, &ids(&["pn-0a1b2c3d"]), "pn"),
+            "Fix login\n\nSigned-off-by: A <a@b.c>\nRefs: pn-0a1b2c3d\n"
+        );
+    }
+
+    #[test]
+    fn append_refs_skips_already_mentioned() {
+        let msg = "Fix pn-0a1b2c3d\n";
+        assert_eq!(append_refs(msg, &ids(&["pn-0a1b2c3d"]), "pn"), msg);
+    }
+
+    #[test]
+    fn append_refs_keeps_comment_block_last() {
+        let msg = "\n# Please enter the commit message\n# pn-0a1b2c3d\n";
+        assert_eq!(
+            append_refs(msg, &ids(&["pn-0a1b2c3d"]), "pn"),
+            "\n\nRefs: pn-0a1b2c3d\n# Please enter the commit message\n# pn-0a1b2c3d\n"
+        );
+    }
+
+    #[test]
+    fn install_writes_hooks_and_respects_foreign_hooks() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let out = Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(tmp.path())
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+
+        let paths = install_hooks(tmp.path(), false).unwrap();
+        assert_eq!(paths.len(), 2);
+        for path in &paths {
+            let content = std::fs::read_to_string(path).unwrap();
+            assert!(content.contains(HOOK_MARKER));
+        }
+        install_hooks(tmp.path(), false).unwrap();
+
+        std::fs::write(&paths[1], "#!/bin/sh\nexit 0\n").unwrap();
+        assert!(matches!(
+            install_hooks(tmp.path(), false),
+            Err(PensaError::InvalidArgument(_))
+        ));
+        install_hooks(tmp.path(), true).unwrap();
+        assert!(
+            std::fs::read_to_string(&paths[1])
+                .unwrap()
+                .contains(HOOK_MARKER)
+        );
+    }
+}
@@ -1,7 +1,9 @@
 pub mod client;
 pub mod daemon;
-pub mod db;
-pub mod error;
-pub mod id;
+pub mod hooks;
 pub mod output;
-pub mod types;
+pub mod rate_limit;
+pub mod stress;
+pub mod webhooks;
+
+pub use pensa_core::{Pensa, api_version, commits, config, db, error, id, types};
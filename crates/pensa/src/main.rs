@@ -4,10 +4,14 @@ use std::time::{Duration, Instant};
 
 use clap::{Parser, Subcommand};
 
-use pensa::client::Client;
+use pensa::api_version::{ApiVersion, CURRENT_API_VERSION};
+use pensa::client::{Client, DaemonInfo};
+use pensa::config::Config;
 use pensa::error::PensaError;
-use pensa::output::{self, OutputMode};
-use pensa::types::{CreateIssueParams, IssueType, ListFilters, Priority, Status};
+use pensa::output::{self, ListColumn, ListFormat, OutputMode};
+use pensa::types::{
+    CreateIssueParams, IssueType, ListFilters, PlannedIssue, Priority, Resolution, Status,
+};
 
 #[derive(Parser)]
 #[command(name = "pn", about = "Agent persistent memory — issue/task tracker")]
@@ -18,6 +22,9 @@ struct Cli {
     #[arg(long, default_value_t = false, global = true)]
     json: bool,
 
+    #[arg(long, env = "PN_API_VERSION", global = true)]
+    api_version: Option<u32>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -29,6 +36,8 @@ enum Commands {
         port: Option<u16>,
         #[arg(long)]
         project_dir: Option<std::path::PathBuf>,
+        #[arg(long, default_value_t = false)]
+        read_only: bool,
         #[command(subcommand)]
         subcmd: Option<DaemonSubcommand>,
     },
@@ -37,8 +46,9 @@ enum Commands {
         title: String,
         #[arg(short = 't', long)]
         issue_type: IssueType,
-        #[arg(short = 'p', long, default_value = "p2")]
-        priority: Priority,
+        /// Defaults to `[issues] default_priority`, or p2.
+        #[arg(short = 'p', long)]
+        priority: Option<Priority>,
         #[arg(short = 'a', long)]
         assignee: Option<String>,
         #[arg(long)]
@@ -52,6 +62,9 @@ enum Commands {
     },
     Show {
         id: String,
+        /// Show the title and description as they read then (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        at: Option<String>,
     },
     Update {
         id: String,
@@ -73,11 +86,16 @@ enum Commands {
         claim: bool,
         #[arg(long, default_value_t = false)]
         unclaim: bool,
+        /// Fail instead of updating if the issue is no longer at this revision
+        #[arg(long)]
+        expect_rev: Option<i64>,
     },
     Close {
         id: String,
         #[arg(long)]
         reason: Option<String>,
+        #[arg(long)]
+        resolution: Option<Resolution>,
         #[arg(long, default_value_t = false)]
         force: bool,
     },
@@ -85,14 +103,35 @@ enum Commands {
         id: String,
         #[arg(long)]
         reason: Option<String>,
+        /// Also reopen the closed tasks that fix this issue
+        #[arg(long, default_value_t = false)]
+        cascade: bool,
     },
     Release {
         id: String,
     },
+    /// Create every issue in a JSON plan, with its deps, in one transaction
+    Seed {
+        /// A JSON array of `{key, title, type, priority, description, spec,
+        /// assignee, deps, labels}` entries, or `-` for stdin
+        file: String,
+        /// Spec for entries that do not name one
+        #[arg(long)]
+        spec: Option<String>,
+    },
+    /// Tick a `- [ ]` item in the description, or untick a ticked one
+    Check {
+        id: String,
+        /// The item's number, counting from 1
+        index: usize,
+    },
     Delete {
         id: String,
         #[arg(long, default_value_t = false)]
         force: bool,
+        /// List the rows that would be deleted without deleting them
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
     },
     List {
         #[arg(long)]
@@ -106,9 +145,18 @@ enum Commands {
         #[arg(long)]
         spec: Option<String>,
         #[arg(long)]
+        resolution: Option<Resolution>,
+        #[arg(long, allow_hyphen_values = true)]
         sort: Option<String>,
         #[arg(short = 'n', long)]
         limit: Option<usize>,
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<ListColumn>,
+        #[arg(long)]
+        format: Option<ListFormat>,
+        /// Return only these issue fields (with --json)
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
     },
     Ready {
         #[arg(short = 'n', long)]
@@ -121,10 +169,40 @@ enum Commands {
         issue_type: Option<IssueType>,
         #[arg(long)]
         spec: Option<String>,
+        /// List the unclosed issues left out instead, with why
+        #[arg(long)]
+        explain: bool,
+        /// Return only these issue fields (with --json)
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
+    },
+    /// Why an issue is or isn't in `pn ready`
+    Explain {
+        id: String,
+        #[arg(short = 'p', long)]
+        priority: Option<Priority>,
+        #[arg(short = 'a', long)]
+        assignee: Option<String>,
+        #[arg(short = 't', long)]
+        issue_type: Option<IssueType>,
+        #[arg(long)]
+        spec: Option<String>,
     },
     Blocked,
+    /// Issues not updated for a while, oldest first
+    Stale {
+        /// Days without an update
+        #[arg(long, default_value_t = 14)]
+        days: i64,
+        /// Only issues with this status (default: every unclosed one)
+        #[arg(long)]
+        status: Option<Status>,
+    },
     Search {
         query: String,
+        /// Return only these issue fields (with --json)
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
     },
     Count {
         #[arg(long, default_value_t = false)]
@@ -135,8 +213,20 @@ enum Commands {
         by_issue_type: bool,
         #[arg(long, default_value_t = false)]
         by_assignee: bool,
+        #[arg(long, default_value_t = false)]
+        by_spec: bool,
+        #[arg(long, default_value_t = false)]
+        by_label: bool,
+        #[arg(long, default_value_t = false)]
+        by_resolution: bool,
+        #[arg(long)]
+        resolution: Option<Resolution>,
     },
     Status,
+    Workload {
+        #[arg(long)]
+        max_active: Option<i64>,
+    },
     History {
         id: String,
     },
@@ -158,29 +248,129 @@ enum Commands {
         #[command(subcommand)]
         subcmd: DocRefSubcommand,
     },
+    Label {
+        #[command(subcommand)]
+        subcmd: LabelSubcommand,
+    },
+    Watch {
+        #[command(subcommand)]
+        subcmd: WatchSubcommand,
+    },
+    Inbox {
+        #[arg(long, default_value_t = false)]
+        peek: bool,
+    },
+    Webhook {
+        #[command(subcommand)]
+        subcmd: WebhookSubcommand,
+    },
+    /// Names seen as actors or assignees, and which ones are the same identity
+    Actor {
+        #[command(subcommand)]
+        subcmd: ActorSubcommand,
+    },
+    Snapshot {
+        #[command(subcommand)]
+        subcmd: SnapshotSubcommand,
+    },
+    /// What changed between two snapshots, or from one to now
+    Diff {
+        /// Snapshot id or label to diff from
+        #[arg(required_unless_present = "since", conflicts_with = "since")]
+        from: Option<String>,
+        /// Snapshot id or label to diff to (default: now)
+        to: Option<String>,
+        /// Diff from the newest snapshot at or before this date to now
+        #[arg(long)]
+        since: Option<String>,
+    },
+    Commit {
+        #[command(subcommand)]
+        subcmd: CommitSubcommand,
+    },
+    ScanCommits {
+        #[arg(long)]
+        since: Option<String>,
+        #[arg(long, default_value_t = false)]
+        close: bool,
+    },
+    Hooks {
+        #[command(subcommand)]
+        subcmd: HooksSubcommand,
+    },
+    Config {
+        #[command(subcommand)]
+        subcmd: ConfigSubcommand,
+    },
+    Spec {
+        #[command(subcommand)]
+        subcmd: SpecSubcommand,
+    },
     Export,
-    Import,
+    Import {
+        /// List the rows the import would change without changing them
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
     Doctor {
         #[arg(long, default_value_t = false)]
         fix: bool,
+        /// With --fix, list the rows the fixes would change without changing them
+        #[arg(long, default_value_t = false, requires = "fix")]
+        dry_run: bool,
+        #[arg(long)]
+        stale_after: Option<String>,
+    },
+    Escalate {
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    Stress {
+        #[arg(long, default_value_t = 8)]
+        agents: usize,
+        #[arg(long, default_value_t = 1000)]
+        ops: usize,
+        #[arg(long, default_value_t = false)]
+        keep: bool,
     },
 }
 
 #[derive(Subcommand)]
 enum DaemonSubcommand {
-    Status,
+    Status {
+        /// Also show uptime, schema version, table sizes, and the last export
+        #[arg(short, long, default_value_t = false)]
+        verbose: bool,
+    },
+    #[command(name = "read-only")]
+    ReadOnly {
+        #[arg(value_parser = ["on", "off"])]
+        state: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 enum DepSubcommand {
     Add {
-        child: String,
-        parent: String,
+        #[arg(required_unless_present = "from_refs")]
+        child: Option<String>,
+        #[arg(required_unless_present = "from_refs")]
+        parent: Option<String>,
+        #[arg(long, conflicts_with_all = ["child", "parent"])]
+        from_refs: Option<String>,
     },
     Remove {
         child: String,
         parent: String,
     },
+    /// Mark an external dep (a GitHub issue, Jira key or URL) resolved
+    Resolve {
+        child: String,
+        target: String,
+        /// Mark it open again
+        #[arg(long)]
+        undo: bool,
+    },
     List {
         id: String,
     },
@@ -230,6 +420,108 @@ enum DocRefSubcommand {
     },
 }
 
+#[derive(Subcommand)]
+enum LabelSubcommand {
+    Add { id: String, label: String },
+    Remove { id: String, label: String },
+    List { id: String },
+}
+
+#[derive(Subcommand)]
+enum WatchSubcommand {
+    Add { id: String },
+    Remove { id: String },
+    List { id: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum ActorSubcommand {
+    List,
+    /// Set the display name of the identity `actor` stands for; omit it to clear
+    Rename {
+        actor: String,
+        display_name: Option<String>,
+    },
+    /// Make `alias` another name for `actor`'s identity
+    Alias {
+        alias: String,
+        /// Required unless --remove is given
+        actor: Option<String>,
+        /// Make `alias` an identity of its own again
+        #[arg(long, default_value_t = false, conflicts_with = "actor")]
+        remove: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum WebhookSubcommand {
+    Add {
+        url: String,
+        /// Comma-separated event types to deliver (default: all)
+        #[arg(long, value_delimiter = ',')]
+        events: Vec<String>,
+        #[arg(long)]
+        spec: Option<String>,
+    },
+    List,
+    Remove {
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotSubcommand {
+    /// Save every issue as it is now
+    Create {
+        label: Option<String>,
+    },
+    List,
+}
+
+#[derive(Subcommand)]
+enum CommitSubcommand {
+    Link { id: String, sha: String },
+    List { id: String },
+}
+
+#[derive(Subcommand)]
+enum SpecSubcommand {
+    Status {
+        #[arg(long)]
+        spec: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigSubcommand {
+    /// Print a setting from `.pensa/config.toml`, e.g. `issues.default_priority`.
+    Get { key: String },
+    /// Change a setting, keeping the rest of the file as it is.
+    Set { key: String, value: String },
+}
+
+#[derive(Subcommand)]
+enum HooksSubcommand {
+    Install {
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    #[command(name = "prepare-commit-msg", hide = true)]
+    PrepareCommitMsg {
+        file: std::path::PathBuf,
+        source: Option<String>,
+        sha: Option<String>,
+    },
+    #[command(name = "commit-msg", hide = true)]
+    CommitMsg { file: std::path::PathBuf },
+}
+
+/// `@me` as an assignee stands for the current actor, and so for every
+/// alias of its identity when filtering.
+fn expand_me(assignee: Option<String>, actor: &str) -> Option<String> {
+    assignee.map(|a| if a == "@me" { actor.to_string() } else { a })
+}
+
 fn resolve_actor(flag: Option<String>) -> String {
     if let Some(a) = flag {
         return a;
@@ -250,15 +542,140 @@ fn resolve_actor(flag: Option<String>) -> String {
     std::env::var("USER").unwrap_or_else(|_| "unknown".to_string())
 }
 
+fn scan_commits(
+    client: &Client,
+    since: Option<&str>,
+    close: bool,
+    actor: &str,
+) -> Result<serde_json::Value, PensaError> {
+    let repo = std::env::current_dir().unwrap();
+    let commits = pensa::commits::git_log(&repo, since)?;
+    let config = Config::load(&pensa_dir())?;
+
+    let mut linked = Vec::new();
+    let mut closed = Vec::new();
+    let mut unknown: Vec<String> = Vec::new();
+    for commit in &commits {
+        let refs = pensa::commits::parse_commit_refs(&commit.message, config.id_prefix());
+        for id in &refs.mentioned {
+            match client.link_commit(id, &commit.sha, Some(&commit.summary), actor) {
+                Ok(_) => linked.push(serde_json::json!({"issue_id": id, "sha": commit.sha})),
+                Err(PensaError::NotFound(_)) => {
+                    if !unknown.contains(id) {
+                        unknown.push(id.clone());
+                    }
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+
+            if close && refs.fixes.contains(id) {
+                let issue = client.get_issue(id)?;
+                if issue["status"].as_str() != Some("closed") {
+                    let short = commit.sha.get(..10).unwrap_or(&commit.sha);
+                    let reason = format!("fixed in {short}");
+                    client.close_issue(id, Some(&reason), Some(Resolution::Fixed), false, actor)?;
+                    closed.push(id.clone());
+                }
+            }
+        }
+    }
+
+    Ok(serde_json::json!({
+        "scanned": commits.len(),
+        "linked": linked,
+        "closed": closed,
+        "unknown": unknown,
+    }))
+}
+
+fn json_fields(fields: Vec<String>, mode: OutputMode) -> Vec<String> {
+    if mode == OutputMode::Json {
+        fields
+    } else {
+        Vec::new()
+    }
+}
+
+fn read_commit_msg(file: &std::path::Path) -> Result<String, PensaError> {
+    std::fs::read_to_string(file)
+        .map_err(|e| PensaError::Internal(format!("failed to read {}: {e}", file.display())))
+}
+
+fn prepare_commit_msg(
+    client: &Client,
+    file: &std::path::Path,
+    actor: &str,
+) -> Result<(), PensaError> {
+    let filters = ListFilters {
+        status: Some(Status::InProgress),
+        assignee: Some(actor.to_string()),
+        ..Default::default()
+    };
+    let claimed = client.list_issues(&filters)?;
+    let ids: Vec<String> = claimed
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|issue| issue["id"].as_str().map(String::from))
+        .collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let message = read_commit_msg(file)?;
+    let config = Config::load(&pensa_dir())?;
+    let updated = pensa::hooks::append_refs(&message, &ids, config.id_prefix());
+    if updated != message {
+        std::fs::write(file, updated).map_err(|e| {
+            PensaError::Internal(format!("failed to write {}: {e}", file.display()))
+        })?;
+    }
+    Ok(())
+}
+
+fn check_commit_msg(client: &Client, file: &std::path::Path) -> Result<(), PensaError> {
+    let message = pensa::hooks::strip_comments(&read_commit_msg(file)?);
+    let config = Config::load(&pensa_dir())?;
+    let mut unknown = Vec::new();
+    for id in pensa::commits::find_issue_ids(&message, config.id_prefix()) {
+        match client.get_issue(&id) {
+            Ok(_) => {}
+            Err(PensaError::NotFound(_)) => unknown.push(id),
+            Err(e) => return Err(e),
+        }
+    }
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(PensaError::InvalidArgument(format!(
+            "commit message references unknown issue(s): {}",
+            unknown.join(", ")
+        )))
+    }
+}
+
+fn run_commit_hook(result: Result<(), PensaError>, mode: OutputMode) {
+    match result {
+        Ok(()) => {}
+        Err(e @ PensaError::InvalidArgument(_)) => fail(e, mode),
+        Err(e) => eprintln!("pn: warning: skipping commit hook: {e}"),
+    }
+}
+
 fn fail(err: PensaError, mode: OutputMode) -> ! {
     output::print_error(&err, mode);
-    process::exit(1);
+    process::exit(err.exit_code());
 }
 
 fn project_dir() -> std::path::PathBuf {
     pensa::db::find_project_root().unwrap_or_else(|| std::env::current_dir().unwrap())
 }
 
+fn pensa_dir() -> std::path::PathBuf {
+    project_dir().join(".pensa")
+}
+
 fn is_remote_host() -> bool {
     if let Ok(host) = std::env::var("PN_DAEMON_HOST") {
         let h = host.trim();
@@ -304,7 +721,34 @@ fn is_daemon_stale(dir: &std::path::Path) -> bool {
     stored_path != canonical
 }
 
-fn ensure_daemon() {
+/// Explains why the daemon described by `info` cannot serve this CLI, which
+/// reads `api_version` responses (the current version when unpinned).
+/// Daemons that report no API version are assumed compatible.
+fn version_mismatch(info: &DaemonInfo, api_version: Option<u32>) -> Option<String> {
+    let wanted = ApiVersion(api_version.unwrap_or(CURRENT_API_VERSION));
+    let max = info.api_version?;
+    let min = info.min_api_version.unwrap_or(max);
+    if wanted.served_by(min, max) {
+        return None;
+    }
+    let daemon = match &info.version {
+        Some(v) => format!("pensa {v}"),
+        None => "an older pensa".to_string(),
+    };
+    let served = if min == max {
+        format!("v{max}")
+    } else {
+        format!("v{min}-v{max}")
+    };
+    Some(format!(
+        "daemon at {} ({daemon}) serves API {served}, but pn {} needs v{}; stop the daemon so pn can start a matching one",
+        info.url,
+        env!("CARGO_PKG_VERSION"),
+        wanted.0
+    ))
+}
+
+fn ensure_daemon(mode: OutputMode, api_version: Option<u32>) -> Client {
     let dir = project_dir();
 
     if is_daemon_stale(&dir) {
@@ -312,17 +756,27 @@ fn ensure_daemon() {
         clear_stale_daemon_files(&dir.join(".pensa"));
     }
 
-    let client = Client::new();
-    if client.check_reachable().is_ok() {
-        return;
+    let client = Client::with_api_version(api_version);
+    if let Ok(info) = client.daemon_info() {
+        // A pinned version the daemon can't serve is rejected per request
+        // with `unsupported_api_version`.
+        if api_version.is_none()
+            && let Some(msg) = version_mismatch(&info, None)
+        {
+            fail(PensaError::DaemonVersionMismatch(msg), mode);
+        }
+        return client;
     }
 
     if is_remote_host() {
-        eprintln!(
-            "pn: daemon unreachable at {} (remote host configured via PN_DAEMON or PN_DAEMON_HOST)",
+        let err = PensaError::DaemonUnreachable(format!(
+            "{} (remote host configured via PN_DAEMON or PN_DAEMON_HOST)",
             client.base_url()
-        );
-        process::exit(1);
+        ));
+        if mode == OutputMode::Json {
+            eprintln!("pn: {err}");
+        }
+        fail(err, mode);
     }
 
     let port = pensa::db::project_port(&dir);
@@ -342,7 +796,7 @@ fn ensure_daemon() {
         .spawn()
     {
         eprintln!("pn: failed to start daemon: {e}");
-        return;
+        return client;
     }
 
     let deadline = Instant::now() + Duration::from_secs(5);
@@ -350,15 +804,22 @@ fn ensure_daemon() {
         thread::sleep(Duration::from_millis(100));
         if client.check_reachable().is_ok() {
             eprintln!("pn: daemon ready");
-            return;
+            return client;
         }
     }
 
     eprintln!("pn: warning: daemon did not become ready within 5s");
+    client
 }
 
 fn needs_daemon(cmd: &Commands) -> bool {
-    !matches!(cmd, Commands::Daemon { .. } | Commands::Where)
+    !matches!(
+        cmd,
+        Commands::Daemon { .. }
+            | Commands::Where
+            | Commands::Hooks { .. }
+            | Commands::Config { .. }
+    )
 }
 
 fn main() {
@@ -370,43 +831,69 @@ fn main() {
         OutputMode::Human
     };
     let actor = resolve_actor(cli.actor);
+    let api_version = cli.api_version;
 
-    if needs_daemon(&cli.command) {
-        ensure_daemon();
-    }
+    let client = if needs_daemon(&cli.command) {
+        ensure_daemon(mode, api_version)
+    } else {
+        Client::with_api_version(api_version)
+    };
 
     match cli.command {
         Commands::Daemon {
             port,
             project_dir,
+            read_only,
             subcmd,
         } => match subcmd {
-            Some(DaemonSubcommand::Status) => {
-                let client = Client::new();
-                match client.check_reachable() {
-                    Ok(()) => {
-                        println!("daemon reachable at {}", client.base_url());
-                        let dir = std::env::current_dir().unwrap_or_default();
-                        let project_file = dir.join(".pensa/daemon.project");
-                        if let Ok(project_dir) = std::fs::read_to_string(&project_file) {
-                            let project_dir = project_dir.trim();
-                            if !project_dir.is_empty() {
-                                println!("project directory: {project_dir}");
-                            }
+            Some(DaemonSubcommand::Status { verbose }) => match client.daemon_info() {
+                Ok(info) => {
+                    let dir = std::env::current_dir().unwrap_or_default();
+                    let project_dir = std::fs::read_to_string(dir.join(".pensa/daemon.project"))
+                        .ok()
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty());
+                    let mut status = serde_json::json!({
+                        "url": info.url,
+                        "version": info.version,
+                        "api_version": info.api_version,
+                        "min_api_version": info.min_api_version,
+                        "cli_version": env!("CARGO_PKG_VERSION"),
+                        "cli_api_version": api_version.unwrap_or(CURRENT_API_VERSION),
+                        "project_dir": project_dir,
+                        "mismatch": version_mismatch(&info, api_version),
+                    });
+                    if verbose {
+                        match client.health() {
+                            Ok(health) => status["health"] = health,
+                            Err(e) => fail(e, mode),
                         }
-                        process::exit(0);
-                    }
-                    Err(e) => {
-                        eprintln!("daemon unreachable: {e}");
-                        process::exit(1);
                     }
+                    output::print_daemon_status(&status, mode);
+                    process::exit(0);
+                }
+                Err(e) => fail(PensaError::DaemonUnreachable(e), mode),
+            },
+            Some(DaemonSubcommand::ReadOnly { state }) => {
+                let result = match state.as_deref() {
+                    Some(s) => client.set_read_only(s == "on"),
+                    None => client.read_only(),
+                };
+                match result {
+                    Ok(v) => output::print_read_only(&v, mode),
+                    Err(e) => fail(e, mode),
                 }
             }
             None => {
                 let dir = project_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
                 let port = port.unwrap_or_else(|| pensa::db::project_port(&dir));
                 let rt = tokio::runtime::Runtime::new().expect("failed to create tokio runtime");
-                rt.block_on(pensa::daemon::start(port, dir));
+                rt.block_on(pensa::daemon::start_with_options(
+                    port,
+                    dir,
+                    None,
+                    pensa::daemon::DaemonOptions { read_only },
+                ));
             }
         },
 
@@ -426,7 +913,13 @@ fn main() {
             description,
             deps,
         } => {
-            let client = Client::new();
+            let priority = match priority {
+                Some(p) => p,
+                None => match Config::load(&pensa_dir()) {
+                    Ok(config) => config.default_priority_for(spec.as_deref()),
+                    Err(e) => fail(e, mode),
+                },
+            };
             let params = CreateIssueParams {
                 title,
                 issue_type,
@@ -434,8 +927,9 @@ fn main() {
                 description,
                 spec,
                 fixes,
-                assignee,
+                assignee: expand_me(assignee, &actor),
                 deps,
+                labels: Vec::new(),
                 actor: actor.clone(),
             };
             match client.create_issue(&params) {
@@ -444,13 +938,24 @@ fn main() {
             }
         }
 
-        Commands::Show { id } => {
-            let client = Client::new();
-            match client.get_issue(&id) {
-                Ok(v) => output::print_issue_detail(&v, mode),
-                Err(e) => fail(e, mode),
+        Commands::Show { id, at: Some(at) } => match client.issue_text_at(&id, &at) {
+            Ok(v) => output::print_issue_text(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+        Commands::Show { id, .. } => match client.get_issue(&id) {
+            Ok(v) => {
+                let history = match mode {
+                    OutputMode::Json => Vec::new(),
+                    OutputMode::Human => client
+                        .issue_history(&id)
+                        .ok()
+                        .and_then(|h| h.as_array().cloned())
+                        .unwrap_or_default(),
+                };
+                output::print_issue_detail(&v, &history, mode)
             }
-        }
+            Err(e) => fail(e, mode),
+        },
 
         Commands::Update {
             id,
@@ -463,8 +968,8 @@ fn main() {
             fixes,
             claim,
             unclaim,
+            expect_rev,
         } => {
-            let client = Client::new();
             let mut body = serde_json::Map::new();
             if let Some(t) = title {
                 body.insert("title".into(), serde_json::Value::String(t));
@@ -481,7 +986,7 @@ fn main() {
                     serde_json::Value::String(p.as_str().to_string()),
                 );
             }
-            if let Some(a) = assignee {
+            if let Some(a) = expand_me(assignee, &actor) {
                 body.insert("assignee".into(), serde_json::Value::String(a));
             }
             if let Some(d) = description {
@@ -500,64 +1005,118 @@ fn main() {
                 body.insert("unclaim".into(), serde_json::Value::Bool(true));
             }
 
-            match client.update_issue(&id, &serde_json::Value::Object(body), &actor) {
+            match client.update_issue(&id, &serde_json::Value::Object(body), expect_rev, &actor) {
                 Ok(v) => output::print_issue(&v, mode),
                 Err(e) => fail(e, mode),
             }
         }
 
-        Commands::Close { id, reason, force } => {
-            let client = Client::new();
-            match client.close_issue(&id, reason.as_deref(), force, &actor) {
-                Ok(v) => output::print_issue(&v, mode),
-                Err(e) => fail(e, mode),
-            }
-        }
+        Commands::Close {
+            id,
+            reason,
+            resolution,
+            force,
+        } => match client.close_issue(&id, reason.as_deref(), resolution, force, &actor) {
+            Ok(v) => output::print_issue(&v, mode),
+            Err(e) => fail(e, mode),
+        },
 
-        Commands::Reopen { id, reason } => {
-            let client = Client::new();
-            match client.reopen_issue(&id, reason.as_deref(), &actor) {
-                Ok(v) => output::print_issue(&v, mode),
-                Err(e) => fail(e, mode),
-            }
-        }
+        Commands::Reopen {
+            id,
+            reason,
+            cascade,
+        } => match client.reopen_issue(&id, reason.as_deref(), cascade, &actor) {
+            Ok(v) => output::print_issue(&v, mode),
+            Err(e) => fail(e, mode),
+        },
 
-        Commands::Release { id } => {
-            let client = Client::new();
-            match client.release_issue(&id, &actor) {
-                Ok(v) => output::print_issue(&v, mode),
-                Err(e) => fail(e, mode),
+        Commands::Release { id } => match client.release_issue(&id, &actor) {
+            Ok(v) => output::print_issue(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+        Commands::Seed { file, spec } => {
+            let text = if file == "-" {
+                std::io::read_to_string(std::io::stdin())
+            } else {
+                std::fs::read_to_string(&file)
+            };
+            let text = match text {
+                Ok(text) => text,
+                Err(e) => fail(
+                    PensaError::InvalidArgument(format!("failed to read {file}: {e}")),
+                    mode,
+                ),
+            };
+            let mut plan: Vec<PlannedIssue> = match serde_json::from_str(&text) {
+                Ok(plan) => plan,
+                Err(e) => fail(
+                    PensaError::InvalidArgument(format!("invalid plan in {file}: {e}")),
+                    mode,
+                ),
+            };
+            for entry in &mut plan {
+                if entry.spec.is_none() {
+                    entry.spec.clone_from(&spec);
+                }
+                entry.assignee = expand_me(entry.assignee.take(), &actor);
             }
-        }
-
-        Commands::Delete { id, force } => {
-            let client = Client::new();
-            match client.delete_issue(&id, force) {
-                Ok(()) => output::print_deleted(mode),
+            match client.seed_issues(&plan, &actor) {
+                Ok(v) => output::print_seeded(&v, mode),
                 Err(e) => fail(e, mode),
             }
         }
 
+        Commands::Check { id, index } => match client.check_item(&id, index, &actor) {
+            Ok(v) => output::print_issue(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::Delete {
+            id,
+            force,
+            dry_run: true,
+        } => match client.preview_delete(&id, force) {
+            Ok(v) => output::print_dry_run(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+        Commands::Delete { id, force, .. } => match client.delete_issue(&id, force) {
+            Ok(()) => output::print_deleted(mode),
+            Err(e) => fail(e, mode),
+        },
+
         Commands::List {
             status,
             priority,
             assignee,
             issue_type,
             spec,
+            resolution,
             sort,
             limit,
+            columns,
+            format,
+            fields,
         } => {
-            let client = Client::new();
             let filters = ListFilters {
                 status,
                 priority,
-                assignee,
+                assignee: expand_me(assignee, &actor),
                 issue_type,
                 spec,
+                resolution,
                 sort,
                 limit,
+                fields: json_fields(fields, mode),
             };
             match client.list_issues(&filters) {
+                Ok(v) if mode == OutputMode::Human && (format.is_some() || !columns.is_empty()) => {
+                    let columns = if columns.is_empty() {
+                        ListColumn::DEFAULT
+                    } else {
+                        &columns
+                    };
+                    output::print_issue_table(&v, columns, format.unwrap_or(ListFormat::Table));
+                }
                 Ok(v) => output::print_issue_list(&v, mode),
                 Err(e) => fail(e, mode),
             }
@@ -569,33 +1128,63 @@ fn main() {
             assignee,
             issue_type,
             spec,
+            explain,
+            fields,
         } => {
-            let client = Client::new();
             let filters = ListFilters {
                 priority,
-                assignee,
+                assignee: expand_me(assignee, &actor),
                 issue_type,
                 spec,
                 limit,
+                fields: json_fields(fields, mode),
                 ..Default::default()
             };
-            match client.ready_issues(&filters) {
-                Ok(v) => output::print_issue_list(&v, mode),
-                Err(e) => fail(e, mode),
+            if explain {
+                match client.explain_not_ready(&filters) {
+                    Ok(v) => output::print_ready_explanations(&v, mode),
+                    Err(e) => fail(e, mode),
+                }
+            } else {
+                match client.ready_issues(&filters) {
+                    Ok(v) => output::print_issue_list(&v, mode),
+                    Err(e) => fail(e, mode),
+                }
             }
         }
 
-        Commands::Blocked => {
-            let client = Client::new();
-            match client.blocked_issues() {
-                Ok(v) => output::print_issue_list(&v, mode),
+        Commands::Explain {
+            id,
+            priority,
+            assignee,
+            issue_type,
+            spec,
+        } => {
+            let filters = ListFilters {
+                priority,
+                assignee: expand_me(assignee, &actor),
+                issue_type,
+                spec,
+                ..Default::default()
+            };
+            match client.explain_ready(&id, &filters) {
+                Ok(v) => output::print_ready_explanation(&v, mode),
                 Err(e) => fail(e, mode),
             }
         }
 
-        Commands::Search { query } => {
-            let client = Client::new();
-            match client.search_issues(&query) {
+        Commands::Blocked => match client.blocked_issues() {
+            Ok(v) => output::print_issue_list(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::Stale { days, status } => match client.stale_issues(days, status) {
+            Ok(v) => output::print_stale(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::Search { query, fields } => {
+            match client.search_issues(&query, &json_fields(fields, mode)) {
                 Ok(v) => output::print_issue_list(&v, mode),
                 Err(e) => fail(e, mode),
             }
@@ -606,146 +1195,364 @@ fn main() {
             by_priority,
             by_issue_type,
             by_assignee,
+            by_spec,
+            by_label,
+            by_resolution,
+            resolution,
         } => {
-            let client = Client::new();
-            match client.count_issues(by_status, by_priority, by_issue_type, by_assignee) {
+            let group_by: Vec<&str> = [
+                (by_status, "status"),
+                (by_priority, "priority"),
+                (by_issue_type, "issue_type"),
+                (by_assignee, "assignee"),
+                (by_spec, "spec"),
+                (by_label, "label"),
+                (by_resolution, "resolution"),
+            ]
+            .into_iter()
+            .filter_map(|(on, field)| on.then_some(field))
+            .collect();
+            match client.count_issues(&group_by, resolution) {
                 Ok(v) => output::print_count(&v, mode),
                 Err(e) => fail(e, mode),
             }
         }
 
-        Commands::Status => {
-            let client = Client::new();
-            match client.project_status() {
-                Ok(v) => output::print_status(&v, mode),
+        Commands::Status => match client.project_status() {
+            Ok(v) => output::print_status(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::Workload { max_active } => match client.workload(max_active) {
+            Ok(v) => output::print_workload(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::History { id } => match client.issue_history(&id) {
+            Ok(v) => output::print_events(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::Dep { subcmd } => match subcmd {
+            DepSubcommand::Add {
+                from_refs: Some(id),
+                ..
+            } => match client.promote_refs(&id, &actor) {
+                Ok(v) => output::print_promoted_refs(&v, mode),
                 Err(e) => fail(e, mode),
+            },
+            DepSubcommand::Add { child, parent, .. } => {
+                let (child, parent) = (child.unwrap_or_default(), parent.unwrap_or_default());
+                match client.add_dep(&child, &parent, &actor) {
+                    Ok(v) => output::print_dep_status(&v, mode),
+                    Err(e) => fail(e, mode),
+                }
             }
-        }
+            DepSubcommand::Remove { child, parent } => match client.remove_dep(&child, &parent) {
+                Ok(v) => output::print_dep_status(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            DepSubcommand::Resolve {
+                child,
+                target,
+                undo,
+            } => match client.resolve_dep(&child, &target, !undo, &actor) {
+                Ok(v) => output::print_external_dep(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            DepSubcommand::List { id } => match client.list_deps(&id) {
+                Ok(v) => output::print_issue_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            DepSubcommand::Tree { id, direction } => match client.dep_tree(&id, &direction) {
+                Ok(v) => output::print_dep_tree(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            DepSubcommand::Cycles => match client.dep_cycles() {
+                Ok(v) => output::print_cycles(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+        },
 
-        Commands::History { id } => {
-            let client = Client::new();
-            match client.issue_history(&id) {
-                Ok(v) => output::print_events(&v, mode),
+        Commands::Comment { subcmd } => match subcmd {
+            CommentSubcommand::Add { id, text } => match client.add_comment(&id, &text, &actor) {
+                Ok(v) => output::print_comment(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            CommentSubcommand::List { id } => match client.list_comments(&id) {
+                Ok(v) => output::print_comment_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+        },
+
+        Commands::SrcRef { subcmd } => match subcmd {
+            SrcRefSubcommand::Add { id, path, reason } => {
+                match client.add_src_ref(&id, &path, reason.as_deref(), &actor) {
+                    Ok(v) => output::print_ref(&v, mode),
+                    Err(e) => fail(e, mode),
+                }
+            }
+            SrcRefSubcommand::List { id } => match client.list_src_refs(&id) {
+                Ok(v) => output::print_ref_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            SrcRefSubcommand::Remove { ref_id } => match client.remove_src_ref(&ref_id, &actor) {
+                Ok(()) => output::print_deleted(mode),
+                Err(e) => fail(e, mode),
+            },
+        },
+
+        Commands::DocRef { subcmd } => match subcmd {
+            DocRefSubcommand::Add { id, path, reason } => {
+                match client.add_doc_ref(&id, &path, reason.as_deref(), &actor) {
+                    Ok(v) => output::print_ref(&v, mode),
+                    Err(e) => fail(e, mode),
+                }
+            }
+            DocRefSubcommand::List { id } => match client.list_doc_refs(&id) {
+                Ok(v) => output::print_ref_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            DocRefSubcommand::Remove { ref_id } => match client.remove_doc_ref(&ref_id, &actor) {
+                Ok(()) => output::print_deleted(mode),
+                Err(e) => fail(e, mode),
+            },
+        },
+
+        Commands::Label { subcmd } => {
+            let result = match subcmd {
+                LabelSubcommand::Add { id, label } => client.add_label(&id, &label, &actor),
+                LabelSubcommand::Remove { id, label } => client.remove_label(&id, &label, &actor),
+                LabelSubcommand::List { id } => client.list_labels(&id),
+            };
+            match result {
+                Ok(v) => output::print_labels(&v, mode),
                 Err(e) => fail(e, mode),
             }
         }
 
-        Commands::Dep { subcmd } => {
-            let client = Client::new();
-            match subcmd {
-                DepSubcommand::Add { child, parent } => {
-                    match client.add_dep(&child, &parent, &actor) {
-                        Ok(v) => output::print_dep_status(&v, mode),
-                        Err(e) => fail(e, mode),
-                    }
+        Commands::Watch { subcmd } => match subcmd {
+            WatchSubcommand::Add { id } => match client.watch_issue(&id, &actor) {
+                Ok(v) => output::print_watchers(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            WatchSubcommand::Remove { id } => match client.unwatch_issue(&id, &actor) {
+                Ok(v) => output::print_watchers(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            WatchSubcommand::List { id: Some(id) } => match client.list_watchers(&id) {
+                Ok(v) => output::print_watchers(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            WatchSubcommand::List { id: None } => match client.watched_issues(&actor) {
+                Ok(v) => output::print_issue_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+        },
+
+        Commands::Webhook { subcmd } => match subcmd {
+            WebhookSubcommand::Add { url, events, spec } => {
+                match client.add_webhook(&url, &events, spec.as_deref()) {
+                    Ok(v) => output::print_webhook(&v, mode),
+                    Err(e) => fail(e, mode),
                 }
-                DepSubcommand::Remove { child, parent } => {
-                    match client.remove_dep(&child, &parent) {
-                        Ok(v) => output::print_dep_status(&v, mode),
-                        Err(e) => fail(e, mode),
-                    }
+            }
+            WebhookSubcommand::List => match client.list_webhooks() {
+                Ok(v) => output::print_webhook_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            WebhookSubcommand::Remove { id } => match client.remove_webhook(&id) {
+                Ok(()) => output::print_deleted(mode),
+                Err(e) => fail(e, mode),
+            },
+        },
+        Commands::Actor { subcmd } => match subcmd {
+            ActorSubcommand::List => match client.list_actors() {
+                Ok(v) => output::print_actor_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            ActorSubcommand::Rename {
+                actor,
+                display_name,
+            } => match client.rename_actor(&actor, display_name.as_deref()) {
+                Ok(v) => output::print_actor(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            ActorSubcommand::Alias {
+                alias,
+                actor,
+                remove,
+            } => {
+                if actor.is_none() && !remove {
+                    fail(
+                        PensaError::InvalidArgument(
+                            "give the actor to alias to, or --remove".to_string(),
+                        ),
+                        mode,
+                    );
                 }
-                DepSubcommand::List { id } => match client.list_deps(&id) {
-                    Ok(v) => output::print_issue_list(&v, mode),
+                match client.alias_actor(&alias, actor.as_deref()) {
+                    Ok(v) => output::print_actor(&v, mode),
                     Err(e) => fail(e, mode),
-                },
-                DepSubcommand::Tree { id, direction } => match client.dep_tree(&id, &direction) {
-                    Ok(v) => output::print_dep_tree(&v, mode),
-                    Err(e) => fail(e, mode),
-                },
-                DepSubcommand::Cycles => match client.dep_cycles() {
-                    Ok(v) => output::print_cycles(&v, mode),
+                }
+            }
+        },
+        Commands::Snapshot { subcmd } => match subcmd {
+            SnapshotSubcommand::Create { label } => {
+                match client.create_snapshot(label.as_deref()) {
+                    Ok(v) => output::print_snapshot(&v, mode),
                     Err(e) => fail(e, mode),
-                },
+                }
+            }
+            SnapshotSubcommand::List => match client.list_snapshots() {
+                Ok(v) => output::print_snapshot_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+        },
+        Commands::Diff { from, to, since } => {
+            match client.diff(from.as_deref(), to.as_deref(), since.as_deref()) {
+                Ok(v) => output::print_diff(&v, mode),
+                Err(e) => fail(e, mode),
             }
         }
-
-        Commands::Comment { subcmd } => {
-            let client = Client::new();
-            match subcmd {
-                CommentSubcommand::Add { id, text } => {
-                    match client.add_comment(&id, &text, &actor) {
-                        Ok(v) => output::print_comment(&v, mode),
-                        Err(e) => fail(e, mode),
-                    }
+        Commands::Inbox { peek } => match client.inbox(&actor) {
+            Ok(v) => {
+                output::print_inbox(&v, mode);
+                let newest = v["entries"]
+                    .as_array()
+                    .and_then(|entries| entries.last())
+                    .and_then(|e| e["id"].as_i64());
+                if !peek
+                    && let Some(up_to) = newest
+                    && let Err(e) = client.mark_inbox_read(up_to, &actor)
+                {
+                    eprintln!("pn: inbox not marked read: {e}");
                 }
-                CommentSubcommand::List { id } => match client.list_comments(&id) {
-                    Ok(v) => output::print_comment_list(&v, mode),
-                    Err(e) => fail(e, mode),
-                },
             }
-        }
+            Err(e) => fail(e, mode),
+        },
 
-        Commands::SrcRef { subcmd } => {
-            let client = Client::new();
-            match subcmd {
-                SrcRefSubcommand::Add { id, path, reason } => {
-                    match client.add_src_ref(&id, &path, reason.as_deref(), &actor) {
-                        Ok(v) => output::print_ref(&v, mode),
-                        Err(e) => fail(e, mode),
-                    }
-                }
-                SrcRefSubcommand::List { id } => match client.list_src_refs(&id) {
-                    Ok(v) => output::print_ref_list(&v, mode),
+        Commands::Commit { subcmd } => match subcmd {
+            CommitSubcommand::Link { id, sha } => {
+                let repo = std::env::current_dir().unwrap();
+                let (sha, summary) = match pensa::commits::resolve_commit(&repo, &sha) {
+                    Some((full, summary)) => (full, Some(summary)),
+                    None => (sha, None),
+                };
+                match client.link_commit(&id, &sha, summary.as_deref(), &actor) {
+                    Ok(v) => output::print_commit(&v, mode),
                     Err(e) => fail(e, mode),
-                },
-                SrcRefSubcommand::Remove { ref_id } => {
-                    match client.remove_src_ref(&ref_id, &actor) {
-                        Ok(()) => output::print_deleted(mode),
-                        Err(e) => fail(e, mode),
-                    }
                 }
             }
+            CommitSubcommand::List { id } => match client.list_commits(&id) {
+                Ok(v) => output::print_commit_list(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+        },
+
+        Commands::ScanCommits { since, close } => {
+            match scan_commits(&client, since.as_deref(), close, &actor) {
+                Ok(v) => output::print_scan_commits(&v, mode),
+                Err(e) => fail(e, mode),
+            }
         }
 
-        Commands::DocRef { subcmd } => {
-            let client = Client::new();
-            match subcmd {
-                DocRefSubcommand::Add { id, path, reason } => {
-                    match client.add_doc_ref(&id, &path, reason.as_deref(), &actor) {
-                        Ok(v) => output::print_ref(&v, mode),
-                        Err(e) => fail(e, mode),
-                    }
-                }
-                DocRefSubcommand::List { id } => match client.list_doc_refs(&id) {
-                    Ok(v) => output::print_ref_list(&v, mode),
+        Commands::Spec { subcmd } => match subcmd {
+            SpecSubcommand::Status { spec: Some(spec) } => match client.spec_detail(&spec) {
+                Ok(v) => output::print_spec_detail(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+            SpecSubcommand::Status { spec: None } => match client.spec_status() {
+                Ok(v) => output::print_spec_status(&v, mode),
+                Err(e) => fail(e, mode),
+            },
+        },
+
+        Commands::Config { subcmd } => match subcmd {
+            ConfigSubcommand::Get { key } => match pensa::config::get_value(&pensa_dir(), &key) {
+                Ok(Some(value)) => output::print_config_value(&key, &value, mode),
+                Ok(None) => fail(PensaError::NotFound(format!("{key} is not set")), mode),
+                Err(e) => fail(e, mode),
+            },
+            ConfigSubcommand::Set { key, value } => {
+                let dir = pensa_dir();
+                match pensa::config::set_value(&dir, &key, &value)
+                    .and_then(|()| pensa::config::get_value(&dir, &key))
+                {
+                    Ok(value) => output::print_config_value(&key, &value.unwrap_or_default(), mode),
                     Err(e) => fail(e, mode),
-                },
-                DocRefSubcommand::Remove { ref_id } => {
-                    match client.remove_doc_ref(&ref_id, &actor) {
-                        Ok(()) => output::print_deleted(mode),
-                        Err(e) => fail(e, mode),
-                    }
                 }
             }
-        }
+        },
 
-        Commands::Export => {
-            let client = Client::new();
-            match client.export() {
-                Ok(v) => {
-                    output::print_export_import(&v, mode);
-                    let _ = std::process::Command::new("git")
-                        .args(["add", ".pensa/*.jsonl"])
-                        .status();
+        Commands::Hooks { subcmd } => match subcmd {
+            HooksSubcommand::Install { force } => {
+                let repo = std::env::current_dir().unwrap();
+                match pensa::hooks::install_hooks(&repo, force) {
+                    Ok(paths) => output::print_hooks_installed(&paths, mode),
+                    Err(e) => fail(e, mode),
                 }
-                Err(e) => fail(e, mode),
             }
-        }
+            HooksSubcommand::PrepareCommitMsg { file, source, sha } => {
+                if sha.is_some() || matches!(source.as_deref(), Some("merge" | "squash" | "commit"))
+                {
+                    return;
+                }
+                if let Err(e) = client.check_reachable() {
+                    eprintln!("pn: warning: skipping commit hook: {e}");
+                    return;
+                }
+                run_commit_hook(prepare_commit_msg(&client, &file, &actor), mode);
+            }
+            HooksSubcommand::CommitMsg { file } => {
+                if let Err(e) = client.check_reachable() {
+                    eprintln!("pn: warning: skipping commit hook: {e}");
+                    return;
+                }
+                run_commit_hook(check_commit_msg(&client, &file), mode);
+            }
+        },
 
-        Commands::Import => {
-            let client = Client::new();
-            match client.import() {
-                Ok(v) => output::print_export_import(&v, mode),
-                Err(e) => fail(e, mode),
+        Commands::Export => match client.export() {
+            Ok(v) => {
+                output::print_export_import(&v, mode);
+                let _ = std::process::Command::new("git")
+                    .args(["add", ".pensa/*.jsonl"])
+                    .status();
             }
-        }
+            Err(e) => fail(e, mode),
+        },
 
-        Commands::Doctor { fix } => {
-            let client = Client::new();
-            match client.doctor(fix) {
-                Ok(v) => output::print_doctor(&v, mode),
-                Err(e) => fail(e, mode),
+        Commands::Import { dry_run: true } => match client.import(true) {
+            Ok(v) => output::print_dry_run(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+        Commands::Import { dry_run: false } => match client.import(false) {
+            Ok(v) => output::print_export_import(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::Doctor {
+            fix,
+            dry_run,
+            stale_after,
+        } => match client.doctor(fix, dry_run, stale_after.as_deref()) {
+            Ok(v) => output::print_doctor(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::Escalate { dry_run } => match client.escalate(dry_run) {
+            Ok(v) => output::print_escalations(&v, mode),
+            Err(e) => fail(e, mode),
+        },
+
+        Commands::Stress { agents, ops, keep } => {
+            let report =
+                pensa::stress::run(agents, ops, keep, || Client::with_api_version(api_version));
+            let failed = report.errors > 0;
+            output::print_stress(&serde_json::to_value(report).unwrap(), mode);
+            if failed {
+                std::process::exit(1);
             }
         }
     }
@@ -1,6 +1,11 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 
 use crate::error::PensaError;
+use crate::types::ParseEnumError;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputMode {
@@ -16,7 +21,7 @@ pub fn print_error(err: &PensaError, mode: OutputMode) {
     match mode {
         OutputMode::Json => {
             let resp = crate::error::ErrorResponse::from(err);
-            eprintln!("{}", serde_json::to_string(&resp).unwrap());
+            println!("{}", serde_json::to_string(&resp).unwrap());
         }
         OutputMode::Human => {
             eprintln!("error: {err}");
@@ -34,89 +39,261 @@ pub fn print_issue(value: &Value, mode: OutputMode) {
             let priority = value["priority"].as_str().unwrap_or("?");
             let itype = value["issue_type"].as_str().unwrap_or("?");
             let assignee = value["assignee"].as_str().unwrap_or("-");
-            println!("{id}  {priority} {status:<11} [{itype}] {title}  @{assignee}");
+            match format_checklist(value) {
+                Some(progress) => println!(
+                    "{id}  {priority} {status:<11} [{itype}] {title}  [{progress}]  @{assignee}"
+                ),
+                None => println!("{id}  {priority} {status:<11} [{itype}] {title}  @{assignee}"),
+            }
+            if let Some(closed) = value["auto_closed"].as_array() {
+                let ids: Vec<&str> = closed.iter().filter_map(|c| c.as_str()).collect();
+                println!("  also closed: {}", ids.join(", "));
+            }
+            if let Some(warnings) = value["warnings"].as_array() {
+                for w in warnings.iter().filter_map(|w| w.as_str()) {
+                    eprintln!("warning: {w}");
+                }
+            }
         }
     }
 }
 
-pub fn print_issue_detail(value: &Value, mode: OutputMode) {
+pub fn print_seeded(value: &Value, mode: OutputMode) {
     match mode {
         OutputMode::Json => print_json(value),
         OutputMode::Human => {
-            let id = value["id"].as_str().unwrap_or("?");
-            let title = value["title"].as_str().unwrap_or("?");
-            let status = value["status"].as_str().unwrap_or("?");
-            let priority = value["priority"].as_str().unwrap_or("?");
-            let itype = value["issue_type"].as_str().unwrap_or("?");
-            let assignee = value["assignee"].as_str().unwrap_or("-");
-            let created = value["created_at"].as_str().unwrap_or("?");
-
-            println!("{id}  [{itype}] {title}");
-            println!("  status: {status}  priority: {priority}  assignee: {assignee}");
-            println!("  created: {created}");
-
-            if let Some(desc) = value["description"].as_str() {
-                println!("  description: {desc}");
-            }
-            if let Some(spec) = value["spec"].as_str() {
-                println!("  spec: {spec}");
+            let issues = value["issues"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            for issue in issues {
+                print_issue(issue, mode);
             }
-            if let Some(fixes) = value["fixes"].as_str() {
-                println!("  fixes: {fixes}");
-            }
-
-            if let Some(deps) = value["deps"].as_array()
-                && !deps.is_empty()
-            {
-                println!("  deps:");
-                for dep in deps {
-                    let dep_id = dep["id"].as_str().unwrap_or("?");
-                    let dep_title = dep["title"].as_str().unwrap_or("?");
-                    let dep_status = dep["status"].as_str().unwrap_or("?");
-                    println!("    {dep_id} [{dep_status}] {dep_title}");
+            if let Some(warnings) = value["warnings"].as_array() {
+                for w in warnings.iter().filter_map(|w| w.as_str()) {
+                    eprintln!("warning: {w}");
                 }
             }
+            let noun = if issues.len() == 1 { "issue" } else { "issues" };
+            println!("created {} {noun}", issues.len());
+        }
+    }
+}
 
-            if let Some(src_refs) = value["src_refs"].as_array()
-                && !src_refs.is_empty()
-            {
-                println!("  src-refs:");
-                for r in src_refs {
-                    let rid = r["id"].as_str().unwrap_or("?");
-                    let path = r["path"].as_str().unwrap_or("?");
-                    let reason = r["reason"].as_str();
-                    match reason {
-                        Some(rs) => println!("    {rid}  {path}  ({rs})"),
-                        None => println!("    {rid}  {path}"),
-                    }
-                }
-            }
+fn format_checklist(issue: &Value) -> Option<String> {
+    let checklist = &issue["checklist"];
+    Some(format!(
+        "{}/{}",
+        checklist["done"].as_u64()?,
+        checklist["total"].as_u64()?
+    ))
+}
 
-            if let Some(doc_refs) = value["doc_refs"].as_array()
-                && !doc_refs.is_empty()
-            {
-                println!("  doc-refs:");
-                for r in doc_refs {
-                    let rid = r["id"].as_str().unwrap_or("?");
-                    let path = r["path"].as_str().unwrap_or("?");
-                    let reason = r["reason"].as_str();
-                    match reason {
-                        Some(rs) => println!("    {rid}  {path}  ({rs})"),
-                        None => println!("    {rid}  {path}"),
-                    }
-                }
+fn status_marker(issue: &Value) -> &'static str {
+    if issue["status"] == "closed" {
+        "✓"
+    } else {
+        "●"
+    }
+}
+
+fn format_linked_issue(issue: &Value) -> String {
+    format!(
+        "{} {} [{}] {}",
+        status_marker(issue),
+        issue["id"].as_str().unwrap_or("?"),
+        issue["status"].as_str().unwrap_or("?"),
+        issue["title"].as_str().unwrap_or("?")
+    )
+}
+
+/// Events and comments on one issue, oldest first. `commented` and
+/// `mentioned` events repeat a comment, so only the comment is kept.
+fn timeline(history: &[Value], comments: &[Value]) -> Vec<(String, String)> {
+    let mut entries: Vec<(String, String)> = history
+        .iter()
+        .filter(|e| e["event_type"] != "commented" && e["event_type"] != "mentioned")
+        .map(|e| {
+            let etype = e["event_type"].as_str().unwrap_or("?");
+            let actor = e["actor"].as_str().unwrap_or("-");
+            let line = match e["detail"].as_str().filter(|d| !d.is_empty()) {
+                Some(detail) => format!("{etype} by {actor}: {detail}"),
+                None => format!("{etype} by {actor}"),
+            };
+            (e["created_at"].as_str().unwrap_or("?").to_string(), line)
+        })
+        .collect();
+    // History comes newest first; reverse it so equal timestamps keep
+    // the order the events happened in.
+    entries.reverse();
+    entries.extend(comments.iter().map(|c| {
+        let actor = c["actor"].as_str().unwrap_or("?");
+        let text = c["text"].as_str().unwrap_or("");
+        (
+            c["created_at"].as_str().unwrap_or("?").to_string(),
+            format!("💬 {actor}: {text}"),
+        )
+    }));
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+pub fn render_issue_detail(value: &Value, history: &[Value]) -> String {
+    let mut out = String::new();
+    let mut line = |l: String| {
+        out.push_str(&l);
+        out.push('\n');
+    };
+    let list = |key: &str| value[key].as_array().cloned().unwrap_or_default();
+
+    let id = value["id"].as_str().unwrap_or("?");
+    let title = value["title"].as_str().unwrap_or("?");
+    let status = value["status"].as_str().unwrap_or("?");
+    let priority = value["priority"].as_str().unwrap_or("?");
+    let itype = value["issue_type"].as_str().unwrap_or("?");
+    let assignee = value["assignee"].as_str().unwrap_or("-");
+    let created = value["created_at"].as_str().unwrap_or("?");
+
+    line(format!("{} {id}  [{itype}] {title}", status_marker(value)));
+    line(format!(
+        "  status: {status}  priority: {priority}  assignee: {assignee}"
+    ));
+    match value["rev"].as_i64() {
+        Some(rev) => line(format!("  created: {created}  rev: {rev}")),
+        None => line(format!("  created: {created}")),
+    }
+    let labels = list("labels");
+    if !labels.is_empty() {
+        let chips: Vec<String> = labels
+            .iter()
+            .filter_map(|l| l.as_str())
+            .map(|l| format!("[{l}]"))
+            .collect();
+        line(format!("  {}", chips.join(" ")));
+    }
+    if let Some(spec) = value["spec"].as_str() {
+        line(format!("  spec: {spec}"));
+    }
+    if let Some(progress) = format_checklist(value) {
+        line(format!("  checklist: {progress} done"));
+    }
+    match (value["resolution"].as_str(), value["close_reason"].as_str()) {
+        (Some(resolution), Some(reason)) => line(format!("  resolution: {resolution} ({reason})")),
+        (Some(resolution), None) => line(format!("  resolution: {resolution}")),
+        (None, Some(reason)) => line(format!("  close reason: {reason}")),
+        (None, None) => {}
+    }
+    if let Some(desc) = value["description"].as_str() {
+        line(format!("  description: {desc}"));
+    }
+
+    if let Some(fixes) = value["fixes"].as_str() {
+        line(format!("  fixes: {fixes}"));
+    }
+    let fixed_by = list("fixed_by");
+    if !fixed_by.is_empty() {
+        line("  fixed by:".to_string());
+        for f in &fixed_by {
+            line(format!("    {}", format_linked_issue(f)));
+        }
+    }
+
+    let deps = list("deps");
+    if !deps.is_empty() {
+        let open = deps.iter().filter(|d| d["status"] != "closed").count();
+        line(if open == 0 {
+            "  deps: all closed".to_string()
+        } else {
+            format!("  deps: {open} open blocker(s)")
+        });
+        for dep in &deps {
+            line(format!("    {}", format_linked_issue(dep)));
+        }
+    }
+    let external = list("external_deps");
+    if !external.is_empty() {
+        let open = external
+            .iter()
+            .filter(|d| d["resolved_at"].is_null())
+            .count();
+        line(if open == 0 {
+            "  external deps: all resolved".to_string()
+        } else {
+            format!("  external deps: {open} unresolved")
+        });
+        for dep in &external {
+            line(format!("    {}", format_external_dep(dep)));
+        }
+    }
+
+    let refs = list("references");
+    if !refs.is_empty() {
+        let ids: Vec<&str> = refs.iter().filter_map(|r| r.as_str()).collect();
+        line(format!("  references: {}", ids.join(", ")));
+    }
+    let referenced_by = list("referenced_by");
+    if !referenced_by.is_empty() {
+        line("  referenced by:".to_string());
+        for r in &referenced_by {
+            line(format!("    {}", format_linked_issue(r)));
+        }
+    }
+
+    for (key, heading) in [("src_refs", "src-refs"), ("doc_refs", "doc-refs")] {
+        let refs = list(key);
+        if refs.is_empty() {
+            continue;
+        }
+        line(format!("  {heading}:"));
+        for r in &refs {
+            let rid = r["id"].as_str().unwrap_or("?");
+            let path = r["path"].as_str().unwrap_or("?");
+            match r["reason"].as_str() {
+                Some(rs) => line(format!("    {rid}  {path}  ({rs})")),
+                None => line(format!("    {rid}  {path}")),
             }
+        }
+    }
 
-            if let Some(comments) = value["comments"].as_array()
-                && !comments.is_empty()
-            {
-                println!("  comments:");
-                for c in comments {
-                    let actor = c["actor"].as_str().unwrap_or("?");
-                    let text = c["text"].as_str().unwrap_or("");
-                    let at = c["created_at"].as_str().unwrap_or("?");
-                    println!("    [{at}] {actor}: {text}");
-                }
+    let commits = list("commits");
+    if !commits.is_empty() {
+        line("  commits:".to_string());
+        for c in &commits {
+            line(format!("    {}", format_commit(c)));
+        }
+    }
+
+    let entries = timeline(history, &list("comments"));
+    if !entries.is_empty() {
+        line("  timeline:".to_string());
+        for (at, text) in entries {
+            line(format!("    {at}  {text}"));
+        }
+    }
+    out
+}
+
+pub fn print_issue_detail(value: &Value, history: &[Value], mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => print!("{}", render_issue_detail(value, history)),
+    }
+}
+
+pub fn print_issue_text(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let s = |key: &str| value[key].as_str().unwrap_or("");
+            println!("{} as of {}", s("id"), s("at"));
+            println!("title: {}", s("title"));
+            match value["edited_at"].as_str() {
+                Some(at) => println!("last edited {at} by {}", s("edited_by")),
+                None => println!("unedited since created"),
+            }
+            if let Some(description) = value["description"].as_str() {
+                println!();
+                println!("{description}");
             }
         }
     }
@@ -139,6 +316,307 @@ pub fn print_issue_list(value: &Value, mode: OutputMode) {
     }
 }
 
+fn format_exclusion(reason: &Value) -> String {
+    let ids = || {
+        reason["ids"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| id.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match reason["reason"].as_str().unwrap_or("?") {
+        "closed" => "closed".to_string(),
+        "claimed" => match reason["assignee"].as_str() {
+            Some(assignee) => format!("claimed by {assignee}"),
+            None => "in progress".to_string(),
+        },
+        "blocked_by" => format!("blocked by {}", ids()),
+        "fix_in_progress" => format!("fix in progress: {}", ids()),
+        "filtered" => format!(
+            "filtered by --{}: wants {}, has {}",
+            reason["filter"].as_str().unwrap_or("?").replace('_', "-"),
+            reason["wanted"].as_str().unwrap_or("?"),
+            reason["actual"].as_str().unwrap_or("none")
+        ),
+        "past_limit" => format!("past --limit {}", reason["limit"]),
+        other => other.to_string(),
+    }
+}
+
+fn render_ready_explanation(explanation: &Value) -> String {
+    let id = explanation["id"].as_str().unwrap_or("?");
+    if explanation["ready"] == true {
+        return format!("{id} is ready\n");
+    }
+    let mut out = format!(
+        "{id}  {} {:<11} [{}] {}\n",
+        explanation["priority"].as_str().unwrap_or("?"),
+        explanation["status"].as_str().unwrap_or("?"),
+        explanation["issue_type"].as_str().unwrap_or("?"),
+        explanation["title"].as_str().unwrap_or("?")
+    );
+    for reason in explanation["reasons"].as_array().into_iter().flatten() {
+        out.push_str(&format!("  - {}\n", format_exclusion(reason)));
+    }
+    out
+}
+
+pub fn print_ready_explanation(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => print!("{}", render_ready_explanation(value)),
+    }
+}
+
+pub fn print_ready_explanations(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("(every unclosed issue is ready)");
+                } else {
+                    for explanation in arr {
+                        print!("{}", render_ready_explanation(explanation));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListColumn {
+    Id,
+    Title,
+    Status,
+    Priority,
+    Type,
+    Assignee,
+    Spec,
+    Resolution,
+    Age,
+    Created,
+    Updated,
+    Checklist,
+}
+
+impl ListColumn {
+    pub const DEFAULT: &'static [ListColumn] = &[
+        ListColumn::Id,
+        ListColumn::Priority,
+        ListColumn::Status,
+        ListColumn::Type,
+        ListColumn::Title,
+        ListColumn::Assignee,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListColumn::Id => "id",
+            ListColumn::Title => "title",
+            ListColumn::Status => "status",
+            ListColumn::Priority => "priority",
+            ListColumn::Type => "type",
+            ListColumn::Assignee => "assignee",
+            ListColumn::Spec => "spec",
+            ListColumn::Resolution => "resolution",
+            ListColumn::Age => "age",
+            ListColumn::Created => "created",
+            ListColumn::Updated => "updated",
+            ListColumn::Checklist => "checklist",
+        }
+    }
+
+    fn value(&self, issue: &Value, now: DateTime<Utc>) -> Option<String> {
+        let field = |name: &str| issue[name].as_str().map(str::to_string);
+        match self {
+            ListColumn::Id => field("id"),
+            ListColumn::Title => field("title"),
+            ListColumn::Status => field("status"),
+            ListColumn::Priority => field("priority"),
+            ListColumn::Type => field("issue_type"),
+            ListColumn::Assignee => field("assignee"),
+            ListColumn::Spec => field("spec"),
+            ListColumn::Resolution => field("resolution"),
+            ListColumn::Age => issue["created_at"]
+                .as_str()
+                .and_then(|s| s.parse::<DateTime<Utc>>().ok())
+                .map(|created| format_age(now - created)),
+            ListColumn::Created => field("created_at"),
+            ListColumn::Updated => field("updated_at"),
+            ListColumn::Checklist => format_checklist(issue),
+        }
+    }
+}
+
+impl fmt::Display for ListColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ListColumn {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "id" => Ok(ListColumn::Id),
+            "title" => Ok(ListColumn::Title),
+            "status" => Ok(ListColumn::Status),
+            "priority" => Ok(ListColumn::Priority),
+            "type" | "issue_type" => Ok(ListColumn::Type),
+            "assignee" => Ok(ListColumn::Assignee),
+            "spec" => Ok(ListColumn::Spec),
+            "resolution" => Ok(ListColumn::Resolution),
+            "age" => Ok(ListColumn::Age),
+            "created" | "created_at" => Ok(ListColumn::Created),
+            "updated" | "updated_at" => Ok(ListColumn::Updated),
+            "checklist" => Ok(ListColumn::Checklist),
+            _ => Err(ParseEnumError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFormat {
+    Table,
+    Tsv,
+    Yaml,
+}
+
+impl FromStr for ListFormat {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(ListFormat::Table),
+            "tsv" => Ok(ListFormat::Tsv),
+            "yaml" => Ok(ListFormat::Yaml),
+            _ => Err(ParseEnumError(s.to_string())),
+        }
+    }
+}
+
+fn format_age(age: chrono::Duration) -> String {
+    let minutes = age.num_minutes().max(0);
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else if minutes < 60 * 24 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}d", minutes / (60 * 24))
+    }
+}
+
+/// Renders issue rows as a table, TSV, or YAML restricted to `columns`.
+/// Table widths fit the longest value; when `COLUMNS` is set the title
+/// column is truncated so each row fits the terminal.
+pub fn render_issue_table(issues: &[Value], columns: &[ListColumn], format: ListFormat) -> String {
+    let now = Utc::now();
+    let rows: Vec<Vec<Option<String>>> = issues
+        .iter()
+        .map(|issue| columns.iter().map(|c| c.value(issue, now)).collect())
+        .collect();
+    let mut out = String::new();
+
+    match format {
+        ListFormat::Tsv => {
+            let header: Vec<&str> = columns.iter().map(ListColumn::as_str).collect();
+            out.push_str(&header.join("\t"));
+            out.push('\n');
+            for row in &rows {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|v| v.as_deref().unwrap_or("").replace(['\t', '\n'], " "))
+                    .collect();
+                out.push_str(&cells.join("\t"));
+                out.push('\n');
+            }
+        }
+        ListFormat::Yaml => {
+            if rows.is_empty() {
+                out.push_str("[]\n");
+            }
+            for row in &rows {
+                for (i, (column, v)) in columns.iter().zip(row).enumerate() {
+                    let prefix = if i == 0 { "- " } else { "  " };
+                    let scalar = match v {
+                        Some(v) => serde_json::to_string(v).unwrap(),
+                        None => "null".to_string(),
+                    };
+                    out.push_str(&format!("{prefix}{column}: {scalar}\n"));
+                }
+            }
+        }
+        ListFormat::Table => {
+            let cells: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|v| v.clone().unwrap_or_else(|| "-".to_string()))
+                        .collect()
+                })
+                .collect();
+            let mut widths: Vec<usize> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    cells
+                        .iter()
+                        .map(|row| row[i].chars().count())
+                        .chain([c.as_str().len()])
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+
+            let max_width = std::env::var("COLUMNS")
+                .ok()
+                .and_then(|c| c.parse::<usize>().ok());
+            if let (Some(max_width), Some(title)) = (
+                max_width,
+                columns.iter().position(|c| *c == ListColumn::Title),
+            ) {
+                let total: usize = widths.iter().sum::<usize>() + 2 * (widths.len() - 1);
+                if total > max_width {
+                    let min_title = ListColumn::Title.as_str().len();
+                    widths[title] = widths[title]
+                        .saturating_sub(total - max_width)
+                        .max(min_title);
+                }
+            }
+
+            let header: Vec<String> = columns.iter().map(|c| c.as_str().to_uppercase()).collect();
+            for row in std::iter::once(&header).chain(&cells) {
+                let padded: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, &width)| format!("{:<width$}", truncate(cell, width)))
+                    .collect();
+                out.push_str(padded.join("  ").trim_end());
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_string();
+    }
+    let mut truncated: String = s.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+pub fn print_issue_table(value: &Value, columns: &[ListColumn], format: ListFormat) {
+    let issues = value.as_array().map(Vec::as_slice).unwrap_or(&[]);
+    print!("{}", render_issue_table(issues, columns, format));
+}
+
 pub fn print_events(value: &Value, mode: OutputMode) {
     match mode {
         OutputMode::Json => print_json(value),
@@ -176,6 +654,57 @@ pub fn print_dep_status(value: &Value, mode: OutputMode) {
     }
 }
 
+fn format_external_dep(dep: &Value) -> String {
+    let mark = if dep["resolved_at"].is_null() {
+        "○"
+    } else {
+        "✓"
+    };
+    let target = dep["target"].as_str().unwrap_or("?");
+    let kind = dep["kind"].as_str().unwrap_or("?");
+    format!("{mark} {target} ({kind})")
+}
+
+pub fn print_external_dep(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let issue_id = value["issue_id"].as_str().unwrap_or("?");
+            let status = if value["resolved_at"].is_null() {
+                "open"
+            } else {
+                "resolved"
+            };
+            println!("{issue_id} -> {}: {status}", format_external_dep(value));
+        }
+    }
+}
+
+pub fn print_promoted_refs(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let added: Vec<&str> = value["added"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str())
+                .collect();
+            if added.is_empty() {
+                println!("no references promoted");
+            }
+            for id in added {
+                println!("dep added: {id}");
+            }
+            for s in value["skipped"].as_array().into_iter().flatten() {
+                let id = s["id"].as_str().unwrap_or("?");
+                let reason = s["reason"].as_str().unwrap_or("?");
+                println!("  skipped {id}: {reason}");
+            }
+        }
+    }
+}
+
 pub fn print_dep_tree(value: &Value, mode: OutputMode) {
     match mode {
         OutputMode::Json => print_json(value),
@@ -279,6 +808,334 @@ pub fn print_ref_list(value: &Value, mode: OutputMode) {
     }
 }
 
+fn format_commit(value: &Value) -> String {
+    let sha = value["sha"].as_str().unwrap_or("?");
+    let short = sha.get(..10).unwrap_or(sha);
+    match value["summary"].as_str() {
+        Some(summary) => format!("{short}  {summary}"),
+        None => short.to_string(),
+    }
+}
+
+pub fn print_commit(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let issue_id = value["issue_id"].as_str().unwrap_or("?");
+            println!("{issue_id}  {}", format_commit(value));
+        }
+    }
+}
+
+pub fn print_commit_list(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("(no commits)");
+                } else {
+                    for c in arr {
+                        println!("{}", format_commit(c));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn print_labels(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("(no labels)");
+                } else {
+                    for l in arr.iter().filter_map(|l| l.as_str()) {
+                        println!("{l}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn print_watchers(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("(no watchers)");
+                } else {
+                    for w in arr.iter().filter_map(|w| w.as_str()) {
+                        println!("{w}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn format_webhook(w: &Value) -> String {
+    let events = w["events"]
+        .as_array()
+        .filter(|e| !e.is_empty())
+        .map(|e| {
+            e.iter()
+                .filter_map(|e| e.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .unwrap_or_else(|| "all events".to_string());
+    let mut line = format!(
+        "{}  {}  {events}",
+        w["id"].as_str().unwrap_or("?"),
+        w["url"].as_str().unwrap_or("?")
+    );
+    if let Some(spec) = w["spec"].as_str() {
+        line.push_str(&format!("  spec:{spec}"));
+    }
+    line
+}
+
+pub fn print_webhook(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            println!("{}", format_webhook(value));
+            if let Some(secret) = value["secret"].as_str() {
+                println!("secret: {secret}");
+            }
+        }
+    }
+}
+
+pub fn print_webhook_list(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("(no webhooks)");
+                } else {
+                    for w in arr {
+                        println!("{}", format_webhook(w));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn format_actor(actor: &Value) -> String {
+    let name = actor["name"].as_str().unwrap_or("?");
+    let last_seen = actor["last_seen"].as_str().unwrap_or("?");
+    match actor["alias_of"].as_str() {
+        Some(identity) => format!("  {name}  alias of {identity}  last seen {last_seen}"),
+        None => match actor["display_name"].as_str() {
+            Some(display) => format!("{name}  \"{display}\"  last seen {last_seen}"),
+            None => format!("{name}  last seen {last_seen}"),
+        },
+    }
+}
+
+pub fn print_actor(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => println!("{}", format_actor(value).trim_start()),
+    }
+}
+
+pub fn print_actor_list(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("(no actors)");
+                } else {
+                    for a in arr {
+                        println!("{}", format_actor(a));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn format_snapshot(snap: &Value) -> String {
+    let mut line = format!(
+        "{}  {}  {} issues",
+        snap["id"].as_str().unwrap_or("?"),
+        snap["created_at"].as_str().unwrap_or("?"),
+        snap["issue_count"].as_u64().unwrap_or(0)
+    );
+    if let Some(label) = snap["label"].as_str() {
+        line.push_str(&format!("  {label}"));
+    }
+    line
+}
+
+pub fn print_snapshot(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => println!("{}", format_snapshot(value)),
+    }
+}
+
+pub fn print_snapshot_list(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("(no snapshots)");
+                } else {
+                    for snap in arr {
+                        println!("{}", format_snapshot(snap));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn render_diff(diff: &Value) -> String {
+    let mut out = format!(
+        "{} → {}\n",
+        diff["from"].as_str().unwrap_or("?"),
+        diff["to"].as_str().unwrap_or("?")
+    );
+    let section = |key: &str| diff[key].as_array().cloned().unwrap_or_default();
+    let issue_line = |issue: &Value| {
+        format!(
+            "  {}  {} [{}] {}",
+            issue["id"].as_str().unwrap_or("?"),
+            issue["priority"].as_str().unwrap_or("?"),
+            issue["issue_type"].as_str().unwrap_or("?"),
+            issue["title"].as_str().unwrap_or("?")
+        )
+    };
+    let (created, closed, changed, deleted) = (
+        section("created"),
+        section("closed"),
+        section("changed"),
+        section("deleted"),
+    );
+    if created.is_empty() && closed.is_empty() && changed.is_empty() && deleted.is_empty() {
+        out.push_str("(no changes)\n");
+        return out;
+    }
+    for (name, issues) in [
+        ("created", &created),
+        ("closed", &closed),
+        ("deleted", &deleted),
+    ] {
+        if issues.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("{name} ({})\n", issues.len()));
+        for issue in issues {
+            out.push_str(&issue_line(issue));
+            if name == "closed"
+                && let Some(resolution) = issue["resolution"].as_str()
+            {
+                out.push_str(&format!(" ({resolution})"));
+            }
+            out.push('\n');
+        }
+    }
+    if !changed.is_empty() {
+        out.push_str(&format!("changed ({})\n", changed.len()));
+        for issue in &changed {
+            out.push_str(&format!(
+                "  {}  {}\n",
+                issue["id"].as_str().unwrap_or("?"),
+                issue["title"].as_str().unwrap_or("?")
+            ));
+            for change in issue["changes"].as_array().into_iter().flatten() {
+                let field = change["field"].as_str().unwrap_or("?");
+                if field == "description" {
+                    out.push_str("    description changed\n");
+                } else {
+                    out.push_str(&format!(
+                        "    {field}: {} → {}\n",
+                        change["from"].as_str().unwrap_or("-"),
+                        change["to"].as_str().unwrap_or("-")
+                    ));
+                }
+            }
+        }
+    }
+    out
+}
+
+pub fn print_diff(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => print!("{}", render_diff(value)),
+    }
+}
+
+pub fn print_inbox(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let entries = value["entries"].as_array().cloned().unwrap_or_default();
+            if entries.is_empty() {
+                println!("inbox empty");
+                return;
+            }
+            for e in &entries {
+                let ts = e["created_at"].as_str().unwrap_or("?");
+                let id = e["issue_id"].as_str().unwrap_or("?");
+                let title = e["title"].as_str().unwrap_or("?");
+                let etype = e["event_type"].as_str().unwrap_or("?");
+                let actor = e["actor"].as_str().unwrap_or("-");
+                match e["detail"].as_str() {
+                    Some(detail) => println!("{ts}  {id}  {title}  {etype} by {actor}: {detail}"),
+                    None => println!("{ts}  {id}  {title}  {etype} by {actor}"),
+                }
+            }
+        }
+    }
+}
+
+pub fn print_scan_commits(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let scanned = value["scanned"].as_i64().unwrap_or(0);
+            let linked = value["linked"].as_array().map_or(0, |a| a.len());
+            println!("scanned {scanned} commits, linked {linked}");
+            if let Some(closed) = value["closed"].as_array() {
+                for id in closed.iter().filter_map(|v| v.as_str()) {
+                    println!("  closed {id}");
+                }
+            }
+            if let Some(unknown) = value["unknown"].as_array()
+                && !unknown.is_empty()
+            {
+                let ids: Vec<&str> = unknown.iter().filter_map(|v| v.as_str()).collect();
+                println!("  unknown issue ids: {}", ids.join(", "));
+            }
+        }
+    }
+}
+
+pub fn print_hooks_installed(paths: &[std::path::PathBuf], mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(&serde_json::json!({ "installed": paths })),
+        OutputMode::Human => {
+            for path in paths {
+                println!("installed {}", path.display());
+            }
+        }
+    }
+}
+
 pub fn print_count(value: &Value, mode: OutputMode) {
     match mode {
         OutputMode::Json => print_json(value),
@@ -320,6 +1177,115 @@ pub fn print_status(value: &Value, mode: OutputMode) {
     }
 }
 
+pub fn print_workload(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let entries = value["assignees"].as_array().cloned().unwrap_or_default();
+            if entries.is_empty() {
+                println!("(no assigned issues)");
+            } else {
+                let name = |e: &Value| {
+                    e["display_name"]
+                        .as_str()
+                        .or(e["assignee"].as_str())
+                        .unwrap_or("?")
+                        .to_string()
+                };
+                let width = entries
+                    .iter()
+                    .map(name)
+                    .map(|n| n.len())
+                    .max()
+                    .unwrap_or(0)
+                    .max("assignee".len());
+                println!(
+                    "{:<width$} {:>11} {:>5} {:>6} {:>6}",
+                    "assignee", "in_progress", "open", "active", "urgent"
+                );
+                for e in &entries {
+                    let name = name(e);
+                    let in_prog = e["in_progress"].as_i64().unwrap_or(0);
+                    let open = e["open"].as_i64().unwrap_or(0);
+                    let active = e["active"].as_i64().unwrap_or(0);
+                    let urgent = e["urgent"].as_i64().unwrap_or(0);
+                    let flag = if e["overloaded"].as_bool().unwrap_or(false) {
+                        "  overloaded"
+                    } else {
+                        ""
+                    };
+                    println!(
+                        "{name:<width$} {in_prog:>11} {open:>5} {active:>6} {urgent:>6}{flag}"
+                    );
+                }
+            }
+            let unassigned = value["unassigned"].as_i64().unwrap_or(0);
+            let max_active = value["max_active"].as_i64().unwrap_or(0);
+            println!("unassigned open: {unassigned}  (overloaded above {max_active} active)");
+        }
+    }
+}
+
+pub fn print_spec_status(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("(no specs)");
+                    return;
+                }
+                let width = arr
+                    .iter()
+                    .filter_map(|e| e["spec"].as_str())
+                    .map(str::len)
+                    .max()
+                    .unwrap_or(0)
+                    .max(4);
+                println!(
+                    "{:<width$} {:>5} {:>11} {:>7} {:>3} {:>3} {:>7}",
+                    "spec", "open", "in_progress", "closed", "p0", "p1", "blocked"
+                );
+                for entry in arr {
+                    print_spec_status_row(entry, width);
+                }
+            }
+        }
+    }
+}
+
+fn print_spec_status_row(entry: &Value, width: usize) {
+    let spec = entry["spec"].as_str().unwrap_or("?");
+    let open = entry["open"].as_i64().unwrap_or(0);
+    let in_prog = entry["in_progress"].as_i64().unwrap_or(0);
+    let closed = entry["closed"].as_i64().unwrap_or(0);
+    let p0 = entry["open_p0"].as_i64().unwrap_or(0);
+    let p1 = entry["open_p1"].as_i64().unwrap_or(0);
+    let blocked = entry["blocked"].as_i64().unwrap_or(0);
+    println!("{spec:<width$} {open:>5} {in_prog:>11} {closed:>7} {p0:>3} {p1:>3} {blocked:>7}");
+}
+
+pub fn print_spec_detail(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let spec = value["spec"].as_str().unwrap_or("?");
+            let open = value["open"].as_i64().unwrap_or(0);
+            let in_prog = value["in_progress"].as_i64().unwrap_or(0);
+            let closed = value["closed"].as_i64().unwrap_or(0);
+            let p0 = value["open_p0"].as_i64().unwrap_or(0);
+            let p1 = value["open_p1"].as_i64().unwrap_or(0);
+            let blocked = value["blocked"].as_i64().unwrap_or(0);
+            println!("spec: {spec}");
+            println!("open: {open}  in_progress: {in_prog}  closed: {closed}");
+            println!("p0: {p0}  p1: {p1}  blocked: {blocked}");
+            println!();
+            println!("outstanding:");
+            print_issue_list(&value["outstanding"], OutputMode::Human);
+        }
+    }
+}
+
 pub fn print_doctor(value: &Value, mode: OutputMode) {
     match mode {
         OutputMode::Json => print_json(value),
@@ -335,16 +1301,240 @@ pub fn print_doctor(value: &Value, mode: OutputMode) {
                     }
                 }
             }
+            let dry_run = value["dry_run"].as_bool().unwrap_or(false);
             if let Some(fixes) = value["fixes_applied"].as_array()
                 && !fixes.is_empty()
             {
-                println!("fixes applied:");
+                println!(
+                    "{}",
+                    if dry_run {
+                        "fixes that would apply:"
+                    } else {
+                        "fixes applied:"
+                    }
+                );
                 for fix in fixes {
                     if let Some(s) = fix.as_str() {
                         println!("  {s}");
                     }
                 }
             }
+            if dry_run {
+                print!("{}", render_dry_run(value));
+            }
+        }
+    }
+}
+
+const DRY_RUN_ROWS_SHOWN: usize = 10;
+
+pub fn render_dry_run(value: &Value) -> String {
+    let changes = value["changes"].as_array().cloned().unwrap_or_default();
+    if changes.is_empty() {
+        return "no rows would change\n".to_string();
+    }
+    let mut groups: Vec<((&str, &str), Vec<&str>)> = Vec::new();
+    for change in &changes {
+        let group = (
+            change["action"].as_str().unwrap_or("?"),
+            change["table"].as_str().unwrap_or("?"),
+        );
+        let key = change["key"].as_str().unwrap_or("?");
+        match groups.iter_mut().find(|(g, _)| *g == group) {
+            Some((_, keys)) => keys.push(key),
+            None => groups.push((group, vec![key])),
+        }
+    }
+    let mut out = format!("would change {} row(s):\n", changes.len());
+    for ((action, table), keys) in groups {
+        if keys.len() > DRY_RUN_ROWS_SHOWN {
+            out.push_str(&format!("  {action} {table}: {} rows\n", keys.len()));
+        } else {
+            for key in keys {
+                out.push_str(&format!("  {action} {table} {key}\n"));
+            }
+        }
+    }
+    out
+}
+
+pub fn print_dry_run(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => print!("{}", render_dry_run(value)),
+    }
+}
+
+pub fn print_stale(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("no stale issues");
+                } else {
+                    for issue in arr {
+                        let s = |key: &str| issue[key].as_str().unwrap_or("?");
+                        let age = issue["age_days"].as_i64().unwrap_or(0);
+                        println!(
+                            "{:>4}d  {}  {}  {}  {}",
+                            age,
+                            s("id"),
+                            s("status"),
+                            s("priority"),
+                            s("title")
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn print_escalations(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            if let Some(arr) = value.as_array() {
+                if arr.is_empty() {
+                    println!("no issues to escalate");
+                } else {
+                    for e in arr {
+                        let id = e["issue_id"].as_str().unwrap_or("?");
+                        let from = e["from"].as_str().unwrap_or("?");
+                        let to = e["to"].as_str().unwrap_or("?");
+                        let title = e["title"].as_str().unwrap_or("?");
+                        let reason = e["reason"].as_str().unwrap_or("");
+                        println!("{id}  {from} -> {to}  {title}  ({reason})");
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn print_stress(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let agents = value["agents"].as_u64().unwrap_or(0);
+            let ops = value["ops"].as_u64().unwrap_or(0);
+            let elapsed = value["elapsed_ms"].as_f64().unwrap_or(0.0);
+            let rate = value["ops_per_sec"].as_f64().unwrap_or(0.0);
+            println!("{ops} ops across {agents} agents in {elapsed:.0}ms ({rate:.1} ops/s)");
+            println!(
+                "{:<8} {:>6} {:>6} {:>9} {:>6} {:>8} {:>8} {:>8} {:>8}",
+                "OP", "COUNT", "OK", "CONFLICTS", "ERRORS", "P50", "P90", "P99", "MAX"
+            );
+            for op in value["operations"].as_array().into_iter().flatten() {
+                let ms = |k: &str| format!("{:.1}ms", op[k].as_f64().unwrap_or(0.0));
+                println!(
+                    "{:<8} {:>6} {:>6} {:>9} {:>6} {:>8} {:>8} {:>8} {:>8}",
+                    op["op"].as_str().unwrap_or("?"),
+                    op["count"].as_u64().unwrap_or(0),
+                    op["ok"].as_u64().unwrap_or(0),
+                    op["conflicts"].as_u64().unwrap_or(0),
+                    op["errors"].as_u64().unwrap_or(0),
+                    ms("p50_ms"),
+                    ms("p90_ms"),
+                    ms("p99_ms"),
+                    ms("max_ms"),
+                );
+            }
+            let conflicts = value["conflicts"].as_u64().unwrap_or(0);
+            let errors = value["errors"].as_u64().unwrap_or(0);
+            let error_rate = value["error_rate"].as_f64().unwrap_or(0.0) * 100.0;
+            println!("conflicts: {conflicts}  errors: {errors} ({error_rate:.2}%)");
+            for e in value["sample_errors"].as_array().into_iter().flatten() {
+                println!("  {}", e.as_str().unwrap_or("?"));
+            }
+            let cleaned = value["cleaned_up"].as_u64().unwrap_or(0);
+            if cleaned > 0 {
+                println!("deleted {cleaned} stress issues");
+            }
+        }
+    }
+}
+
+pub fn print_daemon_status(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            println!(
+                "daemon reachable at {}",
+                value["url"].as_str().unwrap_or("?")
+            );
+            if let Some(version) = value["version"].as_str() {
+                println!(
+                    "version: pensa {version} (API v{}-v{})",
+                    value["min_api_version"], value["api_version"]
+                );
+            }
+            if let Some(project_dir) = value["project_dir"].as_str() {
+                println!("project directory: {project_dir}");
+            }
+            if let Some(mismatch) = value["mismatch"].as_str() {
+                println!("warning: {mismatch}");
+            }
+            if value["health"].is_object() {
+                print!("{}", render_health(&value["health"]));
+            }
+        }
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+pub fn render_health(value: &Value) -> String {
+    let uptime = chrono::Duration::seconds(value["uptime_secs"].as_i64().unwrap_or(0));
+    let mut out = format!(
+        "uptime: {}\nread-only: {}\nschema version: {}\n",
+        format_age(uptime),
+        if value["read_only"].as_bool().unwrap_or(false) {
+            "on"
+        } else {
+            "off"
+        },
+        value["schema_version"]
+    );
+    out.push_str(&format!(
+        "database: {} (WAL {})\n",
+        format_bytes(value["db_bytes"].as_u64().unwrap_or(0)),
+        format_bytes(value["wal_bytes"].as_u64().unwrap_or(0))
+    ));
+    out.push_str(&format!(
+        "last export: {}\n",
+        value["last_export"].as_str().unwrap_or("never")
+    ));
+    if let Some(tables) = value["tables"].as_object() {
+        out.push_str("rows:\n");
+        let width = tables.keys().map(String::len).max().unwrap_or(0);
+        for (table, count) in tables {
+            out.push_str(&format!("  {table:<width$}  {count}\n"));
+        }
+    }
+    out
+}
+
+pub fn print_read_only(value: &Value, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(value),
+        OutputMode::Human => {
+            let on = value["read_only"].as_bool().unwrap_or(false);
+            println!("read-only: {}", if on { "on" } else { "off" });
         }
     }
 }
@@ -359,16 +1549,301 @@ pub fn print_export_import(value: &Value, mode: OutputMode) {
             let comments = value["comments"].as_i64().unwrap_or(0);
             let src_refs = value["src_refs"].as_i64().unwrap_or(0);
             let doc_refs = value["doc_refs"].as_i64().unwrap_or(0);
+            let commits = value["commits"].as_i64().unwrap_or(0);
+            let labels = value["labels"].as_i64().unwrap_or(0);
+            let external_deps = value["external_deps"].as_i64().unwrap_or(0);
+            let actors = value["actors"].as_i64().unwrap_or(0);
             println!(
-                "{status}: {issues} issues, {deps} deps, {comments} comments, {src_refs} src-refs, {doc_refs} doc-refs"
+                "{status}: {issues} issues, {deps} deps, {external_deps} external deps, {comments} comments, {src_refs} src-refs, {doc_refs} doc-refs, {commits} commits, {labels} labels, {actors} actors"
             );
         }
     }
 }
 
+pub fn print_config_value(key: &str, value: &str, mode: OutputMode) {
+    match mode {
+        OutputMode::Json => print_json(&serde_json::json!({"key": key, "value": value})),
+        OutputMode::Human => println!("{value}"),
+    }
+}
+
 pub fn print_deleted(mode: OutputMode) {
     match mode {
         OutputMode::Json => print_json(&serde_json::json!({"status": "deleted"})),
         OutputMode::Human => println!("deleted"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues() -> Vec<Value> {
+        vec![
+            serde_json::json!({
+                "id": "pn-aaaa1111",
+                "title": "Fix the login crash on empty password",
+                "status": "open",
+                "priority": "p0",
+                "issue_type": "bug",
+                "created_at": "2020-01-01T00:00:00Z",
+            }),
+            serde_json::json!({
+                "id": "pn-bbbb2222",
+                "title": "Tabs\tin title",
+                "status": "in_progress",
+                "priority": "p2",
+                "issue_type": "task",
+                "assignee": "alice",
+                "created_at": "2020-01-01T00:00:00Z",
+            }),
+        ]
+    }
+
+    #[test]
+    fn list_column_parses_aliases() {
+        assert_eq!("type".parse::<ListColumn>().unwrap(), ListColumn::Type);
+        assert_eq!(
+            "issue_type".parse::<ListColumn>().unwrap(),
+            ListColumn::Type
+        );
+        assert!("bogus".parse::<ListColumn>().is_err());
+    }
+
+    #[test]
+    fn checklist_column_shows_progress() {
+        let mut issues = issues();
+        issues[0]["checklist"] = serde_json::json!({ "done": 1, "total": 3 });
+        let out = render_issue_table(
+            &issues,
+            &[ListColumn::Id, ListColumn::Checklist],
+            ListFormat::Tsv,
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "id\tchecklist");
+        assert_eq!(lines[1], "pn-aaaa1111\t1/3");
+        assert_eq!(lines[2], "pn-bbbb2222\t");
+    }
+
+    #[test]
+    fn tsv_has_header_and_sanitized_cells() {
+        let columns = [ListColumn::Id, ListColumn::Title, ListColumn::Assignee];
+        let out = render_issue_table(&issues(), &columns, ListFormat::Tsv);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "id\ttitle\tassignee");
+        assert_eq!(
+            lines[1],
+            "pn-aaaa1111\tFix the login crash on empty password\t"
+        );
+        assert_eq!(lines[2], "pn-bbbb2222\tTabs in title\talice");
+    }
+
+    #[test]
+    fn yaml_emits_one_mapping_per_issue() {
+        let columns = [ListColumn::Id, ListColumn::Assignee];
+        let out = render_issue_table(&issues(), &columns, ListFormat::Yaml);
+        assert_eq!(
+            out,
+            "- id: \"pn-aaaa1111\"\n  assignee: null\n- id: \"pn-bbbb2222\"\n  assignee: \"alice\"\n"
+        );
+        assert_eq!(render_issue_table(&[], &columns, ListFormat::Yaml), "[]\n");
+    }
+
+    #[test]
+    fn table_aligns_columns_and_computes_age() {
+        let columns = [ListColumn::Id, ListColumn::Age, ListColumn::Status];
+        let out = render_issue_table(&issues(), &columns, ListFormat::Table);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].starts_with("ID           AGE"));
+        assert!(lines[1].starts_with("pn-aaaa1111  "));
+        assert!(lines[1].contains("d  open"));
+        assert!(lines[2].ends_with("in_progress"));
+    }
+
+    #[test]
+    fn issue_detail_marks_links_and_merges_the_timeline() {
+        let detail = serde_json::json!({
+            "id": "pn-aaaa1111",
+            "title": "Login crash",
+            "status": "open",
+            "priority": "p0",
+            "issue_type": "bug",
+            "created_at": "2020-01-01T00:00:00Z",
+            "labels": ["backend", "triaged"],
+            "deps": [
+                { "id": "pn-dddd0001", "title": "Schema", "status": "closed" },
+                { "id": "pn-dddd0002", "title": "Session store", "status": "in_progress" },
+            ],
+            "external_deps": [
+                { "target": "OPS-17", "kind": "jira", "created_at": "2020-01-01T00:00:00Z" },
+            ],
+            "fixed_by": [{ "id": "pn-ffff0001", "title": "Guard empty password", "status": "open" }],
+            "comments": [{ "actor": "bob", "text": "repro attached", "created_at": "2020-01-01T00:02:00Z" }],
+        });
+        let history = [
+            serde_json::json!({ "event_type": "commented", "actor": "bob", "detail": "repro attached", "created_at": "2020-01-01T00:02:00Z" }),
+            serde_json::json!({ "event_type": "labeled", "actor": "alice", "detail": "backend", "created_at": "2020-01-01T00:00:00Z" }),
+            serde_json::json!({ "event_type": "created", "actor": "alice", "created_at": "2020-01-01T00:00:00Z" }),
+        ];
+        let out = render_issue_detail(&detail, &history);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "● pn-aaaa1111  [bug] Login crash");
+        assert!(lines.contains(&"  [backend] [triaged]"), "{out}");
+        assert!(lines.contains(&"  deps: 1 open blocker(s)"), "{out}");
+        assert!(lines.contains(&"  external deps: 1 unresolved"), "{out}");
+        assert!(lines.contains(&"    ○ OPS-17 (jira)"), "{out}");
+        assert!(
+            lines.contains(&"    ✓ pn-dddd0001 [closed] Schema"),
+            "{out}"
+        );
+        assert!(
+            lines.contains(&"    ● pn-dddd0002 [in_progress] Session store"),
+            "{out}"
+        );
+        assert!(
+            lines.contains(&"    ● pn-ffff0001 [open] Guard empty password"),
+            "{out}"
+        );
+        let timeline = &lines[lines.iter().position(|l| *l == "  timeline:").unwrap() + 1..];
+        assert_eq!(
+            timeline,
+            [
+                "    2020-01-01T00:00:00Z  created by alice",
+                "    2020-01-01T00:00:00Z  labeled by alice: backend",
+                "    2020-01-01T00:02:00Z  💬 bob: repro attached",
+            ]
+        );
+    }
+
+    #[test]
+    fn dry_run_lists_rows_and_counts_large_groups() {
+        let mut changes = vec![
+            serde_json::json!({ "table": "issues", "action": "delete", "key": "pn-aaaa1111" }),
+            serde_json::json!({ "table": "deps", "action": "delete", "key": "pn-aaaa1111 pn-bbbb2222" }),
+        ];
+        changes.extend((1..=12).map(
+            |n| serde_json::json!({ "table": "events", "action": "delete", "key": n.to_string() }),
+        ));
+        let out = render_dry_run(&serde_json::json!({ "changes": changes }));
+        assert_eq!(
+            out,
+            "would change 14 row(s):\n  delete issues pn-aaaa1111\n  delete deps pn-aaaa1111 pn-bbbb2222\n  delete events: 12 rows\n"
+        );
+        assert_eq!(
+            render_dry_run(&serde_json::json!({ "changes": [] })),
+            "no rows would change\n"
+        );
+    }
+
+    #[test]
+    fn health_shows_sizes_and_row_counts() {
+        let health = serde_json::json!({
+            "uptime_secs": 7200,
+            "read_only": false,
+            "schema_version": 3,
+            "tables": { "deps": 4, "issues": 12 },
+            "db_bytes": 1536,
+            "wal_bytes": 0,
+        });
+        assert_eq!(
+            render_health(&health),
+            "uptime: 2h\nread-only: off\nschema version: 3\ndatabase: 1.5 KiB (WAL 0 B)\nlast export: never\nrows:\n  deps    4\n  issues  12\n"
+        );
+    }
+
+    #[test]
+    fn truncate_marks_cut_text() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("a long title", 6), "a lon…");
+    }
+
+    #[test]
+    fn actors_show_display_names_and_indent_aliases() {
+        let identity = serde_json::json!({
+            "name": "wlorey",
+            "display_name": "William Lorey",
+            "last_seen": "2026-10-01T00:00:00Z",
+        });
+        assert_eq!(
+            format_actor(&identity),
+            "wlorey  \"William Lorey\"  last seen 2026-10-01T00:00:00Z"
+        );
+        let alias = serde_json::json!({
+            "name": "sgf-build-auth-1",
+            "alias_of": "wlorey",
+            "last_seen": "2026-10-02T00:00:00Z",
+        });
+        assert_eq!(
+            format_actor(&alias),
+            "  sgf-build-auth-1  alias of wlorey  last seen 2026-10-02T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn diff_lists_each_kind_of_change() {
+        let diff = serde_json::json!({
+            "from": "sn-1111",
+            "to": "now",
+            "created": [issues()[1]],
+            "closed": [{
+                "id": "pn-cccc3333",
+                "title": "Old crash",
+                "priority": "p1",
+                "issue_type": "bug",
+                "resolution": "fixed",
+            }],
+            "changed": [{
+                "id": "pn-aaaa1111",
+                "title": "Login crash",
+                "changes": [
+                    { "field": "priority", "from": "p2", "to": "p0" },
+                    { "field": "assignee", "from": null, "to": "bob" },
+                    { "field": "description", "from": "a", "to": "b" },
+                ],
+            }],
+            "deleted": [],
+        });
+        assert_eq!(
+            render_diff(&diff),
+            "sn-1111 → now\n\
+             created (1)\n  pn-bbbb2222  p2 [task] Tabs\tin title\n\
+             closed (1)\n  pn-cccc3333  p1 [bug] Old crash (fixed)\n\
+             changed (1)\n  pn-aaaa1111  Login crash\n    priority: p2 → p0\n\
+             \x20   assignee: - → bob\n    description changed\n"
+        );
+        let empty = serde_json::json!({
+            "from": "a", "to": "b", "created": [], "closed": [], "changed": [], "deleted": [],
+        });
+        assert_eq!(render_diff(&empty), "a → b\n(no changes)\n");
+    }
+
+    #[test]
+    fn ready_explanations_name_each_reason() {
+        let explanation = serde_json::json!({
+            "id": "pn-aaaa1111",
+            "title": "Login crash",
+            "status": "in_progress",
+            "priority": "p1",
+            "issue_type": "bug",
+            "ready": false,
+            "reasons": [
+                { "reason": "claimed", "assignee": "alice" },
+                { "reason": "blocked_by", "ids": ["pn-1", "pn-2"] },
+                { "reason": "fix_in_progress", "ids": ["pn-3"] },
+                { "reason": "filtered", "filter": "spec", "wanted": "auth", "actual": null },
+                { "reason": "past_limit", "limit": 5 },
+            ],
+        });
+        assert_eq!(
+            render_ready_explanation(&explanation),
+            "pn-aaaa1111  p1 in_progress [bug] Login crash\n\
+             \x20 - claimed by alice\n\
+             \x20 - blocked by pn-1, pn-2\n\
+             \x20 - fix in progress: pn-3\n\
+             \x20 - filtered by --spec: wants auth, has none\n\
+             \x20 - past --limit 5\n"
+        );
+        let ready = serde_json::json!({ "id": "pn-bbbb2222", "ready": true, "reasons": [] });
+        assert_eq!(render_ready_explanation(&ready), "pn-bbbb2222 is ready\n");
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::RateLimit;
+
+/// Buckets kept before full ones, which carry no state, are dropped.
+const MAX_BUCKETS: usize = 1024;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn check(&self, key: &str, limit: RateLimit, now: Instant) -> Result<(), Duration> {
+        let burst = f64::from(limit.burst);
+        let rate = f64::from(limit.requests_per_second);
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|_, b| {
+                b.tokens + now.saturating_duration_since(b.updated).as_secs_f64() * rate < burst
+            });
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_allow_a_burst_then_refill_per_actor() {
+        let limiter = RateLimiter::default();
+        let limit = RateLimit {
+            requests_per_second: 2,
+            burst: 3,
+        };
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check("alice", limit, start).is_ok());
+        }
+        let wait = limiter.check("alice", limit, start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+        assert!(limiter.check("bob", limit, start).is_ok());
+
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.check("alice", limit, later).is_ok());
+        assert!(limiter.check("alice", limit, later).is_err());
+
+        let idle = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(limiter.check("alice", limit, idle).is_ok());
+        }
+        assert!(limiter.check("alice", limit, idle).is_err());
+    }
+}
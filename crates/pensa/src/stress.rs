@@ -0,0 +1,295 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::Client;
+use crate::error::PensaError;
+use crate::types::{CreateIssueParams, IssueType, Priority};
+
+const OPS: [&str; 3] = ["create", "claim", "close"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpStats {
+    pub op: String,
+    pub count: usize,
+    pub ok: usize,
+    pub conflicts: usize,
+    pub errors: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressReport {
+    pub agents: usize,
+    pub ops: usize,
+    pub elapsed_ms: f64,
+    pub ops_per_sec: f64,
+    pub conflicts: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+    pub operations: Vec<OpStats>,
+    pub sample_errors: Vec<String>,
+    pub cleaned_up: usize,
+}
+
+enum Outcome {
+    Ok,
+    Conflict,
+    Error(String),
+}
+
+struct Sample {
+    op: usize,
+    latency: Duration,
+    outcome: Outcome,
+}
+
+/// Issues visible to every worker. Claim targets are drawn from `open` at
+/// random, so two workers regularly race for the same issue.
+#[derive(Default)]
+struct Shared {
+    open: Vec<String>,
+    created: Vec<String>,
+}
+
+pub fn run<F>(agents: usize, ops: usize, keep: bool, connect: F) -> StressReport
+where
+    F: Fn() -> Client + Sync,
+{
+    let agents = agents.max(1);
+    let shared = Mutex::new(Shared::default());
+    let started = Instant::now();
+
+    let samples: Vec<Sample> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..agents)
+            .map(|agent| {
+                let quota = ops / agents + usize::from(agent < ops % agents);
+                let shared = &shared;
+                let connect = &connect;
+                scope.spawn(move || worker(agent, quota, &connect(), shared))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("stress worker panicked"))
+            .collect()
+    });
+    let elapsed = started.elapsed();
+
+    let created = shared.into_inner().unwrap().created;
+    let cleaned_up = if keep {
+        0
+    } else {
+        let client = connect();
+        created
+            .iter()
+            .filter(|id| client.delete_issue(id, true).is_ok())
+            .count()
+    };
+
+    summarize(agents, &samples, elapsed, cleaned_up)
+}
+
+fn worker(agent: usize, quota: usize, client: &Client, shared: &Mutex<Shared>) -> Vec<Sample> {
+    let actor = format!("stress-{agent}");
+    let mut claimed: Vec<String> = Vec::new();
+    let mut samples = Vec::with_capacity(quota);
+
+    for i in 0..quota {
+        let mut op = i % OPS.len();
+        let target = match op {
+            1 => {
+                let guard = shared.lock().unwrap();
+                if guard.open.is_empty() {
+                    None
+                } else {
+                    Some(guard.open[random_index(guard.open.len())].clone())
+                }
+            }
+            2 => claimed.pop(),
+            _ => None,
+        };
+        if target.is_none() {
+            op = 0;
+        }
+
+        let start = Instant::now();
+        let result = match (op, &target) {
+            (1, Some(id)) => client
+                .update_issue(id, &serde_json::json!({ "claim": true }), None, &actor)
+                .map(|_| ()),
+            (2, Some(id)) => client
+                .close_issue(id, Some("stress"), None, false, &actor)
+                .map(|_| ()),
+            _ => client
+                .create_issue(&CreateIssueParams {
+                    title: format!("stress {agent}-{i}"),
+                    issue_type: IssueType::Chore,
+                    priority: Priority::P3,
+                    description: None,
+                    spec: None,
+                    fixes: None,
+                    assignee: None,
+                    deps: vec![],
+                    labels: Vec::new(),
+                    actor: actor.clone(),
+                })
+                .and_then(|v| {
+                    v["id"]
+                        .as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| PensaError::Internal("create returned no id".into()))
+                })
+                .map(|id| {
+                    let mut guard = shared.lock().unwrap();
+                    guard.open.push(id.clone());
+                    guard.created.push(id);
+                }),
+        };
+        let latency = start.elapsed();
+
+        let outcome = match result {
+            Ok(()) => {
+                if let (1, Some(id)) = (op, target) {
+                    shared.lock().unwrap().open.retain(|o| *o != id);
+                    claimed.push(id);
+                }
+                Outcome::Ok
+            }
+            Err(PensaError::AlreadyClaimed { .. } | PensaError::InvalidStatusTransition { .. }) => {
+                Outcome::Conflict
+            }
+            Err(e) => Outcome::Error(e.to_string()),
+        };
+        samples.push(Sample {
+            op,
+            latency,
+            outcome,
+        });
+    }
+    samples
+}
+
+fn summarize(
+    agents: usize,
+    samples: &[Sample],
+    elapsed: Duration,
+    cleaned_up: usize,
+) -> StressReport {
+    let mut sample_errors: Vec<String> = Vec::new();
+    let operations: Vec<OpStats> = OPS
+        .iter()
+        .enumerate()
+        .map(|(op, name)| {
+            let mut latencies = Vec::new();
+            let (mut ok, mut conflicts, mut errors) = (0, 0, 0);
+            for s in samples.iter().filter(|s| s.op == op) {
+                latencies.push(s.latency.as_secs_f64() * 1000.0);
+                match &s.outcome {
+                    Outcome::Ok => ok += 1,
+                    Outcome::Conflict => conflicts += 1,
+                    Outcome::Error(msg) => {
+                        errors += 1;
+                        if sample_errors.len() < 5 && !sample_errors.contains(msg) {
+                            sample_errors.push(msg.clone());
+                        }
+                    }
+                }
+            }
+            latencies.sort_by(f64::total_cmp);
+            OpStats {
+                op: name.to_string(),
+                count: latencies.len(),
+                ok,
+                conflicts,
+                errors,
+                p50_ms: percentile(&latencies, 50.0),
+                p90_ms: percentile(&latencies, 90.0),
+                p99_ms: percentile(&latencies, 99.0),
+                max_ms: latencies.last().copied().unwrap_or(0.0),
+            }
+        })
+        .collect();
+
+    let conflicts = operations.iter().map(|o| o.conflicts).sum();
+    let errors = operations.iter().map(|o| o.errors).sum();
+    let secs = elapsed.as_secs_f64();
+    StressReport {
+        agents,
+        ops: samples.len(),
+        elapsed_ms: secs * 1000.0,
+        ops_per_sec: if secs > 0.0 {
+            samples.len() as f64 / secs
+        } else {
+            0.0
+        },
+        conflicts,
+        errors,
+        error_rate: if samples.is_empty() {
+            0.0
+        } else {
+            errors as f64 / samples.len() as f64
+        },
+        operations,
+        sample_errors,
+        cleaned_up,
+    }
+}
+
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (pct / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn random_index(len: usize) -> usize {
+    (RandomState::new().build_hasher().finish() % len as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let values: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&values, 50.0), 50.0);
+        assert_eq!(percentile(&values, 99.0), 99.0);
+        assert_eq!(percentile(&values, 100.0), 100.0);
+        assert_eq!(percentile(&[7.0], 50.0), 7.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn summary_separates_conflicts_from_errors() {
+        let sample = |op, ms, outcome| Sample {
+            op,
+            latency: Duration::from_millis(ms),
+            outcome,
+        };
+        let samples = vec![
+            sample(0, 2, Outcome::Ok),
+            sample(1, 4, Outcome::Ok),
+            sample(1, 6, Outcome::Conflict),
+            sample(2, 8, Outcome::Error("boom".into())),
+        ];
+        let report = summarize(2, &samples, Duration::from_secs(1), 0);
+        assert_eq!(report.ops, 4);
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.error_rate, 0.25);
+        assert_eq!(report.sample_errors, vec!["boom".to_string()]);
+        let claim = &report.operations[1];
+        assert_eq!((claim.count, claim.ok, claim.conflicts), (2, 1, 1));
+        assert_eq!(claim.max_ms, 6.0);
+    }
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+use crate::types::{Event, Issue, Webhook};
+
+pub const SIGNATURE_HEADER: &str = "x-pensa-signature";
+pub const EVENT_HEADER: &str = "x-pensa-event";
+pub const DELIVERY_HEADER: &str = "x-pensa-delivery";
+
+pub const MAX_ATTEMPTS: u32 = 5;
+
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    const BLOCK: usize = 64;
+    let mut key = [0u8; BLOCK];
+    if secret.len() > BLOCK {
+        key[..32].copy_from_slice(&Sha256::digest(secret));
+    } else {
+        key[..secret.len()].copy_from_slice(secret);
+    }
+    let pad = |byte: u8| key.map(|k| k ^ byte);
+    let inner = Sha256::new()
+        .chain_update(pad(0x36))
+        .chain_update(body)
+        .finalize();
+    let outer = Sha256::new()
+        .chain_update(pad(0x5c))
+        .chain_update(inner)
+        .finalize();
+    outer.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn payload(webhook: &Webhook, event: &Event, issue: &Issue) -> serde_json::Value {
+    serde_json::json!({
+        "webhook": webhook.id,
+        "event": event,
+        "issue": issue,
+    })
+}
+
+/// POSTs one event, retrying on errors and non-2xx responses with the wait
+/// doubling from a second. Returns the last failure once the attempts run out.
+pub async fn deliver(
+    client: &reqwest::Client,
+    webhook: &Webhook,
+    event: &Event,
+    issue: &Issue,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(&payload(webhook, event, issue)).unwrap();
+    let signature = format!(
+        "sha256={}",
+        sign(
+            webhook.secret.as_deref().unwrap_or_default().as_bytes(),
+            &body
+        )
+    );
+    let mut wait = FIRST_RETRY;
+    let mut attempt = 1;
+    loop {
+        let result = client
+            .post(&webhook.url)
+            .header("content-type", "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .header(EVENT_HEADER, &event.event_type)
+            .header(DELIVERY_HEADER, event.id.to_string())
+            .body(body.clone())
+            .send()
+            .await;
+        let failure = match result {
+            Ok(resp) if resp.status().is_success() => return Ok(()),
+            Ok(resp) => format!("{} responded {}", webhook.url, resp.status()),
+            Err(e) => format!("{}: {e}", webhook.url),
+        };
+        if attempt == MAX_ATTEMPTS {
+            return Err(failure);
+        }
+        tracing::debug!(webhook = %webhook.id, attempt, "webhook delivery failed: {failure}");
+        tokio::time::sleep(wait).await;
+        wait *= 2;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_match_rfc_4231() {
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            sign(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+}
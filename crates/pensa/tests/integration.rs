@@ -531,6 +531,140 @@ fn no_spec_bypasses_forma_validation() {
     );
 }
 
+#[test]
+fn spec_file_validation_rejects_or_warns_per_config() {
+    let d = PensaOnlyDaemon::start();
+    let pensa_dir = d.dir().join(".pensa");
+    std::fs::create_dir_all(&pensa_dir).unwrap();
+    std::fs::create_dir_all(d.dir().join("specs")).unwrap();
+    std::fs::write(d.dir().join("specs/auth.md"), "# Auth").unwrap();
+
+    let create = |spec: &str| {
+        d.client
+            .post(d.url("/issues"))
+            .json(&serde_json::json!({
+                "title": "Implement login",
+                "issue_type": "task",
+                "spec": spec
+            }))
+            .send()
+            .unwrap()
+    };
+
+    std::fs::write(
+        pensa_dir.join("config.toml"),
+        "[specs]\nvalidate = \"reject\"\n",
+    )
+    .unwrap();
+    let resp = create("auth");
+    assert_eq!(resp.status(), 201, "existing spec file should be accepted");
+    let body: Value = resp.json().unwrap();
+    assert!(body.get("warnings").is_none());
+    let resp = create("billing");
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().unwrap();
+    assert_eq!(body["code"], "spec_not_found");
+
+    std::fs::write(
+        pensa_dir.join("config.toml"),
+        "[specs]\nvalidate = \"warn\"\n",
+    )
+    .unwrap();
+    let resp = create("billing");
+    assert_eq!(resp.status(), 201);
+    let body: Value = resp.json().unwrap();
+    let id = body["id"].as_str().unwrap().to_string();
+    assert!(
+        body["warnings"][0]
+            .as_str()
+            .unwrap()
+            .contains("specs/billing.md")
+    );
+
+    let resp = d.client.post(d.url("/doctor")).send().unwrap();
+    assert_eq!(resp.status(), 200);
+    let report: Value = resp.json().unwrap();
+    let missing: Vec<&Value> = report["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|f| f["check"] == "missing_spec")
+        .collect();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0]["issue_id"], id.as_str());
+}
+
+#[test]
+fn shutdown_exports_after_in_flight_writes() {
+    let d = PensaOnlyDaemon::start();
+    let resp = d
+        .client
+        .post(d.url("/issues"))
+        .json(&serde_json::json!({"title": "Written before shutdown", "issue_type": "task"}))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+    let id = resp.json::<Value>().unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(!d.dir().join(".pensa/issues.jsonl").exists());
+    let wal = d.dir().join("pensa-data/db.sqlite-wal");
+    assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+
+    d.client.post(d.url("/shutdown")).send().unwrap();
+    let port_file = d.dir().join(".pensa/daemon.port");
+    for _ in 0..50 {
+        if !port_file.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(!port_file.exists(), "daemon did not shut down");
+    let exported = std::fs::read_to_string(d.dir().join(".pensa/issues.jsonl")).unwrap();
+    assert!(exported.contains(&id), "{exported}");
+    assert_eq!(std::fs::metadata(&wal).map(|m| m.len()).unwrap_or(0), 0);
+}
+
+#[test]
+fn config_set_applies_default_priority_and_auto_export() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |args: &[&str]| run_pn(Command::new(pn_bin()).current_dir(d.dir()).args(args));
+
+    for (key, value) in [("issues.default_priority", "p1"), ("export.auto", "true")] {
+        let out = pn(&["config", "set", key, value]);
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    let out = pn(&["config", "get", "issues.default_priority"]);
+    assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "p1");
+    assert!(
+        !pn(&["config", "set", "issues.default_priority", "urgent"])
+            .status
+            .success()
+    );
+    assert!(
+        !pn(&["config", "get", "workflow.require_claim"])
+            .status
+            .success()
+    );
+
+    let resp = d
+        .client
+        .post(d.url("/issues"))
+        .json(&serde_json::json!({"title": "Default priority", "issue_type": "task"}))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+    let body: Value = resp.json().unwrap();
+    assert_eq!(body["priority"], "p1");
+    let exported = std::fs::read_to_string(d.dir().join(".pensa/issues.jsonl")).unwrap();
+    assert!(exported.contains(body["id"].as_str().unwrap()));
+}
+
 #[test]
 fn forma_spec_validation_on_update() {
     let d = DualDaemon::start();
@@ -1343,6 +1477,8 @@ fn single_fix_auto_close() {
         .send()
         .unwrap();
     assert_eq!(resp.status(), 200, "closing fix task should succeed");
+    let closed: Value = resp.json().unwrap();
+    assert_eq!(closed["auto_closed"], serde_json::json!([bug_id]));
 
     // Verify the bug was auto-closed with reason "fixed"
     let resp = d
@@ -1366,6 +1502,403 @@ fn single_fix_auto_close() {
     );
 }
 
+#[test]
+fn reopen_cascade_reopens_the_fix_task() {
+    let d = PensaOnlyDaemon::start();
+    let create = |body: Value| -> String {
+        let resp = d.client.post(d.url("/issues")).json(&body).send().unwrap();
+        assert_eq!(resp.status(), 201);
+        resp.json::<Value>().unwrap()["id"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+    let bug_id = create(serde_json::json!({
+        "title": "Widget crashes on nil input",
+        "issue_type": "bug",
+        "actor": "tester"
+    }));
+    let fix_id = create(serde_json::json!({
+        "title": "Handle nil input in widget",
+        "issue_type": "task",
+        "fixes": bug_id,
+        "actor": "tester"
+    }));
+    let resp = d
+        .client
+        .post(d.url(&format!("/issues/{fix_id}/close")))
+        .json(&serde_json::json!({ "actor": "tester" }))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let resp = d
+        .client
+        .post(d.url(&format!("/issues/{bug_id}/reopen")))
+        .json(&serde_json::json!({
+            "reason": "still crashes",
+            "cascade": true,
+            "actor": "qa"
+        }))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.json::<Value>().unwrap()["status"], "open");
+
+    let fix: Value = d
+        .client
+        .get(d.url(&format!("/issues/{fix_id}")))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(fix["status"], "open", "cascade should reopen the fix task");
+    let events: Value = d
+        .client
+        .get(d.url(&format!("/issues/{fix_id}/history")))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    let reopened = events
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["event_type"] == "reopened")
+        .unwrap()
+        .clone();
+    assert_eq!(
+        reopened["detail"],
+        format!("{bug_id} reopened: still crashes")
+    );
+}
+
+/// Accepts HTTP requests on a local port, failing the first `failures` with
+/// a 500, and sends each request's headers and body down the channel.
+fn webhook_receiver(failures: usize) -> (u16, std::sync::mpsc::Receiver<(String, Vec<u8>)>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for (n, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line.to_ascii_lowercase());
+            }
+            let len = head
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .map_or(0, |v| v.trim().parse().unwrap());
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).unwrap();
+            let status = if n < failures {
+                "500 Internal Server Error"
+            } else {
+                "200 OK"
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+            )
+            .unwrap();
+            if tx.send((head, body)).is_err() {
+                break;
+            }
+        }
+    });
+    (port, rx)
+}
+
+#[test]
+fn webhooks_post_signed_payloads_and_retry_failures() {
+    let d = PensaOnlyDaemon::start();
+    let (port, requests) = webhook_receiver(1);
+
+    let resp = d
+        .client
+        .post(d.url("/webhooks"))
+        .json(&serde_json::json!({
+            "url": format!("http://127.0.0.1:{port}/hook"),
+            "events": ["closed"]
+        }))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+    let hook: Value = resp.json().unwrap();
+    let secret = hook["secret"].as_str().unwrap().to_string();
+    let listed: Value = d
+        .client
+        .get(d.url("/webhooks"))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(listed[0]["id"], hook["id"]);
+    assert!(listed[0].get("secret").is_none(), "list hides secrets");
+
+    let resp = d
+        .client
+        .post(d.url("/issues"))
+        .json(&serde_json::json!({ "title": "Ship it", "issue_type": "task", "actor": "tester" }))
+        .send()
+        .unwrap();
+    let id = resp.json::<Value>().unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let resp = d
+        .client
+        .post(d.url(&format!("/issues/{id}/close")))
+        .json(&serde_json::json!({ "actor": "tester" }))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let timeout = Duration::from_secs(15);
+    let (_, failed) = requests.recv_timeout(timeout).expect("first delivery");
+    let (head, body) = requests.recv_timeout(timeout).expect("retried delivery");
+    assert_eq!(body, failed, "a retry resends the same payload");
+    assert!(head.contains("x-pensa-event: closed"), "{head}");
+    let signature = format!("sha256={}", pensa::webhooks::sign(secret.as_bytes(), &body));
+    assert!(
+        head.contains(&format!("x-pensa-signature: {signature}")),
+        "{head}"
+    );
+    let payload: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(payload["event"]["event_type"], "closed");
+    assert_eq!(payload["issue"]["id"], id.as_str());
+    assert_eq!(payload["issue"]["status"], "closed");
+    assert!(
+        requests.recv_timeout(Duration::from_secs(2)).is_err(),
+        "the created event is filtered out and the close is not sent again"
+    );
+
+    let resp = d
+        .client
+        .delete(d.url(&format!("/webhooks/{}", hook["id"].as_str().unwrap())))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 204);
+    let listed: Value = d
+        .client
+        .get(d.url("/webhooks"))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert!(listed.as_array().unwrap().is_empty());
+}
+
+#[test]
+fn snapshot_and_diff_via_cli() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |args: &[&str]| {
+        let out = run_pn(Command::new(pn_bin()).current_dir(d.dir()).args(args));
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        serde_json::from_slice::<Value>(&out.stdout).unwrap()
+    };
+
+    let old = pn(&["create", "Old task", "-t", "task", "--json"]);
+    let snap = pn(&["snapshot", "create", "loop-start", "--json"]);
+    assert_eq!(snap["label"], "loop-start");
+    assert_eq!(snap["issue_count"], 1);
+
+    let new = pn(&["create", "New task", "-t", "task", "--json"]);
+    pn(&["close", old["id"].as_str().unwrap(), "--json"]);
+
+    let diff = pn(&["diff", "loop-start", "--json"]);
+    assert_eq!(diff["from"], snap["id"]);
+    assert_eq!(diff["to"], "now");
+    assert_eq!(diff["created"][0]["id"], new["id"]);
+    assert_eq!(diff["closed"][0]["id"], old["id"]);
+
+    let diff = pn(&["diff", "--since", "2999-01-01", "--json"]);
+    assert_eq!(diff["from"], snap["id"]);
+    assert_eq!(
+        pn(&["snapshot", "list", "--json"])
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let out =
+        run_pn(
+            Command::new(pn_bin())
+                .current_dir(d.dir())
+                .args(["diff", "--since", "2000-01-01"]),
+        );
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("pn snapshot create"));
+}
+
+#[test]
+fn health_reports_uptime_schema_and_row_counts() {
+    let d = PensaOnlyDaemon::start();
+    let before: Value = d
+        .client
+        .get(d.url("/health"))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(before["schema_version"], 4);
+    assert_eq!(before["read_only"], false);
+    assert_eq!(before["tables"]["issues"], 0);
+    assert!(before["db_bytes"].as_u64().unwrap() > 0);
+    assert!(before["uptime_secs"].is_u64());
+
+    let out = run_pn(
+        Command::new(pn_bin())
+            .current_dir(d.dir())
+            .args(["create", "Task A", "-t", "task"]),
+    );
+    assert!(out.status.success());
+    let out = run_pn(Command::new(pn_bin()).current_dir(d.dir()).args([
+        "daemon",
+        "status",
+        "--verbose",
+        "--json",
+    ]));
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let status: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(status["health"]["tables"]["issues"], 1);
+    assert_eq!(status["health"]["tables"]["events"], 1);
+    assert!(status["health"]["wal_bytes"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn dry_runs_report_rows_without_changing_them() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |args: &[&str]| {
+        let out = run_pn(Command::new(pn_bin()).current_dir(d.dir()).args(args));
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        serde_json::from_slice::<Value>(&out.stdout).unwrap()
+    };
+
+    let a = pn(&["create", "Task A", "-t", "task", "--json"]);
+    let a_id = a["id"].as_str().unwrap();
+    pn(&["update", a_id, "--claim", "--json"]);
+
+    let preview = pn(&["delete", a_id, "--dry-run", "--json"]);
+    let changes = preview["changes"].as_array().unwrap();
+    assert!(changes.contains(&serde_json::json!({
+        "table": "issues", "action": "delete", "key": a_id,
+    })));
+    assert!(changes.iter().all(|c| c["action"] == "delete"));
+    assert_eq!(pn(&["show", a_id, "--json"])["status"], "in_progress");
+
+    let report = pn(&["doctor", "--fix", "--dry-run", "--json"]);
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(
+        report["changes"],
+        serde_json::json!([{ "table": "issues", "action": "update", "key": a_id }])
+    );
+    assert_eq!(pn(&["show", a_id, "--json"])["status"], "in_progress");
+
+    pn(&["export", "--json"]);
+    pn(&["create", "Task B", "-t", "task", "--json"]);
+    let preview = pn(&["import", "--dry-run", "--json"]);
+    assert!(
+        preview["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|c| { c["table"] == "issues" && c["action"] == "delete" && c["key"] != a_id })
+    );
+    assert_eq!(pn(&["list", "--json"]).as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn rate_limit_refuses_a_runaway_actor_with_429() {
+    let d = PensaOnlyDaemon::start();
+    std::fs::write(
+        d.dir().join(".pensa/config.toml"),
+        "[rate_limit]\nrequests_per_second = 1\nburst = 2\n",
+    )
+    .unwrap();
+    let list = |actor: &str| {
+        d.client
+            .get(d.url("/issues"))
+            .header("x-pensa-actor", actor)
+            .send()
+            .unwrap()
+    };
+
+    assert_eq!(list("runaway").status(), 200);
+    assert_eq!(list("runaway").status(), 200);
+    let resp = list("runaway");
+    assert_eq!(resp.status(), 429);
+    assert_eq!(resp.headers()["retry-after"], "1");
+    let body: Value = resp.json().unwrap();
+    assert_eq!(body["code"], "rate_limited");
+    assert_eq!(body["details"]["retry_after"], 1);
+
+    assert_eq!(list("someone-else").status(), 200);
+    let resp = d
+        .client
+        .get(d.url("/read-only"))
+        .header("x-pensa-actor", "runaway")
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    // The daemon caches the config but notices when the file changes.
+    std::fs::write(d.dir().join(".pensa/config.toml"), "").unwrap();
+    assert_eq!(list("runaway").status(), 200);
+}
+
+#[test]
+fn ready_explain_lists_excluded_issues() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |args: &[&str]| {
+        let out = run_pn(Command::new(pn_bin()).current_dir(d.dir()).args(args));
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        out.stdout
+    };
+    let json = |args: &[&str]| serde_json::from_slice::<Value>(&pn(args)).unwrap();
+
+    let a = json(&["create", "Task A", "-t", "task", "--json"]);
+    let a_id = a["id"].as_str().unwrap();
+    let b = json(&["create", "Task B", "-t", "task", "--dep", a_id, "--json"]);
+    let b_id = b["id"].as_str().unwrap();
+
+    let excluded = json(&["ready", "--explain", "--json"]);
+    let excluded = excluded.as_array().unwrap();
+    assert_eq!(excluded.len(), 1);
+    assert_eq!(excluded[0]["id"], b_id);
+    assert_eq!(excluded[0]["reasons"][0]["reason"], "blocked_by");
+
+    let human = String::from_utf8(pn(&["explain", b_id])).unwrap();
+    assert!(human.contains(&format!("blocked by {a_id}")), "{human}");
+    let human = String::from_utf8(pn(&["explain", a_id])).unwrap();
+    assert_eq!(human, format!("{a_id} is ready\n"));
+}
+
 #[test]
 fn claim_semantics_full_flow() {
     let d = PensaOnlyDaemon::start();
@@ -2564,6 +3097,35 @@ fn daemon_status_shows_project_directory() {
     );
 }
 
+#[test]
+fn daemon_status_reports_versions_and_mismatch() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |api_version: &str, args: &[&str]| {
+        run_pn(
+            Command::new(pn_bin())
+                .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+                .env_remove("PN_DAEMON_HOST")
+                .env("PN_API_VERSION", api_version)
+                .current_dir(d.dir())
+                .args(args),
+        )
+    };
+
+    let output = pn("1", &["daemon", "status", "--json"]);
+    assert!(output.status.success());
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(status["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(status["api_version"], 1);
+    assert_eq!(status["min_api_version"], 1);
+    assert!(status["mismatch"].is_null(), "{status}");
+
+    let output = pn("7", &["daemon", "status", "--json"]);
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mismatch = status["mismatch"].as_str().unwrap();
+    assert!(mismatch.contains("serves API v1, but pn"), "{mismatch}");
+    assert!(mismatch.contains("needs v7"), "{mismatch}");
+}
+
 #[test]
 fn refs_cascade_on_force_delete() {
     let d = PensaOnlyDaemon::start();
@@ -2960,3 +3522,861 @@ fn watchdog_tolerates_transient_directory_removal() {
     // Clean up.
     let _ = client.post(format!("{base}/shutdown")).send();
 }
+
+#[test]
+fn scan_commits_links_mentions_and_closes_on_fixes_trailer() {
+    let d = PensaOnlyDaemon::start();
+
+    let mut ids = Vec::new();
+    for title in ["Mentioned task", "Fixed bug"] {
+        let resp = d
+            .client
+            .post(d.url("/issues"))
+            .json(&serde_json::json!({"title": title, "issue_type": "task"}))
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 201);
+        let issue: Value = resp.json().unwrap();
+        ids.push(issue["id"].as_str().unwrap().to_string());
+    }
+
+    let git = |args: &[&str]| {
+        let out = Command::new("git")
+            .args(args)
+            .current_dir(d.dir())
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "git {args:?} failed");
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "test@test.com"]);
+    git(&["config", "user.name", "Test"]);
+    git(&["commit", "-q", "--allow-empty", "-m", "initial"]);
+    let message = format!(
+        "Touch login flow for {}\n\nFixes: {}, pn-00000000",
+        ids[0], ids[1]
+    );
+    git(&["commit", "-q", "--allow-empty", "-m", &message]);
+
+    let output = run_pn(
+        Command::new(pn_bin())
+            .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+            .env_remove("PN_DAEMON_HOST")
+            .current_dir(d.dir())
+            .args(["scan-commits", "--since", "HEAD~1", "--close", "--json"]),
+    );
+    assert!(
+        output.status.success(),
+        "scan-commits failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["scanned"], 1);
+    assert_eq!(result["linked"].as_array().unwrap().len(), 2);
+    assert_eq!(result["closed"], serde_json::json!([ids[1]]));
+    assert_eq!(result["unknown"], serde_json::json!(["pn-00000000"]));
+
+    let mentioned: Value = d
+        .client
+        .get(d.url(&format!("/issues/{}", ids[0])))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(mentioned["status"], "open");
+    let commits = mentioned["commits"].as_array().unwrap();
+    assert_eq!(commits.len(), 1);
+    assert_eq!(
+        commits[0]["summary"],
+        format!("Touch login flow for {}", ids[0])
+    );
+
+    let fixed: Value = d
+        .client
+        .get(d.url(&format!("/issues/{}", ids[1])))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(fixed["status"], "closed");
+    assert!(
+        fixed["close_reason"]
+            .as_str()
+            .unwrap()
+            .starts_with("fixed in ")
+    );
+}
+
+#[test]
+fn hooks_append_claimed_issue_and_reject_unknown_refs() {
+    let d = PensaOnlyDaemon::start();
+
+    let resp = d
+        .client
+        .post(d.url("/issues"))
+        .json(&serde_json::json!({"title": "Hooked task", "issue_type": "task"}))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+    let issue: Value = resp.json().unwrap();
+    let id = issue["id"].as_str().unwrap().to_string();
+    let resp = d
+        .client
+        .patch(d.url(&format!("/issues/{id}")))
+        .json(&serde_json::json!({"claim": true, "actor": "alice"}))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let bin_dir = std::path::Path::new(&pn_bin())
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(d.dir())
+            .env("PATH", &path)
+            .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+            .env_remove("PN_DAEMON_HOST")
+            .env("PN_ACTOR", "alice")
+            .output()
+            .unwrap()
+    };
+    assert!(git(&["init", "-q"]).status.success());
+    assert!(
+        git(&["config", "user.email", "test@test.com"])
+            .status
+            .success()
+    );
+    assert!(git(&["config", "user.name", "Test"]).status.success());
+
+    let output = run_pn(
+        Command::new(pn_bin())
+            .current_dir(d.dir())
+            .args(["hooks", "install"]),
+    );
+    assert!(
+        output.status.success(),
+        "hooks install failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let out = git(&["commit", "-q", "--allow-empty", "-m", "Do the work"]);
+    assert!(
+        out.status.success(),
+        "commit failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let log = git(&["log", "-1", "--format=%B"]);
+    let message = String::from_utf8_lossy(&log.stdout);
+    assert!(
+        message.contains(&format!("Refs: {id}")),
+        "claimed issue should be referenced, got: {message}"
+    );
+
+    let out = git(&["commit", "-q", "--allow-empty", "-m", "Fix pn-00000000"]);
+    assert!(
+        !out.status.success(),
+        "unknown reference should be rejected"
+    );
+    assert!(String::from_utf8_lossy(&out.stderr).contains("pn-00000000"));
+}
+
+#[test]
+fn json_mode_errors_go_to_stdout_with_details_and_exit_codes() {
+    let d = PensaOnlyDaemon::start();
+
+    let resp = d
+        .client
+        .post(d.url("/issues"))
+        .json(&serde_json::json!({"title": "Contested task", "issue_type": "task"}))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+    let issue: Value = resp.json().unwrap();
+    let id = issue["id"].as_str().unwrap().to_string();
+
+    let pn = |actor: &str, args: &[&str]| {
+        run_pn(
+            Command::new(pn_bin())
+                .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+                .env_remove("PN_DAEMON_HOST")
+                .env("PN_ACTOR", actor)
+                .current_dir(d.dir())
+                .args(args),
+        )
+    };
+
+    let output = pn("alice", &["update", &id, "--claim", "--json"]);
+    assert!(output.status.success());
+
+    let output = pn("bob", &["update", &id, "--claim", "--json"]);
+    assert_eq!(output.status.code(), Some(4));
+    let err: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err["code"], "already_claimed");
+    assert_eq!(err["details"]["id"], id.as_str());
+    assert_eq!(err["details"]["holder"], "alice");
+
+    let output = pn("alice", &["close", &id, "--json"]);
+    assert!(output.status.success());
+    let output = pn("alice", &["close", &id, "--json"]);
+    assert_eq!(output.status.code(), Some(6));
+    let err: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err["code"], "invalid_status_transition");
+    assert_eq!(err["details"]["from"], "closed");
+    assert_eq!(err["details"]["allowed"], serde_json::json!(["open"]));
+
+    let output = pn("alice", &["show", "pn-00000000", "--json"]);
+    assert_eq!(output.status.code(), Some(3));
+    let err: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err["code"], "not_found");
+}
+
+#[test]
+fn api_version_header_is_negotiated() {
+    let d = PensaOnlyDaemon::start();
+
+    let resp = d.client.get(d.url("/issues")).send().unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["x-pensa-api-version"], "1");
+
+    let resp = d
+        .client
+        .get(d.url("/issues"))
+        .header("X-Pensa-Api-Version", "1")
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["x-pensa-api-version"], "1");
+
+    let resp = d
+        .client
+        .get(d.url("/issues"))
+        .header("X-Pensa-Api-Version", "99")
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().unwrap();
+    assert_eq!(body["code"], "unsupported_api_version");
+    assert_eq!(body["details"]["requested"], "99");
+
+    let output = run_pn(
+        Command::new(pn_bin())
+            .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+            .env_remove("PN_DAEMON_HOST")
+            .current_dir(d.dir())
+            .args(["list", "--api-version", "99", "--json"]),
+    );
+    assert_eq!(output.status.code(), Some(11));
+    let err: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err["code"], "unsupported_api_version");
+}
+
+#[test]
+fn read_only_mode_rejects_writes_until_toggled_off() {
+    let d = PensaOnlyDaemon::start();
+    let create = || {
+        d.client
+            .post(d.url("/issues"))
+            .json(&serde_json::json!({"title": "Frozen", "issue_type": "task"}))
+            .send()
+            .unwrap()
+    };
+    assert_eq!(create().status(), 201);
+
+    let output = run_pn(
+        Command::new(pn_bin())
+            .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+            .env_remove("PN_DAEMON_HOST")
+            .current_dir(d.dir())
+            .args(["daemon", "read-only", "on", "--json"]),
+    );
+    assert!(output.status.success());
+    let state: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(state["read_only"], true);
+
+    let resp = create();
+    assert_eq!(resp.status(), 403);
+    let body: Value = resp.json().unwrap();
+    assert_eq!(body["code"], "read_only");
+
+    let resp = d.client.get(d.url("/issues")).send().unwrap();
+    assert_eq!(resp.status(), 200);
+    let resp = d.client.post(d.url("/export")).send().unwrap();
+    assert_eq!(resp.status(), 200);
+    let resp = d.client.post(d.url("/doctor?fix=true")).send().unwrap();
+    assert_eq!(resp.status(), 403);
+    let resp = d
+        .client
+        .post(d.url("/issues?dry_run=true"))
+        .json(&serde_json::json!({"title": "Sneaky", "issue_type": "task"}))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 403);
+    let resp = d
+        .client
+        .post(d.url("/doctor?fix=true&dry_run=true"))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let toggle = |token: &str| {
+        d.client
+            .post(d.url("/read-only"))
+            .header("x-pensa-token", token)
+            .json(&serde_json::json!({"enabled": false}))
+            .send()
+            .unwrap()
+    };
+    let resp = toggle("guess");
+    assert_eq!(resp.status(), 403);
+    let body: Value = resp.json().unwrap();
+    assert_eq!(body["code"], "invalid_token");
+    assert_eq!(create().status(), 403);
+
+    let token = std::fs::read_to_string(d.dir().join(".pensa/daemon.token")).unwrap();
+    assert_eq!(toggle(&token).status(), 200);
+    assert_eq!(create().status(), 201);
+}
+
+#[test]
+fn stress_reports_per_op_stats_and_cleans_up() {
+    let d = PensaOnlyDaemon::start();
+    let output = run_pn(
+        Command::new(pn_bin())
+            .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+            .env_remove("PN_DAEMON_HOST")
+            .current_dir(d.dir())
+            .args(["stress", "--agents", "4", "--ops", "60", "--json"]),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["agents"], 4);
+    assert_eq!(report["ops"], 60);
+    assert_eq!(report["errors"], 0);
+
+    let ops = report["operations"].as_array().unwrap();
+    let names: Vec<&str> = ops.iter().map(|o| o["op"].as_str().unwrap()).collect();
+    assert_eq!(names, ["create", "claim", "close"]);
+    let total: u64 = ops.iter().map(|o| o["count"].as_u64().unwrap()).sum();
+    assert_eq!(total, 60);
+    let created = ops[0]["ok"].as_u64().unwrap();
+    assert_eq!(report["cleaned_up"].as_u64().unwrap(), created);
+
+    let issues: Value = d
+        .client
+        .get(d.url("/issues"))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(issues.as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn workload_reports_per_assignee_counts() {
+    let d = PensaOnlyDaemon::start();
+    for title in ["one", "two"] {
+        let issue: Value = d
+            .client
+            .post(d.url("/issues"))
+            .json(&serde_json::json!({"title": title, "issue_type": "task", "priority": "p1"}))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        let id = issue["id"].as_str().unwrap();
+        d.client
+            .patch(d.url(&format!("/issues/{id}")))
+            .json(&serde_json::json!({"claim": true, "actor": "alice"}))
+            .send()
+            .unwrap();
+    }
+    d.client
+        .post(d.url("/issues"))
+        .json(&serde_json::json!({"title": "free", "issue_type": "task"}))
+        .send()
+        .unwrap();
+
+    let report: Value = d
+        .client
+        .get(d.url("/workload?max_active=1"))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(report["max_active"], 1);
+    assert_eq!(report["unassigned"], 1);
+    let alice = &report["assignees"][0];
+    assert_eq!(alice["assignee"], "alice");
+    assert_eq!(alice["in_progress"], 2);
+    assert_eq!(alice["urgent"], 2);
+    assert_eq!(alice["overloaded"], true);
+
+    let output = run_pn(
+        Command::new(pn_bin())
+            .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+            .env_remove("PN_DAEMON_HOST")
+            .current_dir(d.dir())
+            .args(["workload", "--json"]),
+    );
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["max_active"], 3);
+    assert_eq!(report["assignees"][0]["overloaded"], false);
+}
+
+#[test]
+fn close_resolution_is_settable_and_countable() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |args: &[&str]| {
+        run_pn(
+            Command::new(pn_bin())
+                .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+                .env_remove("PN_DAEMON_HOST")
+                .current_dir(d.dir())
+                .args(args),
+        )
+    };
+    let output = pn(&["create", "not doing this", "-t", "task", "--json"]);
+    let issue: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let id = issue["id"].as_str().unwrap();
+
+    let output = pn(&["close", id, "--resolution", "wont_fix", "--json"]);
+    assert!(output.status.success());
+    let closed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(closed["resolution"], "wont_fix");
+
+    let output = pn(&["close", id, "--resolution", "abandoned"]);
+    assert!(!output.status.success());
+
+    let output = pn(&["list", "--resolution", "wont_fix", "--json"]);
+    let listed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+
+    let output = pn(&["count", "--by-resolution", "--json"]);
+    let counts: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        counts["groups"],
+        serde_json::json!([{"key": "wont_fix", "count": 1}])
+    );
+}
+
+#[test]
+fn list_sorts_by_multiple_keys() {
+    let d = PensaOnlyDaemon::start();
+    for (title, priority) in [("b", "p2"), ("a", "p2"), ("c", "p0")] {
+        d.client
+            .post(d.url("/issues"))
+            .json(&serde_json::json!({"title": title, "issue_type": "task", "priority": priority}))
+            .send()
+            .unwrap();
+    }
+    let pn = |args: &[&str]| {
+        run_pn(
+            Command::new(pn_bin())
+                .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+                .env_remove("PN_DAEMON_HOST")
+                .current_dir(d.dir())
+                .args(args),
+        )
+    };
+
+    let output = pn(&["list", "--sort", "-priority,title", "--json"]);
+    assert!(output.status.success());
+    let issues: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let titles: Vec<&str> = issues
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, ["a", "b", "c"]);
+
+    let output = pn(&["list", "--sort", "priority,nope", "--json"]);
+    assert_eq!(output.status.code(), Some(2));
+    let err: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err["code"], "invalid_argument");
+}
+
+#[test]
+fn inbox_lists_watched_events_since_last_check() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |actor: &str, args: &[&str]| {
+        run_pn(
+            Command::new(pn_bin())
+                .env("PN_DAEMON", format!("http://localhost:{}", d.port))
+                .env_remove("PN_DAEMON_HOST")
+                .current_dir(d.dir())
+                .args(["--actor", actor, "--json"])
+                .args(args),
+        )
+    };
+    let output = pn("lead", &["create", "supervised", "-t", "task"]);
+    let issue: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let id = issue["id"].as_str().unwrap();
+
+    let output = pn("lead", &["watch", "add", id]);
+    assert!(output.status.success());
+    let watchers: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(watchers, serde_json::json!(["lead"]));
+
+    pn("agent-1", &["update", id, "--claim"]);
+    pn("agent-1", &["comment", "add", id, "halfway there"]);
+
+    let peek: Value = serde_json::from_slice(&pn("lead", &["inbox", "--peek"]).stdout).unwrap();
+    assert_eq!(peek["entries"].as_array().unwrap().len(), 2);
+
+    let inbox: Value = serde_json::from_slice(&pn("lead", &["inbox"]).stdout).unwrap();
+    let types: Vec<&str> = inbox["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["event_type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types, ["claimed", "commented"]);
+    assert_eq!(inbox["entries"][0]["title"], "supervised");
+
+    let again: Value = serde_json::from_slice(&pn("lead", &["inbox"]).stdout).unwrap();
+    assert!(again["entries"].as_array().unwrap().is_empty());
+
+    let watching: Value = serde_json::from_slice(&pn("lead", &["watch", "list"]).stdout).unwrap();
+    assert_eq!(watching[0]["id"], id);
+}
+
+#[test]
+fn patch_with_stale_if_match_conflicts_with_current_state() {
+    let d = PensaOnlyDaemon::start();
+    let resp = d
+        .client
+        .post(d.url("/issues"))
+        .json(&serde_json::json!({ "title": "Shared", "issue_type": "task", "actor": "tester" }))
+        .send()
+        .unwrap();
+    let id = resp.json::<Value>().unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let resp = d
+        .client
+        .get(d.url(&format!("/issues/{id}")))
+        .send()
+        .unwrap();
+    assert_eq!(resp.headers()["etag"], "\"1\"");
+
+    let patch = |if_match: &str, title: &str| {
+        d.client
+            .patch(d.url(&format!("/issues/{id}")))
+            .header("if-match", if_match)
+            .json(&serde_json::json!({ "title": title, "actor": "tester" }))
+            .send()
+            .unwrap()
+    };
+    let resp = patch("\"1\"", "First edit");
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.json::<Value>().unwrap()["rev"], 2);
+
+    let resp = patch("\"1\"", "Second edit");
+    assert_eq!(resp.status(), 409);
+    let err: Value = resp.json().unwrap();
+    assert_eq!(err["code"], "revision_mismatch");
+    assert_eq!(err["details"]["expected"], 1);
+    assert_eq!(err["details"]["current"]["title"], "First edit");
+    assert_eq!(err["details"]["current"]["rev"], 2);
+
+    assert_eq!(patch("2", "Second edit").status(), 200);
+    assert_eq!(patch("latest", "Third edit").status(), 400);
+}
+
+#[test]
+fn actor_aliases_resolve_assignee_at_me() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |args: &[&str]| {
+        let out = run_pn(Command::new(pn_bin()).current_dir(d.dir()).args(args));
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        serde_json::from_slice::<Value>(&out.stdout).unwrap()
+    };
+
+    let mine = pn(&["create", "Mine", "-t", "task", "-a", "wlorey", "--json"]);
+    let looped = pn(&[
+        "create",
+        "Looped",
+        "-t",
+        "task",
+        "-a",
+        "@me",
+        "--actor",
+        "sgf-build-auth-1",
+        "--json",
+    ]);
+    assert_eq!(looped["assignee"], "sgf-build-auth-1");
+    pn(&["create", "Theirs", "-t", "task", "-a", "bob", "--json"]);
+
+    let alias = pn(&["actor", "alias", "sgf-build-auth-1", "wlorey", "--json"]);
+    assert_eq!(alias["alias_of"], "wlorey");
+    let renamed = pn(&[
+        "actor",
+        "rename",
+        "sgf-build-auth-1",
+        "William Lorey",
+        "--json",
+    ]);
+    assert_eq!(renamed["name"], "wlorey");
+    assert_eq!(renamed["display_name"], "William Lorey");
+
+    let listed = pn(&["list", "-a", "@me", "--actor", "wlorey", "--json"]);
+    let mut ids: Vec<&str> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["id"].as_str().unwrap())
+        .collect();
+    ids.sort();
+    let mut expected = vec![mine["id"].as_str().unwrap(), looped["id"].as_str().unwrap()];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    let actors = pn(&["actor", "list", "--json"]);
+    let names: Vec<&str> = actors
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["name"].as_str().unwrap())
+        .collect();
+    let at = names.iter().position(|n| *n == "wlorey").unwrap();
+    assert_eq!(
+        names[at + 1],
+        "sgf-build-auth-1",
+        "aliases follow their identity"
+    );
+
+    pn(&["actor", "alias", "sgf-build-auth-1", "--remove", "--json"]);
+    let listed = pn(&["list", "-a", "wlorey", "--json"]);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn routing_rules_fill_in_spec_defaults_on_create() {
+    let d = PensaOnlyDaemon::start();
+    std::fs::write(
+        d.dir().join(".pensa/config.toml"),
+        "[specs]\nvalidate = \"off\"\n\n\
+         [routing.auth]\nassignee = \"wlorey\"\nlabels = [\"security\"]\npriority = \"p1\"\n",
+    )
+    .unwrap();
+    let create = |body: Value| -> Value {
+        let resp = d.client.post(d.url("/issues")).json(&body).send().unwrap();
+        assert_eq!(resp.status(), 201);
+        resp.json().unwrap()
+    };
+
+    let routed = create(serde_json::json!({
+        "title": "Lockout ignored",
+        "issue_type": "bug",
+        "spec": "auth"
+    }));
+    assert_eq!(routed["assignee"], "wlorey");
+    assert_eq!(routed["priority"], "p1");
+    let labels: Value = d
+        .client
+        .get(d.url(&format!(
+            "/issues/{}/labels",
+            routed["id"].as_str().unwrap()
+        )))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(labels, serde_json::json!(["security"]));
+
+    let explicit = create(serde_json::json!({
+        "title": "Session expiry",
+        "issue_type": "bug",
+        "spec": "auth",
+        "priority": "p0",
+        "assignee": "bob"
+    }));
+    assert_eq!(explicit["assignee"], "bob");
+    assert_eq!(explicit["priority"], "p0");
+
+    let other = create(serde_json::json!({
+        "title": "Invoice rounding",
+        "issue_type": "bug",
+        "spec": "billing"
+    }));
+    assert!(other.get("assignee").is_none());
+    assert_eq!(other["priority"], "p2");
+}
+
+#[test]
+fn seed_creates_a_plan_with_deps_or_nothing() {
+    let d = PensaOnlyDaemon::start();
+    std::fs::write(
+        d.dir().join(".pensa/config.toml"),
+        "[specs]\nvalidate = \"off\"\n",
+    )
+    .unwrap();
+    let seed = |plan: &str| {
+        std::fs::write(d.dir().join("plan.json"), plan).unwrap();
+        run_pn(Command::new(pn_bin()).current_dir(d.dir()).args([
+            "seed",
+            "plan.json",
+            "--spec",
+            "auth",
+            "--json",
+        ]))
+    };
+
+    let out = seed(
+        r#"[
+            {"key": "api", "title": "Login endpoint", "deps": ["schema"], "priority": "p1"},
+            {"key": "schema", "title": "Users table", "labels": ["db"]}
+        ]"#,
+    );
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let created: Value = serde_json::from_slice(&out.stdout).unwrap();
+    let issues = created["issues"].as_array().unwrap();
+    assert_eq!(issues.len(), 2);
+    assert_eq!(issues[0]["title"], "Login endpoint");
+    assert_eq!(issues[0]["priority"], "p1");
+    assert_eq!(issues[1]["issue_type"], "task");
+    assert_eq!(issues[1]["spec"], "auth");
+    let api_id = issues[0]["id"].as_str().unwrap();
+    let detail: Value = d
+        .client
+        .get(d.url(&format!("/issues/{api_id}")))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(detail["deps"][0]["id"], issues[1]["id"]);
+
+    let out = seed(r#"[{"key": "a", "title": "A"}, {"key": "b", "title": "B", "deps": ["nope"]}]"#);
+    assert!(!out.status.success());
+    let all: Value = d
+        .client
+        .get(d.url("/issues"))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(
+        all.as_array().unwrap().len(),
+        2,
+        "the failed plan created nothing"
+    );
+}
+
+#[test]
+fn list_endpoints_return_only_selected_fields() {
+    let d = PensaOnlyDaemon::start();
+    let resp = d
+        .client
+        .post(d.url("/issues"))
+        .json(&serde_json::json!({
+            "title": "Fix login redirect",
+            "issue_type": "bug",
+            "description": "A long description nobody asked for"
+        }))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+
+    for path in [
+        "/issues?fields=id,title,status",
+        "/issues/ready?fields=id,title,status",
+        "/issues/search?q=login&fields=id,title,status",
+    ] {
+        let issues: Value = d.client.get(d.url(path)).send().unwrap().json().unwrap();
+        let issue = issues[0].as_object().unwrap();
+        let keys: Vec<&str> = issue.keys().map(String::as_str).collect();
+        assert_eq!(keys.len(), 3, "{path}: {keys:?}");
+        assert_eq!(issue["title"], "Fix login redirect");
+        assert_eq!(issue["status"], "open");
+    }
+
+    let resp = d
+        .client
+        .get(d.url("/issues?fields=id,body"))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let out = run_pn(Command::new(pn_bin()).current_dir(d.dir()).args([
+        "list",
+        "--fields",
+        "id,priority",
+        "--json",
+    ]));
+    assert!(out.status.success());
+    let listed: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(listed[0]["id"].is_string());
+    assert!(listed[0].get("description").is_none());
+}
+
+#[test]
+fn show_at_reads_the_description_then() {
+    let d = PensaOnlyDaemon::start();
+    let pn = |args: &[&str]| {
+        let out = run_pn(Command::new(pn_bin()).current_dir(d.dir()).args(args));
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        serde_json::from_slice::<Value>(&out.stdout).unwrap()
+    };
+    let created = pn(&[
+        "create",
+        "Add login",
+        "-t",
+        "task",
+        "--description",
+        "Use sessions",
+        "--json",
+    ]);
+    let id = created["id"].as_str().unwrap();
+    pn(&["update", id, "--description", "Use OIDC", "--json"]);
+
+    let history: Value = d
+        .client
+        .get(d.url(&format!("/issues/{id}/history")))
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    let detail: Value = serde_json::from_str(history[0]["detail"].as_str().unwrap()).unwrap();
+    assert_eq!(detail["previous"]["description"], "Use sessions");
+
+    let text = pn(&["show", id, "--at", "2999-01-01", "--json"]);
+    assert_eq!(text["description"], "Use OIDC");
+    assert!(text["edited_by"].is_string());
+
+    let out = run_pn(Command::new(pn_bin()).current_dir(d.dir()).args([
+        "show",
+        id,
+        "--at",
+        "2000-01-01",
+        "--json",
+    ]));
+    assert!(!out.status.success(), "the issue did not exist yet");
+}
@@ -63,6 +63,7 @@ fn arb_create_params() -> impl Strategy<Value = CreateIssueParams> {
                 fixes: None,
                 assignee: None,
                 deps: vec![],
+                labels: Vec::new(),
                 actor: "prop-agent".into(),
             },
         )
@@ -93,6 +94,7 @@ fn make_issues(db: &Db, n: usize) -> Vec<String> {
                 fixes: None,
                 assignee: None,
                 deps: vec![],
+                labels: Vec::new(),
                 actor: "prop-agent".into(),
             })
             .unwrap()
@@ -256,8 +258,8 @@ proptest! {
             match op {
                 StateOp::Claim(idx, actor) => { let _ = db.claim_issue(&ids[*idx], actor); }
                 StateOp::Release(idx) => { let _ = db.release_issue(&ids[*idx], "prop-agent"); }
-                StateOp::Close(idx) => { let _ = db.close_issue(&ids[*idx], None, false, "prop-agent"); }
-                StateOp::Reopen(idx) => { let _ = db.reopen_issue(&ids[*idx], None, "prop-agent"); }
+                StateOp::Close(idx) => { let _ = db.close_issue(&ids[*idx], None, None, false, "prop-agent"); }
+                StateOp::Reopen(idx) => { let _ = db.reopen_issue(&ids[*idx], None, false, "prop-agent"); }
             }
         }
 
@@ -356,6 +358,7 @@ proptest! {
             fixes: None,
             assignee: None,
             deps: vec![],
+            labels: Vec::new(),
             actor: "prop-agent".into(),
         }).unwrap();
 
@@ -397,6 +400,7 @@ proptest! {
             fixes: None,
             assignee: None,
             deps: vec![],
+            labels: Vec::new(),
             actor: "prop-agent".into(),
         }).unwrap();
 
@@ -438,6 +442,7 @@ proptest! {
             fixes: None,
             assignee: None,
             deps: vec![],
+            labels: Vec::new(),
             actor: "prop-agent".into(),
         }).unwrap();
 
@@ -480,6 +485,7 @@ proptest! {
             fixes: None,
             assignee: None,
             deps: vec![],
+            labels: Vec::new(),
             actor: "prop-agent".into(),
         }).unwrap();
 
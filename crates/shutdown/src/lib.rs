@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::io::{self, Read as _};
 use std::os::unix::process::CommandExt;
 use std::process::{Child, Command, ExitStatus, Output};
@@ -12,6 +13,12 @@ use signal_hook::consts::{SIGINT, SIGTERM};
 
 const CHILD_GUARD_KILL_TIMEOUT: Duration = Duration::from_millis(200);
 
+const MAX_PENDING_KEYS: usize = 64;
+
+/// Ctrl-D as a byte, which is what it reads as once the terminal is out of
+/// canonical mode.
+const EOT: u8 = 0x04;
+
 pub struct ChildGuard {
     child: Option<Child>,
     pid: u32,
@@ -154,6 +161,23 @@ struct Inner {
     eof_count: AtomicU64,
     sigterm: Arc<AtomicBool>,
     stop: AtomicBool,
+    keys: Mutex<VecDeque<u8>>,
+}
+
+impl Inner {
+    fn record_input(&self, bytes: &[u8]) {
+        let mut keys = self.keys.lock().unwrap();
+        for &byte in bytes {
+            if byte == EOT {
+                self.eof_count.fetch_add(1, Ordering::SeqCst);
+            } else {
+                if keys.len() == MAX_PENDING_KEYS {
+                    keys.pop_front();
+                }
+                keys.push_back(byte);
+            }
+        }
+    }
 }
 
 pub struct ShutdownController {
@@ -175,6 +199,7 @@ impl ShutdownController {
             eof_count: AtomicU64::new(0),
             sigterm: Arc::new(AtomicBool::new(false)),
             stop: AtomicBool::new(false),
+            keys: Mutex::new(VecDeque::new()),
         });
 
         let mut signal_ids = Vec::new();
@@ -218,7 +243,7 @@ impl ShutdownController {
                                 Ok(0) => {
                                     inner_stdin.eof_count.fetch_add(1, Ordering::SeqCst);
                                 }
-                                Ok(_) => {}
+                                Ok(n) => inner_stdin.record_input(&buf[..n]),
                                 Err(_) => break,
                             }
                             drop(handle);
@@ -242,6 +267,16 @@ impl ShutdownController {
         })
     }
 
+    pub fn monitors_stdin(&self) -> bool {
+        self.stdin_thread.is_some()
+    }
+
+    /// The oldest byte read from stdin and not yet taken. Ctrl-D never shows
+    /// up here; it counts toward shutdown instead.
+    pub fn take_key(&self) -> Option<u8> {
+        self.inner.keys.lock().unwrap().pop_front()
+    }
+
     pub fn poll(&self) -> ShutdownStatus {
         if self.confirmed_shutdown.get() {
             return ShutdownStatus::Shutdown;
@@ -446,6 +481,24 @@ mod tests {
         assert!(config.monitor_stdin);
     }
 
+    #[test]
+    fn stdin_bytes_queue_as_keys_and_ctrl_d_counts_as_eof() {
+        let ctrl = controller_no_stdin();
+        assert!(!ctrl.monitors_stdin());
+        ctrl.inner.record_input(b"p\x04q");
+        assert_eq!(ctrl.take_key(), Some(b'p'));
+        assert_eq!(ctrl.take_key(), Some(b'q'));
+        assert_eq!(ctrl.take_key(), None);
+        assert_eq!(ctrl.poll(), ShutdownStatus::Pending);
+
+        ctrl.inner.record_input(&[b'x'; MAX_PENDING_KEYS + 1]);
+        let mut taken = 0;
+        while ctrl.take_key().is_some() {
+            taken += 1;
+        }
+        assert_eq!(taken, MAX_PENDING_KEYS);
+    }
+
     #[test]
     fn poll_returns_running_initially() {
         let ctrl = controller_no_stdin();
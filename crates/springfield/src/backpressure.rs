@@ -0,0 +1,172 @@
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Build,
+    Test,
+    Lint,
+    Format,
+    Other,
+}
+
+impl Kind {
+    pub const RUNNABLE: [Kind; 4] = [Self::Build, Self::Test, Self::Lint, Self::Format];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Build => "build",
+            Self::Test => "test",
+            Self::Lint => "lint",
+            Self::Format => "format",
+            Self::Other => "other",
+        }
+    }
+
+    fn of_label(label: &str) -> Self {
+        let first = label
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match first.as_str() {
+            "build" => Self::Build,
+            "test" | "tests" => Self::Test,
+            "lint" | "typecheck" => Self::Lint,
+            "format" => Self::Format,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl std::str::FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "build" => Ok(Self::Build),
+            "test" => Ok(Self::Test),
+            "lint" => Ok(Self::Lint),
+            "format" => Ok(Self::Format),
+            "other" => Ok(Self::Other),
+            _ => Err(format!(
+                "unknown backpressure kind: {s} (expected build, test, lint, format, or other)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub kind: Kind,
+    pub label: String,
+    pub commands: Vec<String>,
+}
+
+impl Check {
+    /// Whether the commands can run as written: none has a `<placeholder>`
+    /// left for the agent to fill in.
+    pub fn runnable(&self) -> bool {
+        !self
+            .commands
+            .iter()
+            .any(|c| c.contains('<') && c.contains('>'))
+    }
+}
+
+pub fn load(root: &Path) -> io::Result<Vec<Check>> {
+    crate::prompt::read_layered(root, "BACKPRESSURE.md").map(|text| parse(&text))
+}
+
+pub fn parse(markdown: &str) -> Vec<Check> {
+    markdown.lines().filter_map(parse_item).collect()
+}
+
+pub fn commands_of(checks: &[Check], kinds: &[Kind]) -> Vec<String> {
+    checks
+        .iter()
+        .filter(|c| kinds.contains(&c.kind) && c.runnable())
+        .flat_map(|c| c.commands.iter().cloned())
+        .collect()
+}
+
+fn parse_item(line: &str) -> Option<Check> {
+    let item = line
+        .trim_start()
+        .strip_prefix(['-', '*'])?
+        .strip_prefix(' ')?;
+    let item = item.trim_start();
+    let (label, rest) = item.strip_prefix("**").unwrap_or(item).split_once(':')?;
+    let label = label.trim().trim_end_matches('*').trim();
+    if label.is_empty() || label.contains('`') {
+        return None;
+    }
+    // Commands are backticked and joined by "and"; whatever follows the
+    // last one (an example, a note) is not a command.
+    let mut rest = rest.trim_start_matches('*').trim_start();
+    let mut commands = Vec::new();
+    while let Some(after) = rest.strip_prefix('`') {
+        let (command, tail) = after.split_once('`')?;
+        commands.push(command.trim().to_string());
+        match tail.trim_start().strip_prefix("and ") {
+            Some(next) => rest = next.trim_start(),
+            None => break,
+        }
+    }
+    (!commands.is_empty()).then(|| Check {
+        kind: Kind::of_label(label),
+        label: label.to_string(),
+        commands,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_profile_and_bold_label_items() {
+        let checks = parse(
+            "# Backpressure\n\n\
+             - Format: `cargo fmt --all -- --check`\n\
+             - Lint: `cargo clippy -- -D warnings` and `pnpm lint`\n\
+             - **Build all:** `cargo build --workspace`\n\
+             - **Test single:** `cargo test -p <crate> <test_name>` (e.g., `cargo test -p app login`)\n\
+             - **Smoke test:** `smoke_test_tauri` with `cwd`\n\
+             - Not a command: just prose\n",
+        );
+        let summary: Vec<_> = checks
+            .iter()
+            .map(|c| (c.kind, c.label.as_str(), c.commands.len(), c.runnable()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Kind::Format, "Format", 1, true),
+                (Kind::Lint, "Lint", 2, true),
+                (Kind::Build, "Build all", 1, true),
+                (Kind::Test, "Test single", 1, false),
+                (Kind::Other, "Smoke test", 1, true),
+            ]
+        );
+        assert_eq!(checks[1].commands[1], "pnpm lint");
+        assert_eq!(
+            commands_of(&checks, &[Kind::Test, Kind::Build]),
+            ["cargo build --workspace"]
+        );
+    }
+
+    #[test]
+    fn every_profile_template_parses() {
+        for profile in crate::profile::Profile::ALL {
+            let checks = parse(profile.backpressure());
+            assert!(
+                checks.iter().any(|c| c.kind == Kind::Test && c.runnable()),
+                "{profile} has a runnable test command"
+            );
+        }
+    }
+}
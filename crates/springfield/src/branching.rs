@@ -0,0 +1,181 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::MergeBack;
+
+pub fn loop_branch(loop_id: &str) -> String {
+    format!("sgf/{loop_id}")
+}
+
+fn run(root: &Path, program: &str, args: &[&str]) -> io::Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| io::Error::other(format!("failed to run {program}: {e}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{program} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn git(root: &Path, args: &[&str]) -> io::Result<String> {
+    run(root, "git", args)
+}
+
+fn current_branch(root: &Path) -> Option<String> {
+    git(root, &["symbolic-ref", "--quiet", "--short", "HEAD"]).ok()
+}
+
+/// Moves `root` onto a new `sgf/<loop_id>` branch at HEAD and returns the
+/// branch it left. Stays put and returns `None` on a detached HEAD or when
+/// already on an `sgf/` branch, as parallel worktrees are.
+///
+/// When there is an `origin`, the branch is set to push to a branch of the
+/// same name there, so auto-push works from the first iteration.
+pub fn enter(root: &Path, loop_id: &str) -> io::Result<Option<String>> {
+    let Some(base) = current_branch(root).filter(|b| !b.starts_with("sgf/")) else {
+        return Ok(None);
+    };
+    let branch = loop_branch(loop_id);
+    git(root, &["switch", "--quiet", "-c", &branch])?;
+    if git(root, &["remote", "get-url", "origin"]).is_ok() {
+        git(
+            root,
+            &["config", &format!("branch.{branch}.remote"), "origin"],
+        )?;
+        git(
+            root,
+            &[
+                "config",
+                &format!("branch.{branch}.merge"),
+                &format!("refs/heads/{branch}"),
+            ],
+        )?;
+    }
+    Ok(Some(base))
+}
+
+pub fn reenter(root: &Path, branch: &str) -> io::Result<()> {
+    if current_branch(root).as_deref() != Some(branch) {
+        git(root, &["switch", "--quiet", branch])?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeOutcome {
+    Kept,
+    FastForwarded,
+    PullRequest(String),
+}
+
+/// Applies `mode` to a completed loop's `branch`. Except for `Off`, the
+/// checkout ends up back on `base`.
+pub fn merge_back(
+    root: &Path,
+    branch: &str,
+    base: &str,
+    mode: MergeBack,
+) -> io::Result<MergeOutcome> {
+    match mode {
+        MergeBack::Off => Ok(MergeOutcome::Kept),
+        MergeBack::Ff => {
+            git(root, &["switch", "--quiet", base])?;
+            git(root, &["merge", "--ff-only", "--quiet", branch]).map_err(|e| {
+                io::Error::other(format!(
+                    "{base} cannot be fast-forwarded to {branch}; merge it by hand ({e})"
+                ))
+            })?;
+            git(root, &["branch", "--quiet", "-d", branch])?;
+            Ok(MergeOutcome::FastForwarded)
+        }
+        MergeBack::Pr => {
+            git(root, &["push", "--quiet", "-u", "origin", branch])?;
+            let url = run(
+                root,
+                "gh",
+                &["pr", "create", "--base", base, "--head", branch, "--fill"],
+            )?;
+            git(root, &["switch", "--quiet", base])?;
+            Ok(MergeOutcome::PullRequest(url))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn repo() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        git(root, &["init", "--quiet", "-b", "main"]).unwrap();
+        git(root, &["config", "user.email", "test@test.com"]).unwrap();
+        git(root, &["config", "user.name", "Test"]).unwrap();
+        commit(root, "base");
+        tmp
+    }
+
+    fn commit(root: &Path, name: &str) {
+        fs::write(root.join(name), name).unwrap();
+        git(root, &["add", name]).unwrap();
+        git(root, &["commit", "--quiet", "-m", name]).unwrap();
+    }
+
+    #[test]
+    fn enter_branches_once_and_sets_push_target() {
+        let tmp = repo();
+        let root = tmp.path();
+        git(root, &["remote", "add", "origin", "/nonexistent"]).unwrap();
+
+        assert_eq!(enter(root, "build-1").unwrap().as_deref(), Some("main"));
+        assert_eq!(current_branch(root).as_deref(), Some("sgf/build-1"));
+        assert_eq!(
+            git(root, &["config", "branch.sgf/build-1.merge"]).unwrap(),
+            "refs/heads/sgf/build-1"
+        );
+        assert_eq!(enter(root, "build-2").unwrap(), None, "already on sgf/");
+
+        git(root, &["switch", "--quiet", "main"]).unwrap();
+        reenter(root, "sgf/build-1").unwrap();
+        assert_eq!(current_branch(root).as_deref(), Some("sgf/build-1"));
+    }
+
+    #[test]
+    fn ff_merge_back_moves_base_and_deletes_branch() {
+        let tmp = repo();
+        let root = tmp.path();
+        enter(root, "build-1").unwrap();
+        commit(root, "work");
+        let tip = git(root, &["rev-parse", "HEAD"]).unwrap();
+
+        let outcome = merge_back(root, "sgf/build-1", "main", MergeBack::Ff).unwrap();
+        assert_eq!(outcome, MergeOutcome::FastForwarded);
+        assert_eq!(current_branch(root).as_deref(), Some("main"));
+        assert_eq!(git(root, &["rev-parse", "HEAD"]).unwrap(), tip);
+        assert!(git(root, &["rev-parse", "--verify", "sgf/build-1"]).is_err());
+    }
+
+    #[test]
+    fn ff_merge_back_keeps_branch_when_base_diverged() {
+        let tmp = repo();
+        let root = tmp.path();
+        enter(root, "build-1").unwrap();
+        commit(root, "work");
+        git(root, &["switch", "--quiet", "main"]).unwrap();
+        commit(root, "elsewhere");
+        git(root, &["switch", "--quiet", "sgf/build-1"]).unwrap();
+
+        let err = merge_back(root, "sgf/build-1", "main", MergeBack::Ff).unwrap_err();
+        assert!(err.to_string().contains("merge it by hand"), "{err}");
+        assert!(git(root, &["rev-parse", "--verify", "sgf/build-1"]).is_ok());
+    }
+}
@@ -0,0 +1,330 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+use chrono::TimeDelta;
+
+use crate::cursus::state::{self, RunStatus};
+use crate::loop_mgmt;
+
+pub const DEFAULT_LOG_RETENTION: TimeDelta = TimeDelta::days(14);
+
+#[derive(Debug, Clone, Default)]
+pub struct CleanOptions {
+    pub logs_older_than: Option<TimeDelta>,
+    pub assembled: bool,
+    pub run: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CleanReport {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl CleanReport {
+    fn remove_file(&mut self, path: &Path) -> io::Result<()> {
+        let len = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        fs::remove_file(path)?;
+        self.files += 1;
+        self.bytes += len;
+        Ok(())
+    }
+
+    fn remove_dir(&mut self, path: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(path)?.flatten() {
+            let p = entry.path();
+            if entry.file_type()?.is_dir() {
+                self.remove_dir(&p)?;
+            } else {
+                self.remove_file(&p)?;
+            }
+        }
+        fs::remove_dir(path)
+    }
+}
+
+pub fn clean(root: &Path, opts: &CleanOptions) -> io::Result<CleanReport> {
+    let mut report = CleanReport::default();
+    if let Some(age) = opts.logs_older_than {
+        prune_logs(root, age, &mut report)?;
+    }
+    if opts.assembled {
+        prune_assembled(root, &mut report)?;
+    }
+    if opts.run {
+        prune_run(root, &mut report)?;
+    }
+    Ok(report)
+}
+
+/// Prunes logs per `log_retention` in `.sgf/config.toml`, if set. Called as
+/// loops launch; failures are logged rather than surfaced.
+pub fn apply_log_retention(root: &Path) {
+    let Some(age) = crate::config::SgfConfig::load(root)
+        .ok()
+        .and_then(|c| c.log_retention())
+    else {
+        return;
+    };
+    let mut report = CleanReport::default();
+    match prune_logs(root, age, &mut report) {
+        Ok(()) => tracing::debug!(files = report.files, "log retention pruned old logs"),
+        Err(e) => tracing::warn!(error = %e, "log retention failed"),
+    }
+}
+
+fn is_live(root: &Path, loop_id: &str) -> bool {
+    loop_mgmt::read_loop_pid(root, loop_id).is_some_and(loop_mgmt::is_pid_alive)
+}
+
+fn prune_logs(root: &Path, age: TimeDelta, report: &mut CleanReport) -> io::Result<()> {
+    let cutoff = SystemTime::now() - age.to_std().unwrap_or_default();
+    prune_log_dir(root, &root.join(".sgf/logs"), cutoff, report)?;
+    for month in loop_mgmt::archive_months(root) {
+        prune_log_dir(root, &month, cutoff, report)?;
+        if fs::read_dir(&month)?.next().is_none() {
+            fs::remove_dir(&month)?;
+        }
+    }
+    Ok(())
+}
+
+fn prune_log_dir(
+    root: &Path,
+    dir: &Path,
+    cutoff: SystemTime,
+    report: &mut CleanReport,
+) -> io::Result<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let meta = entry.metadata()?;
+        if !meta.is_file() || meta.modified()? >= cutoff {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let loop_id = name.split_once('.').map_or(name.as_str(), |(id, _)| id);
+        if !is_live(root, loop_id) {
+            report.remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+fn prune_assembled(root: &Path, report: &mut CleanReport) -> io::Result<()> {
+    let legacy = root.join(".sgf/prompts/.assembled");
+    if legacy.is_dir() {
+        report.remove_dir(&legacy)?;
+    }
+    let Ok(entries) = fs::read_dir(root.join(".sgf/run")) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let run_id = entry.file_name().to_string_lossy().to_string();
+        if !entry.file_type()?.is_dir() || is_live(root, &run_id) {
+            continue;
+        }
+        for file in fs::read_dir(entry.path())?.flatten() {
+            let name = file.file_name().to_string_lossy().to_string();
+            if name.starts_with("prompt-") && name.ends_with(".md") {
+                report.remove_file(&file.path())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn prune_run(root: &Path, report: &mut CleanReport) -> io::Result<()> {
+    let run_dir = root.join(".sgf/run");
+    for (loop_id, pid) in loop_mgmt::list_pid_files(root) {
+        if !loop_mgmt::is_pid_alive(pid) {
+            report.remove_file(&run_dir.join(format!("{loop_id}.pid")))?;
+        }
+    }
+    for meta in loop_mgmt::list_session_metadata(root)? {
+        if meta.status == "completed" {
+            report.remove_file(&run_dir.join(format!("{}.json", meta.loop_id)))?;
+        }
+    }
+
+    let Ok(entries) = fs::read_dir(&run_dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type()?.is_dir() {
+            if is_live(root, &name) {
+                continue;
+            }
+            let completed = state::read_metadata(root, &name)
+                .ok()
+                .flatten()
+                .is_some_and(|m| m.status == RunStatus::Completed);
+            let pid_path = state::pid_path(root, &name);
+            if completed {
+                report.remove_dir(&path)?;
+            } else if pid_path.exists() {
+                report.remove_file(&pid_path)?;
+            }
+        } else if name.starts_with("loop")
+            && name.ends_with(".lock")
+            && loop_mgmt::read_lock(&path).is_none()
+        {
+            report.remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn backdate(path: &Path, days: u64) {
+        let when = SystemTime::now() - Duration::from_secs(days * 86_400);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(when)
+            .unwrap();
+    }
+
+    #[test]
+    fn prunes_only_old_logs_of_finished_loops() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let logs = root.join(".sgf/logs");
+        fs::create_dir_all(&logs).unwrap();
+        fs::create_dir_all(root.join(".sgf/run")).unwrap();
+        for name in ["old-1.log", "old-1.json", "recent-2.log", "live-3.log"] {
+            fs::write(logs.join(name), "output\n").unwrap();
+        }
+        for name in ["old-1.log", "old-1.json", "live-3.log"] {
+            backdate(&logs.join(name), 30);
+        }
+        loop_mgmt::write_pid_file(root, "live-3").unwrap();
+
+        let opts = CleanOptions {
+            logs_older_than: Some(TimeDelta::days(14)),
+            ..Default::default()
+        };
+        let report = clean(root, &opts).unwrap();
+        assert_eq!(
+            report,
+            CleanReport {
+                files: 2,
+                bytes: 14
+            }
+        );
+        assert!(!logs.join("old-1.log").exists());
+        assert!(!logs.join("old-1.json").exists());
+        assert!(logs.join("recent-2.log").exists());
+        assert!(logs.join("live-3.log").exists(), "live loop kept");
+    }
+
+    #[test]
+    fn prunes_archived_logs_and_empty_months() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let record = loop_mgmt::LoopRecord {
+            loop_id: "build-1".to_string(),
+            stage: "build".to_string(),
+            spec: None,
+            started_at: "2026-08-01T09:00:00Z".to_string(),
+            attempt_started_at: None,
+            ended_at: Some("2026-08-01T10:00:00Z".to_string()),
+            exit_code: Some(0),
+            iterations_completed: 1,
+            head_before: None,
+            head_after: None,
+            branch: None,
+            base_branch: None,
+            attempt: 1,
+            incidents: Vec::new(),
+        };
+        loop_mgmt::write_loop_record(root, &record).unwrap();
+        fs::write(root.join(".sgf/logs/build-1.log"), "output\n").unwrap();
+        let month = loop_mgmt::archive_loop(root, &record).unwrap();
+        for name in ["build-1.log", "build-1.json"] {
+            backdate(&month.join(name), 30);
+        }
+        let kept = root.join(".sgf/archive/2026-09");
+        fs::create_dir_all(&kept).unwrap();
+        fs::write(kept.join("build-2.log"), "output\n").unwrap();
+
+        let opts = CleanOptions {
+            logs_older_than: Some(TimeDelta::days(14)),
+            ..Default::default()
+        };
+        let report = clean(root, &opts).unwrap();
+        assert_eq!(report.files, 2);
+        assert!(!month.exists(), "emptied month removed");
+        assert!(kept.join("build-2.log").exists());
+    }
+
+    #[test]
+    fn prunes_assembled_prompts_and_finished_run_state() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+
+        let mut done = state::RunMetadata::new("build", "build", None, None);
+        done.run_id = "build-done".to_string();
+        done.status = RunStatus::Completed;
+        let mut stalled = state::RunMetadata::new("build", "build", None, None);
+        stalled.run_id = "build-stalled".to_string();
+        stalled.status = RunStatus::Stalled;
+        for meta in [&done, &stalled] {
+            state::create_run_dir(root, &meta.run_id).unwrap();
+            state::write_metadata(root, meta).unwrap();
+            fs::write(state::pid_path(root, &meta.run_id), "999999999").unwrap();
+            fs::write(
+                state::run_dir(root, &meta.run_id).join("prompt-build.md"),
+                "Build it.\n",
+            )
+            .unwrap();
+        }
+        fs::create_dir_all(root.join(".sgf/prompts/.assembled")).unwrap();
+        fs::write(root.join(".sgf/prompts/.assembled/build.md"), "x").unwrap();
+        fs::write(root.join(".sgf/run/simple-1.pid"), "999999999").unwrap();
+        fs::write(root.join(".sgf/run/loop.lock"), "{}").unwrap();
+
+        let opts = CleanOptions {
+            assembled: true,
+            ..Default::default()
+        };
+        clean(root, &opts).unwrap();
+        assert!(!root.join(".sgf/prompts/.assembled").exists());
+        assert!(
+            !state::run_dir(root, "build-stalled")
+                .join("prompt-build.md")
+                .exists()
+        );
+        assert!(
+            state::pid_path(root, "build-stalled").exists(),
+            "--run not given"
+        );
+
+        let opts = CleanOptions {
+            run: true,
+            ..Default::default()
+        };
+        clean(root, &opts).unwrap();
+        assert!(!state::run_dir(root, "build-done").exists());
+        assert!(
+            state::read_metadata(root, "build-stalled")
+                .unwrap()
+                .is_some()
+        );
+        assert!(!state::pid_path(root, "build-stalled").exists());
+        assert!(!root.join(".sgf/run/simple-1.pid").exists());
+        assert!(!root.join(".sgf/run/loop.lock").exists());
+    }
+}
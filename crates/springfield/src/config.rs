@@ -0,0 +1,1148 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::cursus::toml::{CursusDefinition, IterDefinition, Mode, RetryConfig, Transitions};
+use crate::iter_runner::MAX_ITERATIONS;
+use crate::notify::LoopEvent;
+use crate::profile::Profile;
+
+pub const DEFAULT_AGENT_COMMAND: &str = "cl";
+pub const DEFAULT_DING_SOUND: &str = "/System/Library/Sounds/Blow.aiff";
+pub const DEFAULT_EXPENSIVE_TURN_TOKENS: u64 = 10_000;
+
+pub const TEMPLATE: &str = "\
+# Springfield project settings. Every key is optional; CLI flags win.
+
+# Stack picked with `sgf init --profile`: \"rust\", \"node\", \"python\", or
+# \"tauri\". Decides which registries `sgf init` adds to the sandbox.
+# profile = \"rust\"
+
+# Agent binary launched for each iteration (SGF_AGENT_COMMAND overrides).
+# agent_command = \"cl\"
+
+# A clone can override a few settings without editing this file:
+# `git config sgf.autoPush false`, `sgf.agentCommand`, `sgf.pushRemote`,
+# and `sgf.pushBranch` replace auto_push, agent_command, and [push]
+# remote and branch.
+
+# Text file shown above the startup banner when sgf writes to a terminal
+# (--banner-file overrides, --no-art leaves it out).
+# banner_file = \".sgf/banner.txt\"
+
+# Upper bound for -n and cursus iteration counts (hard limit: 1000).
+# max_iterations = 1000
+
+# Set to false to behave as if --no-push were always passed.
+# auto_push = true
+
+# Concurrent loops for `sgf <command> <spec>...` and --all-ready.
+# jobs = 4
+
+# Loops that may not run at once in this checkout: \"global\" (one at a
+# time), \"spec\" (one per spec), or \"off\". --force overrides the lock.
+# lock = \"global\"
+
+# Run each loop in this checkout on its own sgf/<loop-id> branch, and on
+# completion merge it back: \"off\" (leave the branch), \"ff\" (fast-forward
+# the branch it started from), or \"pr\" (push it and open a PR with gh).
+# branch_per_loop = false
+# merge_back = \"off\"
+
+# In a monorepo root, the package sgf runs in when started here (as if
+# --project-dir were given). Set it in the root's own .sgf/config.toml.
+# project_dir = \"packages/api\"
+
+# What happens when .claude/settings.json lost its .sgf/.claude deny rules
+# or an earlier loop changed .sgf/prompts: \"refuse\" to launch, \"warn\"
+# and launch, or \"off\". `sgf prompt trust` accepts changed prompts.
+# guard = \"refuse\"
+
+# Tool calls from an AFK turn that added at least this many tokens are
+# marked with the count, e.g. `[+12.3k tokens]`. 0 turns the marks off.
+# expensive_turn_tokens = 10000
+
+# Delete loop logs older than this whenever a loop starts (e.g. \"14d\").
+# Also the default for `sgf clean --logs-older-than`. Unset keeps logs.
+# log_retention = \"14d\"
+
+# Per-iter defaults, keyed by iter name. Iters whose cursus file leaves
+# `iterations` out run spec 1, verify 10, build 30, test 15, and others 1.
+# [stages.build]
+# iterations = 30
+# auto_push = true
+
+# Custom stages: a stage with a prompt runs with `sgf run <name>`.
+# [stages.docs]
+# prompt = \"docs.md\"
+# description = \"Refresh the user docs\"
+# iterations = 5
+# afk = true
+# vars = { audience = \"new contributors\" }
+
+# Auto-push policy: push after each \"iteration\" with new commits or once
+# when the loop is \"complete\", optionally to another remote or branch. With
+# pr = true, a completed build loop also runs `gh pr create --fill`, labeled
+# spec:<spec> plus any labels listed here.
+# [push]
+# when = \"iteration\"
+# remote = \"origin\"
+# branch = \"agent-work\"
+# pr = false
+# labels = [\"agent\"]
+
+# Checks a stage must pass before it launches, keyed by stage. Each stage
+# in after must have completed its latest loop (for the same spec, if one is
+# given); each command runs with sh and must exit 0, as do the BACKPRESSURE.md
+# commands of each kind in backpressure (build, test, lint, format).
+# --skip-gates bypasses.
+# [gates.test]
+# after = [\"verify\"]
+# commands = [\"cargo test --workspace\"]
+# backpressure = [\"lint\"]
+
+# What pre-launch recovery does with a dirty tree left by a crashed loop:
+# \"reset\" (discard it), \"stash\" (stash it under refs/sgf/recovery/), or
+# \"abort\" (ask when interactive, otherwise refuse to launch). Paths in
+# exclude are never touched; .sgf/ never is.
+# [recovery]
+# strategy = \"reset\"
+# exclude = [\"notes/\"]
+
+# Kill an AFK agent that has printed nothing for stall_after, and
+# record the incident on the loop. restart = true runs the iterations left
+# instead of ending the loop. Unset stall_after turns the watchdog off.
+# [watchdog]
+# stall_after = \"20m\"
+# restart = false
+
+# Extra hosts the agent sandbox may reach, added by `sgf init`.
+# [sandbox]
+# allowed_domains = [\"pypi.org\"]
+# AFK loops stop when the agent reports working outside the project. Set
+# this when the agent's sandbox mounts the project somewhere else.
+# project_path = \"/workspace\"
+# Variables the agent gets from sgf's environment, besides PATH, HOME, the
+# locale, and ANTHROPIC_*/CLAUDE_*/SGF_*/PN_*/FM_*. Unset passes them all.
+# A trailing * matches a prefix. `sgf <stage> --env KEY=VALUE` adds more.
+# env = [\"GITHUB_TOKEN\", \"FEATURE_*\"]
+
+# Each iteration gets an empty .sgf/scratch/<loop-id>/<iteration> for temp
+# files, named by $SGF_SCRATCH. keep is how many iteration directories a
+# loop keeps; a loop's directory untouched for max_age is removed when
+# another loop starts. Unset max_age keeps them.
+# [scratch]
+# keep = 3
+# max_age = \"7d\"
+
+# Sound played when the agent touches .iter-ding, plus loop lifecycle
+# notifications. enabled = false silences all of them.
+# [notifications]
+# enabled = true
+# sound = \"/System/Library/Sounds/Blow.aiff\"
+# events = [\"start\", \"complete\", \"exhausted\", \"error\"]  # also \"interrupted\"
+# desktop = true
+# webhook = \"https://hooks.example.com/sgf\"  # JSON payload POSTed with curl
+# command = \"notify-me\"                      # run by sh, JSON payload on stdin
+
+# Run profiles, picked with `sgf <command> --profile <name>`. Each key is
+# what the matching flag would set; flags given on the command line win.
+# backoff replaces the cursus retry settings, notifications replaces the
+# [notifications] table, and vars are added to every prompt's template vars.
+# [profiles.overnight]
+# afk = true
+# iterations = 50
+# max_cost = 25.0
+# iteration_timeout = \"45m\"
+# complete_when = \"cargo test -q\"
+# model = \"opus\"
+# auto_push = true
+# vars = { pace = \"thorough\" }
+# [profiles.overnight.backoff]
+# immediate = 3
+# interval = \"10m\"
+# max_duration = \"8h\"
+# [profiles.overnight.notifications]
+# events = [\"complete\", \"exhausted\", \"error\"]
+# command = \"notify-me\"
+";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SgfConfig {
+    pub profile: Option<Profile>,
+    pub agent_command: Option<String>,
+    pub banner_file: Option<PathBuf>,
+    pub max_iterations: Option<u32>,
+    pub auto_push: Option<bool>,
+    pub jobs: Option<usize>,
+    pub lock: Option<LockScope>,
+    pub log_retention: Option<String>,
+    pub expensive_turn_tokens: Option<u64>,
+    pub branch_per_loop: Option<bool>,
+    pub merge_back: Option<MergeBack>,
+    pub guard: Option<GuardMode>,
+    pub project_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub stages: HashMap<String, StageConfig>,
+    #[serde(default)]
+    pub push: PushConfig,
+    #[serde(default)]
+    pub gates: HashMap<String, GateConfig>,
+    #[serde(default)]
+    pub recovery: RecoveryConfig,
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub scratch: ScratchConfig,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub profiles: HashMap<String, RunProfile>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunProfile {
+    pub afk: Option<bool>,
+    pub iterations: Option<u32>,
+    pub max_cost: Option<f64>,
+    pub iteration_timeout: Option<String>,
+    pub complete_when: Option<String>,
+    pub model: Option<String>,
+    pub auto_push: Option<bool>,
+    pub backoff: Option<BackoffConfig>,
+    pub notifications: Option<NotificationConfig>,
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackoffConfig {
+    pub immediate: Option<u32>,
+    pub interval: Option<String>,
+    pub max_duration: Option<String>,
+}
+
+fn duration_secs(value: Option<&str>) -> Option<u64> {
+    crate::logs::parse_since(value?)
+        .ok()
+        .and_then(|d| d.to_std().ok())
+        .map(|d| d.as_secs())
+}
+
+impl RunProfile {
+    pub fn iteration_timeout(&self) -> Option<Duration> {
+        duration_secs(self.iteration_timeout.as_deref()).map(Duration::from_secs)
+    }
+
+    pub fn apply_backoff(&self, retry: &mut RetryConfig) {
+        let Some(backoff) = &self.backoff else {
+            return;
+        };
+        if let Some(n) = backoff.immediate {
+            retry.immediate = n;
+        }
+        if let Some(secs) = duration_secs(backoff.interval.as_deref()) {
+            retry.interval_secs = secs;
+        }
+        if let Some(secs) = duration_secs(backoff.max_duration.as_deref()) {
+            retry.max_duration_secs = secs;
+        }
+    }
+
+    pub fn apply(&self, def: &mut CursusDefinition) {
+        self.apply_backoff(&mut def.retry);
+        for iter in &mut def.iters {
+            iter.vars
+                .extend(self.vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(max) = self.max_cost
+            && !(max > 0.0 && max.is_finite())
+        {
+            return Err(format!("max_cost must be a positive amount: {max}"));
+        }
+        let backoff = self.backoff.clone().unwrap_or_default();
+        for (key, value) in [
+            ("iteration_timeout", &self.iteration_timeout),
+            ("backoff.interval", &backoff.interval),
+            ("backoff.max_duration", &backoff.max_duration),
+        ] {
+            if let Some(value) = value {
+                crate::logs::parse_since(value).map_err(|e| format!("{key}: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+static ACTIVE_PROFILE: OnceLock<RunProfile> = OnceLock::new();
+
+pub fn activate_profile(profile: RunProfile) {
+    let _ = ACTIVE_PROFILE.set(profile);
+}
+
+pub fn active_profile() -> Option<&'static RunProfile> {
+    ACTIVE_PROFILE.get()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockScope {
+    #[default]
+    Global,
+    Spec,
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeBack {
+    #[default]
+    Off,
+    Ff,
+    Pr,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardMode {
+    #[default]
+    Refuse,
+    Warn,
+    Off,
+}
+
+/// Per-stage settings. Entries that set `prompt` are custom stages, runnable
+/// with `sgf run <name>`; the rest only override iters of that name.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StageConfig {
+    pub iterations: Option<u32>,
+    pub auto_push: Option<bool>,
+    pub prompt: Option<String>,
+    pub description: Option<String>,
+    pub afk: Option<bool>,
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushWhen {
+    #[default]
+    Iteration,
+    Complete,
+}
+
+/// Where and when loops push, and whether a completed build loop opens a
+/// pull request. `auto_push = false` still turns all of it off.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushConfig {
+    pub when: Option<PushWhen>,
+    pub remote: Option<String>,
+    pub branch: Option<String>,
+    pub pr: Option<bool>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+impl PushConfig {
+    pub fn when(&self) -> PushWhen {
+        self.when.unwrap_or_default()
+    }
+
+    pub fn pr(&self) -> bool {
+        self.pr.unwrap_or(false)
+    }
+
+    pub fn target(&self) -> vcs_utils::PushTarget {
+        vcs_utils::PushTarget {
+            remote: self.remote.clone(),
+            branch: self.branch.clone(),
+        }
+    }
+
+    pub fn pr_labels(&self, spec: Option<&str>) -> Vec<String> {
+        let mut labels = self.labels.clone();
+        if let Some(spec) = spec {
+            labels.push(format!("spec:{spec}"));
+        }
+        labels
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GateConfig {
+    #[serde(default)]
+    pub after: Vec<String>,
+    #[serde(default)]
+    pub commands: Vec<String>,
+    #[serde(default)]
+    pub backpressure: Vec<crate::backpressure::Kind>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecoveryConfig {
+    pub strategy: Option<RecoveryStrategy>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecoveryStrategy {
+    #[default]
+    Reset,
+    Stash,
+    Abort,
+}
+
+impl std::str::FromStr for RecoveryStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reset" => Ok(Self::Reset),
+            "stash" => Ok(Self::Stash),
+            "abort" => Ok(Self::Abort),
+            _ => Err(format!(
+                "unknown recovery strategy: {s} (expected reset, stash, or abort)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchdogConfig {
+    pub stall_after: Option<String>,
+    pub restart: Option<bool>,
+}
+
+impl WatchdogConfig {
+    pub fn stall_after(&self) -> Option<std::time::Duration> {
+        self.stall_after
+            .as_deref()
+            .and_then(|s| crate::logs::parse_since(s).ok())
+            .and_then(|d| d.to_std().ok())
+    }
+
+    pub fn restart(&self) -> bool {
+        self.restart.unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScratchConfig {
+    pub keep: Option<usize>,
+    pub max_age: Option<String>,
+}
+
+impl ScratchConfig {
+    pub fn keep(&self) -> usize {
+        self.keep.unwrap_or(3).max(1)
+    }
+
+    pub fn max_age(&self) -> Option<chrono::TimeDelta> {
+        self.max_age
+            .as_deref()
+            .and_then(|s| crate::logs::parse_since(s).ok())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SandboxConfig {
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    pub project_path: Option<PathBuf>,
+    pub env: Option<Vec<String>>,
+}
+
+/// Variables an agent keeps whatever `[sandbox] env` lists: what a shell and
+/// the agent need to run, and sgf's, pensa's, and forma's own settings.
+pub const AGENT_BASE_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "COLORTERM",
+    "LANG",
+    "LC_*",
+    "TZ",
+    "TMPDIR",
+    "ANTHROPIC_*",
+    "CLAUDE_*",
+    "SGF_*",
+    "PN_*",
+    "FM_*",
+];
+
+impl SandboxConfig {
+    /// Whether `name` reaches the agent from sgf's environment. Patterns
+    /// ending in `*` match a prefix.
+    pub fn passes_env(&self, name: &str) -> bool {
+        let Some(allowed) = &self.env else {
+            return true;
+        };
+        AGENT_BASE_ENV
+            .iter()
+            .copied()
+            .chain(allowed.iter().map(String::as_str))
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == pattern,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationConfig {
+    pub enabled: Option<bool>,
+    pub sound: Option<String>,
+    pub events: Option<Vec<LoopEvent>>,
+    pub desktop: Option<bool>,
+    pub webhook: Option<String>,
+    pub command: Option<String>,
+}
+
+impl NotificationConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    pub fn announces(&self, event: LoopEvent) -> bool {
+        self.enabled()
+            && match &self.events {
+                Some(events) => events.contains(&event),
+                None => event != LoopEvent::Interrupted,
+            }
+    }
+
+    pub fn desktop(&self) -> bool {
+        self.desktop.unwrap_or(false)
+    }
+
+    pub fn sound(&self) -> &str {
+        self.sound.as_deref().unwrap_or(DEFAULT_DING_SOUND)
+    }
+}
+
+pub fn config_path(root: &Path) -> PathBuf {
+    root.join(".sgf/config.toml")
+}
+
+type GitConfigEntries = Vec<(String, String)>;
+
+// `SgfConfig::load` runs several times per iteration; ask git once per root.
+static GIT_CONFIG_ENTRIES: LazyLock<Mutex<HashMap<PathBuf, GitConfigEntries>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn cached_git_config_entries(root: &Path) -> GitConfigEntries {
+    let mut cache = GIT_CONFIG_ENTRIES.lock().unwrap();
+    cache
+        .entry(root.to_path_buf())
+        .or_insert_with(|| git_config_entries(root))
+        .clone()
+}
+
+fn git_config_entries(root: &Path) -> GitConfigEntries {
+    let Ok(output) = Command::new("git")
+        .args(["config", "--get-regexp", r"^sgf\."])
+        .current_dir(root)
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| match line.split_once(' ') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            // `[sgf] autoPush` with no value means true.
+            None => (line.to_string(), "true".to_string()),
+        })
+        .collect()
+}
+
+fn parse_git_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" | "" => Some(false),
+        _ => None,
+    }
+}
+
+impl SgfConfig {
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let config: SgfConfig = toml::from_str(content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        if config.jobs == Some(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "jobs must be at least 1",
+            ));
+        }
+        if let Some(ref retention) = config.log_retention {
+            crate::logs::parse_since(retention).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("log_retention: {e}"))
+            })?;
+        }
+        if let Some(ref stall_after) = config.watchdog.stall_after {
+            crate::logs::parse_since(stall_after).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("watchdog.stall_after: {e}"),
+                )
+            })?;
+        }
+        if let Some(ref max_age) = config.scratch.max_age {
+            crate::logs::parse_since(max_age).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("scratch.max_age: {e}"))
+            })?;
+        }
+        for (name, profile) in &config.profiles {
+            profile.validate().map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("profiles.{name}.{e}"))
+            })?;
+        }
+        Ok(config)
+    }
+
+    pub fn load(root: &Path) -> io::Result<Self> {
+        let path = config_path(root);
+        let mut config = match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e),
+        };
+        config.apply_git_config(&cached_git_config_entries(root))?;
+        Ok(config)
+    }
+
+    /// Overrides settings with `sgf.*` git config entries, as
+    /// `git config --get-regexp` lists them. Unknown keys are ignored.
+    pub fn apply_git_config(&mut self, entries: &[(String, String)]) -> io::Result<()> {
+        for (key, value) in entries {
+            match key.as_str() {
+                "sgf.autopush" => {
+                    self.auto_push = Some(parse_git_bool(value).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("git config {key}: expected a boolean, got {value:?}"),
+                        )
+                    })?);
+                }
+                "sgf.agentcommand" => self.agent_command = Some(value.clone()),
+                "sgf.pushremote" => self.push.remote = Some(value.clone()),
+                "sgf.pushbranch" => self.push.branch = Some(value.clone()),
+                _ => tracing::debug!(key, "ignoring unknown sgf git config key"),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn max_iterations(&self) -> u32 {
+        self.max_iterations
+            .unwrap_or(MAX_ITERATIONS)
+            .min(MAX_ITERATIONS)
+    }
+
+    pub fn agent_command(&self) -> String {
+        std::env::var("SGF_AGENT_COMMAND")
+            .ok()
+            .or_else(|| self.agent_command.clone())
+            .unwrap_or_else(|| DEFAULT_AGENT_COMMAND.to_string())
+    }
+
+    pub fn lock_scope(&self) -> LockScope {
+        self.lock.unwrap_or_default()
+    }
+
+    pub fn branch_per_loop(&self) -> bool {
+        self.branch_per_loop.unwrap_or(false)
+    }
+
+    pub fn merge_back(&self) -> MergeBack {
+        self.merge_back.unwrap_or_default()
+    }
+
+    pub fn guard(&self) -> GuardMode {
+        self.guard.unwrap_or_default()
+    }
+
+    pub fn log_retention(&self) -> Option<chrono::TimeDelta> {
+        self.log_retention
+            .as_deref()
+            .and_then(|r| crate::logs::parse_since(r).ok())
+    }
+
+    pub fn auto_push(&self) -> bool {
+        self.auto_push.unwrap_or(true)
+    }
+
+    pub fn expensive_turn_tokens(&self) -> Option<u64> {
+        Some(
+            self.expensive_turn_tokens
+                .unwrap_or(DEFAULT_EXPENSIVE_TURN_TOKENS),
+        )
+        .filter(|&t| t > 0)
+    }
+
+    pub fn gate(&self, stage: &str) -> Option<&GateConfig> {
+        self.gates.get(stage)
+    }
+
+    pub fn stage(&self, name: &str) -> Option<&StageConfig> {
+        self.stages.get(name)
+    }
+
+    pub fn run_profile(&self, name: &str) -> Result<&RunProfile, String> {
+        self.profiles.get(name).ok_or_else(|| {
+            let mut names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            names.sort();
+            if names.is_empty() {
+                format!("unknown profile: {name} (define [profiles.{name}] in .sgf/config.toml)")
+            } else {
+                format!("unknown profile: {name} (defined: {})", names.join(", "))
+            }
+        })
+    }
+
+    pub fn custom_stages(&self) -> Vec<(String, String)> {
+        let mut stages: Vec<(String, String)> = self
+            .stages
+            .iter()
+            .filter(|(_, s)| s.prompt.is_some())
+            .map(|(name, s)| {
+                let desc = s.description.clone().unwrap_or_else(|| {
+                    format!("Custom stage ({})", s.prompt.as_deref().unwrap_or_default())
+                });
+                (name.clone(), desc)
+            })
+            .collect();
+        stages.sort();
+        stages
+    }
+
+    pub fn stage_definition(&self, name: &str) -> Option<CursusDefinition> {
+        let stage = self.stage(name)?;
+        let prompt = stage.prompt.clone()?;
+        Some(CursusDefinition {
+            description: stage
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("Custom stage ({prompt})")),
+            alias: None,
+            trigger: "manual".to_string(),
+            auto_push: self.auto_push(),
+            retry: RetryConfig::default(),
+            iters: vec![IterDefinition {
+                name: name.to_string(),
+                prompt,
+                mode: if stage.afk.unwrap_or(false) {
+                    Mode::Afk
+                } else {
+                    Mode::Interactive
+                },
+                iterations: Some(stage.iterations.unwrap_or(1)),
+                produces: None,
+                consumes: vec![],
+                auto_push: stage.auto_push,
+                next: None,
+                banner: false,
+                transitions: Transitions::default(),
+                vars: stage.vars.clone(),
+            }],
+        })
+    }
+
+    /// Applies `[stages.<iter>]` defaults to a cursus definition. Runs
+    /// before CLI overrides so `-n` and `--no-push` still win.
+    pub fn apply_stage_defaults(&self, def: &mut CursusDefinition) {
+        for iter in &mut def.iters {
+            let Some(stage) = self.stage(&iter.name) else {
+                continue;
+            };
+            if let Some(n) = stage.iterations {
+                iter.iterations = Some(n);
+            }
+            if let Some(push) = stage.auto_push {
+                iter.auto_push = Some(push);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn git_config_overrides_the_file() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let git = |args: &[&str]| {
+            assert!(
+                Command::new("git")
+                    .args(args)
+                    .current_dir(root)
+                    .status()
+                    .unwrap()
+                    .success()
+            );
+        };
+        git(&["init", "--quiet"]);
+        fs::create_dir_all(root.join(".sgf")).unwrap();
+        fs::write(
+            config_path(root),
+            "auto_push = true\nagent_command = \"my-agent\"\n[push]\nremote = \"origin\"\n",
+        )
+        .unwrap();
+        git(&["config", "sgf.autoPush", "off"]);
+        git(&["config", "sgf.pushRemote", "backup"]);
+        git(&["config", "sgf.pushBranch", "agent-work"]);
+
+        let config = SgfConfig::load(root).unwrap();
+        assert!(!config.auto_push());
+        assert_eq!(config.agent_command.as_deref(), Some("my-agent"));
+        assert_eq!(config.push.remote.as_deref(), Some("backup"));
+        assert_eq!(config.push.branch.as_deref(), Some("agent-work"));
+
+        // The overlay is cached per root, so re-read git directly.
+        git(&["config", "sgf.autoPush", "sometimes"]);
+        let err = SgfConfig::default()
+            .apply_git_config(&git_config_entries(root))
+            .unwrap_err();
+        assert!(err.to_string().contains("git config sgf.autopush"), "{err}");
+    }
+
+    #[test]
+    fn missing_file_yields_defaults() {
+        let tmp = TempDir::new().unwrap();
+        let config = SgfConfig::load(tmp.path()).unwrap();
+        assert_eq!(config.max_iterations(), MAX_ITERATIONS);
+        assert!(config.auto_push());
+        assert!(config.notifications.enabled());
+        assert_eq!(config.notifications.sound(), DEFAULT_DING_SOUND);
+        assert!(config.stage("build").is_none());
+        assert_eq!(config.lock_scope(), LockScope::Global);
+        assert!(config.log_retention().is_none());
+        assert!(!config.branch_per_loop());
+        assert_eq!(config.merge_back(), MergeBack::Off);
+        assert_eq!(config.guard(), GuardMode::Refuse);
+        assert_eq!(config.push.when(), PushWhen::Iteration);
+        assert_eq!(config.push.target(), vcs_utils::PushTarget::default());
+        assert!(!config.push.pr());
+        assert!(config.recovery.strategy.is_none());
+        assert!(config.recovery.exclude.is_empty());
+        assert!(config.watchdog.stall_after().is_none());
+        assert!(!config.watchdog.restart());
+        assert_eq!(config.scratch.keep(), 3);
+        assert!(config.scratch.max_age().is_none());
+        assert_eq!(
+            config.expensive_turn_tokens(),
+            Some(DEFAULT_EXPENSIVE_TURN_TOKENS)
+        );
+        let off = SgfConfig::parse(
+            "expensive_turn_tokens = 0
+",
+        )
+        .unwrap();
+        assert_eq!(off.expensive_turn_tokens(), None);
+    }
+
+    #[test]
+    fn template_parses_to_defaults() {
+        let config = SgfConfig::parse(TEMPLATE).unwrap();
+        assert!(config.agent_command.is_none());
+        assert!(config.stages.is_empty());
+    }
+
+    #[test]
+    fn parses_all_sections() {
+        let config = SgfConfig::parse(
+            r#"
+profile = "python"
+agent_command = "my-agent"
+max_iterations = 5000
+auto_push = false
+jobs = 2
+lock = "spec"
+log_retention = "14d"
+branch_per_loop = true
+merge_back = "pr"
+guard = "warn"
+
+[stages.build]
+iterations = 12
+auto_push = true
+
+[push]
+when = "complete"
+branch = "agent-work"
+pr = true
+labels = ["agent"]
+
+[gates.test]
+after = ["verify"]
+commands = ["cargo test"]
+backpressure = ["lint", "format"]
+
+[recovery]
+strategy = "stash"
+exclude = ["notes/"]
+
+[watchdog]
+stall_after = "20m"
+restart = true
+
+[sandbox]
+allowed_domains = ["pypi.org"]
+project_path = "/workspace"
+env = ["GITHUB_TOKEN", "FEATURE_*"]
+
+[scratch]
+keep = 5
+max_age = "7d"
+
+[notifications]
+enabled = false
+sound = "/tmp/ding.wav"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.profile, Some(Profile::Python));
+        assert_eq!(config.agent_command.as_deref(), Some("my-agent"));
+        assert_eq!(
+            config.max_iterations(),
+            MAX_ITERATIONS,
+            "clamped to hard limit"
+        );
+        assert!(!config.auto_push());
+        assert_eq!(config.jobs, Some(2));
+        assert_eq!(config.lock_scope(), LockScope::Spec);
+        assert_eq!(config.log_retention(), Some(chrono::TimeDelta::days(14)));
+        assert!(config.branch_per_loop());
+        assert_eq!(config.merge_back(), MergeBack::Pr);
+        assert_eq!(config.guard(), GuardMode::Warn);
+        let build = config.stage("build").unwrap();
+        assert_eq!(build.iterations, Some(12));
+        assert_eq!(build.auto_push, Some(true));
+        assert_eq!(config.push.when(), PushWhen::Complete);
+        assert_eq!(config.push.target().branch.as_deref(), Some("agent-work"));
+        assert!(config.push.target().remote.is_none());
+        assert!(config.push.pr());
+        assert_eq!(config.push.pr_labels(Some("auth")), ["agent", "spec:auth"]);
+        let gate = config.gate("test").unwrap();
+        assert_eq!(gate.after, ["verify"]);
+        assert_eq!(gate.commands, ["cargo test"]);
+        assert_eq!(
+            gate.backpressure,
+            [
+                crate::backpressure::Kind::Lint,
+                crate::backpressure::Kind::Format
+            ]
+        );
+        assert!(config.gate("build").is_none());
+        assert_eq!(config.recovery.strategy, Some(RecoveryStrategy::Stash));
+        assert_eq!(config.recovery.exclude, ["notes/"]);
+        assert_eq!(
+            config.watchdog.stall_after(),
+            Some(std::time::Duration::from_secs(1200))
+        );
+        assert!(config.watchdog.restart());
+        assert_eq!(config.sandbox.allowed_domains, vec!["pypi.org".to_string()]);
+        assert_eq!(
+            config.sandbox.project_path.as_deref(),
+            Some(Path::new("/workspace"))
+        );
+        assert!(config.sandbox.passes_env("GITHUB_TOKEN"));
+        assert!(config.sandbox.passes_env("FEATURE_NEW_UI"));
+        assert!(config.sandbox.passes_env("LC_ALL"));
+        assert!(!config.sandbox.passes_env("AWS_SECRET_ACCESS_KEY"));
+        assert!(SandboxConfig::default().passes_env("AWS_SECRET_ACCESS_KEY"));
+        assert_eq!(config.scratch.keep(), 5);
+        assert_eq!(config.scratch.max_age(), Some(chrono::TimeDelta::days(7)));
+        assert!(!config.notifications.enabled());
+        assert_eq!(config.notifications.sound(), "/tmp/ding.wav");
+    }
+
+    #[test]
+    fn notification_events_default_and_filter() {
+        let config = SgfConfig::parse("").unwrap();
+        assert!(config.notifications.announces(LoopEvent::Start));
+        assert!(config.notifications.announces(LoopEvent::Error));
+        assert!(!config.notifications.announces(LoopEvent::Interrupted));
+        assert!(!config.notifications.desktop());
+
+        let config = SgfConfig::parse(
+            "[notifications]\nevents = [\"error\", \"interrupted\"]\ndesktop = true\n",
+        )
+        .unwrap();
+        assert!(!config.notifications.announces(LoopEvent::Complete));
+        assert!(config.notifications.announces(LoopEvent::Interrupted));
+        assert!(config.notifications.desktop());
+
+        let err = SgfConfig::parse("[notifications]\nevents = [\"done\"]\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant `done`"), "{err}");
+    }
+
+    #[test]
+    fn stage_defaults_apply_by_iter_name() {
+        let mut def = crate::cursus::toml::parse(
+            r#"
+description = "Build"
+[[iter]]
+name = "build"
+prompt = "build.md"
+iterations = 30
+[[iter]]
+name = "verify"
+prompt = "verify.md"
+iterations = 3
+"#,
+        )
+        .unwrap();
+        let config =
+            SgfConfig::parse("[stages.build]\niterations = 8\nauto_push = false\n").unwrap();
+        config.apply_stage_defaults(&mut def);
+        assert_eq!(def.iters[0].iterations(), 8);
+        assert_eq!(def.iters[0].auto_push, Some(false));
+        assert_eq!(def.iters[1].iterations(), 3);
+        assert_eq!(def.iters[1].auto_push, None);
+    }
+
+    #[test]
+    fn custom_stage_builds_single_iter_cursus() {
+        let config = SgfConfig::parse(
+            r#"
+[stages.build]
+iterations = 8
+
+[stages.docs]
+prompt = "docs.md"
+iterations = 4
+afk = true
+vars = { audience = "users" }
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.custom_stages(),
+            vec![("docs".to_string(), "Custom stage (docs.md)".to_string())]
+        );
+        assert!(config.stage_definition("build").is_none(), "no prompt");
+
+        let def = config.stage_definition("docs").unwrap();
+        assert_eq!(def.iters.len(), 1);
+        let iter = &def.iters[0];
+        assert_eq!(iter.name, "docs");
+        assert_eq!(iter.prompt, "docs.md");
+        assert_eq!(iter.mode, Mode::Afk);
+        assert_eq!(iter.iterations(), 4);
+        assert_eq!(iter.vars["audience"], "users");
+        crate::cursus::toml::validate(&def).unwrap();
+    }
+
+    #[test]
+    fn run_profiles_parse_and_apply_to_a_cursus() {
+        let config = SgfConfig::parse(
+            r#"
+[profiles.overnight]
+afk = true
+iterations = 50
+max_cost = 25.0
+iteration_timeout = "45m"
+auto_push = false
+vars = { pace = "thorough" }
+
+[profiles.overnight.backoff]
+immediate = 1
+interval = "10m"
+
+[profiles.overnight.notifications]
+events = ["error"]
+command = "notify-me"
+"#,
+        )
+        .unwrap();
+        let profile = config.run_profile("overnight").unwrap();
+        assert_eq!(profile.iterations, Some(50));
+        assert_eq!(profile.iteration_timeout(), Some(Duration::from_secs(2700)));
+        let notifications = profile.notifications.as_ref().unwrap();
+        assert!(!notifications.announces(LoopEvent::Complete));
+        assert!(notifications.announces(LoopEvent::Error));
+        let err = config.run_profile("weekend").unwrap_err();
+        assert!(err.contains("defined: overnight"), "{err}");
+
+        let mut def = crate::cursus::toml::parse(
+            "description = \"Build\"\n[[iter]]\nname = \"build\"\nprompt = \"build.md\"\nvars = { pace = \"quick\", tone = \"terse\" }\n",
+        )
+        .unwrap();
+        profile.apply(&mut def);
+        assert_eq!(def.retry.immediate, 1);
+        assert_eq!(def.retry.interval_secs, 600);
+        assert_eq!(
+            def.retry.max_duration_secs,
+            RetryConfig::default().max_duration_secs
+        );
+        assert_eq!(def.iters[0].vars["pace"], "thorough");
+        assert_eq!(def.iters[0].vars["tone"], "terse");
+
+        let err = SgfConfig::parse("[profiles.bad]\nmax_cost = -5.0\n").unwrap_err();
+        assert!(err.to_string().contains("profiles.bad.max_cost"), "{err}");
+        let err = SgfConfig::parse("[profiles.bad.backoff]\ninterval = \"later\"\n").unwrap_err();
+        assert!(
+            err.to_string().contains("profiles.bad.backoff.interval"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_bad_values() {
+        let err = SgfConfig::parse("max_iteration = 3\n").unwrap_err();
+        assert!(err.to_string().contains("max_iteration"));
+        let err = SgfConfig::parse("jobs = 0\n").unwrap_err();
+        assert!(err.to_string().contains("jobs must be at least 1"));
+        let err = SgfConfig::parse("log_retention = \"two weeks\"\n").unwrap_err();
+        assert!(err.to_string().contains("log_retention"));
+        let err = SgfConfig::parse("[watchdog]\nstall_after = \"soon\"\n").unwrap_err();
+        assert!(err.to_string().contains("watchdog.stall_after"));
+    }
+
+    #[test]
+    fn load_error_names_the_file() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".sgf")).unwrap();
+        fs::write(config_path(tmp.path()), "jobs = \"many\"\n").unwrap();
+        let err = SgfConfig::load(tmp.path()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains(".sgf/config.toml"));
+    }
+}
@@ -101,13 +101,14 @@ mod tests {
                     name: name.to_string(),
                     prompt: format!("{name}.md"),
                     mode: Mode::default(),
-                    iterations: 1,
+                    iterations: Some(1),
                     produces: produces.map(|p| p.to_string()),
                     consumes: consumes.into_iter().map(|c| c.to_string()).collect(),
                     auto_push: None,
                     next: None,
                     banner: false,
                     transitions: Transitions::default(),
+                    vars: HashMap::new(),
                 })
                 .collect(),
         }
@@ -69,6 +69,15 @@ fn load_all_from_dir(dir: &Path) -> HashMap<String, CursusDefinition> {
     map
 }
 
+pub fn resolve_stage(root: &Path, name: &str) -> Option<ResolvedCursus> {
+    let config = crate::config::SgfConfig::load(root).ok()?;
+    Some(ResolvedCursus {
+        name: name.to_string(),
+        definition: config.stage_definition(name)?,
+        path: crate::config::config_path(root),
+    })
+}
+
 pub fn resolve_alias(root: &Path, alias: &str) -> Option<ResolvedCursus> {
     let local_dir = root.join(".sgf/cursus");
     let local_defs = load_all_from_dir(&local_dir);
@@ -129,10 +138,18 @@ pub fn list_all(root: &Path) -> Vec<(String, String)> {
 fn list_all_with_global(root: &Path, global_dir: Option<&Path>) -> Vec<(String, String)> {
     let defs = load_all_definitions_with_global(root, global_dir);
 
+    let stages = crate::config::SgfConfig::load(root)
+        .unwrap_or_default()
+        .custom_stages();
     let mut entries: Vec<(String, String)> = defs
         .into_iter()
         .map(|(name, def)| (name, def.description))
         .collect();
+    for (name, desc) in stages {
+        if !entries.iter().any(|(n, _)| *n == name) {
+            entries.push((name, desc));
+        }
+    }
     entries.sort_by(|a, b| a.0.cmp(&b.0));
     entries
 }
@@ -146,6 +163,10 @@ pub fn resolve_command(root: &Path, command: &str) -> Result<ResolvedCursus, io:
         return Ok(resolved);
     }
 
+    if let Some(resolved) = resolve_stage(root, command) {
+        return Ok(resolved);
+    }
+
     Err(io::Error::new(
         io::ErrorKind::NotFound,
         format!("unknown command: {command}"),
@@ -246,6 +267,25 @@ prompt = "test.md"
         assert!(err.to_string().contains("unknown command: ghost"));
     }
 
+    #[test]
+    fn resolve_command_falls_back_to_custom_stage() {
+        let tmp = TempDir::new().unwrap();
+        write_cursus_toml(&tmp.path().join(".sgf/cursus"), "build", SIMPLE_CURSUS);
+        fs::write(
+            tmp.path().join(".sgf/config.toml"),
+            "[stages.docs]\nprompt = \"docs.md\"\ndescription = \"Docs loop\"\n",
+        )
+        .unwrap();
+
+        let resolved = resolve_command(tmp.path(), "docs").unwrap();
+        assert_eq!(resolved.name, "docs");
+        assert_eq!(resolved.definition.iters[0].prompt, "docs.md");
+        assert!(resolved.path.ends_with(".sgf/config.toml"));
+
+        let listed = list_all_with_global(tmp.path(), None);
+        assert!(listed.contains(&("docs".to_string(), "Docs loop".to_string())));
+    }
+
     #[test]
     fn local_cursus_overrides_global_alias() {
         let tmp = TempDir::new().unwrap();
@@ -1,17 +1,19 @@
 use chrono::Utc;
 use shutdown::{ShutdownConfig, ShutdownController};
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::io::{IsTerminal, Read as _};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use uuid::Uuid;
 
 use crate::cursus::context;
 use crate::cursus::events::{self, Event, IterSummary};
 use crate::cursus::state::{self, CompletedIter, RunMetadata, RunStatus};
 use crate::cursus::toml::{CursusDefinition, IterDefinition, Mode, RetryConfig};
-use crate::iter_runner::{self, IterExitCode, IterRunnerConfig};
+use crate::iter_runner::{self, AgentTuning, IterExitCode, IterRunnerConfig};
 use crate::loop_mgmt;
 use crate::style;
 
@@ -31,6 +33,8 @@ pub struct CursusConfig {
     /// `Some(content)` — use as the user's message (read from stdin by caller).
     /// `None` — no initial input (used for resume turns and tests).
     pub initial_input: Option<String>,
+    pub tuning: AgentTuning,
+    pub task: Option<crate::next::Task>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -93,7 +97,7 @@ pub fn detect_outcome(
     if find_sentinel(root, ".iter-revise", SENTINEL_MAX_DEPTH).is_some() {
         return IterOutcome::Revise;
     }
-    if *effective_mode == Mode::Interactive && iter.iterations <= 1 {
+    if *effective_mode == Mode::Interactive && iter.iterations() <= 1 {
         return IterOutcome::Complete;
     }
     if exit_code == 0 {
@@ -162,25 +166,61 @@ pub fn resolve_iter_index(
     }
 }
 
-fn resolve_prompt(root: &Path, prompt: &str) -> Option<PathBuf> {
-    let local = root.join(format!(".sgf/prompts/{prompt}"));
-    if local.exists() {
-        return Some(local);
+/// Renders an iter's prompt. A task from `sgf next` fills the
+/// [`TASK_VARS`](crate::next::TASK_VARS) placeholders, or is appended as its
+/// own section when the template uses none of them.
+fn render_iter_prompt(
+    root: &Path,
+    template: &str,
+    iter: &IterDefinition,
+    config: &CursusConfig,
+) -> String {
+    let Some(task) = &config.task else {
+        return crate::prompt::render(root, template, &iter.vars, config.spec.as_deref());
+    };
+    let mut vars = task.vars();
+    vars.extend(iter.vars.clone());
+    let mut assembled = crate::prompt::render(root, template, &vars, config.spec.as_deref());
+    let placed = crate::prompt::placeholders(template)
+        .iter()
+        .any(|name| crate::next::TASK_VARS.contains(&name.as_str()));
+    if !placed {
+        assembled.push_str(&task.prompt_section());
     }
-    let global = std::env::var("HOME")
-        .ok()
-        .map(|h| PathBuf::from(h).join(format!(".sgf/prompts/{prompt}")))?;
-    if global.exists() {
-        return Some(global);
+    assembled
+}
+
+/// Fills the template's `{{name}}` placeholders from live project context,
+/// the iter's `vars`, and `{{spec}}`. Returns the template itself when
+/// nothing was substituted, otherwise a copy written to the run directory.
+fn assemble_prompt(
+    root: &Path,
+    run_id: &str,
+    iter: &IterDefinition,
+    config: &CursusConfig,
+    template_path: &Path,
+) -> io::Result<PathBuf> {
+    let template = fs::read_to_string(template_path)?;
+    let assembled = render_iter_prompt(root, &template, iter, config);
+    if assembled == template {
+        return Ok(template_path.to_path_buf());
     }
-    None
+    let path = assembled_prompt_path(root, run_id, iter);
+    fs::write(&path, assembled)?;
+    Ok(path)
 }
 
-fn resolve_agent_command(config: &CursusConfig) -> String {
+fn assembled_prompt_path(root: &Path, run_id: &str, iter: &IterDefinition) -> PathBuf {
+    state::run_dir(root, run_id).join(format!("prompt-{}.md", iter.name))
+}
+
+fn resolve_agent_command(root: &Path, config: &CursusConfig) -> String {
     if let Some(ref bin) = config.agent_command {
         return bin.clone();
     }
-    std::env::var("SGF_AGENT_COMMAND").unwrap_or_else(|_| "cl".to_string())
+    crate::config::SgfConfig::load(root)
+        .unwrap_or_default()
+        .agent_command()
 }
 
 struct IterInvocation<'a> {
@@ -190,6 +230,8 @@ struct IterInvocation<'a> {
     config: &'a CursusConfig,
     session_id: &'a str,
     prompt_path: &'a Path,
+    iterations: u32,
+    iterations_used: &'a Rc<Cell<u32>>,
     consumed_content: &'a str,
     auto_push: bool,
     effective_mode: &'a Mode,
@@ -215,7 +257,7 @@ fn run_iter(
     retry_config: &RetryConfig,
     controller: &ShutdownController,
 ) -> io::Result<i32> {
-    let agent_cmd = resolve_agent_command(inv.config);
+    let agent_cmd = resolve_agent_command(inv.root, inv.config);
 
     let mut prompt_files = Vec::new();
     if !inv.consumed_content.is_empty() {
@@ -231,6 +273,7 @@ fn run_iter(
     let mut env_vars = vec![
         ("SGF_AGENT".to_string(), "1".to_string()),
         (ctx_env_name, abs_ctx_val),
+        loop_mgmt::actor_env_var(inv.run_id),
     ];
     if std::env::var("SGF_AGENT").is_err() {
         env_vars.push(("SGF_ORCHESTRATOR".to_string(), "1".to_string()));
@@ -243,9 +286,10 @@ fn run_iter(
         afk: *inv.effective_mode == Mode::Afk,
         banner: inv.iter.banner,
         loop_id: Some(inv.run_id.to_string()),
-        iterations: inv.iter.iterations,
+        iterations: inv.iterations,
         prompt: inv.prompt_path.to_string_lossy().to_string(),
         auto_push: inv.auto_push,
+        push_target: crate::push::target(inv.root),
         command: Some(agent_cmd),
         prompt_files,
         log_file: Some(log_path),
@@ -262,11 +306,12 @@ fn run_iter(
         inactivity_timeout: crate::iter_runner::default_inactivity_timeout(),
         stdin_input: None,
         on_iteration_start: None,
-        on_iteration_complete: None,
+        on_iteration_complete: Some(record_iteration(inv)),
         retry_immediate: retry_config.immediate,
         retry_interval_secs: retry_config.interval_secs,
         retry_max_duration_secs: retry_config.max_duration_secs,
         on_retry: build_retry_callback(inv.config.programmatic),
+        tuning: inv.config.tuning.clone(),
     };
 
     let exit_code = iter_runner::run_iteration_loop(iter_config, controller);
@@ -279,6 +324,24 @@ fn run_iter(
     })
 }
 
+/// Counts each finished iteration into `iterations_used` and the run
+/// metadata, so a crash still leaves an accurate count for the resume.
+fn record_iteration(inv: &IterInvocation<'_>) -> iter_runner::IterationCallback {
+    let root = inv.root.to_path_buf();
+    let run_id = inv.run_id.to_string();
+    let used = Rc::clone(inv.iterations_used);
+    let base = used.get();
+    Box::new(move |iteration, _| {
+        used.set(base + iteration);
+        if let Ok(Some(mut metadata)) = state::read_metadata(&root, &run_id) {
+            metadata.iterations_used = used.get();
+            if let Err(e) = state::write_metadata(&root, &metadata) {
+                tracing::warn!(error = %e, "failed to record iteration");
+            }
+        }
+    })
+}
+
 fn run_programmatic_turn(
     inv: &IterInvocation<'_>,
     retry_config: &RetryConfig,
@@ -286,7 +349,7 @@ fn run_programmatic_turn(
     resume_input: Option<&str>,
     user_input: Option<&str>,
 ) -> io::Result<iter_runner::ProgrammaticResult> {
-    let agent_cmd = resolve_agent_command(inv.config);
+    let agent_cmd = resolve_agent_command(inv.root, inv.config);
 
     let mut prompt_files = Vec::new();
     if !inv.consumed_content.is_empty() {
@@ -313,6 +376,7 @@ fn run_programmatic_turn(
     let mut env_vars = vec![
         ("SGF_AGENT".to_string(), "1".to_string()),
         (ctx_env_name, abs_ctx_val),
+        loop_mgmt::actor_env_var(inv.run_id),
     ];
     if std::env::var("SGF_AGENT").is_err() {
         env_vars.push(("SGF_ORCHESTRATOR".to_string(), "1".to_string()));
@@ -333,6 +397,7 @@ fn run_programmatic_turn(
         iterations: 1,
         prompt: main_prompt,
         auto_push: inv.auto_push,
+        push_target: crate::push::target(inv.root),
         command: Some(agent_cmd),
         prompt_files,
         log_file: None,
@@ -350,6 +415,7 @@ fn run_programmatic_turn(
         retry_interval_secs: retry_config.interval_secs,
         retry_max_duration_secs: retry_config.max_duration_secs,
         on_retry: build_retry_callback(inv.config.programmatic),
+        tuning: inv.config.tuning.clone(),
     };
 
     iter_runner::run_programmatic(
@@ -439,7 +505,7 @@ fn prompt_resume_action(meta: &RunMetadata) -> io::Result<ResumeAction> {
     let has_session = meta.current_session_id.is_some();
     if has_session {
         eprintln!("  1. Resume — continue the interrupted conversation");
-        eprintln!("  2. Retry  — re-run the iter from scratch");
+        eprintln!("  2. Retry  — start a new conversation for the iterations left");
         eprintln!("  3. Skip   — advance to the next iter");
         eprintln!("  4. Abort  — mark run as interrupted and exit");
         eprintln!();
@@ -501,8 +567,52 @@ fn run_cursus_loop(
     start_index: usize,
     resume_input: Option<String>,
     resume_session_id: Option<String>,
+) -> io::Result<i32> {
+    let run_id = metadata.run_id.clone();
+    loop_mgmt::begin_loop_record(root, root, &run_id, cursus_name, metadata.spec.as_deref());
+    let result = run_iters(
+        root,
+        cursus_name,
+        def,
+        config,
+        metadata,
+        start_index,
+        resume_input,
+        resume_session_id,
+    );
+    if let Ok(code) = result {
+        let pushing = !config.no_push && def.iters.iter().any(|iter| def.effective_auto_push(iter));
+        crate::push::after_loop(root, &run_id, code, pushing);
+    }
+    loop_mgmt::finish_loop_record(
+        root,
+        root,
+        &run_id,
+        result.as_ref().ok().copied(),
+        metadata.iters_completed.len() as u32,
+    );
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_iters(
+    root: &Path,
+    cursus_name: &str,
+    def: &CursusDefinition,
+    config: &CursusConfig,
+    metadata: &mut RunMetadata,
+    start_index: usize,
+    resume_input: Option<String>,
+    resume_session_id: Option<String>,
 ) -> io::Result<i32> {
     let mut current_index = start_index;
+    // A resume picks the interrupted iter back up where it stopped: same
+    // assembled prompt, remaining iterations only.
+    let mut resuming_iter = metadata.status != RunStatus::Running
+        && start_index == metadata.current_iter_index as usize;
+    if !resuming_iter {
+        metadata.iterations_used = 0;
+    }
     let mut ready_signaled = false;
     let mut resume_input = resume_input;
     let mut resume_session_id = resume_session_id;
@@ -553,7 +663,7 @@ fn run_cursus_loop(
             None => Uuid::new_v4().to_string(),
         };
 
-        let prompt_path = resolve_prompt(root, &iter.prompt).ok_or_else(|| {
+        let prompt_path = crate::prompt::resolve_template(root, &iter.prompt).ok_or_else(|| {
             let msg = format!("prompt not found: {}", iter.prompt);
             emit_if_programmatic(
                 config,
@@ -565,8 +675,22 @@ fn run_cursus_loop(
             );
             io::Error::new(io::ErrorKind::NotFound, msg)
         })?;
+        let assembled = assembled_prompt_path(root, &metadata.run_id, iter);
+        let prompt_path = if resuming_iter && assembled.exists() {
+            assembled
+        } else {
+            assemble_prompt(root, &metadata.run_id, iter, config, &prompt_path)?
+        };
 
-        let auto_push = !config.no_push && def.effective_auto_push(iter);
+        // An exhausted budget (a stalled iter being retried) starts over.
+        if metadata.iterations_used >= iter.iterations() {
+            metadata.iterations_used = 0;
+        }
+        let iterations = iter.iterations() - metadata.iterations_used;
+        resuming_iter = false;
+
+        let auto_push =
+            !config.no_push && def.effective_auto_push(iter) && crate::push::per_iteration(root);
 
         // Emit context_consumed events for each consumed key
         if config.programmatic {
@@ -585,7 +709,7 @@ fn run_cursus_loop(
             &Event::IterStart {
                 iter: iter.name.clone(),
                 mode: mode_str(&effective_mode).to_string(),
-                iteration: iter.iterations,
+                iteration: iter.iterations(),
                 session_id: session_id.clone(),
             },
         );
@@ -598,15 +722,23 @@ fn run_cursus_loop(
                     current_index + 1,
                     def.iters.len()
                 ),
-                &format!(
-                    "mode: {} · iterations: {}",
-                    mode_str(&effective_mode),
-                    iter.iterations
-                ),
+                &if iterations < iter.iterations() {
+                    format!(
+                        "mode: {} · iterations: {iterations} left of {}",
+                        mode_str(&effective_mode),
+                        iter.iterations()
+                    )
+                } else {
+                    format!(
+                        "mode: {} · iterations: {iterations}",
+                        mode_str(&effective_mode)
+                    )
+                },
             );
         }
 
         let resuming = resuming_session.is_some();
+        let iterations_used = Rc::new(Cell::new(metadata.iterations_used));
         let inv = IterInvocation {
             root,
             run_id: &metadata.run_id,
@@ -614,6 +746,8 @@ fn run_cursus_loop(
             config,
             session_id: &session_id,
             prompt_path: &prompt_path,
+            iterations,
+            iterations_used: &iterations_used,
             consumed_content: &consumed_content,
             auto_push,
             effective_mode: &effective_mode,
@@ -661,7 +795,7 @@ fn run_cursus_loop(
             let waiting_for_input = !has_any_sentinel(root) && turn_result.exit_code == 0;
 
             if let Some(ref before) = head_before {
-                vcs_utils::auto_push_if_changed(before, |msg| {
+                vcs_utils::auto_push_to(before, &crate::push::target(root), |msg| {
                     style::print_action(msg);
                 });
             }
@@ -695,6 +829,7 @@ fn run_cursus_loop(
         } else {
             run_iter(&inv, &def.retry, &controller)?
         };
+        metadata.iterations_used = iterations_used.get();
 
         if exit_code == 130 {
             metadata.current_session_id = Some(session_id.clone());
@@ -724,7 +859,7 @@ fn run_cursus_loop(
             &Event::IterComplete {
                 iter: iter.name.clone(),
                 outcome: outcome.to_string(),
-                iterations_used: iter.iterations,
+                iterations_used: iter.iterations(),
             },
         );
 
@@ -748,6 +883,43 @@ fn run_cursus_loop(
                     iter: iter.name.clone(),
                 },
             );
+            match crate::test_issues::on_produced(
+                root,
+                key,
+                &context::context_file_path(root, &metadata.run_id, key),
+                metadata.spec.as_deref(),
+                &metadata.run_id,
+            ) {
+                Ok(Some(summary)) => style::print_action(&summary.describe()),
+                Ok(None) => {}
+                Err(e) => style::print_warning(&format!("pensa sync for {key} failed: {e}")),
+            }
+            match crate::task_plan::on_produced(
+                root,
+                key,
+                &context::context_file_path(root, &metadata.run_id, key),
+                metadata.spec.as_deref(),
+            ) {
+                Ok(Some(summary)) => {
+                    style::print_action(&summary.describe());
+                    for (id, title) in &summary.created {
+                        eprintln!("  {id}  {title}");
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => style::print_warning(&format!("seeding pensa from {key} failed: {e}")),
+            }
+        }
+
+        match crate::verify_findings::sync(root, metadata.spec.as_deref(), &metadata.run_id) {
+            Ok(Some(summary)) => {
+                style::print_action(&summary.describe());
+                for line in &summary.lines {
+                    eprintln!("  {line}");
+                }
+            }
+            Ok(None) => {}
+            Err(e) => style::print_warning(&format!("filing verify findings failed: {e}")),
         }
 
         let transition = resolve_transition(iter, &outcome)?;
@@ -761,7 +933,7 @@ fn run_cursus_loop(
                 if config.programmatic {
                     events::emit_event(&Event::Stall {
                         iter: iter.name.clone(),
-                        iterations_attempted: iter.iterations,
+                        iterations_attempted: iter.iterations(),
                         actions: vec![
                             "resume".to_string(),
                             "retry".to_string(),
@@ -775,7 +947,12 @@ fn run_cursus_loop(
                         resume_command: format!("sgf {cursus_name} --resume {}", metadata.run_id),
                     });
                 } else {
-                    print_stall_banner(cursus_name, &iter.name, iter.iterations, &metadata.run_id);
+                    print_stall_banner(
+                        cursus_name,
+                        &iter.name,
+                        iter.iterations(),
+                        &metadata.run_id,
+                    );
                 }
                 state::remove_pid_file(root, &metadata.run_id);
                 break 2;
@@ -797,6 +974,7 @@ fn run_cursus_loop(
                         },
                     );
                     current_index = next_idx;
+                    metadata.iterations_used = 0;
                 }
                 None => {
                     metadata.status = RunStatus::Completed;
@@ -825,6 +1003,120 @@ fn run_cursus_loop(
     Ok(exit_code)
 }
 
+pub fn dry_run(
+    root: &Path,
+    cursus_name: &str,
+    def: &CursusDefinition,
+    config: &CursusConfig,
+) -> io::Result<String> {
+    use std::fmt::Write as _;
+
+    let run_id = state::generate_run_id(cursus_name);
+    let agent_cmd = resolve_agent_command(root, config);
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let agent_path = crate::doctor::find_executable(&agent_cmd, &path)
+        .map_or_else(|| "not found".to_string(), |p| p.display().to_string());
+
+    let mut out = String::new();
+    let _ = writeln!(out, "loop id:  {run_id}");
+    let _ = writeln!(out, "agent:    {agent_cmd} ({agent_path})");
+    if !config.tuning.env.is_empty() {
+        let names: Vec<&str> = config.tuning.env.iter().map(|(k, _)| k.as_str()).collect();
+        let _ = writeln!(out, "env:      {}", names.join(", "));
+    }
+    match crate::recovery::plan(root, None)? {
+        Some(plan) => {
+            let _ = writeln!(out, "recovery:");
+            for line in plan.describe() {
+                let _ = writeln!(out, "  {line}");
+            }
+        }
+        None => {
+            let _ = writeln!(out, "recovery: none needed");
+        }
+    }
+
+    for (index, iter) in def.iters.iter().enumerate() {
+        let template_path =
+            crate::prompt::resolve_template(root, &iter.prompt).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("prompt not found: {}", iter.prompt),
+                )
+            })?;
+        let template = fs::read_to_string(&template_path)?;
+        let assembled = render_iter_prompt(root, &template, iter, config);
+        let prompt_path = if assembled == template {
+            template_path
+        } else {
+            assembled_prompt_path(root, &run_id, iter)
+        };
+        let mode = config
+            .mode_override
+            .clone()
+            .unwrap_or_else(|| iter.mode.clone());
+
+        let iter_config = IterRunnerConfig {
+            afk: mode == Mode::Afk,
+            banner: iter.banner,
+            loop_id: Some(run_id.clone()),
+            iterations: iter.iterations(),
+            prompt: prompt_path.to_string_lossy().to_string(),
+            auto_push: !config.no_push && def.effective_auto_push(iter),
+            push_target: Default::default(),
+            command: Some(agent_cmd.clone()),
+            prompt_files: Vec::new(),
+            log_file: None,
+            session_id: None,
+            resume: None,
+            env_vars: Vec::new(),
+            runner_name: None,
+            work_dir: Some(root.to_path_buf()),
+            post_result_timeout: iter_runner::default_post_result_timeout(),
+            inactivity_timeout: iter_runner::default_inactivity_timeout(),
+            stdin_input: None,
+            on_iteration_start: None,
+            on_iteration_complete: None,
+            retry_immediate: def.retry.immediate,
+            retry_interval_secs: def.retry.interval_secs,
+            retry_max_duration_secs: def.retry.max_duration_secs,
+            on_retry: None,
+            tuning: config.tuning.clone(),
+        };
+        let args = iter_runner::agent_args(&iter_config, true, 1, "<session-id>");
+
+        let _ = writeln!(
+            out,
+            "\niter {}/{}: {} ({}, {} iterations, auto-push {})",
+            index + 1,
+            def.iters.len(),
+            iter.name,
+            mode_str(&mode),
+            iter.iterations(),
+            if iter_config.auto_push { "on" } else { "off" }
+        );
+        let command_line: Vec<String> = std::iter::once(agent_cmd.as_str())
+            .chain(args.iter().map(String::as_str))
+            .map(shell_quote)
+            .collect();
+        let _ = writeln!(out, "  {}", command_line.join(" "));
+        let _ = writeln!(out, "  prompt: {}", prompt_path.display());
+        for line in assembled.lines() {
+            let _ = writeln!(out, "  | {line}");
+        }
+    }
+    Ok(out)
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./@=:,+%".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
 pub fn run_cursus(
     root: &Path,
     cursus_name: &str,
@@ -867,7 +1159,7 @@ pub fn run_cursus(
                 .map(|i| IterSummary {
                     name: i.name.clone(),
                     mode: mode_str(&i.mode).to_string(),
-                    iterations: i.iterations,
+                    iterations: i.iterations(),
                 })
                 .collect(),
         },
@@ -896,12 +1188,14 @@ pub fn resume_cursus(root: &Path, run_id: &str) -> io::Result<i32> {
         ));
     }
 
-    let resolved = crate::cursus::resolve_cursus(root, &metadata.cursus).ok_or_else(|| {
-        io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("cursus definition not found: {}", metadata.cursus),
-        )
-    })?;
+    let resolved = crate::cursus::resolve_cursus(root, &metadata.cursus)
+        .or_else(|| crate::cursus::resolve_stage(root, &metadata.cursus))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("cursus definition not found: {}", metadata.cursus),
+            )
+        })?;
 
     let def = &resolved.definition;
 
@@ -940,6 +1234,8 @@ pub fn resume_cursus(root: &Path, run_id: &str) -> io::Result<i32> {
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         state::write_pid_file(root, run_id)?;
@@ -966,7 +1262,7 @@ pub fn resume_cursus(root: &Path, run_id: &str) -> io::Result<i32> {
         }
         events::emit_event(&Event::Stall {
             iter: metadata.current_iter.clone(),
-            iterations_attempted: def.iters[current_index].iterations,
+            iterations_attempted: def.iters[current_index].iterations(),
             actions,
         });
 
@@ -997,6 +1293,8 @@ pub fn resume_cursus(root: &Path, run_id: &str) -> io::Result<i32> {
         monitor_stdin_override: if programmatic { Some(false) } else { None },
         programmatic,
         initial_input: None,
+        tuning: Default::default(),
+        task: None,
     };
 
     match action {
@@ -1122,7 +1420,7 @@ mod tests {
             name: name.to_string(),
             prompt: format!("{name}.md"),
             mode,
-            iterations,
+            iterations: Some(iterations),
             produces: None,
             consumes: vec![],
             auto_push: None,
@@ -1132,6 +1430,7 @@ mod tests {
                 on_reject: on_reject.map(|s| s.to_string()),
                 on_revise: on_revise.map(|s| s.to_string()),
             },
+            vars: HashMap::new(),
         }
     }
 
@@ -1461,6 +1760,7 @@ mod tests {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn make_iter_full(
         name: &str,
         mode: Mode,
@@ -1475,7 +1775,7 @@ mod tests {
             name: name.to_string(),
             prompt: format!("{name}.md"),
             mode,
-            iterations,
+            iterations: Some(iterations),
             produces: produces.map(|s| s.to_string()),
             consumes: consumes.into_iter().map(|s| s.to_string()).collect(),
             auto_push: None,
@@ -1485,6 +1785,7 @@ mod tests {
                 on_reject: on_reject.map(|s| s.to_string()),
                 on_revise: on_revise.map(|s| s.to_string()),
             },
+            vars: HashMap::new(),
         }
     }
 
@@ -1517,6 +1818,8 @@ mod tests {
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "build", &def, &config).unwrap();
@@ -1563,6 +1866,8 @@ mod tests {
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "build", &def, &config).unwrap();
@@ -1613,6 +1918,8 @@ mod tests {
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "spec", &def, &config).unwrap();
@@ -1681,6 +1988,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "spec", &def, &config).unwrap();
@@ -1761,6 +2070,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "spec", &def, &config).unwrap();
@@ -1847,6 +2158,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "pipeline", &def, &config).unwrap();
@@ -1911,6 +2224,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "build", &def, &config).unwrap();
@@ -1953,6 +2268,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "build", &def, &config).unwrap();
@@ -1969,6 +2286,59 @@ exit 0
         assert_eq!(meta.spec.as_deref(), Some("auth"));
     }
 
+    #[test]
+    fn run_cursus_assembles_prompt_vars() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        setup_cursus_project(root, &[]);
+        fs::write(
+            root.join(".sgf/prompts/docs.md"),
+            "Document {{spec}} for {{audience}}.\n{{spec_content}}",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("specs")).unwrap();
+        fs::write(root.join("specs/auth.md"), "# Auth\n").unwrap();
+
+        let mock = mock_script(
+            root,
+            "mock_agent.sh",
+            &format!(
+                "#!/bin/sh\nfor a in \"$@\"; do case \"$a\" in @*) cp \"${{a#@}}\" \"{root}/seen.md\";; esac; done\ntouch \"{root}/.iter-complete\"\nexit 0\n",
+                root = root.display()
+            ),
+        );
+
+        let mut iter = make_iter("docs", Mode::Afk, 1, None, None, None);
+        iter.vars
+            .insert("audience".to_string(), "new users".to_string());
+        let def = make_cursus_def(vec![iter], false);
+
+        let config = CursusConfig {
+            spec: Some("auth".to_string()),
+            mode_override: None,
+            no_push: true,
+            agent_command: Some(mock),
+            skip_preflight: true,
+            monitor_stdin_override: Some(false),
+            programmatic: false,
+            initial_input: None,
+            tuning: Default::default(),
+            task: None,
+        };
+
+        let exit_code = run_cursus(root, "docs", &def, &config).unwrap();
+        assert_eq!(exit_code, 0);
+        assert_eq!(
+            fs::read_to_string(root.join("seen.md")).unwrap(),
+            "Document auth for new users.\n# Auth"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join(".sgf/prompts/docs.md")).unwrap(),
+            "Document {{spec}} for {{audience}}.\n{{spec_content}}",
+            "template is left untouched"
+        );
+    }
+
     #[test]
     fn run_cursus_pid_file_cleaned_up() {
         let tmp = TempDir::new().unwrap();
@@ -1998,6 +2368,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         run_cursus(root, "build", &def, &config).unwrap();
@@ -2031,6 +2403,8 @@ exit 0
             monitor_stdin_override: None,
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let err = run_cursus(root, "empty", &def, &config).unwrap_err();
@@ -2083,6 +2457,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "spec", &def, &config).unwrap();
@@ -2111,7 +2487,7 @@ exit 0
         fs::create_dir_all(root.join(".sgf/prompts")).unwrap();
         fs::write(root.join(".sgf/prompts/build.md"), "prompt content").unwrap();
 
-        let result = resolve_prompt(root, "build.md");
+        let result = crate::prompt::resolve_template(root, "build.md");
         assert!(result.is_some());
         assert!(result.unwrap().ends_with(".sgf/prompts/build.md"));
     }
@@ -2119,7 +2495,7 @@ exit 0
     #[test]
     fn resolve_prompt_missing() {
         let tmp = TempDir::new().unwrap();
-        let result = resolve_prompt(tmp.path(), "nonexistent.md");
+        let result = crate::prompt::resolve_template(tmp.path(), "nonexistent.md");
         assert!(result.is_none());
     }
 
@@ -2144,6 +2520,7 @@ exit 0
                 status: RunStatus::Completed,
                 current_iter: "build".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -2173,6 +2550,7 @@ exit 0
                 status: RunStatus::Stalled,
                 current_iter: "build".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -2203,6 +2581,7 @@ exit 0
                 status: RunStatus::Stalled,
                 current_iter: "deleted-iter".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -2230,6 +2609,56 @@ prompt = "build.md"
         assert!(err.to_string().contains("iter 'deleted-iter' not found"));
     }
 
+    #[test]
+    fn run_cursus_loop_resume_spends_only_remaining_iterations() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        setup_cursus_project(root, &["build.md"]);
+
+        let runs = root.join("runs.txt");
+        let mock_agent = mock_script(
+            root,
+            "mock_agent.sh",
+            &format!("#!/bin/sh\necho \"$@\" >> \"{}\"\nexit 0\n", runs.display()),
+        );
+        let def = make_cursus_def(
+            vec![make_iter("build", Mode::Afk, 5, None, None, None)],
+            false,
+        );
+        let config = CursusConfig {
+            spec: None,
+            mode_override: None,
+            no_push: true,
+            agent_command: Some(mock_agent),
+            skip_preflight: true,
+            monitor_stdin_override: Some(false),
+            programmatic: false,
+            initial_input: None,
+            tuning: Default::default(),
+            task: None,
+        };
+
+        let mut metadata = RunMetadata::new("build", "build", None, None);
+        metadata.status = RunStatus::Interrupted;
+        metadata.iterations_used = 3;
+        state::create_run_dir(root, &metadata.run_id).unwrap();
+        state::write_metadata(root, &metadata).unwrap();
+        let assembled = assembled_prompt_path(root, &metadata.run_id, &def.iters[0]);
+        fs::write(&assembled, "assembled when the loop started").unwrap();
+
+        run_cursus_loop(root, "build", &def, &config, &mut metadata, 0, None, None).unwrap();
+
+        let runs = fs::read_to_string(&runs).unwrap();
+        assert_eq!(runs.lines().count(), 2, "{runs}");
+        assert!(runs.contains(&*assembled.to_string_lossy()), "{runs}");
+        assert_eq!(metadata.status, RunStatus::Stalled);
+        assert_eq!(metadata.iterations_used, 5);
+        let saved = state::read_metadata(root, &metadata.run_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.iterations_used, 5);
+    }
+
     #[test]
     fn run_cursus_loop_resumes_from_stalled_iter() {
         let tmp = TempDir::new().unwrap();
@@ -2263,6 +2692,8 @@ prompt = "build.md"
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let run_id = "spec-20260317T140000";
@@ -2275,6 +2706,7 @@ prompt = "build.md"
             status: RunStatus::Stalled,
             current_iter: "review".to_string(),
             current_iter_index: 1,
+            iterations_used: 0,
             iters_completed: vec![CompletedIter {
                 name: "draft".to_string(),
                 session_id: "sess-1".to_string(),
@@ -2333,6 +2765,8 @@ prompt = "build.md"
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let run_id = "spec-20260317T140000";
@@ -2345,6 +2779,7 @@ prompt = "build.md"
             status: RunStatus::Stalled,
             current_iter: "draft".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -2457,6 +2892,8 @@ prompt = "build.md"
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         assert!(config.programmatic);
@@ -2476,6 +2913,8 @@ prompt = "build.md"
             monitor_stdin_override: None,
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         assert!(!config.programmatic);
@@ -2510,6 +2949,8 @@ prompt = "build.md"
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "build", &def, &config).unwrap();
@@ -2574,6 +3015,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "spec", &def, &config).unwrap();
@@ -2615,6 +3058,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "build", &def, &config).unwrap();
@@ -2687,6 +3132,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "pipeline", &def, &config).unwrap();
@@ -2716,6 +3163,8 @@ exit 0
             monitor_stdin_override: None,
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
         // Should not panic when emitting events
         emit_if_programmatic(
@@ -2739,6 +3188,8 @@ exit 0
             monitor_stdin_override: None,
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
         // Should not emit anything (no way to assert, but verifies no panic)
         emit_if_programmatic(
@@ -2799,6 +3250,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "chat", &def, &config).unwrap();
@@ -2845,6 +3298,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "chat", &def, &config).unwrap();
@@ -2892,6 +3347,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "chat", &def, &config).unwrap();
@@ -2938,6 +3395,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "build", &def, &config).unwrap();
@@ -2999,6 +3458,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         // First run: should get WaitingForInput
@@ -3026,6 +3487,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         state::write_pid_file(root, &run_id).unwrap();
@@ -3093,6 +3556,8 @@ exit 0
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         // Both iters complete because the mock always creates .iter-complete
@@ -3142,6 +3607,8 @@ exit 1
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "chat", &def, &config).unwrap();
@@ -3175,6 +3642,7 @@ exit 1
                 status: RunStatus::Completed,
                 current_iter: "build".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -3231,7 +3699,7 @@ exit 1
 
         let mock_agent = mock_script(root, "mock_agent.sh", "#!/bin/sh\nexit 2\n");
 
-        let def = make_cursus_def(
+        let _def = make_cursus_def(
             vec![make_iter("build", Mode::Afk, 5, None, None, None)],
             false,
         );
@@ -3245,6 +3713,7 @@ exit 1
             status: RunStatus::Stalled,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: vec![],
             spec: None,
             mode_override: None,
@@ -3255,7 +3724,7 @@ exit 1
         };
         state::write_metadata(root, &metadata).unwrap();
 
-        let config = CursusConfig {
+        let _config = CursusConfig {
             spec: None,
             mode_override: None,
             no_push: true,
@@ -3264,6 +3733,8 @@ exit 1
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         metadata.status = RunStatus::Interrupted;
@@ -3304,6 +3775,7 @@ exit 1
             status: RunStatus::Stalled,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: vec![],
             spec: None,
             mode_override: None,
@@ -3322,6 +3794,8 @@ exit 1
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code =
@@ -3363,6 +3837,7 @@ exit 1
             status: RunStatus::Stalled,
             current_iter: "draft".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: vec![],
             spec: None,
             mode_override: None,
@@ -3381,6 +3856,8 @@ exit 1
             monitor_stdin_override: Some(false),
             programmatic: true,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code =
@@ -3439,6 +3916,8 @@ touch "$(dirname "$0")/.iter-complete"
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "test", &def, &config).unwrap();
@@ -3496,6 +3975,8 @@ exit 1
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus(root, "test", &def, &config).unwrap();
@@ -3547,6 +4028,7 @@ exit 1
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: vec![],
             spec: None,
             mode_override: None,
@@ -3565,6 +4047,8 @@ exit 1
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code =
@@ -3613,6 +4097,7 @@ exit 1
             status: RunStatus::Stalled,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: vec![],
             spec: None,
             mode_override: None,
@@ -3631,6 +4116,8 @@ exit 1
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code = run_cursus_loop(
@@ -3687,6 +4174,7 @@ exit 1
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: vec![],
             spec: None,
             mode_override: None,
@@ -3705,6 +4193,8 @@ exit 1
             monitor_stdin_override: Some(false),
             programmatic: false,
             initial_input: None,
+            tuning: Default::default(),
+            task: None,
         };
 
         let exit_code =
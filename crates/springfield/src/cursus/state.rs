@@ -43,6 +43,10 @@ pub struct RunMetadata {
     pub status: RunStatus,
     pub current_iter: String,
     pub current_iter_index: u32,
+    /// Iterations of `current_iter` already run, so a resumed iter only gets
+    /// what is left of its budget.
+    #[serde(default)]
+    pub iterations_used: u32,
     pub iters_completed: Vec<CompletedIter>,
     pub spec: Option<String>,
     pub mode_override: Option<String>,
@@ -69,6 +73,7 @@ impl RunMetadata {
             status: RunStatus::Running,
             current_iter: first_iter.to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: spec.map(|s| s.to_string()),
             mode_override: mode_override.map(|m| m.to_string()),
@@ -154,7 +159,7 @@ pub fn read_pid(root: &Path, run_id: &str) -> Option<u32> {
 }
 
 pub fn is_pid_alive(pid: u32) -> bool {
-    unsafe { libc::kill(pid as i32, 0) == 0 }
+    crate::platform::pid_alive(pid)
 }
 
 pub fn is_stale_run(root: &Path, run_id: &str) -> io::Result<bool> {
@@ -271,6 +276,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "draft".to_string(),
             current_iter_index: 1,
+            iterations_used: 0,
             iters_completed: vec![CompletedIter {
                 name: "discuss".to_string(),
                 session_id: "a1b2c3d4".to_string(),
@@ -314,6 +320,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -389,6 +396,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -416,6 +424,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -444,6 +453,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -470,6 +480,7 @@ mod tests {
             status: RunStatus::Completed,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -503,6 +514,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -523,6 +535,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -570,6 +583,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -623,6 +637,7 @@ mod tests {
             status: RunStatus::WaitingForInput,
             current_iter: "build".to_string(),
             current_iter_index: 0,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
@@ -686,6 +701,7 @@ mod tests {
                 status: RunStatus::Stalled,
                 current_iter: "draft".to_string(),
                 current_iter_index: 1,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -707,6 +723,7 @@ mod tests {
                 status: RunStatus::Interrupted,
                 current_iter: "build".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -728,6 +745,7 @@ mod tests {
                 status: RunStatus::Completed,
                 current_iter: "test".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -749,6 +767,7 @@ mod tests {
                 status: RunStatus::Running,
                 current_iter: "verify".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -770,6 +789,7 @@ mod tests {
                 status: RunStatus::WaitingForInput,
                 current_iter: "discuss".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -813,6 +833,7 @@ mod tests {
                 status: RunStatus::Stalled,
                 current_iter: "build".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -834,6 +855,7 @@ mod tests {
                 status: RunStatus::Stalled,
                 current_iter: "build".to_string(),
                 current_iter_index: 0,
+                iterations_used: 0,
                 iters_completed: Vec::new(),
                 spec: None,
                 mode_override: None,
@@ -864,6 +886,7 @@ mod tests {
             status: RunStatus::Stalled,
             current_iter: "draft".to_string(),
             current_iter_index: 1,
+            iterations_used: 0,
             iters_completed: vec![CompletedIter {
                 name: "discuss".to_string(),
                 session_id: "sess-1".to_string(),
@@ -903,6 +926,7 @@ mod tests {
             status: RunStatus::Running,
             current_iter: "review".to_string(),
             current_iter_index: 3,
+            iterations_used: 0,
             iters_completed: Vec::new(),
             spec: None,
             mode_override: None,
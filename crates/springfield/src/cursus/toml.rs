@@ -3,19 +3,14 @@ use std::collections::{HashMap, HashSet};
 use std::io;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Mode {
     Afk,
+    #[default]
     Interactive,
 }
 
-impl Default for Mode {
-    fn default() -> Self {
-        Self::Interactive
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Default)]
 pub struct Transitions {
     pub on_reject: Option<String>,
@@ -28,8 +23,9 @@ pub struct IterDefinition {
     pub prompt: String,
     #[serde(default)]
     pub mode: Mode,
-    #[serde(default = "default_iterations")]
-    pub iterations: u32,
+    /// Unset means the stage default; read it through [`IterDefinition::iterations`].
+    #[serde(default)]
+    pub iterations: Option<u32>,
     pub produces: Option<String>,
     #[serde(default)]
     pub consumes: Vec<String>,
@@ -39,12 +35,32 @@ pub struct IterDefinition {
     pub banner: bool,
     #[serde(default)]
     pub transitions: Transitions,
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
 }
 
 fn default_iterations() -> u32 {
     1
 }
 
+/// Iterations for an iter whose definition leaves `iterations` out, by the
+/// stage it is named after. `[stages.<name>] iterations` and `-n` override it.
+pub fn default_stage_iterations(name: &str) -> u32 {
+    match name {
+        "verify" => 10,
+        "build" => 30,
+        "test" => 15,
+        _ => default_iterations(),
+    }
+}
+
+impl IterDefinition {
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+            .unwrap_or_else(|| default_stage_iterations(&self.name))
+    }
+}
+
 fn default_trigger() -> String {
     "manual".to_string()
 }
@@ -118,14 +134,15 @@ pub fn parse_file(path: &Path) -> Result<CursusDefinition, io::Error> {
 pub fn clamp_iterations(def: &mut CursusDefinition) {
     use crate::iter_runner::MAX_ITERATIONS;
     for iter in &mut def.iters {
-        if iter.iterations > MAX_ITERATIONS {
+        let requested = iter.iterations();
+        if requested > MAX_ITERATIONS {
             tracing::warn!(
                 iter = %iter.name,
-                requested = iter.iterations,
+                requested,
                 max = MAX_ITERATIONS,
                 "clamping iter iterations to hard limit"
             );
-            iter.iterations = MAX_ITERATIONS;
+            iter.iterations = Some(MAX_ITERATIONS);
         }
     }
 }
@@ -280,7 +297,7 @@ iterations = 30
         assert_eq!(iter.name, "build");
         assert_eq!(iter.prompt, "build.md");
         assert_eq!(iter.mode, Mode::Interactive);
-        assert_eq!(iter.iterations, 30);
+        assert_eq!(iter.iterations(), 30);
         assert!(iter.produces.is_none());
         assert!(iter.consumes.is_empty());
         assert!(iter.auto_push.is_none());
@@ -390,7 +407,7 @@ prompt = "run.md"
 
         let iter = &def.iters[0];
         assert_eq!(iter.mode, Mode::Interactive);
-        assert_eq!(iter.iterations, 1);
+        assert_eq!(iter.iterations(), 1);
         assert!(iter.consumes.is_empty());
         assert!(iter.produces.is_none());
         assert!(iter.auto_push.is_none());
@@ -399,6 +416,34 @@ prompt = "run.md"
         assert!(iter.transitions.on_revise.is_none());
     }
 
+    #[test]
+    fn stage_iteration_defaults_fill_unset_iterations() {
+        let toml = r#"
+description = "Pipeline"
+
+[[iter]]
+name = "spec"
+prompt = "spec.md"
+
+[[iter]]
+name = "build"
+prompt = "build.md"
+
+[[iter]]
+name = "verify"
+prompt = "verify.md"
+
+[[iter]]
+name = "test"
+prompt = "test.md"
+iterations = 4
+"#;
+        let def = parse(toml).unwrap();
+        let counts: Vec<u32> = def.iters.iter().map(|i| i.iterations()).collect();
+        assert_eq!(counts, vec![1, 30, 10, 4]);
+        assert_eq!(default_stage_iterations("test"), 15);
+    }
+
     #[test]
     fn parse_banner_true() {
         let toml = r#"
@@ -520,7 +565,7 @@ iterations = 5
 "#;
         let def = parse(toml).unwrap();
         assert_eq!(def.iters[0].mode, Mode::Interactive);
-        assert_eq!(def.iters[0].iterations, 5);
+        assert_eq!(def.iters[0].iterations(), 5);
         // validate() should succeed (warning, not error)
         assert!(validate(&def).is_ok());
     }
@@ -617,13 +662,14 @@ auto_push = false
                 name: iter_name.to_string(),
                 prompt: format!("{iter_name}.md"),
                 mode: Mode::default(),
-                iterations: 1,
+                iterations: Some(1),
                 produces: None,
                 consumes: vec![],
                 auto_push: None,
                 next: None,
                 banner: false,
                 transitions: Transitions::default(),
+                vars: HashMap::new(),
             }],
         }
     }
@@ -657,25 +703,25 @@ auto_push = false
     #[test]
     fn clamp_iterations_above_max() {
         let mut def = make_def("Test", None, "build");
-        def.iters[0].iterations = 2000;
+        def.iters[0].iterations = Some(2000);
         clamp_iterations(&mut def);
-        assert_eq!(def.iters[0].iterations, 1000);
+        assert_eq!(def.iters[0].iterations(), 1000);
     }
 
     #[test]
     fn clamp_iterations_at_max_unchanged() {
         let mut def = make_def("Test", None, "build");
-        def.iters[0].iterations = 1000;
+        def.iters[0].iterations = Some(1000);
         clamp_iterations(&mut def);
-        assert_eq!(def.iters[0].iterations, 1000);
+        assert_eq!(def.iters[0].iterations(), 1000);
     }
 
     #[test]
     fn clamp_iterations_below_max_unchanged() {
         let mut def = make_def("Test", None, "build");
-        def.iters[0].iterations = 500;
+        def.iters[0].iterations = Some(500);
         clamp_iterations(&mut def);
-        assert_eq!(def.iters[0].iterations, 500);
+        assert_eq!(def.iters[0].iterations(), 500);
     }
 
     #[test]
@@ -691,31 +737,33 @@ auto_push = false
                     name: "a".to_string(),
                     prompt: "a.md".to_string(),
                     mode: Mode::default(),
-                    iterations: 1500,
+                    iterations: Some(1500),
                     produces: None,
                     consumes: vec![],
                     auto_push: None,
                     next: None,
                     banner: false,
                     transitions: Transitions::default(),
+                    vars: HashMap::new(),
                 },
                 IterDefinition {
                     name: "b".to_string(),
                     prompt: "b.md".to_string(),
                     mode: Mode::default(),
-                    iterations: 30,
+                    iterations: Some(30),
                     produces: None,
                     consumes: vec![],
                     auto_push: None,
                     next: None,
                     banner: false,
                     transitions: Transitions::default(),
+                    vars: HashMap::new(),
                 },
             ],
         };
         clamp_iterations(&mut def);
-        assert_eq!(def.iters[0].iterations, 1000);
-        assert_eq!(def.iters[1].iterations, 30);
+        assert_eq!(def.iters[0].iterations(), 1000);
+        assert_eq!(def.iters[1].iterations(), 30);
     }
 
     #[test]
@@ -760,13 +808,14 @@ mode = "turbo"
                 name: "build".to_string(),
                 prompt: "build.md".to_string(),
                 mode: Mode::default(),
-                iterations: 1,
+                iterations: Some(1),
                 produces: None,
                 consumes: vec![],
                 auto_push: None,
                 next: None,
                 banner: false,
                 transitions: Transitions::default(),
+                vars: HashMap::new(),
             }],
         };
 
@@ -788,13 +837,14 @@ mode = "turbo"
                 name: "build".to_string(),
                 prompt: "missing.md".to_string(),
                 mode: Mode::default(),
-                iterations: 1,
+                iterations: Some(1),
                 produces: None,
                 consumes: vec![],
                 auto_push: None,
                 next: None,
                 banner: false,
                 transitions: Transitions::default(),
+                vars: HashMap::new(),
             }],
         };
 
@@ -820,25 +870,27 @@ mode = "turbo"
                     name: "build".to_string(),
                     prompt: "build.md".to_string(),
                     mode: Mode::default(),
-                    iterations: 1,
+                    iterations: Some(1),
                     produces: None,
                     consumes: vec![],
                     auto_push: None,
                     next: None,
                     banner: false,
                     transitions: Transitions::default(),
+                    vars: HashMap::new(),
                 },
                 IterDefinition {
                     name: "review".to_string(),
                     prompt: "review.md".to_string(),
                     mode: Mode::default(),
-                    iterations: 1,
+                    iterations: Some(1),
                     produces: None,
                     consumes: vec![],
                     auto_push: None,
                     next: None,
                     banner: false,
                     transitions: Transitions::default(),
+                    vars: HashMap::new(),
                 },
             ],
         };
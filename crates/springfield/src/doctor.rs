@@ -0,0 +1,541 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+
+use crate::config::SgfConfig;
+use crate::{cursus, init, platform, recovery};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub check: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub suggestion: Option<String>,
+    pub fixable: bool,
+    pub fixed: bool,
+}
+
+impl Finding {
+    fn ok(check: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Ok,
+            message: message.into(),
+            suggestion: None,
+            fixable: false,
+            fixed: false,
+        }
+    }
+
+    fn problem(
+        check: &'static str,
+        severity: Severity,
+        message: impl Into<String>,
+        suggestion: impl Into<String>,
+    ) -> Self {
+        Self {
+            check,
+            severity,
+            message: message.into(),
+            suggestion: Some(suggestion.into()),
+            fixable: false,
+            fixed: false,
+        }
+    }
+
+    fn fixable(mut self) -> Self {
+        self.fixable = true;
+        self
+    }
+
+    pub fn is_failure(&self) -> bool {
+        self.severity == Severity::Fail && !self.fixed
+    }
+}
+
+pub fn diagnose(root: &Path, fix: bool) -> Vec<Finding> {
+    let path = env::var_os("PATH").unwrap_or_default();
+    let mut findings = vec![check_git(root), check_push(root), check_platform(root)];
+    findings.extend(check_directories(root, fix));
+    findings.push(check_config(root));
+    findings.push(check_sandbox(root, fix));
+    findings.push(check_deny_rules(root, fix));
+    for (bin, name) in [("pn", "pensa"), ("fm", "forma")] {
+        findings.push(check_tool(bin, name, &path));
+    }
+    findings.push(check_daemons(root, fix, &path));
+    findings.push(check_agent(root, &path));
+    findings.push(check_pty());
+    findings.push(check_prompts(root));
+    findings
+}
+
+fn check_git(root: &Path) -> Finding {
+    let inside = Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(root)
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if inside {
+        Finding::ok("git", "inside a git work tree")
+    } else {
+        Finding::problem(
+            "git",
+            Severity::Fail,
+            "not a git repository",
+            "run `git init` (loops commit their work)",
+        )
+    }
+}
+
+fn git_output(root: &Path, args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn check_push(root: &Path) -> Finding {
+    if git_output(root, &["rev-parse", "--is-inside-work-tree"]).is_none() {
+        return Finding::problem(
+            "push",
+            Severity::Warn,
+            "skipped: not a git repository",
+            "fix the git check first",
+        );
+    }
+    let missing: Vec<&str> = ["user.name", "user.email"]
+        .into_iter()
+        .filter(|key| git_output(root, &["config", key]).is_none())
+        .collect();
+    if !missing.is_empty() {
+        return Finding::problem(
+            "push",
+            Severity::Fail,
+            format!("git {} not set", missing.join(" and ")),
+            "set them with `git config --global user.name` and `user.email` (loops commit their work)",
+        );
+    }
+    let config = SgfConfig::load(root).unwrap_or_default();
+    if !config.auto_push() {
+        return Finding::ok("push", "auto-push is off");
+    }
+    let target = config.push.target();
+    if target.remote.is_some() || target.branch.is_some() {
+        let remote = target.remote.as_deref().unwrap_or("origin");
+        return match git_output(root, &["remote", "get-url", remote]) {
+            Some(url) => Finding::ok("push", format!("pushes to {remote} ({url})")),
+            None => Finding::problem(
+                "push",
+                Severity::Fail,
+                format!("[push] remote `{remote}` does not exist"),
+                format!("add it with `git remote add {remote} <url>`, or change [push] remote"),
+            ),
+        };
+    }
+    match git_output(root, &["rev-parse", "--abbrev-ref", "@{u}"]) {
+        Some(upstream) => Finding::ok("push", format!("pushes to {upstream}")),
+        None => Finding::problem(
+            "push",
+            Severity::Warn,
+            "the current branch has no upstream, so auto-push will fail",
+            "run `git push -u origin <branch>`, set [push] remote, or pass --no-push",
+        ),
+    }
+}
+
+fn check_pty() -> Finding {
+    match crate::iter_runner::pty_tee::open_pty() {
+        Ok(_) => Finding::ok("pty", "pseudo-terminals available"),
+        Err(e) => Finding::problem(
+            "pty",
+            Severity::Fail,
+            format!("cannot open a pseudo-terminal: {e}"),
+            "interactive iterations need one; check /dev/ptmx (in a container, mount devpts)",
+        ),
+    }
+}
+
+fn check_platform(root: &Path) -> Finding {
+    if !platform::is_wsl() {
+        return Finding::ok("platform", env::consts::OS);
+    }
+    match platform::windows_drive(root) {
+        Some(drive) => Finding::problem(
+            "platform",
+            Severity::Warn,
+            format!(
+                "WSL checkout on the Windows drive {drive}:, which keeps no Unix permissions and is slow"
+            ),
+            "clone the project into the Linux filesystem, e.g. under ~",
+        ),
+        None => Finding::ok("platform", "WSL, Linux filesystem"),
+    }
+}
+
+fn check_directories(root: &Path, fix: bool) -> Vec<Finding> {
+    let mut missing = Vec::new();
+    let mut locked = Vec::new();
+    for dir in init::DIRECTORIES {
+        match fs::metadata(root.join(dir)) {
+            Ok(meta) if !platform::owner_has_full_access(&meta) => locked.push(*dir),
+            Ok(_) => {}
+            Err(_) => missing.push(*dir),
+        }
+    }
+
+    let mut findings = Vec::new();
+    if missing.is_empty() && locked.is_empty() {
+        findings.push(Finding::ok("directories", "project directories present"));
+        return findings;
+    }
+    // Permissions first, so missing children can be created under them.
+    if !locked.is_empty() {
+        let mut finding = Finding::problem(
+            "permissions",
+            Severity::Fail,
+            format!("not readable and writable by owner: {}", locked.join(", ")),
+            "run `chmod u+rwx` on them or `sgf doctor --fix`",
+        )
+        .fixable();
+        if fix {
+            finding.fixed = locked
+                .iter()
+                .all(|dir| platform::grant_owner_full_access(&root.join(dir)).is_ok());
+        }
+        findings.push(finding);
+    }
+    if !missing.is_empty() {
+        let mut finding = Finding::problem(
+            "directories",
+            Severity::Fail,
+            format!("missing: {}", missing.join(", ")),
+            "run `sgf init` or `sgf doctor --fix` to create them",
+        )
+        .fixable();
+        if fix {
+            finding.fixed = missing
+                .iter()
+                .all(|dir| fs::create_dir_all(root.join(dir)).is_ok());
+        }
+        findings.push(finding);
+    }
+    findings
+}
+
+fn check_config(root: &Path) -> Finding {
+    match SgfConfig::load(root) {
+        Ok(_) if crate::config::config_path(root).exists() => {
+            Finding::ok("config", ".sgf/config.toml parses")
+        }
+        Ok(_) => Finding::ok("config", "no .sgf/config.toml (using defaults)"),
+        Err(e) => Finding::problem(
+            "config",
+            Severity::Fail,
+            e.to_string(),
+            "correct .sgf/config.toml",
+        ),
+    }
+}
+
+fn check_sandbox(root: &Path, fix: bool) -> Finding {
+    let path = root.join(".claude/settings.json");
+    let enabled = fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        .map(|doc| doc.pointer("/sandbox/enabled").and_then(Value::as_bool));
+    match enabled {
+        Some(Some(true)) => Finding::ok("sandbox", "enabled in .claude/settings.json"),
+        Some(Some(false)) => Finding::problem(
+            "sandbox",
+            Severity::Warn,
+            "disabled in .claude/settings.json",
+            "set sandbox.enabled to true so agents run sandboxed",
+        ),
+        Some(None) | None => {
+            let mut finding = Finding::problem(
+                "sandbox",
+                Severity::Fail,
+                if path.exists() {
+                    "no sandbox config in .claude/settings.json"
+                } else {
+                    "no .claude/settings.json"
+                },
+                "run `sgf init` or `sgf doctor --fix` to merge the sandbox config",
+            )
+            .fixable();
+            if fix {
+                finding.fixed = init::merge_claude_settings(root).is_ok();
+            }
+            finding
+        }
+    }
+}
+
+fn check_deny_rules(root: &Path, fix: bool) -> Finding {
+    let message = match crate::guard::missing_deny_rules(root) {
+        Ok(missing) if missing.is_empty() => {
+            return Finding::ok("deny rules", "protect .sgf/ and .claude/");
+        }
+        Ok(missing) => format!("missing: {}", missing.join(", ")),
+        Err(e) => e,
+    };
+    let mut finding = Finding::problem(
+        "deny rules",
+        Severity::Fail,
+        message,
+        "run `sgf init` or `sgf doctor --fix` to merge them into .claude/settings.json",
+    )
+    .fixable();
+    if fix {
+        finding.fixed = init::merge_claude_settings(root).is_ok();
+    }
+    finding
+}
+
+fn check_tool(bin: &str, name: &'static str, path: &OsStr) -> Finding {
+    match find_executable(bin, path) {
+        Some(found) => Finding::ok(name, found.display().to_string()),
+        None => Finding::problem(
+            name,
+            Severity::Fail,
+            format!("`{bin}` not found on PATH"),
+            format!("install it with `cargo install --path crates/{name}`"),
+        ),
+    }
+}
+
+fn check_daemons(root: &Path, fix: bool, path: &OsStr) -> Finding {
+    if find_executable("pn", path).is_none() || find_executable("fm", path).is_none() {
+        return Finding::problem(
+            "daemons",
+            Severity::Warn,
+            "skipped: pn or fm missing",
+            "install pn and fm first",
+        );
+    }
+    let down: Vec<&str> = ["pn", "fm"]
+        .into_iter()
+        .filter(|bin| !recovery::daemon_is_reachable(bin, root))
+        .collect();
+    if down.is_empty() {
+        return Finding::ok("daemons", "pensa and forma daemons reachable");
+    }
+    let mut finding = Finding::problem(
+        "daemons",
+        Severity::Warn,
+        format!("not running: {}", down.join(", ")),
+        "they start when a loop launches; `sgf doctor --fix` starts them now",
+    )
+    .fixable();
+    if fix {
+        match recovery::ensure_daemons(root) {
+            Ok(()) => finding.fixed = true,
+            Err(e) => {
+                finding.severity = Severity::Fail;
+                finding.message = e.to_string();
+                finding.suggestion = Some(format!("run `{} daemon` by hand to see why", down[0]));
+            }
+        }
+    }
+    finding
+}
+
+fn check_agent(root: &Path, path: &OsStr) -> Finding {
+    let agent = SgfConfig::load(root).unwrap_or_default().agent_command();
+    match find_executable(&agent, path) {
+        Some(found) => Finding::ok("agent", found.display().to_string()),
+        None => Finding::problem(
+            "agent",
+            Severity::Fail,
+            format!("agent command `{agent}` not found"),
+            "install it, or set agent_command in .sgf/config.toml or SGF_AGENT_COMMAND",
+        ),
+    }
+}
+
+fn check_prompts(root: &Path) -> Finding {
+    let commands = cursus::list_all(root);
+    if commands.is_empty() {
+        return Finding::problem(
+            "prompts",
+            Severity::Warn,
+            "no cursus definitions found",
+            "add one under .sgf/cursus/ or ~/.sgf/cursus/",
+        );
+    }
+    let mut problems = Vec::new();
+    for (name, _) in &commands {
+        let result = cursus::resolve_command(root, name)
+            .and_then(|r| cursus::toml::validate_prompts(root, &r.definition));
+        if let Err(e) = result {
+            problems.push(format!("{name}: {e}"));
+        }
+    }
+    if problems.is_empty() {
+        Finding::ok(
+            "prompts",
+            format!("templates present for {} command(s)", commands.len()),
+        )
+    } else {
+        Finding::problem(
+            "prompts",
+            Severity::Fail,
+            problems.join("; "),
+            "add the missing templates under .sgf/prompts/ or ~/.sgf/prompts/",
+        )
+    }
+}
+
+/// Resolves `bin` the way `Command::new` would: as a path if it has a slash,
+/// otherwise by searching `path`.
+pub(crate) fn find_executable(bin: &str, path: &OsStr) -> Option<PathBuf> {
+    if bin.contains('/') {
+        let p = PathBuf::from(bin);
+        return platform::is_executable(&p).then_some(p);
+    }
+    env::split_paths(path)
+        .map(|dir| dir.join(bin))
+        .find(|p| platform::is_executable(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn find<'a>(findings: &'a [Finding], check: &str) -> &'a Finding {
+        findings.iter().find(|f| f.check == check).unwrap()
+    }
+
+    #[test]
+    fn find_executable_searches_path_and_skips_plain_files() {
+        let tmp = TempDir::new().unwrap();
+        let bin = tmp.path().join("cl");
+        fs::write(&bin, "#!/bin/sh\n").unwrap();
+        let path = env::join_paths([tmp.path()]).unwrap();
+        assert_eq!(find_executable("cl", &path), None, "not executable");
+
+        fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(find_executable("cl", &path), Some(bin.clone()));
+        assert_eq!(
+            find_executable(bin.to_str().unwrap(), OsStr::new("")),
+            Some(bin)
+        );
+        assert_eq!(find_executable("pn", &path), None);
+    }
+
+    #[test]
+    fn fix_creates_directories_and_restores_permissions() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join(".sgf")).unwrap();
+        fs::set_permissions(root.join(".sgf"), fs::Permissions::from_mode(0o500)).unwrap();
+
+        let findings = check_directories(root, false);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(
+            findings[0].message,
+            "not readable and writable by owner: .sgf"
+        );
+        assert!(findings[1].message.contains(".sgf/logs"));
+        assert!(findings.iter().all(|f| f.fixable && f.is_failure()));
+
+        let fixed = check_directories(root, true);
+        assert!(fixed.iter().all(|f| f.fixed), "{fixed:?}");
+        assert_eq!(check_directories(root, false)[0].severity, Severity::Ok);
+    }
+
+    #[test]
+    fn fix_merges_sandbox_config_but_respects_explicit_opt_out() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let finding = check_sandbox(root, true);
+        assert!(finding.fixed, "{finding:?}");
+        assert_eq!(check_sandbox(root, false).severity, Severity::Ok);
+
+        fs::write(
+            root.join(".claude/settings.json"),
+            r#"{"sandbox": {"enabled": false}}"#,
+        )
+        .unwrap();
+        let finding = check_sandbox(root, true);
+        assert_eq!(finding.severity, Severity::Warn);
+        assert!(!finding.fixable && !finding.fixed);
+
+        let finding = check_deny_rules(root, false);
+        assert!(finding.message.starts_with("missing: Edit .sgf/**"));
+        assert!(check_deny_rules(root, true).fixed);
+        assert_eq!(check_deny_rules(root, false).severity, Severity::Ok);
+        assert_eq!(check_sandbox(root, false).severity, Severity::Warn);
+    }
+
+    #[test]
+    fn push_needs_an_author_and_somewhere_to_push() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let git = |args: &[&str]| {
+            let ok = Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .status()
+                .unwrap()
+                .success();
+            assert!(ok, "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+
+        let finding = check_push(root);
+        assert_eq!(finding.severity, Severity::Warn, "{finding:?}");
+        assert!(finding.message.contains("no upstream"));
+
+        fs::create_dir_all(root.join(".sgf")).unwrap();
+        fs::write(
+            root.join(".sgf/config.toml"),
+            "[push]\nremote = \"backup\"\n",
+        )
+        .unwrap();
+        assert!(check_push(root).is_failure());
+        git(&["remote", "add", "backup", "git@example.com:team/app.git"]);
+        assert_eq!(check_push(root).severity, Severity::Ok);
+
+        fs::write(root.join(".sgf/config.toml"), "auto_push = false\n").unwrap();
+        assert_eq!(check_push(root).message, "auto-push is off");
+    }
+
+    #[test]
+    fn diagnose_reports_missing_scaffold_with_suggestions() {
+        let tmp = TempDir::new().unwrap();
+        let findings = diagnose(tmp.path(), false);
+        let git = find(&findings, "git");
+        assert!(git.is_failure());
+        assert!(git.suggestion.as_deref().unwrap().contains("git init"));
+        assert_eq!(find(&findings, "push").severity, Severity::Warn);
+        assert!(find(&findings, "directories").is_failure());
+        assert_eq!(find(&findings, "config").severity, Severity::Ok);
+        assert!(
+            findings
+                .iter()
+                .all(|f| (f.severity == Severity::Ok) == f.suggestion.is_none())
+        );
+    }
+}
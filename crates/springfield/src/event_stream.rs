@@ -0,0 +1,71 @@
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::loop_mgmt::LoopRecord;
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+pub fn enable() {
+    let _ = ENABLED.set(true);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(false)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LoopEvent {
+    LoopStarted {
+        loop_id: String,
+        stage: String,
+        spec: Option<String>,
+        attempt: u32,
+    },
+    IterationProgress {
+        loop_id: Option<String>,
+        iteration: u32,
+        iterations: u32,
+        session_id: String,
+        cost_usd: f64,
+    },
+    LoopFinished {
+        loop_id: String,
+        stage: String,
+        spec: Option<String>,
+        exit_code: Option<i32>,
+        outcome: String,
+        iterations: u32,
+    },
+}
+
+impl LoopEvent {
+    pub fn started(record: &LoopRecord) -> Self {
+        Self::LoopStarted {
+            loop_id: record.loop_id.clone(),
+            stage: record.stage.clone(),
+            spec: record.spec.clone(),
+            attempt: record.attempt,
+        }
+    }
+
+    pub fn finished(record: &LoopRecord) -> Self {
+        Self::LoopFinished {
+            loop_id: record.loop_id.clone(),
+            stage: record.stage.clone(),
+            spec: record.spec.clone(),
+            exit_code: record.exit_code,
+            outcome: record.outcome().to_string(),
+            iterations: record.iterations_completed,
+        }
+    }
+}
+
+pub fn emit(event: &LoopEvent) {
+    if enabled()
+        && let Ok(json) = serde_json::to_string(event)
+    {
+        println!("{json}");
+    }
+}
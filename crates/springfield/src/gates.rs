@@ -0,0 +1,202 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::backpressure;
+use crate::config::GateConfig;
+use crate::loop_mgmt;
+
+const OUTPUT_TAIL_LINES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateFailure {
+    pub check: String,
+    pub reason: String,
+    pub output: Option<String>,
+}
+
+/// Runs every check in `gate` and returns the ones that failed. An `after`
+/// stage passes when its most recent loop (for `spec`, when there is one)
+/// exited 0.
+pub fn check(root: &Path, spec: Option<&str>, gate: &GateConfig) -> Vec<GateFailure> {
+    let records = loop_mgmt::list_loop_records(root);
+    let mut failures = Vec::new();
+
+    for stage in &gate.after {
+        let latest = records
+            .iter()
+            .find(|r| &r.stage == stage && (spec.is_none() || r.spec.as_deref() == spec));
+        let reason = match latest {
+            None => match spec {
+                Some(spec) => format!("no {stage} loop has run for {spec}"),
+                None => format!("no {stage} loop has run"),
+            },
+            Some(record) if record.outcome() == "completed" => continue,
+            Some(record) => format!(
+                "latest {stage} loop {} {}",
+                record.loop_id,
+                record.outcome()
+            ),
+        };
+        failures.push(GateFailure {
+            check: format!("after {stage}"),
+            reason,
+            output: None,
+        });
+    }
+
+    failures.extend(
+        commands(root, gate)
+            .iter()
+            .filter_map(|command| run_command(root, command)),
+    );
+    failures
+}
+
+pub fn commands(root: &Path, gate: &GateConfig) -> Vec<String> {
+    let mut commands = gate.commands.clone();
+    if !gate.backpressure.is_empty() {
+        match backpressure::load(root) {
+            Ok(checks) => commands.extend(backpressure::commands_of(&checks, &gate.backpressure)),
+            Err(e) => tracing::warn!(error = %e, "failed to read BACKPRESSURE.md"),
+        }
+    }
+    commands
+}
+
+pub fn run_command(root: &Path, command: &str) -> Option<GateFailure> {
+    let result = Command::new("sh")
+        .args(["-c", command])
+        .current_dir(root)
+        .output();
+    let (reason, output) = match result {
+        Ok(out) if out.status.success() => return None,
+        Ok(out) => {
+            let reason = match out.status.code() {
+                Some(code) => format!("exited {code}"),
+                None => "killed by a signal".to_string(),
+            };
+            let mut text = String::from_utf8_lossy(&out.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&out.stderr));
+            (reason, tail(&text))
+        }
+        Err(e) => (format!("could not run: {e}"), None),
+    };
+    Some(GateFailure {
+        check: command.to_string(),
+        reason,
+        output,
+    })
+}
+
+fn tail(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let start = lines.len().saturating_sub(OUTPUT_TAIL_LINES);
+    (!lines.is_empty()).then(|| lines[start..].join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loop_mgmt::{LoopRecord, write_loop_record};
+    use tempfile::TempDir;
+
+    fn record(loop_id: &str, stage: &str, spec: Option<&str>, exit_code: i32) -> LoopRecord {
+        LoopRecord {
+            loop_id: loop_id.to_string(),
+            stage: stage.to_string(),
+            spec: spec.map(str::to_string),
+            started_at: format!("2026-10-16T10:00:0{}Z", &loop_id[loop_id.len() - 1..]),
+            attempt_started_at: None,
+            ended_at: Some("2026-10-16T11:00:00Z".to_string()),
+            exit_code: Some(exit_code),
+            iterations_completed: 1,
+            head_before: None,
+            head_after: None,
+            branch: None,
+            base_branch: None,
+            attempt: 1,
+            incidents: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn after_uses_the_latest_loop_for_the_spec() {
+        let tmp = TempDir::new().unwrap();
+        write_loop_record(tmp.path(), &record("verify-1", "verify", Some("auth"), 0)).unwrap();
+        write_loop_record(tmp.path(), &record("verify-2", "verify", Some("auth"), 2)).unwrap();
+        write_loop_record(
+            tmp.path(),
+            &record("verify-3", "verify", Some("billing"), 0),
+        )
+        .unwrap();
+        let gate = GateConfig {
+            after: vec!["verify".to_string(), "build".to_string()],
+            commands: Vec::new(),
+            backpressure: Vec::new(),
+        };
+
+        assert!(
+            check(
+                tmp.path(),
+                Some("billing"),
+                &GateConfig {
+                    after: vec!["verify".to_string()],
+                    commands: Vec::new(),
+                    backpressure: Vec::new(),
+                }
+            )
+            .is_empty()
+        );
+
+        let failures = check(tmp.path(), Some("auth"), &gate);
+        assert_eq!(
+            failures
+                .iter()
+                .map(|f| (f.check.as_str(), f.reason.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("after verify", "latest verify loop verify-2 exhausted"),
+                ("after build", "no build loop has run for auth"),
+            ]
+        );
+    }
+
+    #[test]
+    fn failing_commands_report_exit_code_and_output() {
+        let tmp = TempDir::new().unwrap();
+        let gate = GateConfig {
+            after: Vec::new(),
+            commands: vec![
+                "true".to_string(),
+                "echo compiling; echo 'test broke' >&2; exit 101".to_string(),
+            ],
+            backpressure: Vec::new(),
+        };
+
+        let failures = check(tmp.path(), None, &gate);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].reason, "exited 101");
+        assert_eq!(failures[0].output.as_deref(), Some("compiling\ntest broke"));
+    }
+
+    #[test]
+    fn backpressure_kinds_add_the_project_commands() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join(".sgf")).unwrap();
+        std::fs::write(
+            tmp.path().join(".sgf/BACKPRESSURE.md"),
+            "- Lint: `exit 3`\n- Test: `true`\n- Test single: `cargo test <name>`\n",
+        )
+        .unwrap();
+        let gate = GateConfig {
+            after: Vec::new(),
+            commands: vec!["true".to_string()],
+            backpressure: vec![backpressure::Kind::Test, backpressure::Kind::Lint],
+        };
+
+        assert_eq!(commands(tmp.path(), &gate), ["true", "exit 3", "true"]);
+        let failures = check(tmp.path(), None, &gate);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].check, "exit 3");
+    }
+}
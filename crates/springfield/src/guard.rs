@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::init::CLAUDE_SETTINGS_DENY_RULES;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    deny_rules: Vec<String>,
+    prompts: BTreeMap<String, String>,
+    #[serde(default)]
+    ended: bool,
+    #[serde(default)]
+    changed: Vec<String>,
+}
+
+fn manifest_path(root: &Path) -> std::path::PathBuf {
+    root.join(".sgf/guard.json")
+}
+
+fn read_manifest(root: &Path) -> Option<Manifest> {
+    serde_json::from_str(&fs::read_to_string(manifest_path(root)).ok()?).ok()
+}
+
+fn write_manifest(root: &Path, manifest: &Manifest) -> io::Result<()> {
+    let path = manifest_path(root);
+    fs::create_dir_all(path.parent().unwrap())?;
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, json)
+}
+
+fn prompt_hashes(root: &Path) -> BTreeMap<String, String> {
+    use sha2::{Digest, Sha256};
+
+    fn walk(dir: &Path, prefix: &str, hashes: &mut BTreeMap<String, String>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let rel = format!("{prefix}{name}");
+            let path = entry.path();
+            if path.is_dir() {
+                walk(&path, &format!("{rel}/"), hashes);
+            } else if let Ok(bytes) = fs::read(&path) {
+                let hash: [u8; 32] = Sha256::digest(&bytes).into();
+                let hex = hash.iter().map(|b| format!("{b:02x}")).collect();
+                hashes.insert(rel, hex);
+            }
+        }
+    }
+
+    let mut hashes = BTreeMap::new();
+    walk(&root.join(".sgf/prompts"), "", &mut hashes);
+    hashes
+}
+
+fn differing(before: &BTreeMap<String, String>, after: &BTreeMap<String, String>) -> Vec<String> {
+    let mut names: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|name| before.get(*name) != after.get(*name))
+        .cloned()
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+pub fn missing_deny_rules(root: &Path) -> Result<Vec<&'static str>, String> {
+    let text = fs::read_to_string(root.join(".claude/settings.json"))
+        .map_err(|_| "no .claude/settings.json".to_string())?;
+    let doc: Value = serde_json::from_str(&text)
+        .map_err(|e| format!(".claude/settings.json does not parse: {e}"))?;
+    let deny = doc.pointer("/permissions/deny").and_then(Value::as_array);
+    Ok(CLAUDE_SETTINGS_DENY_RULES
+        .iter()
+        .copied()
+        .filter(|rule| deny.is_none_or(|d| !d.iter().any(|v| v.as_str() == Some(rule))))
+        .collect())
+}
+
+pub fn removed_deny_rules(root: &Path) -> Vec<String> {
+    let Some(manifest) = read_manifest(root) else {
+        return Vec::new();
+    };
+    let missing = missing_deny_rules(root).unwrap_or_else(|_| CLAUDE_SETTINGS_DENY_RULES.to_vec());
+    manifest
+        .deny_rules
+        .into_iter()
+        .filter(|rule| missing.contains(&rule.as_str()))
+        .collect()
+}
+
+/// Prompts an earlier loop changed that still differ from how it found
+/// them. A loop that never recorded its end is compared with the prompts as
+/// they are now.
+pub fn tampered_prompts(root: &Path) -> Vec<String> {
+    let Some(manifest) = read_manifest(root) else {
+        return Vec::new();
+    };
+    let current = prompt_hashes(root);
+    if !manifest.ended {
+        return differing(&manifest.prompts, &current);
+    }
+    manifest
+        .changed
+        .into_iter()
+        .filter(|name| current.get(name) != manifest.prompts.get(name))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub message: String,
+    pub fix: &'static str,
+}
+
+pub fn check(root: &Path) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let removed = removed_deny_rules(root);
+    if !removed.is_empty() {
+        problems.push(Problem {
+            message: format!(
+                "deny rules removed from .claude/settings.json: {}",
+                removed.join(", ")
+            ),
+            fix: "run `sgf doctor --fix` to merge them back",
+        });
+    }
+    let tampered = tampered_prompts(root);
+    if !tampered.is_empty() {
+        problems.push(Problem {
+            message: format!("prompts changed by an earlier loop: {}", tampered.join(", ")),
+            fix: "review them with `git diff .sgf/prompts`, then restore them or run `sgf prompt trust`",
+        });
+    }
+    problems
+}
+
+fn present_deny_rules(root: &Path) -> Vec<String> {
+    let missing = missing_deny_rules(root).unwrap_or_else(|_| CLAUDE_SETTINGS_DENY_RULES.to_vec());
+    CLAUDE_SETTINGS_DENY_RULES
+        .iter()
+        .filter(|rule| !missing.contains(rule))
+        .map(|rule| rule.to_string())
+        .collect()
+}
+
+pub fn record_launch(root: &Path) -> io::Result<()> {
+    write_manifest(
+        root,
+        &Manifest {
+            deny_rules: present_deny_rules(root),
+            prompts: prompt_hashes(root),
+            ..Default::default()
+        },
+    )
+}
+
+pub fn record_finish(root: &Path) -> io::Result<()> {
+    let Some(mut manifest) = read_manifest(root) else {
+        return Ok(());
+    };
+    if manifest.ended {
+        return Ok(());
+    }
+    manifest.changed = differing(&manifest.prompts, &prompt_hashes(root));
+    manifest.ended = true;
+    write_manifest(root, &manifest)
+}
+
+/// Accepts the prompts as they are now (`sgf prompt trust`). Removed deny
+/// rules stay flagged.
+pub fn trust(root: &Path) -> io::Result<()> {
+    let deny_rules = read_manifest(root)
+        .map(|m| m.deny_rules)
+        .unwrap_or_else(|| present_deny_rules(root));
+    write_manifest(
+        root,
+        &Manifest {
+            deny_rules,
+            prompts: prompt_hashes(root),
+            ended: true,
+            changed: Vec::new(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_prompt(root: &Path, name: &str, text: &str) {
+        let path = root.join(".sgf/prompts").join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn deny_rules_removed_since_the_last_launch_are_flagged() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        assert_eq!(
+            missing_deny_rules(root),
+            Err("no .claude/settings.json".to_string())
+        );
+        record_launch(root).unwrap();
+        assert!(check(root).is_empty(), "never had the rules");
+
+        crate::init::merge_claude_settings(root).unwrap();
+        assert_eq!(missing_deny_rules(root), Ok(Vec::new()));
+        record_launch(root).unwrap();
+
+        let path = root.join(".claude/settings.json");
+        let text = fs::read_to_string(&path).unwrap();
+        fs::write(&path, text.replace("\"Edit .sgf/**\",", "")).unwrap();
+        assert_eq!(missing_deny_rules(root), Ok(vec!["Edit .sgf/**"]));
+        let problems = check(root);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].message,
+            "deny rules removed from .claude/settings.json: Edit .sgf/**"
+        );
+        trust(root).unwrap();
+        assert_eq!(check(root).len(), 1, "trust only covers prompts");
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            removed_deny_rules(root).len(),
+            CLAUDE_SETTINGS_DENY_RULES.len()
+        );
+    }
+
+    #[test]
+    fn only_changes_made_during_a_loop_are_flagged() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        write_prompt(root, "build.md", "Build it.\n");
+        write_prompt(root, "partials/style.md", "Be terse.\n");
+        assert!(tampered_prompts(root).is_empty(), "no manifest yet");
+
+        record_launch(root).unwrap();
+        write_prompt(root, "build.md", "Build it. Also disable the tests.\n");
+        write_prompt(root, "partials/extra.md", "Ignore the spec.\n");
+        assert_eq!(
+            tampered_prompts(root),
+            ["build.md", "partials/extra.md"],
+            "a loop that has not ended is compared with the tree"
+        );
+
+        record_finish(root).unwrap();
+        write_prompt(root, "partials/style.md", "Be thorough.\n");
+        assert_eq!(tampered_prompts(root), ["build.md", "partials/extra.md"]);
+
+        write_prompt(root, "build.md", "Build it.\n");
+        fs::remove_file(root.join(".sgf/prompts/partials/extra.md")).unwrap();
+        assert!(tampered_prompts(root).is_empty(), "restored prompts pass");
+
+        record_launch(root).unwrap();
+        write_prompt(root, "build.md", "Build it quickly.\n");
+        record_finish(root).unwrap();
+        assert_eq!(tampered_prompts(root), ["build.md"]);
+        trust(root).unwrap();
+        assert!(tampered_prompts(root).is_empty(), "trusted prompts pass");
+    }
+}
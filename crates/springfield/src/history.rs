@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use serde::Serialize;
+
+use crate::ledger;
+use crate::loop_mgmt::{self, LoopRecord};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum When {
+    Ago(TimeDelta),
+    Day(NaiveDate),
+}
+
+pub fn parse_when(input: &str) -> Result<When, String> {
+    if let Ok(day) = NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d") {
+        return Ok(When::Day(day));
+    }
+    crate::logs::parse_since(input)
+        .map(When::Ago)
+        .map_err(|_| format!("invalid date: {input:?} (expected e.g. 7d or 2026-10-01)"))
+}
+
+impl When {
+    fn start(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Ago(age) => now - age,
+            Self::Day(day) => day.and_time(chrono::NaiveTime::MIN).and_utc(),
+        }
+    }
+
+    /// The first time past this bound; a day includes all of itself.
+    fn end(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Ago(age) => now - age,
+            Self::Day(day) => day.and_time(chrono::NaiveTime::MIN).and_utc() + TimeDelta::days(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub stage: Option<String>,
+    pub spec: Option<String>,
+    pub since: Option<When>,
+    pub until: Option<When>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    pub loop_id: String,
+    pub stage: String,
+    pub spec: Option<String>,
+    pub started_at: String,
+    pub ended_at: String,
+    pub outcome: String,
+    pub duration_secs: i64,
+    pub iterations: u32,
+    pub cost_usd: Option<f64>,
+}
+
+pub fn list(root: &Path, filter: &HistoryFilter) -> Vec<HistoryEntry> {
+    let now = Utc::now();
+    let since = filter.since.map(|w| w.start(now));
+    let until = filter.until.map(|w| w.end(now));
+    let entries = ledger::read(root);
+    loop_mgmt::list_loop_records(root)
+        .into_iter()
+        .filter(|r| filter.stage.as_ref().is_none_or(|s| &r.stage == s))
+        .filter(|r| filter.spec.is_none() || r.spec == filter.spec)
+        .filter_map(|r| {
+            let ended = DateTime::parse_from_rfc3339(r.ended_at.as_deref()?).ok()?;
+            let in_range = since.is_none_or(|t| ended >= t) && until.is_none_or(|t| ended < t);
+            in_range.then(|| entry(&r, &entries))
+        })
+        .collect()
+}
+
+fn entry(record: &LoopRecord, ledger: &[ledger::LedgerEntry]) -> HistoryEntry {
+    let attempts: Vec<&ledger::LedgerEntry> = ledger
+        .iter()
+        .filter(|e| e.loop_id == record.loop_id)
+        .collect();
+    let ended_at = record.ended_at.clone().unwrap_or_default();
+    // Loops from before the ledger only have their start and end times.
+    let duration_secs = if attempts.is_empty() {
+        match (
+            DateTime::parse_from_rfc3339(&record.started_at),
+            DateTime::parse_from_rfc3339(&ended_at),
+        ) {
+            (Ok(start), Ok(end)) => (end - start).num_seconds().max(0),
+            _ => 0,
+        }
+    } else {
+        attempts.iter().map(|e| e.duration_secs).sum()
+    };
+    let costs: Vec<f64> = attempts.iter().filter_map(|e| e.cost_usd).collect();
+    HistoryEntry {
+        loop_id: record.loop_id.clone(),
+        stage: record.stage.clone(),
+        spec: record.spec.clone(),
+        started_at: record.started_at.clone(),
+        outcome: record.outcome().to_string(),
+        ended_at,
+        duration_secs,
+        iterations: record.iterations_completed,
+        cost_usd: (!costs.is_empty()).then(|| costs.iter().sum()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loop_mgmt::write_loop_record;
+    use tempfile::TempDir;
+
+    fn record(
+        loop_id: &str,
+        stage: &str,
+        spec: Option<&str>,
+        ended_at: Option<&str>,
+    ) -> LoopRecord {
+        LoopRecord {
+            loop_id: loop_id.to_string(),
+            stage: stage.to_string(),
+            spec: spec.map(str::to_string),
+            started_at: "2026-09-30T09:00:00Z".to_string(),
+            attempt_started_at: None,
+            ended_at: ended_at.map(str::to_string),
+            exit_code: ended_at.map(|_| 0),
+            iterations_completed: 3,
+            head_before: None,
+            head_after: None,
+            branch: None,
+            base_branch: None,
+            attempt: 1,
+            incidents: Vec::new(),
+        }
+    }
+
+    fn ledger_entry(
+        loop_id: &str,
+        duration_secs: i64,
+        cost_usd: Option<f64>,
+    ) -> ledger::LedgerEntry {
+        ledger::LedgerEntry {
+            loop_id: loop_id.to_string(),
+            stage: "build".to_string(),
+            spec: Some("auth".to_string()),
+            attempt: 1,
+            ended_at: "2026-10-01T10:00:00Z".to_string(),
+            outcome: "completed".to_string(),
+            iterations: 3,
+            duration_secs,
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn when_parses_durations_and_days() {
+        assert_eq!(parse_when("7d"), Ok(When::Ago(TimeDelta::days(7))));
+        assert_eq!(
+            parse_when("2026-10-01"),
+            Ok(When::Day(NaiveDate::from_ymd_opt(2026, 10, 1).unwrap()))
+        );
+        assert!(parse_when("last week").is_err());
+    }
+
+    #[test]
+    fn archived_loops_are_listed_with_ledger_totals() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let done = record(
+            "build-auth-1",
+            "build",
+            Some("auth"),
+            Some("2026-10-01T10:00:00Z"),
+        );
+        write_loop_record(root, &done).unwrap();
+        std::fs::write(root.join(".sgf/logs/build-auth-1.log"), "log\n").unwrap();
+        let archived = loop_mgmt::archive_loop(root, &done).unwrap();
+        assert_eq!(archived, root.join(".sgf/archive/2026-10"));
+        assert!(archived.join("build-auth-1.log").exists());
+        assert!(!root.join(".sgf/logs/build-auth-1.json").exists());
+
+        write_loop_record(
+            root,
+            &record(
+                "verify-auth-2",
+                "verify",
+                Some("auth"),
+                Some("2026-09-30T10:00:00Z"),
+            ),
+        )
+        .unwrap();
+        write_loop_record(
+            root,
+            &record("build-billing-3", "build", Some("billing"), None),
+        )
+        .unwrap();
+        ledger::append(root, &ledger_entry("build-auth-1", 600, Some(1.5))).unwrap();
+        ledger::append(root, &ledger_entry("build-auth-1", 300, Some(0.25))).unwrap();
+
+        let all = list(root, &HistoryFilter::default());
+        let ids: Vec<&str> = all.iter().map(|e| e.loop_id.as_str()).collect();
+        assert_eq!(ids.len(), 2, "running loops are not history: {ids:?}");
+        let build = all.iter().find(|e| e.loop_id == "build-auth-1").unwrap();
+        assert_eq!((build.duration_secs, build.cost_usd), (900, Some(1.75)));
+        let verify = all.iter().find(|e| e.loop_id == "verify-auth-2").unwrap();
+        assert_eq!((verify.duration_secs, verify.cost_usd), (3600, None));
+
+        let builds = list(
+            root,
+            &HistoryFilter {
+                stage: Some("build".to_string()),
+                spec: Some("auth".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(builds, std::slice::from_ref(build));
+
+        let day = |d| Some(When::Day(NaiveDate::from_ymd_opt(2026, 9, d).unwrap()));
+        let september = list(
+            root,
+            &HistoryFilter {
+                since: day(30),
+                until: day(30),
+                ..Default::default()
+            },
+        );
+        assert_eq!(september, std::slice::from_ref(verify));
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::loop_mgmt::LoopRecord;
+
+pub const HOOKS_DIR: &str = ".sgf/hooks";
+
+const FAILURE_LINES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    PreLoop,
+    PostIteration,
+    PostLoop,
+}
+
+impl Hook {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::PreLoop => "pre-loop",
+            Self::PostIteration => "post-iteration",
+            Self::PostLoop => "post-loop",
+        }
+    }
+}
+
+pub fn script(root: &Path, hook: Hook) -> Option<PathBuf> {
+    let path = root.join(HOOKS_DIR).join(hook.file_name());
+    path.is_file().then_some(path)
+}
+
+pub fn record_env(record: &LoopRecord) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("SGF_LOOP_ID", record.loop_id.clone()),
+        ("SGF_STAGE", record.stage.clone()),
+        ("SGF_SPEC", record.spec.clone().unwrap_or_default()),
+        ("SGF_ITERATIONS", record.iterations_completed.to_string()),
+    ];
+    if record.ended_at.is_some() {
+        let code = record.exit_code.map(|c| c.to_string()).unwrap_or_default();
+        env.push(("SGF_EXIT_CODE", code));
+        env.push(("SGF_OUTCOME", record.outcome().to_string()));
+    }
+    env
+}
+
+/// Runs `hook` from `root`'s hooks directory in `cwd` with `env` and
+/// `SGF_HOOK` set, waiting for it to finish. Returns whether a script ran
+/// and succeeded; a missing script is not an error.
+pub fn run(root: &Path, cwd: &Path, hook: Hook, env: &[(&str, String)]) -> bool {
+    let Some(path) = script(root, hook) else {
+        return false;
+    };
+    let name = format!("{HOOKS_DIR}/{}", hook.file_name());
+    if !crate::platform::is_executable(&path) {
+        crate::style::print_warning(&format!("skipping {name}: not executable (chmod +x)"));
+        return false;
+    }
+    let output = Command::new(&path)
+        .current_dir(cwd)
+        .env("SGF_HOOK", hook.file_name())
+        .env("SGF_ROOT", root)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => true,
+        Ok(output) => {
+            let status = output
+                .status
+                .code()
+                .map_or_else(|| "a signal".to_string(), |c| format!("exit code {c}"));
+            crate::style::print_warning(&format!("{name} failed with {status}"));
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let lines: Vec<&str> = stderr.lines().collect();
+            for line in &lines[lines.len().saturating_sub(FAILURE_LINES)..] {
+                crate::style::print_detail(line);
+            }
+            false
+        }
+        Err(e) => {
+            crate::style::print_warning(&format!("{name} could not run: {e}"));
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn install(root: &Path, hook: Hook, body: &str, mode: u32) {
+        let dir = root.join(HOOKS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(hook.file_name());
+        fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn hooks_run_with_loop_details_in_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        install(
+            root,
+            Hook::PostLoop,
+            r#"echo "$SGF_HOOK $SGF_LOOP_ID $SGF_SPEC $SGF_OUTCOME $SGF_EXIT_CODE" > seen"#,
+            0o755,
+        );
+        let record = LoopRecord {
+            loop_id: "build-auth-1".to_string(),
+            stage: "build".to_string(),
+            spec: Some("auth".to_string()),
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+            attempt_started_at: None,
+            ended_at: Some("2026-01-01T01:00:00Z".to_string()),
+            exit_code: Some(2),
+            iterations_completed: 5,
+            head_before: None,
+            head_after: None,
+            branch: None,
+            base_branch: None,
+            attempt: 1,
+            incidents: Vec::new(),
+        };
+
+        assert!(run(root, root, Hook::PostLoop, &record_env(&record)));
+        let seen = fs::read_to_string(root.join("seen")).unwrap();
+        assert_eq!(seen.trim(), "post-loop build-auth-1 auth exhausted 2");
+
+        assert!(
+            !run(root, root, Hook::PreLoop, &[]),
+            "no script, nothing ran"
+        );
+        install(root, Hook::PreLoop, "exit 0", 0o644);
+        assert!(!run(root, root, Hook::PreLoop, &[]), "not executable");
+        install(root, Hook::PostIteration, "exit 3", 0o755);
+        assert!(!run(root, root, Hook::PostIteration, &[]));
+    }
+}
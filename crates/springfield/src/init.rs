@@ -9,7 +9,10 @@ use std::os::unix::fs as unix_fs;
 
 use serde_json::Value;
 
-const DIRECTORIES: &[&str] = &[
+use crate::profile::{GitignoreSection, Profile};
+use crate::upgrade::read_optional;
+
+pub(crate) const DIRECTORIES: &[&str] = &[
     ".pensa",
     ".forma",
     ".sgf",
@@ -18,74 +21,170 @@ const DIRECTORIES: &[&str] = &[
     ".sgf/run",
 ];
 
+const CONFIG_TOML: &str = ".sgf/config.toml";
+const BACKPRESSURE: &str = ".sgf/BACKPRESSURE.md";
+const CLAUDE_SETTINGS: &str = ".claude/settings.json";
+const PRE_COMMIT_CONFIG: &str = ".pre-commit-config.yaml";
+
 struct SkeletonFile {
     path: &'static str,
     content: &'static str,
 }
 
 const SKELETON_FILES: &[SkeletonFile] = &[SkeletonFile {
-    path: "AGENTS.md",
-    content: include_str!("../../../AGENTS.md"),
+    path: ".prettierignore",
+    content: PRETTIERIGNORE,
 }];
 
-const GITIGNORE_FULL: &str = "\
-# Springfield
-.pensa/db.sqlite
-**/.pensa/daemon.port
-**/.pensa/daemon.project
-**/.pensa/daemon.url
-**/.forma/daemon.port
-**/.forma/daemon.project
-**/.forma/daemon.url
-.sgf/logs/
-.sgf/run/
-.iter-*
-
-# Rust
-/target
-
-# Node
-node_modules/
-
-# Playwright
-/test-results/
-/playwright-report/
-/blob-report/
-/playwright/.cache/
-
-# Environment
-.env
-.env.local
-.env.*.local
-
-# macOS
-.DS_Store
+const AGENTS_MD_INTRO: &str = "\
+# AGENTS.md
+
+Guidance every agent reads at the start of a session. `CLAUDE.md` links
+here. Add project notes below; `sgf init` only appends the sections it
+finds missing.
 ";
 
-const GITIGNORE_ENTRIES: &[&str] = &[
-    ".pensa/db.sqlite",
-    "**/.pensa/daemon.port",
-    "**/.pensa/daemon.project",
-    "**/.pensa/daemon.url",
-    "**/.forma/daemon.port",
-    "**/.forma/daemon.project",
-    "**/.forma/daemon.url",
-    ".sgf/logs/",
-    ".sgf/run/",
-    ".iter-*",
-    "/target",
-    "node_modules/",
-    "/test-results/",
-    "/playwright-report/",
-    "/blob-report/",
-    "/playwright/.cache/",
-    ".env",
-    ".env.local",
-    ".env.*.local",
-    ".DS_Store",
+const AGENTS_MD_COMPLETION: &str = "\
+Springfield watches for sentinel files to learn how an iteration ended.
+Touch exactly one, in the project root, then stop working:
+
+| File | Meaning |
+|------|---------|
+| `.iter-complete` | The stage's work is done; the loop ends |
+| `.iter-reject` | Review iters only: the draft is rejected and goes back to its author |
+| `.iter-revise` | Review iters only: the draft needs another revision pass |
+
+Ending a session without a sentinel starts the next iteration. Touch
+`.iter-ding` to ask for attention without ending anything.
+
+When each stage is done:
+
+- **spec**: the spec is written and its work is filed as `pn` issues.
+- **build**: `pn ready` has nothing left to claim.
+- **verify**: every spec has been checked against the code and gaps filed as bugs.
+- **test**: every test issue has been run and its result recorded.
+- **custom stages**: whatever their prompt says; when in doubt, one task per iteration.
+";
+
+const AGENTS_MD_PENSA: &str = "\
+Track all work in pensa with the `pn` CLI; never keep a separate TODO list.
+
+- Take one issue per iteration: `pn ready`, then `pn update <id> --claim`.
+  If the claim fails, pick another issue, even if you hold the claim.
+- Log problems you find but did not cause: `pn create \"...\" -t bug`.
+- Leave notes for the next iteration with `pn comment add <id> \"...\"`.
+- When done, `pn close <id> --reason \"...\"`; if you cannot finish,
+  `pn release <id>` and comment on why.
+- Pass `--json` when reading output in a script.
+";
+
+const AGENTS_MD_BACKPRESSURE_INTRO: &str = "\
+Run the checks in `.sgf/BACKPRESSURE.md` before every commit and fix
+whatever fails before moving on. Never commit with failing checks, and
+never weaken a check to make it pass.
+";
+
+fn agents_md_sections(profile: Option<Profile>) -> Vec<(&'static str, String)> {
+    let mut backpressure = AGENTS_MD_BACKPRESSURE_INTRO.to_string();
+    if let Some(profile) = profile {
+        backpressure.push_str(&format!("\nFor this {profile} project:\n\n"));
+        for line in profile.backpressure().lines() {
+            if line.starts_with("- ") {
+                backpressure.push_str(line);
+                backpressure.push('\n');
+            }
+        }
+    }
+    vec![
+        ("Completion Protocol", AGENTS_MD_COMPLETION.to_string()),
+        ("Issue Tracking", AGENTS_MD_PENSA.to_string()),
+        ("Backpressure", backpressure),
+    ]
+}
+
+/// AGENTS.md with the sections an existing one lacks appended, matched by
+/// their `## ` heading. Nothing already in the file is changed.
+fn agents_md_content(root: &Path, profile: Option<Profile>) -> io::Result<Option<String>> {
+    let existing = read_optional(&root.join("AGENTS.md"))?;
+    let headings: Vec<&str> = existing
+        .as_deref()
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("## "))
+        .map(str::trim)
+        .collect();
+    let missing: Vec<String> = agents_md_sections(profile)
+        .into_iter()
+        .filter(|(heading, _)| !headings.contains(heading))
+        .map(|(heading, body)| format!("## {heading}\n\n{body}"))
+        .collect();
+    if missing.is_empty() {
+        return Ok(None);
+    }
+    let mut content = existing.unwrap_or_else(|| AGENTS_MD_INTRO.to_string());
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for section in missing {
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(&section);
+    }
+    Ok(Some(content))
+}
+
+const GITIGNORE_SPRINGFIELD: GitignoreSection = (
+    "Springfield",
+    &[
+        ".pensa/db.sqlite",
+        "**/.pensa/daemon.port",
+        "**/.pensa/daemon.project",
+        "**/.pensa/daemon.token",
+        "**/.pensa/daemon.url",
+        "**/.forma/daemon.port",
+        "**/.forma/daemon.project",
+        "**/.forma/daemon.url",
+        ".sgf/archive/",
+        ".sgf/guard.json",
+        ".sgf/ledger.jsonl",
+        ".sgf/logs/",
+        ".sgf/out/",
+        ".sgf/run/",
+        ".sgf/scratch/",
+        ".sgf/worktrees/",
+        ".iter-*",
+    ],
+);
+
+const GITIGNORE_DEFAULT_STACK: &[GitignoreSection] = &[
+    ("Rust", &["/target"]),
+    ("Node", &["node_modules/"]),
+    (
+        "Playwright",
+        &[
+            "/test-results/",
+            "/playwright-report/",
+            "/blob-report/",
+            "/playwright/.cache/",
+        ],
+    ),
+];
+
+const GITIGNORE_COMMON: &[GitignoreSection] = &[
+    ("Environment", &[".env", ".env.local", ".env.*.local"]),
+    ("macOS", &[".DS_Store"]),
 ];
 
-const CLAUDE_SETTINGS_DENY_RULES: &[&str] = &[
+fn gitignore_sections(profile: Option<Profile>) -> Vec<GitignoreSection> {
+    let stack = profile.map_or(GITIGNORE_DEFAULT_STACK, Profile::gitignore);
+    std::iter::once(GITIGNORE_SPRINGFIELD)
+        .chain(stack.iter().copied())
+        .chain(GITIGNORE_COMMON.iter().copied())
+        .collect()
+}
+
+pub(crate) const CLAUDE_SETTINGS_DENY_RULES: &[&str] = &[
     "Edit .sgf/**",
     "Write .sgf/**",
     "Bash rm .sgf/**",
@@ -106,6 +205,19 @@ const SANDBOX_ALLOWED_DOMAINS: &[&str] = &[
     "registry.yarnpkg.com",
 ];
 
+const SANDBOX_BASE_DOMAINS: &[&str] = &["localhost", "github.com", "*.githubusercontent.com"];
+
+fn sandbox_domains(profile: Option<Profile>) -> Vec<&'static str> {
+    match profile {
+        None => SANDBOX_ALLOWED_DOMAINS.to_vec(),
+        Some(p) => SANDBOX_BASE_DOMAINS
+            .iter()
+            .chain(p.allowed_domains())
+            .copied()
+            .collect(),
+    }
+}
+
 const PRETTIERIGNORE: &str = "\
 .forma/
 .pensa/
@@ -146,24 +258,26 @@ repos:
         stages: [post-merge, post-checkout, post-rewrite]
 ";
 
-fn merge_gitignore(root: &Path) -> io::Result<()> {
-    let path = root.join(".gitignore");
-    if !path.exists() {
-        return fs::write(&path, GITIGNORE_FULL);
-    }
-
-    let existing = fs::read_to_string(&path)?;
+fn gitignore_content(root: &Path, profile: Option<Profile>) -> io::Result<Option<String>> {
+    let sections = gitignore_sections(profile);
+    let Some(existing) = read_optional(&root.join(".gitignore"))? else {
+        let full: Vec<String> = sections
+            .iter()
+            .map(|(heading, entries)| format!("# {heading}\n{}\n", entries.join("\n")))
+            .collect();
+        return Ok(Some(full.join("\n")));
+    };
     let existing_lines: Vec<&str> = existing.lines().map(|l| l.trim()).collect();
 
     let mut to_add: Vec<&str> = Vec::new();
-    for entry in GITIGNORE_ENTRIES {
-        if !existing_lines.contains(entry) {
+    for entry in sections.iter().flat_map(|(_, entries)| entries.iter()) {
+        if !existing_lines.contains(entry) && !to_add.contains(entry) {
             to_add.push(entry);
         }
     }
 
     if to_add.is_empty() {
-        return Ok(());
+        return Ok(None);
     }
 
     let mut content = existing;
@@ -176,19 +290,27 @@ fn merge_gitignore(root: &Path) -> io::Result<()> {
         content.push_str(entry);
         content.push('\n');
     }
-    fs::write(&path, content)
+    Ok(Some(content))
 }
 
-fn merge_claude_settings(root: &Path) -> io::Result<()> {
-    let dir = root.join(".claude");
-    fs::create_dir_all(&dir)?;
-    let path = dir.join("settings.json");
+pub(crate) fn merge_claude_settings(root: &Path) -> io::Result<()> {
+    let config = crate::config::SgfConfig::load(root)?;
+    write_change(
+        root,
+        CLAUDE_SETTINGS,
+        claude_settings_content(root, &config)?,
+    )
+}
 
-    let mut doc: Value = if path.exists() {
-        let content = fs::read_to_string(&path)?;
-        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
-    } else {
-        serde_json::json!({})
+fn claude_settings_content(
+    root: &Path,
+    config: &crate::config::SgfConfig,
+) -> io::Result<Option<String>> {
+    let existing = read_optional(&root.join(CLAUDE_SETTINGS))?;
+    let mut doc: Value = match &existing {
+        Some(content) => serde_json::from_str(content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        None => serde_json::json!({}),
     };
 
     let permissions = doc
@@ -252,8 +374,12 @@ fn merge_claude_settings(root: &Path) -> io::Result<()> {
     let domains_arr = allowed_domains.as_array_mut().ok_or_else(|| {
         io::Error::new(io::ErrorKind::InvalidData, "allowedDomains is not an array")
     })?;
-    for domain in SANDBOX_ALLOWED_DOMAINS {
-        let val = Value::String(domain.to_string());
+    for domain in sandbox_domains(config.profile)
+        .iter()
+        .map(|d| d.to_string())
+        .chain(config.sandbox.allowed_domains.iter().cloned())
+    {
+        let val = Value::String(domain);
         if !domains_arr.contains(&val) {
             domains_arr.push(val);
         }
@@ -263,16 +389,13 @@ fn merge_claude_settings(root: &Path) -> io::Result<()> {
         .or_insert(serde_json::json!(true));
 
     let formatted = serde_json::to_string_pretty(&doc).map_err(io::Error::other)?;
-    fs::write(&path, format!("{formatted}\n"))
+    Ok(changed(existing, format!("{formatted}\n")))
 }
 
-fn merge_pre_commit_config(root: &Path) -> io::Result<()> {
-    let path = root.join(".pre-commit-config.yaml");
-    if !path.exists() {
-        return fs::write(&path, PRE_COMMIT_YAML_FULL);
-    }
-
-    let content = fs::read_to_string(&path)?;
+fn pre_commit_content(root: &Path) -> io::Result<Option<String>> {
+    let Some(content) = read_optional(&root.join(PRE_COMMIT_CONFIG))? else {
+        return Ok(Some(PRE_COMMIT_YAML_FULL.to_string()));
+    };
     let mut doc: serde_yaml::Value = serde_yaml::from_str(&content)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
@@ -302,7 +425,7 @@ fn merge_pre_commit_config(root: &Path) -> io::Result<()> {
     let has_forma_import = has_hook(&doc, "forma-import");
 
     if has_pensa_export && has_pensa_import && has_forma_export && has_forma_import {
-        return Ok(());
+        return Ok(None);
     }
 
     let repos = doc
@@ -374,7 +497,7 @@ fn merge_pre_commit_config(root: &Path) -> io::Result<()> {
     }
 
     let output = serde_yaml::to_string(&doc).map_err(io::Error::other)?;
-    fs::write(&path, output)
+    Ok(Some(output))
 }
 
 fn install_prek_hooks(root: &Path) -> io::Result<()> {
@@ -400,16 +523,21 @@ fn create_directories(root: &Path) -> io::Result<()> {
     Ok(())
 }
 
-fn write_if_missing(path: &Path, content: &str) -> io::Result<()> {
-    if path.exists() {
+fn write_change(root: &Path, rel: &str, content: Option<String>) -> io::Result<()> {
+    let Some(content) = content else {
         return Ok(());
-    }
+    };
+    let path = root.join(rel);
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
     fs::write(path, content)
 }
 
+fn changed(existing: Option<String>, new: String) -> Option<String> {
+    (existing.as_ref() != Some(&new)).then_some(new)
+}
+
 fn check_git_clean(root: &Path, paths: &[&str]) -> io::Result<Vec<String>> {
     let mut problems = Vec::new();
 
@@ -506,69 +634,171 @@ fn scaffold_frontend(root: &Path) -> io::Result<()> {
     Ok(())
 }
 
-pub fn run(root: &Path, force: bool, no_fe: bool) -> io::Result<()> {
-    if !no_fe {
-        scaffold_frontend(root)?;
+/// `.sgf/config.toml` from the template when it is missing, with `profile`
+/// recorded. Refuses to switch a project that already names another one.
+fn config_content(root: &Path, profile: Option<Profile>) -> io::Result<Option<String>> {
+    let Some(content) = read_optional(&crate::config::config_path(root))? else {
+        return Ok(Some(match profile {
+            Some(profile) => crate::config::TEMPLATE.replacen(
+                "# profile = \"rust\"",
+                &format!("profile = \"{profile}\""),
+                1,
+            ),
+            None => crate::config::TEMPLATE.to_string(),
+        }));
+    };
+    let Some(profile) = profile else {
+        return Ok(None);
+    };
+    match crate::config::SgfConfig::load(root)?.profile {
+        Some(existing) if existing == profile => Ok(None),
+        Some(existing) => Err(io::Error::other(format!(
+            "project is already initialized with the {existing} profile; \
+             change `profile` in .sgf/config.toml to switch"
+        ))),
+        None => Ok(Some(format!("profile = \"{profile}\"\n\n{content}"))),
     }
+}
 
-    create_directories(root)?;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub before: Option<String>,
+    pub after: String,
+}
 
-    if force {
-        let force_paths: Vec<&str> = SKELETON_FILES
-            .iter()
-            .filter(|sf| sf.path != "AGENTS.md")
-            .map(|sf| sf.path)
-            .collect();
-        let existing: Vec<&str> = force_paths
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    pub frontend: bool,
+    pub directories: Vec<&'static str>,
+    pub files: Vec<FileChange>,
+    pub overwritten: Vec<&'static str>,
+    pub link_claude_md: bool,
+}
+
+fn wants_frontend(root: &Path, no_fe: bool, profile: Option<Profile>) -> bool {
+    !no_fe
+        && profile.is_none_or(Profile::has_frontend)
+        && !root.join("package.json").exists()
+        && !root.join("vite.config.ts").exists()
+}
+
+pub fn plan(root: &Path, force: bool, no_fe: bool, profile: Option<Profile>) -> io::Result<Plan> {
+    let mut plan = Plan {
+        frontend: wants_frontend(root, no_fe, profile),
+        directories: DIRECTORIES
             .iter()
-            .filter(|p| root.join(p).exists())
             .copied()
-            .collect();
+            .filter(|dir| !root.join(dir).is_dir())
+            .collect(),
+        link_claude_md: root.join("CLAUDE.md").symlink_metadata().is_err(),
+        ..Default::default()
+    };
+    let mut add = |path: &str, after: Option<String>| -> io::Result<()> {
+        if let Some(after) = after {
+            plan.files.push(FileChange {
+                path: path.to_string(),
+                before: read_optional(&root.join(path))?,
+                after,
+            });
+        }
+        Ok(())
+    };
 
-        if !existing.is_empty() {
-            let problems = check_git_clean(root, &existing)?;
-            if !problems.is_empty() {
-                let list = problems.join("\n  ");
-                return Err(io::Error::other(format!(
-                    "cannot --force: the following files have issues:\n  {list}"
-                )));
-            }
-            if !confirm_overwrite(&existing)? {
-                return Err(io::Error::other("aborted"));
-            }
-            for sf in SKELETON_FILES {
-                if sf.path != "AGENTS.md" {
-                    fs::write(root.join(sf.path), sf.content)?;
-                }
+    add(CONFIG_TOML, config_content(root, profile)?)?;
+    if let Some(profile) = profile
+        && !root.join(BACKPRESSURE).exists()
+    {
+        let content = profile.backpressure().to_string();
+        add(BACKPRESSURE, Some(content.clone()))?;
+        let base = crate::upgrade::base_path(root, BACKPRESSURE);
+        let base = base.strip_prefix(root).unwrap_or(&base).to_string_lossy();
+        add(&base, Some(content))?;
+    }
+    for sf in SKELETON_FILES {
+        match read_optional(&root.join(sf.path))? {
+            None => add(sf.path, Some(sf.content.to_string()))?,
+            Some(content) if force && content != sf.content => {
+                add(sf.path, Some(sf.content.to_string()))?;
+                plan.overwritten.push(sf.path);
             }
+            Some(_) => {}
         }
+    }
+    add("AGENTS.md", agents_md_content(root, profile)?)?;
+    add(".gitignore", gitignore_content(root, profile)?)?;
+    let mut config = crate::config::SgfConfig::load(root)?;
+    config.profile = profile.or(config.profile);
+    add(CLAUDE_SETTINGS, claude_settings_content(root, &config)?)?;
+    add(PRE_COMMIT_CONFIG, pre_commit_content(root)?)?;
+    Ok(plan)
+}
 
-        for sf in SKELETON_FILES {
-            write_if_missing(&root.join(sf.path), sf.content)?;
+pub fn run(root: &Path, force: bool, no_fe: bool, profile: Option<Profile>) -> io::Result<()> {
+    if wants_frontend(root, no_fe, profile) {
+        scaffold_frontend(root)?;
+    }
+
+    let plan = plan(root, force, no_fe, profile)?;
+    if !plan.overwritten.is_empty() {
+        let problems = check_git_clean(root, &plan.overwritten)?;
+        if !problems.is_empty() {
+            let list = problems.join("\n  ");
+            return Err(io::Error::other(format!(
+                "cannot --force: the following files have issues:\n  {list}"
+            )));
         }
-    } else {
-        for sf in SKELETON_FILES {
-            write_if_missing(&root.join(sf.path), sf.content)?;
+        if !confirm_overwrite(&plan.overwritten)? {
+            return Err(io::Error::other("aborted"));
         }
     }
 
+    create_directories(root)?;
+    for file in &plan.files {
+        write_change(root, &file.path, Some(file.after.clone()))?;
+    }
+
     // CLAUDE.md is a symlink to AGENTS.md
-    let claude_md = root.join("CLAUDE.md");
-    if claude_md.symlink_metadata().is_err() {
+    if plan.link_claude_md {
         #[cfg(unix)]
-        unix_fs::symlink("AGENTS.md", &claude_md)?;
+        unix_fs::symlink("AGENTS.md", root.join("CLAUDE.md"))?;
     }
 
-    write_if_missing(&root.join(".prettierignore"), PRETTIERIGNORE)?;
-    merge_gitignore(root)?;
-    merge_claude_settings(root)?;
-    merge_pre_commit_config(root)?;
     install_prek_hooks(root)?;
 
     crate::style::print_success("project scaffolded successfully");
     Ok(())
 }
 
+pub fn dry_run(root: &Path, force: bool, no_fe: bool, profile: Option<Profile>) -> io::Result<()> {
+    let plan = plan(root, force, no_fe, profile)?;
+    if plan.frontend {
+        println!("run     pnpm create vite@latest . -- --template react-ts");
+    }
+    for dir in &plan.directories {
+        println!("create  {dir}/");
+    }
+    for file in &plan.files {
+        match &file.before {
+            None => println!(
+                "create  {} ({} lines)",
+                file.path,
+                file.after.lines().count()
+            ),
+            Some(before) => {
+                println!("{}", crate::style::bold(&format!("modify  {}", file.path)));
+                crate::upgrade::print_hunks(&crate::upgrade::unified_diff(before, &file.after)?);
+            }
+        }
+    }
+    if plan.link_claude_md {
+        println!("link    CLAUDE.md -> AGENTS.md");
+    }
+    println!("run     prek install");
+    crate::style::print_success("dry run: nothing was written");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -596,7 +826,7 @@ mod tests {
     fn creates_all_directories() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         for dir in DIRECTORIES {
             assert!(tmp.path().join(dir).is_dir(), "directory missing: {dir}");
@@ -607,7 +837,7 @@ mod tests {
     fn creates_all_skeleton_files() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         for sf in SKELETON_FILES {
             let path = tmp.path().join(sf.path);
@@ -618,16 +848,47 @@ mod tests {
     }
 
     #[test]
-    fn agents_md_scaffolded_with_repo_content() {
+    fn agents_md_scaffolded_from_template() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap();
-        assert!(!content.is_empty(), "AGENTS.md should not be empty");
-        assert!(
-            content.contains("## Code Style"),
-            "AGENTS.md should contain scaffolded repo content"
+        assert!(content.starts_with(AGENTS_MD_INTRO));
+        for heading in [
+            "## Completion Protocol",
+            "## Issue Tracking",
+            "## Backpressure",
+        ] {
+            assert!(content.contains(heading), "missing {heading}");
+        }
+        assert!(content.contains("`.iter-complete`"));
+        assert!(!content.contains("cargo"), "no profile, no commands");
+
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        run(tmp.path(), false, true, Some(Profile::Python)).unwrap();
+        let content = fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap();
+        assert!(content.contains("For this python project:\n\n- Format: `ruff format --check .`"));
+    }
+
+    #[test]
+    fn agents_md_gains_missing_sections_only() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        let custom = "# Notes\n\n## Backpressure\n\nJust run make.";
+        fs::write(tmp.path().join("AGENTS.md"), custom).unwrap();
+
+        run(tmp.path(), false, true, None).unwrap();
+        let merged = fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap();
+        assert!(merged.starts_with(&format!("{custom}\n\n## Completion Protocol")));
+        assert!(merged.contains("## Issue Tracking"));
+        assert_eq!(merged.matches("## Backpressure").count(), 1);
+
+        run(tmp.path(), false, true, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap(),
+            merged
         );
     }
 
@@ -635,7 +896,7 @@ mod tests {
     fn claude_md_is_symlink() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let claude_md = tmp.path().join("CLAUDE.md");
         let meta = claude_md.symlink_metadata().unwrap();
@@ -655,7 +916,7 @@ mod tests {
     fn cursus_directory_created() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(
             tmp.path().join(".sgf/cursus").is_dir(),
@@ -667,7 +928,7 @@ mod tests {
     fn forma_directory_created() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(
             tmp.path().join(".forma").is_dir(),
@@ -679,7 +940,7 @@ mod tests {
     fn specs_directory_not_created() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(
             !tmp.path().join("specs").exists(),
@@ -691,7 +952,7 @@ mod tests {
     fn no_memento_or_pensa_scaffolded() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(
             !tmp.path().join(".sgf/MEMENTO.md").exists(),
@@ -707,16 +968,17 @@ mod tests {
     fn does_not_overwrite_existing_skeleton() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
-        let modified = "custom AGENTS.md";
+        let modified = "custom AGENTS.md\n";
         fs::write(tmp.path().join("AGENTS.md"), modified).unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
-        assert_eq!(
-            fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap(),
-            modified
+        assert!(
+            fs::read_to_string(tmp.path().join("AGENTS.md"))
+                .unwrap()
+                .starts_with(modified)
         );
 
         let claude_md = tmp.path().join("CLAUDE.md");
@@ -733,7 +995,7 @@ mod tests {
     fn idempotent_run() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let first_run: Vec<(String, String)> = SKELETON_FILES
             .iter()
@@ -745,7 +1007,7 @@ mod tests {
             })
             .collect();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         for (path, content) in &first_run {
             let after = fs::read_to_string(tmp.path().join(path)).unwrap();
@@ -757,7 +1019,7 @@ mod tests {
     fn prek_hooks_installed() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(
             tmp.path().join(".git/hooks/pre-commit").exists(),
@@ -771,7 +1033,7 @@ mod tests {
     fn prettierignore_created() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".prettierignore")).unwrap();
         assert!(content.contains(".forma/"));
@@ -786,7 +1048,7 @@ mod tests {
         let custom = "custom\n";
         fs::write(tmp.path().join(".prettierignore"), custom).unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".prettierignore")).unwrap();
         assert_eq!(content, custom);
@@ -798,10 +1060,10 @@ mod tests {
     fn gitignore_created_from_scratch() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
-        for entry in GITIGNORE_ENTRIES {
+        for entry in gitignore_sections(None).iter().flat_map(|(_, e)| e.iter()) {
             assert!(
                 content.lines().any(|l| l.trim() == *entry),
                 "missing gitignore entry: {entry}"
@@ -816,11 +1078,11 @@ mod tests {
         git_init(tmp.path());
         fs::write(tmp.path().join(".gitignore"), "# Custom\nmy-secret.key\n").unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
         assert!(content.contains("my-secret.key"), "custom entry lost");
-        for entry in GITIGNORE_ENTRIES {
+        for entry in gitignore_sections(None).iter().flat_map(|(_, e)| e.iter()) {
             assert!(
                 content.lines().any(|l| l.trim() == *entry),
                 "missing gitignore entry after merge: {entry}"
@@ -832,10 +1094,10 @@ mod tests {
     fn gitignore_no_duplicates_on_rerun() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
         let first = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
         let second = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
 
         assert_eq!(first, second, ".gitignore changed on second run");
@@ -847,7 +1109,7 @@ mod tests {
         git_init(tmp.path());
         fs::write(tmp.path().join(".gitignore"), "/target\n.DS_Store\n").unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
         let target_count = content.lines().filter(|l| l.trim() == "/target").count();
@@ -866,7 +1128,7 @@ mod tests {
     fn settings_json_created_from_scratch() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
         let doc: Value = serde_json::from_str(&content).unwrap();
@@ -906,7 +1168,7 @@ mod tests {
         )
         .unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
         let doc: Value = serde_json::from_str(&content).unwrap();
@@ -952,9 +1214,9 @@ mod tests {
     fn settings_json_no_duplicates_on_rerun() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
         let doc: Value = serde_json::from_str(&content).unwrap();
@@ -971,13 +1233,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn settings_json_includes_config_allowed_domains() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        run(tmp.path(), false, true, None).unwrap();
+        assert!(
+            fs::read_to_string(tmp.path().join(".sgf/config.toml"))
+                .unwrap()
+                .contains("agent_command"),
+            "config template written"
+        );
+
+        fs::write(
+            tmp.path().join(".sgf/config.toml"),
+            "[sandbox]\nallowed_domains = [\"pypi.org\"]\n",
+        )
+        .unwrap();
+        run(tmp.path(), false, true, None).unwrap();
+
+        let content = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
+        let doc: Value = serde_json::from_str(&content).unwrap();
+        let domains = doc["sandbox"]["network"]["allowedDomains"]
+            .as_array()
+            .unwrap();
+        assert!(domains.contains(&Value::String("pypi.org".to_string())));
+        assert_eq!(domains.len(), SANDBOX_ALLOWED_DOMAINS.len() + 1);
+    }
+
     // --- .pre-commit-config.yaml tests ---
 
     #[test]
     fn pre_commit_created_from_scratch() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".pre-commit-config.yaml")).unwrap();
         assert!(content.contains("pensa-export"));
@@ -1003,7 +1293,7 @@ repos:
 ";
         fs::write(tmp.path().join(".pre-commit-config.yaml"), existing).unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".pre-commit-config.yaml")).unwrap();
         assert!(
@@ -1020,10 +1310,10 @@ repos:
     fn pre_commit_no_duplicates_on_rerun() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
         let first = fs::read_to_string(tmp.path().join(".pre-commit-config.yaml")).unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
         let second = fs::read_to_string(tmp.path().join(".pre-commit-config.yaml")).unwrap();
 
         for hook_id in &[
@@ -1044,7 +1334,7 @@ repos:
         // pre-commit passes staged filenames causing "unexpected argument" errors.
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(tmp.path().join(".pre-commit-config.yaml")).unwrap();
         let doc: serde_yaml::Value = serde_yaml::from_str(&content).unwrap();
@@ -1077,7 +1367,7 @@ repos:
         // properties as those from the fresh-template path.
         let tmp_fresh = TempDir::new().unwrap();
         git_init(tmp_fresh.path());
-        run(tmp_fresh.path(), false, true).unwrap();
+        run(tmp_fresh.path(), false, true, None).unwrap();
 
         let tmp_merge = TempDir::new().unwrap();
         git_init(tmp_merge.path());
@@ -1087,7 +1377,7 @@ repos:
             "repos:\n  - repo: https://example.com\n    rev: v1\n    hooks:\n      - id: dummy\n",
         )
         .unwrap();
-        run(tmp_merge.path(), false, true).unwrap();
+        run(tmp_merge.path(), false, true, None).unwrap();
 
         let fresh: serde_yaml::Value = serde_yaml::from_str(
             &fs::read_to_string(tmp_fresh.path().join(".pre-commit-config.yaml")).unwrap(),
@@ -1124,19 +1414,61 @@ repos:
         }
     }
 
+    #[test]
+    fn plan_lists_what_run_writes_and_nothing_after() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        fs::write(tmp.path().join(".gitignore"), "/out\n").unwrap();
+
+        let before = plan(tmp.path(), false, true, Some(Profile::Rust)).unwrap();
+        assert!(!tmp.path().join(".sgf").exists(), "planning writes nothing");
+        assert_eq!(before.directories, DIRECTORIES);
+        assert!(before.link_claude_md);
+        let paths: Vec<&str> = before.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                CONFIG_TOML,
+                BACKPRESSURE,
+                ".sgf/upgrade/BACKPRESSURE.md",
+                ".prettierignore",
+                "AGENTS.md",
+                ".gitignore",
+                CLAUDE_SETTINGS,
+                PRE_COMMIT_CONFIG,
+            ]
+        );
+        let gitignore = &before.files[5];
+        assert_eq!(gitignore.before.as_deref(), Some("/out\n"));
+
+        run(tmp.path(), false, true, Some(Profile::Rust)).unwrap();
+        for file in &before.files {
+            assert_eq!(
+                fs::read_to_string(tmp.path().join(&file.path)).unwrap(),
+                file.after,
+                "{}",
+                file.path
+            );
+        }
+        assert_eq!(
+            plan(tmp.path(), false, true, Some(Profile::Rust)).unwrap(),
+            Plan::default()
+        );
+    }
+
     // --- Full idempotency including config files ---
 
     #[test]
     fn full_init_idempotent_with_config_files() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let gitignore1 = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
         let settings1 = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
         let precommit1 = fs::read_to_string(tmp.path().join(".pre-commit-config.yaml")).unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let gitignore2 = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
         let settings2 = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
@@ -1156,7 +1488,7 @@ repos:
     fn backpressure_not_scaffolded() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(
             !tmp.path().join("BACKPRESSURE.md").exists(),
@@ -1172,7 +1504,7 @@ repos:
     fn prompts_not_scaffolded() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(
             !tmp.path().join(".sgf/prompts").exists(),
@@ -1184,16 +1516,19 @@ repos:
     fn force_does_not_overwrite_agents_md() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let agents_path = tmp.path().join("AGENTS.md");
         let custom = "Custom AGENTS.md content\n";
         fs::write(&agents_path, custom).unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         let content = fs::read_to_string(&agents_path).unwrap();
-        assert_eq!(content, custom, "should not overwrite AGENTS.md");
+        assert!(
+            content.starts_with(custom),
+            "should not overwrite AGENTS.md"
+        );
     }
 
     #[test]
@@ -1249,7 +1584,7 @@ repos:
     fn warns_missing_memento_and_backpressure() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(
             !tmp.path().join(".sgf/MEMENTO.md").exists(),
@@ -1269,7 +1604,7 @@ repos:
         fs::write(tmp.path().join(".sgf/MEMENTO.md"), "# Memento").unwrap();
         fs::write(tmp.path().join(".sgf/BACKPRESSURE.md"), "# Backpressure").unwrap();
 
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(tmp.path().join(".sgf/MEMENTO.md").exists());
         assert!(tmp.path().join(".sgf/BACKPRESSURE.md").exists());
@@ -1305,7 +1640,7 @@ repos:
     fn no_fe_flag_skips_frontend_scaffolding() {
         let tmp = TempDir::new().unwrap();
         git_init(tmp.path());
-        run(tmp.path(), false, true).unwrap();
+        run(tmp.path(), false, true, None).unwrap();
 
         assert!(!tmp.path().join("package.json").exists());
         assert!(!tmp.path().join("vite.config.ts").exists());
@@ -1397,7 +1732,7 @@ repos:
             return;
         }
 
-        run(tmp.path(), false, false).unwrap();
+        run(tmp.path(), false, false, None).unwrap();
         let pkg = fs::read_to_string(tmp.path().join("package.json")).unwrap();
 
         // Commit skeleton so --force doesn't fail on untracked
@@ -1412,9 +1747,65 @@ repos:
             .output()
             .unwrap();
 
-        run(tmp.path(), true, false).unwrap();
+        run(tmp.path(), true, false, None).unwrap();
         let pkg_after = fs::read_to_string(tmp.path().join("package.json")).unwrap();
 
         assert_eq!(pkg, pkg_after, "force should not re-run create-vite");
     }
+
+    // --- Profile tests ---
+
+    #[test]
+    fn python_profile_tailors_scaffold() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        // No --no-fe: a Python project never gets a Vite frontend.
+        run(tmp.path(), false, false, Some(Profile::Python)).unwrap();
+
+        assert!(!tmp.path().join("package.json").exists());
+        let gitignore = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
+        assert!(gitignore.contains("# Python\n__pycache__/"), "{gitignore}");
+        assert!(gitignore.contains(".sgf/logs/"));
+        assert!(gitignore.contains(".DS_Store"));
+        assert!(!gitignore.contains("/target"), "{gitignore}");
+        assert!(!gitignore.contains("node_modules/"), "{gitignore}");
+
+        let backpressure = fs::read_to_string(tmp.path().join(".sgf/BACKPRESSURE.md")).unwrap();
+        assert!(backpressure.contains("pytest"));
+
+        let config = crate::config::SgfConfig::load(tmp.path()).unwrap();
+        assert_eq!(config.profile, Some(Profile::Python));
+
+        let content = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
+        let doc: Value = serde_json::from_str(&content).unwrap();
+        let domains = doc["sandbox"]["network"]["allowedDomains"]
+            .as_array()
+            .unwrap();
+        assert!(domains.contains(&Value::String("pypi.org".to_string())));
+        assert!(domains.contains(&Value::String("github.com".to_string())));
+        assert!(!domains.contains(&Value::String("crates.io".to_string())));
+    }
+
+    #[test]
+    fn profile_is_added_to_existing_config_and_not_switched() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        fs::create_dir_all(tmp.path().join(".sgf")).unwrap();
+        fs::write(tmp.path().join(".sgf/config.toml"), "jobs = 2\n").unwrap();
+        fs::write(tmp.path().join(".sgf/BACKPRESSURE.md"), "# Mine\n").unwrap();
+
+        run(tmp.path(), false, true, Some(Profile::Rust)).unwrap();
+        let config = crate::config::SgfConfig::load(tmp.path()).unwrap();
+        assert_eq!(config.profile, Some(Profile::Rust));
+        assert_eq!(config.jobs, Some(2));
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(".sgf/BACKPRESSURE.md")).unwrap(),
+            "# Mine\n",
+            "existing backpressure is kept"
+        );
+
+        run(tmp.path(), false, true, Some(Profile::Rust)).unwrap();
+        let err = run(tmp.path(), false, true, Some(Profile::Node)).unwrap_err();
+        assert!(err.to_string().contains("rust profile"), "{err}");
+    }
 }
@@ -0,0 +1,300 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::next::pn;
+use crate::style;
+
+pub const TRIAGED_LABEL: &str = "triaged";
+
+const PRIORITIES: &[&str] = &["p0", "p1", "p2", "p3"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Bug {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub status: String,
+    pub priority: String,
+    #[serde(default)]
+    pub spec: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub closed_at: Option<String>,
+    #[serde(default)]
+    pub resolution: Option<String>,
+}
+
+fn parse_time(t: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(t)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+impl Bug {
+    fn created(&self) -> Option<DateTime<Utc>> {
+        parse_time(&self.created_at)
+    }
+
+    /// When it was closed as fixed. Bugs closed before pensa recorded
+    /// resolutions count as fixed.
+    fn fixed_at(&self) -> Option<DateTime<Utc>> {
+        let fixed =
+            self.status == "closed" && self.resolution.as_deref().is_none_or(|r| r == "fixed");
+        fixed.then(|| parse_time(self.closed_at.as_deref()?))?
+    }
+}
+
+pub fn bugs(root: &Path) -> io::Result<Vec<Bug>> {
+    serde_json::from_slice(&pn(root, &["list", "-t", "bug", "--json"])?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn labels(root: &Path, id: &str) -> io::Result<Vec<String>> {
+    serde_json::from_slice(&pn(root, &["label", "list", id, "--json"])?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn untriaged(root: &Path) -> io::Result<Vec<Bug>> {
+    let mut queue = Vec::new();
+    for bug in bugs(root)? {
+        if bug.status == "open" && !labels(root, &bug.id)?.iter().any(|l| l == TRIAGED_LABEL) {
+            queue.push(bug);
+        }
+    }
+    queue.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(queue)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Decision {
+    pub priority: Option<String>,
+    pub spec: Option<String>,
+    pub deps: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Triage(Decision),
+    Skip,
+    Quit,
+}
+
+/// Parses a triage answer: `0`-`3` (or `p0`-`p3`) sets the priority, `@auth`
+/// the spec, and `+pn-3c4d` adds a dependency, in any order. An empty line
+/// accepts the bug as it is; `s` skips it and `q` quits.
+pub fn parse_answer(line: &str) -> Result<Answer, String> {
+    let line = line.trim();
+    match line {
+        "s" | "skip" => return Ok(Answer::Skip),
+        "q" | "quit" => return Ok(Answer::Quit),
+        _ => {}
+    }
+    let mut decision = Decision::default();
+    for token in line.split_whitespace() {
+        let priority = token.strip_prefix('p').unwrap_or(token);
+        if let Some(spec) = token.strip_prefix('@').filter(|s| !s.is_empty()) {
+            decision.spec = Some(spec.to_string());
+        } else if let Some(dep) = token.strip_prefix('+').filter(|d| !d.is_empty()) {
+            decision.deps.push(dep.to_string());
+        } else if let Some(p) = PRIORITIES.iter().find(|p| p[1..] == *priority) {
+            decision.priority = Some(p.to_string());
+        } else {
+            return Err(format!(
+                "don't understand {token:?} (0-3 priority, @spec, +dep, s skip, q quit)"
+            ));
+        }
+    }
+    Ok(Answer::Triage(decision))
+}
+
+fn apply(root: &Path, bug: &Bug, decision: &Decision) -> io::Result<()> {
+    let mut update = vec!["update", bug.id.as_str()];
+    if let Some(priority) = &decision.priority {
+        update.extend(["-p", priority]);
+    }
+    if let Some(spec) = &decision.spec {
+        update.extend(["--spec", spec]);
+    }
+    if update.len() > 2 {
+        update.push("--json");
+        pn(root, &update)?;
+    }
+    for dep in &decision.deps {
+        pn(root, &["dep", "add", &bug.id, dep, "--json"])?;
+    }
+    pn(root, &["label", "add", &bug.id, TRIAGED_LABEL, "--json"]).map(|_| ())
+}
+
+fn show(bug: &Bug, position: usize, total: usize) {
+    let age = bug
+        .created()
+        .map_or(0, |created| (Utc::now() - created).num_days());
+    println!();
+    println!(
+        "{} {}  {}  {}",
+        style::dim(&format!("[{position}/{total}]")),
+        style::bold(&bug.id),
+        bug.priority,
+        bug.title
+    );
+    println!(
+        "  {}",
+        style::dim(&format!(
+            "spec: {}, opened {age}d ago",
+            bug.spec.as_deref().unwrap_or("-")
+        ))
+    );
+    if let Some(description) = bug.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        for line in description.trim_end().lines().take(5) {
+            println!("  {line}");
+        }
+    }
+}
+
+fn ask(input: &mut impl BufRead) -> io::Result<Answer> {
+    loop {
+        eprint!("0-3 priority, @spec, +dep, Enter to accept, s to skip, q to quit: ");
+        io::stderr().flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(Answer::Quit);
+        }
+        match parse_answer(&line) {
+            Ok(answer) => return Ok(answer),
+            Err(e) => style::print_warning(&e),
+        }
+    }
+}
+
+pub fn triage(root: &Path) -> io::Result<()> {
+    let queue = untriaged(root)?;
+    if queue.is_empty() {
+        style::print_success("no untriaged bugs");
+        return Ok(());
+    }
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut input = stdin.lock();
+    let (mut triaged, mut skipped) = (0, 0);
+    for (i, bug) in queue.iter().enumerate() {
+        show(bug, i + 1, queue.len());
+        let answer = ask(&mut input)?;
+        if !interactive {
+            eprintln!();
+        }
+        match answer {
+            Answer::Triage(decision) => match apply(root, bug, &decision) {
+                Ok(()) => triaged += 1,
+                Err(e) => {
+                    style::print_warning(&format!("{}: {e}", bug.id));
+                    skipped += 1;
+                }
+            },
+            Answer::Skip => skipped += 1,
+            Answer::Quit => break,
+        }
+    }
+    let left = queue.len() - triaged;
+    style::print_success(&format!(
+        "triaged {triaged}, skipped {skipped}; {left} left in the queue"
+    ));
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WeekStats {
+    pub week_start: String,
+    pub opened: usize,
+    pub fixed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BugStats {
+    pub open: usize,
+    pub weeks: Vec<WeekStats>,
+}
+
+pub fn stats(bugs: &[Bug], weeks: u32, now: DateTime<Utc>) -> BugStats {
+    let weeks = (0..weeks)
+        .rev()
+        .map(|ago| {
+            let end = now - TimeDelta::weeks(i64::from(ago));
+            let start = end - TimeDelta::weeks(1);
+            let within = |t: Option<DateTime<Utc>>| t.is_some_and(|t| t >= start && t < end);
+            WeekStats {
+                week_start: start.date_naive().to_string(),
+                opened: bugs.iter().filter(|b| within(b.created())).count(),
+                fixed: bugs.iter().filter(|b| within(b.fixed_at())).count(),
+            }
+        })
+        .collect();
+    BugStats {
+        open: bugs.iter().filter(|b| b.status != "closed").count(),
+        weeks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bug(created_days_ago: i64, closed: Option<(i64, &str)>) -> Bug {
+        let now = Utc::now();
+        Bug {
+            id: format!("pn-{created_days_ago:04x}"),
+            title: "Crash".to_string(),
+            description: None,
+            status: if closed.is_some() { "closed" } else { "open" }.to_string(),
+            priority: "p2".to_string(),
+            spec: None,
+            created_at: (now - TimeDelta::days(created_days_ago)).to_rfc3339(),
+            closed_at: closed.map(|(days, _)| (now - TimeDelta::days(days)).to_rfc3339()),
+            resolution: closed.map(|(_, r)| r.to_string()),
+        }
+    }
+
+    #[test]
+    fn answers_parse_in_any_order() {
+        assert_eq!(
+            parse_answer("1 @auth +pn-3c4d +pn-5e6f\n"),
+            Ok(Answer::Triage(Decision {
+                priority: Some("p1".to_string()),
+                spec: Some("auth".to_string()),
+                deps: vec!["pn-3c4d".to_string(), "pn-5e6f".to_string()],
+            }))
+        );
+        assert_eq!(
+            parse_answer("@billing p0"),
+            Ok(Answer::Triage(Decision {
+                priority: Some("p0".to_string()),
+                spec: Some("billing".to_string()),
+                deps: Vec::new(),
+            }))
+        );
+        assert_eq!(parse_answer(""), Ok(Answer::Triage(Decision::default())));
+        assert_eq!(parse_answer("s"), Ok(Answer::Skip));
+        assert_eq!(parse_answer("q\n"), Ok(Answer::Quit));
+        assert!(parse_answer("4").is_err());
+        assert!(parse_answer("@").is_err());
+    }
+
+    #[test]
+    fn stats_count_weekly_inflow_against_fixes() {
+        let bugs = [
+            bug(2, None),
+            bug(3, Some((1, "fixed"))),
+            bug(10, Some((9, "wont_fix"))),
+            bug(12, Some((4, "fixed"))),
+            bug(40, None),
+        ];
+        let stats = stats(&bugs, 2, Utc::now());
+        assert_eq!(stats.open, 2);
+        let counts: Vec<(usize, usize)> = stats.weeks.iter().map(|w| (w.opened, w.fixed)).collect();
+        assert_eq!(counts, [(2, 0), (2, 2)]);
+        assert!(stats.weeks[0].week_start < stats.weeks[1].week_start);
+    }
+}
@@ -1,7 +1,24 @@
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
 use crate::style;
 
 const MIN_WIDTH: usize = 40;
 
+static ART: OnceLock<String> = OnceLock::new();
+
+pub fn set_art(art: &str) {
+    let _ = ART.set(art.trim_end().to_string());
+}
+
+/// The startup art, when there is some and stdout is a terminal. Art is
+/// left out of piped output so captured logs stay readable.
+pub fn art() -> Option<&'static str> {
+    let art = ART.get().filter(|a| !a.is_empty())?;
+    let to_terminal = !crate::event_stream::enabled() && std::io::stdout().is_terminal();
+    to_terminal.then_some(art.as_str())
+}
+
 pub fn render_box(title: &str, lines: &[String]) -> String {
     render_box_styled(title, lines, style::bold)
 }
@@ -11,6 +28,12 @@ pub fn render_box_styled(
     lines: &[String],
     title_style: impl Fn(&str) -> String,
 ) -> String {
+    // A line with embedded newlines or tabs would break the right border.
+    let lines: Vec<String> = lines
+        .iter()
+        .flat_map(|l| l.split('\n'))
+        .map(|l| l.trim_end_matches('\r').replace('\t', "    "))
+        .collect();
     let title_len = title.chars().count();
     let content_width = lines
         .iter()
@@ -26,7 +49,7 @@ pub fn render_box_styled(
     out.push_str(&title_style(title));
     out.push_str(&style::dim(&format!(" {}╮", "─".repeat(fill_len))));
 
-    for line in lines {
+    for line in &lines {
         let pad = inner - 3 - line.chars().count();
         out.push('\n');
         out.push_str(&style::dim("│"));
@@ -103,6 +126,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn multi_line_body_entries_keep_the_box_aligned() {
+        let body = vec![
+            "Prompt:      fix the\nflaky\ttest".to_string(),
+            "Agent:       cl\r".to_string(),
+        ];
+        let result = strip_ansi(&render_box("Test", &body));
+        let lines: Vec<&str> = result.split('\n').collect();
+        assert_eq!(lines.len(), 5, "{result}");
+        assert!(lines[2].contains("flaky    test"), "{result}");
+        let widths: Vec<usize> = lines.iter().map(|l| l.chars().count()).collect();
+        assert!(
+            widths.windows(2).all(|w| w[0] == w[1]),
+            "widths not aligned: {:?}\n{}",
+            widths,
+            result
+        );
+    }
+
     #[test]
     fn min_width_enforced() {
         let result = strip_ansi(&render_box("Hi", &[]));
@@ -13,11 +13,18 @@ enum StreamEvent {
         session_id: Option<String>,
         #[serde(default)]
         usage: Option<Usage>,
+        #[serde(default)]
+        total_cost_usd: Option<f64>,
     },
     User {
         message: UserMessage,
     },
-    System {},
+    System {
+        #[serde(default)]
+        subtype: Option<String>,
+        #[serde(default)]
+        cwd: Option<String>,
+    },
     #[serde(other)]
     Unknown,
 }
@@ -25,6 +32,26 @@ enum StreamEvent {
 #[derive(Deserialize)]
 struct AssistantMessage {
     content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Option<TurnUsage>,
+}
+
+#[derive(Deserialize)]
+struct TurnUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+impl TurnUsage {
+    /// Tokens the turn added: new input (mostly the previous tool results)
+    /// plus its output. Cache reads are left out as they cost little.
+    fn added(&self) -> u64 {
+        self.input_tokens + self.cache_creation_input_tokens + self.output_tokens
+    }
 }
 
 #[derive(Deserialize)]
@@ -75,8 +102,12 @@ pub enum FormattedOutput {
     Usage {
         input_tokens: u64,
         output_tokens: u64,
+        cost_usd: Option<f64>,
     },
     Result(String),
+    Init {
+        cwd: String,
+    },
     Skip,
 }
 
@@ -84,17 +115,54 @@ pub enum FormattedOutput {
 pub struct FormattedToolCall {
     pub name: String,
     pub detail: String,
+    pub turn_tokens: Option<u64>,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct FormattedToolResult {
     pub lines: Vec<String>,
     pub is_error: bool,
+    pub permission_denied: bool,
     pub truncated_count: usize,
 }
 
 const MAX_TOOL_RESULT_LINES: usize = 15;
 
+pub const REPEATED_DENIALS: u32 = 3;
+
+/// Whether a failed tool result is a deny rule refusing the call, as opposed
+/// to the tool failing (a shell's `Permission denied` is the latter).
+fn is_permission_denial(text: &str) -> bool {
+    let text = text.to_ascii_lowercase();
+    text.contains("requested permissions to use")
+        || (text.contains("permission to use") && text.contains("denied"))
+}
+
+pub fn denial_line(denials: u32) -> String {
+    let calls = if denials == 1 { "call" } else { "calls" };
+    let mut line = format!("  {denials} tool {calls} denied by permission rules");
+    if denials >= REPEATED_DENIALS {
+        line.push_str("; the loop is likely stuck against its guardrails and should be stopped");
+    }
+    line
+}
+
+pub fn usage_line(input_tokens: u64, output_tokens: u64, cost_usd: Option<f64>) -> String {
+    let mut line = format!("  Input: {input_tokens} tokens · Output: {output_tokens} tokens");
+    if let Some(cost) = cost_usd {
+        line.push_str(&format!(" · Cost: ${cost:.4}"));
+    }
+    line
+}
+
+pub fn token_count(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
 pub fn format_line(line: &str) -> FormattedOutput {
     if !line.starts_with('{') {
         return FormattedOutput::Skip;
@@ -118,11 +186,18 @@ pub fn format_line(line: &str) -> FormattedOutput {
                     ContentBlock::Text { text } => texts.push(text),
                     ContentBlock::ToolUse { name, input } => {
                         let detail = format_tool_detail(&name, &input);
-                        tool_calls.push(FormattedToolCall { name, detail });
+                        tool_calls.push(FormattedToolCall {
+                            name,
+                            detail,
+                            turn_tokens: None,
+                        });
                     }
                     ContentBlock::Unknown => {}
                 }
             }
+            if let Some(last) = tool_calls.last_mut() {
+                last.turn_tokens = message.usage.as_ref().map(TurnUsage::added);
+            }
 
             if !tool_calls.is_empty() {
                 FormattedOutput::ToolCalls(tool_calls)
@@ -139,10 +214,12 @@ pub fn format_line(line: &str) -> FormattedOutput {
                     input_tokens: Some(input),
                     output_tokens: Some(output),
                 }),
+            total_cost_usd,
             ..
         } => FormattedOutput::Usage {
             input_tokens: input,
             output_tokens: output,
+            cost_usd: total_cost_usd,
         },
         StreamEvent::Result { result, .. } => FormattedOutput::Result(result),
         StreamEvent::User { message } => {
@@ -153,6 +230,7 @@ pub fn format_line(line: &str) -> FormattedOutput {
                     UserContentBlock::ToolResult { content, is_error } => {
                         let text = extract_tool_result_text(&content);
                         let is_error = is_error.unwrap_or(false);
+                        let permission_denied = is_error && is_permission_denial(&text);
                         let all_lines: Vec<String> = text.lines().map(|l| l.to_string()).collect();
                         let total = all_lines.len();
                         let truncated_count = total.saturating_sub(MAX_TOOL_RESULT_LINES);
@@ -161,6 +239,7 @@ pub fn format_line(line: &str) -> FormattedOutput {
                         Some(FormattedToolResult {
                             lines,
                             is_error,
+                            permission_denied,
                             truncated_count,
                         })
                     }
@@ -174,7 +253,11 @@ pub fn format_line(line: &str) -> FormattedOutput {
                 FormattedOutput::ToolResults(results)
             }
         }
-        StreamEvent::System {} => {
+        StreamEvent::System {
+            subtype: Some(subtype),
+            cwd: Some(cwd),
+        } if subtype == "init" => FormattedOutput::Init { cwd },
+        StreamEvent::System { .. } => {
             tracing::debug!("skipping system event");
             FormattedOutput::Skip
         }
@@ -289,6 +372,7 @@ mod tests {
         FormattedToolCall {
             name: name.into(),
             detail: detail.into(),
+            turn_tokens: None,
         }
     }
 
@@ -417,10 +501,29 @@ mod tests {
             FormattedOutput::Usage {
                 input_tokens: 12450,
                 output_tokens: 1230,
+                cost_usd: None,
             }
         );
     }
 
+    #[test]
+    fn result_with_cost_reports_it_in_usage_line() {
+        let line = r#"{"type":"result","result":"Done.","total_cost_usd":0.0421,"usage":{"input_tokens":10,"output_tokens":2}}"#;
+        let FormattedOutput::Usage {
+            input_tokens,
+            output_tokens,
+            cost_usd,
+        } = format_line(line)
+        else {
+            panic!("expected usage");
+        };
+        assert_eq!(cost_usd, Some(0.0421));
+        assert_eq!(
+            usage_line(input_tokens, output_tokens, cost_usd),
+            "  Input: 10 tokens · Output: 2 tokens · Cost: $0.0421"
+        );
+    }
+
     #[test]
     fn result_with_partial_usage_returns_result() {
         let line = r#"{"type":"result","result":"Done.","usage":{"input_tokens":100}}"#;
@@ -448,12 +551,35 @@ mod tests {
             FormattedOutput::ToolResults(results) => {
                 assert_eq!(results.len(), 1);
                 assert!(results[0].is_error);
+                assert!(!results[0].permission_denied);
                 assert_eq!(results[0].lines, vec!["Error: file not found"]);
             }
             other => panic!("expected ToolResults, got {:?}", other),
         }
     }
 
+    #[test]
+    fn denied_tool_results_are_flagged() {
+        let denied = [
+            "Claude requested permissions to use Edit, but you haven't granted it yet.",
+            "Permission to use Bash with command rm .sgf/config.toml has been denied.",
+        ];
+        for text in denied {
+            let line = format!(
+                r#"{{"type":"user","message":{{"content":[{{"type":"tool_result","content":{},"is_error":true}}]}}}}"#,
+                serde_json::to_string(text).unwrap()
+            );
+            match format_line(&line) {
+                FormattedOutput::ToolResults(results) => assert!(results[0].permission_denied),
+                other => panic!("expected ToolResults, got {:?}", other),
+            }
+        }
+        assert!(!is_permission_denial("bash: ./run.sh: Permission denied"));
+
+        assert_eq!(denial_line(1), "  1 tool call denied by permission rules");
+        assert!(denial_line(REPEATED_DENIALS).contains("should be stopped"));
+    }
+
     #[test]
     fn user_event_tool_result_truncation() {
         let long_content = (1..=20)
@@ -494,6 +620,19 @@ mod tests {
         assert_eq!(format_line(line), FormattedOutput::Skip);
     }
 
+    #[test]
+    fn init_event_reports_the_working_directory() {
+        let line = r#"{"type":"system","subtype":"init","cwd":"/work/app","tools":[]}"#;
+        assert_eq!(
+            format_line(line),
+            FormattedOutput::Init {
+                cwd: "/work/app".to_string()
+            }
+        );
+        let line = r#"{"type":"system","subtype":"compact_boundary","cwd":"/work/app"}"#;
+        assert_eq!(format_line(line), FormattedOutput::Skip);
+    }
+
     #[test]
     fn non_json_line_returns_skip() {
         assert_eq!(format_line("some random text"), FormattedOutput::Skip);
@@ -520,6 +659,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn turn_usage_lands_on_the_last_call() {
+        let line = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Read","input":{"file_path":"/a.rs"}},{"type":"tool_use","name":"Bash","input":{"command":"cargo build"}}],"usage":{"input_tokens":12000,"cache_creation_input_tokens":250,"cache_read_input_tokens":90000,"output_tokens":50}}}"#;
+        let FormattedOutput::ToolCalls(calls) = format_line(line) else {
+            panic!("expected tool calls");
+        };
+        assert_eq!(calls[0].turn_tokens, None);
+        assert_eq!(calls[1].turn_tokens, Some(12_300));
+    }
+
+    #[test]
+    fn token_counts_are_abbreviated() {
+        assert_eq!(token_count(950), "950");
+        assert_eq!(token_count(12_300), "12.3k");
+        assert_eq!(token_count(1_250_000), "1.2M");
+    }
+
     #[test]
     fn truncate_respects_utf8() {
         let s = "é".repeat(50);
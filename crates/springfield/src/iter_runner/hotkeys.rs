@@ -0,0 +1,178 @@
+use std::cell::Cell;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use shutdown::{ShutdownController, ShutdownStatus};
+
+use super::{TeeWriter, banner, write_terminal};
+use crate::style;
+
+const MAX_LINE_WIDTH: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hotkey {
+    Pause,
+    Resume,
+    Stop,
+}
+
+impl Hotkey {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'p' | b'P' => Some(Self::Pause),
+            b'\r' | b'\n' | b' ' => Some(Self::Resume),
+            b'q' | b'Q' => Some(Self::Stop),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct Hotkeys {
+    enabled: bool,
+    iterations: u32,
+    iteration: Cell<u32>,
+    spent_before: Cell<f64>,
+    stop_requested: Cell<bool>,
+}
+
+impl Hotkeys {
+    pub(crate) fn new(controller: &ShutdownController, iterations: u32) -> Self {
+        Self {
+            enabled: controller.monitors_stdin(),
+            iterations,
+            iteration: Cell::new(1),
+            spent_before: Cell::new(0.0),
+            stop_requested: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn begin_iteration(&self, iteration: u32, spent_usd: f64) {
+        self.iteration.set(iteration);
+        self.spent_before.set(spent_usd);
+    }
+
+    pub(crate) fn stop_requested(&self) -> bool {
+        self.stop_requested.get()
+    }
+
+    pub(crate) fn listen(&self, tee: &TeeWriter) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(mut termios) = super::save_terminal_settings() {
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            super::restore_terminal_settings(&termios);
+        }
+        if self.iteration.get() == 1 {
+            tee.write_ansi_line(&style::dim(
+                "p to pause and show status, q to stop after this iteration",
+            ));
+        }
+    }
+
+    /// Acts on the keys pressed since the last call. Returns how long output
+    /// was paused, so the caller's timeouts can leave that time out.
+    pub(crate) fn handle_keys(
+        &self,
+        controller: &ShutdownController,
+        tee: &TeeWriter,
+        cost_usd: f64,
+    ) -> Duration {
+        let mut paused = Duration::ZERO;
+        if !self.enabled {
+            return paused;
+        }
+        while let Some(key) = controller.take_key() {
+            match Hotkey::from_byte(key) {
+                Some(Hotkey::Pause) => paused += self.pause(controller, tee, cost_usd),
+                Some(Hotkey::Stop) => self.request_stop(tee),
+                Some(Hotkey::Resume) | None => {}
+            }
+        }
+        paused
+    }
+
+    fn request_stop(&self, tee: &TeeWriter) {
+        if !self.stop_requested.replace(true) {
+            tee.write_ansi_line(&style::yellow("stopping after this iteration"));
+        }
+    }
+
+    /// Shows the status screen and waits for a key, leaving the agent's
+    /// output unread meanwhile. A shutdown also ends the pause.
+    fn pause(&self, controller: &ShutdownController, tee: &TeeWriter, cost_usd: f64) -> Duration {
+        let started = Instant::now();
+        write_terminal(&format!(
+            "\n{}\n",
+            status_screen(
+                self.iteration.get(),
+                self.iterations,
+                self.spent_before.get() + cost_usd,
+                &tee.recent_lines(),
+            )
+        ));
+        loop {
+            if controller.poll() == ShutdownStatus::Shutdown {
+                break;
+            }
+            match controller.take_key().and_then(Hotkey::from_byte) {
+                Some(Hotkey::Pause | Hotkey::Resume) => break,
+                Some(Hotkey::Stop) => {
+                    self.request_stop(tee);
+                    break;
+                }
+                None => thread::sleep(Duration::from_millis(100)),
+            }
+        }
+        write_terminal(&format!("{}\n", style::dim("resumed")));
+        started.elapsed()
+    }
+}
+
+pub fn status_screen(iteration: u32, iterations: u32, cost_usd: f64, recent: &[String]) -> String {
+    let mut lines = vec![format!("cost so far: ${cost_usd:.2}")];
+    if !recent.is_empty() {
+        lines.push(String::new());
+        lines.extend(recent.iter().map(|line| {
+            if line.chars().count() > MAX_LINE_WIDTH {
+                let cut: String = line.chars().take(MAX_LINE_WIDTH - 1).collect();
+                format!("{cut}…")
+            } else {
+                line.clone()
+            }
+        }));
+    }
+    lines.push(String::new());
+    lines.push("p or Enter to resume, q to stop after this iteration".to_string());
+    banner::render_box_styled(
+        &format!("Paused: iteration {iteration} of {iterations}"),
+        &lines,
+        |s| style::bold(&style::yellow(s)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_map_to_hotkeys() {
+        assert_eq!(Hotkey::from_byte(b'p'), Some(Hotkey::Pause));
+        assert_eq!(Hotkey::from_byte(b'Q'), Some(Hotkey::Stop));
+        assert_eq!(Hotkey::from_byte(b'\n'), Some(Hotkey::Resume));
+        assert_eq!(Hotkey::from_byte(b'x'), None);
+    }
+
+    #[test]
+    fn status_screen_shows_iteration_cost_and_recent_output() {
+        let recent = vec!["  ─ Read  src/main.rs".to_string(), "x".repeat(150)];
+        let screen = style::strip_ansi(&status_screen(3, 10, 1.5, &recent));
+        assert!(screen.contains("Paused: iteration 3 of 10"), "{screen}");
+        assert!(screen.contains("cost so far: $1.50"), "{screen}");
+        assert!(screen.contains("─ Read  src/main.rs"), "{screen}");
+        assert!(screen.contains(&format!("{}…", "x".repeat(99))), "{screen}");
+        assert!(!screen.contains(&"x".repeat(100)), "{screen}");
+    }
+}
@@ -1,8 +1,11 @@
 pub mod banner;
 pub mod format;
+pub mod hotkeys;
 pub mod pty_tee;
+pub mod scratch;
 
 use shutdown::{ShutdownController, ShutdownStatus, kill_process_group};
+use std::collections::VecDeque;
 use std::fs;
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::process::CommandExt;
@@ -11,10 +14,12 @@ use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
+use crate::config::{NotificationConfig, SandboxConfig, SgfConfig};
 use crate::style;
+use hotkeys::Hotkeys;
 
 pub const SENTINEL: &str = ".iter-complete";
 pub const SENTINEL_MAX_DEPTH: usize = 2;
@@ -66,6 +71,61 @@ pub struct ProgrammaticResult {
 
 pub type IterationCallback = Box<dyn FnMut(u32, &str)>;
 
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentTuning {
+    pub model: Option<String>,
+    pub extra_args: Vec<String>,
+    /// Longest an AFK iteration may run before the agent is stopped and the
+    /// loop moves on to the next iteration.
+    pub iteration_timeout: Option<Duration>,
+    /// Once the cost the agent reports in AFK mode reaches this many US
+    /// dollars, the loop stops as exhausted.
+    pub max_cost_usd: Option<f64>,
+    /// Shell command run after each iteration. Exiting 0 completes the loop,
+    /// as the sentinel file does.
+    pub complete_when: Option<String>,
+    pub env: Vec<(String, String)>,
+}
+
+impl AgentTuning {
+    fn agent_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(model) = &self.model {
+            args.extend(["--model".to_string(), model.clone()]);
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+
+    pub fn cli_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(model) = &self.model {
+            args.extend(["--model".to_string(), model.clone()]);
+        }
+        if let Some(timeout) = self.iteration_timeout {
+            args.extend([
+                "--iteration-timeout".to_string(),
+                format!("{}s", timeout.as_secs()),
+            ]);
+        }
+        if let Some(max) = self.max_cost_usd {
+            args.extend(["--max-cost".to_string(), max.to_string()]);
+        }
+        if let Some(command) = &self.complete_when {
+            args.extend(["--complete-when".to_string(), command.clone()]);
+        }
+        for arg in &self.extra_args {
+            args.extend(["--agent-arg".to_string(), arg.clone()]);
+        }
+        // Names only: the child gets the values in its environment, so they
+        // stay out of its command line.
+        for (key, _) in &self.env {
+            args.extend(["--env".to_string(), key.clone()]);
+        }
+        args
+    }
+}
+
 /// Callback invoked when a retry attempt is about to be made.
 /// Arguments: (attempt_number, reason, next_retry_delay_secs).
 pub type RetryCallback = Box<dyn FnMut(u32, &str, u64)>;
@@ -77,6 +137,7 @@ pub struct IterRunnerConfig {
     pub iterations: u32,
     pub prompt: String,
     pub auto_push: bool,
+    pub push_target: vcs_utils::PushTarget,
     /// Override: path to executable replacing agent invocation (for testing).
     pub command: Option<String>,
     /// Additional prompt file paths injected via --append-system-prompt.
@@ -111,13 +172,17 @@ pub struct IterRunnerConfig {
     pub retry_max_duration_secs: u64,
     /// Called when a retry attempt is about to be made.
     pub on_retry: Option<RetryCallback>,
+    pub tuning: AgentTuning,
 }
 
 pub(crate) struct AgentExitStatus {
     pub(crate) exit_code: Option<i32>,
     pub(crate) killed_by_timeout: bool,
     pub(crate) killed_by_inactivity: bool,
+    pub(crate) stalled_for: Option<Duration>,
+    pub(crate) cost_usd: f64,
     pub(crate) ctrl_c_forwarded: bool,
+    pub(crate) wrong_cwd: Option<PathBuf>,
 }
 
 /// Exit codes returned by the iteration loop.
@@ -132,8 +197,28 @@ pub enum IterExitCode {
     Interrupted = 130,
 }
 
+fn run_post_iteration_hook(root: &Path, loop_id: Option<&str>, iteration: u32, iterations: u32) {
+    use crate::hooks::{self, Hook};
+
+    if hooks::script(root, Hook::PostIteration).is_none() {
+        return;
+    }
+    let record = loop_id.and_then(|id| crate::loop_mgmt::read_loop_record(root, id).ok().flatten());
+    let mut env = match (&record, loop_id) {
+        (Some(record), _) => hooks::record_env(record),
+        (None, Some(id)) => vec![("SGF_LOOP_ID", id.to_string())],
+        (None, None) => Vec::new(),
+    };
+    env.push(("SGF_ITERATION", iteration.to_string()));
+    env.push(("SGF_MAX_ITERATIONS", iterations.to_string()));
+    hooks::run(root, root, Hook::PostIteration, &env);
+}
+
+const RECENT_LINES: usize = 20;
+
 pub struct TeeWriter {
     log_file: Option<Mutex<fs::File>>,
+    recent: Mutex<VecDeque<String>>,
 }
 
 impl TeeWriter {
@@ -149,41 +234,76 @@ impl TeeWriter {
             }
             None => None,
         };
-        Ok(TeeWriter { log_file })
+        Ok(TeeWriter {
+            log_file,
+            recent: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    pub fn recent_lines(&self) -> Vec<String> {
+        self.recent
+            .lock()
+            .map(|r| r.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn remember(&self, line: &str) {
+        let line = style::strip_ansi(line);
+        if line.trim().is_empty() || line.starts_with(r#"{"event":"heartbeat""#) {
+            return;
+        }
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_LINES {
+                recent.pop_front();
+            }
+            recent.push_back(line);
+        }
     }
 
     pub fn writeln(&self, line: &str) {
-        let stdout = std::io::stdout();
-        let mut lock = stdout.lock();
-        if let Err(e) = writeln!(lock, "{line}") {
-            warn!(error = %e, "failed to write to stdout");
+        write_terminal(&format!("{line}\n"));
+        self.remember(line);
+        if let Some(ref f) = self.log_file
+            && let Ok(mut f) = f.lock()
+        {
+            let _ = writeln!(f, "{}", crate::logs::stamp(&style::strip_ansi(line)));
         }
-        drop(lock);
+    }
+
+    pub fn log_line(&self, line: &str) {
         if let Some(ref f) = self.log_file
             && let Ok(mut f) = f.lock()
         {
-            let _ = writeln!(f, "{}", style::strip_ansi(line));
+            let _ = writeln!(f, "{}", crate::logs::stamp(line));
         }
     }
 
     pub fn write_ansi_line(&self, line: &str) {
-        let stdout = std::io::stdout();
-        let mut lock = stdout.lock();
-        if let Err(e) = write!(lock, "\r\x1b[2K{line}\n") {
-            warn!(error = %e, "failed to write to stdout");
-        }
-        if let Err(e) = lock.flush() {
-            warn!(error = %e, "failed to flush stdout");
-        }
-        drop(lock);
+        write_terminal(&format!("\r\x1b[2K{line}\n"));
+        self.remember(line);
         if let Some(ref f) = self.log_file
             && let Ok(mut f) = f.lock()
         {
-            let _ = writeln!(f, "{}", style::strip_ansi(line));
+            let _ = writeln!(f, "{}", crate::logs::stamp(&style::strip_ansi(line)));
         }
     }
 }
 
+/// Writes runner output to stdout, or to stderr while stdout carries the
+/// `--output json` event stream.
+fn write_terminal(text: &str) {
+    let result = if crate::event_stream::enabled() {
+        let mut lock = std::io::stderr().lock();
+        lock.write_all(text.as_bytes()).and_then(|()| lock.flush())
+    } else {
+        let mut lock = std::io::stdout().lock();
+        lock.write_all(text.as_bytes()).and_then(|()| lock.flush())
+    };
+    if let Err(e) = result {
+        warn!(error = %e, "failed to write runner output");
+    }
+}
+
 pub fn find_sentinel(dir: &Path, max_depth: usize) -> Option<PathBuf> {
     let candidate = dir.join(SENTINEL);
     if candidate.exists() {
@@ -259,13 +379,46 @@ fn build_append_system_prompt_args(prompt_files: &[String]) -> Vec<String> {
     vec!["--append-system-prompt".to_string(), parts.join("\n")]
 }
 
-fn ding_watcher(stop: &AtomicBool) {
+pub fn agent_args(
+    config: &IterRunnerConfig,
+    is_file: bool,
+    iteration: u32,
+    session_id: &str,
+) -> Vec<String> {
+    let mut args = vec!["--verbose"];
+    if config.afk {
+        args.extend(["--print", "--output-format", "stream-json"]);
+    }
+    args.extend([
+        "--dangerously-skip-permissions",
+        "--settings",
+        r#"{"autoMemoryEnabled": false, "sandbox": {"allowUnsandboxedCommands": false}}"#,
+    ]);
+    let mut args: Vec<String> = args.into_iter().map(str::to_string).collect();
+    args.extend(build_append_system_prompt_args(&config.prompt_files));
+    args.extend(config.tuning.agent_args());
+    match &config.resume {
+        Some(resume) if iteration == 1 => {
+            args.extend(["--resume".to_string(), resume.clone()]);
+        }
+        _ => {
+            args.extend(["--session-id".to_string(), session_id.to_string()]);
+            args.push(if is_file {
+                format!("@{}", config.prompt)
+            } else {
+                config.prompt.clone()
+            });
+        }
+    }
+    args
+}
+
+fn ding_watcher(stop: &AtomicBool, notifications: &NotificationConfig) {
     while !stop.load(Ordering::Relaxed) {
         if Path::new(DING_SENTINEL).exists() {
             let _ = fs::remove_file(DING_SENTINEL);
-            if let Ok(mut child) = Command::new("afplay")
-                .arg("/System/Library/Sounds/Blow.aiff")
-                .spawn()
+            if notifications.enabled()
+                && let Ok(mut child) = Command::new("afplay").arg(notifications.sound()).spawn()
             {
                 let _ = child.wait();
             }
@@ -284,33 +437,16 @@ fn run_interactive(
 ) -> AgentExitStatus {
     let stop = Arc::new(AtomicBool::new(false));
     let stop_clone = stop.clone();
-    let watcher = thread::spawn(move || ding_watcher(&stop_clone));
-
-    let asp_args = build_append_system_prompt_args(&config.prompt_files);
+    let root = config
+        .work_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("."));
+    let notifications = SgfConfig::load(&root).unwrap_or_default().notifications;
+    let watcher = thread::spawn(move || ding_watcher(&stop_clone, &notifications));
 
     let mut command = Command::new(agent_cmd);
-    command.args([
-        "--verbose",
-        "--dangerously-skip-permissions",
-        "--settings",
-        r#"{"autoMemoryEnabled": false, "sandbox": {"allowUnsandboxedCommands": false}}"#,
-    ]);
-    command.args(&asp_args);
-    for (key, val) in &config.env_vars {
-        command.env(key, val);
-    }
-    let resuming = iteration == 1 && config.resume.is_some();
-    if resuming {
-        command.args(["--resume", config.resume.as_ref().unwrap()]);
-    } else {
-        command.args(["--session-id", session_id]);
-        let prompt_arg = if is_file {
-            format!("@{}", config.prompt)
-        } else {
-            config.prompt.clone()
-        };
-        command.arg(&prompt_arg);
-    }
+    command.args(agent_args(config, is_file, iteration, session_id));
+    set_agent_env(&mut command, config, iteration);
 
     let result =
         pty_tee::run_interactive_with_pty(&mut command, config.log_file.as_deref(), controller);
@@ -333,17 +469,91 @@ fn run_interactive(
                 exit_code: None,
                 killed_by_timeout: false,
                 killed_by_inactivity: false,
+                stalled_for: None,
                 ctrl_c_forwarded: false,
+                wrong_cwd: None,
+                cost_usd: 0.0,
             }
         }
     }
 }
 
+/// Gives the agent sgf's environment, narrowed to `[sandbox] env` when that
+/// is set, and the iteration's scratch directory, then the loop's own
+/// variables and `--env` on top.
+fn set_agent_env(cmd: &mut Command, config: &IterRunnerConfig, iteration: u32) {
+    let root = config.work_dir.as_deref().unwrap_or(Path::new("."));
+    let sandbox = match SgfConfig::load(root) {
+        Ok(config) => config.sandbox,
+        Err(e) => {
+            // Fail closed: a config we can't read may have narrowed the env.
+            warn!(error = %e, "failed to load sgf config; passing only the base env to the agent");
+            SandboxConfig {
+                env: Some(Vec::new()),
+                ..Default::default()
+            }
+        }
+    };
+    if sandbox.env.is_some() {
+        cmd.env_clear();
+        cmd.envs(
+            std::env::vars_os()
+                .filter(|(key, _)| key.to_str().is_some_and(|key| sandbox.passes_env(key))),
+        );
+    }
+    let project = match sandbox.project_path {
+        Some(path) => path,
+        None => std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf()),
+    };
+    cmd.env(
+        scratch::SCRATCH_ENV,
+        project.join(scratch::iteration_dir(config.loop_id.as_deref(), iteration)),
+    );
+    cmd.envs(config.env_vars.iter().cloned());
+    cmd.envs(config.tuning.env.iter().cloned());
+}
+
+/// The AFK reader's clocks. A pause moves each of them forward by its length,
+/// so time spent paused counts toward no timeout, the watchdog included.
+struct AfkClocks {
+    started_at: Instant,
+    last_activity_at: Instant,
+    last_heartbeat_at: Instant,
+    result_received_at: Option<Instant>,
+}
+
+impl AfkClocks {
+    fn new(now: Instant) -> Self {
+        Self {
+            started_at: now,
+            last_activity_at: now,
+            last_heartbeat_at: now,
+            result_received_at: None,
+        }
+    }
+
+    fn shift(&mut self, paused: Duration) {
+        self.started_at += paused;
+        self.last_activity_at += paused;
+        self.last_heartbeat_at += paused;
+        if let Some(received_at) = &mut self.result_received_at {
+            *received_at += paused;
+        }
+    }
+
+    /// How long the agent has been silent at `now`.
+    fn idle(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_activity_at)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn run_afk(
     agent_cmd: &str,
     config: &IterRunnerConfig,
     is_file: bool,
     controller: &ShutdownController,
+    hotkeys: &Hotkeys,
     tee: &TeeWriter,
     iteration: u32,
     session_id: &str,
@@ -356,34 +566,9 @@ fn run_afk(
         Ok(())
     };
 
-    let asp_args = build_append_system_prompt_args(&config.prompt_files);
-
     let mut cmd = Command::new(agent_cmd);
-    cmd.args([
-        "--verbose",
-        "--print",
-        "--output-format",
-        "stream-json",
-        "--dangerously-skip-permissions",
-        "--settings",
-        r#"{"autoMemoryEnabled": false, "sandbox": {"allowUnsandboxedCommands": false}}"#,
-    ]);
-    cmd.args(&asp_args);
-    for (key, val) in &config.env_vars {
-        cmd.env(key, val);
-    }
-    let resuming = iteration == 1 && config.resume.is_some();
-    if resuming {
-        cmd.args(["--resume", config.resume.as_ref().unwrap()]);
-    } else {
-        cmd.args(["--session-id", session_id]);
-        let prompt_arg = if is_file {
-            format!("@{}", config.prompt)
-        } else {
-            config.prompt.clone()
-        };
-        cmd.arg(&prompt_arg);
-    }
+    cmd.args(agent_args(config, is_file, iteration, session_id));
+    set_agent_env(&mut cmd, config, iteration);
     let child = unsafe {
         cmd.stdin(Stdio::null())
             .stdout(Stdio::piped())
@@ -400,7 +585,10 @@ fn run_afk(
                 exit_code: None,
                 killed_by_timeout: false,
                 killed_by_inactivity: false,
+                stalled_for: None,
                 ctrl_c_forwarded: false,
+                wrong_cwd: None,
+                cost_usd: 0.0,
             };
         }
     };
@@ -413,7 +601,10 @@ fn run_afk(
                 exit_code: None,
                 killed_by_timeout: false,
                 killed_by_inactivity: false,
+                stalled_for: None,
                 ctrl_c_forwarded: false,
+                wrong_cwd: None,
+                cost_usd: 0.0,
             };
         }
     };
@@ -430,17 +621,32 @@ fn run_afk(
     }));
 
     let child_pid = child.id();
-    let mut result_received_at: Option<std::time::Instant> = None;
     let post_result_timeout = config.post_result_timeout;
     let inactivity_timeout = config.inactivity_timeout;
     let hb_interval = heartbeat_interval();
     let mut killed_by_timeout = false;
     let mut killed_by_inactivity = false;
-    let mut last_activity_at = std::time::Instant::now();
-    let mut last_heartbeat_at = std::time::Instant::now();
+    let mut stalled_for = None;
+    let mut wrong_cwd = None;
+    let root = config.work_dir.as_deref().unwrap_or(Path::new("."));
+    let sgf_config = SgfConfig::load(root).unwrap_or_default();
+    let project = sgf_config
+        .sandbox
+        .project_path
+        .clone()
+        .unwrap_or_else(|| root.to_path_buf());
+    let stall_after = sgf_config.watchdog.stall_after();
+    let expensive_turn = sgf_config.expensive_turn_tokens();
     let mut got_any_output = false;
+    let mut cost_usd = 0.0;
+    let mut denials = 0;
+    let mut clocks = AfkClocks::new(Instant::now());
+    hotkeys.listen(tee);
 
     loop {
+        let paused = hotkeys.handle_keys(controller, tee, cost_usd);
+        clocks.shift(paused);
+
         if controller.poll() == ShutdownStatus::Shutdown {
             kill_process_group(child_pid, Duration::from_millis(200));
             let _ = child.wait();
@@ -451,11 +657,14 @@ fn run_afk(
                 exit_code: None,
                 killed_by_timeout: false,
                 killed_by_inactivity: false,
+                stalled_for: None,
                 ctrl_c_forwarded: false,
+                wrong_cwd: None,
+                cost_usd: 0.0,
             };
         }
 
-        if let Some(received_at) = result_received_at
+        if let Some(received_at) = clocks.result_received_at
             && received_at.elapsed() > post_result_timeout
         {
             warn!(
@@ -466,9 +675,24 @@ fn run_afk(
             break;
         }
 
+        if let Some(limit) = config.tuning.iteration_timeout
+            && clocks.started_at.elapsed() > limit
+        {
+            warn!(
+                limit_secs = limit.as_secs(),
+                "iteration timeout reached, stopping agent"
+            );
+            tee.write_ansi_line(&style::yellow(&format!(
+                "iteration timed out after {}s",
+                limit.as_secs()
+            )));
+            killed_by_timeout = true;
+            break;
+        }
+
         match rx.recv_timeout(Duration::from_millis(100)) {
             Ok(Ok(line)) => {
-                last_activity_at = std::time::Instant::now();
+                clocks.last_activity_at = Instant::now();
                 got_any_output = true;
                 match format::format_line(&line) {
                     format::FormattedOutput::Text(text) => {
@@ -480,23 +704,51 @@ fn run_afk(
                     }
                     format::FormattedOutput::ToolCalls(calls) => {
                         for call in &calls {
+                            let spend = match call.turn_tokens {
+                                Some(t) if expensive_turn.is_some_and(|min| t >= min) => {
+                                    style::yellow(&format!(
+                                        "  [+{} tokens]",
+                                        format::token_count(t)
+                                    ))
+                                }
+                                _ => String::new(),
+                            };
                             tee.write_ansi_line(&format!(
-                                "  {} {}  {}",
+                                "  {} {}  {}{spend}",
                                 style::dim("─"),
                                 style::tool_name_style(&call.name),
                                 style::white(&call.detail),
                             ));
                         }
                     }
-                    format::FormattedOutput::ToolResults(_) => {}
+                    format::FormattedOutput::ToolResults(results) => {
+                        for r in results.iter().filter(|r| r.is_error) {
+                            let first = r.lines.first().map(String::as_str).unwrap_or_default();
+                            if r.permission_denied {
+                                denials += 1;
+                                tee.write_ansi_line(&style::bold(&style::yellow(&format!(
+                                    "  ⚠ permission denied: {first}"
+                                ))));
+                            } else {
+                                tee.log_line(&format!("  ✗ {first}"));
+                            }
+                        }
+                    }
                     format::FormattedOutput::Usage {
                         input_tokens,
                         output_tokens,
+                        cost_usd: cost,
                     } => {
-                        tee.write_ansi_line(&style::dim(&format!(
-                            "  Input: {input_tokens} tokens · Output: {output_tokens} tokens"
+                        tee.write_ansi_line(&style::dim(&format::usage_line(
+                            input_tokens,
+                            output_tokens,
+                            cost,
                         )));
-                        result_received_at = Some(std::time::Instant::now());
+                        if denials > 0 {
+                            tee.write_ansi_line(&style::yellow(&format::denial_line(denials)));
+                        }
+                        cost_usd += cost.unwrap_or_default();
+                        clocks.result_received_at = Some(Instant::now());
                     }
                     format::FormattedOutput::Result(text) => {
                         tee.write_ansi_line("");
@@ -504,8 +756,20 @@ fn run_afk(
                             tee.write_ansi_line(l);
                         }
                         tee.write_ansi_line("");
-                        if result_received_at.is_none() {
-                            result_received_at = Some(std::time::Instant::now());
+                        if clocks.result_received_at.is_none() {
+                            clocks.result_received_at = Some(Instant::now());
+                        }
+                    }
+                    format::FormattedOutput::Init { cwd } => {
+                        if !cwd_in_project(Path::new(&cwd), &project) {
+                            warn!(cwd, project = %project.display(), "agent is outside the project");
+                            tee.write_ansi_line(&style::red(&format!(
+                                "agent is working in {cwd}, not the project at {}; check how \
+                                 the sandbox mounts the repo, or set [sandbox] project_path",
+                                project.display()
+                            )));
+                            wrong_cwd = Some(PathBuf::from(cwd));
+                            break;
                         }
                     }
                     format::FormattedOutput::Skip => {}
@@ -532,18 +796,22 @@ fn run_afk(
                                         tee.write_ansi_line(l);
                                     }
                                     tee.write_ansi_line("");
-                                    if result_received_at.is_none() {
-                                        result_received_at = Some(std::time::Instant::now());
+                                    if clocks.result_received_at.is_none() {
+                                        clocks.result_received_at = Some(Instant::now());
                                     }
                                 }
                                 format::FormattedOutput::Usage {
                                     input_tokens,
                                     output_tokens,
+                                    cost_usd: cost,
                                 } => {
-                                    tee.write_ansi_line(&style::dim(&format!(
-                                        "  Input: {input_tokens} tokens · Output: {output_tokens} tokens"
+                                    tee.write_ansi_line(&style::dim(&format::usage_line(
+                                        input_tokens,
+                                        output_tokens,
+                                        cost,
                                     )));
-                                    result_received_at = Some(std::time::Instant::now());
+                                    cost_usd += cost.unwrap_or_default();
+                                    clocks.result_received_at = Some(Instant::now());
                                 }
                                 _ => {}
                             }
@@ -551,19 +819,30 @@ fn run_afk(
                         break;
                     }
                     Ok(None) => {
-                        if last_activity_at.elapsed() > inactivity_timeout {
+                        let idle = clocks.idle(Instant::now());
+                        if idle > inactivity_timeout {
                             warn!(
-                                elapsed_secs = last_activity_at.elapsed().as_secs(),
+                                elapsed_secs = idle.as_secs(),
                                 "no output from agent, killing due to inactivity"
                             );
                             killed_by_inactivity = true;
                             break;
                         }
-                        if result_received_at.is_none()
-                            && last_heartbeat_at.elapsed() >= hb_interval
+                        if let Some(limit) = stall_after
+                            && idle > limit
+                        {
+                            warn!(
+                                idle_secs = idle.as_secs(),
+                                "no output from agent, watchdog killing agent"
+                            );
+                            stalled_for = Some(idle);
+                            break;
+                        }
+                        if clocks.result_received_at.is_none()
+                            && clocks.last_heartbeat_at.elapsed() >= hb_interval
                         {
                             tee.writeln(r#"{"event":"heartbeat"}"#);
-                            last_heartbeat_at = std::time::Instant::now();
+                            clocks.last_heartbeat_at = Instant::now();
                         }
                     }
                     Err(_) => {}
@@ -574,7 +853,7 @@ fn run_afk(
         }
     }
 
-    if !got_any_output && !killed_by_timeout && !killed_by_inactivity {
+    if !got_any_output && !killed_by_timeout && !killed_by_inactivity && stalled_for.is_none() {
         warn!("inner session produced no output");
         tee.writeln(r#"{"event":"error","message":"inner session died"}"#);
     }
@@ -601,10 +880,21 @@ fn run_afk(
         exit_code,
         killed_by_timeout,
         killed_by_inactivity,
+        stalled_for,
         ctrl_c_forwarded: false,
+        wrong_cwd,
+        cost_usd,
     }
 }
 
+/// Whether `cwd`, as the agent reported it, is `project` or inside it.
+/// Paths that do not exist here, such as sandbox mounts, are compared as
+/// given.
+fn cwd_in_project(cwd: &Path, project: &Path) -> bool {
+    let resolve = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    resolve(cwd).starts_with(resolve(project))
+}
+
 pub fn run_programmatic(
     agent_cmd: &str,
     config: &IterRunnerConfig,
@@ -625,9 +915,8 @@ pub fn run_programmatic(
         r#"{"autoMemoryEnabled": false, "sandbox": {"allowUnsandboxedCommands": false}}"#,
     ]);
     cmd.args(&asp_args);
-    for (key, val) in &config.env_vars {
-        cmd.env(key, val);
-    }
+    cmd.args(config.tuning.agent_args());
+    set_agent_env(&mut cmd, config, iteration);
 
     let resuming = iteration == 1 && config.resume.is_some();
     if resuming {
@@ -711,13 +1000,35 @@ fn log_resource_usage(iteration: u32) {
     );
 }
 
+fn completion_command_passes(command: &str, root: &Path, tee: &TeeWriter) -> bool {
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .output();
+    let outcome = match output {
+        Ok(out) if out.status.success() => return true,
+        Ok(out) => match out.status.code() {
+            Some(code) => format!("exited {code}"),
+            None => "was killed by a signal".to_string(),
+        },
+        Err(e) => format!("could not run: {e}"),
+    };
+    tee.writeln(&style::dim(&format!(
+        "completion check `{command}` {outcome}; not complete yet"
+    )));
+    false
+}
+
 fn auto_push_if_changed(config: &IterRunnerConfig, head_before: &Option<String>, tee: &TeeWriter) {
     if !config.auto_push {
         return;
     }
 
     if let Some(before) = head_before {
-        vcs_utils::auto_push_if_changed(before, |msg| tee.writeln(&style::dim(msg)));
+        vcs_utils::auto_push_to(before, &config.push_target, |msg| {
+            tee.writeln(&style::dim(msg))
+        });
     }
 }
 
@@ -755,6 +1066,10 @@ fn print_startup_banner(
         Some(name) => format!("{} Loop Starting", name),
         None => "Iteration Loop Starting".to_string(),
     };
+    // The art goes to the terminal only; the log gets the details.
+    if let Some(art) = banner::art() {
+        write_terminal(&format!("{art}\n\n"));
+    }
     for line in banner::render_box(&title, &body).split('\n') {
         tee.writeln(line);
     }
@@ -767,7 +1082,7 @@ fn print_startup_banner(
 /// - TeeWriter (dual stdout + log file output)
 /// - Stdout reader thread (AFK mode NDJSON parsing)
 fn is_retryable_process_failure(status: &AgentExitStatus, elapsed: Duration) -> bool {
-    if status.killed_by_timeout {
+    if status.killed_by_timeout || status.stalled_for.is_some() || status.wrong_cwd.is_some() {
         return false;
     }
     if status.killed_by_inactivity {
@@ -789,26 +1104,29 @@ fn is_retryable_process_failure(status: &AgentExitStatus, elapsed: Duration) ->
 
 fn estimate_max_attempts(immediate: u32, interval_secs: u64, max_duration_secs: u64) -> u32 {
     immediate
-        + if interval_secs > 0 {
-            (max_duration_secs / interval_secs) as u32
-        } else {
-            0
-        }
+        + max_duration_secs
+            .checked_div(interval_secs)
+            .map_or(0, |n| n as u32)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_agent_with_retry(
     agent_cmd: &str,
     config: &mut IterRunnerConfig,
     is_file: bool,
     controller: &ShutdownController,
+    hotkeys: &Hotkeys,
     tee: &Arc<TeeWriter>,
     iteration: u32,
     session_id: &str,
+    spent_usd: &mut f64,
+    stalled_for: &mut Option<Duration>,
+    wrong_cwd: &mut Option<PathBuf>,
 ) -> bool {
     let start = std::time::Instant::now();
     let status = if config.afk {
         run_afk(
-            agent_cmd, config, is_file, controller, tee, iteration, session_id,
+            agent_cmd, config, is_file, controller, hotkeys, tee, iteration, session_id,
         )
     } else {
         run_interactive(
@@ -816,6 +1134,9 @@ fn run_agent_with_retry(
         )
     };
     let elapsed = start.elapsed();
+    *spent_usd += status.cost_usd;
+    *stalled_for = status.stalled_for;
+    *wrong_cwd = status.wrong_cwd.clone();
 
     if !is_retryable_process_failure(&status, elapsed) {
         return status.ctrl_c_forwarded;
@@ -889,11 +1210,16 @@ fn run_agent_with_retry(
 
         let start = std::time::Instant::now();
         let retry_status = if config.afk {
-            run_afk(agent_cmd, config, is_file, controller, tee, 1, session_id)
+            run_afk(
+                agent_cmd, config, is_file, controller, hotkeys, tee, 1, session_id,
+            )
         } else {
             run_interactive(agent_cmd, config, is_file, controller, 1, session_id)
         };
         let retry_elapsed = start.elapsed();
+        *spent_usd += retry_status.cost_usd;
+        *stalled_for = retry_status.stalled_for;
+        *wrong_cwd = retry_status.wrong_cwd.clone();
 
         if !is_retryable_process_failure(&retry_status, retry_elapsed) {
             return retry_status.ctrl_c_forwarded;
@@ -959,7 +1285,19 @@ pub fn run_iteration_loop(
     remove_sentinel_from(root);
     let _ = fs::remove_file(root.join(DING_SENTINEL));
 
+    let scratch_config = SgfConfig::load(root).unwrap_or_default().scratch;
+    if let Some(cutoff) = scratch_config
+        .max_age()
+        .and_then(|age| age.to_std().ok())
+        .and_then(|age| std::time::SystemTime::now().checked_sub(age))
+        && let Err(e) = scratch::prune_stale(root, config.loop_id.as_deref(), cutoff)
+    {
+        warn!(error = %e, "failed to prune old scratch directories");
+    }
+
     let saved_termios = save_terminal_settings();
+    let mut spent_usd = 0.0;
+    let hotkeys = Hotkeys::new(controller, iterations);
 
     for i in 1..=iterations {
         remove_sentinel_from(root);
@@ -989,15 +1327,26 @@ pub fn run_iteration_loop(
         }
 
         let head_before = vcs_utils::git_head();
+        hotkeys.begin_iteration(i, spent_usd);
+        if let Err(e) = scratch::prepare(root, config.loop_id.as_deref(), i, scratch_config.keep())
+        {
+            warn!(error = %e, "failed to prepare the scratch directory");
+        }
 
+        let mut stalled_for = None;
+        let mut wrong_cwd = None;
         let ctrl_c_forwarded = run_agent_with_retry(
             &agent_cmd,
             &mut config,
             is_file,
             controller,
+            &hotkeys,
             &tee,
             i,
             &iter_session_id,
+            &mut spent_usd,
+            &mut stalled_for,
+            &mut wrong_cwd,
         );
 
         if let Some(ref termios) = saved_termios {
@@ -1007,6 +1356,14 @@ pub fn run_iteration_loop(
         if let Some(ref mut cb) = config.on_iteration_complete {
             cb(i, &iter_session_id);
         }
+        crate::event_stream::emit(&crate::event_stream::LoopEvent::IterationProgress {
+            loop_id: config.loop_id.clone(),
+            iteration: i,
+            iterations,
+            session_id: iter_session_id.clone(),
+            cost_usd: spent_usd,
+        });
+        run_post_iteration_hook(root, config.loop_id.as_deref(), i, iterations);
 
         if controller.poll() == ShutdownStatus::Shutdown {
             warn!("interrupted");
@@ -1014,8 +1371,28 @@ pub fn run_iteration_loop(
             return IterExitCode::Interrupted;
         }
 
-        if let Some(sentinel_path) = find_sentinel(root, SENTINEL_MAX_DEPTH) {
+        if wrong_cwd.is_some() {
+            let title = "Stopped: the agent is not working on this project";
+            tee.writeln("");
+            for line in
+                banner::render_box_styled(title, &[], |s| style::bold(&style::red(s))).split('\n')
+            {
+                tee.writeln(line);
+            }
+            return IterExitCode::Error;
+        }
+
+        let sentinel = find_sentinel(root, SENTINEL_MAX_DEPTH);
+        if let Some(ref sentinel_path) = sentinel {
             let _ = fs::remove_file(sentinel_path);
+        }
+        let complete = sentinel.is_some()
+            || config
+                .tuning
+                .complete_when
+                .as_deref()
+                .is_some_and(|command| completion_command_passes(command, root, &tee));
+        if complete {
             let complete_title = match &config.runner_name {
                 Some(name) => format!("{} COMPLETE after {} iterations!", name, i),
                 None => format!("COMPLETE after {} iterations!", i),
@@ -1031,12 +1408,67 @@ pub fn run_iteration_loop(
             return IterExitCode::Complete;
         }
 
+        if let Some(idle) = stalled_for {
+            let restart = SgfConfig::load(root).unwrap_or_default().watchdog.restart();
+            let remaining = iterations - i;
+            tee.write_ansi_line(&style::yellow(&format!(
+                "watchdog: no log output for {}s, killed iteration {i}; {}",
+                idle.as_secs(),
+                if restart && remaining > 0 {
+                    format!("continuing with {remaining} iterations left")
+                } else {
+                    "stopping".to_string()
+                }
+            )));
+            if let Some(ref loop_id) = config.loop_id {
+                let incident = crate::loop_mgmt::Incident {
+                    at: chrono::Utc::now().to_rfc3339(),
+                    iteration: i,
+                    stalled_secs: idle.as_secs(),
+                    restarted: restart && remaining > 0,
+                };
+                if let Err(e) = crate::loop_mgmt::record_incident(root, loop_id, incident) {
+                    warn!(error = %e, "failed to record watchdog incident");
+                }
+            }
+            if !restart {
+                auto_push_if_changed(&config, &head_before, &tee);
+                return IterExitCode::Error;
+            }
+        }
+
         if ctrl_c_forwarded {
             warn!("interrupted (ctrl-c forwarded to agent)");
             auto_push_if_changed(&config, &head_before, &tee);
             return IterExitCode::Interrupted;
         }
 
+        if let Some(max) = config.tuning.max_cost_usd
+            && spent_usd >= max
+        {
+            let title = format!("Cost limit reached (${spent_usd:.2} of ${max:.2})");
+            tee.writeln("");
+            for line in banner::render_box_styled(&title, &[], |s| style::bold(&style::yellow(s)))
+                .split('\n')
+            {
+                tee.writeln(line);
+            }
+            auto_push_if_changed(&config, &head_before, &tee);
+            return IterExitCode::Exhausted;
+        }
+
+        if hotkeys.stop_requested() {
+            let title = format!("Stopped after iteration {i} as requested");
+            tee.writeln("");
+            for line in banner::render_box_styled(&title, &[], |s| style::bold(&style::yellow(s)))
+                .split('\n')
+            {
+                tee.writeln(line);
+            }
+            auto_push_if_changed(&config, &head_before, &tee);
+            return IterExitCode::Interrupted;
+        }
+
         log_resource_usage(i);
 
         tee.writeln("");
@@ -1160,6 +1592,28 @@ mod tests {
         tee.write_ansi_line("world");
     }
 
+    #[test]
+    fn tee_writer_keeps_the_last_lines_plain() {
+        let tee = TeeWriter::new(None).unwrap();
+        tee.write_ansi_line(&style::bold("first"));
+        tee.writeln("");
+        tee.writeln(r#"{"event":"heartbeat"}"#);
+        for i in 0..RECENT_LINES {
+            tee.writeln(&format!("line {i}"));
+        }
+        let recent = tee.recent_lines();
+        assert_eq!(recent.len(), RECENT_LINES);
+        assert_eq!(recent[0], "line 0");
+        assert_eq!(
+            recent[RECENT_LINES - 1],
+            format!("line {}", RECENT_LINES - 1)
+        );
+
+        let tee = TeeWriter::new(None).unwrap();
+        tee.write_ansi_line(&style::bold("first"));
+        assert_eq!(tee.recent_lines(), ["first"]);
+    }
+
     #[test]
     fn tee_writer_with_log() {
         let dir = tempfile::tempdir().unwrap();
@@ -1167,9 +1621,11 @@ mod tests {
         let tee = TeeWriter::new(Some(&log_path)).unwrap();
         tee.writeln("hello");
         tee.write_ansi_line(&style::bold("styled"));
+        tee.log_line("log only");
         let content = fs::read_to_string(&log_path).unwrap();
         assert!(content.contains("hello"));
         assert!(content.contains("styled"));
+        assert!(content.contains("log only"));
         assert!(!content.contains("\x1b["));
     }
 
@@ -1235,6 +1691,7 @@ mod tests {
             iterations: 1,
             prompt: "test".to_string(),
             auto_push: false,
+            push_target: Default::default(),
             command: Some(command),
             prompt_files: vec![],
             log_file: None,
@@ -1252,9 +1709,29 @@ mod tests {
             retry_interval_secs: 300,
             retry_max_duration_secs: 43200,
             on_retry: None,
+            tuning: AgentTuning::default(),
         }
     }
 
+    #[test]
+    fn pause_longer_than_stall_after_does_not_count_as_silence() {
+        let stall_after = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut clocks = AfkClocks::new(start);
+        clocks.result_received_at = Some(start);
+
+        clocks.shift(Duration::from_secs(300));
+        let resumed = start + Duration::from_secs(310);
+        assert_eq!(clocks.idle(resumed), Duration::from_secs(10));
+        assert!(clocks.idle(resumed) < stall_after);
+        assert_eq!(clocks.started_at, start + Duration::from_secs(300));
+        assert_eq!(
+            clocks.result_received_at,
+            Some(start + Duration::from_secs(300))
+        );
+        assert!(clocks.idle(resumed + stall_after) > stall_after);
+    }
+
     #[test]
     fn post_result_timeout_kills_hung_process() {
         let dir = tempfile::tempdir().unwrap();
@@ -1291,6 +1768,118 @@ mod tests {
         assert!(matches!(exit_code, IterExitCode::Exhausted));
     }
 
+    #[test]
+    fn agent_tuning_passes_args_and_enforces_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let args_file = dir.path().join("args.txt");
+        let result_json = r#"{"type":"result","result":"Done.","session_id":"s1","total_cost_usd":0.75,"usage":{"input_tokens":100,"output_tokens":200}}"#;
+        let script = mock_script(
+            dir.path(),
+            "costly.sh",
+            &format!(
+                "#!/bin/sh\necho \"$@\" >> '{}'\necho '{}'\nexit 0\n",
+                args_file.display(),
+                result_json
+            ),
+        );
+        let controller = ShutdownController::new(ShutdownConfig {
+            monitor_stdin: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut config = make_config(dir.path(), script);
+        config.iterations = 5;
+        config.tuning = AgentTuning {
+            model: Some("opus".to_string()),
+            extra_args: vec!["--fallback-model".to_string(), "sonnet".to_string()],
+            iteration_timeout: None,
+            max_cost_usd: Some(1.0),
+            complete_when: None,
+            env: Vec::new(),
+        };
+        let exit_code = run_iteration_loop(config, &controller);
+        assert!(matches!(exit_code, IterExitCode::Exhausted));
+        let args = fs::read_to_string(&args_file).unwrap();
+        assert_eq!(args.lines().count(), 2, "stops once $1.50 >= $1.00: {args}");
+        assert!(
+            args.contains("--model opus --fallback-model sonnet"),
+            "{args}"
+        );
+
+        let script = mock_script(dir.path(), "slow.sh", "#!/bin/sh\necho '{}'\nsleep 300\n");
+        let mut config = make_config(dir.path(), script);
+        config.tuning.iteration_timeout = Some(Duration::from_secs(1));
+        let start = std::time::Instant::now();
+        let exit_code = run_iteration_loop(config, &controller);
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "{:?}",
+            start.elapsed()
+        );
+        assert!(matches!(exit_code, IterExitCode::Exhausted));
+    }
+
+    #[test]
+    fn complete_when_command_ends_the_loop_once_it_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let runs = dir.path().join("runs");
+        let script = mock_script(
+            dir.path(),
+            "agent.sh",
+            &format!("#!/bin/sh\necho run >> {}\necho '{{}}'\n", runs.display()),
+        );
+        let controller = ShutdownController::new(ShutdownConfig {
+            monitor_stdin: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut config = make_config(dir.path(), script);
+        config.iterations = 5;
+        config.tuning.complete_when = Some("[ $(wc -l < runs) -ge 2 ]".to_string());
+        let exit_code = run_iteration_loop(config, &controller);
+        assert!(matches!(exit_code, IterExitCode::Complete));
+        assert_eq!(fs::read_to_string(&runs).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn agent_outside_the_project_stops_the_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let runs = dir.path().join("runs");
+        let agent = |cwd: &Path| {
+            format!(
+                "#!/bin/sh\necho run >> {}\necho '{{\"type\":\"system\",\"subtype\":\"init\",\"cwd\":\"{}\"}}'\necho '{{}}'\n",
+                runs.display(),
+                cwd.display()
+            )
+        };
+        let controller = ShutdownController::new(ShutdownConfig {
+            monitor_stdin: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let script = mock_script(dir.path(), "inside.sh", &agent(&dir.path().join("src")));
+        let mut config = make_config(dir.path(), script);
+        config.iterations = 2;
+        let exit_code = run_iteration_loop(config, &controller);
+        assert!(matches!(exit_code, IterExitCode::Exhausted));
+        assert_eq!(fs::read_to_string(&runs).unwrap().lines().count(), 2);
+
+        fs::remove_file(&runs).unwrap();
+        let script = mock_script(dir.path(), "outside.sh", &agent(Path::new("/elsewhere")));
+        let mut config = make_config(dir.path(), script);
+        config.iterations = 2;
+        let exit_code = run_iteration_loop(config, &controller);
+        assert!(matches!(exit_code, IterExitCode::Error));
+        assert_eq!(
+            fs::read_to_string(&runs).unwrap().lines().count(),
+            1,
+            "no retries or further iterations"
+        );
+    }
+
     #[test]
     fn clean_exit_not_affected_by_post_result_timeout() {
         let dir = tempfile::tempdir().unwrap();
@@ -1704,12 +2293,68 @@ fi
         assert_eq!(result.content, "hello_world");
     }
 
+    #[test]
+    fn sandbox_env_narrows_what_the_agent_inherits() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = mock_script(
+            dir.path(),
+            "prog_sandbox_env.sh",
+            "#!/bin/sh\necho \"{\\\"result\\\":\\\"$HOME|$CARGO_MANIFEST_DIR|$MY_VAR|$FLAG|$SGF_SCRATCH\\\",\\\"session_id\\\":\\\"s1\\\"}\"\n",
+        );
+        fs::create_dir_all(dir.path().join(".sgf")).unwrap();
+        fs::write(dir.path().join(".sgf/config.toml"), "[sandbox]\nenv = []\n").unwrap();
+
+        let mut config = make_config(dir.path(), script.clone());
+        config.env_vars = vec![("MY_VAR".to_string(), "hello".to_string())];
+        config.tuning.env = vec![("FLAG".to_string(), "on".to_string())];
+        config.loop_id = Some("build-1".to_string());
+        let controller = ShutdownController::new(ShutdownConfig {
+            monitor_stdin: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let result = run_programmatic(&script, &config, false, &controller, 2, "test-sid").unwrap();
+        let home = std::env::var("HOME").unwrap_or_default();
+        let scratch = dir.path().join(".sgf/scratch/build-1/2");
+        assert_eq!(
+            result.content,
+            format!("{home}||hello|on|{}", scratch.display())
+        );
+    }
+
+    #[test]
+    fn broken_config_passes_only_the_base_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = mock_script(
+            dir.path(),
+            "prog_broken_config_env.sh",
+            "#!/bin/sh\necho \"{\\\"result\\\":\\\"$HOME|$CARGO_MANIFEST_DIR\\\",\\\"session_id\\\":\\\"s1\\\"}\"\n",
+        );
+        fs::create_dir_all(dir.path().join(".sgf")).unwrap();
+        fs::write(dir.path().join(".sgf/config.toml"), "[sandbox\n").unwrap();
+
+        let config = make_config(dir.path(), script.clone());
+        let controller = ShutdownController::new(ShutdownConfig {
+            monitor_stdin: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let result = run_programmatic(&script, &config, false, &controller, 1, "test-sid").unwrap();
+        let home = std::env::var("HOME").unwrap_or_default();
+        assert_eq!(result.content, format!("{home}|"));
+    }
+
     fn exit(code: Option<i32>) -> AgentExitStatus {
         AgentExitStatus {
             exit_code: code,
             killed_by_timeout: false,
             killed_by_inactivity: false,
+            stalled_for: None,
             ctrl_c_forwarded: false,
+            wrong_cwd: None,
+            cost_usd: 0.0,
         }
     }
 
@@ -1807,7 +2452,10 @@ fi
             exit_code: None,
             killed_by_timeout: true,
             killed_by_inactivity: false,
+            stalled_for: None,
             ctrl_c_forwarded: false,
+            wrong_cwd: None,
+            cost_usd: 0.0,
         };
         assert!(!is_retryable_process_failure(
             &status,
@@ -1820,8 +2468,11 @@ fi
         let status = AgentExitStatus {
             exit_code: None,
             killed_by_inactivity: true,
+            stalled_for: None,
             killed_by_timeout: false,
             ctrl_c_forwarded: false,
+            wrong_cwd: None,
+            cost_usd: 0.0,
         };
         assert!(is_retryable_process_failure(
             &status,
@@ -1829,6 +2480,23 @@ fi
         ));
     }
 
+    #[test]
+    fn watchdog_kill_is_not_retried() {
+        let status = AgentExitStatus {
+            exit_code: None,
+            killed_by_inactivity: false,
+            stalled_for: Some(Duration::from_secs(1200)),
+            killed_by_timeout: false,
+            ctrl_c_forwarded: false,
+            wrong_cwd: None,
+            cost_usd: 0.0,
+        };
+        assert!(!is_retryable_process_failure(
+            &status,
+            Duration::from_secs(10)
+        ));
+    }
+
     #[test]
     fn estimate_max_attempts_default_config() {
         // 3 immediate + 43200/300 = 3 + 144 = 147
@@ -13,7 +13,7 @@ use tracing::warn;
 
 use super::AgentExitStatus;
 
-fn open_pty() -> io::Result<(OwnedFd, OwnedFd)> {
+pub(crate) fn open_pty() -> io::Result<(OwnedFd, OwnedFd)> {
     unsafe {
         let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
         if master < 0 {
@@ -434,7 +434,10 @@ pub(crate) fn run_interactive_with_pty(
         exit_code,
         killed_by_timeout: false,
         killed_by_inactivity: false,
+        stalled_for: None,
         ctrl_c_forwarded,
+        wrong_cwd: None,
+        cost_usd: 0.0,
     })
 }
 
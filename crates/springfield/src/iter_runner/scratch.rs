@@ -0,0 +1,110 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const SCRATCH_DIR: &str = ".sgf/scratch";
+pub const SCRATCH_ENV: &str = "SGF_SCRATCH";
+
+const NO_LOOP: &str = "session";
+
+pub fn iteration_dir(loop_id: Option<&str>, iteration: u32) -> PathBuf {
+    Path::new(SCRATCH_DIR)
+        .join(loop_id.unwrap_or(NO_LOOP))
+        .join(iteration.to_string())
+}
+
+/// Empties or creates the iteration's directory, then removes the loop's
+/// iteration directories that fall outside the newest `keep`.
+pub fn prepare(
+    root: &Path,
+    loop_id: Option<&str>,
+    iteration: u32,
+    keep: usize,
+) -> io::Result<PathBuf> {
+    let dir = root.join(iteration_dir(loop_id, iteration));
+    match fs::remove_dir_all(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    fs::create_dir_all(&dir)?;
+    let oldest_kept = iteration.saturating_sub(keep.max(1) as u32 - 1);
+    for entry in fs::read_dir(dir.parent().unwrap())? {
+        let entry = entry?;
+        let older = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+            .is_some_and(|n| n < oldest_kept);
+        if older {
+            fs::remove_dir_all(entry.path())?;
+        }
+    }
+    Ok(dir)
+}
+
+pub fn prune_stale(root: &Path, current: Option<&str>, cutoff: SystemTime) -> io::Result<usize> {
+    let entries = match fs::read_dir(root.join(SCRATCH_DIR)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let current = current.unwrap_or(NO_LOOP);
+    let mut removed = 0;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_name() == current || !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if entry.metadata()?.modified()? < cutoff {
+            fs::remove_dir_all(entry.path())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn prepare_empties_the_iteration_and_keeps_the_newest() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for i in 1..=3 {
+            let dir = prepare(root, Some("build-1"), i, 2).unwrap();
+            fs::write(dir.join("out.txt"), "x").unwrap();
+        }
+        let loop_dir = root.join(".sgf/scratch/build-1");
+        assert!(!loop_dir.join("1").exists());
+        assert!(loop_dir.join("2/out.txt").exists());
+
+        let dir = prepare(root, Some("build-1"), 3, 2).unwrap();
+        assert_eq!(dir, loop_dir.join("3"));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0, "emptied");
+        assert!(loop_dir.join("2").exists());
+
+        prepare(root, None, 1, 0).unwrap();
+        assert!(root.join(".sgf/scratch/session/1").is_dir());
+    }
+
+    #[test]
+    fn prune_stale_spares_the_current_loop() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        prepare(root, Some("old"), 1, 3).unwrap();
+        prepare(root, Some("current"), 1, 3).unwrap();
+
+        let future = SystemTime::now() + Duration::from_secs(60);
+        assert_eq!(prune_stale(root, Some("current"), future).unwrap(), 1);
+        assert!(!root.join(".sgf/scratch/old").exists());
+        assert!(root.join(".sgf/scratch/current").exists());
+        assert_eq!(
+            prune_stale(root, Some("x"), SystemTime::UNIX_EPOCH).unwrap(),
+            0
+        );
+    }
+}
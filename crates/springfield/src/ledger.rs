@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::loop_mgmt::LoopRecord;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub loop_id: String,
+    pub stage: String,
+    pub spec: Option<String>,
+    pub attempt: u32,
+    pub ended_at: String,
+    pub outcome: String,
+    pub iterations: u32,
+    pub duration_secs: i64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    #[default]
+    Stage,
+    Spec,
+}
+
+impl std::str::FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stage" => Ok(Self::Stage),
+            "spec" => Ok(Self::Spec),
+            _ => Err(format!("unknown grouping: {s} (expected stage or spec)")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CostSummary {
+    pub key: String,
+    pub loops: usize,
+    pub iterations: u32,
+    pub duration_secs: i64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+pub fn ledger_path(root: &Path) -> PathBuf {
+    root.join(".sgf/ledger.jsonl")
+}
+
+impl LedgerEntry {
+    pub fn for_record(root: &Path, record: &LoopRecord) -> Option<Self> {
+        let ended_at = record.ended_at.clone()?;
+        let started_at = record
+            .attempt_started_at
+            .as_deref()
+            .unwrap_or(&record.started_at);
+        let duration_secs = match (
+            DateTime::parse_from_rfc3339(started_at),
+            DateTime::parse_from_rfc3339(&ended_at),
+        ) {
+            (Ok(start), Ok(end)) => (end - start).num_seconds().max(0),
+            _ => 0,
+        };
+        let log = crate::report::read_log(root, &record.loop_id);
+        Some(Self {
+            loop_id: record.loop_id.clone(),
+            stage: record.stage.clone(),
+            spec: record.spec.clone(),
+            attempt: record.attempt,
+            outcome: record.outcome().to_string(),
+            ended_at,
+            iterations: log.iterations,
+            duration_secs,
+            input_tokens: log.input_tokens,
+            output_tokens: log.output_tokens,
+            cost_usd: log.cost_usd,
+        })
+    }
+}
+
+pub fn append(root: &Path, entry: &LedgerEntry) -> io::Result<()> {
+    let path = ledger_path(root);
+    fs::create_dir_all(path.parent().unwrap())?;
+    let line =
+        serde_json::to_string(entry).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+pub fn read(root: &Path) -> Vec<LedgerEntry> {
+    fs::read_to_string(ledger_path(root))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+pub fn summarize(
+    entries: &[LedgerEntry],
+    since: Option<TimeDelta>,
+    by: GroupBy,
+) -> Vec<CostSummary> {
+    let cutoff = since.map(|age| Utc::now() - age);
+    let mut groups: BTreeMap<String, CostSummary> = BTreeMap::new();
+    for entry in entries {
+        if let Some(cutoff) = cutoff
+            && DateTime::parse_from_rfc3339(&entry.ended_at).is_ok_and(|t| t < cutoff)
+        {
+            continue;
+        }
+        let key = match by {
+            GroupBy::Stage => entry.stage.clone(),
+            GroupBy::Spec => entry.spec.clone().unwrap_or_else(|| "-".to_string()),
+        };
+        let group = groups.entry(key.clone()).or_insert_with(|| CostSummary {
+            key,
+            ..Default::default()
+        });
+        group.loops += 1;
+        group.iterations += entry.iterations;
+        group.duration_secs += entry.duration_secs;
+        group.input_tokens += entry.input_tokens;
+        group.output_tokens += entry.output_tokens;
+        group.cost_usd += entry.cost_usd.unwrap_or_default();
+    }
+    let mut summaries: Vec<CostSummary> = groups.into_values().collect();
+    summaries.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(stage: &str, spec: Option<&str>, ended_at: &str, cost: Option<f64>) -> LedgerEntry {
+        LedgerEntry {
+            loop_id: format!("{stage}-1"),
+            stage: stage.to_string(),
+            spec: spec.map(str::to_string),
+            attempt: 1,
+            ended_at: ended_at.to_string(),
+            outcome: "completed".to_string(),
+            iterations: 2,
+            duration_secs: 600,
+            input_tokens: 1000,
+            output_tokens: 100,
+            cost_usd: cost,
+        }
+    }
+
+    #[test]
+    fn entries_round_trip_through_the_ledger() {
+        let tmp = TempDir::new().unwrap();
+        let first = entry("build", Some("auth"), "2026-10-16T10:00:00Z", Some(1.5));
+        let second = entry("spec", None, "2026-10-16T11:00:00Z", None);
+        append(tmp.path(), &first).unwrap();
+        append(tmp.path(), &second).unwrap();
+        fs::write(
+            ledger_path(tmp.path()),
+            fs::read_to_string(ledger_path(tmp.path())).unwrap() + "not json\n",
+        )
+        .unwrap();
+        assert_eq!(read(tmp.path()), [first, second]);
+    }
+
+    #[test]
+    fn summarize_groups_filters_and_sorts_by_cost() {
+        let now = Utc::now();
+        let recent = (now - TimeDelta::hours(1)).to_rfc3339();
+        let old = (now - TimeDelta::days(30)).to_rfc3339();
+        let entries = [
+            entry("build", Some("auth"), &recent, Some(1.0)),
+            entry("build", Some("billing"), &recent, Some(2.5)),
+            entry("verify", Some("auth"), &recent, Some(0.5)),
+            entry("spec", None, &recent, None),
+            entry("build", Some("auth"), &old, Some(40.0)),
+        ];
+
+        let by_stage = summarize(&entries, Some(TimeDelta::days(7)), GroupBy::Stage);
+        let keys: Vec<(&str, usize, f64)> = by_stage
+            .iter()
+            .map(|s| (s.key.as_str(), s.loops, s.cost_usd))
+            .collect();
+        assert_eq!(
+            keys,
+            [("build", 2, 3.5), ("verify", 1, 0.5), ("spec", 1, 0.0)]
+        );
+        assert_eq!(by_stage[0].duration_secs, 1200);
+        assert_eq!(by_stage[0].iterations, 4);
+
+        let by_spec = summarize(&entries, None, GroupBy::Spec);
+        let keys: Vec<(&str, f64)> = by_spec
+            .iter()
+            .map(|s| (s.key.as_str(), s.cost_usd))
+            .collect();
+        assert_eq!(keys, [("auth", 41.5), ("billing", 2.5), ("-", 0.0)]);
+    }
+}
@@ -1,8 +1,41 @@
+#[cfg(not(unix))]
+compile_error!(
+    "sgf needs a Unix platform (a PTY, process groups, and signals); on Windows, build and run it under WSL"
+);
+
+pub mod backpressure;
+pub mod branching;
+pub mod clean;
+pub mod config;
 pub mod cursus;
+pub mod doctor;
+pub mod event_stream;
+pub mod gates;
+pub mod guard;
+pub mod history;
+pub mod hooks;
 pub mod init;
+pub mod issues;
 pub mod iter_runner;
+pub mod ledger;
+pub mod logs;
 pub mod loop_mgmt;
+pub mod next;
+pub mod notify;
 pub mod orchestrate;
+pub mod parallel;
+pub mod platform;
+pub mod profile;
+pub mod project;
 pub mod prompt;
+pub mod push;
 pub mod recovery;
+pub mod remote_prompt;
+pub mod report;
+pub mod rollback;
 pub mod style;
+pub mod task_plan;
+pub mod test_issues;
+pub mod upgrade;
+pub mod verify_findings;
+pub mod watch;
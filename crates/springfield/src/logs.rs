@@ -0,0 +1,311 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use regex::Regex;
+
+const STAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+const STAMP_LEN: usize = "2026-01-01T00:00:00Z".len();
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub fn stamp(line: &str) -> String {
+    if line.is_empty() {
+        return String::new();
+    }
+    format!("{} {line}", Utc::now().format(STAMP_FORMAT))
+}
+
+pub fn split_stamp(line: &str) -> (Option<DateTime<Utc>>, &str) {
+    let parsed = line
+        .get(..STAMP_LEN)
+        .filter(|_| line[STAMP_LEN..].starts_with(' '))
+        .and_then(|s| NaiveDateTime::parse_from_str(s, STAMP_FORMAT).ok());
+    match parsed {
+        Some(t) => (Some(t.and_utc()), &line[STAMP_LEN + 1..]),
+        None => (None, line),
+    }
+}
+
+pub fn parse_since(input: &str) -> Result<TimeDelta, String> {
+    let invalid = || format!("invalid duration: {input:?} (expected e.g. 10m, 2h, 1d)");
+    let mut total: i64 = 0;
+    let mut digits = String::new();
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let n: i64 = digits.parse().map_err(|_| invalid())?;
+        digits.clear();
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return Err(invalid()),
+        };
+        total = n
+            .checked_mul(unit)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(invalid)?;
+    }
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    TimeDelta::try_seconds(total).ok_or_else(invalid)
+}
+
+/// Which lines `sgf logs` prints. Unstamped lines take the time of the last
+/// stamped line above them, and pass `since` if there is none.
+#[derive(Debug, Default)]
+pub struct LogFilter {
+    pub pattern: Option<Regex>,
+    pub since: Option<DateTime<Utc>>,
+    pub errors_only: bool,
+    last_stamp: Option<DateTime<Utc>>,
+}
+
+impl LogFilter {
+    pub fn new(pattern: Option<Regex>, since: Option<TimeDelta>, errors_only: bool) -> Self {
+        Self {
+            pattern,
+            since: since.map(|d| Utc::now() - d),
+            errors_only,
+            last_stamp: None,
+        }
+    }
+
+    pub fn accepts(&mut self, line: &str) -> bool {
+        let (stamp, text) = split_stamp(line);
+        if stamp.is_some() {
+            self.last_stamp = stamp;
+        }
+        if let (Some(since), Some(at)) = (self.since, self.last_stamp)
+            && at < since
+        {
+            return false;
+        }
+        if self.errors_only && !is_error_line(text) {
+            return false;
+        }
+        self.pattern.as_ref().is_none_or(|p| p.is_match(text))
+    }
+}
+
+fn is_error_line(text: &str) -> bool {
+    static ERROR_WORDS: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+        Regex::new(r"(?i)\b(error|errors|failed|failure|panicked|fatal)\b").unwrap()
+    });
+    ERROR_WORDS.is_match(text)
+}
+
+pub fn tail(root: &Path, loop_id: &str, n: usize) -> Vec<String> {
+    const TAIL_BYTES: u64 = 16 * 1024;
+    let path = crate::loop_mgmt::log_path(root, loop_id);
+    let Ok(mut file) = fs::File::open(path) else {
+        return Vec::new();
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut buf = Vec::new();
+    if file
+        .seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+        .and_then(|_| io::Read::read_to_end(&mut file, &mut buf))
+        .is_err()
+    {
+        return Vec::new();
+    }
+    let text = crate::style::strip_ansi(&String::from_utf8_lossy(&buf));
+    let mut lines: Vec<String> = text
+        .lines()
+        .rev()
+        .map(|l| split_stamp(l).1.trim_end())
+        .filter(|l| !l.trim().is_empty())
+        .take(n)
+        .map(str::to_string)
+        .collect();
+    lines.reverse();
+    lines
+}
+
+pub fn run_logs(
+    root: &Path,
+    loop_id: &str,
+    filter: &mut LogFilter,
+    follow: bool,
+) -> io::Result<()> {
+    let log_path = crate::loop_mgmt::log_path(root, loop_id);
+    if !log_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("log file not found: {}", log_path.display()),
+        ));
+    }
+    let mut out = io::stdout().lock();
+    if follow {
+        follow_log(&log_path, filter, &mut out)
+    } else {
+        let mut reader = BufReader::new(fs::File::open(&log_path)?);
+        let mut partial = String::new();
+        copy_lines(&mut reader, filter, &mut partial, &mut out)?;
+        if !partial.is_empty() && filter.accepts(&partial) {
+            writeln!(out, "{partial}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes every complete line from `reader` that passes `filter`. A trailing
+/// line without a newline is kept in `partial` until the rest arrives.
+fn copy_lines(
+    reader: &mut impl BufRead,
+    filter: &mut LogFilter,
+    partial: &mut String,
+    out: &mut impl io::Write,
+) -> io::Result<()> {
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(());
+        }
+        partial.push_str(&String::from_utf8_lossy(&buf));
+        if !partial.ends_with('\n') {
+            continue;
+        }
+        let line = partial.trim_end_matches(['\n', '\r']);
+        if filter.accepts(line) {
+            writeln!(out, "{line}")?;
+        }
+        partial.clear();
+    }
+}
+
+/// `tail -F`: follows the file by name, starting over when it is replaced or
+/// truncated (as when a resumed loop recreates its log).
+fn follow_log(path: &Path, filter: &mut LogFilter, out: &mut impl io::Write) -> io::Result<()> {
+    let mut file = fs::File::open(path)?;
+    let mut inode = file.metadata()?.ino();
+    let mut partial = String::new();
+    loop {
+        let mut reader = BufReader::new(&file);
+        copy_lines(&mut reader, filter, &mut partial, out)?;
+        out.flush()?;
+        let pos = reader.stream_position()?;
+        drop(reader);
+
+        thread::sleep(POLL_INTERVAL);
+        let Ok(meta) = fs::metadata(path) else {
+            continue;
+        };
+        if meta.ino() != inode || meta.len() < pos {
+            file = fs::File::open(path)?;
+            inode = file.metadata()?.ino();
+            partial.clear();
+        } else {
+            file.seek(SeekFrom::Start(pos))?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn filtered(filter: &mut LogFilter, text: &str) -> String {
+        let mut out = Vec::new();
+        let mut partial = String::new();
+        copy_lines(&mut text.as_bytes(), filter, &mut partial, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn stamp_round_trips_and_skips_blank_lines() {
+        let line = stamp("running tests");
+        let (at, text) = split_stamp(&line);
+        assert_eq!(text, "running tests");
+        assert!(Utc::now() - at.unwrap() < TimeDelta::seconds(5));
+        assert_eq!(stamp(""), "");
+        assert_eq!(split_stamp("plain line"), (None, "plain line"));
+    }
+
+    #[test]
+    fn parse_since_accepts_units_and_rejects_garbage() {
+        assert_eq!(parse_since("10m").unwrap(), TimeDelta::minutes(10));
+        assert_eq!(parse_since("1h30m").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(parse_since("2d").unwrap(), TimeDelta::days(2));
+        for bad in ["", "10", "m", "0s", "5x", "-1m"] {
+            assert!(parse_since(bad).is_err(), "{bad:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn filter_applies_since_grep_and_errors_only() {
+        let old = (Utc::now() - TimeDelta::hours(2)).format(STAMP_FORMAT);
+        let new = Utc::now().format(STAMP_FORMAT);
+        let log = format!(
+            "banner\n{old} old error here\ncontinued\n{new} cargo test\n{new} test failed: auth\ntail\n"
+        );
+
+        let mut since = LogFilter::new(None, Some(TimeDelta::minutes(10)), false);
+        let out = filtered(&mut since, &log);
+        assert!(out.starts_with("banner\n"), "unstamped lead passes: {out}");
+        assert!(!out.contains("old error") && !out.contains("continued"));
+        assert!(out.contains("cargo test") && out.ends_with("tail\n"));
+
+        let mut errors = LogFilter::new(None, None, true);
+        let out = filtered(&mut errors, &log);
+        assert_eq!(out.lines().count(), 2, "{out}");
+
+        let mut grep = LogFilter::new(Some(Regex::new("auth|cargo").unwrap()), None, true);
+        assert_eq!(
+            filtered(&mut grep, &log),
+            format!("{new} test failed: auth\n")
+        );
+    }
+
+    #[test]
+    fn copy_lines_holds_partial_line_until_complete() {
+        let mut filter = LogFilter::default();
+        let mut partial = String::new();
+        let mut out = Vec::new();
+        copy_lines(
+            &mut "one\ntw".as_bytes(),
+            &mut filter,
+            &mut partial,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(partial, "tw");
+        copy_lines(&mut "o\n".as_bytes(), &mut filter, &mut partial, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn tail_strips_stamps_and_skips_blank_lines() {
+        let tmp = TempDir::new().unwrap();
+        let logs = tmp.path().join(".sgf/logs");
+        fs::create_dir_all(&logs).unwrap();
+        fs::write(
+            logs.join("build-1.log"),
+            "one\n2026-10-16T10:00:00Z \x1b[1mtwo\x1b[0m\n\nthree\n",
+        )
+        .unwrap();
+        assert_eq!(tail(tmp.path(), "build-1", 2), ["two", "three"]);
+        assert_eq!(tail(tmp.path(), "build-1", 10).len(), 3);
+        assert!(tail(tmp.path(), "missing", 5).is_empty());
+    }
+
+    #[test]
+    fn run_logs_missing_file() {
+        let tmp = TempDir::new().unwrap();
+        let err =
+            run_logs(tmp.path(), "nonexistent", &mut LogFilter::default(), false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("log file not found"));
+    }
+}
@@ -1,6 +1,8 @@
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use chrono::Local;
 use serde::{Deserialize, Serialize};
@@ -112,6 +114,10 @@ pub fn generate_loop_id(stage: &str, spec: Option<&str>) -> String {
     }
 }
 
+pub fn actor_env_var(loop_id: &str) -> (String, String) {
+    ("PN_ACTOR".to_string(), format!("sgf-{loop_id}"))
+}
+
 pub fn write_pid_file(root: &Path, loop_id: &str) -> io::Result<PathBuf> {
     let pid_path = root.join(".sgf/run").join(format!("{loop_id}.pid"));
     fs::create_dir_all(pid_path.parent().unwrap())?;
@@ -150,7 +156,7 @@ pub fn list_pid_files(root: &Path) -> Vec<(String, u32)> {
 }
 
 pub fn is_pid_alive(pid: u32) -> bool {
-    unsafe { libc::kill(pid as i32, 0) == 0 }
+    crate::platform::pid_alive(pid)
 }
 
 pub fn create_log_file(root: &Path, loop_id: &str) -> io::Result<PathBuf> {
@@ -160,25 +166,720 @@ pub fn create_log_file(root: &Path, loop_id: &str) -> io::Result<PathBuf> {
     Ok(log_path)
 }
 
-pub fn run_logs(root: &Path, loop_id: &str) -> io::Result<()> {
-    let log_path = root.join(".sgf/logs").join(format!("{loop_id}.log"));
-    if !log_path.exists() {
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LoopRecord {
+    pub loop_id: String,
+    pub stage: String,
+    pub spec: Option<String>,
+    pub started_at: String,
+    #[serde(default)]
+    pub attempt_started_at: Option<String>,
+    pub ended_at: Option<String>,
+    pub exit_code: Option<i32>,
+    pub iterations_completed: u32,
+    pub head_before: Option<String>,
+    pub head_after: Option<String>,
+    #[serde(default)]
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    #[serde(default = "first_attempt")]
+    pub attempt: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub incidents: Vec<Incident>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Incident {
+    pub at: String,
+    pub iteration: u32,
+    pub stalled_secs: u64,
+    pub restarted: bool,
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+impl LoopRecord {
+    pub fn outcome(&self) -> &'static str {
+        match (&self.ended_at, self.exit_code) {
+            (None, _) => "running",
+            (Some(_), Some(0)) => "completed",
+            (Some(_), Some(2)) => "exhausted",
+            (Some(_), Some(130)) => "interrupted",
+            (Some(_), _) => "failed",
+        }
+    }
+}
+
+pub fn attempt_log_path(root: &Path, loop_id: &str, attempt: u32) -> PathBuf {
+    root.join(".sgf/logs")
+        .join(format!("{loop_id}.attempt-{attempt}.log"))
+}
+
+pub fn archive_dir(root: &Path) -> PathBuf {
+    root.join(".sgf/archive")
+}
+
+pub(crate) fn archive_months(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(archive_dir(root)) else {
+        return Vec::new();
+    };
+    let mut months: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    months.sort_by(|a, b| b.cmp(a));
+    months
+}
+
+/// `loop_id`'s `<loop_id>.<ext>` file: in `.sgf/logs` while the loop is
+/// live, in the archive once it completed. A file in neither place gets its
+/// `.sgf/logs` path.
+pub fn loop_file(root: &Path, loop_id: &str, ext: &str) -> PathBuf {
+    let name = format!("{loop_id}.{ext}");
+    let live = root.join(".sgf/logs").join(&name);
+    if live.exists() {
+        return live;
+    }
+    archive_months(root)
+        .into_iter()
+        .map(|month| month.join(&name))
+        .find(|p| p.exists())
+        .unwrap_or(live)
+}
+
+pub fn log_path(root: &Path, loop_id: &str) -> PathBuf {
+    loop_file(root, loop_id, "log")
+}
+
+pub fn loop_record_path(root: &Path, loop_id: &str) -> PathBuf {
+    loop_file(root, loop_id, "json")
+}
+
+pub fn write_loop_record(root: &Path, record: &LoopRecord) -> io::Result<()> {
+    let target = loop_record_path(root, &record.loop_id);
+    fs::create_dir_all(target.parent().unwrap())?;
+    let tmp = target.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(record)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, &target)
+}
+
+pub fn read_loop_record(root: &Path, loop_id: &str) -> io::Result<Option<LoopRecord>> {
+    let path = loop_record_path(root, loop_id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&path)?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn record_incident(root: &Path, loop_id: &str, incident: Incident) -> io::Result<()> {
+    let Some(mut record) = read_loop_record(root, loop_id)? else {
+        return Ok(());
+    };
+    record.incidents.push(incident);
+    write_loop_record(root, &record)
+}
+
+pub fn list_loop_records(root: &Path) -> Vec<LoopRecord> {
+    let mut records: Vec<LoopRecord> = std::iter::once(root.join(".sgf/logs"))
+        .chain(archive_months(root))
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .filter_map(|s| serde_json::from_str(&s).ok())
+        .collect();
+    records.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    records.dedup_by(|a, b| a.loop_id == b.loop_id);
+    records
+}
+
+pub fn archive_loop(root: &Path, record: &LoopRecord) -> io::Result<PathBuf> {
+    let ended = record
+        .ended_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map_or_else(chrono::Utc::now, |t| t.with_timezone(&chrono::Utc));
+    let dir = archive_dir(root).join(ended.format("%Y-%m").to_string());
+    fs::create_dir_all(&dir)?;
+    let prefix = format!("{}.", record.loop_id);
+    for entry in fs::read_dir(root.join(".sgf/logs"))?.flatten() {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with(&prefix) {
+            fs::rename(entry.path(), dir.join(&name))?;
+        }
+    }
+    Ok(dir)
+}
+
+fn head_of(checkout: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(checkout)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Writes the opening record for a loop running in `checkout`. A resumed loop
+/// keeps its original start time and starting HEAD. With `branch_per_loop`,
+/// a loop in the main checkout is first moved onto its own branch (or back
+/// onto it, on resume). Announces the start and runs the `pre-loop` hook.
+pub fn begin_loop_record(
+    root: &Path,
+    checkout: &Path,
+    loop_id: &str,
+    stage: &str,
+    spec: Option<&str>,
+) {
+    let record = match read_loop_record(root, loop_id) {
+        Ok(Some(existing)) => {
+            if let Some(branch) = &existing.branch
+                && let Err(e) = crate::branching::reenter(root, branch)
+            {
+                crate::style::print_warning(&format!("staying on the current branch: {e}"));
+            }
+            // The new attempt recreates the log, so keep the old one aside.
+            let log = root.join(".sgf/logs").join(format!("{loop_id}.log"));
+            if log.exists()
+                && let Err(e) = fs::rename(&log, attempt_log_path(root, loop_id, existing.attempt))
+            {
+                tracing::warn!(error = %e, "failed to keep the previous attempt's log");
+            }
+            LoopRecord {
+                attempt_started_at: Some(chrono::Utc::now().to_rfc3339()),
+                ended_at: None,
+                exit_code: None,
+                head_after: None,
+                attempt: existing.attempt + 1,
+                ..existing
+            }
+        }
+        _ => {
+            let base_branch = if checkout == root {
+                enter_loop_branch(root, loop_id)
+            } else {
+                None
+            };
+            let now = chrono::Utc::now().to_rfc3339();
+            LoopRecord {
+                loop_id: loop_id.to_string(),
+                stage: stage.to_string(),
+                spec: spec.map(str::to_string),
+                started_at: now.clone(),
+                attempt_started_at: Some(now),
+                ended_at: None,
+                exit_code: None,
+                iterations_completed: 0,
+                head_before: head_of(checkout),
+                head_after: None,
+                branch: base_branch
+                    .is_some()
+                    .then(|| crate::branching::loop_branch(loop_id)),
+                base_branch,
+                attempt: 1,
+                incidents: Vec::new(),
+            }
+        }
+    };
+    if let Err(e) = write_loop_record(root, &record) {
+        tracing::warn!(error = %e, "failed to write loop record");
+    }
+    crate::event_stream::emit(&crate::event_stream::LoopEvent::started(&record));
+    crate::notify::announce(root, crate::notify::LoopEvent::Start, &record);
+    let env = crate::hooks::record_env(&record);
+    crate::hooks::run(root, checkout, crate::hooks::Hook::PreLoop, &env);
+}
+
+fn enter_loop_branch(root: &Path, loop_id: &str) -> Option<String> {
+    let config = crate::config::SgfConfig::load(root).unwrap_or_default();
+    if !config.branch_per_loop() {
+        return None;
+    }
+    match crate::branching::enter(root, loop_id) {
+        Ok(Some(base)) => {
+            let branch = crate::branching::loop_branch(loop_id);
+            crate::style::print_action(&format!("working on {branch} (from {base})"));
+            Some(base)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            crate::style::print_warning(&format!("branch_per_loop: {e}"));
+            None
+        }
+    }
+}
+
+fn merge_loop_branch(root: &Path, branch: &str, base: &str) {
+    use crate::branching::MergeOutcome;
+
+    let mode = crate::config::SgfConfig::load(root)
+        .unwrap_or_default()
+        .merge_back();
+    match crate::branching::merge_back(root, branch, base, mode) {
+        Ok(MergeOutcome::Kept) => {
+            crate::style::print_action(&format!("loop work is on {branch}"));
+        }
+        Ok(MergeOutcome::FastForwarded) => {
+            crate::style::print_success(&format!("fast-forwarded {base} to {branch}"));
+        }
+        Ok(MergeOutcome::PullRequest(url)) => {
+            crate::style::print_success_detail(
+                &format!("opened a pull request for {branch}"),
+                &url,
+            );
+        }
+        Err(e) => crate::style::print_warning(&format!("merge_back: {e}")),
+    }
+}
+
+/// Order matters: the record and ledger are written before the branch is
+/// merged back, and the loop is archived only after the `post-loop` hook has
+/// read its log.
+pub fn finish_loop_record(
+    root: &Path,
+    checkout: &Path,
+    loop_id: &str,
+    exit_code: Option<i32>,
+    iterations_completed: u32,
+) {
+    let Ok(Some(mut record)) = read_loop_record(root, loop_id) else {
+        tracing::warn!(loop_id, "loop record missing at exit");
+        return;
+    };
+    record.ended_at = Some(chrono::Utc::now().to_rfc3339());
+    record.exit_code = exit_code;
+    record.iterations_completed = iterations_completed;
+    record.head_after = head_of(checkout);
+    if let Err(e) = write_loop_record(root, &record) {
+        tracing::warn!(error = %e, "failed to update loop record");
+    }
+    if let Err(e) = crate::guard::record_finish(root) {
+        tracing::warn!(error = %e, "failed to record prompt changes");
+    }
+    if let Some(entry) = crate::ledger::LedgerEntry::for_record(root, &record)
+        && let Err(e) = crate::ledger::append(root, &entry)
+    {
+        tracing::warn!(error = %e, "failed to append to the cost ledger");
+    }
+    if record.exit_code == Some(0)
+        && let (Some(branch), Some(base)) = (&record.branch, &record.base_branch)
+    {
+        merge_loop_branch(root, branch, base);
+    }
+    crate::event_stream::emit(&crate::event_stream::LoopEvent::finished(&record));
+    let event = crate::notify::LoopEvent::for_outcome(&record);
+    crate::notify::announce(root, event, &record);
+    let env = crate::hooks::record_env(&record);
+    crate::hooks::run(root, checkout, crate::hooks::Hook::PostLoop, &env);
+    if record.exit_code == Some(0)
+        && let Err(e) = archive_loop(root, &record)
+    {
+        tracing::warn!(error = %e, "failed to archive the loop");
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunningLoop {
+    pub loop_id: String,
+    pub stage: String,
+    pub spec: Option<String>,
+    pub pid: u32,
+    pub started_at: String,
+}
+
+pub fn list_running_loops(root: &Path) -> Vec<RunningLoop> {
+    use crate::cursus::state::{self, RunStatus};
+
+    let mut running = Vec::new();
+    if let Ok(entries) = fs::read_dir(root.join(".sgf/run")) {
+        for entry in entries.flatten() {
+            if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                continue;
+            }
+            let run_id = entry.file_name().to_string_lossy().to_string();
+            if let Ok(Some(meta)) = state::read_metadata(root, &run_id)
+                && meta.status == RunStatus::Running
+                && let Some(pid) = state::read_pid(root, &run_id)
+                && is_pid_alive(pid)
+            {
+                running.push(RunningLoop {
+                    loop_id: run_id,
+                    stage: format!("{}/{}", meta.cursus, meta.current_iter),
+                    spec: meta.spec,
+                    pid,
+                    started_at: meta.created_at,
+                });
+            }
+        }
+    }
+
+    let pids = list_pid_files(root);
+    for meta in list_session_metadata(root).unwrap_or_default() {
+        if meta.status != "running" {
+            continue;
+        }
+        if let Some(&(_, pid)) = pids.iter().find(|(id, _)| *id == meta.loop_id)
+            && is_pid_alive(pid)
+        {
+            running.push(RunningLoop {
+                loop_id: meta.loop_id,
+                stage: meta.stage,
+                spec: meta.spec,
+                pid,
+                started_at: meta.created_at,
+            });
+        }
+    }
+
+    running.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    running
+}
+
+pub fn find_loop_by_pid(root: &Path, pid: u32) -> Option<String> {
+    if let Some((id, _)) = list_pid_files(root).into_iter().find(|(_, p)| *p == pid) {
+        return Some(id);
+    }
+    let entries = fs::read_dir(root.join(".sgf/run")).ok()?;
+    entries.flatten().find_map(|entry| {
+        let run_id = entry.file_name().to_string_lossy().to_string();
+        (entry.path().is_dir() && crate::cursus::state::read_pid(root, &run_id) == Some(pid))
+            .then_some(run_id)
+    })
+}
+
+pub fn read_loop_pid(root: &Path, loop_id: &str) -> Option<u32> {
+    crate::cursus::state::read_pid(root, loop_id).or_else(|| {
+        fs::read_to_string(root.join(".sgf/run").join(format!("{loop_id}.pid")))
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+    })
+}
+
+pub fn last_log_line(root: &Path, loop_id: &str) -> Option<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    const TAIL_BYTES: u64 = 8192;
+    let mut file = fs::File::open(log_path(root, loop_id)).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+        .ok()?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).ok()?;
+    let text = crate::style::strip_ansi(&String::from_utf8_lossy(&buf));
+    text.lines()
+        .rev()
+        .map(|l| crate::logs::split_stamp(l).1.trim())
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+pub fn format_uptime(started_at: &str) -> String {
+    let Ok(started) = chrono::DateTime::parse_from_rfc3339(started_at) else {
+        return "unknown".to_string();
+    };
+    format_duration(
+        chrono::Utc::now()
+            .signed_duration_since(started)
+            .num_seconds(),
+    )
+}
+
+pub fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    let (days, hours, mins) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{days}d{hours:02}h")
+    } else if hours > 0 {
+        format!("{hours}h{mins:02}m")
+    } else if mins > 0 {
+        format!("{mins}m{:02}s", secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopOutcome {
+    NotRunning,
+    Stopped,
+    Killed,
+}
+
+/// Sends SIGTERM to the loop's process group (or just the process when it
+/// does not lead one), waits up to `grace` for it to exit, then SIGKILLs it.
+/// A loop that could not record its own exit is marked interrupted so it
+/// shows up in `sgf resume`.
+pub fn stop_loop(root: &Path, loop_id: &str, grace: Duration) -> io::Result<StopOutcome> {
+    let Some(pid) = read_loop_pid(root, loop_id).filter(|&p| is_pid_alive(p)) else {
+        mark_loop_interrupted(root, loop_id)?;
+        return Ok(StopOutcome::NotRunning);
+    };
+
+    let _ = crate::platform::signal_loop(pid, false);
+
+    let start = Instant::now();
+    let outcome = loop {
+        if !is_pid_alive(pid) {
+            break StopOutcome::Stopped;
+        }
+        if start.elapsed() >= grace {
+            let _ = crate::platform::signal_loop(pid, true);
+            while is_pid_alive(pid) && start.elapsed() < grace + Duration::from_secs(2) {
+                thread::sleep(Duration::from_millis(50));
+            }
+            break StopOutcome::Killed;
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    mark_loop_interrupted(root, loop_id)?;
+    Ok(outcome)
+}
+
+fn mark_loop_interrupted(root: &Path, loop_id: &str) -> io::Result<()> {
+    use crate::cursus::state::{self, RunStatus};
+
+    if let Some(mut meta) = state::read_metadata(root, loop_id)? {
+        if meta.status == RunStatus::Running {
+            meta.status = RunStatus::Interrupted;
+            meta.touch();
+            state::write_metadata(root, &meta)?;
+        }
+        state::remove_pid_file(root, loop_id);
+    } else if let Some(mut meta) = read_session_metadata(root, loop_id)? {
+        if meta.status == "running" {
+            meta.status = "interrupted".to_string();
+            meta.updated_at = chrono::Utc::now().to_rfc3339();
+            write_session_metadata(root, &meta)?;
+        }
+        remove_pid_file(root, loop_id);
+    } else {
         return Err(io::Error::new(
             io::ErrorKind::NotFound,
-            format!("log file not found: {}", log_path.display()),
+            format!("loop not found: {loop_id}"),
         ));
     }
+    Ok(())
+}
+
+const DETACH_STARTUP_TIMEOUT: Duration = Duration::from_secs(15);
 
-    let status = std::process::Command::new("tail")
-        .args(["-f", &log_path.to_string_lossy()])
-        .status()?;
+#[derive(Debug)]
+pub struct DetachedLoop {
+    pub pid: u32,
+    pub loop_id: String,
+    /// Where the detached process's own stdout and stderr go; the agent
+    /// output itself lands in the usual `.sgf/logs/<loop_id>.log`.
+    pub output: PathBuf,
+}
 
-    if !status.success() {
-        return Err(io::Error::other(format!(
-            "tail exited with status: {status}"
-        )));
+/// Re-runs `sgf` with `args` in a new session so it survives the terminal
+/// (and a sleeping laptop's SIGHUP) going away. Waits until the child has
+/// written its PID file so the caller can report the loop ID.
+pub fn spawn_detached(
+    root: &Path,
+    args: &[String],
+    env: &[(String, String)],
+) -> io::Result<DetachedLoop> {
+    use std::process::{Command, Stdio};
+
+    let logs_dir = root.join(".sgf/logs");
+    fs::create_dir_all(&logs_dir)?;
+    let pending = logs_dir.join(format!(
+        "detached-{}-{}.out",
+        std::process::id(),
+        Local::now().format("%Y%m%dT%H%M%S")
+    ));
+    let out = fs::File::create(&pending)?;
+
+    let mut cmd = Command::new(std::env::current_exe()?);
+    cmd.args(args)
+        .current_dir(root)
+        .envs(env.iter().cloned())
+        .env("SGF_DETACHED", "1")
+        .env_remove("SGF_PROJECT_DIR")
+        .stdin(Stdio::null())
+        .stdout(out.try_clone()?)
+        .stderr(out);
+    crate::platform::detach(&mut cmd);
+    let mut child = cmd.spawn()?;
+    let pid = child.id();
+
+    let start = Instant::now();
+    while start.elapsed() < DETACH_STARTUP_TIMEOUT {
+        if let Some(loop_id) = find_loop_by_pid(root, pid) {
+            let output = logs_dir.join(format!("{loop_id}.out"));
+            fs::rename(&pending, &output)?;
+            return Ok(DetachedLoop {
+                pid,
+                loop_id,
+                output,
+            });
+        }
+        if let Some(status) = child.try_wait()? {
+            let tail = fs::read_to_string(&pending).unwrap_or_default();
+            let _ = fs::remove_file(&pending);
+            return Err(io::Error::other(format!(
+                "detached loop exited before starting ({status}): {}",
+                tail.trim()
+            )));
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!(
+            "detached loop (pid {pid}) did not register within {}s; see {}",
+            DETACH_STARTUP_TIMEOUT.as_secs(),
+            pending.display()
+        ),
+    ))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoopLock {
+    pub pid: u32,
+    pub command: String,
+    pub spec: Option<String>,
+    pub started_at: String,
+}
+
+pub fn lock_path(root: &Path, key: Option<&str>) -> PathBuf {
+    let name = match key {
+        Some(k) => format!("loop-{k}.lock"),
+        None => "loop.lock".to_string(),
+    };
+    root.join(".sgf/run").join(name)
+}
+
+pub fn read_lock(path: &Path) -> Option<LoopLock> {
+    let content = fs::read_to_string(path).ok()?;
+    let lock: LoopLock = serde_json::from_str(&content).ok()?;
+    is_pid_alive(lock.pid).then_some(lock)
+}
+
+/// How long a lock file that doesn't parse is treated as mid-write rather
+/// than abandoned.
+const UNREADABLE_LOCK_GRACE: Duration = Duration::from_secs(10);
+
+/// Takes the lock for this process, replacing a stale one. With `force`,
+/// a live holder is replaced too; otherwise it is an `AlreadyExists` error
+/// naming the running loop.
+///
+/// The lock is written to a temp file and hard-linked into place, so other
+/// acquirers never see it half-written.
+pub fn acquire_lock(
+    root: &Path,
+    key: Option<&str>,
+    command: &str,
+    spec: Option<&str>,
+    force: bool,
+) -> io::Result<PathBuf> {
+    let path = lock_path(root, key);
+    fs::create_dir_all(path.parent().unwrap())?;
+    let lock = LoopLock {
+        pid: std::process::id(),
+        command: command.to_string(),
+        spec: spec.map(str::to_string),
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let json = serde_json::to_string(&lock).map_err(io::Error::other)?;
+    let tmp = path.with_extension(format!("lock.{}.tmp", lock.pid));
+    fs::write(&tmp, json)?;
+    let result = link_lock(root, &tmp, &path, lock.pid, force);
+    let _ = fs::remove_file(&tmp);
+    result.map(|()| path)
+}
+
+fn link_lock(root: &Path, tmp: &Path, path: &Path, pid: u32, force: bool) -> io::Result<()> {
+    loop {
+        match fs::hard_link(tmp, path) {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                let content = match fs::read_to_string(path) {
+                    Ok(content) => content,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e),
+                };
+                match serde_json::from_str::<LoopLock>(&content) {
+                    Ok(holder) => {
+                        if !force && holder.pid != pid && is_pid_alive(holder.pid) {
+                            return Err(io::Error::new(
+                                io::ErrorKind::AlreadyExists,
+                                lock_held_message(root, &holder),
+                            ));
+                        }
+                    }
+                    Err(_) if !force && !lock_older_than(path, UNREADABLE_LOCK_GRACE) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            format!(
+                                "lock {} is unreadable and may be mid-write; retry shortly or pass --force",
+                                path.display()
+                            ),
+                        ));
+                    }
+                    Err(_) => {}
+                }
+                match fs::remove_file(path) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn lock_older_than(path: &Path, age: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed > age)
+}
+
+fn lock_held_message(root: &Path, holder: &LoopLock) -> String {
+    let loop_name = find_loop_by_pid(root, holder.pid).unwrap_or_else(|| match &holder.spec {
+        Some(spec) => format!("{} {spec}", holder.command),
+        None => holder.command.clone(),
+    });
+    let since = chrono::DateTime::parse_from_rfc3339(&holder.started_at)
+        .map(|t| {
+            t.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| holder.started_at.clone());
+    format!(
+        "loop {loop_name} already running since {since} (pid {}); stop it with `sgf stop` or pass --force",
+        holder.pid
+    )
+}
+
+pub fn release_lock(path: &Path) {
+    let ours = fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<LoopLock>(&c).ok())
+        .is_some_and(|l| l.pid == std::process::id());
+    if ours {
+        let _ = fs::remove_file(path);
     }
-    Ok(())
 }
 
 #[cfg(test)]
@@ -203,6 +904,17 @@ mod tests {
         assert_eq!(ts_part.len(), 15);
     }
 
+    #[test]
+    fn actor_env_var_names_the_loop() {
+        assert_eq!(
+            actor_env_var("build-auth-20260316T120000"),
+            (
+                "PN_ACTOR".to_string(),
+                "sgf-build-auth-20260316T120000".to_string()
+            )
+        );
+    }
+
     #[test]
     fn loop_id_compound_stage() {
         let id = generate_loop_id("issues-plan", None);
@@ -310,11 +1022,151 @@ mod tests {
     }
 
     #[test]
-    fn run_logs_missing_file() {
+    fn loop_record_tracks_start_finish_and_resume() {
         let tmp = TempDir::new().unwrap();
-        let err = run_logs(tmp.path(), "nonexistent").unwrap_err();
-        assert_eq!(err.kind(), io::ErrorKind::NotFound);
-        assert!(err.to_string().contains("log file not found"));
+        let root = tmp.path();
+
+        begin_loop_record(root, root, "build-auth-1", "build", Some("auth"));
+        let started = read_loop_record(root, "build-auth-1").unwrap().unwrap();
+        assert_eq!(started.stage, "build");
+        assert_eq!(started.spec.as_deref(), Some("auth"));
+        assert_eq!(started.outcome(), "running");
+        assert!(started.head_before.is_none(), "not a git checkout");
+
+        finish_loop_record(root, root, "build-auth-1", Some(2), 3);
+        let finished = read_loop_record(root, "build-auth-1").unwrap().unwrap();
+        assert_eq!(finished.outcome(), "exhausted");
+        assert_eq!(finished.iterations_completed, 3);
+        assert!(finished.ended_at.is_some());
+
+        fs::write(root.join(".sgf/logs/build-auth-1.log"), "first attempt\n").unwrap();
+        begin_loop_record(root, root, "build-auth-1", "build", Some("auth"));
+        let resumed = read_loop_record(root, "build-auth-1").unwrap().unwrap();
+        assert_eq!(resumed.started_at, started.started_at);
+        assert_eq!(resumed.iterations_completed, 3);
+        assert!(resumed.ended_at.is_none());
+        assert_eq!((started.attempt, resumed.attempt), (1, 2));
+        assert_eq!(
+            fs::read_to_string(attempt_log_path(root, "build-auth-1", 1)).unwrap(),
+            "first attempt\n"
+        );
+
+        begin_loop_record(root, root, "verify-2", "verify", None);
+        fs::write(root.join(".sgf/logs/verify-2.log"), "").unwrap();
+        let ids: Vec<_> = list_loop_records(root)
+            .into_iter()
+            .map(|r| r.loop_id)
+            .collect();
+        assert_eq!(ids, ["verify-2", "build-auth-1"]);
+    }
+
+    #[test]
+    fn last_log_line_skips_blank_lines_and_ansi() {
+        let tmp = TempDir::new().unwrap();
+        let log = create_log_file(tmp.path(), "build-1").unwrap();
+        fs::write(&log, "first\n\x1b[1;32mrunning tests\x1b[0m\n\n  \n").unwrap();
+        assert_eq!(
+            last_log_line(tmp.path(), "build-1").as_deref(),
+            Some("running tests")
+        );
+        fs::write(&log, crate::logs::stamp("stamped") + "\n").unwrap();
+        assert_eq!(
+            last_log_line(tmp.path(), "build-1").as_deref(),
+            Some("stamped")
+        );
+        assert!(last_log_line(tmp.path(), "missing").is_none());
+    }
+
+    #[test]
+    fn format_uptime_scales_units() {
+        let ago = |secs: i64| (chrono::Utc::now() - chrono::Duration::seconds(secs)).to_rfc3339();
+        assert_eq!(format_uptime(&ago(5)), "5s");
+        assert_eq!(format_uptime(&ago(125)), "2m05s");
+        assert_eq!(format_uptime(&ago(2 * 3600 + 300)), "2h05m");
+        assert_eq!(format_uptime(&ago(26 * 3600)), "1d02h");
+        assert_eq!(format_uptime("garbage"), "unknown");
+    }
+
+    #[test]
+    fn running_loops_include_live_sessions_and_cursus_runs() {
+        use crate::cursus::state::{self, RunMetadata, RunStatus};
+
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+
+        let mut live = make_metadata("simple-20260316T120000", "2026-03-16T12:00:00Z");
+        live.status = "running".to_string();
+        write_session_metadata(root, &live).unwrap();
+        write_pid_file(root, &live.loop_id).unwrap();
+
+        let mut dead = make_metadata("simple-20260316T110000", "2026-03-16T11:00:00Z");
+        dead.status = "running".to_string();
+        write_session_metadata(root, &dead).unwrap();
+        fs::write(root.join(".sgf/run/simple-20260316T110000.pid"), "4000000").unwrap();
+
+        let mut run = RunMetadata::new("build", "build", Some("auth"), None);
+        run.created_at = "2026-03-16T10:00:00Z".to_string();
+        state::write_metadata(root, &run).unwrap();
+        state::write_pid_file(root, &run.run_id).unwrap();
+
+        let mut done = RunMetadata::new("verify", "verify", None, None);
+        done.run_id = "verify-done".to_string();
+        done.status = RunStatus::Completed;
+        state::write_metadata(root, &done).unwrap();
+
+        let running = list_running_loops(root);
+        let ids: Vec<&str> = running.iter().map(|l| l.loop_id.as_str()).collect();
+        assert_eq!(ids, vec![run.run_id.as_str(), "simple-20260316T120000"]);
+        assert_eq!(running[0].stage, "build/build");
+        assert_eq!(running[0].spec.as_deref(), Some("auth"));
+        assert_eq!(running[0].pid, std::process::id());
+
+        assert!(find_loop_by_pid(root, std::process::id()).is_some());
+        assert_eq!(
+            find_loop_by_pid(root, 4_000_000).as_deref(),
+            Some("simple-20260316T110000")
+        );
+    }
+
+    #[test]
+    fn stop_loop_terminates_process_and_marks_interrupted() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let mut cmd = std::process::Command::new("sleep");
+        cmd.arg("30");
+        crate::platform::detach(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+
+        let mut meta = make_metadata("simple-20260316T120000", "2026-03-16T12:00:00Z");
+        meta.status = "running".to_string();
+        write_session_metadata(root, &meta).unwrap();
+        fs::write(
+            root.join(".sgf/run/simple-20260316T120000.pid"),
+            child.id().to_string(),
+        )
+        .unwrap();
+
+        let reaper = thread::spawn(move || child.wait());
+        let outcome = stop_loop(root, "simple-20260316T120000", Duration::from_secs(5)).unwrap();
+        reaper.join().unwrap().unwrap();
+        assert_eq!(outcome, StopOutcome::Stopped);
+
+        let meta = read_session_metadata(root, "simple-20260316T120000")
+            .unwrap()
+            .unwrap();
+        assert_eq!(meta.status, "interrupted");
+        assert!(!root.join(".sgf/run/simple-20260316T120000.pid").exists());
+
+        assert_eq!(
+            stop_loop(root, "simple-20260316T120000", Duration::from_secs(1)).unwrap(),
+            StopOutcome::NotRunning
+        );
+        assert_eq!(
+            stop_loop(root, "nope", Duration::from_secs(1))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
     }
 
     fn make_metadata(loop_id: &str, updated_at: &str) -> SessionMetadata {
@@ -683,4 +1535,83 @@ mod tests {
             .unwrap();
         assert_eq!(read_back.cursus, None);
     }
+
+    #[test]
+    fn lock_blocks_live_holder_and_replaces_stale_one() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let mut sleeper = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        fs::create_dir_all(root.join(".sgf/run")).unwrap();
+        fs::write(
+            root.join(".sgf/run/build-auth-20260316T120000.pid"),
+            sleeper.id().to_string(),
+        )
+        .unwrap();
+        let held = LoopLock {
+            pid: sleeper.id(),
+            command: "build".to_string(),
+            spec: Some("auth".to_string()),
+            started_at: "2026-03-16T12:00:00Z".to_string(),
+        };
+        let path = lock_path(root, None);
+        fs::write(&path, serde_json::to_string(&held).unwrap()).unwrap();
+
+        let err = acquire_lock(root, None, "build", None, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        let msg = err.to_string();
+        assert!(
+            msg.starts_with("loop build-auth-20260316T120000 already running since "),
+            "{msg}"
+        );
+        assert!(msg.contains(&format!("pid {}", sleeper.id())), "{msg}");
+
+        let spec_lock =
+            acquire_lock(root, Some("billing"), "build", Some("billing"), false).unwrap();
+        assert!(spec_lock.ends_with(".sgf/run/loop-billing.lock"));
+
+        assert_eq!(acquire_lock(root, None, "build", None, true).unwrap(), path);
+        assert_eq!(read_lock(&path).unwrap().pid, std::process::id());
+        release_lock(&path);
+        assert!(!path.exists());
+
+        sleeper.kill().unwrap();
+        sleeper.wait().unwrap();
+        fs::write(&path, serde_json::to_string(&held).unwrap()).unwrap();
+        assert!(read_lock(&path).is_none(), "dead holder is stale");
+        acquire_lock(root, None, "verify", None, false).unwrap();
+        assert_eq!(read_lock(&path).unwrap().command, "verify");
+    }
+
+    #[test]
+    fn unreadable_lock_is_busy_until_the_grace_period_passes() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let path = lock_path(root, None);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "").unwrap();
+
+        let err = acquire_lock(root, None, "build", None, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(err.to_string().contains("mid-write"), "{err}");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+        let old = std::time::SystemTime::now() - UNREADABLE_LOCK_GRACE * 2;
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        acquire_lock(root, None, "build", None, false).unwrap();
+        assert_eq!(read_lock(&path).unwrap().pid, std::process::id());
+        let leftovers: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name())
+            .collect();
+        assert_eq!(leftovers, vec![std::ffi::OsString::from("loop.lock")]);
+    }
 }
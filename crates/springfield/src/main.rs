@@ -1,5 +1,5 @@
 use std::ffi::OsString;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use clap::{Parser, Subcommand};
 use shutdown::{ShutdownConfig, ShutdownController};
@@ -11,6 +11,10 @@ use springfield::iter_runner::IterRunnerConfig;
 #[derive(Parser)]
 #[command(name = "sgf")]
 struct Cli {
+    /// Run in this subproject of a monorepo; git still works on the whole repo
+    #[arg(long, global = true, value_name = "PATH", env = "SGF_PROJECT_DIR")]
+    project_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -24,6 +28,15 @@ enum Commands {
         /// Skip frontend scaffolding (pnpm create vite)
         #[arg(long)]
         no_fe: bool,
+        /// Tailor the scaffold to a stack: rust, node, python, or tauri
+        #[arg(long, value_name = "PROFILE")]
+        profile: Option<springfield::profile::Profile>,
+        /// Print what would be created or changed, with diffs, and write nothing
+        #[arg(long)]
+        dry_run: bool,
+        /// Print the available profiles and exit
+        #[arg(long, conflicts_with_all = ["force", "no_fe", "profile", "dry_run"])]
+        list_profiles: bool,
     },
 
     /// Show available commands with descriptions
@@ -41,16 +54,230 @@ enum Commands {
         run_id: String,
     },
 
-    /// Tail a running loop's output
+    /// Print or follow a loop's output
     Logs {
-        /// Loop ID to tail
+        /// Loop ID whose log to print
+        #[arg(required_unless_present = "list")]
+        loop_id: Option<String>,
+
+        /// List recent loops with their outcomes
+        #[arg(long, conflicts_with_all = ["loop_id", "info", "follow", "grep", "since", "errors_only"])]
+        list: bool,
+
+        /// Print the loop's recorded metadata instead of its log
+        #[arg(long, requires = "loop_id")]
+        info: bool,
+
+        /// Keep printing new output, reopening the log if it is replaced
+        #[arg(short, long, conflicts_with = "info")]
+        follow: bool,
+
+        /// Only print lines matching this regular expression
+        #[arg(long, value_name = "PATTERN", value_parser = regex::Regex::new, conflicts_with = "info")]
+        grep: Option<regex::Regex>,
+
+        /// Only print lines logged within this long ago (e.g. 10m, 2h, 1d)
+        #[arg(long, value_name = "DURATION", value_parser = springfield::logs::parse_since, conflicts_with = "info")]
+        since: Option<chrono::TimeDelta>,
+
+        /// Only print lines that report errors or failures
+        #[arg(long, conflicts_with = "info")]
+        errors_only: bool,
+    },
+
+    /// List running loops
+    Ps,
+
+    /// Dashboard of running loops, their output, issue counts, and recent
+    /// commits
+    Watch,
+
+    /// Summarize a finished loop: iterations, commits, files, gates, and cost
+    Report {
+        /// Loop ID to summarize
+        loop_id: String,
+        /// Print JSON instead of markdown
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Total agent cost and wall-clock time per stage or spec from the ledger
+    Costs {
+        /// Only count loops that ended within this long (e.g. 7d)
+        #[arg(long, value_name = "DURATION", value_parser = springfield::logs::parse_since)]
+        since: Option<chrono::TimeDelta>,
+        /// Group by stage or spec
+        #[arg(long, value_name = "stage|spec", default_value = "stage")]
+        by: springfield::ledger::GroupBy,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Finished loops with their outcome, duration, iterations, and cost
+    History {
+        /// Only loops of this stage or cursus
+        #[arg(long)]
+        stage: Option<String>,
+        /// Only loops for this spec
+        #[arg(long)]
+        spec: Option<String>,
+        /// Only loops that ended since this long ago (7d) or this day (2026-10-01)
+        #[arg(long, value_name = "WHEN", value_parser = springfield::history::parse_when)]
+        since: Option<springfield::history::When>,
+        /// Only loops that ended before this long ago or through this day
+        #[arg(long, value_name = "WHEN", value_parser = springfield::history::parse_when)]
+        until: Option<springfield::history::When>,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Delete old logs, assembled prompts, and stale run state (all three
+    /// when no flag is given)
+    Clean {
+        /// Delete logs last written longer ago than this (default: log_retention, else 14d)
+        #[arg(long, value_name = "DURATION", value_parser = springfield::logs::parse_since)]
+        logs_older_than: Option<chrono::TimeDelta>,
+
+        /// Delete assembled prompt copies from finished runs
+        #[arg(long)]
+        assembled: bool,
+
+        /// Delete stale PID and lock files and the state of completed runs
+        #[arg(long)]
+        run: bool,
+    },
+
+    /// Check that this checkout has what loops need, with a fix for each
+    /// problem found
+    Doctor {
+        /// Apply the fixes that only add to the project: create directories,
+        /// restore owner permissions, merge sandbox settings, start daemons
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Clean up after crashed loops now, as the next launch would
+    Recover {
+        /// Print what would be restored, stashed, or deleted, and change nothing
+        #[arg(long)]
+        dry_run: bool,
+        /// Override `[recovery] strategy`: reset, stash, or abort
+        #[arg(long, value_name = "STRATEGY")]
+        strategy: Option<springfield::config::RecoveryStrategy>,
+    },
+
+    /// Compare project prompts and backpressure with the current templates
+    Upgrade {
+        /// Print the diffs without asking or changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Print or run the commands in BACKPRESSURE.md
+    Backpressure {
+        #[command(subcommand)]
+        action: BackpressureAction,
+    },
+
+    /// Render or check prompt templates without launching an agent
+    Prompt {
+        #[command(subcommand)]
+        action: PromptAction,
+    },
+
+    /// Run a custom stage defined in .sgf/config.toml
+    Run {
+        /// Stage name followed by the usual command flags and spec
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+
+    /// Triage pensa bugs or summarize how fast they are fixed
+    Issues {
+        #[command(subcommand)]
+        action: IssuesAction,
+    },
+
+    /// Claim the top ready pensa issue and run the build loop on it
+    Next {
+        /// Only consider issues for this spec
+        #[arg(long)]
+        spec: Option<String>,
+        /// Build loop flags such as -a, -n, --no-push, or --dry-run
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+
+    /// Undo a finished loop's commits and reopen the issues it closed
+    Rollback {
+        /// Loop ID to roll back
+        loop_id: String,
+        /// Rewind the loop's own branch instead of reverting on a new one
+        #[arg(long)]
+        reset: bool,
+    },
+
+    /// Stop a running loop: SIGTERM, then SIGKILL after a grace period
+    Stop {
+        /// Loop ID to stop
         loop_id: String,
+        /// Seconds to wait for a graceful exit before forcing it
+        #[arg(long, default_value_t = 10)]
+        timeout: u64,
     },
 
     #[command(external_subcommand)]
     Dynamic(Vec<OsString>),
 }
 
+#[derive(Subcommand)]
+enum PromptAction {
+    /// Print a command's assembled prompt to stdout
+    Assemble {
+        /// Cursus, alias, or custom stage name
+        stage: String,
+        /// Spec to fill {{spec}} and {{spec_content}}
+        #[arg(long)]
+        spec: Option<String>,
+        /// Iter to render (defaults to the first)
+        #[arg(long)]
+        iter: Option<String>,
+    },
+    /// Check every template for unresolved variables, missing includes, and size
+    Lint,
+    /// Accept prompts an earlier loop changed, after reviewing them
+    Trust,
+}
+
+#[derive(Subcommand)]
+enum BackpressureAction {
+    /// List each command with its kind and label
+    Show,
+    /// Run the build, test, lint, and format commands and report each
+    Run {
+        /// Only run commands of this kind: build, test, lint, format, or other
+        #[arg(long = "only", value_name = "KIND")]
+        kinds: Vec<springfield::backpressure::Kind>,
+    },
+}
+
+#[derive(Subcommand)]
+enum IssuesAction {
+    /// Set priority, spec, and dependencies for each untriaged open bug
+    Triage,
+    /// Bugs opened against bugs fixed, week by week
+    Stats {
+        /// Number of weeks to cover, ending now
+        #[arg(long, default_value_t = 4)]
+        weeks: u32,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Debug)]
 struct DynamicArgs {
     command: String,
@@ -60,8 +287,41 @@ struct DynamicArgs {
     iterations: Option<u32>,
     no_push: bool,
     skip_preflight: bool,
+    skip_gates: bool,
     resume: Option<String>,
     output_format: Option<String>,
+    json_events: bool,
+    detach: bool,
+    dry_run: bool,
+    force: bool,
+    /// Specs after the first; any present means a parallel run.
+    extra_specs: Vec<String>,
+    all_ready: bool,
+    jobs: Option<usize>,
+    tuning: springfield::iter_runner::AgentTuning,
+    profile: Option<String>,
+    no_art: bool,
+    banner_file: Option<PathBuf>,
+    task: Option<springfield::next::Task>,
+    refresh: bool,
+}
+
+/// `--env KEY=VALUE`, or `--env KEY` for KEY's value in sgf's environment.
+fn parse_env_arg(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = match arg.split_once('=') {
+        Some((key, value)) => (key, Some(value.to_string())),
+        None => (arg, None),
+    };
+    let valid = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("invalid --env name: {key}"));
+    }
+    let value = match value {
+        Some(value) => value,
+        None => std::env::var(key).map_err(|_| format!("--env {key} is not set"))?,
+    };
+    Ok((key.to_string(), value))
 }
 
 fn parse_dynamic_args(args: Vec<OsString>) -> Result<DynamicArgs, String> {
@@ -86,8 +346,21 @@ fn parse_dynamic_args(args: Vec<OsString>) -> Result<DynamicArgs, String> {
     let mut iterations = None;
     let mut no_push = false;
     let mut skip_preflight = false;
+    let mut skip_gates = false;
     let mut resume = None;
     let mut output_format = None;
+    let mut json_events = false;
+    let mut detach = false;
+    let mut dry_run = false;
+    let mut force = false;
+    let mut extra_specs = Vec::new();
+    let mut all_ready = false;
+    let mut jobs = None;
+    let mut tuning = springfield::iter_runner::AgentTuning::default();
+    let mut profile = None;
+    let mut no_art = false;
+    let mut banner_file = None;
+    let mut refresh = false;
 
     let mut i = 0;
     while i < rest.len() {
@@ -96,6 +369,39 @@ fn parse_dynamic_args(args: Vec<OsString>) -> Result<DynamicArgs, String> {
             "-i" | "--interactive" => interactive = true,
             "--no-push" => no_push = true,
             "--skip-preflight" => skip_preflight = true,
+            "--skip-gates" => skip_gates = true,
+            "-d" | "--detach" => detach = true,
+            "--dry-run" => dry_run = true,
+            "--no-art" => no_art = true,
+            "--force" => force = true,
+            "--refresh" => refresh = true,
+            "--all-ready" => all_ready = true,
+            "-j" | "--jobs" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--jobs requires a value".to_string());
+                }
+                let n = rest[i]
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid job count: {}", rest[i]))?;
+                jobs = Some(n);
+            }
+            "--profile" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--profile requires a value".to_string());
+                }
+                profile = Some(rest[i].clone());
+            }
+            "--banner-file" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--banner-file requires a value".to_string());
+                }
+                banner_file = Some(PathBuf::from(&rest[i]));
+            }
             "--resume" => {
                 i += 1;
                 if i >= rest.len() {
@@ -114,7 +420,83 @@ fn parse_dynamic_args(args: Vec<OsString>) -> Result<DynamicArgs, String> {
                 }
                 output_format = Some(val);
             }
-            "-n" | "--iterations" => {
+            "--output" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--output requires a value".to_string());
+                }
+                json_events = match rest[i].as_str() {
+                    "json" => true,
+                    "text" => false,
+                    other => {
+                        return Err(format!(
+                            "unsupported output: {other} (expected json or text)"
+                        ));
+                    }
+                };
+            }
+            // Applied in main before anything else runs.
+            "--project-dir" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--project-dir requires a value".to_string());
+                }
+            }
+            "--model" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--model requires a value".to_string());
+                }
+                tuning.model = Some(rest[i].clone());
+            }
+            "--iteration-timeout" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--iteration-timeout requires a value".to_string());
+                }
+                let timeout = springfield::logs::parse_since(&rest[i])?
+                    .to_std()
+                    .map_err(|_| format!("invalid iteration timeout: {}", rest[i]))?;
+                tuning.iteration_timeout = Some(timeout);
+            }
+            "--max-cost" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--max-cost requires a value".to_string());
+                }
+                let max = rest[i]
+                    .trim_start_matches('$')
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|&n| n > 0.0 && n.is_finite())
+                    .ok_or_else(|| format!("invalid cost limit: {}", rest[i]))?;
+                tuning.max_cost_usd = Some(max);
+            }
+            "--complete-when" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--complete-when requires a value".to_string());
+                }
+                if rest[i].trim().is_empty() {
+                    return Err("--complete-when needs a command".to_string());
+                }
+                tuning.complete_when = Some(rest[i].clone());
+            }
+            "--agent-arg" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--agent-arg requires a value".to_string());
+                }
+                tuning.extra_args.push(rest[i].clone());
+            }
+            "--env" => {
+                i += 1;
+                if i >= rest.len() {
+                    return Err("--env requires a value".to_string());
+                }
+                tuning.env.push(parse_env_arg(&rest[i])?);
+            }
+            "-n" | "--iterations" | "--max-iterations" => {
                 i += 1;
                 if i >= rest.len() {
                     return Err("--iterations requires a value".to_string());
@@ -137,9 +519,10 @@ fn parse_dynamic_args(args: Vec<OsString>) -> Result<DynamicArgs, String> {
             }
             positional => {
                 if spec.is_some() {
-                    return Err(format!("unexpected argument: {positional}"));
+                    extra_specs.push(positional.to_string());
+                } else {
+                    spec = Some(positional.to_string());
                 }
-                spec = Some(positional.to_string());
             }
         }
         i += 1;
@@ -154,6 +537,37 @@ fn parse_dynamic_args(args: Vec<OsString>) -> Result<DynamicArgs, String> {
     if resume.is_some() && interactive {
         return Err("--resume and -i/--interactive are mutually exclusive".to_string());
     }
+    if detach && interactive {
+        return Err("-d/--detach and -i/--interactive are mutually exclusive".to_string());
+    }
+    if detach && resume.is_some() {
+        return Err("-d/--detach and --resume are mutually exclusive".to_string());
+    }
+    if dry_run && (detach || resume.is_some()) {
+        return Err("--dry-run cannot be used with -d/--detach or --resume".to_string());
+    }
+    if detach && output_format.is_some() {
+        return Err("-d/--detach and --output-format are mutually exclusive".to_string());
+    }
+    if detach && json_events {
+        return Err("-d/--detach and --output json are mutually exclusive".to_string());
+    }
+    if all_ready && spec.is_some() {
+        return Err("--all-ready and spec arguments are mutually exclusive".to_string());
+    }
+    if all_ready || !extra_specs.is_empty() {
+        for (set, flag) in [
+            (interactive, "-i/--interactive"),
+            (resume.is_some(), "--resume"),
+            (detach, "-d/--detach"),
+            (dry_run, "--dry-run"),
+            (output_format.is_some(), "--output-format"),
+        ] {
+            if set {
+                return Err(format!("{flag} cannot be used with multiple specs"));
+            }
+        }
+    }
 
     Ok(DynamicArgs {
         command,
@@ -163,30 +577,149 @@ fn parse_dynamic_args(args: Vec<OsString>) -> Result<DynamicArgs, String> {
         iterations,
         no_push,
         skip_preflight,
+        skip_gates,
         resume,
         output_format,
+        json_events,
+        detach,
+        dry_run,
+        force,
+        extra_specs,
+        all_ready,
+        jobs,
+        tuning,
+        profile,
+        no_art,
+        banner_file,
+        task: None,
+        refresh,
     })
 }
 
-fn run_pre_launch(root: &Path, skip_preflight: bool) {
-    if skip_preflight {
+fn merge_profile(args: &mut DynamicArgs, profile: &springfield::config::RunProfile) {
+    if !args.afk && !args.interactive && profile.afk == Some(true) {
+        args.afk = true;
+    }
+    args.iterations = args
+        .iterations
+        .or(profile.iterations)
+        .map(|n| n.min(springfield::iter_runner::MAX_ITERATIONS));
+    if profile.auto_push == Some(false) {
+        args.no_push = true;
+    }
+    let tuning = &mut args.tuning;
+    tuning.model = tuning.model.take().or_else(|| profile.model.clone());
+    tuning.max_cost_usd = tuning.max_cost_usd.or(profile.max_cost);
+    tuning.iteration_timeout = tuning.iteration_timeout.or(profile.iteration_timeout());
+    tuning.complete_when = tuning
+        .complete_when
+        .take()
+        .or_else(|| profile.complete_when.clone());
+}
+
+fn apply_profile(root: &Path, args: &mut DynamicArgs) {
+    let Some(name) = args.profile.clone() else {
+        return;
+    };
+    let config = load_config(root);
+    match config.run_profile(&name) {
+        Ok(profile) => {
+            merge_profile(args, profile);
+            springfield::config::activate_profile(profile.clone());
+        }
+        Err(e) => {
+            springfield::style::print_error(&e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn load_banner_art(root: &Path, args: &DynamicArgs) {
+    if args.no_art {
+        return;
+    }
+    let Some(path) = args
+        .banner_file
+        .clone()
+        .or_else(|| load_config(root).banner_file.map(|p| root.join(p)))
+    else {
         return;
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(art) => springfield::iter_runner::banner::set_art(&art),
+        Err(e) => {
+            springfield::style::print_warning(&format!("banner file {}: {e}", path.display()))
+        }
     }
+}
 
-    if let Err(e) = springfield::recovery::pre_launch_recovery(root) {
-        springfield::style::print_warning(&format!("pre-launch recovery: {e}"));
+fn run_pre_launch(root: &Path, skip_preflight: bool) {
+    springfield::clean::apply_log_retention(root);
+    if skip_preflight {
+        return;
     }
+    run_guard(root);
 
-    if std::env::var("SGF_SKIP_PREFLIGHT").is_err()
-        && let Err(e) = springfield::recovery::ensure_daemons(root)
-    {
-        springfield::style::print_warning(&format!("daemon startup: {e}"));
+    match springfield::recovery::pre_launch_recovery(root) {
+        Ok(springfield::recovery::RecoveryOutcome::Aborted) => {
+            recovery_aborted();
+        }
+        Ok(_) => {}
+        Err(e) => springfield::style::print_warning(&format!("pre-launch recovery: {e}")),
+    }
+
+    if std::env::var("SGF_SKIP_PREFLIGHT").is_err() {
+        if let Err(e) = springfield::recovery::ensure_daemons(root) {
+            springfield::style::print_warning(&format!("daemon startup: {e}"));
+        } else if let Err(e) = springfield::recovery::check_pensa_version(root) {
+            springfield::style::print_error_detail(
+                &e,
+                "stop the daemon so sgf starts one from the installed pn, or install a matching pensa",
+            );
+            std::process::exit(1);
+        }
     }
 
     springfield::recovery::export_pensa();
     springfield::recovery::export_forma();
 }
 
+/// Checks the deny rules and prompts as `guard` says, exiting on a problem
+/// when it is `refuse`, then snapshots the prompts for the loop launching.
+fn run_guard(root: &Path) {
+    use springfield::config::GuardMode;
+
+    let mode = load_config(root).guard();
+    if mode == GuardMode::Off {
+        return;
+    }
+    let problems = springfield::guard::check(root);
+    for problem in &problems {
+        if mode == GuardMode::Refuse {
+            springfield::style::print_error_detail(&problem.message, problem.fix);
+        } else {
+            springfield::style::print_warning_detail(&problem.message, problem.fix);
+        }
+    }
+    if mode == GuardMode::Refuse && !problems.is_empty() {
+        eprintln!("To launch anyway: set guard = \"warn\" in .sgf/config.toml");
+        std::process::exit(1);
+    }
+    if let Err(e) = springfield::guard::record_launch(root) {
+        tracing::warn!(error = %e, "failed to snapshot prompts");
+    }
+}
+
+fn load_config(root: &Path) -> springfield::config::SgfConfig {
+    match springfield::config::SgfConfig::load(root) {
+        Ok(c) => c,
+        Err(e) => {
+            springfield::style::print_error(&format!("config: {e}"));
+            std::process::exit(1);
+        }
+    }
+}
+
 fn resolve_command(root: &Path, name: &str) -> Result<cursus::ResolvedCursus, String> {
     if let Some(resolved) = cursus::resolve_cursus(root, name) {
         return Ok(resolved);
@@ -196,24 +729,183 @@ fn resolve_command(root: &Path, name: &str) -> Result<cursus::ResolvedCursus, St
         return Ok(resolved);
     }
 
+    if let Some(resolved) = cursus::resolve_stage(root, name) {
+        return Ok(resolved);
+    }
+
     Err(format!("unknown command: {name}"))
 }
 
+fn check_custom_stage(root: &Path, name: &str) -> Result<(), String> {
+    if cursus::resolve_stage(root, name).is_none() {
+        return Err(format!(
+            "unknown stage: {name} (define [stages.{name}] with a prompt in .sgf/config.toml)"
+        ));
+    }
+    if let Some(shadow) =
+        cursus::resolve_cursus(root, name).or_else(|| cursus::resolve_alias(root, name))
+    {
+        return Err(format!(
+            "stage {name} is shadowed by cursus {}; rename one of them",
+            shadow.name
+        ));
+    }
+    Ok(())
+}
+
+fn take_loop_lock(
+    root: &Path,
+    command: &str,
+    spec: Option<&str>,
+    force: bool,
+) -> Option<std::path::PathBuf> {
+    use springfield::config::LockScope;
+
+    let key = match load_config(root).lock_scope() {
+        LockScope::Off => return None,
+        LockScope::Global => None,
+        LockScope::Spec => spec,
+    };
+    match springfield::loop_mgmt::acquire_lock(root, key, command, spec, force) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            springfield::style::print_error(&e.to_string());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn gates_pass(root: &Path, stage: &str, spec: Option<&str>) -> bool {
+    let config = load_config(root);
+    let Some(gate) = config.gate(stage) else {
+        return true;
+    };
+    let failures = springfield::gates::check(root, spec, gate);
+    if failures.is_empty() {
+        return true;
+    }
+    springfield::style::print_error(&format!(
+        "{stage}: {} of {} gate checks failed",
+        failures.len(),
+        gate.after.len() + springfield::gates::commands(root, gate).len()
+    ));
+    for failure in &failures {
+        springfield::style::print_detail(&format!("{}: {}", failure.check, failure.reason));
+        for line in failure.output.iter().flat_map(|o| o.lines()) {
+            springfield::style::print_detail(&format!("  {line}"));
+        }
+    }
+    eprintln!("To launch anyway: add --skip-gates");
+    false
+}
+
+fn load_backpressure(root: &Path) -> Vec<springfield::backpressure::Check> {
+    match springfield::backpressure::load(root) {
+        Ok(checks) if checks.is_empty() => {
+            springfield::style::print_warning("no commands found in BACKPRESSURE.md");
+            std::process::exit(1);
+        }
+        Ok(checks) => checks,
+        Err(e) => {
+            springfield::style::print_error(&format!("backpressure: {e}"));
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_backpressure_show(root: &Path) {
+    let checks = load_backpressure(root);
+    let width = checks.iter().map(|c| c.label.len()).max().unwrap_or(0);
+    for check in &checks {
+        let note = if check.runnable() {
+            ""
+        } else {
+            "  (needs arguments)"
+        };
+        for command in &check.commands {
+            println!(
+                "  {:<6}  {:<width$}  {command}{note}",
+                check.kind.name(),
+                check.label
+            );
+        }
+    }
+}
+
+fn run_backpressure(root: &Path, kinds: &[springfield::backpressure::Kind]) {
+    let kinds = if kinds.is_empty() {
+        &springfield::backpressure::Kind::RUNNABLE[..]
+    } else {
+        kinds
+    };
+    let commands = springfield::backpressure::commands_of(&load_backpressure(root), kinds);
+    if commands.is_empty() {
+        springfield::style::print_warning("no runnable backpressure commands of those kinds");
+        std::process::exit(1);
+    }
+    let mut failed = 0;
+    for command in &commands {
+        springfield::style::print_action(command);
+        match springfield::gates::run_command(root, command) {
+            None => springfield::style::print_success("passed"),
+            Some(failure) => {
+                failed += 1;
+                springfield::style::print_error(&failure.reason);
+                for line in failure.output.iter().flat_map(|o| o.lines()) {
+                    springfield::style::print_detail(&format!("  {line}"));
+                }
+            }
+        }
+    }
+    if failed > 0 {
+        springfield::style::print_error(&format!(
+            "{failed} of {} backpressure commands failed",
+            commands.len()
+        ));
+        std::process::exit(1);
+    }
+    springfield::style::print_success(&format!(
+        "all {} backpressure commands passed",
+        commands.len()
+    ));
+}
+
+/// Checks everything a launch needs that would otherwise only fail once an
+/// agent is running: the definition, its prompt templates, and each spec.
+fn validate_launch(
+    root: &Path,
+    resolved: &cursus::ResolvedCursus,
+    specs: &[String],
+) -> Result<(), String> {
+    cursus::toml::validate(&resolved.definition)
+        .and_then(|()| cursus::toml::validate_prompts(root, &resolved.definition))
+        .map_err(|e| format!("{}: {e}", resolved.name))?;
+    for stem in specs {
+        springfield::prompt::check_spec(root, stem).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 fn run_simple_prompt(root: &Path, args: &DynamicArgs, prompt_path: &Path) -> ! {
     use chrono::Utc;
     use springfield::loop_mgmt::{self, IterationRecord, SessionMetadata};
 
+    let lock = take_loop_lock(root, &args.command, None, args.force);
     run_pre_launch(root, args.skip_preflight);
 
     let afk = args.afk;
-    let iterations = args.iterations.unwrap_or(1);
-    let auto_push = !args.no_push;
+    let config = load_config(root);
+    let iterations = args.iterations.unwrap_or(1).min(config.max_iterations());
+    let auto_push = !args.no_push && config.auto_push();
 
     let loop_id = loop_mgmt::generate_loop_id("simple", None);
 
     let log_file = loop_mgmt::create_log_file(root, &loop_id).ok();
+    loop_mgmt::begin_loop_record(root, root, &loop_id, "simple", None);
 
-    let agent_command = std::env::var("SGF_AGENT_COMMAND").ok();
+    let agent_command = std::env::var("SGF_AGENT_COMMAND")
+        .ok()
+        .or(config.agent_command.clone());
 
     let mode = if afk { "afk" } else { "interactive" };
     let now = Utc::now().to_rfc3339();
@@ -235,6 +927,9 @@ fn run_simple_prompt(root: &Path, args: &DynamicArgs, prompt_path: &Path) -> ! {
     if let Err(e) = loop_mgmt::write_session_metadata(root, &metadata) {
         tracing::warn!(error = %e, "failed to write initial session metadata");
     }
+    if let Err(e) = loop_mgmt::write_pid_file(root, &loop_id) {
+        tracing::warn!(error = %e, "failed to write pid file");
+    }
 
     let root_for_start = root.to_path_buf();
     let loop_id_for_start = loop_id.clone();
@@ -286,19 +981,24 @@ fn run_simple_prompt(root: &Path, args: &DynamicArgs, prompt_path: &Path) -> ! {
         },
     );
 
+    let mut retry = cursus::toml::RetryConfig::default();
+    if let Some(profile) = springfield::config::active_profile() {
+        profile.apply_backoff(&mut retry);
+    }
     let config = IterRunnerConfig {
         afk,
         banner: true,
         loop_id: Some(loop_id.clone()),
         iterations,
         prompt: prompt_str,
-        auto_push,
+        auto_push: auto_push && springfield::push::per_iteration(root),
+        push_target: springfield::push::target(root),
         command: agent_command,
         prompt_files: vec![],
         log_file,
         session_id: Some(uuid::Uuid::new_v4().to_string()),
         resume: None,
-        env_vars: vec![],
+        env_vars: vec![loop_mgmt::actor_env_var(&loop_id)],
         runner_name: Some("sgf".to_string()),
         work_dir: Some(root.to_path_buf()),
         post_result_timeout: springfield::iter_runner::default_post_result_timeout(),
@@ -306,10 +1006,11 @@ fn run_simple_prompt(root: &Path, args: &DynamicArgs, prompt_path: &Path) -> ! {
         stdin_input: None,
         on_iteration_start: Some(on_iteration_start),
         on_iteration_complete: Some(on_iteration_complete),
-        retry_immediate: 3,
-        retry_interval_secs: 300,
-        retry_max_duration_secs: 43200,
+        retry_immediate: retry.immediate,
+        retry_interval_secs: retry.interval_secs,
+        retry_max_duration_secs: retry.max_duration_secs,
         on_retry: None,
+        tuning: args.tuning.clone(),
     };
 
     let is_tty = args.output_format.is_none()
@@ -361,17 +1062,49 @@ fn run_simple_prompt(root: &Path, args: &DynamicArgs, prompt_path: &Path) -> ! {
             "interrupted"
         }
     };
+    let code = exit_code as i32;
+    let mut completed = 0;
     if let Ok(Some(mut meta)) = loop_mgmt::read_session_metadata(root, &loop_id) {
+        completed = meta
+            .iterations
+            .iter()
+            .filter(|i| !i.completed_at.is_empty())
+            .count() as u32;
         meta.status = status.to_string();
         meta.updated_at = Utc::now().to_rfc3339();
         if let Err(e) = loop_mgmt::write_session_metadata(root, &meta) {
             tracing::warn!(error = %e, "failed to update session metadata on exit");
         }
     }
+    springfield::push::after_loop(root, &loop_id, code, auto_push);
+    loop_mgmt::finish_loop_record(root, root, &loop_id, Some(code), completed);
+    loop_mgmt::remove_pid_file(root, &loop_id);
+    if let Some(ref path) = lock {
+        loop_mgmt::release_lock(path);
+    }
 
     eprintln!("To resume: sgf {} --resume {}", args.command, loop_id);
 
-    std::process::exit(exit_code as i32);
+    std::process::exit(code);
+}
+
+fn resume_locked(root: &Path, run_id: &str, force: bool) -> std::io::Result<i32> {
+    let spec = cursus::state::read_metadata(root, run_id)
+        .ok()
+        .flatten()
+        .and_then(|m| m.spec)
+        .or_else(|| {
+            springfield::loop_mgmt::read_session_metadata(root, run_id)
+                .ok()
+                .flatten()
+                .and_then(|m| m.spec)
+        });
+    let lock = take_loop_lock(root, run_id, spec.as_deref(), force);
+    let result = resume_dispatch(root, run_id);
+    if let Some(ref path) = lock {
+        springfield::loop_mgmt::release_lock(path);
+    }
+    result
 }
 
 fn resume_dispatch(root: &Path, run_id: &str) -> std::io::Result<i32> {
@@ -392,11 +1125,16 @@ fn resume_dispatch(root: &Path, run_id: &str) -> std::io::Result<i32> {
     }
 }
 
-fn run_dynamic(args: DynamicArgs) -> ! {
+fn run_dynamic(mut args: DynamicArgs) -> ! {
     let root = std::env::current_dir().expect("failed to get current directory");
+    apply_profile(&root, &mut args);
+    load_banner_art(&root, &args);
+    if args.json_events {
+        springfield::event_stream::enable();
+    }
 
     if let Some(ref run_id) = args.resume {
-        match resume_dispatch(&root, run_id) {
+        match resume_locked(&root, run_id, args.force) {
             Ok(code) => std::process::exit(code),
             Err(e) => {
                 springfield::style::print_error(&format!("resume: {e}"));
@@ -405,8 +1143,34 @@ fn run_dynamic(args: DynamicArgs) -> ! {
         }
     }
 
+    if args.all_ready || !args.extra_specs.is_empty() {
+        run_parallel_dispatch(&root, &args);
+    }
+
+    if args.detach {
+        run_detached(&root, &args);
+    }
+
+    if springfield::remote_prompt::is_url(&args.command) {
+        if args.dry_run {
+            springfield::style::print_error("--dry-run only applies to cursus commands");
+            std::process::exit(1);
+        }
+        match springfield::remote_prompt::fetch(&args.command, args.refresh) {
+            Ok(prompt_path) => run_simple_prompt(&root, &args, &prompt_path),
+            Err(e) => {
+                springfield::style::print_error(&format!("prompt: {e}"));
+                std::process::exit(1);
+            }
+        }
+    }
+
     let candidate = Path::new(&args.command);
     if candidate.exists() && candidate.is_file() {
+        if args.dry_run {
+            springfield::style::print_error("--dry-run only applies to cursus commands");
+            std::process::exit(1);
+        }
         let prompt_path = if candidate.is_absolute() {
             candidate.to_path_buf()
         } else {
@@ -426,35 +1190,239 @@ fn run_dynamic(args: DynamicArgs) -> ! {
     run_cursus_dispatch(&root, &args, resolved);
 }
 
-fn run_cursus_dispatch(root: &Path, args: &DynamicArgs, resolved: cursus::ResolvedCursus) -> ! {
-    run_pre_launch(root, args.skip_preflight);
-
-    let mut def = resolved.definition.clone();
+/// The picked issue is not claimed here but in the dispatch, once the loop
+/// lock is held.
+fn run_next(root: &Path, spec: Option<String>, args: Vec<OsString>) -> ! {
+    let mut parsed = match parse_dynamic_args(std::iter::once("build".into()).chain(args).collect())
+    {
+        Ok(a) => a,
+        Err(e) => {
+            springfield::style::print_error(&e);
+            std::process::exit(1);
+        }
+    };
+    for (set, flag) in [
+        (parsed.spec.is_some(), "a spec argument (use --spec)"),
+        (parsed.resume.is_some(), "--resume"),
+        (parsed.detach, "-d/--detach"),
+        (parsed.all_ready, "--all-ready"),
+    ] {
+        if set {
+            springfield::style::print_error(&format!("next: {flag} is not supported"));
+            std::process::exit(1);
+        }
+    }
 
-    if let Err(e) = cursus::toml::validate(&def) {
-        springfield::style::print_error(&format!("{}: {e}", resolved.name));
+    let task = match springfield::next::pick(root, spec.as_deref()) {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            eprintln!("No ready issues");
+            std::process::exit(0);
+        }
+        Err(e) => {
+            springfield::style::print_error(&format!("next: {e}"));
+            std::process::exit(1);
+        }
+    };
+    apply_profile(root, &mut parsed);
+    parsed.spec = task.spec.clone().or(spec);
+    parsed.task = Some(task);
+    if parsed.json_events {
+        springfield::event_stream::enable();
+    }
+
+    let resolved = match resolve_command(root, &parsed.command) {
+        Ok(r) => r,
+        Err(e) => {
+            springfield::style::print_error(&e);
+            std::process::exit(1);
+        }
+    };
+    run_cursus_dispatch(root, &parsed, resolved);
+}
+
+fn run_parallel_dispatch(root: &Path, args: &DynamicArgs) -> ! {
+    use springfield::parallel::{self, ParallelConfig};
+
+    let resolved = match resolve_command(root, &args.command) {
+        Ok(r) => r,
+        Err(e) => {
+            springfield::style::print_error(&e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut specs: Vec<String> = if args.all_ready {
+        match parallel::ready_specs(root) {
+            Ok(s) => s,
+            Err(e) => {
+                springfield::style::print_error(&format!("--all-ready: {e}"));
+                std::process::exit(1);
+            }
+        }
+    } else {
+        args.spec.iter().chain(&args.extra_specs).cloned().collect()
+    };
+    let mut seen = std::collections::HashSet::new();
+    specs.retain(|s| seen.insert(s.clone()));
+    if specs.is_empty() {
+        eprintln!("No specs with ready issues");
+        std::process::exit(0);
+    }
+    if let Err(e) = validate_launch(root, &resolved, &specs) {
+        springfield::style::print_error(&e);
         std::process::exit(1);
     }
-    if let Err(e) = cursus::toml::validate_prompts(root, &def) {
-        springfield::style::print_error(&format!("{}: {e}", resolved.name));
+
+    run_pre_launch(root, args.skip_preflight);
+
+    // Worktrees have no loop history, so each spec's gate is checked here.
+    if !args.skip_gates {
+        let passed = specs
+            .iter()
+            .filter(|spec| gates_pass(root, &resolved.name, Some(spec)))
+            .count();
+        if passed < specs.len() {
+            std::process::exit(1);
+        }
+    }
+
+    let config = ParallelConfig {
+        command: args.command.clone(),
+        jobs: args
+            .jobs
+            .or(load_config(root).jobs)
+            .unwrap_or(parallel::DEFAULT_JOBS),
+        specs,
+        iterations: args.iterations,
+        no_push: args.no_push,
+        tuning: args.tuning.clone(),
+        profile: args.profile.clone(),
+    };
+    springfield::style::print_action_detail(
+        &format!("launching {} parallel loops", config.specs.len()),
+        &format!("specs: {} · jobs: {}", config.specs.join(", "), config.jobs),
+    );
+
+    let results = parallel::run_parallel(root, &config);
+    parallel::print_summary(&results);
+    let code = if results.iter().all(|r| r.succeeded()) {
+        0
+    } else {
+        1
+    };
+    std::process::exit(code);
+}
+
+/// Starts the loop in a background session and returns once it has a loop
+/// ID. The child always runs AFK with preflight already done here.
+fn run_detached(root: &Path, args: &DynamicArgs) -> ! {
+    // A URL prompt is fetched here so the child runs the local copy.
+    let command = if springfield::remote_prompt::is_url(&args.command) {
+        match springfield::remote_prompt::fetch(&args.command, args.refresh) {
+            Ok(path) => path.to_string_lossy().to_string(),
+            Err(e) => {
+                springfield::style::print_error(&format!("prompt: {e}"));
+                std::process::exit(1);
+            }
+        }
+    } else {
+        args.command.clone()
+    };
+    let resolved = if Path::new(&command).is_file() {
+        None
+    } else {
+        match resolve_command(root, &command).and_then(|resolved| {
+            validate_launch(root, &resolved, args.spec.as_slice()).map(|()| resolved)
+        }) {
+            Ok(resolved) => Some(resolved),
+            Err(e) => {
+                springfield::style::print_error(&e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    run_pre_launch(root, args.skip_preflight);
+
+    // Gates are checked here so a failure shows up before detaching.
+    if let Some(ref resolved) = resolved
+        && !args.skip_gates
+        && !gates_pass(root, &resolved.name, args.spec.as_deref())
+    {
         std::process::exit(1);
     }
 
+    let mut child_args = vec![command];
+    child_args.extend(args.spec.clone());
+    child_args.extend([
+        "-a".to_string(),
+        "--skip-preflight".to_string(),
+        "--skip-gates".to_string(),
+    ]);
+    if let Some(n) = args.iterations {
+        child_args.extend(["-n".to_string(), n.to_string()]);
+    }
+    if args.no_push {
+        child_args.push("--no-push".to_string());
+    }
+    if args.force {
+        child_args.push("--force".to_string());
+    }
+    child_args.extend(args.tuning.cli_args());
+    if let Some(ref profile) = args.profile {
+        child_args.extend(["--profile".to_string(), profile.clone()]);
+    }
+
+    match springfield::loop_mgmt::spawn_detached(root, &child_args, &args.tuning.env) {
+        Ok(detached) => {
+            springfield::style::print_success_detail(
+                &format!("detached [{}]", detached.loop_id),
+                &format!("pid: {}", detached.pid),
+            );
+            eprintln!("To follow: sgf logs {}", detached.loop_id);
+            eprintln!("To stop: sgf stop {}", detached.loop_id);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            springfield::style::print_error(&format!("detach: {e}"));
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_cursus_dispatch(root: &Path, args: &DynamicArgs, resolved: cursus::ResolvedCursus) -> ! {
+    if let Err(e) = validate_launch(root, &resolved, args.spec.as_slice()) {
+        springfield::style::print_error(&e);
+        std::process::exit(1);
+    }
     let all_defs = cursus::load_all_definitions(root);
     if let Err(e) = cursus::toml::validate_aliases(&all_defs) {
         springfield::style::print_error(&format!("alias validation: {e}"));
         std::process::exit(1);
     }
 
+    let mut def = resolved.definition.clone();
+
+    let config = load_config(root);
+    config.apply_stage_defaults(&mut def);
+    if let Some(profile) = springfield::config::active_profile() {
+        profile.apply(&mut def);
+    }
+
     if let Some(n) = args.iterations {
         for iter in &mut def.iters {
-            iter.iterations = n;
+            iter.iterations = Some(n);
         }
     }
 
     cursus::toml::clamp_iterations(&mut def);
+    let max_iterations = config.max_iterations();
+    for iter in &mut def.iters {
+        iter.iterations = Some(iter.iterations().min(max_iterations));
+    }
 
-    if args.no_push {
+    if args.no_push || !config.auto_push() {
         def.auto_push = false;
         for iter in &mut def.iters {
             iter.auto_push = Some(false);
@@ -469,18 +1437,58 @@ fn run_cursus_dispatch(root: &Path, args: &DynamicArgs, resolved: cursus::Resolv
         None
     };
 
-    if let Some(ref stem) = args.spec {
-        let spec_path = root.join(format!("specs/{stem}.md"));
-        if !spec_path.exists() {
-            springfield::style::print_error(&format!("spec not found: specs/{stem}.md"));
+    if args.dry_run {
+        let config = cursus::runner::CursusConfig {
+            spec: args.spec.clone(),
+            mode_override,
+            no_push: args.no_push,
+            agent_command: None,
+            skip_preflight: args.skip_preflight,
+            monitor_stdin_override: None,
+            programmatic: false,
+            initial_input: None,
+            tuning: args.tuning.clone(),
+            task: args.task.clone(),
+        };
+        match cursus::runner::dry_run(root, &resolved.name, &def, &config) {
+            Ok(plan) => {
+                print!("{plan}");
+                std::process::exit(0);
+            }
+            Err(e) => {
+                springfield::style::print_error(&format!("{}: {e}", resolved.name));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let lock = take_loop_lock(root, &args.command, args.spec.as_deref(), args.force);
+    run_pre_launch(root, args.skip_preflight);
+
+    if !args.skip_gates && !gates_pass(root, &resolved.name, args.spec.as_deref()) {
+        if let Some(ref path) = lock {
+            springfield::loop_mgmt::release_lock(path);
+        }
+        std::process::exit(1);
+    }
+
+    if let Some(ref task) = args.task {
+        if let Err(e) = springfield::next::claim(root, task) {
+            springfield::style::print_error(&format!("next: {e}"));
+            if let Some(ref path) = lock {
+                springfield::loop_mgmt::release_lock(path);
+            }
             std::process::exit(1);
         }
+        springfield::style::print_action(&format!("claimed {}: {}", task.id, task.title));
     }
 
     let is_tty = std::env::var("SGF_FORCE_TERMINAL")
         .map(|v| v == "1")
         .unwrap_or_else(|_| std::io::IsTerminal::is_terminal(&std::io::stdin()));
-    let programmatic = args.output_format.as_deref() == Some("json") || !is_tty;
+    // A detached loop has no terminal but is not driven by a caller either.
+    let detached = std::env::var("SGF_DETACHED").is_ok();
+    let programmatic = !detached && (args.output_format.as_deref() == Some("json") || !is_tty);
 
     let initial_input = if programmatic {
         let mut buf = String::new();
@@ -499,9 +1507,27 @@ fn run_cursus_dispatch(root: &Path, args: &DynamicArgs, resolved: cursus::Resolv
         monitor_stdin_override: None,
         programmatic,
         initial_input,
+        tuning: args.tuning.clone(),
+        task: args.task.clone(),
     };
 
-    match cursus::runner::run_cursus(root, &resolved.name, &def, &config) {
+    let result = cursus::runner::run_cursus(root, &resolved.name, &def, &config);
+    if let Some(ref path) = lock {
+        springfield::loop_mgmt::release_lock(path);
+    }
+    if let Some(ref task) = args.task {
+        let code = *result.as_ref().unwrap_or(&1);
+        match springfield::next::settle(root, task, code) {
+            Ok(springfield::next::Settlement::Closed) => {
+                springfield::style::print_success(&format!("closed {}", task.id))
+            }
+            Ok(springfield::next::Settlement::Released) => {
+                springfield::style::print_warning(&format!("released {}", task.id))
+            }
+            Err(e) => springfield::style::print_error(&format!("next: {e}")),
+        }
+    }
+    match result {
         Ok(code) => std::process::exit(code),
         Err(e) => {
             springfield::style::print_error(&format!("{}: {e}", resolved.name));
@@ -549,7 +1575,7 @@ fn collect_resumable(root: &Path) -> Vec<ResumableEntry> {
 
 fn run_resume_command(root: &Path, run_id: Option<&str>) -> ! {
     if let Some(id) = run_id {
-        match resume_dispatch(root, id) {
+        match resume_locked(root, id, false) {
             Ok(code) => std::process::exit(code),
             Err(e) => {
                 springfield::style::print_error(&format!("resume: {e}"));
@@ -594,7 +1620,7 @@ fn run_resume_command(root: &Path, run_id: Option<&str>) -> ! {
     };
 
     let selected = &entries[choice - 1];
-    match resume_dispatch(root, &selected.run_id) {
+    match resume_locked(root, &selected.run_id, false) {
         Ok(code) => std::process::exit(code),
         Err(e) => {
             springfield::style::print_error(&format!("resume: {e}"));
@@ -611,65 +1637,567 @@ fn run_kill(root: &Path, run_id: &str) -> ! {
             std::process::exit(1);
         }
         Err(e) => {
-            springfield::style::print_error(&format!("failed to read metadata: {e}"));
+            springfield::style::print_error(&format!("failed to read metadata: {e}"));
+            std::process::exit(1);
+        }
+    };
+
+    if meta.status != cursus::state::RunStatus::Running {
+        eprintln!("Run {run_id} is already {}, nothing to kill", meta.status);
+        std::process::exit(0);
+    }
+
+    let killed = if let Some(pid) = cursus::state::read_pid(root, run_id) {
+        if cursus::state::is_pid_alive(pid) {
+            let ok = shutdown::kill_process_group(pid, std::time::Duration::from_secs(2));
+            if ok {
+                eprintln!("Killed process group (pid {pid})");
+            } else {
+                eprintln!("Process {pid} already exited");
+            }
+            true
+        } else {
+            eprintln!("Process {pid} already exited");
+            true
+        }
+    } else {
+        eprintln!("No PID file found, updating status only");
+        false
+    };
+
+    let mut meta = if killed {
+        match cursus::state::read_metadata(root, run_id) {
+            Ok(Some(m)) => m,
+            _ => meta,
+        }
+    } else {
+        meta
+    };
+
+    meta.status = cursus::state::RunStatus::WaitingForInput;
+    meta.touch();
+    if let Err(e) = cursus::state::write_metadata(root, &meta) {
+        springfield::style::print_error(&format!("failed to update metadata: {e}"));
+        std::process::exit(1);
+    }
+    cursus::state::remove_pid_file(root, run_id);
+
+    springfield::style::print_success(&format!("Marked {run_id} as waiting_for_input"));
+    eprintln!("To resume: sgf {} --resume {run_id}", meta.cursus);
+    std::process::exit(0);
+}
+
+fn run_clean(root: &Path, logs_older_than: Option<chrono::TimeDelta>, assembled: bool, run: bool) {
+    use springfield::clean::{self, CleanOptions};
+
+    let everything = logs_older_than.is_none() && !assembled && !run;
+    let opts = CleanOptions {
+        logs_older_than: logs_older_than.or_else(|| {
+            everything.then(|| {
+                load_config(root)
+                    .log_retention()
+                    .unwrap_or(clean::DEFAULT_LOG_RETENTION)
+            })
+        }),
+        assembled: assembled || everything,
+        run: run || everything,
+    };
+    match clean::clean(root, &opts) {
+        Ok(report) => springfield::style::print_success(&format!(
+            "removed {} {} ({:.1} MB)",
+            report.files,
+            if report.files == 1 { "file" } else { "files" },
+            report.bytes as f64 / 1_048_576.0
+        )),
+        Err(e) => {
+            springfield::style::print_error(&format!("clean: {e}"));
+            std::process::exit(1);
+        }
+    }
+}
+
+fn recovery_aborted() -> ! {
+    springfield::style::print_error_detail(
+        "a crashed loop left changes in the working tree",
+        "review them, then run `sgf recover --strategy stash` or `--strategy reset`",
+    );
+    std::process::exit(1);
+}
+
+fn run_recover(
+    root: &Path,
+    dry_run: bool,
+    strategy: Option<springfield::config::RecoveryStrategy>,
+) {
+    use springfield::recovery::{self, RecoveryOutcome};
+
+    let plan = match recovery::plan(root, strategy) {
+        Ok(Some(plan)) => plan,
+        Ok(None) => {
+            springfield::style::print_success("nothing to recover");
+            return;
+        }
+        Err(e) => {
+            springfield::style::print_error(&format!("recover: {e}"));
+            std::process::exit(1);
+        }
+    };
+    if dry_run {
+        for line in plan.describe() {
+            println!("{line}");
+        }
+        return;
+    }
+    match recovery::apply(root, &plan) {
+        Ok(RecoveryOutcome::Aborted) => recovery_aborted(),
+        Ok(_) => {}
+        Err(e) => {
+            springfield::style::print_error(&format!("recover: {e}"));
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_doctor(root: &Path, fix: bool) {
+    use springfield::doctor::{self, Severity};
+    use springfield::style;
+
+    let findings = doctor::diagnose(root, fix);
+    for f in &findings {
+        let msg = format!("{}: {}", f.check, f.message);
+        let suggestion = f.suggestion.as_deref().unwrap_or_default();
+        match f.severity {
+            _ if f.fixed => style::print_success_detail(&msg, "fixed"),
+            Severity::Ok => style::print_success(&msg),
+            Severity::Warn => style::print_warning_detail(&msg, suggestion),
+            Severity::Fail => style::print_error_detail(&msg, suggestion),
+        }
+    }
+
+    let pending = findings.iter().filter(|f| f.fixable && !f.fixed).count();
+    if !fix && pending > 0 {
+        style::print_action(&format!(
+            "run `sgf doctor --fix` to apply {pending} safe {}",
+            if pending == 1 { "fix" } else { "fixes" }
+        ));
+    }
+    let failures = findings.iter().filter(|f| f.is_failure()).count();
+    if failures > 0 {
+        style::print_error(&format!(
+            "{failures} {} attention",
+            if failures == 1 {
+                "problem needs"
+            } else {
+                "problems need"
+            }
+        ));
+        std::process::exit(1);
+    }
+    style::print_success("environment ok");
+}
+
+fn run_logs_list(root: &Path) {
+    const RECENT: usize = 20;
+
+    let records = springfield::loop_mgmt::list_loop_records(root);
+    if records.is_empty() {
+        eprintln!("No recorded loops");
+        return;
+    }
+
+    let rows: Vec<[String; 6]> = records
+        .iter()
+        .take(RECENT)
+        .map(|r| {
+            [
+                r.loop_id.clone(),
+                r.stage.clone(),
+                r.spec.clone().unwrap_or_else(|| "-".to_string()),
+                r.outcome().to_string(),
+                r.iterations_completed.to_string(),
+                springfield::orchestrate::humanize_relative_time(&r.started_at),
+            ]
+        })
+        .collect();
+
+    print_table(
+        ["ID", "STAGE", "SPEC", "OUTCOME", "ITERS", "STARTED"],
+        &rows,
+    );
+}
+
+fn run_logs_info(root: &Path, loop_id: &str) -> std::io::Result<()> {
+    let record = springfield::loop_mgmt::read_loop_record(root, loop_id)?.ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no metadata recorded for {loop_id}"),
+        )
+    })?;
+    let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+    println!("id:          {}", record.loop_id);
+    println!("stage:       {}", record.stage);
+    println!("spec:        {}", or_dash(record.spec.clone()));
+    println!("outcome:     {}", record.outcome());
+    println!(
+        "exit code:   {}",
+        or_dash(record.exit_code.map(|c| c.to_string()))
+    );
+    println!("iterations:  {}", record.iterations_completed);
+    println!("attempt:     {}", record.attempt);
+    println!("started:     {}", record.started_at);
+    println!("ended:       {}", or_dash(record.ended_at.clone()));
+    println!("head before: {}", or_dash(record.head_before.clone()));
+    println!("head after:  {}", or_dash(record.head_after.clone()));
+    for incident in &record.incidents {
+        println!(
+            "stalled:     iteration {} at {}, no output for {}s{}",
+            incident.iteration,
+            incident.at,
+            incident.stalled_secs,
+            if incident.restarted {
+                ", restarted"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(())
+}
+
+fn run_ps(root: &Path) {
+    const LAST_OUTPUT_WIDTH: usize = 60;
+
+    let loops = springfield::loop_mgmt::list_running_loops(root);
+    if loops.is_empty() {
+        eprintln!("No running loops");
+        return;
+    }
+
+    let rows: Vec<[String; 6]> = loops
+        .iter()
+        .map(|l| {
+            let last: String = springfield::loop_mgmt::last_log_line(root, &l.loop_id)
+                .unwrap_or_default()
+                .chars()
+                .take(LAST_OUTPUT_WIDTH)
+                .collect();
+            [
+                l.loop_id.clone(),
+                l.stage.clone(),
+                l.spec.clone().unwrap_or_else(|| "-".to_string()),
+                l.pid.to_string(),
+                springfield::loop_mgmt::format_uptime(&l.started_at),
+                last,
+            ]
+        })
+        .collect();
+
+    print_table(
+        ["ID", "STAGE", "SPEC", "PID", "UPTIME", "LAST OUTPUT"],
+        &rows,
+    );
+}
+
+fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let print_row = |cells: [&str; N]| {
+        let line: Vec<String> = cells[..N - 1]
+            .iter()
+            .zip(widths)
+            .map(|(c, w)| format!("{c:<w$}"))
+            .collect();
+        println!("{}  {}", line.join("  "), cells[N - 1]);
+    };
+    print_row(header);
+    for row in rows {
+        print_row(row.each_ref().map(String::as_str));
+    }
+}
+
+fn run_issue_stats(root: &Path, weeks: u32, json: bool) -> std::io::Result<()> {
+    use springfield::issues;
+
+    let stats = issues::stats(&issues::bugs(root)?, weeks, chrono::Utc::now());
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).expect("stats serialize")
+        );
+        return Ok(());
+    }
+    let rows: Vec<[String; 4]> = stats
+        .weeks
+        .iter()
+        .map(|w| {
+            [
+                w.week_start.to_string(),
+                w.opened.to_string(),
+                w.fixed.to_string(),
+                format!("{:+}", w.opened as i64 - w.fixed as i64),
+            ]
+        })
+        .collect();
+    print_table(["WEEK OF", "OPENED", "FIXED", "NET"], &rows);
+    let opened: usize = stats.weeks.iter().map(|w| w.opened).sum();
+    let fixed: usize = stats.weeks.iter().map(|w| w.fixed).sum();
+    println!();
+    println!(
+        "{} open bugs; {opened} opened and {fixed} fixed in {weeks} weeks",
+        stats.open
+    );
+    Ok(())
+}
+
+fn run_costs(
+    root: &Path,
+    since: Option<chrono::TimeDelta>,
+    by: springfield::ledger::GroupBy,
+    json: bool,
+) {
+    use springfield::ledger;
+    use springfield::loop_mgmt::format_duration;
+
+    let summaries = ledger::summarize(&ledger::read(root), since, by);
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&summaries).expect("summaries serialize")
+        );
+        return;
+    }
+    if summaries.is_empty() {
+        eprintln!("No loops in the cost ledger");
+        return;
+    }
+
+    let total = summaries
+        .iter()
+        .fold(ledger::CostSummary::default(), |mut total, s| {
+            total.loops += s.loops;
+            total.iterations += s.iterations;
+            total.duration_secs += s.duration_secs;
+            total.input_tokens += s.input_tokens;
+            total.output_tokens += s.output_tokens;
+            total.cost_usd += s.cost_usd;
+            total
+        });
+    let row = |key: &str, s: &ledger::CostSummary| {
+        [
+            key.to_string(),
+            s.loops.to_string(),
+            s.iterations.to_string(),
+            format_duration(s.duration_secs),
+            (s.input_tokens + s.output_tokens).to_string(),
+            format!("${:.2}", s.cost_usd),
+        ]
+    };
+    let mut rows: Vec<[String; 6]> = summaries.iter().map(|s| row(&s.key, s)).collect();
+    rows.push(row("total", &total));
+    let key = match by {
+        ledger::GroupBy::Stage => "STAGE",
+        ledger::GroupBy::Spec => "SPEC",
+    };
+    print_table([key, "LOOPS", "ITERS", "TIME", "TOKENS", "COST"], &rows);
+}
+
+fn run_history(root: &Path, filter: &springfield::history::HistoryFilter, json: bool) {
+    use springfield::loop_mgmt::format_duration;
+
+    let entries = springfield::history::list(root, filter);
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).expect("history serializes")
+        );
+        return;
+    }
+    if entries.is_empty() {
+        eprintln!("No finished loops");
+        return;
+    }
+
+    let rows: Vec<[String; 8]> = entries
+        .iter()
+        .map(|e| {
+            let ended = chrono::DateTime::parse_from_rfc3339(&e.ended_at)
+                .map(|t| {
+                    t.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_else(|_| "-".to_string());
+            [
+                e.loop_id.clone(),
+                e.stage.clone(),
+                e.spec.clone().unwrap_or_else(|| "-".to_string()),
+                e.outcome.clone(),
+                ended,
+                format_duration(e.duration_secs),
+                e.iterations.to_string(),
+                e.cost_usd
+                    .map_or_else(|| "-".to_string(), |c| format!("${c:.2}")),
+            ]
+        })
+        .collect();
+    print_table(
+        [
+            "ID", "STAGE", "SPEC", "OUTCOME", "ENDED", "TIME", "ITERS", "COST",
+        ],
+        &rows,
+    );
+}
+
+fn run_stop(root: &Path, loop_id: &str, timeout: u64) -> ! {
+    use springfield::loop_mgmt::StopOutcome;
+
+    match springfield::loop_mgmt::stop_loop(root, loop_id, std::time::Duration::from_secs(timeout))
+    {
+        Ok(StopOutcome::NotRunning) => {
+            eprintln!("Loop {loop_id} is not running, nothing to stop");
+            std::process::exit(0);
+        }
+        Ok(StopOutcome::Stopped) => {
+            springfield::style::print_success(&format!("stopped [{loop_id}]"));
+        }
+        Ok(StopOutcome::Killed) => {
+            springfield::style::print_warning(&format!(
+                "killed [{loop_id}] after {timeout}s without a graceful exit"
+            ));
+        }
+        Err(e) => {
+            springfield::style::print_error(&format!("stop: {e}"));
             std::process::exit(1);
         }
-    };
-
-    if meta.status != cursus::state::RunStatus::Running {
-        eprintln!("Run {run_id} is already {}, nothing to kill", meta.status);
-        std::process::exit(0);
     }
+    eprintln!("To resume: sgf resume {loop_id}");
+    std::process::exit(0);
+}
 
-    let killed = if let Some(pid) = cursus::state::read_pid(root, run_id) {
-        if cursus::state::is_pid_alive(pid) {
-            let ok = shutdown::kill_process_group(pid, std::time::Duration::from_secs(2));
-            if ok {
-                eprintln!("Killed process group (pid {pid})");
-            } else {
-                eprintln!("Process {pid} already exited");
-            }
-            true
-        } else {
-            eprintln!("Process {pid} already exited");
-            true
+fn run_rollback(root: &Path, loop_id: &str, reset: bool) -> ! {
+    let rollback = match springfield::rollback::run(root, loop_id, reset) {
+        Ok(rollback) => rollback,
+        Err(e) => {
+            springfield::style::print_error(&format!("rollback: {e}"));
+            std::process::exit(1);
         }
-    } else {
-        eprintln!("No PID file found, updating status only");
-        false
     };
-
-    let mut meta = if killed {
-        match cursus::state::read_metadata(root, run_id) {
-            Ok(Some(m)) => m,
-            _ => meta,
-        }
+    let commits = match rollback.commits {
+        1 => "1 commit".to_string(),
+        n => format!("{n} commits"),
+    };
+    if rollback.reset {
+        springfield::style::print_success_detail(
+            &format!("rolled back [{loop_id}]"),
+            &format!(
+                "{} reset past {commits} ({})",
+                rollback.branch, rollback.range
+            ),
+        );
     } else {
-        meta
+        springfield::style::print_success_detail(
+            &format!("rolled back [{loop_id}]"),
+            &format!("{commits} reverted on {}", rollback.branch),
+        );
+    }
+    if !rollback.reopened.is_empty() {
+        eprintln!("Reopened: {}", rollback.reopened.join(", "));
+    }
+    if let Some(e) = rollback.pensa_error {
+        springfield::style::print_warning(&format!("issues not reopened: {e}"));
+        std::process::exit(1);
+    }
+    if !rollback.reset {
+        eprintln!("To apply: git merge {}", rollback.branch);
+    }
+    std::process::exit(0);
+}
+
+fn assemble_prompt(
+    root: &Path,
+    stage: &str,
+    spec: Option<&str>,
+    iter: Option<&str>,
+) -> Result<String, String> {
+    let resolved = resolve_command(root, stage)?;
+    let iters = &resolved.definition.iters;
+    let iter_def = match iter {
+        Some(name) => iters.iter().find(|i| i.name == name).ok_or_else(|| {
+            let names: Vec<&str> = iters.iter().map(|i| i.name.as_str()).collect();
+            format!(
+                "{} has no iter {name} (iters: {})",
+                resolved.name,
+                names.join(", ")
+            )
+        })?,
+        None => iters
+            .first()
+            .ok_or_else(|| format!("{} has no iters", resolved.name))?,
     };
+    if let Some(stem) = spec
+        && !root.join(format!("specs/{stem}.md")).exists()
+    {
+        return Err(format!("spec not found: specs/{stem}.md"));
+    }
+    let path = springfield::prompt::resolve_template(root, &iter_def.prompt)
+        .ok_or_else(|| format!("prompt not found: {}", iter_def.prompt))?;
+    let template =
+        std::fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(springfield::prompt::render(
+        root,
+        &template,
+        &iter_def.vars,
+        spec,
+    ))
+}
 
-    meta.status = cursus::state::RunStatus::WaitingForInput;
-    meta.touch();
-    if let Err(e) = cursus::state::write_metadata(root, &meta) {
-        springfield::style::print_error(&format!("failed to update metadata: {e}"));
+fn run_prompt_lint(root: &Path) {
+    let report = springfield::prompt::lint(root);
+    for issue in &report.issues {
+        let line = format!("{}: {}", issue.template, issue.message);
+        if issue.error {
+            springfield::style::print_error(&line);
+        } else {
+            springfield::style::print_warning(&line);
+        }
+    }
+    if report.has_errors() {
         std::process::exit(1);
     }
-    cursus::state::remove_pid_file(root, run_id);
-
-    springfield::style::print_success(&format!("Marked {run_id} as waiting_for_input"));
-    eprintln!("To resume: sgf {} --resume {run_id}", meta.cursus);
-    std::process::exit(0);
+    springfield::style::print_success(&format!("{} templates ok", report.templates));
 }
 
 fn run_list(root: &Path) {
     let commands = cursus::list_all(root);
 
     let builtins = [
+        ("clean", "Delete old logs and stale run state"),
+        ("costs", "Total agent cost and time per stage or spec"),
+        ("backpressure", "Print or run the BACKPRESSURE.md commands"),
+        ("doctor", "Check the environment loops need"),
+        (
+            "history",
+            "List finished loops with outcome, time, and cost",
+        ),
         ("init", "Scaffold a new project"),
+        ("issues", "Triage pensa bugs or show their fix rate"),
         ("kill", "Kill a running cursus and mark it resumable"),
         ("list", "Show available commands"),
-        ("logs", "Tail a running loop's output"),
+        ("logs", "Print or follow a loop's output"),
+        ("next", "Claim the top ready issue and build it"),
+        ("prompt", "Assemble, lint, or trust prompt templates"),
+        ("ps", "List running loops"),
+        ("recover", "Clean up after crashed loops"),
+        ("report", "Summarize a finished loop"),
         ("resume", "List and resume a previous session"),
+        ("run", "Run a custom stage from .sgf/config.toml"),
+        ("stop", "Stop a running loop"),
+        ("upgrade", "Adopt newer prompt and backpressure templates"),
+        ("watch", "Dashboard of running loops"),
     ];
 
     let max_name = commands
@@ -693,6 +2221,37 @@ fn run_list(root: &Path) {
     }
 }
 
+/// `--project-dir` given among a loop command's own flags, where clap leaves
+/// it.
+fn trailing_project_dir(command: &Commands) -> Option<PathBuf> {
+    let args = match command {
+        Commands::Dynamic(args) | Commands::Run { args } | Commands::Next { args, .. } => args,
+        _ => return None,
+    };
+    args.iter()
+        .position(|a| a == "--project-dir")
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from)
+}
+
+/// Moves into the subproject sgf should run in, if any, so every later
+/// `current_dir()` is the project root.
+fn enter_project_dir(requested: Option<&Path>) {
+    let cwd = std::env::current_dir().expect("failed to get current directory");
+    let dir = match springfield::project::resolve(&cwd, requested) {
+        Ok(Some(dir)) => dir,
+        Ok(None) => return,
+        Err(e) => {
+            springfield::style::print_error(&e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = std::env::set_current_dir(&dir) {
+        springfield::style::print_error(&format!("project dir {}: {e}", dir.display()));
+        std::process::exit(1);
+    }
+}
+
 fn main() {
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
@@ -700,11 +2259,30 @@ fn main() {
         .init();
 
     let cli = Cli::parse();
+    let project_dir = trailing_project_dir(&cli.command).or(cli.project_dir.clone());
+    enter_project_dir(project_dir.as_deref());
 
     match cli.command {
-        Commands::Init { force, no_fe } => {
+        Commands::Init {
+            force,
+            no_fe,
+            profile,
+            dry_run,
+            list_profiles,
+        } => {
+            if list_profiles {
+                for profile in springfield::profile::Profile::ALL {
+                    println!("{:<8}{}", profile.name(), profile.description());
+                }
+                return;
+            }
             let root = std::env::current_dir().expect("failed to get current directory");
-            if let Err(e) = springfield::init::run(&root, force, no_fe) {
+            let result = if dry_run {
+                springfield::init::dry_run(&root, force, no_fe, profile)
+            } else {
+                springfield::init::run(&root, force, no_fe, profile)
+            };
+            if let Err(e) = result {
                 springfield::style::print_error(&format!("init: {e}"));
                 std::process::exit(1);
             }
@@ -721,13 +2299,168 @@ fn main() {
             let root = std::env::current_dir().expect("failed to get current directory");
             run_resume_command(&root, run_id.as_deref());
         }
-        Commands::Logs { loop_id } => {
+        Commands::Logs {
+            loop_id,
+            list,
+            info,
+            follow,
+            grep,
+            since,
+            errors_only,
+        } => {
             let root = std::env::current_dir().expect("failed to get current directory");
-            if let Err(e) = springfield::loop_mgmt::run_logs(&root, &loop_id) {
+            let result = match loop_id {
+                _ if list => {
+                    run_logs_list(&root);
+                    Ok(())
+                }
+                Some(id) if info => run_logs_info(&root, &id),
+                Some(id) => {
+                    let mut filter = springfield::logs::LogFilter::new(grep, since, errors_only);
+                    springfield::logs::run_logs(&root, &id, &mut filter, follow)
+                }
+                None => unreachable!("clap requires a loop ID without --list"),
+            };
+            if let Err(e) = result {
                 springfield::style::print_error(&format!("logs: {e}"));
                 std::process::exit(1);
             }
         }
+        Commands::Clean {
+            logs_older_than,
+            assembled,
+            run,
+        } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            run_clean(&root, logs_older_than, assembled, run);
+        }
+        Commands::Watch => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            if let Err(e) = springfield::watch::run(&root) {
+                springfield::style::print_error(&format!("watch: {e}"));
+                std::process::exit(1);
+            }
+        }
+        Commands::Recover { dry_run, strategy } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            run_recover(&root, dry_run, strategy);
+        }
+        Commands::Upgrade { dry_run } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            if let Err(e) = springfield::upgrade::run(&root, dry_run) {
+                springfield::style::print_error(&format!("upgrade: {e}"));
+                std::process::exit(1);
+            }
+        }
+        Commands::Doctor { fix } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            run_doctor(&root, fix);
+        }
+        Commands::Costs { since, by, json } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            run_costs(&root, since, by, json);
+        }
+        Commands::History {
+            stage,
+            spec,
+            since,
+            until,
+            json,
+        } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            let filter = springfield::history::HistoryFilter {
+                stage,
+                spec,
+                since,
+                until,
+            };
+            run_history(&root, &filter, json);
+        }
+        Commands::Report { loop_id, json } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            match springfield::report::build(&root, &loop_id) {
+                Ok(report) if json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).expect("report serializes")
+                ),
+                Ok(report) => print!("{}", report.to_markdown()),
+                Err(e) => {
+                    springfield::style::print_error(&format!("report: {e}"));
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Ps => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            run_ps(&root);
+        }
+        Commands::Rollback { loop_id, reset } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            run_rollback(&root, &loop_id, reset);
+        }
+        Commands::Stop { loop_id, timeout } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            run_stop(&root, &loop_id, timeout);
+        }
+        Commands::Backpressure { action } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            match action {
+                BackpressureAction::Show => run_backpressure_show(&root),
+                BackpressureAction::Run { kinds } => run_backpressure(&root, &kinds),
+            }
+        }
+        Commands::Prompt { action } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            match action {
+                PromptAction::Assemble { stage, spec, iter } => {
+                    match assemble_prompt(&root, &stage, spec.as_deref(), iter.as_deref()) {
+                        Ok(prompt) => print!("{prompt}"),
+                        Err(e) => {
+                            springfield::style::print_error(&format!("prompt assemble: {e}"));
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                PromptAction::Lint => run_prompt_lint(&root),
+                PromptAction::Trust => match springfield::guard::trust(&root) {
+                    Ok(()) => springfield::style::print_success("prompts trusted"),
+                    Err(e) => {
+                        springfield::style::print_error(&format!("prompt trust: {e}"));
+                        std::process::exit(1);
+                    }
+                },
+            }
+        }
+        Commands::Issues { action } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            let result = match action {
+                IssuesAction::Triage => springfield::issues::triage(&root),
+                IssuesAction::Stats { weeks, json } => run_issue_stats(&root, weeks, json),
+            };
+            if let Err(e) = result {
+                springfield::style::print_error(&format!("issues: {e}"));
+                std::process::exit(1);
+            }
+        }
+        Commands::Next { spec, args } => {
+            let root = std::env::current_dir().expect("failed to get current directory");
+            run_next(&root, spec, args);
+        }
+        Commands::Run { args } => {
+            let parsed = match parse_dynamic_args(args) {
+                Ok(a) => a,
+                Err(e) => {
+                    springfield::style::print_error(&e);
+                    std::process::exit(1);
+                }
+            };
+            let root = std::env::current_dir().expect("failed to get current directory");
+            if let Err(e) = check_custom_stage(&root, &parsed.command) {
+                springfield::style::print_error(&e);
+                std::process::exit(1);
+            }
+            run_dynamic(parsed);
+        }
         Commands::Dynamic(args) => {
             let parsed = match parse_dynamic_args(args) {
                 Ok(a) => a,
@@ -832,6 +2565,13 @@ mod tests {
         assert!(parsed.skip_preflight);
     }
 
+    #[test]
+    fn parse_skip_gates() {
+        let parsed = parse_dynamic_args(vec![os("test"), os("auth"), os("--skip-gates")]).unwrap();
+        assert!(parsed.skip_gates);
+        assert_eq!(parsed.spec.as_deref(), Some("auth"));
+    }
+
     #[test]
     fn parse_all_flags_with_spec() {
         let args = vec![
@@ -906,10 +2646,113 @@ mod tests {
     }
 
     #[test]
-    fn parse_duplicate_spec_error() {
-        let args = vec![os("build"), os("auth"), os("extra")];
-        let err = parse_dynamic_args(args).unwrap_err();
-        assert!(err.contains("unexpected argument"));
+    fn parse_multiple_specs() {
+        let args = vec![os("build"), os("auth"), os("billing"), os("-j"), os("2")];
+        let parsed = parse_dynamic_args(args).unwrap();
+        assert_eq!(parsed.spec.as_deref(), Some("auth"));
+        assert_eq!(parsed.extra_specs, vec!["billing".to_string()]);
+        assert_eq!(parsed.jobs, Some(2));
+    }
+
+    #[test]
+    fn parse_all_ready() {
+        let parsed = parse_dynamic_args(vec![os("build"), os("--all-ready")]).unwrap();
+        assert!(parsed.all_ready);
+        assert!(parsed.spec.is_none());
+
+        let err = parse_dynamic_args(vec![os("build"), os("auth"), os("--all-ready")]).unwrap_err();
+        assert!(err.contains("--all-ready and spec arguments are mutually exclusive"));
+    }
+
+    #[test]
+    fn parse_parallel_conflicts() {
+        let err = parse_dynamic_args(vec![os("build"), os("a"), os("b"), os("-i")]).unwrap_err();
+        assert!(err.contains("-i/--interactive cannot be used with multiple specs"));
+        let err = parse_dynamic_args(vec![os("build"), os("--all-ready"), os("-d")]).unwrap_err();
+        assert!(err.contains("-d/--detach cannot be used with multiple specs"));
+    }
+
+    #[test]
+    fn parse_jobs_invalid() {
+        let err = parse_dynamic_args(vec![os("build"), os("-j"), os("0")]).unwrap_err();
+        assert!(err.contains("invalid job count: 0"));
+        let err = parse_dynamic_args(vec![os("build"), os("--jobs")]).unwrap_err();
+        assert!(err.contains("--jobs requires a value"));
+    }
+
+    #[test]
+    fn parse_agent_tuning_flags() {
+        let args = [
+            "build",
+            "--model",
+            "opus",
+            "--max-iterations",
+            "12",
+            "--iteration-timeout",
+            "20m",
+            "--max-cost",
+            "$5",
+            "--complete-when",
+            "cargo test -q",
+            "--agent-arg",
+            "--fallback-model",
+            "--agent-arg",
+            "sonnet",
+        ];
+        let parsed = parse_dynamic_args(args.iter().map(|a| os(a)).collect()).unwrap();
+        assert_eq!(parsed.iterations, Some(12));
+        let tuning = &parsed.tuning;
+        assert_eq!(tuning.model.as_deref(), Some("opus"));
+        assert_eq!(
+            tuning.iteration_timeout,
+            Some(std::time::Duration::from_secs(1200))
+        );
+        assert_eq!(tuning.max_cost_usd, Some(5.0));
+        assert_eq!(tuning.complete_when.as_deref(), Some("cargo test -q"));
+        assert_eq!(tuning.extra_args, ["--fallback-model", "sonnet"]);
+
+        let reparsed = parse_dynamic_args(
+            std::iter::once(os("build"))
+                .chain(tuning.cli_args().iter().map(|a| os(a)))
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(
+            &reparsed.tuning, tuning,
+            "child processes get the same tuning"
+        );
+
+        let parsed = parse_dynamic_args(vec![
+            os("build"),
+            os("--env"),
+            os("API_KEY=sk-123=="),
+            os("--env"),
+            os("CARGO_MANIFEST_DIR"),
+        ])
+        .unwrap();
+        assert_eq!(
+            parsed.tuning.env,
+            [
+                ("API_KEY".to_string(), "sk-123==".to_string()),
+                (
+                    "CARGO_MANIFEST_DIR".to_string(),
+                    env!("CARGO_MANIFEST_DIR").to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            parsed.tuning.cli_args(),
+            ["--env", "API_KEY", "--env", "CARGO_MANIFEST_DIR"],
+            "values stay out of child command lines"
+        );
+        let err = parse_dynamic_args(vec![os("build"), os("--env"), os("1X=y")]).unwrap_err();
+        assert!(err.contains("invalid --env name"), "{err}");
+
+        let err = parse_dynamic_args(vec![os("build"), os("--max-cost"), os("-1")]).unwrap_err();
+        assert!(err.contains("invalid cost limit"), "{err}");
+        let err = parse_dynamic_args(vec![os("build"), os("--iteration-timeout"), os("soon")])
+            .unwrap_err();
+        assert!(err.contains("invalid duration"), "{err}");
     }
 
     #[test]
@@ -987,6 +2830,117 @@ mod tests {
         assert_eq!(parsed.iterations, Some(10));
     }
 
+    #[test]
+    fn parse_output_json() {
+        let parsed = parse_dynamic_args(vec![os("build"), os("--output"), os("json")]).unwrap();
+        assert!(parsed.json_events);
+        assert!(parsed.output_format.is_none());
+        assert!(!parse_dynamic_args(vec![os("build")]).unwrap().json_events);
+        let err = parse_dynamic_args(vec![os("build"), os("--output"), os("xml")]).unwrap_err();
+        assert_eq!(err, "unsupported output: xml (expected json or text)");
+        let err = parse_dynamic_args(vec![os("build"), os("-d"), os("--output"), os("json")])
+            .unwrap_err();
+        assert!(err.contains("mutually exclusive"), "{err}");
+    }
+
+    #[test]
+    fn parse_detach_flag() {
+        let parsed = parse_dynamic_args(vec![os("build"), os("auth"), os("-d")]).unwrap();
+        assert!(parsed.detach);
+        let parsed = parse_dynamic_args(vec![os("build"), os("--detach"), os("-a")]).unwrap();
+        assert!(parsed.detach && parsed.afk);
+        assert!(!parse_dynamic_args(vec![os("build")]).unwrap().detach);
+    }
+
+    #[test]
+    fn profile_fills_in_flags_not_given() {
+        let profile = springfield::config::SgfConfig::parse(
+            "[profiles.overnight]\nafk = true\niterations = 50\nmax_cost = 25.0\nmodel = \"opus\"\nauto_push = false\n",
+        )
+        .unwrap()
+        .profiles
+        .remove("overnight")
+        .unwrap();
+
+        let mut parsed = parse_dynamic_args(vec![
+            os("build"),
+            os("auth"),
+            os("--profile"),
+            os("overnight"),
+            os("-n"),
+            os("5"),
+        ])
+        .unwrap();
+        assert_eq!(parsed.profile.as_deref(), Some("overnight"));
+        merge_profile(&mut parsed, &profile);
+        assert!(parsed.afk && parsed.no_push);
+        assert_eq!(parsed.iterations, Some(5), "-n wins over the profile");
+        assert_eq!(parsed.tuning.model.as_deref(), Some("opus"));
+        assert_eq!(parsed.tuning.max_cost_usd, Some(25.0));
+
+        let mut interactive = parse_dynamic_args(vec![os("build"), os("-i")]).unwrap();
+        merge_profile(&mut interactive, &profile);
+        assert!(!interactive.afk, "-i keeps the loop interactive");
+        assert_eq!(interactive.iterations, Some(50));
+
+        let err = parse_dynamic_args(vec![os("build"), os("--profile")]).unwrap_err();
+        assert!(err.contains("--profile requires a value"));
+    }
+
+    #[test]
+    fn parse_banner_flags() {
+        let parsed = parse_dynamic_args(vec![
+            os("build"),
+            os("--no-art"),
+            os("--banner-file"),
+            os("art.txt"),
+        ])
+        .unwrap();
+        assert!(parsed.no_art);
+        assert_eq!(parsed.banner_file, Some(PathBuf::from("art.txt")));
+        let err = parse_dynamic_args(vec![os("build"), os("--banner-file")]).unwrap_err();
+        assert!(err.contains("--banner-file requires a value"));
+    }
+
+    #[test]
+    fn parse_force_flag() {
+        let parsed = parse_dynamic_args(vec![os("build"), os("auth"), os("--force")]).unwrap();
+        assert!(parsed.force);
+        assert!(!parse_dynamic_args(vec![os("build")]).unwrap().force);
+    }
+
+    #[test]
+    fn parse_url_prompt_with_refresh() {
+        let parsed = parse_dynamic_args(vec![
+            os("https://example.com/prompts/build.md"),
+            os("-a"),
+            os("-n"),
+            os("10"),
+            os("--refresh"),
+        ])
+        .unwrap();
+        assert_eq!(parsed.command, "https://example.com/prompts/build.md");
+        assert!(parsed.refresh && parsed.afk);
+        assert_eq!(parsed.iterations, Some(10));
+    }
+
+    #[test]
+    fn parse_detach_conflicts() {
+        let err = parse_dynamic_args(vec![os("build"), os("-d"), os("-i")]).unwrap_err();
+        assert!(err.contains("-d/--detach and -i/--interactive are mutually exclusive"));
+        let err =
+            parse_dynamic_args(vec![os("build"), os("-d"), os("--resume"), os("r-1")]).unwrap_err();
+        assert!(err.contains("-d/--detach and --resume are mutually exclusive"));
+        let err = parse_dynamic_args(vec![
+            os("build"),
+            os("-d"),
+            os("--output-format"),
+            os("json"),
+        ])
+        .unwrap_err();
+        assert!(err.contains("-d/--detach and --output-format are mutually exclusive"));
+    }
+
     const SIMPLE_CURSUS: &str = r#"
 description = "Build loop"
 alias = "b"
@@ -1226,6 +3180,93 @@ prompt = "test.md"
         assert_eq!(resolved.name, "build");
     }
 
+    #[test]
+    fn custom_stage_resolves_and_rejects_shadowing() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".sgf/cursus")).unwrap();
+        fs::write(tmp.path().join(".sgf/cursus/build.toml"), SIMPLE_CURSUS).unwrap();
+        fs::write(
+            tmp.path().join(".sgf/config.toml"),
+            "[stages.docs]\nprompt = \"docs.md\"\n\n[stages.b]\nprompt = \"b.md\"\n",
+        )
+        .unwrap();
+
+        check_custom_stage(tmp.path(), "docs").unwrap();
+        assert_eq!(resolve_command(tmp.path(), "docs").unwrap().name, "docs");
+
+        let err = check_custom_stage(tmp.path(), "b").unwrap_err();
+        assert!(err.contains("shadowed by cursus build"), "{err}");
+
+        let err = check_custom_stage(tmp.path(), "bench").unwrap_err();
+        assert!(err.contains("unknown stage: bench"), "{err}");
+    }
+
+    #[test]
+    fn assemble_prompt_renders_selected_iter() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join(".sgf/cursus")).unwrap();
+        fs::create_dir_all(root.join(".sgf/prompts")).unwrap();
+        fs::create_dir_all(root.join("specs")).unwrap();
+        fs::write(
+            root.join(".sgf/cursus/spec.toml"),
+            r#"
+description = "Spec"
+
+[[iter]]
+name = "draft"
+prompt = "draft.md"
+
+[[iter]]
+name = "review"
+prompt = "review.md"
+vars = { reviewer = "Lisa" }
+"#,
+        )
+        .unwrap();
+        fs::write(root.join(".sgf/prompts/draft.md"), "Draft {{spec}}").unwrap();
+        fs::write(
+            root.join(".sgf/prompts/review.md"),
+            "{{reviewer}} reviews:\n{{spec_content}}",
+        )
+        .unwrap();
+        fs::write(root.join("specs/auth.md"), "# Auth\n").unwrap();
+
+        assert_eq!(
+            assemble_prompt(root, "spec", Some("auth"), None).unwrap(),
+            "Draft auth"
+        );
+        assert_eq!(
+            assemble_prompt(root, "spec", Some("auth"), Some("review")).unwrap(),
+            "Lisa reviews:\n# Auth"
+        );
+        let err = assemble_prompt(root, "spec", None, Some("nope")).unwrap_err();
+        assert!(err.contains("iters: draft, review"), "{err}");
+        let err = assemble_prompt(root, "spec", Some("ghost"), None).unwrap_err();
+        assert!(err.contains("spec not found"), "{err}");
+    }
+
+    #[test]
+    fn validate_launch_checks_prompts_and_specs() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join(".sgf/cursus")).unwrap();
+        fs::create_dir_all(root.join("specs")).unwrap();
+        fs::write(root.join(".sgf/cursus/build.toml"), SIMPLE_CURSUS).unwrap();
+        fs::write(root.join("specs/auth.md"), "# Auth").unwrap();
+        let resolved = resolve_command(root, "build").unwrap();
+
+        let err = validate_launch(root, &resolved, &[]).unwrap_err();
+        assert!(err.contains("build: prompt not found: build.md"), "{err}");
+
+        fs::create_dir_all(root.join(".sgf/prompts")).unwrap();
+        fs::write(root.join(".sgf/prompts/build.md"), "Build").unwrap();
+        validate_launch(root, &resolved, &["auth".to_string()]).unwrap();
+
+        let err = validate_launch(root, &resolved, &["uath".to_string()]).unwrap_err();
+        assert!(err.contains("did you mean: auth?"), "{err}");
+    }
+
     #[test]
     fn collect_resumable_empty_when_no_sessions() {
         let tmp = TempDir::new().unwrap();
@@ -1577,7 +3618,7 @@ prompt = "test.md"
                 Ok(())
             });
         }
-        let child = cmd.spawn().unwrap();
+        let mut child = cmd.spawn().unwrap();
         let pid = child.id();
         write_pid(root, run_id, pid);
 
@@ -1585,6 +3626,7 @@ prompt = "test.md"
 
         let killed = shutdown::kill_process_group(pid, std::time::Duration::from_secs(2));
         assert!(killed);
+        let _ = child.wait();
 
         let mut meta = cursus::state::read_metadata(root, run_id).unwrap().unwrap();
         meta.status = cursus::state::RunStatus::WaitingForInput;
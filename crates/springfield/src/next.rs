@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use serde::Deserialize;
+
+/// Placeholders a prompt can use to place the task itself. A prompt using
+/// none of them gets [`Task::prompt_section`] appended instead.
+pub const TASK_VARS: &[&str] = &["task_id", "task_title", "task_description"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Task {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub spec: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Settlement {
+    Closed,
+    Released,
+}
+
+impl Task {
+    pub fn vars(&self) -> HashMap<String, String> {
+        HashMap::from([
+            ("task_id".to_string(), self.id.clone()),
+            ("task_title".to_string(), self.title.clone()),
+            (
+                "task_description".to_string(),
+                self.description.clone().unwrap_or_default(),
+            ),
+        ])
+    }
+
+    pub fn prompt_section(&self) -> String {
+        let mut section = format!(
+            "\n\n## Assigned task\n\nWork on pensa issue {}: {}\n",
+            self.id, self.title
+        );
+        if let Some(spec) = &self.spec {
+            section.push_str(&format!("Spec: {spec}\n"));
+        }
+        if let Some(description) = self.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            section.push_str(&format!("\n{}\n", description.trim_end()));
+        }
+        section
+    }
+}
+
+pub(crate) fn pn(root: &Path, args: &[&str]) -> io::Result<Vec<u8>> {
+    run_pn(Path::new("pn"), root, args)
+}
+
+fn run_pn(bin: &Path, root: &Path, args: &[&str]) -> io::Result<Vec<u8>> {
+    let output = Command::new(bin)
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| io::Error::other(format!("failed to run pn: {e}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "pn {} failed: {}",
+            args.join(" "),
+            pn_error(&output)
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// With `--json`, pn prints its error as `{"error": ...}` on stdout rather
+/// than on stderr.
+fn pn_error(output: &Output) -> String {
+    serde_json::from_slice::<serde_json::Value>(&output.stdout)
+        .ok()
+        .and_then(|v| v["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| String::from_utf8_lossy(&output.stderr).trim().to_string())
+}
+
+pub fn pick(root: &Path, spec: Option<&str>) -> io::Result<Option<Task>> {
+    let mut args = vec!["ready", "--json", "-n", "1"];
+    if let Some(spec) = spec {
+        args.extend(["--spec", spec]);
+    }
+    let tasks: Vec<Task> = serde_json::from_slice(&pn(root, &args)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(tasks.into_iter().next())
+}
+
+pub fn claim(root: &Path, task: &Task) -> io::Result<()> {
+    pn(root, &["update", &task.id, "--claim", "--json"]).map(|_| ())
+}
+
+fn is_closed(root: &Path, id: &str) -> bool {
+    pn(root, &["show", id, "--json"])
+        .ok()
+        .and_then(|out| serde_json::from_slice::<serde_json::Value>(&out).ok())
+        .is_some_and(|issue| issue["status"] == "closed")
+}
+
+/// Closes the task after a loop that exited 0 and releases it otherwise.
+/// A task the agent already closed itself counts as closed either way.
+pub fn settle(root: &Path, task: &Task, exit_code: i32) -> io::Result<Settlement> {
+    let result = if exit_code == 0 {
+        pn(
+            root,
+            &[
+                "close",
+                &task.id,
+                "--reason",
+                "completed by sgf next",
+                "--json",
+            ],
+        )
+        .map(|_| Settlement::Closed)
+    } else {
+        pn(root, &["release", &task.id, "--json"]).map(|_| Settlement::Released)
+    };
+    match result {
+        Err(_) if is_closed(root, &task.id) => Ok(Settlement::Closed),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_parses_from_pn_json_and_renders_section() {
+        let json = r#"[{"id":"pn-1a2b","title":"Add login","description":"Use OAuth.\n",
+            "issue_type":"task","status":"open","priority":"p1","spec":"auth",
+            "created_at":"2026-10-16T10:00:00Z","updated_at":"2026-10-16T10:00:00Z"}]"#;
+        let tasks: Vec<Task> = serde_json::from_str(json).unwrap();
+        let task = &tasks[0];
+        assert_eq!(task.spec.as_deref(), Some("auth"));
+        assert_eq!(task.vars()["task_title"], "Add login");
+
+        let section = task.prompt_section();
+        assert!(
+            section.contains("Work on pensa issue pn-1a2b: Add login\nSpec: auth\n\nUse OAuth.\n"),
+            "{section}"
+        );
+
+        let bare = Task {
+            description: None,
+            spec: None,
+            ..task.clone()
+        };
+        assert!(!bare.prompt_section().contains("Spec:"));
+    }
+
+    #[test]
+    fn pn_failures_report_the_json_error() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let stub = dir.path().join("pn");
+        std::fs::write(
+            &stub,
+            "#!/bin/sh\necho '{\"error\":\"issue not found: pn-0000\",\"code\":\"not_found\"}'\n\
+             echo 'ignored' >&2\nexit 3\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let err = run_pn(&stub, dir.path(), &["show", "pn-0000", "--json"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "pn show pn-0000 --json failed: issue not found: pn-0000"
+        );
+
+        std::fs::write(&stub, "#!/bin/sh\necho 'database is locked' >&2\nexit 1\n").unwrap();
+        let err = run_pn(&stub, dir.path(), &["ready"]).unwrap_err();
+        assert_eq!(err.to_string(), "pn ready failed: database is locked");
+    }
+}
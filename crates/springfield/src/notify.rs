@@ -0,0 +1,236 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{NotificationConfig, SgfConfig};
+use crate::loop_mgmt::{self, LoopRecord};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoopEvent {
+    Start,
+    Complete,
+    Exhausted,
+    Interrupted,
+    Error,
+}
+
+impl LoopEvent {
+    pub fn for_outcome(record: &LoopRecord) -> Self {
+        match record.outcome() {
+            "completed" => Self::Complete,
+            "exhausted" => Self::Exhausted,
+            "interrupted" => Self::Interrupted,
+            _ => Self::Error,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Complete => "complete",
+            Self::Exhausted => "exhausted",
+            Self::Interrupted => "interrupted",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Payload {
+    pub event: LoopEvent,
+    pub loop_id: String,
+    pub stage: String,
+    pub spec: Option<String>,
+    pub exit_code: Option<i32>,
+    pub iterations: u32,
+    pub message: String,
+}
+
+impl Payload {
+    pub fn new(event: LoopEvent, record: &LoopRecord, last_line: Option<&str>) -> Self {
+        let id = &record.loop_id;
+        let n = record.iterations_completed;
+        let iters = if n == 1 { "iteration" } else { "iterations" };
+        let message = match event {
+            LoopEvent::Start => format!("{id} started"),
+            LoopEvent::Complete => format!("{id} completed after {n} {iters}"),
+            LoopEvent::Exhausted => format!("{id} used all {n} {iters} without finishing"),
+            LoopEvent::Interrupted => format!("{id} was interrupted after {n} {iters}"),
+            LoopEvent::Error => {
+                let code = record
+                    .exit_code
+                    .map_or_else(|| "unknown".to_string(), |c| c.to_string());
+                match last_line {
+                    Some(line) => format!("{id} failed with exit code {code}: {line}"),
+                    None => format!("{id} failed with exit code {code}"),
+                }
+            }
+        };
+        Self {
+            event,
+            loop_id: id.clone(),
+            stage: record.stage.clone(),
+            spec: record.spec.clone(),
+            exit_code: record.exit_code,
+            iterations: n,
+            message,
+        }
+    }
+}
+
+pub fn announce(root: &Path, event: LoopEvent, record: &LoopRecord) {
+    let config = match crate::config::active_profile().and_then(|p| p.notifications.clone()) {
+        Some(config) => config,
+        None => SgfConfig::load(root).unwrap_or_default().notifications,
+    };
+    let has_channel = config.desktop() || config.webhook.is_some() || config.command.is_some();
+    if !has_channel || !config.announces(event) {
+        return;
+    }
+    let last_line = if event == LoopEvent::Error {
+        loop_mgmt::last_log_line(root, &record.loop_id)
+    } else {
+        None
+    };
+    let payload = Payload::new(event, record, last_line.as_deref());
+    for mut child in dispatch(&config, &payload) {
+        thread::spawn(move || child.wait());
+    }
+}
+
+fn dispatch(config: &NotificationConfig, payload: &Payload) -> Vec<Child> {
+    let json = serde_json::to_string(payload).expect("payload serializes");
+    let mut commands = Vec::new();
+    if config.desktop() {
+        commands.push(("desktop", desktop_command(payload)));
+    }
+    if let Some(url) = &config.webhook {
+        let mut curl = Command::new("curl");
+        curl.args(["-fsS", "-m", "10", "-X", "POST"])
+            .args(["-H", "Content-Type: application/json"])
+            .args(["--data-binary", "@-", url]);
+        commands.push(("webhook", curl));
+    }
+    if let Some(cmd) = &config.command {
+        let mut sh = Command::new("sh");
+        sh.args(["-c", cmd])
+            .env("SGF_EVENT", payload.event.name())
+            .env("SGF_LOOP_ID", &payload.loop_id)
+            .env("SGF_STAGE", &payload.stage)
+            .env("SGF_SPEC", payload.spec.as_deref().unwrap_or_default())
+            .env("SGF_MESSAGE", &payload.message);
+        commands.push(("command", sh));
+    }
+
+    let mut children = Vec::new();
+    for (channel, mut cmd) in commands {
+        let spawned = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(mut child) => {
+                if let Some(mut stdin) = child.stdin.take() {
+                    // The desktop notifier and some hooks never read stdin.
+                    let _ = stdin.write_all(json.as_bytes());
+                }
+                children.push(child);
+            }
+            Err(e) => tracing::warn!(channel, error = %e, "notification failed to start"),
+        }
+    }
+    children
+}
+
+fn desktop_command(payload: &Payload) -> Command {
+    let title = format!("sgf: {}", payload.event.name());
+    if cfg!(target_os = "macos") {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut cmd = Command::new("osascript");
+        cmd.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            quote(&payload.message),
+            quote(&title)
+        ));
+        cmd
+    } else {
+        let mut cmd = Command::new("notify-send");
+        cmd.args([&title, &payload.message]);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn record(exit_code: Option<i32>) -> LoopRecord {
+        LoopRecord {
+            loop_id: "build-auth-1".to_string(),
+            stage: "build".to_string(),
+            spec: Some("auth".to_string()),
+            started_at: "2026-10-16T10:00:00Z".to_string(),
+            attempt_started_at: None,
+            ended_at: exit_code.map(|_| "2026-10-16T11:00:00Z".to_string()),
+            exit_code,
+            iterations_completed: 3,
+            head_before: None,
+            head_after: None,
+            branch: None,
+            base_branch: None,
+            attempt: 1,
+            incidents: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn payload_message_describes_outcome() {
+        let cases = [
+            (Some(0), LoopEvent::Complete, "completed after 3 iterations"),
+            (Some(2), LoopEvent::Exhausted, "used all 3 iterations"),
+            (Some(130), LoopEvent::Interrupted, "interrupted after 3"),
+            (Some(1), LoopEvent::Error, "failed with exit code 1: boom"),
+        ];
+        for (code, event, text) in cases {
+            let rec = record(code);
+            assert_eq!(LoopEvent::for_outcome(&rec), event);
+            let payload = Payload::new(event, &rec, Some("boom"));
+            assert!(payload.message.contains(text), "{}", payload.message);
+        }
+        let start = Payload::new(LoopEvent::Start, &record(None), None);
+        assert_eq!(start.message, "build-auth-1 started");
+    }
+
+    #[test]
+    fn command_receives_payload_and_env() {
+        let tmp = TempDir::new().unwrap();
+        let out = tmp.path().join("payload.json");
+        let env = tmp.path().join("event");
+        let config = NotificationConfig {
+            command: Some(format!(
+                "cat > '{}'; printf %s \"$SGF_EVENT $SGF_SPEC\" > '{}'",
+                out.display(),
+                env.display()
+            )),
+            ..Default::default()
+        };
+        let payload = Payload::new(LoopEvent::Error, &record(Some(1)), None);
+        for mut child in dispatch(&config, &payload) {
+            child.wait().unwrap();
+        }
+
+        let sent: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(sent["event"], "error");
+        assert_eq!(sent["loop_id"], "build-auth-1");
+        assert_eq!(sent["exit_code"], 1);
+        assert_eq!(fs::read_to_string(&env).unwrap(), "error auth");
+    }
+}
@@ -20,6 +20,8 @@ fn exit_code_to_status(code: i32) -> &'static str {
 fn update_metadata_on_exit(root: &Path, loop_id: &str, exit_code: i32) {
     match loop_mgmt::read_session_metadata(root, loop_id) {
         Ok(Some(mut meta)) => {
+            let completed = meta.iterations.len() as u32;
+            loop_mgmt::finish_loop_record(root, root, loop_id, Some(exit_code), completed);
             meta.status = exit_code_to_status(exit_code).to_string();
             meta.updated_at = Utc::now().to_rfc3339();
             if let Err(e) = loop_mgmt::write_session_metadata(root, &meta) {
@@ -78,8 +80,12 @@ fn run_resume_session(root: &Path, meta: &SessionMetadata, session_id: &str) ->
     })?;
 
     let log_path = loop_mgmt::create_log_file(root, loop_id).ok();
+    loop_mgmt::begin_loop_record(root, root, loop_id, &meta.stage, meta.spec.as_deref());
 
-    let mut command = Command::new("cl");
+    let agent = crate::config::SgfConfig::load(root)
+        .unwrap_or_default()
+        .agent_command();
+    let mut command = Command::new(agent);
     command.args([
         "--resume",
         session_id,
@@ -126,7 +132,10 @@ fn restart_with_prompt(
 ) -> io::Result<i32> {
     style::print_action("restarting with original prompt...");
 
-    let mut command = Command::new("cl");
+    let agent = crate::config::SgfConfig::load(root)
+        .unwrap_or_default()
+        .agent_command();
+    let mut command = Command::new(agent);
     command.args([
         "--verbose",
         "--dangerously-skip-permissions",
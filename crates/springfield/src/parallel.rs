@@ -0,0 +1,351 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use chrono::Utc;
+use shutdown::ProcessSemaphore;
+
+use crate::iter_runner::AgentTuning;
+use crate::loop_mgmt::{self, SessionMetadata};
+use crate::style;
+
+pub const DEFAULT_JOBS: usize = 4;
+
+pub struct ParallelConfig {
+    pub command: String,
+    pub specs: Vec<String>,
+    pub jobs: usize,
+    pub iterations: Option<u32>,
+    pub no_push: bool,
+    pub tuning: AgentTuning,
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpecResult {
+    pub spec: String,
+    pub loop_id: String,
+    pub branch: String,
+    pub worktree: PathBuf,
+    pub status: String,
+    pub exit_code: Option<i32>,
+}
+
+impl SpecResult {
+    pub fn succeeded(&self) -> bool {
+        self.status == "completed"
+    }
+}
+
+pub fn ready_specs(root: &Path) -> io::Result<Vec<String>> {
+    let output = crate::next::pn(root, &["ready", "--json"])?;
+    let issues: Vec<serde_json::Value> = serde_json::from_slice(&output)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut specs: Vec<String> = issues
+        .iter()
+        .filter_map(|i| i["spec"].as_str())
+        .filter(|s| root.join(format!("specs/{s}.md")).exists())
+        .map(str::to_string)
+        .collect();
+    specs.sort();
+    specs.dedup();
+    Ok(specs)
+}
+
+pub fn branch_name(command: &str, spec: &str) -> String {
+    format!("sgf/{command}-{spec}")
+}
+
+pub fn worktree_path(root: &Path, spec: &str) -> PathBuf {
+    root.join(".sgf/worktrees").join(spec)
+}
+
+/// Creates `.sgf/worktrees/<spec>` on branch `sgf/<command>-<spec>`, branching
+/// from HEAD the first time and reusing the branch (and any existing
+/// worktree) on later runs.
+pub fn prepare_worktree(root: &Path, command: &str, spec: &str) -> io::Result<(PathBuf, String)> {
+    let path = worktree_path(root, spec);
+    let branch = branch_name(command, spec);
+    if path.join(".git").exists() {
+        return Ok((path, branch));
+    }
+    fs::create_dir_all(path.parent().unwrap())?;
+
+    let path_str = path.to_string_lossy().to_string();
+    let branch_exists = git(root, &["rev-parse", "--verify", "--quiet", &branch]).is_ok();
+    if branch_exists {
+        git(root, &["worktree", "add", &path_str, &branch])?;
+    } else {
+        git(root, &["worktree", "add", "-b", &branch, &path_str, "HEAD"])?;
+    }
+    Ok((path, branch))
+}
+
+fn git(root: &Path, args: &[&str]) -> io::Result<()> {
+    let output = Command::new("git").args(args).current_dir(root).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn status_for_exit(code: Option<i32>) -> &'static str {
+    match code {
+        Some(0) => "completed",
+        Some(2) => "stalled",
+        Some(130) => "interrupted",
+        _ => "failed",
+    }
+}
+
+/// Launches every spec's loop, at most `jobs` at a time, and blocks until all
+/// have finished. Each loop is recorded under `.sgf/run/` in `root` with the
+/// child's PID, so `sgf ps`, `sgf logs`, and `sgf stop` work from the main
+/// checkout; the child's output goes to `.sgf/logs/<loop_id>.log`.
+pub fn run_parallel(root: &Path, config: &ParallelConfig) -> Vec<SpecResult> {
+    let permits = ProcessSemaphore::new(config.jobs.max(1));
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = config
+            .specs
+            .iter()
+            .map(|spec| {
+                let permits = &permits;
+                scope.spawn(move || {
+                    let _permit = permits.acquire();
+                    run_one(root, config, spec)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("parallel loop thread panicked"))
+            .collect()
+    })
+}
+
+fn run_one(root: &Path, config: &ParallelConfig, spec: &str) -> SpecResult {
+    let loop_id = loop_mgmt::generate_loop_id(&config.command, Some(spec));
+    let mut result = SpecResult {
+        spec: spec.to_string(),
+        loop_id: loop_id.clone(),
+        branch: branch_name(&config.command, spec),
+        worktree: worktree_path(root, spec),
+        status: "failed".to_string(),
+        exit_code: None,
+    };
+
+    let (worktree, branch) = match prepare_worktree(root, &config.command, spec) {
+        Ok(w) => w,
+        Err(e) => {
+            style::print_error(&format!("{spec}: {e}"));
+            return result;
+        }
+    };
+    // In a monorepo the loop runs in the worktree's copy of the subproject.
+    result.worktree = worktree.join(crate::project::repo_prefix(root));
+    result.branch = branch;
+
+    loop_mgmt::begin_loop_record(
+        root,
+        &result.worktree,
+        &loop_id,
+        &config.command,
+        Some(spec),
+    );
+    match spawn_and_wait(root, config, spec, &loop_id, &result.worktree) {
+        Ok(code) => {
+            result.exit_code = code;
+            result.status = status_for_exit(code).to_string();
+        }
+        Err(e) => style::print_error(&format!("{spec}: {e}")),
+    }
+    loop_mgmt::finish_loop_record(
+        root,
+        &result.worktree,
+        &loop_id,
+        result.exit_code,
+        worktree_iterations(&result.worktree),
+    );
+
+    if let Ok(Some(mut meta)) = loop_mgmt::read_session_metadata(root, &loop_id) {
+        meta.status = result.status.clone();
+        meta.updated_at = Utc::now().to_rfc3339();
+        if let Err(e) = loop_mgmt::write_session_metadata(root, &meta) {
+            tracing::warn!(error = %e, "failed to update parallel loop metadata");
+        }
+    }
+    loop_mgmt::remove_pid_file(root, &loop_id);
+    result
+}
+
+fn worktree_iterations(worktree: &Path) -> u32 {
+    loop_mgmt::list_loop_records(worktree)
+        .first()
+        .map_or(0, |r| r.iterations_completed)
+}
+
+fn spawn_and_wait(
+    root: &Path,
+    config: &ParallelConfig,
+    spec: &str,
+    loop_id: &str,
+    worktree: &Path,
+) -> io::Result<Option<i32>> {
+    let log = loop_mgmt::create_log_file(root, loop_id)?;
+    let out = fs::File::create(&log)?;
+
+    let mut args = vec![
+        config.command.clone(),
+        spec.to_string(),
+        "-a".to_string(),
+        "--skip-preflight".to_string(),
+        "--skip-gates".to_string(),
+    ];
+    if let Some(n) = config.iterations {
+        args.extend(["-n".to_string(), n.to_string()]);
+    }
+    if config.no_push {
+        args.push("--no-push".to_string());
+    }
+    args.extend(config.tuning.cli_args());
+    if let Some(profile) = &config.profile {
+        args.extend(["--profile".to_string(), profile.clone()]);
+    }
+
+    let mut cmd = Command::new(std::env::current_exe()?);
+    cmd.args(&args)
+        .current_dir(worktree)
+        .envs(config.tuning.env.iter().cloned())
+        .env("SGF_DETACHED", "1")
+        .env_remove("SGF_PROJECT_DIR")
+        .stdin(Stdio::null())
+        .stdout(out.try_clone()?)
+        .stderr(out);
+    // Worktrees would otherwise resolve their own per-path daemons and see
+    // an empty issue store; point them at the main checkout's daemons.
+    if std::env::var("PN_DAEMON").is_err() {
+        let port = crate::recovery::pensa_port(root);
+        cmd.env("PN_DAEMON", format!("http://localhost:{port}"));
+    }
+    if std::env::var("FM_DAEMON").is_err() {
+        let port = crate::recovery::forma_port(root);
+        cmd.env("FM_DAEMON", format!("http://localhost:{port}"));
+    }
+
+    let mut child = cmd.spawn()?;
+    let now = Utc::now().to_rfc3339();
+    let metadata = SessionMetadata {
+        loop_id: loop_id.to_string(),
+        iterations: Vec::new(),
+        stage: config.command.clone(),
+        spec: Some(spec.to_string()),
+        cursus: Some(config.command.clone()),
+        mode: "afk".to_string(),
+        prompt: worktree.to_string_lossy().to_string(),
+        iterations_total: config.iterations.unwrap_or(0),
+        status: "running".to_string(),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    loop_mgmt::write_session_metadata(root, &metadata)?;
+    let pid_path = root.join(".sgf/run").join(format!("{loop_id}.pid"));
+    fs::write(&pid_path, child.id().to_string())?;
+
+    style::print_action_detail(
+        &format!("launched [{loop_id}]"),
+        &format!(
+            "pid: {} · branch: {}",
+            child.id(),
+            branch_name(&config.command, spec)
+        ),
+    );
+
+    Ok(child.wait()?.code())
+}
+
+pub fn print_summary(results: &[SpecResult]) {
+    let width = results.iter().map(|r| r.spec.len()).max().unwrap_or(0);
+    eprintln!();
+    for r in results {
+        let status = match r.status.as_str() {
+            "completed" => style::green(&r.status),
+            "stalled" | "interrupted" => style::yellow(&r.status),
+            _ => style::red(&r.status),
+        };
+        eprintln!(
+            "  {:<width$}  {:<12}  {}  {}",
+            r.spec,
+            status,
+            r.branch,
+            r.loop_id,
+            width = width
+        );
+    }
+    let ok = results.iter().filter(|r| r.succeeded()).count();
+    eprintln!();
+    if ok == results.len() {
+        style::print_success(&format!("{ok}/{} specs completed", results.len()));
+    } else {
+        style::print_warning(&format!("{ok}/{} specs completed", results.len()));
+        eprintln!("To resume a spec: cd .sgf/worktrees/<spec> && sgf resume");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git_repo() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for args in [
+            &["init", "-q"][..],
+            &["config", "user.email", "t@t.com"],
+            &["config", "user.name", "T"],
+            &["commit", "-q", "--allow-empty", "-m", "init"],
+        ] {
+            git(root, args).unwrap();
+        }
+        tmp
+    }
+
+    #[test]
+    fn status_maps_cursus_exit_codes() {
+        assert_eq!(status_for_exit(Some(0)), "completed");
+        assert_eq!(status_for_exit(Some(2)), "stalled");
+        assert_eq!(status_for_exit(Some(130)), "interrupted");
+        assert_eq!(status_for_exit(Some(1)), "failed");
+        assert_eq!(status_for_exit(None), "failed");
+    }
+
+    #[test]
+    fn prepare_worktree_creates_branch_then_reuses_it() {
+        let tmp = git_repo();
+        let root = tmp.path();
+
+        let (path, branch) = prepare_worktree(root, "build", "auth").unwrap();
+        assert_eq!(path, root.join(".sgf/worktrees/auth"));
+        assert_eq!(branch, "sgf/build-auth");
+        assert!(path.join(".git").exists());
+
+        let (again, _) = prepare_worktree(root, "build", "auth").unwrap();
+        assert_eq!(again, path);
+
+        git(
+            root,
+            &["worktree", "remove", "--force", &path.to_string_lossy()],
+        )
+        .unwrap();
+        let (recreated, _) = prepare_worktree(root, "build", "auth").unwrap();
+        assert!(recreated.join(".git").exists(), "existing branch is reused");
+    }
+}
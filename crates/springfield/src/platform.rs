@@ -0,0 +1,81 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+
+pub fn pid_alive(pid: u32) -> bool {
+    unsafe { libc::kill(pid as i32, 0) == 0 }
+}
+
+/// Asks a loop to stop, or makes it stop when `force` is set. Signals the
+/// loop's whole process group when it leads one, so its agent goes too.
+pub fn signal_loop(pid: u32, force: bool) -> io::Result<()> {
+    let target = if unsafe { libc::getpgid(pid as i32) } == pid as i32 {
+        -(pid as i32)
+    } else {
+        pid as i32
+    };
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    if unsafe { libc::kill(target, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+pub fn detach(cmd: &mut Command) {
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+}
+
+/// Whether `path` is a file anyone may execute.
+pub fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+pub fn owner_has_full_access(meta: &fs::Metadata) -> bool {
+    meta.permissions().mode() & 0o700 == 0o700
+}
+
+/// `chmod u+rwx`.
+pub fn grant_owner_full_access(path: &Path) -> io::Result<()> {
+    let mode = fs::metadata(path)?.permissions().mode() | 0o700;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+pub fn is_wsl() -> bool {
+    cfg!(target_os = "linux")
+        && std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+}
+
+/// The Windows drive letter when `path` is a WSL mount such as `/mnt/c`,
+/// where Unix permissions are not kept and file access is slow.
+pub fn windows_drive(path: &Path) -> Option<char> {
+    let mut parts = path.strip_prefix("/mnt").ok()?.components();
+    let drive = parts.next()?.as_os_str().to_str()?;
+    let mut chars = drive.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => Some(letter),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_drives_are_recognized_under_mnt() {
+        assert_eq!(windows_drive(Path::new("/mnt/c/Users/me/app")), Some('c'));
+        assert_eq!(windows_drive(Path::new("/mnt/d")), Some('d'));
+        assert_eq!(windows_drive(Path::new("/mnt/wsl/app")), None);
+        assert_eq!(windows_drive(Path::new("/home/me/app")), None);
+    }
+}
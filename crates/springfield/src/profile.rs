@@ -0,0 +1,170 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+pub type GitignoreSection = (&'static str, &'static [&'static str]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    Rust,
+    Node,
+    Python,
+    Tauri,
+}
+
+const RUST_GITIGNORE: GitignoreSection = ("Rust", &["/target"]);
+const NODE_GITIGNORE: GitignoreSection = ("Node", &["node_modules/", "/dist"]);
+const PLAYWRIGHT_GITIGNORE: GitignoreSection = (
+    "Playwright",
+    &[
+        "/test-results/",
+        "/playwright-report/",
+        "/blob-report/",
+        "/playwright/.cache/",
+    ],
+);
+const PYTHON_GITIGNORE: GitignoreSection = (
+    "Python",
+    &[
+        "__pycache__/",
+        "*.py[cod]",
+        ".venv/",
+        "*.egg-info/",
+        ".pytest_cache/",
+        ".mypy_cache/",
+        ".ruff_cache/",
+        "/dist",
+        "/build",
+    ],
+);
+const TAURI_GITIGNORE: GitignoreSection = ("Tauri", &["/src-tauri/target", "/src-tauri/gen/"]);
+
+const BACKPRESSURE_RUST: &str = "\
+# Backpressure
+
+Run these before every commit and fix whatever fails before moving on.
+
+- Format: `cargo fmt --all -- --check`
+- Lint: `cargo clippy --workspace --all-targets -- -D warnings`
+- Build: `cargo build --workspace`
+- Test: `cargo test --workspace`
+";
+
+const BACKPRESSURE_NODE: &str = "\
+# Backpressure
+
+Run these before every commit and fix whatever fails before moving on.
+
+- Install: `pnpm install --frozen-lockfile`
+- Format: `pnpm exec prettier --check .`
+- Lint: `pnpm lint`
+- Typecheck: `pnpm exec tsc --noEmit`
+- Test: `pnpm test`
+- End-to-end: `pnpm exec playwright test`
+";
+
+const BACKPRESSURE_PYTHON: &str = "\
+# Backpressure
+
+Run these before every commit and fix whatever fails before moving on.
+
+- Format: `ruff format --check .`
+- Lint: `ruff check .`
+- Typecheck: `mypy .`
+- Test: `pytest`
+";
+
+const BACKPRESSURE_TAURI: &str = "\
+# Backpressure
+
+Run these before every commit and fix whatever fails before moving on.
+
+- Format: `cargo fmt --manifest-path src-tauri/Cargo.toml -- --check` and `pnpm exec prettier --check .`
+- Lint: `cargo clippy --manifest-path src-tauri/Cargo.toml --all-targets -- -D warnings` and `pnpm lint`
+- Typecheck: `pnpm exec tsc --noEmit`
+- Test: `cargo test --manifest-path src-tauri/Cargo.toml` and `pnpm test`
+- Build: `pnpm tauri build --debug`
+";
+
+impl Profile {
+    pub const ALL: [Profile; 4] = [Self::Rust, Self::Node, Self::Python, Self::Tauri];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Node => "node",
+            Self::Python => "python",
+            Self::Tauri => "tauri",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Rust => "Cargo workspace; cargo fmt, clippy, and test",
+            Self::Node => "pnpm + Vite frontend; prettier, eslint, tsc, vitest, Playwright",
+            Self::Python => "Python package; ruff, mypy, and pytest",
+            Self::Tauri => "Tauri app: Rust in src-tauri/ plus a pnpm + Vite frontend",
+        }
+    }
+
+    pub fn gitignore(self) -> &'static [GitignoreSection] {
+        match self {
+            Self::Rust => &[RUST_GITIGNORE],
+            Self::Node => &[NODE_GITIGNORE, PLAYWRIGHT_GITIGNORE],
+            Self::Python => &[PYTHON_GITIGNORE],
+            Self::Tauri => &[
+                RUST_GITIGNORE,
+                TAURI_GITIGNORE,
+                NODE_GITIGNORE,
+                PLAYWRIGHT_GITIGNORE,
+            ],
+        }
+    }
+
+    pub fn allowed_domains(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &["crates.io", "*.crates.io", "static.rust-lang.org"],
+            Self::Node => &["registry.npmjs.org", "registry.yarnpkg.com"],
+            Self::Python => &["pypi.org", "files.pythonhosted.org"],
+            Self::Tauri => &[
+                "crates.io",
+                "*.crates.io",
+                "static.rust-lang.org",
+                "registry.npmjs.org",
+                "registry.yarnpkg.com",
+            ],
+        }
+    }
+
+    pub fn has_frontend(self) -> bool {
+        matches!(self, Self::Node | Self::Tauri)
+    }
+
+    pub fn backpressure(self) -> &'static str {
+        match self {
+            Self::Rust => BACKPRESSURE_RUST,
+            Self::Node => BACKPRESSURE_NODE,
+            Self::Python => BACKPRESSURE_PYTHON,
+            Self::Tauri => BACKPRESSURE_TAURI,
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.name() == s)
+            .ok_or_else(|| format!("unknown profile: {s} (expected rust, node, python, or tauri)"))
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::SgfConfig;
+
+/// The subproject to run in, given the directory sgf started in and the
+/// `--project-dir` flag (or `SGF_PROJECT_DIR`). The flag wins over
+/// `project_dir` in `cwd`'s config. Relative paths resolve against `cwd`.
+/// `None` means run in `cwd` itself.
+pub fn resolve(cwd: &Path, requested: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    let requested = match requested {
+        Some(dir) => dir.to_path_buf(),
+        None => match SgfConfig::load(cwd).map(|c| c.project_dir) {
+            Ok(Some(dir)) => dir,
+            _ => return Ok(None),
+        },
+    };
+    let dir = cwd.join(&requested);
+    if !dir.is_dir() {
+        return Err(format!(
+            "project dir {} is not a directory",
+            requested.display()
+        ));
+    }
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| format!("project dir {}: {e}", requested.display()))?;
+    let inside_repo = Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(&dir)
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if !inside_repo {
+        return Err(format!(
+            "project dir {} is not inside a git repository",
+            requested.display()
+        ));
+    }
+    Ok(Some(dir))
+}
+
+/// `root`'s path below the top of its git repository (empty at the top).
+/// A worktree of the repo holds the same project at this path.
+pub fn repo_prefix(root: &Path) -> PathBuf {
+    Command::new("git")
+        .args(["rev-parse", "--show-prefix"])
+        .current_dir(root)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| PathBuf::from(String::from_utf8_lossy(&o.stdout).trim()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn monorepo() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(tmp.path())
+            .status()
+            .unwrap();
+        fs::create_dir_all(tmp.path().join("packages/api/.sgf")).unwrap();
+        tmp
+    }
+
+    #[test]
+    fn flag_wins_over_config_and_paths_resolve_against_cwd() {
+        let tmp = monorepo();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("packages/web")).unwrap();
+        fs::create_dir_all(root.join(".sgf")).unwrap();
+        fs::write(
+            root.join(".sgf/config.toml"),
+            "project_dir = \"packages/web\"\n",
+        )
+        .unwrap();
+        let api = root.join("packages/api").canonicalize().unwrap();
+        let web = root.join("packages/web").canonicalize().unwrap();
+
+        assert_eq!(resolve(root, None).unwrap(), Some(web));
+        assert_eq!(
+            resolve(root, Some(Path::new("packages/api"))).unwrap(),
+            Some(api.clone())
+        );
+        assert_eq!(repo_prefix(&api), Path::new("packages/api/"));
+        assert_eq!(resolve(&api, None).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_dirs_and_dirs_outside_git_are_rejected() {
+        let tmp = monorepo();
+        assert_eq!(
+            resolve(tmp.path(), Some(Path::new("packages/nope"))).unwrap_err(),
+            "project dir packages/nope is not a directory"
+        );
+        let outside = TempDir::new().unwrap();
+        let err = resolve(outside.path(), Some(Path::new("."))).unwrap_err();
+        assert!(err.ends_with("is not inside a git repository"), "{err}");
+    }
+}
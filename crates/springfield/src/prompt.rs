@@ -1,18 +1,41 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
-fn global_prompts_dir() -> Option<PathBuf> {
+pub const CONTEXT_VARS: &[&str] = &[
+    "backpressure",
+    "git_branch",
+    "ready_issues",
+    "recent_commits",
+    "spec_content",
+    "verify_findings",
+];
+
+const RECENT_COMMITS: usize = 10;
+
+pub const MAX_PROMPT_BYTES: usize = 64 * 1024;
+
+pub(crate) fn global_sgf_dir() -> Option<PathBuf> {
     std::env::var("HOME")
         .ok()
-        .map(|h| PathBuf::from(h).join(".sgf/prompts"))
+        .map(|h| PathBuf::from(h).join(".sgf"))
+}
+
+fn global_prompts_dir() -> Option<PathBuf> {
+    global_sgf_dir().map(|d| d.join("prompts"))
 }
 
 pub fn resolve(root: &Path, name: &str) -> Option<PathBuf> {
-    let local = root.join(format!(".sgf/prompts/{name}.md"));
+    resolve_template(root, &format!("{name}.md"))
+}
+
+pub fn resolve_template(root: &Path, file: &str) -> Option<PathBuf> {
+    let local = root.join(".sgf/prompts").join(file);
     if local.exists() {
         return Some(local);
     }
-    let global = global_prompts_dir()?.join(format!("{name}.md"));
+    let global = global_prompts_dir()?.join(file);
     if global.exists() {
         return Some(global);
     }
@@ -28,16 +51,349 @@ pub fn validate(root: &Path, stage: &str, spec: Option<&str>) -> io::Result<Path
     })?;
 
     if let Some(stem) = spec {
-        let spec_path = root.join(format!("specs/{stem}.md"));
-        if !spec_path.exists() {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("spec not found: specs/{stem}.md"),
+        check_spec(root, stem)?;
+    }
+
+    Ok(template_path)
+}
+
+pub fn check_spec(root: &Path, stem: &str) -> io::Result<()> {
+    if root.join(format!("specs/{stem}.md")).exists() {
+        return Ok(());
+    }
+    let mut message = format!("spec not found: specs/{stem}.md");
+    let suggestions = similar_specs(root, stem);
+    if !suggestions.is_empty() {
+        message.push_str(&format!(" (did you mean: {}?)", suggestions.join(", ")));
+    }
+    Err(io::Error::new(io::ErrorKind::NotFound, message))
+}
+
+fn similar_specs(root: &Path, stem: &str) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(root.join("specs")) else {
+        return Vec::new();
+    };
+    let max_distance = (stem.chars().count() / 3).max(2);
+    let mut candidates: Vec<(usize, String)> = entries
+        .flatten()
+        .filter_map(|e| {
+            let path = e.path();
+            (path.extension()? == "md").then_some(())?;
+            let candidate = path.file_stem()?.to_str()?.to_string();
+            let distance = edit_distance(stem, &candidate);
+            (distance <= max_distance || candidate.contains(stem) || stem.contains(&candidate))
+                .then_some((distance, candidate))
+        })
+        .collect();
+    candidates.sort();
+    candidates.into_iter().take(3).map(|(_, c)| c).collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            row[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Replaces `{{name}}` placeholders with values from `vars`. Unknown
+/// placeholders are left as written so a typo stays visible in the prompt.
+pub fn assemble(template: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        match vars.get(after[..end].trim()) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Renders a template with live context, then `vars`, then `{{spec}}`;
+/// later sources win.
+pub fn render(
+    root: &Path,
+    template: &str,
+    vars: &HashMap<String, String>,
+    spec: Option<&str>,
+) -> String {
+    let mut all = context_vars(root, template, spec);
+    all.extend(vars.clone());
+    if let Some(spec) = spec {
+        all.entry("spec".to_string())
+            .or_insert_with(|| spec.to_string());
+    }
+    assemble(template, &all)
+}
+
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim().to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// Values for the [`CONTEXT_VARS`] that `template` uses. Only referenced
+/// variables are computed, so a prompt without them never runs `pn` or `git`.
+pub fn context_vars(root: &Path, template: &str, spec: Option<&str>) -> HashMap<String, String> {
+    placeholders(template)
+        .into_iter()
+        .filter(|name| CONTEXT_VARS.contains(&name.as_str()))
+        .map(|name| {
+            let value = context_var(root, &name, spec);
+            (name, value)
+        })
+        .collect()
+}
+
+fn context_var(root: &Path, name: &str, spec: Option<&str>) -> String {
+    let value = match name {
+        "git_branch" => command_output(root, "git", &["rev-parse", "--abbrev-ref", "HEAD"]),
+        "recent_commits" => command_output(
+            root,
+            "git",
+            &["log", "--oneline", &format!("-n{RECENT_COMMITS}")],
+        ),
+        "ready_issues" => match spec {
+            Some(stem) => command_output(root, "pn", &["ready", "--spec", stem]),
+            None => command_output(root, "pn", &["ready"]),
+        },
+        "spec_content" => spec.map_or(Ok(String::new()), |stem| read_spec(root, stem)),
+        "backpressure" => read_layered(root, "BACKPRESSURE.md"),
+        "verify_findings" => Ok(crate::verify_findings::PROMPT_INSTRUCTIONS.to_string()),
+        _ => Ok(String::new()),
+    };
+    match value {
+        Ok(v) if v.trim().is_empty() => "(none)".to_string(),
+        Ok(v) => v.trim_end().to_string(),
+        Err(e) => {
+            tracing::warn!(var = name, error = %e, "failed to fill prompt context");
+            "(unavailable)".to_string()
+        }
+    }
+}
+
+fn command_output(root: &Path, program: &str, args: &[&str]) -> io::Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{program} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn read_spec(root: &Path, stem: &str) -> io::Result<String> {
+    std::fs::read_to_string(root.join(format!("specs/{stem}.md")))
+        .or_else(|_| std::fs::read_to_string(root.join(format!(".forma/specs/{stem}.md"))))
+}
+
+pub(crate) fn read_layered(root: &Path, name: &str) -> io::Result<String> {
+    match std::fs::read_to_string(root.join(".sgf").join(name)) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => match global_sgf_dir() {
+            Some(dir) => match std::fs::read_to_string(dir.join(name)) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+                other => other,
+            },
+            None => Ok(String::new()),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+pub fn includes(template: &str) -> Vec<String> {
+    template
+        .split_whitespace()
+        .filter_map(|word| {
+            let word = word.trim_start_matches(['(', '[', '`', '"', '\'']);
+            let path = word
+                .strip_prefix('@')?
+                .trim_end_matches(['.', ',', ';', ':', ')', ']', '`', '"', '\'', '!', '?']);
+            (path.contains('/') || path.contains('.')).then(|| path.to_string())
+        })
+        .collect()
+}
+
+fn include_path(root: &Path, include: &str) -> Option<PathBuf> {
+    match include.strip_prefix("~/") {
+        Some(rest) => std::env::var("HOME")
+            .ok()
+            .map(|h| PathBuf::from(h).join(rest)),
+        None => Some(root.join(include)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    pub template: String,
+    pub message: String,
+    pub error: bool,
+}
+
+pub struct LintReport {
+    pub templates: usize,
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.error)
+    }
+}
+
+pub fn lint(root: &Path) -> LintReport {
+    let mut issues = Vec::new();
+    // template file -> (user label, vars that user sets)
+    let mut users: BTreeMap<String, Vec<(String, HashSet<String>)>> = BTreeMap::new();
+
+    let config = crate::config::SgfConfig::load(root).unwrap_or_default();
+    let mut defs: Vec<(String, crate::cursus::toml::CursusDefinition)> =
+        crate::cursus::load_all_definitions(root)
+            .into_iter()
+            .collect();
+    for (name, _) in config.custom_stages() {
+        if let Some(def) = config.stage_definition(&name) {
+            defs.push((name, def));
+        }
+    }
+    for (name, def) in &defs {
+        for iter in &def.iters {
+            let label = format!("{name}/{}", iter.name);
+            if resolve_template(root, &iter.prompt).is_none() {
+                issues.push(LintIssue {
+                    template: iter.prompt.clone(),
+                    message: format!("not found (used by {label})"),
+                    error: true,
+                });
+                continue;
+            }
+            users
+                .entry(iter.prompt.clone())
+                .or_default()
+                .push((label, iter.vars.keys().cloned().collect()));
+        }
+    }
+    if let Ok(entries) = std::fs::read_dir(root.join(".sgf/prompts")) {
+        for entry in entries.flatten() {
+            let file = entry.file_name().to_string_lossy().to_string();
+            if file.ends_with(".md") {
+                users.entry(file).or_default();
+            }
+        }
+    }
+
+    for (file, users) in &users {
+        let Some(path) = resolve_template(root, file) else {
+            continue;
+        };
+        let content = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                issues.push(LintIssue {
+                    template: file.clone(),
+                    message: e.to_string(),
+                    error: true,
+                });
+                continue;
+            }
+        };
+        issues.extend(lint_template(root, file, &content, users));
+    }
+
+    LintReport {
+        templates: users.len(),
+        issues,
+    }
+}
+
+fn lint_template(
+    root: &Path,
+    file: &str,
+    content: &str,
+    users: &[(String, HashSet<String>)],
+) -> Vec<LintIssue> {
+    let issue = |message: String, error: bool| LintIssue {
+        template: file.to_string(),
+        message,
+        error,
+    };
+    let mut issues = Vec::new();
+
+    for name in placeholders(content) {
+        if name == "spec" || CONTEXT_VARS.contains(&name.as_str()) {
+            continue;
+        }
+        let missing: Vec<&str> = users
+            .iter()
+            .filter(|(_, vars)| !vars.contains(&name))
+            .map(|(label, _)| label.as_str())
+            .collect();
+        if users.is_empty() {
+            issues.push(issue(format!("unresolved variable {{{{{name}}}}}"), true));
+        } else if !missing.is_empty() {
+            issues.push(issue(
+                format!(
+                    "unresolved variable {{{{{name}}}}} (not set for {})",
+                    missing.join(", ")
+                ),
+                true,
             ));
         }
     }
 
-    Ok(template_path)
+    for include in includes(content) {
+        if include.contains("{{") {
+            continue;
+        }
+        if !include_path(root, &include).is_some_and(|p| p.exists()) {
+            issues.push(issue(format!("missing include @{include}"), true));
+        }
+    }
+
+    if content.len() > MAX_PROMPT_BYTES {
+        issues.push(issue(
+            format!(
+                "{} KB exceeds the {} KB prompt budget",
+                content.len() / 1024,
+                MAX_PROMPT_BYTES / 1024
+            ),
+            false,
+        ));
+    }
+
+    issues
 }
 
 #[cfg(test)]
@@ -94,6 +450,37 @@ mod tests {
         assert!(err.to_string().contains("spec not found: specs/auth.md"));
     }
 
+    #[test]
+    fn check_spec_suggests_close_matches() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("specs")).unwrap();
+        for stem in ["auth", "authz", "billing", "oauth-login"] {
+            fs::write(tmp.path().join(format!("specs/{stem}.md")), "# spec").unwrap();
+        }
+
+        check_spec(tmp.path(), "auth").unwrap();
+        let err = check_spec(tmp.path(), "atuh").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "spec not found: specs/atuh.md (did you mean: auth?)"
+        );
+        let err = check_spec(tmp.path(), "oauth").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("did you mean: auth, authz, oauth-login?")
+        );
+        let err = check_spec(tmp.path(), "zzzzzz").unwrap_err();
+        assert_eq!(err.to_string(), "spec not found: specs/zzzzzz.md");
+    }
+
+    #[test]
+    fn edit_distance_counts_single_char_edits() {
+        assert_eq!(edit_distance("auth", "auth"), 0);
+        assert_eq!(edit_distance("auth", "autz"), 1);
+        assert_eq!(edit_distance("atuh", "auth"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
     #[test]
     fn validate_dynamic_command_name() {
         let tmp = TempDir::new().unwrap();
@@ -149,4 +536,122 @@ mod tests {
         let read_back = fs::read_to_string(&path).unwrap();
         assert_eq!(read_back, content);
     }
+
+    #[test]
+    fn assemble_substitutes_known_vars() {
+        let vars = HashMap::from([
+            ("spec".to_string(), "auth".to_string()),
+            ("audience".to_string(), "users".to_string()),
+        ]);
+        let out = assemble("Spec {{spec}} for {{ audience }}.", &vars);
+        assert_eq!(out, "Spec auth for users.");
+    }
+
+    #[test]
+    fn assemble_keeps_unknown_and_unclosed_placeholders() {
+        let vars = HashMap::from([("spec".to_string(), "auth".to_string())]);
+        assert_eq!(
+            assemble("{{missing}} {{spec}} {{open", &vars),
+            "{{missing}} auth {{open"
+        );
+    }
+
+    #[test]
+    fn placeholders_lists_names_once_in_order() {
+        assert_eq!(
+            placeholders("{{ spec }} then {{git_branch}} and {{spec}} {{open"),
+            vec!["spec".to_string(), "git_branch".to_string()]
+        );
+    }
+
+    #[test]
+    fn context_vars_fill_only_referenced_names() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for args in [
+            &["init", "-q", "-b", "main"][..],
+            &["config", "user.email", "t@t.com"],
+            &["config", "user.name", "T"],
+            &["commit", "-q", "--allow-empty", "-m", "first change"],
+        ] {
+            command_output(root, "git", args).unwrap();
+        }
+        fs::create_dir_all(root.join("specs")).unwrap();
+        fs::write(root.join("specs/auth.md"), "# Auth spec\n").unwrap();
+        setup_project(root);
+        fs::write(root.join(".sgf/BACKPRESSURE.md"), "cargo test\n").unwrap();
+
+        let vars = context_vars(
+            root,
+            "{{git_branch}} {{recent_commits}} {{spec_content}} {{backpressure}} {{other}}",
+            Some("auth"),
+        );
+        assert_eq!(vars.len(), 4);
+        assert_eq!(vars["git_branch"], "main");
+        assert!(vars["recent_commits"].ends_with("first change"));
+        assert_eq!(vars["spec_content"], "# Auth spec");
+        assert_eq!(vars["backpressure"], "cargo test");
+
+        let vars = context_vars(root, "{{spec_content}} {{verify_findings}}", None);
+        assert_eq!(vars["spec_content"], "(none)");
+        assert!(vars["verify_findings"].contains(".sgf/out/verify-findings.json"));
+    }
+
+    #[test]
+    fn includes_finds_file_references() {
+        assert_eq!(
+            includes("Study @~/.sgf/prompts/change.md, then (@specs/auth.md). Ping @alice."),
+            vec![
+                "~/.sgf/prompts/change.md".to_string(),
+                "specs/auth.md".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_reports_template_problems() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        setup_project(root);
+        fs::create_dir_all(root.join(".sgf/cursus")).unwrap();
+        fs::create_dir_all(root.join("specs")).unwrap();
+        fs::write(root.join("specs/auth.md"), "# Auth").unwrap();
+        fs::write(
+            root.join(".sgf/cursus/build.toml"),
+            "description = \"Build\"\n\n[[iter]]\nname = \"build\"\nprompt = \"build.md\"\nvars = { focus = \"tests\" }\n\n[[iter]]\nname = \"verify\"\nprompt = \"verify.md\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(".sgf/prompts/build.md"),
+            "Build {{spec}} with {{focus}} on {{git_branch}}. See @specs/auth.md.",
+        )
+        .unwrap();
+        fs::write(
+            root.join(".sgf/prompts/extra.md"),
+            format!(
+                "{{{{typo}}}} @specs/gone.md\n{}",
+                "x".repeat(MAX_PROMPT_BYTES)
+            ),
+        )
+        .unwrap();
+
+        let report = lint(root);
+        assert_eq!(report.templates, 2);
+        assert!(report.has_errors());
+        let messages: Vec<String> = report
+            .issues
+            .iter()
+            .map(|i| format!("{}: {}", i.template, i.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "verify.md: not found (used by build/verify)",
+                "extra.md: unresolved variable {{typo}}",
+                "extra.md: missing include @specs/gone.md",
+                "extra.md: 64 KB exceeds the 64 KB prompt budget",
+            ]
+        );
+        assert!(!report.issues[3].error, "size is a warning");
+    }
 }
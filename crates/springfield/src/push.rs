@@ -0,0 +1,99 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::config::{MergeBack, PushWhen, SgfConfig};
+
+const PR_STAGE: &str = "build";
+
+fn config(root: &Path) -> SgfConfig {
+    SgfConfig::load(root).unwrap_or_default()
+}
+
+pub fn per_iteration(root: &Path) -> bool {
+    config(root).push.when() == PushWhen::Iteration
+}
+
+pub fn target(root: &Path) -> vcs_utils::PushTarget {
+    config(root).push.target()
+}
+
+/// Runs once the loop `loop_id` in `root` has ended, before its record is
+/// finished. `pushing` is false when the loop was not allowed to push at all
+/// (`--no-push` or `auto_push = false`). Nothing happens unless the loop
+/// exited 0 and moved HEAD since it started.
+pub fn after_loop(root: &Path, loop_id: &str, exit_code: i32, pushing: bool) {
+    if !pushing || exit_code != 0 {
+        return;
+    }
+    let Ok(Some(record)) = crate::loop_mgmt::read_loop_record(root, loop_id) else {
+        return;
+    };
+    let Some(head_before) = record.head_before.as_deref() else {
+        return;
+    };
+    if vcs_utils::git_head().is_none_or(|head| head == head_before) {
+        return;
+    }
+    let config = config(root);
+    let target = config.push.target();
+    if config.push.when() == PushWhen::Complete {
+        vcs_utils::push_unpushed(&target, crate::style::print_action);
+    }
+
+    // With branch_per_loop and merge_back = "pr", merge-back opens the PR.
+    let merge_back_opens_pr = config.branch_per_loop() && config.merge_back() == MergeBack::Pr;
+    if record.stage != PR_STAGE || !config.push.pr() || merge_back_opens_pr {
+        return;
+    }
+    let Some(head) = target.branch.clone().or_else(|| current_branch(root)) else {
+        crate::style::print_warning("push.pr: HEAD is detached; no pull request opened");
+        return;
+    };
+    let labels = config.push.pr_labels(record.spec.as_deref());
+    match open_pull_request(root, &head, &labels) {
+        Ok(url) => {
+            crate::style::print_success_detail(&format!("opened a pull request for {head}"), &url)
+        }
+        Err(e) => crate::style::print_warning(&format!("push.pr: {e}")),
+    }
+}
+
+fn current_branch(root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .current_dir(root)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn open_pull_request(root: &Path, head: &str, labels: &[String]) -> Result<String, String> {
+    for label in labels {
+        // Fails harmlessly when the label already exists.
+        let _ = Command::new("gh")
+            .args(["label", "create", label])
+            .current_dir(root)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    let mut args = vec!["pr", "create", "--fill", "--head", head];
+    for label in labels {
+        args.extend(["--label", label]);
+    }
+    let output = Command::new("gh")
+        .args(&args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| format!("failed to run gh: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "gh pr create failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
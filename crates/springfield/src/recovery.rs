@@ -4,6 +4,9 @@ use std::process::Command;
 use std::thread;
 use std::time::{Duration, Instant};
 
+use serde::Deserialize;
+
+use crate::config::{RecoveryStrategy, SgfConfig};
 use crate::loop_mgmt;
 use crate::style;
 
@@ -24,55 +27,164 @@ pub(crate) fn forma_port(root: &Path) -> u16 {
     10000 + (raw % 50000)
 }
 
-pub fn pre_launch_recovery(root: &Path) -> io::Result<()> {
-    let pid_entries = loop_mgmt::list_pid_files(root);
+pub const PENSA_API_VERSION: u32 = 1;
 
-    if pid_entries.is_empty() {
-        return Ok(());
-    }
+/// Paths recovery never touches, whatever `[recovery] exclude` says.
+const ALWAYS_EXCLUDED: &[&str] = &[".sgf"];
 
-    let any_alive = pid_entries
-        .iter()
-        .any(|(_, pid)| loop_mgmt::is_pid_alive(*pid));
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryPlan {
+    pub stale_loops: Vec<String>,
+    pub strategy: RecoveryStrategy,
+    pub changes: Vec<String>,
+    pub excluded: Vec<String>,
+}
 
-    if any_alive {
-        return Ok(());
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryOutcome {
+    NotNeeded,
+    Recovered,
+    Stashed(String),
+    Aborted,
+}
 
-    // All PIDs are stale — remove PID files and recover
-    for (loop_id, _) in &pid_entries {
-        loop_mgmt::remove_pid_file(root, loop_id);
+pub fn plan(root: &Path, strategy: Option<RecoveryStrategy>) -> io::Result<Option<RecoveryPlan>> {
+    let pid_entries = loop_mgmt::list_pid_files(root);
+    if pid_entries.is_empty()
+        || pid_entries
+            .iter()
+            .any(|(_, pid)| loop_mgmt::is_pid_alive(*pid))
+    {
+        return Ok(None);
     }
 
-    style::print_action("recovering from stale state...");
+    let config = SgfConfig::load(root).unwrap_or_default().recovery;
+    let excluded: Vec<String> = ALWAYS_EXCLUDED
+        .iter()
+        .map(|p| p.to_string())
+        .chain(config.exclude)
+        .collect();
+    let mut args = vec!["status", "--porcelain", "--untracked-files=all", "--"];
+    let specs = pathspecs(&excluded);
+    args.extend(specs.iter().map(String::as_str));
+    let changes = git(root, &args)?
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    Ok(Some(RecoveryPlan {
+        stale_loops: pid_entries.into_iter().map(|(id, _)| id).collect(),
+        strategy: strategy.or(config.strategy).unwrap_or_default(),
+        changes,
+        excluded,
+    }))
+}
+
+fn pathspecs(excluded: &[String]) -> Vec<String> {
+    std::iter::once(".".to_string())
+        .chain(excluded.iter().map(|p| format!(":(exclude){p}")))
+        .collect()
+}
 
-    let checkout = Command::new("git")
-        .args(["checkout", "--", "."])
+fn git(root: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .args(args)
         .current_dir(root)
-        .status();
-    match checkout {
-        Ok(s) if s.success() => {}
-        Ok(s) => {
-            return Err(io::Error::other(format!(
-                "git checkout -- . failed with {s}"
-            )));
+        .output()
+        .map_err(|e| io::Error::other(format!("failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+impl RecoveryPlan {
+    pub fn describe(&self) -> Vec<String> {
+        let strategy = match self.strategy {
+            RecoveryStrategy::Reset => "reset",
+            RecoveryStrategy::Stash => "stash",
+            RecoveryStrategy::Abort => "abort",
+        };
+        let mut lines = vec![format!("strategy: {strategy}")];
+        for loop_id in &self.stale_loops {
+            lines.push(format!("remove stale PID file: {loop_id}"));
         }
-        Err(e) => {
-            return Err(io::Error::other(format!("failed to run git checkout: {e}")));
+        if self.changes.is_empty() {
+            lines.push("working tree clean: nothing to discard".to_string());
+        }
+        for change in &self.changes {
+            let (status, path) = change.split_at(3.min(change.len()));
+            let action = match self.strategy {
+                RecoveryStrategy::Reset if status.starts_with("??") => "delete",
+                RecoveryStrategy::Reset => "restore",
+                RecoveryStrategy::Stash => "stash",
+                RecoveryStrategy::Abort => "keep (launch stops)",
+            };
+            lines.push(format!("{action}: {path}"));
+        }
+        if self.strategy != RecoveryStrategy::Abort || self.changes.is_empty() {
+            lines.push("run: pn doctor --fix".to_string());
         }
+        lines.push(format!("never touched: {}", self.excluded.join(", ")));
+        lines
     }
+}
 
-    let clean = Command::new("git")
-        .args(["clean", "-fd"])
-        .current_dir(root)
-        .status();
-    match clean {
-        Ok(s) if s.success() => {}
-        Ok(s) => {
-            return Err(io::Error::other(format!("git clean -fd failed with {s}")));
+pub fn pre_launch_recovery(root: &Path) -> io::Result<RecoveryOutcome> {
+    match plan(root, None)? {
+        Some(plan) => apply(root, &plan),
+        None => Ok(RecoveryOutcome::NotNeeded),
+    }
+}
+
+/// Carries out `plan`. Under `abort`, a dirty tree is left as is (PID files
+/// included, so the next launch asks again) unless the user picks another
+/// strategy at the prompt.
+pub fn apply(root: &Path, plan: &RecoveryPlan) -> io::Result<RecoveryOutcome> {
+    apply_with(root, plan, ask_strategy)
+}
+
+fn apply_with(
+    root: &Path,
+    plan: &RecoveryPlan,
+    ask: impl FnOnce(&RecoveryPlan) -> io::Result<RecoveryStrategy>,
+) -> io::Result<RecoveryOutcome> {
+    let mut strategy = plan.strategy;
+    if strategy == RecoveryStrategy::Abort && !plan.changes.is_empty() {
+        strategy = ask(plan)?;
+        if strategy == RecoveryStrategy::Abort {
+            return Ok(RecoveryOutcome::Aborted);
         }
-        Err(e) => {
-            return Err(io::Error::other(format!("failed to run git clean: {e}")));
+    }
+
+    for loop_id in &plan.stale_loops {
+        loop_mgmt::remove_pid_file(root, loop_id);
+    }
+
+    style::print_action("recovering from stale state...");
+
+    let specs = pathspecs(&plan.excluded);
+    let mut outcome = RecoveryOutcome::Recovered;
+    if !plan.changes.is_empty() {
+        match strategy {
+            RecoveryStrategy::Stash => {
+                let reference = stash(root, &specs)?;
+                style::print_success_detail(
+                    &format!("stashed changes as {reference}"),
+                    &format!("restore with: git stash apply {reference}"),
+                );
+                outcome = RecoveryOutcome::Stashed(reference);
+            }
+            _ => {
+                let specs: Vec<&str> = specs.iter().map(String::as_str).collect();
+                git(root, &[&["checkout", "--"][..], &specs].concat())?;
+                git(root, &[&["clean", "-fd", "--"][..], &specs].concat())?;
+            }
         }
     }
 
@@ -91,7 +203,44 @@ pub fn pre_launch_recovery(root: &Path) -> io::Result<()> {
     }
 
     style::print_success("recovery complete");
-    Ok(())
+    Ok(outcome)
+}
+
+/// Stashes the changes, untracked files included, and pins the stash under
+/// `refs/sgf/recovery/<timestamp>` so clearing the stash list cannot lose it.
+fn stash(root: &Path, specs: &[String]) -> io::Result<String> {
+    let stamp = chrono::Local::now().format("%Y%m%dT%H%M%S").to_string();
+    let message = format!("sgf recovery {stamp}");
+    let mut args = vec!["stash", "push", "--include-untracked", "-m", &message, "--"];
+    args.extend(specs.iter().map(String::as_str));
+    git(root, &args)?;
+    let commit = git(root, &["rev-parse", "stash@{0}"])?;
+    let reference = format!("refs/sgf/recovery/{stamp}");
+    git(root, &["update-ref", &reference, commit.trim()])?;
+    Ok(reference)
+}
+
+/// Asks what to do with a crashed loop's changes. Without a terminal to ask
+/// on, the answer is abort.
+fn ask_strategy(plan: &RecoveryPlan) -> io::Result<RecoveryStrategy> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    style::print_warning("a crashed loop left changes in the working tree:");
+    for change in &plan.changes {
+        style::print_detail(change);
+    }
+    if !io::stdin().is_terminal() {
+        return Ok(RecoveryStrategy::Abort);
+    }
+    eprint!("[s]tash, [r]eset, or [a]bort? ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(match answer.trim() {
+        "s" | "stash" => RecoveryStrategy::Stash,
+        "r" | "reset" => RecoveryStrategy::Reset,
+        _ => RecoveryStrategy::Abort,
+    })
 }
 
 pub fn ensure_daemons(root: &Path) -> io::Result<()> {
@@ -154,6 +303,75 @@ pub fn ensure_daemons(root: &Path) -> io::Result<()> {
     ))
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct PensaDaemonStatus {
+    pub url: String,
+    pub version: Option<String>,
+    pub api_version: Option<u32>,
+    pub min_api_version: Option<u32>,
+    pub cli_version: String,
+    pub cli_api_version: u32,
+}
+
+impl PensaDaemonStatus {
+    /// Why the daemon, the `pn` CLI, and sgf's prompts can't work together,
+    /// if they can't. A daemon that reports no API version is assumed to
+    /// serve what is asked of it.
+    pub fn mismatch(&self) -> Option<String> {
+        let max = self.api_version?;
+        let min = self.min_api_version.unwrap_or(max);
+        let daemon = match &self.version {
+            Some(v) => format!("pensa {v}"),
+            None => "an older pensa".to_string(),
+        };
+        let served = if min == max {
+            format!("v{max}")
+        } else {
+            format!("v{min}-v{max}")
+        };
+        let needs = |who: String, version: u32| {
+            format!(
+                "pensa daemon at {} ({daemon}) serves API {served}, but {who} v{version}",
+                self.url
+            )
+        };
+        if !(min..=max).contains(&self.cli_api_version) {
+            return Some(needs(
+                format!("pn {} needs", self.cli_version),
+                self.cli_api_version,
+            ));
+        }
+        if !(min..=max).contains(&PENSA_API_VERSION) {
+            return Some(needs(
+                format!("sgf {}'s prompts need", env!("CARGO_PKG_VERSION")),
+                PENSA_API_VERSION,
+            ));
+        }
+        None
+    }
+}
+
+/// Checks that the running pensa daemon can serve both the `pn` on PATH and
+/// sgf's prompts, so a mismatch stops the launch instead of surfacing as
+/// agent errors mid-loop. A `pn` too old to report versions is not checked.
+pub fn check_pensa_version(root: &Path) -> Result<(), String> {
+    let Ok(output) = Command::new("pn")
+        .args(["daemon", "status", "--json"])
+        .current_dir(root)
+        .stderr(std::process::Stdio::null())
+        .output()
+    else {
+        return Ok(());
+    };
+    let Ok(status) = serde_json::from_slice::<PensaDaemonStatus>(&output.stdout) else {
+        return Ok(());
+    };
+    match status.mismatch() {
+        Some(mismatch) => Err(mismatch),
+        None => Ok(()),
+    }
+}
+
 fn spawn_daemon(bin: &str, port: &str, root_str: &str, root: &Path) -> io::Result<()> {
     Command::new(bin)
         .args(["daemon", "--port", port, "--project-dir", root_str])
@@ -196,7 +414,7 @@ pub fn export_forma() {
     }
 }
 
-fn daemon_is_reachable(bin: &str, root: &Path) -> bool {
+pub(crate) fn daemon_is_reachable(bin: &str, root: &Path) -> bool {
     Command::new(bin)
         .args(["daemon", "status"])
         .current_dir(root)
@@ -334,4 +552,107 @@ mod tests {
         // Dirty file should still exist
         assert!(tmp.path().join("dirty.txt").exists());
     }
+
+    fn stale_repo(config: &str) -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        setup_git_repo(tmp.path());
+        fs::create_dir_all(tmp.path().join(".sgf/run")).unwrap();
+        fs::write(tmp.path().join(".sgf/config.toml"), config).unwrap();
+        fs::write(tmp.path().join(".sgf/run/stale-loop.pid"), "4000000").unwrap();
+        fs::write(tmp.path().join("README.md"), "modified").unwrap();
+        fs::create_dir_all(tmp.path().join("notes")).unwrap();
+        fs::write(tmp.path().join("notes/todo.md"), "keep me").unwrap();
+        fs::write(tmp.path().join("untracked.txt"), "scratch").unwrap();
+        tmp
+    }
+
+    #[test]
+    fn recovery_plan_honors_exclusions() {
+        let tmp = stale_repo("[recovery]\nexclude = [\"notes/\"]\n");
+        let root = tmp.path();
+
+        let plan = plan(root, None).unwrap().unwrap();
+        assert_eq!(plan.strategy, RecoveryStrategy::Reset);
+        assert_eq!(plan.stale_loops, ["stale-loop"]);
+        assert_eq!(plan.changes, [" M README.md", "?? untracked.txt"]);
+        let described = plan.describe();
+        assert!(described.contains(&"restore: README.md".to_string()));
+        assert!(described.contains(&"delete: untracked.txt".to_string()));
+        assert!(described.contains(&"never touched: .sgf, notes/".to_string()));
+
+        assert_eq!(
+            pre_launch_recovery(root).unwrap(),
+            RecoveryOutcome::Recovered
+        );
+        assert!(!root.join("untracked.txt").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("notes/todo.md")).unwrap(),
+            "keep me"
+        );
+        assert!(root.join(".sgf/config.toml").exists());
+    }
+
+    #[test]
+    fn recovery_stash_keeps_changes_under_labeled_ref() {
+        let tmp = stale_repo("[recovery]\nstrategy = \"stash\"\n");
+        let root = tmp.path();
+
+        let RecoveryOutcome::Stashed(reference) = pre_launch_recovery(root).unwrap() else {
+            panic!("expected a stash");
+        };
+        assert!(reference.starts_with("refs/sgf/recovery/"), "{reference}");
+        assert!(!root.join("untracked.txt").exists());
+        assert!(!root.join(".sgf/run/stale-loop.pid").exists());
+
+        git(root, &["stash", "apply", &reference]).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("README.md")).unwrap(),
+            "modified"
+        );
+        assert!(root.join("untracked.txt").exists());
+    }
+
+    #[test]
+    fn recovery_abort_leaves_everything_in_place() {
+        let tmp = stale_repo("");
+        let root = tmp.path();
+
+        let plan = plan(root, Some(RecoveryStrategy::Abort)).unwrap().unwrap();
+        let outcome = apply_with(root, &plan, |_| Ok(RecoveryStrategy::Abort)).unwrap();
+        assert_eq!(outcome, RecoveryOutcome::Aborted);
+        assert!(root.join(".sgf/run/stale-loop.pid").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("README.md")).unwrap(),
+            "modified"
+        );
+        assert!(root.join("untracked.txt").exists());
+    }
+
+    #[test]
+    fn pensa_mismatch_names_who_needs_which_version() {
+        let status = |min, max, cli_api_version| PensaDaemonStatus {
+            url: "http://localhost:4000".to_string(),
+            version: Some("0.3.0".to_string()),
+            api_version: Some(max),
+            min_api_version: Some(min),
+            cli_version: "0.2.0".to_string(),
+            cli_api_version,
+        };
+        assert_eq!(status(1, 1, 1).mismatch(), None);
+        assert_eq!(
+            status(2, 3, 1).mismatch().unwrap(),
+            "pensa daemon at http://localhost:4000 (pensa 0.3.0) serves API v2-v3, but pn 0.2.0 needs v1"
+        );
+        assert!(
+            status(2, 3, 3)
+                .mismatch()
+                .unwrap()
+                .ends_with("prompts need v1")
+        );
+        let unversioned = PensaDaemonStatus {
+            api_version: None,
+            ..status(1, 1, 5)
+        };
+        assert_eq!(unversioned.mismatch(), None);
+    }
 }
@@ -0,0 +1,116 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+
+const CACHE_DIR: &str = "cache/prompts";
+
+const FETCH_TIMEOUT_SECS: &str = "30";
+
+pub fn is_url(command: &str) -> bool {
+    ["http://", "https://", "file://"]
+        .iter()
+        .any(|scheme| command.starts_with(scheme))
+}
+
+pub fn cache_path(url: &str) -> Option<PathBuf> {
+    use sha2::{Digest, Sha256};
+    let hash: [u8; 32] = Sha256::digest(url.as_bytes()).into();
+    let hex: String = hash[..8].iter().map(|b| format!("{b:02x}")).collect();
+    let name: String = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        .collect();
+    let file = if name.is_empty() {
+        format!("{hex}.md")
+    } else {
+        format!("{hex}-{name}")
+    };
+    crate::prompt::global_sgf_dir().map(|d| d.join(CACHE_DIR).join(file))
+}
+
+/// The local file for the prompt at `url`. A cached copy is used unless
+/// `refresh` is set; when a refresh fails, the cached copy is used with a
+/// warning rather than stopping the run.
+pub fn fetch(url: &str, refresh: bool) -> io::Result<PathBuf> {
+    if let Some(path) = url.strip_prefix("file://") {
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("prompt not found: {}", path.display()),
+            ));
+        }
+        return Ok(path);
+    }
+
+    let cached = cache_path(url)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
+    if cached.is_file() && !refresh {
+        return Ok(cached);
+    }
+    match download(url) {
+        Ok(body) => {
+            if let Some(dir) = cached.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&cached, body)?;
+            Ok(cached)
+        }
+        Err(e) if cached.is_file() => {
+            crate::style::print_warning(&format!("{e}; using the cached copy"));
+            Ok(cached)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn download(url: &str) -> io::Result<Vec<u8>> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", FETCH_TIMEOUT_SECS, url])
+        .output()
+        .map_err(|e| io::Error::other(format!("failed to run curl: {e}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "failed to fetch {url}: {}",
+            stderr.trim()
+        )));
+    }
+    if output.stdout.is_empty() {
+        return Err(io::Error::other(format!("{url} returned an empty prompt")));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_resolve_to_local_prompts() {
+        assert!(is_url("https://example.com/prompts/build.md"));
+        assert!(is_url("file:///tmp/build.md"));
+        assert!(!is_url("build"));
+        assert!(!is_url("prompts/build.md"));
+
+        let path = cache_path("https://example.com/team/build.md?ref=main").unwrap();
+        let name = path.file_name().unwrap().to_string_lossy();
+        assert!(name.ends_with("-build.md"), "{name}");
+        assert_ne!(
+            cache_path("https://example.com/other/build.md"),
+            Some(path.clone())
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let prompt = dir.path().join("build.md");
+        fs::write(&prompt, "Build it.\n").unwrap();
+        let url = format!("file://{}", prompt.display());
+        assert_eq!(fetch(&url, false).unwrap(), prompt);
+        assert!(fetch("file:///no/such/prompt.md", true).is_err());
+    }
+}
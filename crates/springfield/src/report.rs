@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::logs::split_stamp;
+use crate::loop_mgmt;
+
+static ITERATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^╭─ Iteration \d+ of \d+").unwrap());
+static TOOL_CALL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s+─ (\S+)\s{2}(.*)$").unwrap());
+static TOOL_ERROR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s+✗ ").unwrap());
+static USAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Input: (\d+) tokens · Output: (\d+) tokens(?: · Cost: \$([0-9.]+))?").unwrap()
+});
+
+const GATES: &[(&str, &str)] = &[
+    (
+        "test",
+        r"\b(cargo (test|nextest)|(npm|pnpm|yarn) (run )?test|pytest|go test|make test)\b",
+    ),
+    (
+        "lint",
+        r"\b(cargo clippy|(npm|pnpm|yarn) (run )?lint|eslint|ruff|golangci-lint)\b",
+    ),
+    (
+        "build",
+        r"\b(cargo build|(npm|pnpm|yarn) (run )?build|go build)\b",
+    ),
+    ("hooks", r"\b(prek|pre-commit)\b"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GateStatus {
+    pub gate: String,
+    pub runs: u32,
+    pub last_result: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LoopReport {
+    pub loop_id: String,
+    pub stage: Option<String>,
+    pub spec: Option<String>,
+    pub iterations: u32,
+    pub commits: Vec<String>,
+    pub files_touched: Vec<String>,
+    pub gates: Vec<GateStatus>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: Option<f64>,
+    pub completion: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct LogSummary {
+    pub(crate) iterations: u32,
+    edited: Vec<String>,
+    gates: Vec<GateStatus>,
+    pub(crate) input_tokens: u64,
+    pub(crate) output_tokens: u64,
+    pub(crate) cost_usd: Option<f64>,
+}
+
+fn parse_log(text: &str) -> LogSummary {
+    let gate_patterns: Vec<(&str, Regex)> = GATES
+        .iter()
+        .map(|(name, pat)| (*name, Regex::new(pat).unwrap()))
+        .collect();
+    let mut summary = LogSummary::default();
+    let mut gates: BTreeMap<&str, GateStatus> = BTreeMap::new();
+    let mut last_gate: Option<&str> = None;
+
+    for raw in text.lines() {
+        let line = split_stamp(raw).1;
+        if ITERATION.is_match(line) {
+            summary.iterations += 1;
+        } else if let Some(c) = TOOL_CALL.captures(line) {
+            let (tool, detail) = (&c[1], &c[2]);
+            last_gate = None;
+            if matches!(tool, "Edit" | "Write") && !summary.edited.iter().any(|f| f == detail) {
+                summary.edited.push(detail.to_string());
+            }
+            if tool == "Bash"
+                && let Some((name, _)) = gate_patterns.iter().find(|(_, re)| re.is_match(detail))
+            {
+                let gate = gates.entry(name).or_insert_with(|| GateStatus {
+                    gate: name.to_string(),
+                    runs: 0,
+                    last_result: String::new(),
+                });
+                gate.runs += 1;
+                gate.last_result = "passed".to_string();
+                last_gate = Some(name);
+            }
+        } else if TOOL_ERROR.is_match(line) {
+            if let Some(gate) = last_gate.and_then(|name| gates.get_mut(name)) {
+                gate.last_result = "failed".to_string();
+            }
+        } else if let Some(c) = USAGE.captures(line) {
+            summary.input_tokens += c[1].parse::<u64>().unwrap_or(0);
+            summary.output_tokens += c[2].parse::<u64>().unwrap_or(0);
+            if let Some(cost) = c.get(3).and_then(|m| m.as_str().parse::<f64>().ok()) {
+                *summary.cost_usd.get_or_insert(0.0) += cost;
+            }
+        }
+    }
+    summary.gates = gates.into_values().collect();
+    summary
+}
+
+fn git_lines(root: &Path, args: &[&str]) -> Vec<String> {
+    Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .filter(|l| !l.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn read_log(root: &Path, loop_id: &str) -> LogSummary {
+    let log_path = loop_mgmt::log_path(root, loop_id);
+    parse_log(&fs::read_to_string(log_path).unwrap_or_default())
+}
+
+pub fn build(root: &Path, loop_id: &str) -> io::Result<LoopReport> {
+    let log_path = loop_mgmt::log_path(root, loop_id);
+    let record = loop_mgmt::read_loop_record(root, loop_id)?;
+    let text = match fs::read_to_string(&log_path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && record.is_some() => String::new(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no log or loop record for {loop_id}"),
+            ));
+        }
+        Err(e) => return Err(e),
+    };
+    let log = parse_log(&text);
+
+    let mut report = LoopReport {
+        loop_id: loop_id.to_string(),
+        iterations: log.iterations,
+        gates: log.gates,
+        input_tokens: log.input_tokens,
+        output_tokens: log.output_tokens,
+        cost_usd: log.cost_usd,
+        completion: "unknown".to_string(),
+        ..Default::default()
+    };
+    let mut files = Vec::new();
+    if let Some(record) = record {
+        report.stage = Some(record.stage.clone());
+        report.spec = record.spec.clone();
+        report.iterations = report.iterations.max(record.iterations_completed);
+        report.completion = record.outcome().to_string();
+        if let (Some(before), Some(after)) = (&record.head_before, &record.head_after)
+            && before != after
+        {
+            let range = format!("{before}..{after}");
+            report.commits = git_lines(root, &["log", "--reverse", "--format=%h %s", &range]);
+            files = git_lines(root, &["diff", "--name-only", before, after]);
+        }
+    }
+    let prefix = format!("{}/", root.display());
+    for path in log.edited {
+        let rel = path.strip_prefix(&prefix).unwrap_or(&path).to_string();
+        if !files.contains(&rel) {
+            files.push(rel);
+        }
+    }
+    files.sort();
+    report.files_touched = files;
+    Ok(report)
+}
+
+impl LoopReport {
+    pub fn to_markdown(&self) -> String {
+        let dash = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+        let mut md = format!("# Loop report: {}\n\n", self.loop_id);
+        md.push_str(&format!("- **Stage:** {}\n", dash(&self.stage)));
+        md.push_str(&format!("- **Spec:** {}\n", dash(&self.spec)));
+        md.push_str(&format!("- **Completion:** {}\n", self.completion));
+        md.push_str(&format!("- **Iterations:** {}\n", self.iterations));
+        md.push_str(&format!(
+            "- **Tokens:** {} in · {} out\n",
+            self.input_tokens, self.output_tokens
+        ));
+        if let Some(cost) = self.cost_usd {
+            md.push_str(&format!("- **Cost:** ${cost:.2}\n"));
+        }
+
+        md.push_str(&format!("\n## Commits ({})\n\n", self.commits.len()));
+        for c in &self.commits {
+            md.push_str(&format!("- {c}\n"));
+        }
+        md.push_str(&format!(
+            "\n## Files touched ({})\n\n",
+            self.files_touched.len()
+        ));
+        for f in &self.files_touched {
+            md.push_str(&format!("- `{f}`\n"));
+        }
+        md.push_str("\n## Gates\n\n");
+        if self.gates.is_empty() {
+            md.push_str("No test, lint, build, or hook commands were run.\n");
+        } else {
+            md.push_str("| Gate | Runs | Last result |\n|------|------|-------------|\n");
+            for g in &self.gates {
+                md.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    g.gate, g.runs, g.last_result
+                ));
+            }
+        }
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const LOG: &str = "\
+2026-10-16T10:00:00Z ╭─ Iteration 1 of 3 [build-auth-1] ──────╮
+2026-10-16T10:00:01Z   ─ Edit  /repo/src/auth.rs
+2026-10-16T10:00:02Z   ─ Bash  cargo test -p auth
+2026-10-16T10:00:03Z   ✗ test login ... FAILED
+2026-10-16T10:00:04Z   ─ Edit  /repo/src/auth.rs
+2026-10-16T10:00:05Z   ─ Bash  cargo test -p auth
+2026-10-16T10:00:06Z   ─ Bash  cargo clippy -- -D warnings
+2026-10-16T10:00:07Z   ✗ error: unused variable
+2026-10-16T10:00:08Z   Input: 1000 tokens · Output: 200 tokens · Cost: $0.1000
+2026-10-16T10:01:00Z ╭─ Iteration 2 of 3 [build-auth-1] ──────╮
+2026-10-16T10:01:01Z   ─ Write  /repo/src/session.rs
+2026-10-16T10:01:02Z   Input: 500 tokens · Output: 50 tokens · Cost: $0.0250
+";
+
+    #[test]
+    fn parse_log_counts_iterations_gates_and_usage() {
+        let summary = parse_log(LOG);
+        assert_eq!(summary.iterations, 2);
+        assert_eq!(
+            summary.edited,
+            ["/repo/src/auth.rs", "/repo/src/session.rs"]
+        );
+        assert_eq!(
+            summary.gates,
+            [
+                GateStatus {
+                    gate: "lint".to_string(),
+                    runs: 1,
+                    last_result: "failed".to_string(),
+                },
+                GateStatus {
+                    gate: "test".to_string(),
+                    runs: 2,
+                    last_result: "passed".to_string(),
+                },
+            ]
+        );
+        assert_eq!((summary.input_tokens, summary.output_tokens), (1500, 250));
+        assert!((summary.cost_usd.unwrap() - 0.125).abs() < 1e-9);
+    }
+
+    #[test]
+    fn build_uses_record_for_outcome_and_reports_missing_loops() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let err = build(root, "nope").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        loop_mgmt::create_log_file(root, "build-auth-1").unwrap();
+        fs::write(root.join(".sgf/logs/build-auth-1.log"), LOG).unwrap();
+        loop_mgmt::begin_loop_record(root, root, "build-auth-1", "build", Some("auth"));
+        loop_mgmt::finish_loop_record(root, root, "build-auth-1", Some(2), 2);
+
+        let report = build(root, "build-auth-1").unwrap();
+        assert_eq!(report.completion, "exhausted");
+        assert_eq!(report.spec.as_deref(), Some("auth"));
+        assert!(report.commits.is_empty(), "not a git checkout");
+        let md = report.to_markdown();
+        assert!(md.starts_with("# Loop report: build-auth-1\n"));
+        assert!(md.contains("- **Cost:** $0.12\n"));
+        assert!(md.contains("| lint | 1 | failed |"));
+        assert!(md.contains("- `/repo/src/session.rs`"));
+    }
+}
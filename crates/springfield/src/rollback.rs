@@ -0,0 +1,324 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::loop_mgmt::{self, LoopRecord};
+use crate::next::pn;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rollback {
+    pub range: String,
+    pub commits: usize,
+    pub branch: String,
+    pub reset: bool,
+    pub reopened: Vec<String>,
+    pub pensa_error: Option<String>,
+}
+
+fn git(root: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| io::Error::other(format!("failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn is_ancestor(root: &Path, commit: &str, of: &str) -> bool {
+    git(root, &["merge-base", "--is-ancestor", commit, of]).is_ok()
+}
+
+pub fn rollback_branch(loop_id: &str) -> String {
+    format!("sgf/rollback-{loop_id}")
+}
+
+pub fn run(root: &Path, loop_id: &str, reset: bool) -> io::Result<Rollback> {
+    let record = loop_mgmt::read_loop_record(root, loop_id)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no loop {loop_id}")))?;
+    if loop_mgmt::list_running_loops(root)
+        .iter()
+        .any(|l| l.loop_id == loop_id)
+    {
+        return Err(io::Error::other(format!(
+            "{loop_id} is still running; stop it first with `sgf stop {loop_id}`"
+        )));
+    }
+    let (Some(before), Some(after)) = (&record.head_before, &record.head_after) else {
+        return Err(io::Error::other(format!(
+            "{loop_id} has no HEAD before and after in its loop record"
+        )));
+    };
+    if before == after {
+        return Err(io::Error::other(format!("{loop_id} made no commits")));
+    }
+    let range = format!("{before}..{after}");
+    let commits = git(root, &["rev-list", "--count", &range])?
+        .parse()
+        .unwrap_or_default();
+
+    let branch = if reset {
+        reset_branch(root, &record, before, after)?
+    } else {
+        revert_on_branch(root, loop_id, &range, after)?
+    };
+
+    let (reopened, pensa_error) = match reopen_closed(root, &record) {
+        Ok(ids) => (ids, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    Ok(Rollback {
+        range,
+        commits,
+        branch,
+        reset,
+        reopened,
+        pensa_error,
+    })
+}
+
+/// Reverts the range on a new branch from HEAD, in a worktree of its own
+/// so the checkout is never touched. Nothing is kept if a revert conflicts.
+fn revert_on_branch(root: &Path, loop_id: &str, range: &str, after: &str) -> io::Result<String> {
+    if !is_ancestor(root, after, "HEAD") {
+        return Err(io::Error::other(format!(
+            "HEAD does not contain {loop_id}'s commits; check out the branch they landed on, \
+             or use --reset to rewind the loop's own branch"
+        )));
+    }
+    let branch = rollback_branch(loop_id);
+    if git(root, &["rev-parse", "--verify", "--quiet", &branch]).is_ok() {
+        return Err(io::Error::other(format!("{branch} already exists")));
+    }
+    let worktree = root
+        .join(".sgf/worktrees")
+        .join(format!("rollback-{loop_id}"));
+    let worktree_str = worktree.to_string_lossy().into_owned();
+    git(
+        root,
+        &[
+            "worktree",
+            "add",
+            "--quiet",
+            "-b",
+            &branch,
+            &worktree_str,
+            "HEAD",
+        ],
+    )?;
+    let reverted = git(&worktree, &["revert", "--no-edit", range]);
+    if reverted.is_err() {
+        let _ = git(&worktree, &["revert", "--abort"]);
+    }
+    git(root, &["worktree", "remove", "--force", &worktree_str])?;
+    if let Err(e) = reverted {
+        let _ = git(root, &["branch", "--quiet", "-D", &branch]);
+        return Err(io::Error::other(format!(
+            "reverting {range} did not apply cleanly; nothing was changed ({e})"
+        )));
+    }
+    Ok(branch)
+}
+
+fn reset_branch(root: &Path, record: &LoopRecord, before: &str, after: &str) -> io::Result<String> {
+    let current = git(root, &["symbolic-ref", "--quiet", "--short", "HEAD"]).ok();
+    let branch = record
+        .branch
+        .clone()
+        .or_else(|| current.clone().filter(|b| b.starts_with("sgf/")))
+        .ok_or_else(|| {
+            io::Error::other(format!(
+                "{} ran on no branch of its own; roll it back without --reset",
+                record.loop_id
+            ))
+        })?;
+    if !is_ancestor(root, after, &branch) {
+        return Err(io::Error::other(format!(
+            "{branch} no longer contains {}'s last commit",
+            record.loop_id
+        )));
+    }
+    if current.as_deref() == Some(branch.as_str()) {
+        if !git(root, &["status", "--porcelain"])?.is_empty() {
+            return Err(io::Error::other(format!(
+                "{branch} has uncommitted changes; commit or stash them first"
+            )));
+        }
+        git(root, &["reset", "--hard", "--quiet", before])?;
+    } else {
+        git(root, &["branch", "--force", &branch, before])?;
+    }
+    Ok(branch)
+}
+
+#[derive(Deserialize)]
+struct ClosedIssue {
+    id: String,
+    #[serde(default)]
+    closed_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IssueEvent {
+    event_type: String,
+    #[serde(default)]
+    actor: Option<String>,
+}
+
+fn parse_time(t: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(t)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Whether the loop's agent was the last to close the issue. `pn history`
+/// lists events newest first.
+fn closed_by(events: &[IssueEvent], actor: &str) -> bool {
+    events
+        .iter()
+        .find(|e| e.event_type == "closed")
+        .is_some_and(|e| e.actor.as_deref() == Some(actor))
+}
+
+fn reopen_closed(root: &Path, record: &LoopRecord) -> io::Result<Vec<String>> {
+    let (_, actor) = loop_mgmt::actor_env_var(&record.loop_id);
+    let started = parse_time(&record.started_at);
+    let ended = record.ended_at.as_deref().and_then(parse_time);
+    let closed: Vec<ClosedIssue> =
+        serde_json::from_slice(&pn(root, &["list", "--status", "closed", "--json"])?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let reason = format!("rolled back {}", record.loop_id);
+    let mut reopened = Vec::new();
+    for issue in closed {
+        let Some(closed_at) = issue.closed_at.as_deref().and_then(parse_time) else {
+            continue;
+        };
+        if started.is_some_and(|s| closed_at < s) || ended.is_some_and(|e| closed_at > e) {
+            continue;
+        }
+        let events: Vec<IssueEvent> =
+            serde_json::from_slice(&pn(root, &["history", &issue.id, "--json"])?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if closed_by(&events, &actor) {
+            pn(root, &["reopen", &issue.id, "--reason", &reason, "--json"])?;
+            reopened.push(issue.id);
+        }
+    }
+    Ok(reopened)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn repo() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        git(root, &["init", "--quiet", "-b", "main"]).unwrap();
+        git(root, &["config", "user.email", "test@test.com"]).unwrap();
+        git(root, &["config", "user.name", "Test"]).unwrap();
+        commit(root, "base");
+        tmp
+    }
+
+    fn commit(root: &Path, name: &str) -> String {
+        fs::write(root.join(name), name).unwrap();
+        git(root, &["add", name]).unwrap();
+        git(root, &["commit", "--quiet", "-m", name]).unwrap();
+        git(root, &["rev-parse", "HEAD"]).unwrap()
+    }
+
+    fn event(event_type: &str, actor: &str) -> IssueEvent {
+        IssueEvent {
+            event_type: event_type.to_string(),
+            actor: Some(actor.to_string()),
+        }
+    }
+
+    #[test]
+    fn revert_lands_on_a_new_branch_and_leaves_head_alone() {
+        let tmp = repo();
+        let root = tmp.path();
+        let before = git(root, &["rev-parse", "HEAD"]).unwrap();
+        commit(root, "garbage-1");
+        let after = commit(root, "garbage-2");
+
+        let branch =
+            revert_on_branch(root, "build-1", &format!("{before}..{after}"), &after).unwrap();
+        assert_eq!(branch, "sgf/rollback-build-1");
+        assert_eq!(git(root, &["rev-parse", "HEAD"]).unwrap(), after);
+        assert!(root.join("garbage-2").exists());
+        assert_eq!(
+            git(root, &["ls-tree", "--name-only", &branch]).unwrap(),
+            "base"
+        );
+        assert!(!root.join(".sgf/worktrees/rollback-build-1").exists());
+
+        let err =
+            revert_on_branch(root, "build-1", &format!("{before}..{after}"), &after).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{err}");
+    }
+
+    #[test]
+    fn reset_rewinds_the_loop_branch() {
+        let tmp = repo();
+        let root = tmp.path();
+        let before = git(root, &["rev-parse", "HEAD"]).unwrap();
+        git(root, &["switch", "--quiet", "-c", "sgf/build-1"]).unwrap();
+        let after = commit(root, "garbage");
+        git(root, &["switch", "--quiet", "main"]).unwrap();
+        let record = LoopRecord {
+            loop_id: "build-1".to_string(),
+            stage: "build".to_string(),
+            spec: None,
+            started_at: "2026-10-01T00:00:00Z".to_string(),
+            attempt_started_at: None,
+            ended_at: None,
+            exit_code: Some(0),
+            iterations_completed: 1,
+            head_before: Some(before.clone()),
+            head_after: Some(after.clone()),
+            branch: Some("sgf/build-1".to_string()),
+            base_branch: Some("main".to_string()),
+            attempt: 1,
+            incidents: Vec::new(),
+        };
+
+        assert_eq!(
+            reset_branch(root, &record, &before, &after).unwrap(),
+            "sgf/build-1"
+        );
+        assert_eq!(git(root, &["rev-parse", "sgf/build-1"]).unwrap(), before);
+        let err = reset_branch(root, &record, &before, &after).unwrap_err();
+        assert!(err.to_string().contains("no longer contains"), "{err}");
+    }
+
+    #[test]
+    fn only_issues_last_closed_by_the_loop_count() {
+        let actor = "sgf-build-1";
+        assert!(closed_by(
+            &[event("closed", actor), event("created", "me")],
+            actor
+        ));
+        assert!(!closed_by(
+            &[
+                event("closed", "me"),
+                event("reopened", "me"),
+                event("closed", actor)
+            ],
+            actor
+        ));
+        assert!(!closed_by(&[event("created", actor)], actor));
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::next::pn;
+
+pub const PLAN_KEY: &str = "task-plan";
+
+#[derive(Debug, Clone, Deserialize)]
+struct FiledIssue {
+    id: String,
+    title: String,
+    spec: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedSummary {
+    pub created: Vec<(String, String)>,
+    pub skipped: usize,
+}
+
+impl SeedSummary {
+    pub fn describe(&self) -> String {
+        let mut line = format!("task plan: {} filed", self.created.len());
+        if self.skipped > 0 {
+            line.push_str(&format!(", {} already in pensa", self.skipped));
+        }
+        line
+    }
+}
+
+fn plan_json(text: &str) -> &str {
+    text.split_once("```json")
+        .and_then(|(_, rest)| rest.split_once("```"))
+        .map_or(text, |(block, _)| block)
+        .trim()
+}
+
+pub fn parse_plan(text: &str) -> io::Result<Vec<Value>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let tasks: Vec<Value> = serde_json::from_str(plan_json(text))
+        .map_err(|e| invalid(format!("task plan is not a JSON array: {e}")))?;
+    for (n, task) in tasks.iter().enumerate() {
+        if task["key"].as_str().is_none() || task["title"].as_str().is_none() {
+            return Err(invalid(format!("task {} needs a key and a title", n + 1)));
+        }
+    }
+    Ok(tasks)
+}
+
+/// Drops tasks already filed under their spec (`spec` when they name none)
+/// and points deps on their keys at the existing issues instead. Returns
+/// how many were dropped.
+fn drop_filed(tasks: &mut Vec<Value>, filed: &[FiledIssue], spec: Option<&str>) -> usize {
+    let mut existing: HashMap<String, String> = HashMap::new();
+    tasks.retain(|task| {
+        let task_spec = task["spec"].as_str().or(spec);
+        let found = filed
+            .iter()
+            .find(|i| i.title == task["title"] && i.spec.as_deref() == task_spec);
+        if let Some(issue) = found {
+            existing.insert(task["key"].as_str().unwrap().to_string(), issue.id.clone());
+        }
+        found.is_none()
+    });
+    for task in tasks.iter_mut() {
+        if let Some(deps) = task["deps"].as_array_mut() {
+            for dep in deps {
+                if let Some(id) = dep.as_str().and_then(|key| existing.get(key)) {
+                    *dep = Value::String(id.clone());
+                }
+            }
+        }
+    }
+    existing.len()
+}
+
+pub fn seed(root: &Path, spec: Option<&str>, path: &Path) -> io::Result<SeedSummary> {
+    let mut tasks = parse_plan(&std::fs::read_to_string(path)?)?;
+    let filed: Vec<FiledIssue> = serde_json::from_slice(&pn(root, &["list", "--json"])?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let skipped = drop_filed(&mut tasks, &filed, spec);
+    if tasks.is_empty() {
+        return Ok(SeedSummary {
+            created: Vec::new(),
+            skipped,
+        });
+    }
+
+    let json_path = path.with_extension("json");
+    std::fs::write(&json_path, serde_json::to_string_pretty(&tasks)?)?;
+    let json_path = json_path.to_string_lossy();
+    let mut args = vec!["seed", &*json_path, "--json"];
+    if let Some(spec) = spec {
+        args.extend(["--spec", spec]);
+    }
+    let seeded: Value = serde_json::from_slice(&pn(root, &args)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let created = seeded["issues"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|issue| {
+            let field = |name: &str| issue[name].as_str().unwrap_or_default().to_string();
+            (field("id"), field("title"))
+        })
+        .collect();
+    Ok(SeedSummary { created, skipped })
+}
+
+pub fn on_produced(
+    root: &Path,
+    key: &str,
+    path: &Path,
+    spec: Option<&str>,
+) -> io::Result<Option<SeedSummary>> {
+    if key != PLAN_KEY {
+        return Ok(None);
+    }
+    seed(root, spec, path).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fenced_plans_parse_and_filed_tasks_become_deps() {
+        let plan = "# Plan\n\n```json\n[\n\
+            {\"key\": \"schema\", \"title\": \"Users table\"},\n\
+            {\"key\": \"api\", \"title\": \"Login endpoint\", \"deps\": [\"schema\"]}\n\
+            ]\n```\n\nNotes after the plan.\n";
+        let mut tasks = parse_plan(plan).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert!(parse_plan("[{\"title\": \"no key\"}]").is_err());
+        assert!(parse_plan("- not json").is_err());
+
+        let filed = [FiledIssue {
+            id: "pn-1a2b".to_string(),
+            title: "Users table".to_string(),
+            spec: Some("auth".to_string()),
+        }];
+        assert_eq!(drop_filed(&mut tasks, &filed, Some("auth")), 1);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0]["deps"], serde_json::json!(["pn-1a2b"]));
+
+        let mut other_spec = parse_plan(plan).unwrap();
+        assert_eq!(drop_filed(&mut other_spec, &filed, Some("billing")), 0);
+    }
+}
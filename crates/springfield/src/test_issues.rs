@@ -0,0 +1,295 @@
+use std::io;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::next::pn;
+
+pub const PLAN_KEY: &str = "test-plan";
+pub const RESULTS_KEY: &str = "test-results";
+
+static ISSUE_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^pn-[0-9a-f]+$").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedTest {
+    pub title: String,
+    pub deps: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reported {
+    pub outcome: Outcome,
+    pub test: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub created: usize,
+    pub closed: usize,
+    pub reopened: usize,
+    pub unknown: Vec<String>,
+}
+
+impl SyncSummary {
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        for (n, what) in [
+            (self.created, "created"),
+            (self.closed, "closed"),
+            (self.reopened, "reopened"),
+        ] {
+            if n > 0 {
+                parts.push(format!("{n} {what}"));
+            }
+        }
+        if parts.is_empty() {
+            parts.push("no changes".to_string());
+        }
+        let mut line = format!("test issues: {}", parts.join(", "));
+        if !self.unknown.is_empty() {
+            line.push_str(&format!("; unknown: {}", self.unknown.join(", ")));
+        }
+        line
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TestIssue {
+    id: String,
+    title: String,
+    status: String,
+}
+
+fn bullet(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let item = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))?
+        .trim_start();
+    let item = item
+        .strip_prefix("[ ] ")
+        .or_else(|| item.strip_prefix("[x] "))
+        .unwrap_or(item);
+    Some(item.trim())
+}
+
+pub fn parse_plan(text: &str) -> Vec<PlannedTest> {
+    text.lines()
+        .filter_map(bullet)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let deps = item
+                .strip_suffix(')')
+                .and_then(|rest| rest.rsplit_once('('))
+                .and_then(|(title, inner)| {
+                    let ids: Vec<String> = inner
+                        .split([',', ' '])
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    (!ids.is_empty() && ids.iter().all(|id| ISSUE_ID.is_match(id)))
+                        .then(|| (title.trim_end(), ids))
+                });
+            match deps {
+                Some((title, deps)) => PlannedTest {
+                    title: title.to_string(),
+                    deps,
+                },
+                None => PlannedTest {
+                    title: item.to_string(),
+                    deps: Vec::new(),
+                },
+            }
+        })
+        .collect()
+}
+
+pub fn parse_results(text: &str) -> Vec<Reported> {
+    text.lines()
+        .filter_map(|line| {
+            let line = bullet(line).unwrap_or(line.trim());
+            let (word, rest) = line.split_once(char::is_whitespace)?;
+            let outcome = match word.to_ascii_lowercase().trim_end_matches(':') {
+                "pass" | "passed" => Outcome::Pass,
+                "fail" | "failed" => Outcome::Fail,
+                _ => return None,
+            };
+            let rest = rest.trim();
+            let (test, detail) = match rest.split_once(": ") {
+                Some((test, detail)) => (test.trim(), Some(detail.trim().to_string())),
+                None => (rest, None),
+            };
+            (!test.is_empty()).then(|| Reported {
+                outcome,
+                test: test.to_string(),
+                detail,
+            })
+        })
+        .collect()
+}
+
+fn test_issues(root: &Path, spec: Option<&str>) -> io::Result<Vec<TestIssue>> {
+    let mut args = vec!["list", "-t", "test", "--json"];
+    if let Some(spec) = spec {
+        args.extend(["--spec", spec]);
+    }
+    serde_json::from_slice(&pn(root, &args)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn sync_plan(root: &Path, spec: Option<&str>, plan: &str) -> io::Result<SyncSummary> {
+    let existing = test_issues(root, spec)?;
+    let mut summary = SyncSummary::default();
+    for test in parse_plan(plan) {
+        if existing.iter().any(|i| i.title == test.title) {
+            continue;
+        }
+        let mut args = vec!["create", &test.title, "-t", "test", "--json"];
+        if let Some(spec) = spec {
+            args.extend(["--spec", spec]);
+        }
+        for dep in &test.deps {
+            args.extend(["--dep", dep]);
+        }
+        pn(root, &args)?;
+        summary.created += 1;
+    }
+    Ok(summary)
+}
+
+/// Closes test issues reported as passing and reopens closed ones reported
+/// as failing. A failure on a still-open issue is recorded as a comment.
+pub fn report_results(
+    root: &Path,
+    spec: Option<&str>,
+    run_id: &str,
+    results: &str,
+) -> io::Result<SyncSummary> {
+    let issues = test_issues(root, spec)?;
+    let mut summary = SyncSummary::default();
+    for reported in parse_results(results) {
+        let Some(issue) = issues
+            .iter()
+            .find(|i| i.id == reported.test || i.title == reported.test)
+        else {
+            summary.unknown.push(reported.test);
+            continue;
+        };
+        let closed = issue.status == "closed";
+        match reported.outcome {
+            Outcome::Pass if !closed => {
+                let reason = format!("passed in {run_id}");
+                pn(root, &["close", &issue.id, "--reason", &reason, "--json"])?;
+                summary.closed += 1;
+            }
+            Outcome::Fail => {
+                let reason = match &reported.detail {
+                    Some(detail) => format!("failed in {run_id}: {detail}"),
+                    None => format!("failed in {run_id}"),
+                };
+                if closed {
+                    pn(root, &["reopen", &issue.id, "--reason", &reason, "--json"])?;
+                    summary.reopened += 1;
+                } else {
+                    pn(root, &["comment", "add", &issue.id, &reason, "--json"])?;
+                }
+            }
+            Outcome::Pass => {}
+        }
+    }
+    Ok(summary)
+}
+
+pub fn on_produced(
+    root: &Path,
+    key: &str,
+    path: &Path,
+    spec: Option<&str>,
+    run_id: &str,
+) -> io::Result<Option<SyncSummary>> {
+    if key != PLAN_KEY && key != RESULTS_KEY {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(path)?;
+    let summary = if key == PLAN_KEY {
+        sync_plan(root, spec, &text)?
+    } else {
+        report_results(root, spec, run_id, &text)?
+    };
+    Ok(Some(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_bullets_become_tests_with_deps() {
+        let plan = "# Test plan\n\n\
+            - Login rejects a bad password (pn-1a2b, pn-3c4d)\n\
+            * [ ] Session expires after an hour (pn-5e6f)\n\
+            - Logout clears cookies (see RFC 6265)\n\
+            Not a bullet\n";
+        assert_eq!(
+            parse_plan(plan),
+            [
+                PlannedTest {
+                    title: "Login rejects a bad password".to_string(),
+                    deps: vec!["pn-1a2b".to_string(), "pn-3c4d".to_string()],
+                },
+                PlannedTest {
+                    title: "Session expires after an hour".to_string(),
+                    deps: vec!["pn-5e6f".to_string()],
+                },
+                PlannedTest {
+                    title: "Logout clears cookies (see RFC 6265)".to_string(),
+                    deps: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn results_lines_parse_ids_titles_and_details() {
+        let results = "pass pn-1a2b\n- FAIL: Session expires after an hour: still valid at 61m\nskipped pn-9\n";
+        assert_eq!(
+            parse_results(results),
+            [
+                Reported {
+                    outcome: Outcome::Pass,
+                    test: "pn-1a2b".to_string(),
+                    detail: None,
+                },
+                Reported {
+                    outcome: Outcome::Fail,
+                    test: "Session expires after an hour".to_string(),
+                    detail: Some("still valid at 61m".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_describes_changes() {
+        let summary = SyncSummary {
+            closed: 2,
+            reopened: 1,
+            unknown: vec!["pn-dead".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            summary.describe(),
+            "test issues: 2 closed, 1 reopened; unknown: pn-dead"
+        );
+        assert_eq!(SyncSummary::default().describe(), "test issues: no changes");
+    }
+}
@@ -0,0 +1,384 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::SgfConfig;
+use crate::style;
+
+pub const BASE_DIR: &str = ".sgf/upgrade";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub path: String,
+    pub source: String,
+    pub local: String,
+    pub template: String,
+    pub base: Option<String>,
+}
+
+impl Candidate {
+    pub fn is_current(&self) -> bool {
+        self.local == self.template
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    Apply,
+    Skip,
+    Merge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Applied,
+    Skipped,
+    Merged { conflicts: usize },
+}
+
+pub(crate) fn base_path(root: &Path, rel: &str) -> PathBuf {
+    root.join(BASE_DIR)
+        .join(rel.strip_prefix(".sgf/").unwrap_or(rel))
+}
+
+pub fn record_base(root: &Path, rel: &str, content: &str) -> io::Result<()> {
+    let path = base_path(root, rel);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)
+}
+
+pub(crate) fn read_optional(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn candidates(root: &Path) -> io::Result<Vec<Candidate>> {
+    let global = crate::prompt::global_sgf_dir();
+    let mut found = Vec::new();
+
+    let mut prompts: Vec<PathBuf> = match fs::read_dir(root.join(".sgf/prompts")) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    prompts.sort();
+    for local_path in prompts {
+        let Some(name) = local_path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some(template_path) = global.as_ref().map(|g| g.join("prompts").join(name)) else {
+            continue;
+        };
+        let Some(template) = read_optional(&template_path)? else {
+            continue;
+        };
+        let path = format!(".sgf/prompts/{name}");
+        found.push(Candidate {
+            local: fs::read_to_string(&local_path)?,
+            base: read_optional(&base_path(root, &path))?,
+            source: format!("~/.sgf/prompts/{name}"),
+            template,
+            path,
+        });
+    }
+
+    let path = ".sgf/BACKPRESSURE.md";
+    if let Some(local) = read_optional(&root.join(path))? {
+        let template = match SgfConfig::load(root)?.profile {
+            Some(profile) => Some((
+                format!("built-in {profile} profile"),
+                profile.backpressure().to_string(),
+            )),
+            None => match global {
+                Some(g) => read_optional(&g.join("BACKPRESSURE.md"))?
+                    .map(|t| ("~/.sgf/BACKPRESSURE.md".to_string(), t)),
+                None => None,
+            },
+        };
+        if let Some((source, template)) = template {
+            found.push(Candidate {
+                path: path.to_string(),
+                source,
+                local,
+                template,
+                base: read_optional(&base_path(root, path))?,
+            });
+        }
+    }
+
+    Ok(found)
+}
+
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(files: &[(&str, &str)]) -> io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!("sgf-upgrade-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir)?;
+        let scratch = Self(dir);
+        for (name, content) in files {
+            fs::write(scratch.0.join(name), content)?;
+        }
+        Ok(scratch)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn diff(candidate: &Candidate) -> io::Result<String> {
+    unified_diff(&candidate.local, &candidate.template)
+}
+
+pub(crate) fn unified_diff(old: &str, new: &str) -> io::Result<String> {
+    let scratch = Scratch::new(&[("old", old), ("new", new)])?;
+    let output = Command::new("git")
+        .args(["diff", "--no-index", "--no-color", "--", "old", "new"])
+        .current_dir(&scratch.0)
+        .output()?;
+    match output.status.code() {
+        Some(0) => return Ok(String::new()),
+        Some(1) => {}
+        _ => {
+            return Err(io::Error::other(format!(
+                "git diff failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let hunks = stdout.find("\n@@").map_or("", |start| &stdout[start + 1..]);
+    Ok(hunks.to_string())
+}
+
+/// Three-way merges the template's changes since the recorded base into
+/// the local copy, returning the result and its conflict count. With no
+/// recorded base every difference is a conflict.
+pub fn merge(candidate: &Candidate) -> io::Result<(String, usize)> {
+    let scratch = Scratch::new(&[
+        ("local", &candidate.local),
+        ("base", candidate.base.as_deref().unwrap_or_default()),
+        ("template", &candidate.template),
+    ])?;
+    let output = Command::new("git")
+        .args(["merge-file", "-p"])
+        .args(["-L", &candidate.path, "-L", "base", "-L", &candidate.source])
+        .args(["local", "base", "template"])
+        .current_dir(&scratch.0)
+        .output()?;
+    match output.status.code() {
+        Some(n) if (0..128).contains(&n) => Ok((
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            n as usize,
+        )),
+        _ => Err(io::Error::other(format!(
+            "git merge-file failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+    }
+}
+
+/// Carries out `choice` for `candidate`. Applying or merging records the
+/// template as the file's new base.
+pub fn resolve(root: &Path, candidate: &Candidate, choice: Choice) -> io::Result<Resolution> {
+    let resolution = match choice {
+        Choice::Skip => return Ok(Resolution::Skipped),
+        Choice::Apply => {
+            fs::write(root.join(&candidate.path), &candidate.template)?;
+            Resolution::Applied
+        }
+        Choice::Merge => {
+            let (merged, conflicts) = merge(candidate)?;
+            fs::write(root.join(&candidate.path), merged)?;
+            Resolution::Merged { conflicts }
+        }
+    };
+    record_base(root, &candidate.path, &candidate.template)?;
+    Ok(resolution)
+}
+
+fn print_diff(candidate: &Candidate, hunks: &str) {
+    println!("{}", style::bold(&format!("--- {}", candidate.path)));
+    println!("{}", style::bold(&format!("+++ {}", candidate.source)));
+    print_hunks(hunks);
+}
+
+pub(crate) fn print_hunks(hunks: &str) {
+    for line in hunks.lines() {
+        let styled = if line.starts_with("@@") {
+            style::cyan(line)
+        } else if line.starts_with('+') {
+            style::green(line)
+        } else if line.starts_with('-') {
+            style::red(line)
+        } else {
+            line.to_string()
+        };
+        println!("{styled}");
+    }
+}
+
+fn ask(candidate: &Candidate) -> io::Result<Choice> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    if !io::stdin().is_terminal() {
+        return Ok(Choice::Skip);
+    }
+    let hint = if candidate.base.is_some() {
+        ""
+    } else {
+        " (no recorded base: merge marks every difference)"
+    };
+    eprint!("{}: [a]pply, [s]kip, or [m]erge{hint}? ", candidate.path);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(match answer.trim() {
+        "a" | "apply" => Choice::Apply,
+        "m" | "merge" => Choice::Merge,
+        _ => Choice::Skip,
+    })
+}
+
+pub fn run(root: &Path, dry_run: bool) -> io::Result<()> {
+    use std::io::IsTerminal;
+
+    if !dry_run && !io::stdin().is_terminal() {
+        style::print_warning("stdin is not a terminal; every file will be skipped");
+    }
+    run_with(root, dry_run, ask).map(|_| ())
+}
+
+fn run_with(
+    root: &Path,
+    dry_run: bool,
+    mut choose: impl FnMut(&Candidate) -> io::Result<Choice>,
+) -> io::Result<Vec<(String, Resolution)>> {
+    let mut resolved = Vec::new();
+    let mut outdated = 0;
+    for candidate in candidates(root)? {
+        if candidate.is_current() {
+            // Files already matching their template become merge bases for
+            // the next upgrade.
+            if !dry_run && candidate.base.as_deref() != Some(&candidate.template) {
+                record_base(root, &candidate.path, &candidate.template)?;
+            }
+            continue;
+        }
+        outdated += 1;
+        print_diff(&candidate, &diff(&candidate)?);
+        if dry_run {
+            continue;
+        }
+        let resolution = resolve(root, &candidate, choose(&candidate)?)?;
+        match resolution {
+            Resolution::Applied => style::print_success(&format!("{}: applied", candidate.path)),
+            Resolution::Skipped => style::print_action(&format!("{}: skipped", candidate.path)),
+            Resolution::Merged { conflicts: 0 } => {
+                style::print_success(&format!("{}: merged", candidate.path))
+            }
+            Resolution::Merged { conflicts } => style::print_warning(&format!(
+                "{}: merged with {conflicts} conflict(s); resolve the markers before the next loop",
+                candidate.path
+            )),
+        }
+        resolved.push((candidate.path, resolution));
+    }
+    if outdated == 0 {
+        style::print_success("prompts and backpressure match the current templates");
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn candidate(local: &str, base: Option<&str>, template: &str) -> Candidate {
+        Candidate {
+            path: ".sgf/prompts/build.md".to_string(),
+            source: "~/.sgf/prompts/build.md".to_string(),
+            local: local.to_string(),
+            template: template.to_string(),
+            base: base.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn diff_shows_hunks_from_local_to_template() {
+        let c = candidate("one\ntwo\n", None, "one\nthree\n");
+        let hunks = diff(&c).unwrap();
+        assert!(hunks.starts_with("@@"), "{hunks}");
+        assert!(hunks.contains("-two\n+three"), "{hunks}");
+        assert_eq!(diff(&candidate("same\n", None, "same\n")).unwrap(), "");
+    }
+
+    #[test]
+    fn merge_keeps_local_edits_and_takes_template_changes() {
+        let base = "intro\n\nrun tests\n\nouttro\n";
+        let local = "intro, customized\n\nrun tests\n\nouttro\n";
+        let template = "intro\n\nrun tests\n\noutro, fixed\n";
+        let (merged, conflicts) = merge(&candidate(local, Some(base), template)).unwrap();
+        assert_eq!(conflicts, 0);
+        assert_eq!(merged, "intro, customized\n\nrun tests\n\noutro, fixed\n");
+
+        let (merged, conflicts) = merge(&candidate(local, None, template)).unwrap();
+        assert!(conflicts > 0);
+        assert!(merged.contains("<<<<<<< .sgf/prompts/build.md"), "{merged}");
+    }
+
+    #[test]
+    fn resolve_records_base_and_candidates_read_it() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join(".sgf")).unwrap();
+        fs::write(root.join(".sgf/config.toml"), "profile = \"python\"\n").unwrap();
+        fs::write(root.join(".sgf/BACKPRESSURE.md"), "mine\n").unwrap();
+
+        let found = candidates(root).unwrap();
+        let backpressure = found
+            .iter()
+            .find(|c| c.path == ".sgf/BACKPRESSURE.md")
+            .unwrap();
+        assert_eq!(backpressure.source, "built-in python profile");
+        assert!(backpressure.base.is_none());
+
+        let resolution = resolve(root, backpressure, Choice::Apply).unwrap();
+        assert_eq!(resolution, Resolution::Applied);
+        let again = candidates(root).unwrap();
+        let backpressure = again
+            .iter()
+            .find(|c| c.path == ".sgf/BACKPRESSURE.md")
+            .unwrap();
+        assert!(backpressure.is_current());
+        assert_eq!(
+            backpressure.base.as_deref(),
+            Some(backpressure.template.as_str())
+        );
+        assert!(root.join(".sgf/upgrade/BACKPRESSURE.md").is_file());
+
+        fs::write(root.join(".sgf/BACKPRESSURE.md"), "edited\n").unwrap();
+        let resolved = run_with(root, false, |_| Ok(Choice::Skip)).unwrap();
+        assert_eq!(
+            resolved,
+            [(".sgf/BACKPRESSURE.md".to_string(), Resolution::Skipped)]
+        );
+        assert_eq!(
+            fs::read_to_string(root.join(".sgf/BACKPRESSURE.md")).unwrap(),
+            "edited\n"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::issues::{self, Bug};
+use crate::next::pn;
+
+pub const FINDINGS_FILE: &str = ".sgf/out/verify-findings.json";
+
+pub const PROMPT_INSTRUCTIONS: &str = "Write every finding to `.sgf/out/verify-findings.json` \
+as a JSON array of objects with a `title`, and optionally a `description`, a `priority` \
+(`p0` to `p3`), a `file`, and a `line`. Write `[]` when nothing is wrong. sgf files each \
+finding as a pensa bug once this iteration ends, so do not create the bugs yourself.";
+
+const PRIORITIES: &[&str] = &["p0", "p1", "p2", "p3"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Finding {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+}
+
+impl Finding {
+    fn location(&self) -> Option<String> {
+        let file = self.file.as_deref()?;
+        Some(match self.line {
+            Some(line) => format!("{file}:{line}"),
+            None => file.to_string(),
+        })
+    }
+
+    fn details(&self, run_id: &str) -> String {
+        let mut parts: Vec<String> = self.description.iter().cloned().collect();
+        if let Some(location) = self.location() {
+            parts.push(format!("Location: {location}"));
+        }
+        parts.push(format!("Found by {run_id}."));
+        parts.join("\n\n")
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FindingsFile {
+    List(Vec<Finding>),
+    Wrapped { findings: Vec<Finding> },
+}
+
+pub fn parse(text: &str) -> Result<Vec<Finding>, serde_json::Error> {
+    let findings = match serde_json::from_str(text)? {
+        FindingsFile::List(findings) | FindingsFile::Wrapped { findings } => findings,
+    };
+    Ok(findings
+        .into_iter()
+        .filter(|f| !f.title.trim().is_empty())
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    Update(String),
+    Reopen(String),
+    Skip(String),
+}
+
+fn same_title(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Decides what each finding does to the tracker. Open bugs are preferred
+/// over closed ones with the same title, and repeated findings in one file
+/// are filed once.
+pub fn plan(findings: Vec<Finding>, bugs: &[Bug]) -> Vec<(Finding, Action)> {
+    let mut planned: Vec<(Finding, Action)> = Vec::new();
+    for finding in findings {
+        if planned
+            .iter()
+            .any(|(f, _)| same_title(&f.title, &finding.title))
+        {
+            continue;
+        }
+        let matching = || bugs.iter().filter(|b| same_title(&b.title, &finding.title));
+        let action = if let Some(bug) = matching().find(|b| b.status != "closed") {
+            Action::Update(bug.id.clone())
+        } else if let Some(bug) = matching().next() {
+            if bug.resolution.as_deref().is_none_or(|r| r == "fixed") {
+                Action::Reopen(bug.id.clone())
+            } else {
+                Action::Skip(bug.id.clone())
+            }
+        } else {
+            Action::Create
+        };
+        planned.push((finding, action));
+    }
+    planned
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FindingsSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub reopened: usize,
+    pub skipped: usize,
+    pub lines: Vec<String>,
+}
+
+impl FindingsSummary {
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        for (n, what) in [
+            (self.created, "created"),
+            (self.updated, "updated"),
+            (self.reopened, "reopened"),
+            (self.skipped, "already closed"),
+        ] {
+            if n > 0 {
+                parts.push(format!("{n} {what}"));
+            }
+        }
+        if parts.is_empty() {
+            parts.push("none".to_string());
+        }
+        format!("verify findings: {}", parts.join(", "))
+    }
+}
+
+#[derive(Deserialize)]
+struct Created {
+    id: String,
+}
+
+fn file_finding(
+    root: &Path,
+    finding: &Finding,
+    action: &Action,
+    spec: Option<&str>,
+    run_id: &str,
+    summary: &mut FindingsSummary,
+) -> io::Result<()> {
+    let details = finding.details(run_id);
+    let (verb, id) = match action {
+        Action::Create => {
+            let mut args = vec![
+                "create",
+                &finding.title,
+                "-t",
+                "bug",
+                "--description",
+                &details,
+                "--json",
+            ];
+            if let Some(priority) = finding.priority.as_deref()
+                && PRIORITIES.contains(&priority)
+            {
+                args.extend(["-p", priority]);
+            }
+            if let Some(spec) = spec {
+                args.extend(["--spec", spec]);
+            }
+            let created: Created = serde_json::from_slice(&pn(root, &args)?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            summary.created += 1;
+            ("created", created.id)
+        }
+        Action::Update(id) => {
+            pn(root, &["comment", "add", id, &details, "--json"])?;
+            summary.updated += 1;
+            ("updated", id.clone())
+        }
+        Action::Reopen(id) => {
+            let reason = format!("found again by {run_id}");
+            pn(root, &["reopen", id, "--reason", &reason, "--json"])?;
+            pn(root, &["comment", "add", id, &details, "--json"])?;
+            summary.reopened += 1;
+            ("reopened", id.clone())
+        }
+        Action::Skip(id) => {
+            summary.skipped += 1;
+            ("skipped", id.clone())
+        }
+    };
+    summary
+        .lines
+        .push(format!("{verb} {id}: {}", finding.title.trim()));
+    Ok(())
+}
+
+/// Files the findings a verify iter left in [`FINDINGS_FILE`] and removes
+/// the file. Returns `None` when there is no file. A file that does not
+/// parse is left in place.
+pub fn sync(root: &Path, spec: Option<&str>, run_id: &str) -> io::Result<Option<FindingsSummary>> {
+    let path = root.join(FINDINGS_FILE);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let findings = parse(&text)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{FINDINGS_FILE}: {e}")))?;
+    let mut summary = FindingsSummary::default();
+    if !findings.is_empty() {
+        let bugs = issues::bugs(root)?;
+        for (finding, action) in plan(findings, &bugs) {
+            file_finding(root, &finding, &action, spec, run_id, &mut summary)?;
+        }
+    }
+    std::fs::remove_file(&path)?;
+    Ok(Some(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bug(id: &str, title: &str, status: &str, resolution: Option<&str>) -> Bug {
+        Bug {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: None,
+            status: status.to_string(),
+            priority: "p2".to_string(),
+            spec: None,
+            created_at: "2026-10-01T00:00:00Z".to_string(),
+            closed_at: None,
+            resolution: resolution.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn findings_parse_from_either_shape() {
+        let list = r#"[{"title": "Login ignores lockout", "file": "src/auth.rs", "line": 42}, {"title": " "}]"#;
+        let findings = parse(list).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location().as_deref(), Some("src/auth.rs:42"));
+        assert_eq!(
+            findings[0].details("verify-1"),
+            "Location: src/auth.rs:42\n\nFound by verify-1."
+        );
+
+        let wrapped = r#"{"findings": [{"title": "Spec 3.2 unimplemented", "priority": "p1"}]}"#;
+        assert_eq!(parse(wrapped).unwrap()[0].priority.as_deref(), Some("p1"));
+        assert!(parse("{\"title\": 1}").is_err());
+    }
+
+    #[test]
+    fn findings_dedupe_against_existing_bugs() {
+        let bugs = [
+            bug("pn-1", "Login ignores lockout", "open", None),
+            bug("pn-2", "Session never expires", "closed", Some("fixed")),
+            bug("pn-3", "Logout is slow", "closed", Some("wontfix")),
+            bug("pn-4", "Session never expires", "in_progress", None),
+            bug("pn-5", "Cookie lacks SameSite", "closed", None),
+        ];
+        let finding = |title: &str| Finding {
+            title: title.to_string(),
+            description: None,
+            priority: None,
+            file: None,
+            line: None,
+        };
+        let actions: Vec<Action> = plan(
+            vec![
+                finding("login  ignores LOCKOUT"),
+                finding("Session never expires"),
+                finding("Logout is slow"),
+                finding("Cookie lacks SameSite"),
+                finding("Password reset leaks emails"),
+                finding("Password reset leaks emails"),
+            ],
+            &bugs,
+        )
+        .into_iter()
+        .map(|(_, action)| action)
+        .collect();
+        assert_eq!(
+            actions,
+            [
+                Action::Update("pn-1".to_string()),
+                Action::Update("pn-4".to_string()),
+                Action::Skip("pn-3".to_string()),
+                Action::Reopen("pn-5".to_string()),
+                Action::Create,
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_describes_counts() {
+        let summary = FindingsSummary {
+            created: 2,
+            reopened: 1,
+            ..Default::default()
+        };
+        assert_eq!(summary.describe(), "verify findings: 2 created, 1 reopened");
+        assert_eq!(
+            FindingsSummary::default().describe(),
+            "verify findings: none"
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::logs;
+use crate::loop_mgmt::{self, RunningLoop};
+
+const TICK: Duration = Duration::from_millis(500);
+const REPO_REFRESH: Duration = Duration::from_secs(5);
+const STOP_GRACE: Duration = Duration::from_secs(10);
+const MAX_TAIL_PANES: usize = 4;
+const RECENT_COMMITS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IssueCounts {
+    ready: usize,
+    blocked: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    None,
+    Quit,
+    Stop(String),
+    OpenLog(String),
+}
+
+struct Dashboard {
+    root: PathBuf,
+    loops: Vec<RunningLoop>,
+    tails: Vec<Vec<String>>,
+    issues: Option<IssueCounts>,
+    commits: Vec<String>,
+    selected: usize,
+    confirm_stop: Option<String>,
+    status: Option<String>,
+}
+
+impl Dashboard {
+    fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            loops: Vec::new(),
+            tails: Vec::new(),
+            issues: None,
+            commits: Vec::new(),
+            selected: 0,
+            confirm_stop: None,
+            status: None,
+        }
+    }
+
+    fn refresh_loops(&mut self, tail_lines: usize) {
+        let selected_id = self.selected_loop().map(|l| l.loop_id.clone());
+        self.loops = loop_mgmt::list_running_loops(&self.root);
+        self.tails = self
+            .loops
+            .iter()
+            .map(|l| logs::tail(&self.root, &l.loop_id, tail_lines))
+            .collect();
+        self.selected = selected_id
+            .and_then(|id| self.loops.iter().position(|l| l.loop_id == id))
+            .unwrap_or(self.selected)
+            .min(self.loops.len().saturating_sub(1));
+    }
+
+    fn refresh_repo(&mut self) {
+        let count = |args: &[&str]| -> Option<usize> {
+            let out = Command::new("pn")
+                .args(args)
+                .current_dir(&self.root)
+                .output()
+                .ok()
+                .filter(|o| o.status.success())?;
+            serde_json::from_slice::<Vec<serde_json::Value>>(&out.stdout)
+                .ok()
+                .map(|v| v.len())
+        };
+        self.issues = count(&["ready", "--json"])
+            .zip(count(&["blocked", "--json"]))
+            .map(|(ready, blocked)| IssueCounts { ready, blocked });
+
+        let limit = format!("-{RECENT_COMMITS}");
+        self.commits = Command::new("git")
+            .args(["log", &limit, "--format=%h %s"])
+            .current_dir(&self.root)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| {
+                String::from_utf8_lossy(&o.stdout)
+                    .lines()
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    fn selected_loop(&self) -> Option<&RunningLoop> {
+        self.loops.get(self.selected)
+    }
+
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Action {
+        self.status = None;
+        if let Some(loop_id) = self.confirm_stop.take() {
+            return match code {
+                KeyCode::Char('y') => Action::Stop(loop_id),
+                _ => {
+                    self.status = Some("stop cancelled".to_string());
+                    Action::None
+                }
+            };
+        }
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+            KeyCode::Down | KeyCode::Char('j') if !self.loops.is_empty() => {
+                self.selected = (self.selected + 1) % self.loops.len();
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') if !self.loops.is_empty() => {
+                self.selected = self.selected.checked_sub(1).unwrap_or(self.loops.len() - 1);
+                Action::None
+            }
+            KeyCode::Char('s') => {
+                if let Some(loop_id) = self.selected_loop().map(|l| l.loop_id.clone()) {
+                    self.status = Some(format!("stop {loop_id}? (y/n)"));
+                    self.confirm_stop = Some(loop_id);
+                }
+                Action::None
+            }
+            KeyCode::Char('l') | KeyCode::Enter => self
+                .selected_loop()
+                .map_or(Action::None, |l| Action::OpenLog(l.loop_id.clone())),
+            _ => Action::None,
+        }
+    }
+
+    fn pane_range(&self) -> std::ops::Range<usize> {
+        let start = (self.selected + 1).saturating_sub(MAX_TAIL_PANES);
+        start..self.loops.len().min(start + MAX_TAIL_PANES)
+    }
+}
+
+fn render(frame: &mut Frame, dash: &Dashboard) {
+    let table_height = (dash.loops.len().max(1) as u16 + 3).min(10);
+    let [loops_area, tails_area, bottom_area, footer_area] = Layout::vertical([
+        Constraint::Length(table_height),
+        Constraint::Min(6),
+        Constraint::Length(RECENT_COMMITS as u16 + 2),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    render_loops(frame, dash, loops_area);
+    render_tails(frame, dash, tails_area);
+
+    let [issues_area, commits_area] =
+        Layout::horizontal([Constraint::Length(24), Constraint::Min(20)]).areas(bottom_area);
+    let issues = match dash.issues {
+        Some(c) => vec![
+            Line::from(format!("Ready:   {}", c.ready)),
+            Line::from(format!("Blocked: {}", c.blocked)),
+        ],
+        None => vec![Line::from("pn unavailable")],
+    };
+    frame.render_widget(
+        Paragraph::new(issues).block(Block::bordered().title(" Issues ")),
+        issues_area,
+    );
+    let commits: Vec<Line> = dash
+        .commits
+        .iter()
+        .map(|c| Line::from(c.as_str()))
+        .collect();
+    frame.render_widget(
+        Paragraph::new(commits).block(Block::bordered().title(" Recent commits ")),
+        commits_area,
+    );
+
+    let footer = dash
+        .status
+        .clone()
+        .unwrap_or_else(|| "↑/↓ select · s stop · l open log · q quit".to_string());
+    frame.render_widget(
+        Paragraph::new(footer).style(Style::new().add_modifier(Modifier::DIM)),
+        footer_area,
+    );
+}
+
+fn render_loops(frame: &mut Frame, dash: &Dashboard, area: Rect) {
+    let block = Block::bordered().title(format!(" Running loops ({}) ", dash.loops.len()));
+    if dash.loops.is_empty() {
+        frame.render_widget(Paragraph::new("No running loops").block(block), area);
+        return;
+    }
+    let rows = dash.loops.iter().map(|l| {
+        Row::new([
+            l.loop_id.clone(),
+            l.stage.clone(),
+            l.spec.clone().unwrap_or_else(|| "-".to_string()),
+            l.pid.to_string(),
+            loop_mgmt::format_uptime(&l.started_at),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(12),
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ],
+    )
+    .header(Row::new(["LOOP ID", "STAGE", "SPEC", "PID", "UPTIME"]).style(Style::new().bold()))
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+    .block(block);
+    let mut state = TableState::default().with_selected(Some(dash.selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_tails(frame: &mut Frame, dash: &Dashboard, area: Rect) {
+    let range = dash.pane_range();
+    if range.is_empty() {
+        frame.render_widget(Block::bordered().title(" Output "), area);
+        return;
+    }
+    let panes = Layout::vertical(vec![Constraint::Fill(1); range.len()]).split(area);
+    for (pane, i) in panes.iter().zip(range) {
+        let height = pane.height.saturating_sub(2) as usize;
+        let tail = &dash.tails[i];
+        let lines: Vec<Line> = tail[tail.len().saturating_sub(height)..]
+            .iter()
+            .map(|l| Line::from(l.as_str()))
+            .collect();
+        let mut block = Block::bordered().title(format!(" {} ", dash.loops[i].loop_id));
+        if i == dash.selected {
+            block = block.border_style(Style::new().fg(Color::Cyan));
+        }
+        frame.render_widget(Paragraph::new(lines).block(block), *pane);
+    }
+}
+
+pub fn run(root: &Path) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, root);
+    ratatui::try_restore()?;
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, root: &Path) -> io::Result<()> {
+    let mut dash = Dashboard::new(root);
+    let mut repo_refreshed: Option<Instant> = None;
+    loop {
+        if repo_refreshed.is_none_or(|t| t.elapsed() >= REPO_REFRESH) {
+            dash.refresh_repo();
+            repo_refreshed = Some(Instant::now());
+        }
+        // Enough lines to fill the tallest pane the terminal could show.
+        dash.refresh_loops(terminal.size()?.height as usize);
+        terminal.draw(|frame| render(frame, &dash))?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match dash.handle_key(key.code, key.modifiers) {
+            Action::None => {}
+            Action::Quit => return Ok(()),
+            Action::Stop(loop_id) => {
+                dash.status = Some(format!("stopping {loop_id}..."));
+                terminal.draw(|frame| render(frame, &dash))?;
+                dash.status = Some(match loop_mgmt::stop_loop(root, &loop_id, STOP_GRACE) {
+                    Ok(loop_mgmt::StopOutcome::Killed) => format!("killed {loop_id}"),
+                    Ok(_) => format!("stopped {loop_id}"),
+                    Err(e) => format!("stop {loop_id}: {e}"),
+                });
+            }
+            Action::OpenLog(loop_id) => {
+                let path = loop_mgmt::log_path(root, &loop_id);
+                let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -R +G".to_string());
+                ratatui::try_restore()?;
+                let opened = Command::new("sh")
+                    .arg("-c")
+                    .arg(format!("{pager} \"$1\""))
+                    .arg("sh")
+                    .arg(&path)
+                    .status();
+                *terminal = ratatui::try_init()?;
+                terminal.clear()?;
+                if let Err(e) = opened {
+                    dash.status = Some(format!("open log: {e}"));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn running(id: &str) -> RunningLoop {
+        RunningLoop {
+            loop_id: id.to_string(),
+            stage: "build".to_string(),
+            spec: Some("auth".to_string()),
+            pid: 4242,
+            started_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn dashboard(ids: &[&str]) -> Dashboard {
+        let mut dash = Dashboard::new(Path::new("/nonexistent"));
+        dash.loops = ids.iter().map(|id| running(id)).collect();
+        dash.tails = ids
+            .iter()
+            .map(|id| vec![format!("{id}: working"), format!("{id}: cargo test")])
+            .collect();
+        dash
+    }
+
+    fn screen(dash: &Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal.draw(|frame| render(frame, dash)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|c| c.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn renders_loops_tails_issues_and_commits() {
+        let mut dash = dashboard(&["build-auth-1", "spec-auth-2"]);
+        dash.issues = Some(IssueCounts {
+            ready: 3,
+            blocked: 1,
+        });
+        dash.commits = vec!["abc1234 add login".to_string()];
+        let text = screen(&dash);
+        assert!(text.contains("Running loops (2)"), "{text}");
+        assert!(text.contains("build-auth-1") && text.contains("4242"));
+        assert!(text.contains("spec-auth-2: cargo test"), "both tails shown");
+        assert!(text.contains("Ready:   3") && text.contains("Blocked: 1"));
+        assert!(text.contains("abc1234 add login"));
+
+        let text = screen(&dashboard(&[]));
+        assert!(text.contains("No running loops") && text.contains("pn unavailable"));
+    }
+
+    #[test]
+    fn keys_move_selection_and_confirm_stop() {
+        let mut dash = dashboard(&["a-1", "b-2", "c-3"]);
+        let none = KeyModifiers::NONE;
+        dash.handle_key(KeyCode::Up, none);
+        assert_eq!(dash.selected, 2, "wraps to the last loop");
+        dash.handle_key(KeyCode::Char('j'), none);
+        assert_eq!(dash.selected, 0);
+
+        assert_eq!(dash.handle_key(KeyCode::Char('s'), none), Action::None);
+        assert_eq!(dash.status.as_deref(), Some("stop a-1? (y/n)"));
+        assert_eq!(dash.handle_key(KeyCode::Char('n'), none), Action::None);
+        dash.handle_key(KeyCode::Char('s'), none);
+        assert_eq!(
+            dash.handle_key(KeyCode::Char('y'), none),
+            Action::Stop("a-1".to_string())
+        );
+
+        assert_eq!(
+            dash.handle_key(KeyCode::Enter, none),
+            Action::OpenLog("a-1".to_string())
+        );
+        assert_eq!(dash.handle_key(KeyCode::Char('q'), none), Action::Quit);
+        assert_eq!(
+            dash.handle_key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Action::Quit
+        );
+    }
+
+    #[test]
+    fn tail_panes_always_include_the_selection() {
+        let mut dash = dashboard(&["a", "b", "c", "d", "e", "f"]);
+        assert_eq!(dash.pane_range(), 0..4);
+        dash.selected = 5;
+        assert_eq!(dash.pane_range(), 2..6);
+        assert!(dashboard(&[]).pane_range().is_empty());
+    }
+}
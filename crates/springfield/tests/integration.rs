@@ -164,6 +164,19 @@ fn git_init(root: &Path) {
         .unwrap();
 }
 
+fn archived_files(root: &Path, ext: &str) -> Vec<std::path::PathBuf> {
+    let Ok(months) = fs::read_dir(root.join(".sgf/archive")) else {
+        return Vec::new();
+    };
+    months
+        .flatten()
+        .filter_map(|month| fs::read_dir(month.path()).ok())
+        .flat_map(|files| files.flatten())
+        .map(|f| f.path())
+        .filter(|p| p.extension().is_some_and(|e| e == ext))
+        .collect()
+}
+
 fn git_add_commit(root: &Path, msg: &str) {
     Command::new("git")
         .args(["add", "."])
@@ -414,6 +427,35 @@ fn init_merges_existing_gitignore() {
     assert!(content.contains(".sgf/logs/"), "sgf entry missing");
 }
 
+#[test]
+fn init_dry_run_previews_changes_without_writing() {
+    let tmp = setup_test_dir();
+    fs::write(
+        tmp.path().join(".gitignore"),
+        "# My Project\nmy-secret.key\n",
+    )
+    .unwrap();
+
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["init", "--dry-run", "--no-fe"]));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("create  .sgf/logs/"), "{stdout}");
+    assert!(stdout.contains("create  AGENTS.md ("), "{stdout}");
+    assert!(stdout.contains("modify  .gitignore"), "{stdout}");
+    assert!(stdout.contains("+.sgf/logs/"), "{stdout}");
+    assert!(
+        stdout.contains("link    CLAUDE.md -> AGENTS.md"),
+        "{stdout}"
+    );
+
+    assert!(!tmp.path().join(".sgf").exists());
+    assert!(!tmp.path().join("AGENTS.md").exists());
+    assert_eq!(
+        fs::read_to_string(tmp.path().join(".gitignore")).unwrap(),
+        "# My Project\nmy-secret.key\n"
+    );
+}
+
 #[test]
 fn init_merges_existing_settings_json() {
     let tmp = setup_test_dir();
@@ -799,76 +841,1528 @@ fn spec_runs_interactive_via_cl() {
             args_file.display()
         ),
     );
-    let mock_path_with_cl = format!("{}:{}", mock_cl_dir.path().display(), mock_bin_path());
-
+    let mock_path_with_cl = format!("{}:{}", mock_cl_dir.path().display(), mock_bin_path());
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .arg("spec")
+            .env("PATH", &mock_path_with_cl)
+            .env_remove("CLAUDECODE"),
+    );
+    assert!(
+        output.status.success(),
+        "sgf spec failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let args = fs::read_to_string(&args_file).unwrap();
+    assert!(
+        args.contains("--dangerously-skip-permissions"),
+        "should pass --dangerously-skip-permissions to agent"
+    );
+    assert!(
+        args.contains(".sgf/prompts/spec.md"),
+        "should pass raw spec prompt path"
+    );
+}
+
+#[test]
+fn issues_log_runs_interactive_via_cl() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    setup_default_cursus(tmp.path());
+
+    let mock_cl_dir = TempDir::new().unwrap();
+    let args_file = mock_cl_dir.path().join("agent_args.txt");
+    create_mock_script(
+        mock_cl_dir.path(),
+        "cl",
+        &format!(
+            "#!/bin/sh\necho \"$@\" > \"{}\"\nexit 0\n",
+            args_file.display()
+        ),
+    );
+    let mock_path_with_cl = format!("{}:{}", mock_cl_dir.path().display(), mock_bin_path());
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["issues-log"])
+            .env("PATH", &mock_path_with_cl)
+            .env_remove("CLAUDECODE"),
+    );
+    assert!(
+        output.status.success(),
+        "sgf issues-log failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let args = fs::read_to_string(&args_file).unwrap();
+    assert!(
+        args.contains("--dangerously-skip-permissions"),
+        "should pass --dangerously-skip-permissions to agent"
+    );
+    assert!(
+        args.contains(".sgf/prompts/issues-log.md"),
+        "should pass raw issues-log prompt path"
+    );
+}
+
+// ===========================================================================
+// Recovery
+// ===========================================================================
+
+#[test]
+fn run_custom_stage_from_config() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        concat!(
+            "[stages.bench]\n",
+            "prompt = \"bench.md\"\n",
+            "description = \"Benchmark loop\"\n",
+            "afk = true\n",
+            "vars = { target = \"p99 latency\" }\n",
+        ),
+    )
+    .unwrap();
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(
+        tmp.path().join(".sgf/prompts/bench.md"),
+        "Improve {{target}}.\n",
+    )
+    .unwrap();
+    git_add_commit(tmp.path(), "add bench stage");
+
+    let mock_dir = TempDir::new().unwrap();
+    let prompt_copy = mock_dir.path().join("prompt.md");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!(
+            "#!/bin/sh\nfor a in \"$@\"; do case \"$a\" in @*) cp \"${{a#@}}\" \"{}\";; esac; done\ntouch \"${{PWD}}/.iter-complete\"\nexit 0\n",
+            prompt_copy.display()
+        ),
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["run", "bench", "--no-push"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    assert!(
+        output.status.success(),
+        "sgf run bench failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&prompt_copy).unwrap(),
+        "Improve p99 latency.\n"
+    );
+
+    let list = run_sgf(sgf_cmd(tmp.path()).arg("list"));
+    let stdout = String::from_utf8_lossy(&list.stdout);
+    assert!(stdout.contains("bench") && stdout.contains("Benchmark loop"));
+
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["run", "nope"]));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown stage: nope"));
+}
+
+#[test]
+fn issues_triage_updates_untriaged_bugs_and_stats_count_fixes() {
+    let tmp = setup_test_dir();
+    let mock_dir = TempDir::new().unwrap();
+    let calls = mock_dir.path().join("pn_calls");
+    let bugs = mock_dir.path().join("bugs.json");
+    let now = chrono::Utc::now();
+    let days_ago = |d: i64| (now - chrono::Duration::days(d)).to_rfc3339();
+    let bug = |id: &str, status: &str, created: i64, closed: Option<i64>| {
+        serde_json::json!({
+            "id": id,
+            "title": format!("Bug {id}"),
+            "issue_type": "bug",
+            "status": status,
+            "priority": "p2",
+            "created_at": days_ago(created),
+            "updated_at": days_ago(created),
+            "closed_at": closed.map(days_ago),
+            "resolution": closed.map(|_| "fixed"),
+        })
+    };
+    fs::write(
+        &bugs,
+        serde_json::to_string(&[
+            bug("pn-3", "open", 1, None),
+            bug("pn-1", "open", 5, None),
+            bug("pn-2", "open", 6, None),
+            bug("pn-4", "closed", 20, Some(2)),
+        ])
+        .unwrap(),
+    )
+    .unwrap();
+    create_mock_script(
+        mock_dir.path(),
+        "pn",
+        &format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n\
+             case \"$1 $2 $3\" in\n\
+             list*) cat '{}' ;;\n\
+             'label list pn-2') echo '[\"triaged\"]' ;;\n\
+             'label list'*) echo '[]' ;;\n\
+             *) echo '{{}}' ;;\n\
+             esac\n",
+            calls.display(),
+            bugs.display()
+        ),
+    );
+    let path = format!("{}:{}", mock_dir.path().display(), mock_bin_path());
+    let answers = mock_dir.path().join("answers");
+    fs::write(&answers, "7\n1 @auth +pn-9\ns\n").unwrap();
+
+    let output = run_sgf(
+        sgf_cmd_with_path(tmp.path(), &path)
+            .args(["issues", "triage"])
+            .stdin(fs::File::open(&answers).unwrap()),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("don't understand \"7\""), "{stderr}");
+    assert!(
+        stderr.contains("triaged 1, skipped 1; 1 left in the queue"),
+        "{stderr}"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first = stdout.find("pn-1").unwrap();
+    assert!(
+        first < stdout.find("pn-3").unwrap(),
+        "oldest first: {stdout}"
+    );
+    assert!(!stdout.contains("pn-2"), "already triaged: {stdout}");
+
+    let logged = fs::read_to_string(&calls).unwrap();
+    let logged: Vec<&str> = logged.lines().collect();
+    let changes: Vec<&str> = logged
+        .iter()
+        .copied()
+        .filter(|c| !c.starts_with("list") && !c.starts_with("label list"))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            "update pn-1 -p p1 --spec auth --json",
+            "dep add pn-1 pn-9 --json",
+            "label add pn-1 triaged --json",
+        ]
+    );
+
+    let output = run_sgf(
+        sgf_cmd_with_path(tmp.path(), &path).args(["issues", "stats", "--weeks", "2", "--json"]),
+    );
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["open"], 3);
+    let weeks = stats["weeks"].as_array().unwrap();
+    assert_eq!(weeks.len(), 2);
+    assert_eq!(
+        (weeks[1]["opened"].clone(), weeks[1]["fixed"].clone()),
+        (3.into(), 1.into())
+    );
+
+    let output = run_sgf(sgf_cmd_with_path(tmp.path(), &path).args(["issues", "stats"]));
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("3 open bugs; 4 opened and 1 fixed in 4 weeks")
+    );
+}
+
+#[test]
+fn next_claims_top_issue_injects_it_and_closes_it() {
+    let tmp = setup_test_dir();
+    setup_default_cursus(tmp.path());
+    create_spec_and_commit(tmp.path(), "auth");
+
+    let mock_dir = TempDir::new().unwrap();
+    let calls = mock_dir.path().join("pn_calls");
+    create_mock_script(
+        mock_dir.path(),
+        "pn",
+        &format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n\
+             case \"$1\" in\n\
+             ready) echo '[{{\"id\":\"pn-7\",\"title\":\"Add login\",\"description\":\"Use OAuth.\",\"spec\":\"auth\"}}]' ;;\n\
+             *) echo '{{}}' ;;\n\
+             esac\n",
+            calls.display()
+        ),
+    );
+    let prompt_copy = mock_dir.path().join("prompt.md");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!(
+            "#!/bin/sh\nfor a in \"$@\"; do case \"$a\" in @*) cp \"${{a#@}}\" \"{}\";; esac; done\ntouch \"${{PWD}}/.iter-complete\"\nexit 0\n",
+            prompt_copy.display()
+        ),
+    );
+    let path = format!("{}:{}", mock_dir.path().display(), mock_bin_path());
+
+    let output = run_sgf(
+        sgf_cmd_with_path(tmp.path(), &path)
+            .args(["next", "--spec", "auth", "--no-push"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("claimed pn-7: Add login"), "{stderr}");
+    assert!(stderr.contains("closed pn-7"), "{stderr}");
+
+    let prompt = fs::read_to_string(&prompt_copy).unwrap();
+    assert!(prompt.starts_with("Build prompt\n"), "{prompt}");
+    assert!(
+        prompt.contains("Work on pensa issue pn-7: Add login\nSpec: auth\n\nUse OAuth.\n"),
+        "{prompt}"
+    );
+
+    let calls = fs::read_to_string(&calls).unwrap();
+    let calls: Vec<&str> = calls.lines().collect();
+    assert_eq!(calls[0], "ready --json -n 1 --spec auth");
+    assert!(calls.contains(&"update pn-7 --claim --json"), "{calls:?}");
+    assert_eq!(
+        calls.last(),
+        Some(&"close pn-7 --reason completed by sgf next --json")
+    );
+}
+
+#[test]
+fn test_plan_and_results_sync_pensa_test_issues() {
+    let tmp = setup_test_dir();
+    create_spec_and_commit(tmp.path(), "auth");
+    write_cursus_toml(
+        tmp.path(),
+        "qa",
+        concat!(
+            "description = \"Plan and run tests\"\n",
+            "auto_push = false\n",
+            "\n",
+            "[[iter]]\n",
+            "name = \"plan\"\n",
+            "prompt = \"plan.md\"\n",
+            "mode = \"afk\"\n",
+            "produces = \"test-plan\"\n",
+            "\n",
+            "[[iter]]\n",
+            "name = \"run\"\n",
+            "prompt = \"run.md\"\n",
+            "mode = \"afk\"\n",
+            "produces = \"test-results\"\n",
+        ),
+    );
+    let prompts_dir = tmp.path().join(".sgf/prompts");
+    fs::create_dir_all(&prompts_dir).unwrap();
+    fs::write(prompts_dir.join("plan.md"), "plan prompt\n").unwrap();
+    fs::write(prompts_dir.join("run.md"), "run prompt\n").unwrap();
+    git_add_commit(tmp.path(), "add qa cursus");
+
+    let mock_dir = TempDir::new().unwrap();
+    let calls = mock_dir.path().join("pn_calls");
+    create_mock_script(
+        mock_dir.path(),
+        "pn",
+        &format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n\
+             case \"$1\" in\n\
+             list) echo '[{{\"id\":\"pn-a1\",\"title\":\"Logout clears cookies\",\"status\":\"closed\"}},\
+             {{\"id\":\"pn-b2\",\"title\":\"Login rejects a bad password\",\"status\":\"open\"}}]' ;;\n\
+             *) echo '{{}}' ;;\n\
+             esac\n",
+            calls.display()
+        ),
+    );
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        concat!(
+            "#!/bin/sh\n",
+            "PROMPT=\"${@: -1}\"\n",
+            "mkdir -p \"$SGF_RUN_CONTEXT\"\n",
+            "if echo \"$PROMPT\" | grep -q 'plan.md'; then\n",
+            "  printf -- '- Login rejects a bad password (pn-1a2b)\\n- Session expires after an hour (pn-1a2b, pn-3c4d)\\n' > \"$SGF_RUN_CONTEXT/test-plan.md\"\n",
+            "else\n",
+            "  printf 'pass pn-b2\\nfail Logout clears cookies: cookie kept\\n' > \"$SGF_RUN_CONTEXT/test-results.md\"\n",
+            "fi\n",
+            "touch \"${PWD}/.iter-complete\"\n",
+            "exit 0\n",
+        ),
+    );
+    let path = format!("{}:{}", mock_dir.path().display(), mock_bin_path());
+
+    let output = run_sgf(
+        sgf_cmd_with_path(tmp.path(), &path)
+            .args(["qa", "auth", "-a"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("test issues: 1 created"), "{stderr}");
+    assert!(
+        stderr.contains("test issues: 1 closed, 1 reopened"),
+        "{stderr}"
+    );
+
+    let calls = fs::read_to_string(&calls).unwrap();
+    let calls: Vec<&str> = calls
+        .lines()
+        .filter(|c| {
+            ["create", "close", "reopen"]
+                .iter()
+                .any(|v| c.starts_with(v))
+        })
+        .collect();
+    let run_id = calls[1]
+        .strip_prefix("close pn-b2 --reason passed in ")
+        .and_then(|rest| rest.strip_suffix(" --json"))
+        .unwrap_or_else(|| panic!("{calls:?}"));
+    assert_eq!(
+        calls,
+        [
+            "create Session expires after an hour -t test --json --spec auth --dep pn-1a2b --dep pn-3c4d".to_string(),
+            format!("close pn-b2 --reason passed in {run_id} --json"),
+            format!("reopen pn-a1 --reason failed in {run_id}: cookie kept --json"),
+        ]
+    );
+}
+
+#[test]
+fn failing_gate_blocks_launch_until_skipped() {
+    let tmp = setup_test_dir();
+    setup_default_cursus(tmp.path());
+    create_spec_and_commit(tmp.path(), "auth");
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "[gates.build]\nafter = [\"verify\"]\ncommands = [\"true\", \"echo 'test auth::login ... FAILED'; exit 101\"]\n",
+    )
+    .unwrap();
+    git_add_commit(tmp.path(), "gate build");
+
+    let mock_dir = TempDir::new().unwrap();
+    let ran = mock_dir.path().join("ran");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!(
+            "#!/bin/sh\ntouch '{}'\ntouch \"${{PWD}}/.iter-complete\"\nexit 0\n",
+            ran.display()
+        ),
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["build", "auth", "-a", "--no-push"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.contains("build: 2 of 3 gate checks failed"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("after verify: no verify loop has run for auth"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("exited 101"), "{stderr}");
+    assert!(stderr.contains("test auth::login ... FAILED"), "{stderr}");
+    assert!(!ran.exists(), "agent should not run behind a failing gate");
+    assert!(
+        !tmp.path().join(".sgf/run/loop.lock").exists(),
+        "lock should be released"
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["build", "auth", "-a", "--no-push", "--skip-gates"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(ran.exists());
+}
+
+#[test]
+fn watchdog_kills_a_stalled_agent_and_records_the_incident() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    let config = |restart: bool| {
+        fs::write(
+            tmp.path().join(".sgf/config.toml"),
+            format!(
+                "[stages.bench]\nprompt = \"bench.md\"\nafk = true\niterations = 3\n\n[watchdog]\nstall_after = \"1s\"\nrestart = {restart}\n"
+            ),
+        )
+        .unwrap();
+    };
+    config(false);
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(tmp.path().join(".sgf/prompts/bench.md"), "Go.\n").unwrap();
+    git_add_commit(tmp.path(), "add bench stage");
+
+    // The agent hangs silently when the hang file exists, consuming it.
+    let mock_dir = TempDir::new().unwrap();
+    let hang = mock_dir.path().join("hang");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!(
+            "#!/bin/sh\nif [ -f '{hang}' ]; then rm '{hang}'; sleep 30; fi\necho '{{\"type\":\"result\",\"result\":\"Done.\"}}'\ntouch \"${{PWD}}/.iter-complete\"\nexit 0\n",
+            hang = hang.display()
+        ),
+    );
+    let launch = || {
+        fs::write(&hang, "").unwrap();
+        run_sgf(
+            sgf_cmd(tmp.path())
+                .args(["run", "bench", "--no-push"])
+                .env("SGF_AGENT_COMMAND", &mock_agent)
+                .env("SGF_HEARTBEAT_INTERVAL_SECS", "0")
+                // The agent needs its own process group to be killed.
+                .env_remove("SGF_TEST_NO_SETSID"),
+        )
+    };
+    let record = |path: &Path| -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    };
+
+    let started = std::time::Instant::now();
+    let output = launch();
+    assert!(started.elapsed() < Duration::from_secs(20), "not killed");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains(r#"{"event":"heartbeat"}"#),
+        "heartbeats keep going while the watchdog waits"
+    );
+    let failed: Vec<_> = fs::read_dir(tmp.path().join(".sgf/logs"))
+        .unwrap()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    assert_eq!(failed.len(), 1);
+    let failed = record(&failed[0]);
+    assert_eq!(failed["exit_code"], 2, "stalled, so it can be resumed");
+    let incidents = failed["incidents"].as_array().unwrap();
+    assert_eq!(incidents.len(), 1);
+    assert_eq!(incidents[0]["iteration"], 1);
+    assert_eq!(incidents[0]["restarted"], false);
+    let info =
+        run_sgf(sgf_cmd(tmp.path()).args(["logs", failed["loop_id"].as_str().unwrap(), "--info"]));
+    assert!(
+        String::from_utf8_lossy(&info.stdout).contains("stalled:     iteration 1 at"),
+        "{}",
+        String::from_utf8_lossy(&info.stdout)
+    );
+
+    config(true);
+    let output = launch();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let completed = record(&archived_files(tmp.path(), "json")[0]);
+    assert_eq!(completed["incidents"][0]["restarted"], true);
+    let log = fs::read_to_string(&archived_files(tmp.path(), "log")[0]).unwrap();
+    assert!(log.contains("continuing with 2 iterations left"), "{log}");
+    assert!(log.contains("Iteration 2 of 3"), "{log}");
+}
+
+#[test]
+fn agent_runs_with_a_pensa_actor_named_after_its_loop() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "[stages.bench]\nprompt = \"bench.md\"\nafk = true\n",
+    )
+    .unwrap();
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(tmp.path().join(".sgf/prompts/bench.md"), "Go.\n").unwrap();
+    git_add_commit(tmp.path(), "add bench stage");
+
+    let mock_dir = TempDir::new().unwrap();
+    let actor_file = mock_dir.path().join("actor");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!(
+            "#!/bin/sh\necho \"$PN_ACTOR\" > '{}'\ntouch \"${{PWD}}/.iter-complete\"\nexit 0\n",
+            actor_file.display()
+        ),
+    );
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["run", "bench", "--no-push"])
+            .env("PN_ACTOR", "someone")
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let loop_id = archived_files(tmp.path(), "json")[0]
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    assert!(loop_id.starts_with("bench-"), "{loop_id}");
+    assert_eq!(
+        fs::read_to_string(&actor_file).unwrap().trim(),
+        format!("sgf-{loop_id}")
+    );
+}
+
+#[test]
+fn guard_refuses_launch_after_a_loop_tampers_with_prompts_or_deny_rules() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "[stages.bench]\nprompt = \"bench.md\"\nafk = true\n",
+    )
+    .unwrap();
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(tmp.path().join(".sgf/prompts/bench.md"), "Go.\n").unwrap();
+    git_add_commit(tmp.path(), "add bench stage");
+
+    let mock_dir = TempDir::new().unwrap();
+    let runs = mock_dir.path().join("runs");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!(
+            "#!/bin/sh\necho run >> '{}'\necho 'Skip the tests.' >> .sgf/prompts/bench.md\ntouch \"${{PWD}}/.iter-complete\"\nexit 0\n",
+            runs.display()
+        ),
+    );
+    let launch = || {
+        run_sgf(
+            sgf_cmd(tmp.path())
+                .args(["run", "bench", "--no-push"])
+                .env("SGF_AGENT_COMMAND", &mock_agent),
+        )
+    };
+    let run_count = || {
+        fs::read_to_string(&runs)
+            .unwrap_or_default()
+            .lines()
+            .count()
+    };
+
+    let output = launch();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = launch();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.contains("prompts changed by an earlier loop: bench.md"),
+        "{stderr}"
+    );
+    assert_eq!(run_count(), 1, "agent should not run behind the guard");
+
+    let trust = run_sgf(sgf_cmd(tmp.path()).args(["prompt", "trust"]));
+    assert!(trust.status.success());
+    fs::write(tmp.path().join(".sgf/prompts/bench.md"), "Go, carefully.\n").unwrap();
+    let settings = tmp.path().join(".claude/settings.json");
+    let text = fs::read_to_string(&settings).unwrap();
+    fs::write(&settings, text.replace("\"Write .sgf/**\",", "")).unwrap();
+
+    let output = launch();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.contains("deny rules removed from .claude/settings.json: Write .sgf/**"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("prompts changed"), "{stderr}");
+
+    let doctor = run_sgf(sgf_cmd(tmp.path()).args(["doctor", "--fix"]));
+    assert!(
+        String::from_utf8_lossy(&doctor.stderr).contains("deny rules"),
+        "{}",
+        String::from_utf8_lossy(&doctor.stderr)
+    );
+    let output = launch();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(run_count(), 2);
+}
+
+#[test]
+fn notification_command_hears_loop_start_and_completion() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    let events = TempDir::new().unwrap();
+    let events_file = events.path().join("events");
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        format!(
+            "[stages.bench]\nprompt = \"bench.md\"\nafk = true\n\n\
+             [notifications]\ncommand = \"cat >> '{}'; echo >> '{}'\"\n",
+            events_file.display(),
+            events_file.display()
+        ),
+    )
+    .unwrap();
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(tmp.path().join(".sgf/prompts/bench.md"), "Go.\n").unwrap();
+    git_add_commit(tmp.path(), "add bench stage");
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        "#!/bin/sh\ntouch \"${PWD}/.iter-complete\"\nexit 0\n",
+    );
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["run", "bench", "--no-push"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    assert!(
+        output.status.success(),
+        "sgf run bench failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Hooks run in the background and may outlive sgf briefly.
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    let payloads: Vec<serde_json::Value> = loop {
+        let text = fs::read_to_string(&events_file).unwrap_or_default();
+        if text.lines().count() >= 2 || std::time::Instant::now() > deadline {
+            break text
+                .lines()
+                .map(|l| serde_json::from_str(l).unwrap())
+                .collect();
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    let kinds: Vec<&str> = payloads
+        .iter()
+        .map(|p| p["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, ["start", "complete"]);
+    assert_eq!(payloads[1]["stage"], "bench");
+    assert_eq!(payloads[1]["exit_code"], 0);
+    assert!(
+        payloads[1]["message"]
+            .as_str()
+            .unwrap()
+            .contains("completed after 1 iteration")
+    );
+}
+
+#[test]
+fn branch_per_loop_merges_back_by_ff_and_pr() {
+    let tmp = setup_test_dir();
+    let root = tmp.path();
+    sgf_init_and_commit(root);
+    let git_out = |args: &[&str]| {
+        let out = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    };
+    let base = git_out(&["symbolic-ref", "--short", "HEAD"]);
+    let write_config = |merge_back: &str| {
+        fs::write(
+            root.join(".sgf/config.toml"),
+            format!(
+                "branch_per_loop = true\nmerge_back = \"{merge_back}\"\n\n\
+                 [stages.bench]\nprompt = \"bench.md\"\nafk = true\n"
+            ),
+        )
+        .unwrap();
+        git_add_commit(root, &format!("merge_back = {merge_back}"));
+    };
+    write_config("ff");
+    fs::create_dir_all(root.join(".sgf/prompts")).unwrap();
+    fs::write(root.join(".sgf/prompts/bench.md"), "Go.\n").unwrap();
+    git_add_commit(root, "add bench stage");
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        "#!/bin/sh\ngit commit -q --allow-empty -m \"agent work\"\ntouch \"${PWD}/.iter-complete\"\nexit 0\n",
+    );
+    let gh_args = mock_dir.path().join("gh-args");
+    create_mock_script(
+        mock_dir.path(),
+        "gh",
+        &format!(
+            "#!/bin/sh\necho \"$@\" > '{}'\necho https://example.test/pull/1\n",
+            gh_args.display()
+        ),
+    );
+    let path = format!("{}:{}", mock_dir.path().display(), mock_bin_path());
+    let run_bench = || {
+        let output = run_sgf(
+            sgf_cmd(root)
+                .args(["run", "bench", "--no-push"])
+                .env("SGF_AGENT_COMMAND", &mock_agent)
+                .env("PATH", &path),
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        assert!(output.status.success(), "sgf run bench failed: {stderr}");
+        stderr
+    };
+
+    let stderr = run_bench();
+    assert!(stderr.contains("working on sgf/bench-"), "{stderr}");
+    assert!(
+        stderr.contains(&format!("fast-forwarded {base}")),
+        "{stderr}"
+    );
+    assert_eq!(git_out(&["symbolic-ref", "--short", "HEAD"]), base);
+    assert_eq!(git_out(&["log", "-1", "--format=%s"]), "agent work");
+    assert_eq!(
+        git_out(&["branch", "--list", "sgf/*"]),
+        "",
+        "merged branch deleted"
+    );
+
+    let origin = TempDir::new().unwrap();
+    Command::new("git")
+        .args(["init", "--bare", "--quiet"])
+        .current_dir(origin.path())
+        .status()
+        .unwrap();
+    git_out(&["remote", "add", "origin", &origin.path().to_string_lossy()]);
+    write_config("pr");
+    // Loop IDs have one-second resolution; a reused ID would resume the first.
+    std::thread::sleep(Duration::from_millis(1100));
+
+    let stderr = run_bench();
+    assert!(stderr.contains("https://example.test/pull/1"), "{stderr}");
+    assert_eq!(git_out(&["symbolic-ref", "--short", "HEAD"]), base);
+    let branch = git_out(&["branch", "--list", "--format=%(refname:short)", "sgf/*"]);
+    assert!(
+        branch.starts_with("sgf/bench-"),
+        "branch kept for the PR: {branch}"
+    );
+    let args = fs::read_to_string(&gh_args).unwrap();
+    assert_eq!(
+        args.trim(),
+        format!("pr create --base {base} --head {branch} --fill")
+    );
+    let pushed = Command::new("git")
+        .args(["rev-parse", "--verify", &branch])
+        .current_dir(origin.path())
+        .status()
+        .unwrap();
+    assert!(pushed.success(), "{branch} pushed to origin");
+}
+
+#[test]
+fn push_on_complete_targets_branch_and_opens_labeled_pr() {
+    let tmp = setup_test_dir();
+    setup_default_cursus(tmp.path());
+    create_spec_and_commit(tmp.path(), "auth");
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "[push]\nwhen = \"complete\"\nbranch = \"agent-work\"\npr = true\nlabels = [\"agent\"]\n",
+    )
+    .unwrap();
+    git_add_commit(tmp.path(), "push policy");
+    let origin = TempDir::new().unwrap();
+    Command::new("git")
+        .args(["init", "--bare", "--quiet"])
+        .current_dir(origin.path())
+        .status()
+        .unwrap();
+    Command::new("git")
+        .args(["remote", "add", "origin", &origin.path().to_string_lossy()])
+        .current_dir(tmp.path())
+        .status()
+        .unwrap();
+
+    let mock_dir = TempDir::new().unwrap();
+    let gh_args = mock_dir.path().join("gh_args");
+    create_mock_script(
+        mock_dir.path(),
+        "gh",
+        &format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n[ \"$1\" = pr ] && echo https://example.test/pull/7\nexit 0\n",
+            gh_args.display()
+        ),
+    );
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        "#!/bin/sh\ngit commit -q --allow-empty -m 'agent work'\ntouch \"${PWD}/.iter-complete\"\nexit 0\n",
+    );
+    let path = format!("{}:{}", mock_dir.path().display(), mock_bin_path());
+
+    let output = run_sgf(
+        sgf_cmd_with_path(tmp.path(), &path)
+            .args(["build", "auth", "-a"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("https://example.test/pull/7"), "{stderr}");
+
+    let head = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    let pushed = Command::new("git")
+        .args(["rev-parse", "refs/heads/agent-work"])
+        .current_dir(origin.path())
+        .output()
+        .unwrap();
+    assert_eq!(pushed.stdout, head.stdout, "HEAD pushed to agent-work");
+
+    let args = fs::read_to_string(&gh_args).unwrap();
+    assert_eq!(
+        args.lines().collect::<Vec<_>>(),
+        [
+            "label create agent",
+            "label create spec:auth",
+            "pr create --fill --head agent-work --label agent --label spec:auth",
+        ]
+    );
+}
+
+#[test]
+fn logs_list_and_info_report_finished_loops() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "[stages.bench]\nprompt = \"bench.md\"\nafk = true\n",
+    )
+    .unwrap();
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(tmp.path().join(".sgf/prompts/bench.md"), "Go.\n").unwrap();
+    git_add_commit(tmp.path(), "add bench stage");
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        "#!/bin/sh\ngit commit -q --allow-empty -m agent\ntouch \"${PWD}/.iter-complete\"\nexit 0\n",
+    );
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["run", "bench", "--no-push"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    assert!(
+        output.status.success(),
+        "sgf run bench failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let list = run_sgf(sgf_cmd(tmp.path()).args(["logs", "--list"]));
+    assert!(list.status.success());
+    let stdout = String::from_utf8_lossy(&list.stdout);
+    let row = stdout
+        .lines()
+        .find(|l| l.starts_with("bench-"))
+        .unwrap_or_else(|| panic!("no bench row in:\n{stdout}"));
+    assert!(row.contains("completed"), "row: {row}");
+    let loop_id = row.split_whitespace().next().unwrap();
+    assert!(!tmp.path().join(format!(".sgf/logs/{loop_id}.log")).exists());
+    assert!(
+        archived_files(tmp.path(), "log")
+            .iter()
+            .any(|p| p.ends_with(format!("{loop_id}.log"))),
+        "completed loop's log should be archived"
+    );
+
+    let history =
+        run_sgf(sgf_cmd(tmp.path()).args(["history", "--stage", "bench", "--since", "1d"]));
+    assert!(history.status.success());
+    let stdout = String::from_utf8_lossy(&history.stdout);
+    let row = stdout
+        .lines()
+        .find(|l| l.starts_with(loop_id))
+        .unwrap_or_else(|| panic!("no {loop_id} row in:\n{stdout}"));
+    assert!(row.contains("completed"), "row: {row}");
+    let other_stage = run_sgf(sgf_cmd(tmp.path()).args(["history", "--stage", "build", "--json"]));
+    assert_eq!(String::from_utf8_lossy(&other_stage.stdout).trim(), "[]");
+
+    let info = run_sgf(sgf_cmd(tmp.path()).args(["logs", loop_id, "--info"]));
+    assert!(info.status.success());
+    let stdout = String::from_utf8_lossy(&info.stdout);
+    let field = |name: &str| {
+        stdout
+            .lines()
+            .find_map(|l| l.strip_prefix(&format!("{name}:")))
+            .map(str::trim)
+            .unwrap_or_else(|| panic!("no {name} in:\n{stdout}"))
+            .to_string()
+    };
+    assert_eq!(field("stage"), "bench");
+    assert_eq!(field("exit code"), "0");
+    assert_eq!(field("iterations"), "1");
+    assert_ne!(field("head before"), field("head after"));
+
+    let missing = run_sgf(sgf_cmd(tmp.path()).args(["logs", "nope", "--info"]));
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("no metadata recorded for nope"));
+
+    let bare = run_sgf(sgf_cmd(tmp.path()).arg("logs"));
+    assert!(!bare.status.success());
+}
+
+#[test]
+fn logs_filters_and_follows_across_replacement() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    let log = tmp.path().join(".sgf/logs/build-auth-1.log");
+    let now = chrono::Utc::now();
+    let stamp = |ago: i64| (now - chrono::Duration::minutes(ago)).format("%Y-%m-%dT%H:%M:%SZ");
+    fs::write(
+        &log,
+        format!(
+            "{} ERROR: stale failure\n{} cargo test\n{} test auth::login failed\n",
+            stamp(60),
+            stamp(2),
+            stamp(1)
+        ),
+    )
+    .unwrap();
+
+    let logs = |args: &[&str]| {
+        let output = run_sgf(
+            sgf_cmd(tmp.path())
+                .arg("logs")
+                .arg("build-auth-1")
+                .args(args),
+        );
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    assert_eq!(logs(&[]).lines().count(), 3);
+    assert_eq!(logs(&["--since", "10m"]).lines().count(), 2);
+    let errors = logs(&["--errors-only", "--since", "10m"]);
+    assert!(errors.contains("auth::login failed") && errors.lines().count() == 1);
+    assert!(logs(&["--grep", "cargo\\s+test"]).contains("cargo test"));
+
+    let bad = run_sgf(sgf_cmd(tmp.path()).args(["logs", "build-auth-1", "--since", "soon"]));
+    assert!(!bad.status.success());
+
+    let out_path = tmp.path().join("follow.out");
+    let mut cmd = sgf_cmd(tmp.path());
+    cmd.args(["logs", "build-auth-1", "--follow", "--grep", "marker"])
+        .stdout(fs::File::create(&out_path).unwrap())
+        .stderr(Stdio::null());
+    let _follower = ChildGuard::spawn(&mut cmd).unwrap();
+    let wait_for = |needle: &str| {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while std::time::Instant::now() < deadline {
+            if fs::read_to_string(&out_path)
+                .unwrap_or_default()
+                .contains(needle)
+            {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        panic!(
+            "follow never printed {needle:?}: {}",
+            fs::read_to_string(&out_path).unwrap_or_default()
+        );
+    };
+
+    std::thread::sleep(Duration::from_millis(300));
+    std::io::Write::write_all(
+        &mut fs::OpenOptions::new().append(true).open(&log).unwrap(),
+        b"appended marker\nother\n",
+    )
+    .unwrap();
+    wait_for("appended marker");
+
+    let replacement = tmp.path().join(".sgf/logs/next.log");
+    fs::write(&replacement, "rotated marker\n").unwrap();
+    fs::rename(&replacement, &log).unwrap();
+    wait_for("rotated marker");
+    assert!(!fs::read_to_string(&out_path).unwrap().contains("other"));
+}
+
+#[test]
+fn clean_prunes_old_logs_per_flags_and_config() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    let logs = tmp.path().join(".sgf/logs");
+    let month_ago = std::time::SystemTime::now() - Duration::from_secs(30 * 86_400);
+    for name in ["build-old.log", "build-new.log"] {
+        fs::write(logs.join(name), "output\n").unwrap();
+    }
+    fs::File::options()
+        .write(true)
+        .open(logs.join("build-old.log"))
+        .unwrap()
+        .set_modified(month_ago)
+        .unwrap();
+
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["clean", "--assembled"]));
+    assert!(output.status.success());
+    assert!(logs.join("build-old.log").exists(), "logs not selected");
+
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "log_retention = \"60d\"\n",
+    )
+    .unwrap();
+    let output = run_sgf(sgf_cmd(tmp.path()).arg("clean"));
+    assert!(output.status.success());
+    assert!(logs.join("build-old.log").exists(), "within log_retention");
+
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["clean", "--logs-older-than", "14d"]));
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("removed 1 file "));
+    assert!(!logs.join("build-old.log").exists());
+    assert!(logs.join("build-new.log").exists());
+}
+
+#[test]
+fn doctor_reports_problems_and_fixes_safe_ones() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    let mock_cl_dir = TempDir::new().unwrap();
+    create_mock_script(mock_cl_dir.path(), "cl", "#!/bin/sh\nexit 0\n");
+    let path = format!("{}:{}", mock_cl_dir.path().display(), mock_bin_path());
+    fs::remove_dir_all(tmp.path().join(".sgf/logs")).unwrap();
+
+    let output = run_sgf(sgf_cmd(tmp.path()).arg("doctor").env("PATH", &path));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.contains("directories: missing: .sgf/logs"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("sgf doctor --fix"), "{stderr}");
+    assert!(stderr.contains("agent: "), "{stderr}");
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["doctor", "--fix"])
+            .env("PATH", &path),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("environment ok"), "{stderr}");
+    assert!(tmp.path().join(".sgf/logs").is_dir());
+}
+
+#[test]
+fn recover_dry_run_prints_plan_and_changes_nothing() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "[recovery]\nexclude = [\"notes/\"]\n",
+    )
+    .unwrap();
+    git_add_commit(tmp.path(), "configure recovery");
+    let run_dir = tmp.path().join(".sgf/run");
+    fs::create_dir_all(&run_dir).unwrap();
+    fs::write(run_dir.join("build-20260101T000000.pid"), "4000000").unwrap();
+    fs::write(tmp.path().join("README.md"), "DIRTY").unwrap();
+    fs::write(tmp.path().join("scratch.txt"), "scratch").unwrap();
+    fs::create_dir_all(tmp.path().join("notes")).unwrap();
+    fs::write(tmp.path().join("notes/keep.md"), "keep").unwrap();
+
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["recover", "--dry-run"]));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("strategy: reset"), "{stdout}");
+    assert!(
+        stdout.contains("remove stale PID file: build-20260101T000000"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("restore: README.md"), "{stdout}");
+    assert!(stdout.contains("delete: scratch.txt"), "{stdout}");
+    assert!(!stdout.contains("notes/keep.md"), "{stdout}");
+    assert!(stdout.contains("never touched: .sgf, notes/"), "{stdout}");
+    assert!(tmp.path().join("scratch.txt").exists());
+    assert!(run_dir.join("build-20260101T000000.pid").exists());
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["recover", "--strategy", "abort"])
+            .stdin(Stdio::null()),
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert!(tmp.path().join("scratch.txt").exists());
+
+    let output = run_sgf(sgf_cmd(tmp.path()).arg("recover"));
+    assert!(output.status.success());
+    assert!(!tmp.path().join("scratch.txt").exists());
+    assert!(tmp.path().join("notes/keep.md").exists());
+    assert!(!run_dir.join("build-20260101T000000.pid").exists());
+}
+
+#[test]
+fn upgrade_dry_run_diffs_prompts_against_global_templates() {
+    let tmp = setup_test_dir();
+    let prompts = tmp.path().join(".sgf/prompts");
+    fs::create_dir_all(&prompts).unwrap();
+    fs::write(prompts.join("build.md"), "Build it.\nRun the tests.\n").unwrap();
+    fs::write(prompts.join("local-only.md"), "Mine.\n").unwrap();
+    let home = TempDir::new().unwrap();
+    let global = home.path().join(".sgf/prompts");
+    fs::create_dir_all(&global).unwrap();
+    fs::write(global.join("build.md"), "Build it.\nRun the tests twice.\n").unwrap();
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .env("HOME", home.path())
+            .env("NO_COLOR", "1")
+            .args(["upgrade", "--dry-run"]),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("--- .sgf/prompts/build.md"), "{stdout}");
+    assert!(stdout.contains("+++ ~/.sgf/prompts/build.md"), "{stdout}");
+    assert!(
+        stdout.contains("-Run the tests.\n+Run the tests twice."),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("local-only"), "{stdout}");
+    assert_eq!(
+        fs::read_to_string(prompts.join("build.md")).unwrap(),
+        "Build it.\nRun the tests.\n"
+    );
+    assert!(!tmp.path().join(".sgf/upgrade").exists());
+}
+
+#[test]
+fn dry_run_prints_agent_invocation_without_spawning() {
+    let tmp = setup_test_dir();
+    setup_default_cursus(tmp.path());
+    create_spec_and_commit(tmp.path(), "auth");
+    let mock_dir = TempDir::new().unwrap();
+    let marker = mock_dir.path().join("spawned");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!("#!/bin/sh\ntouch '{}'\nexit 0\n", marker.display()),
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["build", "auth", "-n", "3", "--model", "opus", "--dry-run"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{stdout}\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("loop id:  build-"), "{stdout}");
+    assert!(
+        stdout.contains(&format!("agent:    {0} ({0})", mock_agent.display())),
+        "{stdout}"
+    );
+    assert!(stdout.contains("recovery: none needed"), "{stdout}");
+    assert!(
+        stdout.contains("iter 1/1: build (afk, 3 iterations"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("--print --output-format stream-json"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("--model opus --session-id '<session-id>' @"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("  | Build prompt"), "{stdout}");
+
+    assert!(!marker.exists(), "the agent must not run");
+    let run_dir = tmp.path().join(".sgf/run");
+    let leftovers: Vec<_> = fs::read_dir(&run_dir)
+        .map(|d| d.flatten().map(|e| e.file_name()).collect())
+        .unwrap_or_default();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+}
+
+#[test]
+fn project_dir_runs_loop_in_monorepo_subproject() {
+    let tmp = setup_test_dir();
+    let api = tmp.path().join("packages/api");
+    fs::create_dir_all(&api).unwrap();
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["--project-dir", "packages/api", "init"]));
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(api.join(".sgf/config.toml").is_file());
+    assert!(!tmp.path().join(".sgf").exists());
+    fs::write(api.join("task.md"), "test task").unwrap();
+    git_add_commit(tmp.path(), "add api project");
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        concat!(
+            "#!/bin/sh\n",
+            "pwd > agent_pwd.txt\n",
+            "git add -A && git commit -q -m 'Record agent directory'\n",
+            "touch .iter-complete\n",
+        ),
+    );
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["task.md", "-a", "-n", "1", "--no-push"])
+            .args(["--project-dir", "packages/api"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let agent_pwd = fs::read_to_string(api.join("agent_pwd.txt")).unwrap();
+    assert_eq!(Path::new(agent_pwd.trim()), api.canonicalize().unwrap());
+    let committed = Command::new("git")
+        .args(["show", "--name-only", "--format=", "HEAD"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&committed.stdout).contains("packages/api/agent_pwd.txt"));
+    assert!(!tmp.path().join(".sgf").exists());
+
+    // project_dir in the root's config does the same without the flag.
+    fs::create_dir_all(tmp.path().join(".sgf")).unwrap();
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "project_dir = \"packages/api\"\n",
+    )
+    .unwrap();
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["logs", "--list"]));
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("completed"));
+
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["ps", "--project-dir", "packages/nope"]));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is not a directory"));
+}
+
+#[test]
+fn output_json_streams_loop_lifecycle_events() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "[stages.bench]\nprompt = \"bench.md\"\nafk = true\niterations = 3\n",
+    )
+    .unwrap();
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(tmp.path().join(".sgf/prompts/bench.md"), "Go.\n").unwrap();
+    git_add_commit(tmp.path(), "add bench stage");
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        concat!(
+            "#!/bin/sh\n",
+            "echo '{\"type\":\"result\",\"result\":\"Done.\",\"total_cost_usd\":0.25,\"usage\":{\"input_tokens\":100,\"output_tokens\":20}}'\n",
+            "if [ -f second ]; then touch .iter-complete; else touch second; fi\n",
+        ),
+    );
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["run", "bench", "--no-push", "--output", "json"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let events: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|_| panic!("not JSON: {line}")))
+        .filter(|e: &serde_json::Value| {
+            matches!(
+                e["event"].as_str(),
+                Some("loop_started" | "iteration_progress" | "loop_finished")
+            )
+        })
+        .collect();
+    let names: Vec<&str> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "loop_started",
+            "iteration_progress",
+            "iteration_progress",
+            "loop_finished"
+        ]
+    );
+    assert_eq!(events[0]["stage"], "bench");
+    assert_eq!(events[2]["iteration"], 2);
+    assert_eq!(events[2]["iterations"], 3);
+    assert_eq!(events[2]["cost_usd"], 0.5);
+    assert_eq!(events[3]["exit_code"], 0);
+    assert_eq!(events[3]["outcome"], "completed");
+    assert_eq!(events[3]["loop_id"], events[0]["loop_id"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Iteration 1 of 3"));
+}
+
+#[test]
+fn report_summarizes_finished_loop() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".sgf/config.toml"),
+        "[stages.bench]\nprompt = \"bench.md\"\nafk = true\n",
+    )
+    .unwrap();
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(tmp.path().join(".sgf/prompts/bench.md"), "Go.\n").unwrap();
+    git_add_commit(tmp.path(), "add bench stage");
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        concat!(
+            "#!/bin/sh\n",
+            "echo 'fn main() {}' > bench.rs\n",
+            "git add bench.rs && git commit -q -m 'Add bench harness'\n",
+            "echo '{\"type\":\"assistant\",\"message\":{\"content\":[{\"type\":\"tool_use\",\"name\":\"Bash\",\"input\":{\"command\":\"cargo test\"}}]}}'\n",
+            "echo '{\"type\":\"result\",\"result\":\"Done.\",\"total_cost_usd\":0.05,\"usage\":{\"input_tokens\":100,\"output_tokens\":20}}'\n",
+            "touch \"${PWD}/.iter-complete\"\n",
+        ),
+    );
     let output = run_sgf(
         sgf_cmd(tmp.path())
-            .arg("spec")
-            .env("PATH", &mock_path_with_cl)
-            .env_remove("CLAUDECODE"),
+            .args(["run", "bench", "--no-push"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
     );
     assert!(
         output.status.success(),
-        "sgf spec failed: {}",
+        "sgf run bench failed: {}",
         String::from_utf8_lossy(&output.stderr)
     );
+    let loop_id = archived_files(tmp.path(), "json")
+        .iter()
+        .find_map(|p| Some(p.file_stem()?.to_string_lossy().to_string()))
+        .expect("loop record archived");
 
-    let args = fs::read_to_string(&args_file).unwrap();
-    assert!(
-        args.contains("--dangerously-skip-permissions"),
-        "should pass --dangerously-skip-permissions to agent"
-    );
-    assert!(
-        args.contains(".sgf/prompts/spec.md"),
-        "should pass raw spec prompt path"
-    );
+    let md = run_sgf(sgf_cmd(tmp.path()).args(["report", &loop_id]));
+    assert!(md.status.success());
+    let md = String::from_utf8_lossy(&md.stdout);
+    assert!(md.contains("- **Completion:** completed"), "{md}");
+    assert!(md.contains("Add bench harness"), "{md}");
+    assert!(md.contains("- `bench.rs`"), "{md}");
+    assert!(md.contains("| test | 1 | passed |"), "{md}");
+
+    let json = run_sgf(sgf_cmd(tmp.path()).args(["report", &loop_id, "--json"]));
+    let report: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(report["iterations"], 1);
+    assert_eq!(report["input_tokens"], 100);
+    assert_eq!(report["cost_usd"], 0.05);
+    assert_eq!(report["commits"].as_array().unwrap().len(), 1);
+
+    let missing = run_sgf(sgf_cmd(tmp.path()).args(["report", "nope"]));
+    assert!(!missing.status.success());
+
+    let costs = run_sgf(sgf_cmd(tmp.path()).args(["costs", "--since", "1d", "--json"]));
+    assert!(costs.status.success());
+    let costs: serde_json::Value = serde_json::from_slice(&costs.stdout).unwrap();
+    assert_eq!(costs[0]["key"], "bench");
+    assert_eq!(costs[0]["loops"], 1);
+    assert_eq!(costs[0]["input_tokens"], 100);
+    assert_eq!(costs[0]["cost_usd"], 0.05);
+
+    let table = run_sgf(sgf_cmd(tmp.path()).args(["costs", "--by", "spec"]));
+    let table = String::from_utf8_lossy(&table.stdout);
+    assert!(table.contains("SPEC"), "{table}");
+    assert!(table.contains("$0.05"), "{table}");
 }
 
 #[test]
-fn issues_log_runs_interactive_via_cl() {
+fn prompt_assemble_and_lint() {
     let tmp = setup_test_dir();
     sgf_init_and_commit(tmp.path());
-    setup_default_cursus(tmp.path());
-
-    let mock_cl_dir = TempDir::new().unwrap();
-    let args_file = mock_cl_dir.path().join("agent_args.txt");
-    create_mock_script(
-        mock_cl_dir.path(),
-        "cl",
-        &format!(
-            "#!/bin/sh\necho \"$@\" > \"{}\"\nexit 0\n",
-            args_file.display()
-        ),
+    create_spec_and_commit(tmp.path(), "auth");
+    write_cursus_toml(
+        tmp.path(),
+        "build",
+        "description = \"Build\"\n\n[[iter]]\nname = \"build\"\nprompt = \"build.md\"\n",
     );
-    let mock_path_with_cl = format!("{}:{}", mock_cl_dir.path().display(), mock_bin_path());
+    fs::create_dir_all(tmp.path().join(".sgf/prompts")).unwrap();
+    fs::write(
+        tmp.path().join(".sgf/prompts/build.md"),
+        "Build {{spec}} on {{git_branch}}.\n",
+    )
+    .unwrap();
 
-    let output = run_sgf(
-        sgf_cmd(tmp.path())
-            .args(["issues-log"])
-            .env("PATH", &mock_path_with_cl)
-            .env_remove("CLAUDECODE"),
+    let output =
+        run_sgf(sgf_cmd(tmp.path()).args(["prompt", "assemble", "build", "--spec", "auth"]));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("Build auth on ") && !stdout.contains("{{"),
+        "{stdout}"
     );
+
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["prompt", "lint"]));
     assert!(
         output.status.success(),
-        "sgf issues-log failed: {}",
+        "lint failed: {}",
         String::from_utf8_lossy(&output.stderr)
     );
 
-    let args = fs::read_to_string(&args_file).unwrap();
+    fs::write(
+        tmp.path().join(".sgf/prompts/build.md"),
+        "Build {{spec}} per @specs/missing.md and {{focus}}.\n",
+    )
+    .unwrap();
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["prompt", "lint"]));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(
-        args.contains("--dangerously-skip-permissions"),
-        "should pass --dangerously-skip-permissions to agent"
+        stderr.contains("build.md: unresolved variable {{focus}}"),
+        "{stderr}"
     );
     assert!(
-        args.contains(".sgf/prompts/issues-log.md"),
-        "should pass raw issues-log prompt path"
+        stderr.contains("build.md: missing include @specs/missing.md"),
+        "{stderr}"
     );
 }
 
-// ===========================================================================
-// Recovery
-// ===========================================================================
-
 #[test]
 fn recovery_cleans_stale_state() {
     let tmp = setup_test_dir();
@@ -1033,6 +2527,119 @@ fn help_flag() {
     assert!(stdout.contains("logs"), "help should list logs built-in");
 }
 
+#[test]
+fn detach_runs_in_background_until_stopped() {
+    let tmp = setup_test_dir();
+    setup_default_cursus(tmp.path());
+    create_spec_and_commit(tmp.path(), "auth");
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        "#!/bin/sh\necho still working\nsleep 30\n",
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["build", "auth", "--detach"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "detach failed: {stderr}");
+    let loop_id = stderr
+        .lines()
+        .find_map(|l| l.split("sgf stop ").nth(1))
+        .expect("detach should print the stop command")
+        .trim()
+        .to_string();
+    assert!(
+        loop_id.starts_with("build-"),
+        "unexpected loop id: {loop_id}"
+    );
+
+    let output = run_sgf(sgf_cmd(tmp.path()).arg("ps"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("UPTIME"),
+        "ps should print a header: {stdout}"
+    );
+    assert!(
+        stdout.contains(&loop_id),
+        "ps should list the loop: {stdout}"
+    );
+    assert!(stdout.contains("auth"), "ps should show the spec: {stdout}");
+
+    let output = run_sgf(sgf_cmd(tmp.path()).args(["stop", &loop_id, "--timeout", "5"]));
+    assert!(
+        output.status.success(),
+        "stop failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        !tmp.path()
+            .join(format!(".sgf/run/{loop_id}/{loop_id}.pid"))
+            .exists()
+    );
+    let meta =
+        fs::read_to_string(tmp.path().join(format!(".sgf/run/{loop_id}/meta.json"))).unwrap();
+    assert!(
+        meta.contains("\"interrupted\""),
+        "stop should leave the run resumable: {meta}"
+    );
+
+    let output = run_sgf(sgf_cmd(tmp.path()).arg("ps"));
+    assert!(
+        !String::from_utf8_lossy(&output.stdout).contains(&loop_id),
+        "stopped loop should no longer be listed"
+    );
+}
+
+#[test]
+fn multiple_specs_run_in_parallel_worktrees() {
+    let tmp = setup_test_dir();
+    setup_default_cursus(tmp.path());
+    create_spec_and_commit(tmp.path(), "auth");
+    create_spec_and_commit(tmp.path(), "billing");
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        "#!/bin/sh\npwd > \"$PWD/agent_cwd.txt\"\ntouch \"$PWD/.iter-complete\"\nexit 0\n",
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["build", "auth", "billing", "-j", "2", "--no-push"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "parallel build failed: {stderr}");
+    assert!(stderr.contains("2/2 specs completed"), "stderr: {stderr}");
+
+    for spec in ["auth", "billing"] {
+        let worktree = tmp.path().join(format!(".sgf/worktrees/{spec}"));
+        let cwd = fs::read_to_string(worktree.join("agent_cwd.txt"))
+            .unwrap_or_else(|_| panic!("agent for {spec} should run in its worktree"));
+        assert!(cwd.trim().ends_with(&format!(".sgf/worktrees/{spec}")));
+
+        let branch = Command::new("git")
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .current_dir(&worktree)
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&branch.stdout).trim(),
+            format!("sgf/build-{spec}")
+        );
+    }
+    assert!(
+        !tmp.path().join("agent_cwd.txt").exists(),
+        "main checkout should be untouched"
+    );
+}
+
 // ===========================================================================
 // ===========================================================================
 // Spec-parity integration tests (implementation plan)
@@ -1578,6 +3185,91 @@ fn build_nonexistent_spec_fails_with_error() {
     );
 }
 
+#[test]
+fn build_misspelled_spec_suggests_and_never_launches() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    setup_default_cursus(tmp.path());
+    create_spec_and_commit(tmp.path(), "auth");
+
+    let mock_dir = TempDir::new().unwrap();
+    let marker = mock_dir.path().join("launched");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!("#!/bin/sh\ntouch \"{}\"\nexit 0\n", marker.display()),
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["build", "atuh", "-a"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("spec not found: specs/atuh.md (did you mean: auth?)"),
+        "stderr should suggest the close match: {stderr}"
+    );
+    assert!(!marker.exists(), "agent must not be launched");
+}
+
+#[test]
+fn loop_lock_blocks_second_loop_until_forced() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+    setup_default_cursus(tmp.path());
+    create_spec_and_commit(tmp.path(), "auth");
+
+    let mut holder = Command::new("sleep").arg("30").spawn().unwrap();
+    fs::create_dir_all(tmp.path().join(".sgf/run")).unwrap();
+    fs::write(
+        tmp.path().join(".sgf/run/loop.lock"),
+        format!(
+            r#"{{"pid":{},"command":"verify","spec":null,"started_at":"2026-03-16T12:00:00Z"}}"#,
+            holder.id()
+        ),
+    )
+    .unwrap();
+
+    let mock_dir = TempDir::new().unwrap();
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        "#!/bin/sh\ntouch \"${PWD}/.iter-complete\"\nexit 0\n",
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["build", "auth", "-a", "--no-push"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("loop verify already running since"),
+        "stderr should name the running loop: {stderr}"
+    );
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["build", "auth", "-a", "--no-push", "--force"])
+            .env("SGF_AGENT_COMMAND", &mock_agent),
+    );
+    holder.kill().unwrap();
+    holder.wait().unwrap();
+    assert!(
+        output.status.success(),
+        "--force should take the lock: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        !tmp.path().join(".sgf/run/loop.lock").exists(),
+        "lock released on exit"
+    );
+}
+
 #[test]
 fn build_valid_spec_proceeds() {
     let tmp = setup_test_dir();
@@ -4203,7 +5895,7 @@ fn cursus_single_iter_dispatches_and_completes() {
     );
 
     // PID file should be cleaned up
-    let remaining_pids: Vec<_> = fs::read_dir(&run_entries[0].path())
+    let remaining_pids: Vec<_> = fs::read_dir(run_entries[0].path())
         .unwrap()
         .filter_map(|e| e.ok())
         .filter(|e| e.path().extension().is_some_and(|ext| ext == "pid"))
@@ -4847,7 +6539,10 @@ fn cursus_multi_iter_stall_recovery() {
         "should stall at draft iter"
     );
     let completed = meta["iters_completed"].as_array().unwrap();
-    assert!(completed.len() >= 1, "discuss should be in completed iters");
+    assert!(
+        !completed.is_empty(),
+        "discuss should be in completed iters"
+    );
     assert_eq!(
         completed[0]["name"].as_str().unwrap(),
         "discuss",
@@ -4975,7 +6670,7 @@ fn cursus_multi_iter_resume_stalled_run() {
     );
     let completed = meta["iters_completed"].as_array().unwrap();
     assert!(
-        completed.len() >= 1,
+        !completed.is_empty(),
         "at least discuss should be in completed"
     );
     assert_eq!(completed[0]["name"].as_str().unwrap(), "discuss");
@@ -7012,24 +8707,17 @@ fn iter_simple_mode_creates_log_file() {
         output.status.code()
     );
 
-    let logs_dir = tmp.path().join(".sgf/logs");
-    if logs_dir.exists() {
-        let log_files: Vec<_> = fs::read_dir(&logs_dir)
-            .unwrap()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().is_some_and(|ext| ext == "log"))
-            .collect();
-        assert!(
-            !log_files.is_empty(),
-            "simple prompt mode should create a log file in .sgf/logs/"
-        );
+    let log_files = archived_files(tmp.path(), "log");
+    assert!(
+        !log_files.is_empty(),
+        "simple prompt mode should create a log file, archived on completion"
+    );
 
-        let log_content = fs::read_to_string(log_files[0].path()).expect("read log file");
-        assert!(
-            log_content.contains("Iteration 1 of 1"),
-            "log should contain iteration banner, got: {log_content}"
-        );
-    }
+    let log_content = fs::read_to_string(&log_files[0]).expect("read log file");
+    assert!(
+        log_content.contains("Iteration 1 of 1"),
+        "log should contain iteration banner, got: {log_content}"
+    );
 }
 
 // ---- SGF_AGENT_COMMAND override (test-harness spec §7) ----
@@ -7668,6 +9356,56 @@ fn daemon_lifecycle_daemons_reachable_and_loop_runs() {
     );
 }
 
+#[test]
+fn pensa_daemon_version_mismatch_blocks_launch() {
+    let tmp = setup_test_dir();
+    sgf_init_and_commit(tmp.path());
+
+    let mock_dir = TempDir::new().unwrap();
+    create_mock_script(
+        mock_dir.path(),
+        "pn",
+        concat!(
+            "#!/bin/sh\n",
+            "if [ \"$1 $2 $3\" = \"daemon status --json\" ]; then\n",
+            "  echo '{\"url\":\"http://localhost:4000\",\"version\":\"9.0.0\",\"api_version\":3,",
+            "\"min_api_version\":2,\"cli_version\":\"9.0.0\",\"cli_api_version\":3}'\n",
+            "fi\n",
+            "exit 0\n",
+        ),
+    );
+    create_mock_script(mock_dir.path(), "fm", "#!/bin/sh\nexit 0\n");
+    let marker = mock_dir.path().join("agent_ran");
+    let mock_agent = create_mock_script(
+        mock_dir.path(),
+        "mock_agent.sh",
+        &format!("#!/bin/sh\ntouch \"{}\"\n", marker.display()),
+    );
+    let mock_path = format!(
+        "{}:{}",
+        mock_dir.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    fs::write(tmp.path().join("task.md"), "test task").unwrap();
+
+    let output = run_sgf(
+        sgf_cmd(tmp.path())
+            .args(["task.md", "-a", "-n", "1"])
+            .env("SGF_AGENT_COMMAND", &mock_agent)
+            .env("PATH", &mock_path)
+            .env_remove("SGF_SKIP_PREFLIGHT"),
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("(pensa 9.0.0) serves API v2-v3, but sgf"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("prompts need v1"), "{stderr}");
+    assert!(!marker.exists(), "agent should not run");
+}
+
 // ===========================================================================
 // Pre-launch: Data export
 // ===========================================================================
@@ -11,18 +11,68 @@ pub fn git_head() -> Option<String> {
         .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
 }
 
-fn has_unpushed_commits() -> bool {
+/// Where auto-push sends commits. The default pushes the current branch to
+/// its upstream with a plain `git push`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushTarget {
+    pub remote: Option<String>,
+    pub branch: Option<String>,
+}
+
+impl PushTarget {
+    fn is_upstream(&self) -> bool {
+        self.remote.is_none() && self.branch.is_none()
+    }
+
+    pub fn describe(&self) -> Option<String> {
+        if self.is_upstream() {
+            return git_output(&["rev-parse", "--abbrev-ref", "@{u}"]);
+        }
+        let remote = self.remote.as_deref().unwrap_or("origin");
+        let branch = self.branch.clone().or_else(current_branch)?;
+        Some(format!("{remote}/{branch}"))
+    }
+
+    fn push_args(&self) -> Vec<String> {
+        if self.is_upstream() {
+            return vec!["push".to_string()];
+        }
+        let remote = self.remote.clone().unwrap_or_else(|| "origin".to_string());
+        let refspec = match &self.branch {
+            Some(branch) => format!("HEAD:refs/heads/{branch}"),
+            None => "HEAD".to_string(),
+        };
+        vec!["push".to_string(), remote, refspec]
+    }
+
+    fn compare_ref(&self) -> Option<String> {
+        if self.is_upstream() {
+            return Some("@{u}".to_string());
+        }
+        self.describe()
+            .map(|remote_branch| format!("refs/remotes/{remote_branch}"))
+    }
+}
+
+fn git_output(args: &[&str]) -> Option<String> {
     Command::new("git")
-        .args(["rev-list", "--count", "@{u}..HEAD"])
+        .args(args)
         .output()
         .ok()
         .filter(|o| o.status.success())
-        .and_then(|o| {
-            String::from_utf8_lossy(&o.stdout)
-                .trim()
-                .parse::<u64>()
-                .ok()
-        })
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+fn current_branch() -> Option<String> {
+    git_output(&["symbolic-ref", "--quiet", "--short", "HEAD"])
+}
+
+fn has_unpushed_commits(target: &PushTarget) -> bool {
+    let Some(compare) = target.compare_ref() else {
+        return true;
+    };
+    git_output(&["rev-list", "--count", &format!("{compare}..HEAD")])
+        .and_then(|count| count.parse::<u64>().ok())
         .is_none_or(|count| count > 0)
 }
 
@@ -30,21 +80,32 @@ fn has_unpushed_commits() -> bool {
 /// Messages are emitted via `emit`. Silent on success.
 /// Push failures are non-fatal — reported through `emit` and execution continues.
 pub fn auto_push_if_changed(head_before: &str, emit: impl Fn(&str)) {
-    let head_after = git_head();
-    if let Some(ref after) = head_after
-        && after != head_before
-        && has_unpushed_commits()
-    {
-        emit("New commits detected, pushing...");
-        match Command::new("git").arg("push").output() {
-            Ok(out) if out.status.success() => {}
-            Ok(out) => {
-                let stderr = String::from_utf8_lossy(&out.stderr);
-                emit(&format!("push failed (non-fatal): {}", stderr.trim()));
-            }
-            Err(e) => {
-                emit(&format!("push failed (non-fatal): {e}"));
-            }
+    auto_push_to(head_before, &PushTarget::default(), emit);
+}
+
+pub fn auto_push_to(head_before: &str, target: &PushTarget, emit: impl Fn(&str)) {
+    if git_head().is_some_and(|after| after != head_before) {
+        push_unpushed(target, emit);
+    }
+}
+
+/// Pushes HEAD to `target` if it has commits the target lacks. Returns
+/// whether a push succeeded; failures are reported through `emit`.
+pub fn push_unpushed(target: &PushTarget, emit: impl Fn(&str)) -> bool {
+    if !has_unpushed_commits(target) {
+        return false;
+    }
+    emit("New commits detected, pushing...");
+    match Command::new("git").args(target.push_args()).output() {
+        Ok(out) if out.status.success() => true,
+        Ok(out) => {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            emit(&format!("push failed (non-fatal): {}", stderr.trim()));
+            false
+        }
+        Err(e) => {
+            emit(&format!("push failed (non-fatal): {e}"));
+            false
         }
     }
 }
@@ -76,6 +137,25 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn push_target_args() {
+        assert_eq!(PushTarget::default().push_args(), ["push"]);
+        let remote = PushTarget {
+            remote: Some("fork".to_string()),
+            branch: None,
+        };
+        assert_eq!(remote.push_args(), ["push", "fork", "HEAD"]);
+        let branch = PushTarget {
+            remote: None,
+            branch: Some("agent-work".to_string()),
+        };
+        assert_eq!(
+            branch.push_args(),
+            ["push", "origin", "HEAD:refs/heads/agent-work"]
+        );
+        assert_eq!(branch.describe().as_deref(), Some("origin/agent-work"));
+    }
+
     #[test]
     fn auto_push_unchanged_head_emits_nothing() {
         let current = git_head().unwrap();
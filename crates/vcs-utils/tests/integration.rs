@@ -180,3 +180,48 @@ fn git_head_returns_none_outside_repo() {
 
     assert!(head.is_none());
 }
+
+#[test]
+fn push_unpushed_sends_head_to_target_branch_once() {
+    let _lock = CWD_LOCK.lock().unwrap();
+    let tmp = tempfile::tempdir().unwrap();
+
+    let remote_dir = tmp.path().join("remote.git");
+    std::fs::create_dir(&remote_dir).unwrap();
+    git(&remote_dir, &["init", "--bare"]);
+
+    let local_dir = tmp.path().join("local");
+    std::fs::create_dir(&local_dir).unwrap();
+    git(&local_dir, &["init"]);
+    git(
+        &local_dir,
+        &["remote", "add", "origin", remote_dir.to_str().unwrap()],
+    );
+
+    std::fs::write(local_dir.join("file.txt"), "initial").unwrap();
+    git(&local_dir, &["add", "."]);
+    git(&local_dir, &["commit", "-m", "initial"]);
+    let head = git(&local_dir, &["rev-parse", "HEAD"]);
+
+    let target = vcs_utils::PushTarget {
+        remote: None,
+        branch: Some("agent-work".to_string()),
+    };
+    let messages = std::cell::RefCell::new(Vec::new());
+
+    let original_dir = std::env::current_dir().unwrap();
+    std::env::set_current_dir(&local_dir).unwrap();
+    let first =
+        vcs_utils::push_unpushed(&target, |msg| messages.borrow_mut().push(msg.to_string()));
+    let second =
+        vcs_utils::push_unpushed(&target, |msg| messages.borrow_mut().push(msg.to_string()));
+    std::env::set_current_dir(&original_dir).unwrap();
+
+    assert!(first);
+    assert!(!second, "nothing left to push");
+    assert_eq!(messages.borrow().len(), 1);
+    assert_eq!(
+        git(&remote_dir, &["rev-parse", "refs/heads/agent-work"]),
+        head
+    );
+}